@@ -0,0 +1,188 @@
+//! Benchmarks for the hot game loop: dealing boards, scoring words, solving
+//! boards and dictionary lookups, on both dictionary backends
+//!
+//! The word list is generated from a fixed seed, so runs on different machines
+//! (and without dictionary.txt) measure the same work. Set `BENCH_DICTIONARY` to
+//! a word list to benchmark against real words instead.
+
+use std::{collections::HashSet, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use spell_cast_backend::{
+    dictionary::{Dictionary, DictionaryBackend},
+    game::{
+        grid::{GridGenerator, MultiplierLayout, GRID_SIZE},
+        rules::GameRules,
+        scorer::Scorer,
+        solver,
+    },
+    models::{Grid, GridCell, Position},
+    utils::letters::get_letter_value,
+};
+
+/// Words in the generated list
+const SYNTHETIC_WORDS: u32 = 50_000;
+const WORD_SEED: u64 = 0x5eed;
+const BOARD_SEED: u64 = 0xb0a2d;
+
+/// Pseudo-words of 2-9 letters, weighted like the letters on a board
+fn synthetic_word_list() -> String {
+    let mut index = 0;
+    let mut next = || {
+        index += 1;
+        GridGenerator::seeded_letter(WORD_SEED, index)
+    };
+    (0..SYNTHETIC_WORDS)
+        .map(|_| {
+            let len = 2 + (next() as usize % 8);
+            (0..len).map(|_| next()).collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn word_list() -> String {
+    std::env::var("BENCH_DICTIONARY")
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_else(synthetic_word_list)
+}
+
+/// The same board every run
+fn fixed_board() -> Grid {
+    (0..GRID_SIZE)
+        .map(|row| {
+            (0..GRID_SIZE)
+                .map(|col| {
+                    let letter =
+                        GridGenerator::seeded_letter(BOARD_SEED, (row * GRID_SIZE + col) as u32);
+                    GridCell {
+                        letter,
+                        value: get_letter_value(letter),
+                        multiplier: None,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn dictionaries() -> Vec<(&'static str, Dictionary)> {
+    let words = word_list();
+    [
+        ("hash", DictionaryBackend::Hash),
+        ("fst", DictionaryBackend::Fst),
+    ]
+    .into_iter()
+    .map(|(name, backend)| {
+        let dictionary = Dictionary::from_lists(&words, "")
+            .with_backend(backend)
+            .expect("word list builds");
+        (name, dictionary)
+    })
+    .collect()
+}
+
+fn grid_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_generation");
+    for layout in [
+        MultiplierLayout::Classic,
+        MultiplierLayout::Chaos,
+        MultiplierLayout::None,
+    ] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", layout)),
+            &layout,
+            |b, &layout| b.iter(|| GridGenerator::generate(layout)),
+        );
+    }
+    group.finish();
+}
+
+fn scoring(c: &mut Criterion) {
+    let grid = GridGenerator::generate(MultiplierLayout::Chaos);
+    let rules = GameRules::default();
+    // A snake through every tile, the longest path a board allows
+    let full_board: Vec<Position> = (0..GRID_SIZE)
+        .flat_map(|row| {
+            (0..GRID_SIZE).map(move |i| Position {
+                row,
+                col: if row % 2 == 0 { i } else { GRID_SIZE - 1 - i },
+            })
+        })
+        .collect();
+    let short = &full_board[..4];
+
+    let mut group = c.benchmark_group("scoring");
+    group.bench_function("4_letters", |b| {
+        b.iter(|| Scorer::calculate_score(black_box(&grid), black_box(short), &rules))
+    });
+    group.bench_function("25_letters", |b| {
+        b.iter(|| Scorer::calculate_score(black_box(&grid), black_box(&full_board), &rules))
+    });
+    group.finish();
+}
+
+fn dictionary_lookups(c: &mut Criterion) {
+    let words = word_list();
+    let sample: Vec<&str> = words.lines().step_by(997).take(50).collect();
+
+    let mut group = c.benchmark_group("dictionary");
+    for (name, dictionary) in dictionaries() {
+        group.bench_with_input(BenchmarkId::new("contains", name), &dictionary, |b, d| {
+            b.iter(|| {
+                sample
+                    .iter()
+                    .filter(|word| d.contains(black_box(word)))
+                    .count()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("contains_miss", name),
+            &dictionary,
+            |b, d| b.iter(|| d.contains(black_box("QQQZZX"))),
+        );
+        group.bench_with_input(BenchmarkId::new("has_prefix", name), &dictionary, |b, d| {
+            b.iter(|| {
+                sample
+                    .iter()
+                    .filter(|word| d.has_prefix(black_box(&word[..2])))
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn solving(c: &mut Criterion) {
+    let grid = fixed_board();
+    let used = HashSet::new();
+    let rules = GameRules::default();
+
+    let mut group = c.benchmark_group("solver");
+    group.sample_size(10);
+    for (name, dictionary) in dictionaries() {
+        group.bench_with_input(
+            BenchmarkId::new("find_unused_words", name),
+            &dictionary,
+            |b, d| b.iter(|| solver::find_unused_words(black_box(&grid), d, &used, 20)),
+        );
+        // The hash backend scans every word per prefix, so a full solve with it
+        // takes seconds; only the FST is worth timing
+        if name == "fst" {
+            group.bench_with_input(BenchmarkId::new("best_words", name), &dictionary, |b, d| {
+                b.iter(|| solver::best_words(black_box(&grid), d, &used, &rules, 10))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    grid_generation,
+    scoring,
+    dictionary_lookups,
+    solving
+);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+//! Arbitrary bytes through the same parse-then-validate steps a WebSocket text
+//! frame goes through before it reaches the handlers
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spell_cast_backend::websocket::{messages::ClientMessage, validate::validate};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(msg) = serde_json::from_str::<ClientMessage>(text) {
+        let _ = validate(&msg);
+    }
+});
@@ -0,0 +1,55 @@
+//! Arbitrary position lists, on arbitrary (possibly ragged or empty) grids,
+//! through path validation and scoring; neither may panic
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use spell_cast_backend::{
+    game::{rules::GameRules, scorer::Scorer, validator::WordValidator},
+    models::{GridCell, Multiplier, Position},
+};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    /// One entry per tile: a letter value and a multiplier selector
+    rows: Vec<Vec<(u8, u8)>>,
+    positions: Vec<(usize, usize)>,
+}
+
+fuzz_target!(|input: Input| {
+    let grid: Vec<Vec<GridCell>> = input
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&(value, multiplier)| GridCell {
+                    letter: 'A',
+                    value,
+                    multiplier: match multiplier % 4 {
+                        1 => Some(Multiplier::DoubleLetter),
+                        2 => Some(Multiplier::TripleLetter),
+                        3 => Some(Multiplier::DoubleWord),
+                        _ => None,
+                    },
+                })
+                .collect()
+        })
+        .collect();
+    let positions: Vec<Position> = input
+        .positions
+        .iter()
+        .map(|&(row, col)| Position { row, col })
+        .collect();
+
+    let validator = WordValidator::new(Default::default());
+    let valid = validator.validate_path(&grid, &positions).is_ok();
+    let score = Scorer::calculate_score(&grid, &positions, &GameRules::default());
+    let word = validator.extract_word(&grid, &positions);
+
+    // A path that validates is on the grid, so it must score and spell a word
+    if valid {
+        assert!(score.is_ok());
+        assert!(word.is_ok());
+    }
+});
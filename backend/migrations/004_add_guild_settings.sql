@@ -0,0 +1,28 @@
+-- Community-wide defaults that server admins can set for their guild
+-- Loaded when a channel lobby for the guild is created
+CREATE TABLE IF NOT EXISTS guild_settings (
+    guild_id BIGINT PRIMARY KEY,
+    default_language VARCHAR(16) NOT NULL DEFAULT 'en',
+    default_rounds SMALLINT NOT NULL DEFAULT 5 CHECK (default_rounds BETWEEN 1 AND 10),
+    profanity_filter BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Game modes (lowercase GameMode names) that lobbies in this guild may start
+    allowed_modes VARCHAR(20)[] NOT NULL DEFAULT '{multiplayer,twovtwo,adventure}',
+    -- Discord user who last changed the settings
+    updated_by BIGINT,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Function to update guild_settings timestamp
+CREATE OR REPLACE FUNCTION update_guild_settings_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Trigger to auto-update timestamp
+CREATE TRIGGER update_guild_settings_timestamp
+    BEFORE UPDATE ON guild_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_guild_settings_updated_at();
@@ -0,0 +1,10 @@
+-- Co-op mode: players share a score and try to reach a target within the rounds
+-- The original constraint also predates sqlx's lowercase encoding of TwoVTwo
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop'));
+
+-- Team score to beat (NULL for competitive games)
+ALTER TABLE games ADD COLUMN coop_target_score INTEGER;
+-- Shared gem pool, flushed with the rest of the game state
+ALTER TABLE games ADD COLUMN coop_gems INTEGER NOT NULL DEFAULT 0;
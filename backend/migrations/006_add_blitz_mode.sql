@@ -0,0 +1,9 @@
+-- Blitz mode: every player gets a chess-style clock for the whole game
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop', 'blitz'));
+
+-- Starting clock per player (NULL for untimed games)
+ALTER TABLE games ADD COLUMN blitz_clock_secs INTEGER;
+-- Clock left at the last flush, so a restarted server resumes the game fairly
+ALTER TABLE game_players ADD COLUMN clock_remaining_ms INTEGER;
@@ -0,0 +1,18 @@
+-- Community word analytics: how often each word is played and how well it scores
+-- Updated in the same transaction that writes game_moves
+CREATE TABLE IF NOT EXISTS word_stats (
+    word VARCHAR(50) PRIMARY KEY,
+    plays BIGINT NOT NULL DEFAULT 0,
+    total_score BIGINT NOT NULL DEFAULT 0,
+    distinct_players INTEGER NOT NULL DEFAULT 0,
+    last_played_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Who has played each word, so distinct_players only counts a player once
+CREATE TABLE IF NOT EXISTS word_stat_players (
+    word VARCHAR(50) NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    PRIMARY KEY (word, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_word_stats_plays ON word_stats(plays DESC);
@@ -0,0 +1,3 @@
+-- How long each move took, from the start of the player's turn to their submission
+-- NULL for moves recorded before move timing existed
+ALTER TABLE game_moves ADD COLUMN IF NOT EXISTS move_time_ms INTEGER;
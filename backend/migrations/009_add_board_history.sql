@@ -0,0 +1,10 @@
+-- Every board revision, keyed by move number (0 is the starting board)
+-- Lets replays show the exact board at each move and desync reports be checked
+-- against what the server had
+CREATE TABLE IF NOT EXISTS game_board_history (
+    game_id UUID NOT NULL REFERENCES games(game_id) ON DELETE CASCADE,
+    move_number INTEGER NOT NULL,
+    grid JSONB NOT NULL,
+    recorded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (game_id, move_number)
+);
@@ -0,0 +1,3 @@
+-- How each finished game was decided: outcome (win, draw, co-op win/loss), the
+-- winners (several for a draw) and which tie-breaker was needed, as JSON
+ALTER TABLE games ADD COLUMN IF NOT EXISTS result JSONB;
@@ -0,0 +1,2 @@
+-- Players who resigned mid-game; they're skipped in the rotation and can't win
+ALTER TABLE game_players ADD COLUMN IF NOT EXISTS forfeited BOOLEAN NOT NULL DEFAULT FALSE;
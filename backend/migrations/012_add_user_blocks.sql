@@ -0,0 +1,8 @@
+-- Per-user block lists; chat and other social messages from blocked users are
+-- filtered out before they reach the blocker
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    blocked_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (blocker_id, blocked_id)
+);
@@ -0,0 +1,10 @@
+-- Cosmetics players equip from titles and badges they've unlocked, plus a name
+-- color; broadcast with their lobby and game player info
+CREATE TABLE IF NOT EXISTS user_profiles (
+    user_id BIGINT PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    title VARCHAR(32),
+    -- #rrggbb
+    name_color CHAR(7),
+    badge VARCHAR(32),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
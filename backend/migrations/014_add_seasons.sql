@@ -0,0 +1,27 @@
+-- Leaderboard seasons; a background task closes each one when it ends, posts
+-- its summary and opens the next
+CREATE TABLE IF NOT EXISTS seasons (
+    season_id SERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL CHECK (ends_at > starts_at),
+    -- Set once the end-of-season summary has been posted
+    summarized_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_seasons_ends_at ON seasons(ends_at);
+
+-- Per-season totals, accumulated alongside the lifetime stats on users when a
+-- game finishes
+CREATE TABLE IF NOT EXISTS season_stats (
+    season_id INTEGER NOT NULL REFERENCES seasons(season_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    wins INTEGER NOT NULL DEFAULT 0,
+    total_score BIGINT NOT NULL DEFAULT 0,
+    highest_word_score INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (season_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_season_stats_score ON season_stats(season_id, total_score DESC);
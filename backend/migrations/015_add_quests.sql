@@ -0,0 +1,14 @@
+-- Rewards earned from quests
+ALTER TABLE users ADD COLUMN IF NOT EXISTS gems BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS xp BIGINT NOT NULL DEFAULT 0;
+
+-- Progress on daily and weekly quests; period_key is the day (2026-10-14) or
+-- ISO week (2026-W42) the quest was on offer, so progress resets each period
+CREATE TABLE IF NOT EXISTS user_quests (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    quest_id VARCHAR(32) NOT NULL,
+    period_key VARCHAR(16) NOT NULL,
+    progress BIGINT NOT NULL DEFAULT 0,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (user_id, quest_id, period_key)
+);
@@ -0,0 +1,43 @@
+-- Cosmetics bought with gems. Items can be added or retired here without a
+-- deploy; retired items (available = false) stay in players' inventories
+CREATE TABLE IF NOT EXISTS shop_items (
+    item_id VARCHAR(32) PRIMARY KEY,
+    -- tile_skin, board_theme or reaction_pack
+    kind VARCHAR(16) NOT NULL CHECK (kind IN ('tile_skin', 'board_theme', 'reaction_pack')),
+    name VARCHAR(64) NOT NULL,
+    price BIGINT NOT NULL CHECK (price >= 0),
+    available BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO shop_items (item_id, kind, name, price) VALUES
+    ('tile_wood', 'tile_skin', 'Wooden Tiles', 100),
+    ('tile_marble', 'tile_skin', 'Marble Tiles', 150),
+    ('tile_neon', 'tile_skin', 'Neon Tiles', 250),
+    ('board_forest', 'board_theme', 'Forest', 200),
+    ('board_midnight', 'board_theme', 'Midnight', 200),
+    ('board_sunset', 'board_theme', 'Sunset', 300),
+    ('reactions_classic', 'reaction_pack', 'Classic Reactions', 80),
+    ('reactions_party', 'reaction_pack', 'Party Reactions', 120)
+ON CONFLICT (item_id) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS user_inventory (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    item_id VARCHAR(32) NOT NULL REFERENCES shop_items(item_id),
+    price_paid BIGINT NOT NULL,
+    purchased_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, item_id)
+);
+
+-- Every change to a user's gems: positive for rewards, negative for purchases
+CREATE TABLE IF NOT EXISTS wallet_transactions (
+    transaction_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    amount BIGINT NOT NULL,
+    -- e.g., quest:daily_play_3 or purchase:tile_neon
+    reason VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_transactions_user
+    ON wallet_transactions(user_id, created_at DESC);
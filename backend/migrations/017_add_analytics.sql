@@ -0,0 +1,57 @@
+-- Aggregates for the ops dashboard (GET /api/admin/analytics/overview).
+-- Refreshed on a schedule by the server (ANALYTICS_REFRESH_SECS) so reading
+-- them never scans the game tables. Days are UTC.
+
+-- Finished games, players and average game length per day
+CREATE MATERIALIZED VIEW IF NOT EXISTS analytics_daily AS
+WITH finished AS (
+    SELECT game_id, (finished_at AT TIME ZONE 'UTC')::DATE AS day,
+        EXTRACT(EPOCH FROM finished_at - started_at) AS length_secs
+    FROM games
+    WHERE state = 'finished' AND finished_at IS NOT NULL
+),
+players AS (
+    SELECT f.day, COUNT(DISTINCT gp.user_id) AS active_players
+    FROM finished f
+    JOIN game_players gp ON gp.game_id = f.game_id
+    WHERE NOT COALESCE(gp.is_bot, FALSE)
+    GROUP BY f.day
+)
+SELECT f.day,
+    COUNT(*) AS games_played,
+    COALESCE(MAX(p.active_players), 0) AS active_players,
+    AVG(f.length_secs)::FLOAT8 AS avg_game_secs
+FROM finished f
+LEFT JOIN players p ON p.day = f.day
+GROUP BY f.day;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_daily_day ON analytics_daily(day);
+
+-- Weekly signup cohorts and how many came back to play 1, 7 and 30 or more
+-- days after signing up (rolling retention)
+CREATE MATERIALIZED VIEW IF NOT EXISTS analytics_cohorts AS
+WITH activity AS (
+    SELECT DISTINCT gp.user_id, (g.finished_at AT TIME ZONE 'UTC')::DATE AS day
+    FROM game_players gp
+    JOIN games g ON g.game_id = gp.game_id
+    WHERE g.state = 'finished' AND g.finished_at IS NOT NULL
+        AND NOT COALESCE(gp.is_bot, FALSE)
+),
+last_played AS (
+    SELECT u.user_id,
+        DATE_TRUNC('week', u.created_at AT TIME ZONE 'UTC')::DATE AS cohort_week,
+        MAX(a.day) - (u.created_at AT TIME ZONE 'UTC')::DATE AS days_retained
+    FROM users u
+    LEFT JOIN activity a ON a.user_id = u.user_id
+    WHERE u.created_at IS NOT NULL
+    GROUP BY u.user_id, u.created_at
+)
+SELECT cohort_week,
+    COUNT(*) AS cohort_size,
+    COUNT(*) FILTER (WHERE days_retained >= 1) AS retained_day_1,
+    COUNT(*) FILTER (WHERE days_retained >= 7) AS retained_day_7,
+    COUNT(*) FILTER (WHERE days_retained >= 30) AS retained_day_30
+FROM last_played
+GROUP BY cohort_week;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_cohorts_week ON analytics_cohorts(cohort_week);
@@ -0,0 +1,12 @@
+-- Invites sent to users who weren't connected at the time; delivered (and
+-- deleted) when they next connect
+CREATE TABLE IF NOT EXISTS lobby_invites (
+    from_user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    to_user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    lobby_code VARCHAR(8) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (to_user_id, from_user_id, lobby_code)
+);
+
+CREATE INDEX IF NOT EXISTS idx_lobby_invites_expires_at ON lobby_invites(expires_at);
@@ -0,0 +1,6 @@
+-- OAuth scopes each user granted on their last sign-in, so features that need
+-- optional scopes (e.g., guilds.members.read) know whether to try
+ALTER TABLE users ADD COLUMN IF NOT EXISTS oauth_scopes TEXT[] NOT NULL DEFAULT '{}';
+
+-- Role IDs from the user's guild member info, alongside their guild nickname
+ALTER TABLE user_guild_profiles ADD COLUMN IF NOT EXISTS roles BIGINT[] NOT NULL DEFAULT '{}';
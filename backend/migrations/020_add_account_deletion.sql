@@ -0,0 +1,10 @@
+-- Accounts whose owner asked for deletion; the request can be undone until the
+-- grace period ends, after which the account is anonymized and deleted
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deletion_requested_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_users_deletion_requested
+    ON users(deletion_requested_at) WHERE deletion_requested_at IS NOT NULL;
+
+-- Deleted players' games and moves move to a placeholder user with a negative ID
+-- (Discord IDs are positive), so opponents keep their history
+CREATE SEQUENCE IF NOT EXISTS deleted_user_ids;
@@ -0,0 +1,3 @@
+-- Tiles left to draw in letter bag games, as a count per letter; NULL for games
+-- whose letters are weighted random
+ALTER TABLE game_boards ADD COLUMN IF NOT EXISTS letter_bag JSONB;
@@ -0,0 +1,3 @@
+-- House rules a game was started with (minimum word length, proper nouns, long-word
+-- bonus, gems); NULL for games from before lobbies had rules, which use the standard ones
+ALTER TABLE games ADD COLUMN IF NOT EXISTS rules JSONB;
@@ -0,0 +1,10 @@
+-- Duel mode: two players race the clock on identical boards of their own
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop', 'blitz', 'duel'));
+
+-- Seed of the letters that refill played tiles, and when the duel ends (NULL outside duels)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS duel_seed BIGINT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS duel_ends_at TIMESTAMPTZ;
+-- Each duel player's own board and played words as of the last flush
+ALTER TABLE game_players ADD COLUMN IF NOT EXISTS board JSONB;
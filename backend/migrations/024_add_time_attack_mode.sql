@@ -0,0 +1,8 @@
+-- Time attack mode: the whole lobby races the clock to use every tile on one board
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop', 'blitz', 'duel', 'timeattack'));
+
+-- When the time attack ends, and the tiles used so far as a bitmap (NULL outside time attacks)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS time_attack_ends_at TIMESTAMPTZ;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS covered_tiles INTEGER;
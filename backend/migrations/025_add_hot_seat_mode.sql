@@ -0,0 +1,8 @@
+-- Hot-seat mode: several local players take turns on one connection
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop', 'blitz', 'duel', 'timeattack', 'hotseat'));
+
+-- The local players' names, scores and turn; the game itself belongs to the one
+-- Discord user playing them (NULL outside hot-seat games)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS hot_seats JSONB;
@@ -0,0 +1,41 @@
+-- Per-player word habits for the stats screen
+-- Updated in the same transaction that writes game_moves
+CREATE TABLE IF NOT EXISTS user_word_stats (
+    user_id BIGINT PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    words_played BIGINT NOT NULL DEFAULT 0,
+    total_letters BIGINT NOT NULL DEFAULT 0,
+    q_words BIGINT NOT NULL DEFAULT 0,
+    z_words BIGINT NOT NULL DEFAULT 0,
+    longest_word VARCHAR(50)
+);
+
+-- How many of each player's words start with each letter
+CREATE TABLE IF NOT EXISTS user_starting_letters (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    letter CHAR(1) NOT NULL,
+    plays BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, letter)
+);
+
+-- Backfill from the moves already on record; hot-seat moves are left out since
+-- they belong to whichever local player made them, not the game's owner
+INSERT INTO user_word_stats (user_id, words_played, total_letters, q_words, z_words, longest_word)
+SELECT
+    user_id,
+    COUNT(*),
+    SUM(LENGTH(word)),
+    COUNT(*) FILTER (WHERE UPPER(word) LIKE '%Q%'),
+    COUNT(*) FILTER (WHERE UPPER(word) LIKE '%Z%'),
+    (ARRAY_AGG(UPPER(word) ORDER BY LENGTH(word) DESC, id))[1]
+FROM game_moves
+WHERE game_id NOT IN (SELECT game_id FROM games WHERE game_mode = 'hotseat')
+GROUP BY user_id
+ON CONFLICT (user_id) DO NOTHING;
+
+INSERT INTO user_starting_letters (user_id, letter, plays)
+SELECT user_id, UPPER(LEFT(word, 1)), COUNT(*)
+FROM game_moves
+WHERE word <> ''
+    AND game_id NOT IN (SELECT game_id FROM games WHERE game_mode = 'hotseat')
+GROUP BY user_id, UPPER(LEFT(word, 1))
+ON CONFLICT (user_id, letter) DO NOTHING;
@@ -0,0 +1,57 @@
+-- Guild leaderboard rollups, so standings don't scan every game_players row in
+-- the guild; accumulated alongside the lifetime and season stats when a game
+-- finishes
+CREATE TABLE IF NOT EXISTS guild_stats (
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    wins INTEGER NOT NULL DEFAULT 0,
+    total_score BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS guild_season_stats (
+    guild_id BIGINT NOT NULL,
+    season_id INTEGER NOT NULL REFERENCES seasons(season_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    wins INTEGER NOT NULL DEFAULT 0,
+    total_score BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, season_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_guild_stats_score ON guild_stats(guild_id, total_score DESC);
+CREATE INDEX IF NOT EXISTS idx_guild_season_stats_score
+    ON guild_season_stats(guild_id, season_id, total_score DESC);
+
+-- Backfill from the games already finished; finish_game records the winner in
+-- current_turn_player
+INSERT INTO guild_stats (guild_id, user_id, games_played, wins, total_score)
+SELECT
+    g.guild_id,
+    gp.user_id,
+    COUNT(*),
+    COUNT(*) FILTER (WHERE g.current_turn_player = gp.user_id),
+    COALESCE(SUM(gp.score), 0)
+FROM game_players gp
+JOIN games g ON g.game_id = gp.game_id
+JOIN users u ON u.user_id = gp.user_id
+WHERE g.guild_id IS NOT NULL AND g.state = 'finished' AND g.game_mode <> 'hotseat'
+GROUP BY g.guild_id, gp.user_id
+ON CONFLICT (guild_id, user_id) DO NOTHING;
+
+INSERT INTO guild_season_stats (guild_id, season_id, user_id, games_played, wins, total_score)
+SELECT
+    g.guild_id,
+    s.season_id,
+    gp.user_id,
+    COUNT(*),
+    COUNT(*) FILTER (WHERE g.current_turn_player = gp.user_id),
+    COALESCE(SUM(gp.score), 0)
+FROM game_players gp
+JOIN games g ON g.game_id = gp.game_id
+JOIN users u ON u.user_id = gp.user_id
+JOIN seasons s ON g.finished_at >= s.starts_at AND g.finished_at < s.ends_at
+WHERE g.guild_id IS NOT NULL AND g.state = 'finished' AND g.game_mode <> 'hotseat'
+GROUP BY g.guild_id, s.season_id, gp.user_id
+ON CONFLICT (guild_id, season_id, user_id) DO NOTHING;
@@ -0,0 +1,49 @@
+-- Recurring weekly game nights, at most one per channel, set up by guild admins
+CREATE TABLE IF NOT EXISTS game_nights (
+    game_night_id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    -- Day of the week, 0 = Monday; with start_time, in UTC
+    weekday SMALLINT NOT NULL CHECK (weekday BETWEEN 0 AND 6),
+    start_time TIME NOT NULL,
+    -- Lowercase GameMode name
+    mode VARCHAR(20) NOT NULL,
+    target_score INTEGER,
+    -- Where the announcement goes; without one it's posted in the channel by the bot
+    webhook_url TEXT,
+    -- Role mentioned in the announcement
+    ping_role_id BIGINT,
+    created_by BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (guild_id, channel_id)
+);
+
+-- Each occurrence of a game night; kept for the guild's history when the game
+-- night itself is removed
+CREATE TABLE IF NOT EXISTS game_night_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    game_night_id INTEGER REFERENCES game_nights(game_night_id) ON DELETE SET NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    mode VARCHAR(20) NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- scheduled, started, cancelled or missed
+    status VARCHAR(16) NOT NULL DEFAULT 'scheduled',
+    announced_at TIMESTAMP WITH TIME ZONE,
+    game_id UUID REFERENCES games(game_id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (game_night_id, starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_night_events_guild
+    ON game_night_events(guild_id, starts_at DESC);
+CREATE INDEX IF NOT EXISTS idx_game_night_events_pending
+    ON game_night_events(starts_at) WHERE status = 'scheduled';
+
+-- Who was in the lobby when an event's game started
+CREATE TABLE IF NOT EXISTS game_night_attendance (
+    event_id BIGINT NOT NULL REFERENCES game_night_events(event_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, user_id)
+);
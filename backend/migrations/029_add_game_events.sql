@@ -0,0 +1,10 @@
+-- Each game's event log, in order; replayed from the starting board it rebuilds
+-- the game, and it's kept as an audit trail once the game is over
+CREATE TABLE IF NOT EXISTS game_events (
+    game_id UUID NOT NULL REFERENCES games(game_id) ON DELETE CASCADE,
+    seq BIGINT NOT NULL,
+    -- A tagged GameEvent
+    event JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (game_id, seq)
+);
@@ -0,0 +1,3 @@
+-- Guests signed in through /api/auth/guest (GUEST_ACCESS) for play-testing without
+-- Discord. They have large negative user IDs and never count towards stats or leaderboards
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_guest BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,15 @@
+-- Hand-crafted boards shared by players; a lobby's host can start games on one.
+-- Admins feature puzzles (e.g. a puzzle of the week)
+CREATE TABLE IF NOT EXISTS puzzles (
+    puzzle_id BIGSERIAL PRIMARY KEY,
+    author_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    title VARCHAR(64) NOT NULL,
+    -- The board, as stored in game_boards
+    grid JSONB NOT NULL,
+    featured_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_puzzles_created ON puzzles(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_puzzles_featured
+    ON puzzles(featured_at DESC) WHERE featured_at IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Puzzle rush mode: one player solves a run of boards, each against a shorter clock
+ALTER TABLE games DROP CONSTRAINT IF EXISTS games_game_mode_check;
+ALTER TABLE games ADD CONSTRAINT games_game_mode_check
+    CHECK (game_mode IN ('multiplayer', '2v2', 'twovtwo', 'adventure', 'coop', 'blitz', 'duel', 'timeattack', 'hotseat', 'puzzlerush'));
+
+-- The board sequence's seed, the streak and the current board's clock
+-- (NULL outside puzzle rushes)
+ALTER TABLE games ADD COLUMN IF NOT EXISTS puzzle_rush JSONB;
+
+-- Every finished run, for the puzzle rush leaderboard
+CREATE TABLE IF NOT EXISTS puzzle_rush_runs (
+    game_id UUID PRIMARY KEY REFERENCES games(game_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    -- Boards solved
+    streak INTEGER NOT NULL,
+    -- Points the solving words scored, which break ties between equal streaks
+    points INTEGER NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_puzzle_rush_runs_user
+    ON puzzle_rush_runs(user_id, streak DESC, points DESC);
@@ -0,0 +1,8 @@
+-- Paging through a game's moves, optionally one player's or one round's, in
+-- play order (GET /api/games/{id}/moves)
+CREATE INDEX IF NOT EXISTS idx_game_moves_game_order
+    ON game_moves(game_id, timestamp, id);
+CREATE INDEX IF NOT EXISTS idx_game_moves_game_user
+    ON game_moves(game_id, user_id, timestamp, id);
+CREATE INDEX IF NOT EXISTS idx_game_moves_game_round
+    ON game_moves(game_id, round_number, timestamp, id);
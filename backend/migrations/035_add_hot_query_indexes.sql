@@ -0,0 +1,23 @@
+-- Indexes for the hot queries, checked against their plans at startup in debug
+-- builds (EXPLAIN_QUERIES). Single-column indexes a new one starts with are
+-- dropped, as the new index serves their lookups too.
+
+-- A lobby's unfinished game on reconnect and after restarts
+CREATE INDEX IF NOT EXISTS idx_games_channel_state
+    ON games(channel_id, state, created_at DESC);
+DROP INDEX IF EXISTS idx_games_channel;
+
+-- A game's players in seat order (by team, then when they joined), as games
+-- are loaded and summarized
+CREATE INDEX IF NOT EXISTS idx_game_players_game_seat
+    ON game_players(game_id, team, joined_at);
+DROP INDEX IF EXISTS idx_game_players_game;
+
+-- A game's moves, optionally one round's, come from the (game_id, ...) indexes
+-- added with the moves endpoint
+DROP INDEX IF EXISTS idx_game_moves_game;
+
+-- The lifetime leaderboard, ordered exactly as get_lifetime_standings sorts it
+CREATE INDEX IF NOT EXISTS idx_users_lifetime_standings
+    ON users((COALESCE(total_score, 0)::BIGINT) DESC, (COALESCE(total_wins, 0)::BIGINT) DESC, user_id)
+    WHERE total_games > 0;
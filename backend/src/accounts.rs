@@ -0,0 +1,42 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+
+use crate::{db, models::PlayerProfile, AppState};
+
+/// How often the deletion task looks for accounts whose grace period is over
+pub const ACCOUNT_DELETION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Background task that deletes accounts once their deletion grace period ends
+///
+/// Until then `POST /api/users/restore` can cancel the deletion; afterwards the
+/// account's games are anonymized and everything else about it is gone.
+pub async fn account_deletion_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(ACCOUNT_DELETION_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("account_deletion", ACCOUNT_DELETION_INTERVAL);
+
+        if let Err(e) = delete_due_accounts(&state).await {
+            tracing::error!("Account deletion failed: {}", e);
+        }
+    }
+}
+
+async fn delete_due_accounts(state: &AppState) -> sqlx::Result<()> {
+    let grace = chrono::Duration::days(state.config.security.account_deletion_grace_days);
+    let due = db::queries::get_accounts_due_for_deletion(&state.db, Utc::now() - grace).await?;
+
+    for user_id in due {
+        let placeholder_id = db::queries::delete_account(&state.db, user_id).await?;
+        state.avatar_cache.remove(user_id);
+        state.player_profiles.set(user_id, PlayerProfile::default());
+        tracing::info!(
+            "Deleted account {}; its games now belong to {}",
+            user_id,
+            placeholder_id
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{db, AppState};
+
+/// Background task that recomputes the ops dashboard aggregates
+///
+/// The analytics views scan the game tables, so they're refreshed on a schedule
+/// rather than on every dashboard load.
+pub async fn analytics_refresh_task(state: Arc<AppState>) {
+    let period = Duration::from_secs(state.config.database.analytics_refresh_secs.max(60));
+    let mut interval = tokio::time::interval(period);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("analytics_refresh", period);
+
+        let started = std::time::Instant::now();
+        match db::queries::refresh_analytics(&state.db).await {
+            Ok(()) => tracing::debug!("Refreshed analytics in {:?}", started.elapsed()),
+            Err(e) => tracing::error!("Failed to refresh analytics: {}", e),
+        }
+    }
+}
@@ -1,19 +1,34 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use axum::{
     extract::{FromRef, FromRequestParts},
-    http::{header, request::Parts, StatusCode},
+    http::{header, request::Parts},
 };
+use dashmap::DashMap;
 use jsonwebtoken::{decode, DecodingKey, Validation};
+use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+use rand::{distr::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use crate::AppState;
+use crate::{
+    db,
+    error::ApiError,
+    models::{ApiKey, ApiScope},
+    AppState,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,      // User ID
     pub username: String, // Username
     pub exp: usize,       // Expiration time
+    /// Issued by `/api/auth/guest` rather than a Discord sign-in
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub guest: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -22,13 +37,29 @@ pub struct AuthenticatedUser {
     pub username: String,
 }
 
+impl AuthenticatedUser {
+    /// Signed in as a guest, without a Discord account
+    pub fn is_guest(&self) -> bool {
+        is_guest_id(self.user_id)
+    }
+}
+
+/// Guest IDs are at or below this: negative, so no Discord snowflake can collide
+/// with them, and far below the small negative IDs of deleted players
+const GUEST_ID_MAX: i64 = -(1 << 62);
+
+/// Whether a user ID belongs to a guest
+pub fn is_guest_id(user_id: i64) -> bool {
+    user_id <= GUEST_ID_MAX
+}
+
 /// Extractor for authenticated users from JWT tokens
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
     S: Send + Sync,
     Arc<AppState>: FromRef<S>,
 {
-    type Rejection = StatusCode;
+    type Rejection = ApiError;
 
     fn from_request_parts(
         parts: &mut Parts,
@@ -56,7 +87,7 @@ where
             });
 
         async move {
-            let token = token.ok_or(StatusCode::UNAUTHORIZED)?;
+            let token = token.ok_or(ApiError::Unauthorized)?;
 
             // Validate the JWT token
             let token_data = decode::<Claims>(
@@ -64,13 +95,19 @@ where
                 &DecodingKey::from_secret(app_state.config.security.jwt_secret.as_ref()),
                 &Validation::default(),
             )
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+            .map_err(|_| ApiError::Unauthorized)?;
 
             let user_id = token_data
                 .claims
                 .sub
                 .parse::<i64>()
-                .map_err(|_| StatusCode::UNAUTHORIZED)?;
+                .map_err(|_| ApiError::Unauthorized)?;
+            // Turning guest access off signs every guest out
+            if token_data.claims.guest != is_guest_id(user_id)
+                || (token_data.claims.guest && !app_state.config.security.guest_access)
+            {
+                return Err(ApiError::Unauthorized);
+            }
 
             Ok(AuthenticatedUser {
                 user_id,
@@ -80,6 +117,78 @@ where
     }
 }
 
+/// Header third-party integrations send their API key in
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// Start of every API key, so a leaked one is easy to recognize
+const API_KEY_PREFIX: &str = "sck_";
+const API_KEY_RANDOM_LEN: usize = 40;
+/// How much of a key is kept in the clear to tell keys apart
+const API_KEY_SHOWN_LEN: usize = 12;
+
+/// Who is calling an endpoint that integrations can read: a signed-in user, or
+/// an API key sent in `X-API-Key`
+///
+/// Users can read everything; a key only what its scopes allow, checked with
+/// `require`.
+#[derive(Debug, Clone)]
+pub enum ApiCaller {
+    User(AuthenticatedUser),
+    Key(ApiKey),
+}
+
+impl ApiCaller {
+    pub fn require(&self, scope: ApiScope) -> Result<(), ApiError> {
+        match self {
+            Self::Key(key) if !key.has_scope(scope) => {
+                Err(ApiError::MissingApiScope(scope.as_str()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for ApiCaller
+where
+    S: Send + Sync,
+    Arc<AppState>: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(key) = parts.headers.get(API_KEY_HEADER) else {
+            return AuthenticatedUser::from_request_parts(parts, state)
+                .await
+                .map(Self::User);
+        };
+        let key = key.to_str().map_err(|_| ApiError::InvalidApiKey)?;
+        let app_state = Arc::<AppState>::from_ref(state);
+        db::queries::use_api_key(&app_state.db, &hash_api_key(key))
+            .await?
+            .map(Self::Key)
+            .ok_or(ApiError::InvalidApiKey)
+    }
+}
+
+/// A new API key, and the start of it that's kept in the clear
+pub fn generate_api_key() -> (String, String) {
+    let random: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_RANDOM_LEN)
+        .map(char::from)
+        .collect();
+    let key = format!("{}{}", API_KEY_PREFIX, random);
+    let shown = key[..API_KEY_SHOWN_LEN].to_string();
+    (key, shown)
+}
+
+/// The SHA-256 of an API key, hex-encoded, as it's stored
+///
+/// Keys are long and random, so a fast unsalted hash is enough to keep a
+/// database leak from exposing them.
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
 /// Generate a JWT token for a user
 pub fn generate_token(
     user_id: i64,
@@ -95,6 +204,7 @@ pub fn generate_token(
         sub: user_id.to_string(),
         username: username.to_string(),
         exp: expiration as usize,
+        guest: false,
     };
 
     jsonwebtoken::encode(
@@ -104,6 +214,272 @@ pub fn generate_token(
     )
 }
 
+/// How long a guest session lasts; guests can't refresh, they sign in again
+pub const GUEST_TOKEN_TTL: chrono::Duration = chrono::Duration::hours(4);
+
+/// A new guest's user ID and generated name (e.g., "Guest-4821")
+pub fn new_guest_identity() -> (i64, String) {
+    let mut rng = rand::rng();
+    let user_id = rng.random_range(i64::MIN..=GUEST_ID_MAX);
+    let username = format!("Guest-{:04}", rng.random_range(0..10_000));
+    (user_id, username)
+}
+
+/// Generate a session token for a guest
+pub fn generate_guest_token(
+    user_id: i64,
+    username: &str,
+    jwt_secret: &str,
+) -> Result<(String, chrono::DateTime<chrono::Utc>), jsonwebtoken::errors::Error> {
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(GUEST_TOKEN_TTL)
+        .expect("valid timestamp");
+
+    let claims = Claims {
+        sub: user_id.to_string(),
+        username: username.to_string(),
+        exp: expires_at.timestamp() as usize,
+        guest: true,
+    };
+
+    let token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_ref()),
+    )?;
+    Ok((token, expires_at))
+}
+
+/// Audience claim that distinguishes invite tokens from session tokens
+const INVITE_AUDIENCE: &str = "lobby_invite";
+
+/// How long an invite link stays valid
+pub const INVITE_TOKEN_TTL: chrono::Duration = chrono::Duration::hours(24);
+
+/// Claims carried by a signed lobby invite
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteClaims {
+    pub lobby_code: String,
+    /// Discord user ID the invite reserves a seat for, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seat_for: Option<i64>,
+    /// User ID of the player who created the invite
+    pub invited_by: i64,
+    pub aud: String,
+    pub exp: usize,
+}
+
+/// Generate a signed, expiring invite token for a custom lobby
+pub fn generate_invite_token(
+    lobby_code: &str,
+    seat_for: Option<i64>,
+    invited_by: i64,
+    jwt_secret: &str,
+) -> Result<(String, chrono::DateTime<chrono::Utc>), jsonwebtoken::errors::Error> {
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(INVITE_TOKEN_TTL)
+        .expect("valid timestamp");
+
+    let claims = InviteClaims {
+        lobby_code: lobby_code.to_string(),
+        seat_for,
+        invited_by,
+        aud: INVITE_AUDIENCE.to_string(),
+        exp: expires_at.timestamp() as usize,
+    };
+
+    let token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_ref()),
+    )?;
+    Ok((token, expires_at))
+}
+
+/// Validate an invite token's signature, expiry and audience
+pub fn validate_invite_token(
+    token: &str,
+    jwt_secret: &str,
+) -> Result<InviteClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[INVITE_AUDIENCE]);
+
+    let token_data = decode::<InviteClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &validation,
+    )?;
+    Ok(token_data.claims)
+}
+
+/// Audience claim that distinguishes OAuth state tokens from other tokens
+const OAUTH_STATE_AUDIENCE: &str = "oauth_state";
+
+/// How long the client has to complete Discord's authorize prompt
+pub const OAUTH_STATE_TTL: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Claims carried by the `state` of an OAuth authorization
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthStateClaims {
+    /// Unique per authorization, so each state can only be exchanged once
+    pub jti: String,
+    /// S256 PKCE challenge sent to Discord; the exchange must present its verifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_challenge: Option<String>,
+    pub aud: String,
+    pub exp: usize,
+}
+
+/// Generate a signed, expiring OAuth `state`, optionally bound to a PKCE challenge
+pub fn generate_oauth_state(
+    code_challenge: Option<&str>,
+    jwt_secret: &str,
+) -> Result<(String, chrono::DateTime<chrono::Utc>), jsonwebtoken::errors::Error> {
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(OAUTH_STATE_TTL)
+        .expect("valid timestamp");
+
+    let claims = OAuthStateClaims {
+        jti: uuid::Uuid::new_v4().to_string(),
+        code_challenge: code_challenge.map(String::from),
+        aud: OAUTH_STATE_AUDIENCE.to_string(),
+        exp: expires_at.timestamp() as usize,
+    };
+
+    let token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_ref()),
+    )?;
+    Ok((token, expires_at))
+}
+
+/// Validate an OAuth state's signature, expiry and audience
+pub fn validate_oauth_state(
+    state: &str,
+    jwt_secret: &str,
+) -> Result<OAuthStateClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[OAUTH_STATE_AUDIENCE]);
+
+    let token_data = decode::<OAuthStateClaims>(
+        state,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &validation,
+    )?;
+    Ok(token_data.claims)
+}
+
+/// Whether a string can be a PKCE code challenge or verifier: 43-128 unreserved
+/// characters (RFC 7636)
+pub fn is_valid_pkce_value(value: &str) -> bool {
+    (43..=128).contains(&value.len())
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+}
+
+/// Check a PKCE code verifier against the S256 challenge it was derived from
+pub fn verify_pkce(code_verifier: &str, code_challenge: &str) -> bool {
+    if !is_valid_pkce_value(code_verifier) {
+        return false;
+    }
+    let verifier = PkceCodeVerifier::new(code_verifier.to_string());
+    PkceCodeChallenge::from_code_verifier_sha256(&verifier).as_str() == code_challenge
+}
+
+/// Remembers values that may only be used once (OAuth states, authorization
+/// codes) for as long as they could otherwise still be replayed
+///
+/// Kept in memory, so each replica only knows about its own exchanges; Discord
+/// rejects a reused authorization code either way, this just refuses it first.
+pub struct ReplayGuard {
+    seen: DashMap<String, Instant>,
+    ttl: Duration,
+}
+
+impl ReplayGuard {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            seen: DashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Record a value; false if it was already used within the TTL
+    pub fn first_use(&self, value: &str, now: Instant) -> bool {
+        let mut fresh = false;
+        self.seen
+            .entry(value.to_string())
+            .and_modify(|used_at| {
+                if now.duration_since(*used_at) >= self.ttl {
+                    *used_at = now;
+                    fresh = true;
+                }
+            })
+            .or_insert_with(|| {
+                fresh = true;
+                now
+            });
+        fresh
+    }
+
+    /// Drop values old enough to have expired anyway
+    pub fn prune(&self, now: Instant) {
+        self.seen
+            .retain(|_, used_at| now.duration_since(*used_at) < self.ttl);
+    }
+}
+
+/// Audience claim that distinguishes account restore tokens from other tokens
+const ACCOUNT_RESTORE_AUDIENCE: &str = "account_restore";
+
+/// Claims carried by the token that undoes a pending account deletion
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountRestoreClaims {
+    pub user_id: i64,
+    pub aud: String,
+    /// When the grace period ends and the account is deleted for good
+    pub exp: usize,
+}
+
+/// Generate a signed token that can cancel a user's deletion until `deletes_at`
+pub fn generate_account_restore_token(
+    user_id: i64,
+    deletes_at: chrono::DateTime<chrono::Utc>,
+    jwt_secret: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = AccountRestoreClaims {
+        user_id,
+        aud: ACCOUNT_RESTORE_AUDIENCE.to_string(),
+        exp: deletes_at.timestamp() as usize,
+    };
+
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+}
+
+/// Validate an account restore token's signature, expiry and audience
+pub fn validate_account_restore_token(
+    token: &str,
+    jwt_secret: &str,
+) -> Result<AccountRestoreClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[ACCOUNT_RESTORE_AUDIENCE]);
+    // The grace period is exact; the deletion task doesn't allow any leeway either
+    validation.leeway = 0;
+
+    let token_data = decode::<AccountRestoreClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &validation,
+    )?;
+    Ok(token_data.claims)
+}
+
 /// Validate a JWT token and extract claims
 #[cfg(test)]
 pub fn validate_token(
@@ -124,6 +500,137 @@ mod tests {
 
     const TEST_JWT_SECRET: &str = "test-jwt-secret-for-unit-tests-only";
 
+    #[test]
+    fn test_invite_token_round_trip() {
+        let (token, expires_at) =
+            generate_invite_token("ABC234", Some(42), 7, TEST_JWT_SECRET).unwrap();
+        let claims = validate_invite_token(&token, TEST_JWT_SECRET).unwrap();
+
+        assert_eq!(claims.lobby_code, "ABC234");
+        assert_eq!(claims.seat_for, Some(42));
+        assert_eq!(claims.invited_by, 7);
+        assert_eq!(claims.exp, expires_at.timestamp() as usize);
+    }
+
+    #[test]
+    fn test_invite_token_wrong_secret() {
+        let (token, _) = generate_invite_token("ABC234", None, 7, TEST_JWT_SECRET).unwrap();
+        assert!(
+            validate_invite_token(&token, "wrong-secret").is_err(),
+            "Invite tokens signed with another secret should be rejected"
+        );
+    }
+
+    #[test]
+    fn test_session_and_invite_tokens_are_not_interchangeable() {
+        // Both token kinds share a secret, so the claims must keep them apart
+        let session = generate_token(7, "host", TEST_JWT_SECRET).unwrap();
+        assert!(
+            validate_invite_token(&session, TEST_JWT_SECRET).is_err(),
+            "A session token must not be accepted as an invite"
+        );
+
+        let (invite, _) = generate_invite_token("ABC234", None, 7, TEST_JWT_SECRET).unwrap();
+        assert!(
+            validate_token(&invite, TEST_JWT_SECRET).is_err(),
+            "An invite token must not be accepted as a session token"
+        );
+    }
+
+    /// The example from RFC 7636 appendix B
+    const RFC_VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const RFC_CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[test]
+    fn test_oauth_state_round_trip() {
+        let (state, expires_at) =
+            generate_oauth_state(Some(RFC_CHALLENGE), TEST_JWT_SECRET).unwrap();
+        let claims = validate_oauth_state(&state, TEST_JWT_SECRET).unwrap();
+        assert_eq!(claims.code_challenge.as_deref(), Some(RFC_CHALLENGE));
+        assert_eq!(claims.exp, expires_at.timestamp() as usize);
+
+        let (other, _) = generate_oauth_state(None, TEST_JWT_SECRET).unwrap();
+        assert_ne!(
+            validate_oauth_state(&other, TEST_JWT_SECRET).unwrap().jti,
+            claims.jti,
+            "Every state is unique"
+        );
+        assert!(validate_oauth_state(&state, "wrong-secret").is_err());
+    }
+
+    #[test]
+    fn test_oauth_state_is_not_a_session_or_invite() {
+        let session = generate_token(7, "host", TEST_JWT_SECRET).unwrap();
+        let (invite, _) = generate_invite_token("ABC234", None, 7, TEST_JWT_SECRET).unwrap();
+        assert!(validate_oauth_state(&session, TEST_JWT_SECRET).is_err());
+        assert!(validate_oauth_state(&invite, TEST_JWT_SECRET).is_err());
+
+        let (state, _) = generate_oauth_state(None, TEST_JWT_SECRET).unwrap();
+        assert!(
+            validate_token(&state, TEST_JWT_SECRET).is_err(),
+            "A state must not be accepted as a session token"
+        );
+    }
+
+    #[test]
+    fn test_pkce_verification() {
+        assert!(verify_pkce(RFC_VERIFIER, RFC_CHALLENGE));
+        assert!(!verify_pkce(&RFC_VERIFIER.replace('d', "e"), RFC_CHALLENGE));
+        assert!(
+            !verify_pkce("too-short", RFC_CHALLENGE),
+            "Verifiers outside RFC 7636's length limits are rejected, not panicked on"
+        );
+        assert!(!is_valid_pkce_value(&"a".repeat(129)));
+        assert!(!is_valid_pkce_value(&format!("{}!", "a".repeat(43))));
+    }
+
+    #[test]
+    fn test_replay_guard() {
+        let guard = ReplayGuard::new(Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(guard.first_use("code", now));
+        assert!(
+            !guard.first_use("code", now + Duration::from_secs(30)),
+            "Replayed"
+        );
+        assert!(guard.first_use("other", now));
+        assert!(
+            guard.first_use("code", now + Duration::from_secs(60)),
+            "Long-expired values are forgotten"
+        );
+
+        guard.prune(now + Duration::from_secs(125));
+        assert!(guard.seen.is_empty());
+    }
+
+    #[test]
+    fn test_account_restore_token() {
+        let deletes_at = chrono::Utc::now() + chrono::Duration::days(7);
+        let token = generate_account_restore_token(7, deletes_at, TEST_JWT_SECRET).unwrap();
+        let claims = validate_account_restore_token(&token, TEST_JWT_SECRET).unwrap();
+        assert_eq!(claims.user_id, 7);
+        assert_eq!(claims.exp, deletes_at.timestamp() as usize);
+        assert!(validate_account_restore_token(&token, "wrong-secret").is_err());
+
+        let expired = generate_account_restore_token(
+            7,
+            chrono::Utc::now() - chrono::Duration::seconds(5),
+            TEST_JWT_SECRET,
+        )
+        .unwrap();
+        assert!(
+            validate_account_restore_token(&expired, TEST_JWT_SECRET).is_err(),
+            "Nothing can be restored once the grace period is over"
+        );
+
+        let session = generate_token(7, "host", TEST_JWT_SECRET).unwrap();
+        assert!(validate_account_restore_token(&session, TEST_JWT_SECRET).is_err());
+        assert!(
+            validate_token(&token, TEST_JWT_SECRET).is_err(),
+            "A restore token must not be accepted as a session token"
+        );
+    }
+
     #[test]
     fn test_generate_token_success() {
         let user_id = 123456789i64;
@@ -238,6 +745,7 @@ mod tests {
             sub: "12345".to_string(),
             username: "test".to_string(),
             exp: 1000000,
+            guest: false,
         };
 
         let json = serde_json::to_string(&claims).unwrap();
@@ -248,6 +756,68 @@ mod tests {
         assert_eq!(claims.exp, deserialized.exp);
     }
 
+    #[test]
+    fn test_guest_tokens() {
+        let (user_id, username) = new_guest_identity();
+        assert!(is_guest_id(user_id), "Guest IDs never look like snowflakes");
+        assert!(!is_guest_id(7));
+        assert!(!is_guest_id(-1), "Deleted players aren't guests");
+        assert!(username.starts_with("Guest-"));
+
+        let (token, expires_at) =
+            generate_guest_token(user_id, &username, TEST_JWT_SECRET).unwrap();
+        let claims = validate_token(&token, TEST_JWT_SECRET).unwrap();
+        assert!(claims.guest);
+        assert_eq!(claims.sub, user_id.to_string());
+        assert_eq!(claims.exp, expires_at.timestamp() as usize);
+
+        let session = generate_token(7, "host", TEST_JWT_SECRET).unwrap();
+        let json =
+            serde_json::to_value(validate_token(&session, TEST_JWT_SECRET).unwrap()).unwrap();
+        assert!(
+            json.get("guest").is_none(),
+            "Discord sessions look the same as before"
+        );
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let (key, shown) = generate_api_key();
+        assert!(key.starts_with("sck_"));
+        assert_eq!(key.len(), 44);
+        assert!(key.starts_with(&shown));
+        assert_ne!(generate_api_key().0, key);
+
+        let hash = hash_api_key(&key);
+        assert_eq!(hash.len(), 64, "Fits the CHAR(64) column");
+        assert_eq!(hash, hash_api_key(&key));
+        assert_ne!(hash, hash_api_key(&generate_api_key().0));
+    }
+
+    #[test]
+    fn test_api_key_scopes() {
+        let key = ApiCaller::Key(ApiKey {
+            key_id: 1,
+            user_id: 7,
+            name: "dashboard".to_string(),
+            key_prefix: "sck_abcdefgh".to_string(),
+            scopes: vec!["read:leaderboard".to_string()],
+            created_at: chrono::Utc::now(),
+            last_used_at: None,
+        });
+        assert!(key.require(ApiScope::ReadLeaderboard).is_ok());
+        assert!(matches!(
+            key.require(ApiScope::ReadGames),
+            Err(ApiError::MissingApiScope("read:games"))
+        ));
+
+        let user = ApiCaller::User(AuthenticatedUser {
+            user_id: 7,
+            username: "host".to_string(),
+        });
+        assert!(user.require(ApiScope::ReadGames).is_ok());
+    }
+
     #[test]
     fn test_authenticated_user_debug() {
         let user = AuthenticatedUser {
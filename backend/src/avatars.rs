@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use dashmap::DashMap;
+
+/// Only avatars on Discord's CDN are fetched, whatever ends up in `users.avatar_url`
+const DISCORD_CDN_PREFIX: &str = "https://cdn.discordapp.com/";
+/// Largest avatar the proxy will cache and serve
+pub const MAX_AVATAR_BYTES: usize = 512 * 1024;
+/// Size requested from Discord; plenty for player lists and game boards
+const AVATAR_SIZE: u32 = 128;
+
+/// Where clients should load a user's avatar from, in place of Discord's CDN
+///
+/// The avatar hash rides along as `?v=`, so a changed avatar gets a new URL and
+/// browsers don't keep showing the cached old one.
+pub fn proxy_url(user_id: i64, avatar_url: Option<&str>) -> Option<String> {
+    let avatar_url = avatar_url?;
+    Some(format!(
+        "/api/avatars/{}?v={}",
+        user_id,
+        avatar_version(avatar_url)
+    ))
+}
+
+/// A Discord avatar URL's hash (`.../avatars/<user>/<hash>.png`), used to tell
+/// whether a cached copy is still current
+pub fn avatar_version(avatar_url: &str) -> &str {
+    let file = avatar_url.rsplit('/').next().unwrap_or(avatar_url);
+    file.split(['.', '?']).next().unwrap_or(file)
+}
+
+/// The URL to fetch for a stored avatar; None if it isn't on Discord's CDN
+pub fn upstream_url(avatar_url: &str) -> Option<String> {
+    avatar_url
+        .starts_with(DISCORD_CDN_PREFIX)
+        .then(|| format!("{}?size={}", avatar_url, AVATAR_SIZE))
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedAvatar {
+    /// `avatar_version` of the URL the image was fetched from
+    pub version: String,
+    pub content_type: String,
+    pub body: Bytes,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    avatar: CachedAvatar,
+    fetched_at: Instant,
+    last_used: Instant,
+}
+
+/// In-memory cache of avatar images, evicting the least recently used once full
+///
+/// Entries older than the TTL are refetched, so avatars changed on Discord show
+/// up even for clients still holding an old URL.
+pub struct AvatarCache {
+    entries: DashMap<i64, CacheEntry>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl AvatarCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// A user's cached avatar if it's within the TTL and, when `version` is
+    /// given, fetched from that version
+    pub fn get(&self, user_id: i64, version: Option<&str>, now: Instant) -> Option<CachedAvatar> {
+        let mut entry = self.entries.get_mut(&user_id)?;
+        if now.duration_since(entry.fetched_at) >= self.ttl
+            || version.is_some_and(|v| v != entry.avatar.version)
+        {
+            return None;
+        }
+        entry.last_used = now;
+        Some(entry.avatar.clone())
+    }
+
+    /// Forget a user's avatar, e.g. once their account is deleted
+    pub fn remove(&self, user_id: i64) {
+        self.entries.remove(&user_id);
+    }
+
+    pub fn insert(&self, user_id: i64, avatar: CachedAvatar, now: Instant) {
+        if !self.entries.contains_key(&user_id) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.last_used)
+                .map(|entry| *entry.key());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            user_id,
+            CacheEntry {
+                avatar,
+                fetched_at: now,
+                last_used: now,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://cdn.discordapp.com/avatars/123/a_1b2c3d.png";
+
+    fn avatar(version: &str) -> CachedAvatar {
+        CachedAvatar {
+            version: version.to_string(),
+            content_type: "image/png".to_string(),
+            body: Bytes::from_static(b"png"),
+        }
+    }
+
+    #[test]
+    fn test_proxy_url_carries_avatar_hash() {
+        assert_eq!(
+            proxy_url(123, Some(URL)).as_deref(),
+            Some("/api/avatars/123?v=a_1b2c3d")
+        );
+        assert_eq!(proxy_url(123, None), None, "No avatar, nothing to proxy");
+    }
+
+    #[test]
+    fn test_only_discord_cdn_is_fetched() {
+        assert_eq!(
+            upstream_url(URL).as_deref(),
+            Some("https://cdn.discordapp.com/avatars/123/a_1b2c3d.png?size=128")
+        );
+        assert_eq!(
+            upstream_url("http://169.254.169.254/latest/meta-data"),
+            None
+        );
+        assert_eq!(
+            upstream_url("https://cdn.discordapp.com.evil.example/a.png"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_expiry_and_versions() {
+        let cache = AvatarCache::new(10, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert(1, avatar("abc"), now);
+
+        assert!(cache.get(1, Some("abc"), now).is_some());
+        assert!(cache.get(1, None, now).is_some(), "Any version will do");
+        assert!(
+            cache.get(1, Some("def"), now).is_none(),
+            "A changed avatar isn't served from the old copy"
+        );
+        assert!(
+            cache.get(1, None, now + Duration::from_secs(60)).is_none(),
+            "Expired entries are refetched"
+        );
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = AvatarCache::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert(1, avatar("a"), now);
+        cache.insert(2, avatar("b"), now + Duration::from_secs(1));
+        cache.get(1, None, now + Duration::from_secs(2));
+
+        cache.insert(3, avatar("c"), now + Duration::from_secs(3));
+        assert_eq!(cache.entries.len(), 2);
+        assert!(
+            cache.get(2, None, now + Duration::from_secs(3)).is_none(),
+            "User 2's avatar was used least recently"
+        );
+        assert!(cache.get(1, None, now + Duration::from_secs(3)).is_some());
+    }
+}
@@ -3,6 +3,8 @@ use std::env;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
+use crate::{dictionary::DictionaryBackend, security_headers};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database: DatabaseConfig,
@@ -10,12 +12,19 @@ pub struct Config {
     pub server: ServerConfig,
     pub security: SecurityConfig,
     pub game: GameConfig,
+    pub limits: LimitsConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
+    /// How often the analytics views behind the ops dashboard are recomputed
+    pub analytics_refresh_secs: u64,
+    /// Queries taking at least this long are logged and counted as slow
+    pub slow_query_ms: u64,
+    /// Whether the hot queries' plans are checked for sequential scans at startup
+    pub explain_queries: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -23,6 +32,35 @@ pub struct DiscordConfig {
     pub client_id: String,
     pub client_secret: String,
     pub redirect_uri: String,
+    /// Optional scopes requested on top of `BASE_OAUTH_SCOPES` (e.g., guilds.members.read)
+    pub extra_scopes: Vec<String>,
+    /// Bot token for DMing players (turn reminders); without one no DMs are sent
+    pub bot_token: Option<String>,
+}
+
+/// Scopes every sign-in needs: the user's identity, and their guilds for permission checks
+pub const BASE_OAUTH_SCOPES: [&str; 2] = ["identify", "guilds"];
+
+impl DiscordConfig {
+    /// Every scope the client should ask for when authorizing
+    pub fn scopes(&self) -> Vec<String> {
+        let mut scopes: Vec<String> = BASE_OAUTH_SCOPES.iter().map(|s| s.to_string()).collect();
+        for scope in &self.extra_scopes {
+            if !scopes.contains(scope) {
+                scopes.push(scope.clone());
+            }
+        }
+        scopes
+    }
+}
+
+/// Parse a comma- or space-separated list of OAuth scopes
+fn parse_scopes(raw: &str) -> Vec<String> {
+    raw.split([',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -30,20 +68,175 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub frontend_url: String,
+    /// Origins allowed for CORS and WebSocket upgrades
+    /// (frontend_url, the Discord activity proxy, and any extras from ALLOWED_ORIGINS)
+    pub allowed_origins: Vec<String>,
+    /// Skip origin checks entirely (local development only)
+    pub allow_any_origin: bool,
+    pub security_headers: SecurityHeadersConfig,
+    /// Serve the frontend from this directory; defaults to `../frontend`, or to
+    /// the embedded bundle when built with the `embedded-frontend` feature
+    pub frontend_dir: Option<String>,
+    /// Avatars kept in memory by `/api/avatars`
+    pub avatar_cache_size: usize,
+    /// How long a cached avatar is served before it's fetched from Discord again
+    pub avatar_cache_ttl_secs: u64,
+    /// How long a public stats response is served from memory
+    pub public_stats_cache_secs: u64,
+}
+
+/// Security headers added to every HTTP response
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityHeadersConfig {
+    /// Content-Security-Policy; empty to send none
+    pub content_security_policy: String,
+    pub referrer_policy: String,
+    /// Send Strict-Transport-Security (only when served over TLS, e.g. behind a proxy)
+    pub hsts: bool,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: security_headers::DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+            referrer_policy: security_headers::DEFAULT_REFERRER_POLICY.to_string(),
+            hsts: false,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Check a request's Origin header value against the allowlist
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        if self.allow_any_origin {
+            return true;
+        }
+        let origin = normalize_origin(origin);
+        self.allowed_origins
+            .iter()
+            .any(|allowed| normalize_origin(allowed) == origin)
+    }
+}
+
+/// Origins compare case-insensitively and without a trailing slash
+fn normalize_origin(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// Parse a comma-separated list of Discord user IDs, skipping blank entries
+fn parse_user_ids(raw: &str) -> Result<Vec<i64>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse()
+                .with_context(|| format!("Invalid Discord user ID: {}", id))
+        })
+        .collect()
+}
+
+/// Build the origin allowlist: the frontend, the Discord activity proxy for this app,
+/// and any comma-separated extras
+fn build_allowed_origins(frontend_url: &str, client_id: &str, extra: &str) -> Vec<String> {
+    let mut origins = vec![
+        frontend_url.to_string(),
+        format!("https://{}.discordsays.com", client_id),
+    ];
+    origins.extend(
+        extra
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+            .map(String::from),
+    );
+    origins
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SecurityConfig {
     pub jwt_secret: String,
     pub encryption_key: String,
+    /// Discord user IDs allowed to open the admin console (`/ws?admin=1`)
+    pub admin_user_ids: Vec<i64>,
+    /// Refuse code exchanges without a server-issued state and PKCE verifier
+    pub require_pkce: bool,
+    /// Days between `DELETE /api/users/me` and the account actually being deleted
+    pub account_deletion_grace_days: i64,
+    /// Let `/api/auth/guest` sign in players without Discord, for play-testing
+    pub guest_access: bool,
+}
+
+impl SecurityConfig {
+    pub fn is_admin(&self, user_id: i64) -> bool {
+        self.admin_user_ids.contains(&user_id)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct GameConfig {
     pub dictionary_path: String,
+    /// Optional list of premium/obscure words, accepted unless a lobby is strict
+    pub extended_dictionary_path: Option<String>,
+    /// Directory of theme packs (`<name>.txt`) that hosts can pick per lobby
+    pub dictionary_packs_dir: String,
+    /// In-memory representation of the word list
+    pub dictionary_backend: DictionaryBackend,
     pub max_players: usize,
     pub default_rounds: u8,
     pub timer_duration: u32,
+    /// How long a host-reserved seat is held before it's released
+    pub seat_reservation_secs: u64,
+    /// Thinking time per player for a whole blitz game
+    pub blitz_clock_secs: u64,
+    /// How long a duel runs from its start
+    pub duel_secs: u64,
+    /// Time a time attack team has to clear the board
+    pub time_attack_secs: u64,
+    /// Time the first board of a puzzle rush gets; each board after gets less
+    pub puzzle_rush_secs: u32,
+    /// Points a word needs to solve a puzzle rush board
+    pub puzzle_rush_target_score: i32,
+    /// Points a player loses for passing their turn
+    pub pass_penalty: u32,
+    /// End a game early once this many unused words (or fewer) remain on the board
+    pub board_exhausted_threshold: usize,
+    /// Boards kept pre-generated per board kind for instant game starts (0 disables)
+    pub grid_pool_size: usize,
+    /// How long each leaderboard season runs
+    pub season_length_days: u32,
+    /// Discord webhook that gets each season's final standings
+    pub season_summary_webhook_url: Option<String>,
+    /// Send `YourTurn` once the current player has been silent this long (0 disables)
+    pub turn_reminder_secs: u64,
+    /// Also DM them through the bot once silent this long (needs a bot token)
+    pub turn_reminder_dm_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LimitsConfig {
+    /// Concurrent WebSocket connections allowed per user
+    pub max_connections_per_user: usize,
+    /// Concurrent WebSocket connections allowed per client IP
+    pub max_connections_per_ip: usize,
+    /// Custom lobbies a user may create per minute
+    pub lobby_creations_per_minute: usize,
+    /// Players a user may invite per minute
+    pub invites_per_minute: usize,
+    /// Public stats requests allowed per client IP per minute
+    pub public_stats_per_minute: usize,
+    /// Words a player of a solo game may have accepted per minute before the
+    /// rest are turned away as implausible
+    pub solo_words_per_minute: u32,
+    /// Take the client IP from the last X-Forwarded-For hop (only behind a
+    /// trusted proxy)
+    pub trust_forwarded_for: bool,
+    /// Messages queued per connection before a slow client starts losing them
+    pub outbound_queue_size: usize,
+    /// Disconnect a client whose send queue stays full this long
+    pub slow_client_timeout_secs: u64,
+    /// Largest WebSocket message (and frame) accepted from a client; bigger ones
+    /// close the connection
+    pub max_message_bytes: usize,
 }
 
 impl Config {
@@ -56,6 +249,18 @@ impl Config {
                 .unwrap_or_else(|_| "10".to_string())
                 .parse()
                 .context("DATABASE_MAX_CONNECTIONS must be a number")?,
+            analytics_refresh_secs: env::var("ANALYTICS_REFRESH_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .context("ANALYTICS_REFRESH_SECS must be a number")?,
+            slow_query_ms: env::var("SLOW_QUERY_MS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .context("SLOW_QUERY_MS must be a number")?,
+            explain_queries: env::var("EXPLAIN_QUERIES")
+                .unwrap_or_else(|_| cfg!(debug_assertions).to_string())
+                .parse()
+                .context("EXPLAIN_QUERIES must be true or false")?,
         };
 
         let discord = DiscordConfig {
@@ -64,27 +269,86 @@ impl Config {
                 .context("DISCORD_CLIENT_SECRET must be set")?,
             redirect_uri: env::var("DISCORD_REDIRECT_URI")
                 .context("DISCORD_REDIRECT_URI must be set")?,
+            extra_scopes: parse_scopes(&env::var("DISCORD_EXTRA_SCOPES").unwrap_or_default()),
+            bot_token: env::var("DISCORD_BOT_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
         };
 
+        let frontend_url =
+            env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
         let server = ServerConfig {
             host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .context("PORT must be a number")?,
-            frontend_url: env::var("FRONTEND_URL")
-                .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            allowed_origins: build_allowed_origins(
+                &frontend_url,
+                &discord.client_id,
+                &env::var("ALLOWED_ORIGINS").unwrap_or_default(),
+            ),
+            allow_any_origin: env::var("CORS_ALLOW_ANY_ORIGIN")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("CORS_ALLOW_ANY_ORIGIN must be true or false")?,
+            frontend_url,
+            frontend_dir: env::var("FRONTEND_DIR").ok().filter(|dir| !dir.is_empty()),
+            avatar_cache_size: env::var("AVATAR_CACHE_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            avatar_cache_ttl_secs: env::var("AVATAR_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            public_stats_cache_secs: env::var("PUBLIC_STATS_CACHE_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            security_headers: SecurityHeadersConfig {
+                content_security_policy: env::var("CONTENT_SECURITY_POLICY").unwrap_or_else(|_| {
+                    security_headers::DEFAULT_CONTENT_SECURITY_POLICY.to_string()
+                }),
+                referrer_policy: env::var("REFERRER_POLICY")
+                    .unwrap_or_else(|_| security_headers::DEFAULT_REFERRER_POLICY.to_string()),
+                hsts: env::var("ENABLE_HSTS")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .context("ENABLE_HSTS must be true or false")?,
+            },
         };
 
         let security = SecurityConfig {
             jwt_secret: env::var("JWT_SECRET").context("JWT_SECRET must be set")?,
             encryption_key: env::var("ENCRYPTION_KEY")
                 .context("ENCRYPTION_KEY must be set (32-byte base64 encoded key)")?,
+            admin_user_ids: parse_user_ids(&env::var("ADMIN_USER_IDS").unwrap_or_default())
+                .context("ADMIN_USER_IDS must be a comma-separated list of user IDs")?,
+            require_pkce: env::var("REQUIRE_PKCE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("REQUIRE_PKCE must be true or false")?,
+            account_deletion_grace_days: env::var("ACCOUNT_DELETION_GRACE_DAYS")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()
+                .unwrap_or(7),
+            guest_access: env::var("GUEST_ACCESS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("GUEST_ACCESS must be true or false")?,
         };
 
         let game = GameConfig {
             dictionary_path: env::var("DICTIONARY_PATH")
                 .unwrap_or_else(|_| "./dictionary.txt".to_string()),
+            extended_dictionary_path: env::var("EXTENDED_DICTIONARY_PATH").ok(),
+            dictionary_packs_dir: env::var("DICTIONARY_PACKS_DIR")
+                .unwrap_or_else(|_| "./dictionaries/packs".to_string()),
+            dictionary_backend: env::var("DICTIONARY_BACKEND")
+                .unwrap_or_else(|_| "hash".to_string())
+                .parse()
+                .unwrap_or_default(),
             max_players: env::var("MAX_PLAYERS")
                 .unwrap_or_else(|_| "6".to_string())
                 .parse()
@@ -97,6 +361,99 @@ impl Config {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            seat_reservation_secs: env::var("SEAT_RESERVATION_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+            blitz_clock_secs: env::var("BLITZ_CLOCK_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()
+                .unwrap_or(120),
+            duel_secs: env::var("DUEL_SECS")
+                .unwrap_or_else(|_| "180".to_string())
+                .parse()
+                .unwrap_or(180),
+            time_attack_secs: env::var("TIME_ATTACK_SECS")
+                .unwrap_or_else(|_| "240".to_string())
+                .parse()
+                .unwrap_or(240),
+            puzzle_rush_secs: env::var("PUZZLE_RUSH_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            puzzle_rush_target_score: env::var("PUZZLE_RUSH_TARGET_SCORE")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            pass_penalty: env::var("PASS_PENALTY")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            board_exhausted_threshold: env::var("BOARD_EXHAUSTED_THRESHOLD")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            grid_pool_size: env::var("GRID_POOL_SIZE")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
+            season_length_days: env::var("SEASON_LENGTH_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            season_summary_webhook_url: env::var("SEASON_SUMMARY_WEBHOOK_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            turn_reminder_secs: env::var("TURN_REMINDER_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            turn_reminder_dm_secs: env::var("TURN_REMINDER_DM_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok()),
+        };
+
+        let limits = LimitsConfig {
+            max_connections_per_user: env::var("MAX_CONNECTIONS_PER_USER")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            max_connections_per_ip: env::var("MAX_CONNECTIONS_PER_IP")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50),
+            lobby_creations_per_minute: env::var("LOBBY_CREATIONS_PER_MINUTE")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            invites_per_minute: env::var("INVITES_PER_MINUTE")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            public_stats_per_minute: env::var("PUBLIC_STATS_PER_MINUTE")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            solo_words_per_minute: env::var("SOLO_WORDS_PER_MINUTE")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            trust_forwarded_for: env::var("TRUST_FORWARDED_FOR")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("TRUST_FORWARDED_FOR must be true or false")?,
+            outbound_queue_size: env::var("OUTBOUND_QUEUE_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            slow_client_timeout_secs: env::var("SLOW_CLIENT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            max_message_bytes: env::var("WS_MAX_MESSAGE_BYTES")
+                .unwrap_or_else(|_| "16384".to_string())
+                .parse()
+                .unwrap_or(16384),
         };
 
         Ok(Config {
@@ -105,6 +462,7 @@ impl Config {
             server,
             security,
             game,
+            limits,
         })
     }
 
@@ -116,3 +474,96 @@ impl Config {
         format!("{}:{}", self.server.host, self.server.port)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_server_config(allow_any_origin: bool) -> ServerConfig {
+        ServerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            frontend_url: "http://localhost:3000".to_string(),
+            allowed_origins: build_allowed_origins(
+                "http://localhost:3000",
+                "123456",
+                "https://staging.example.com, ",
+            ),
+            allow_any_origin,
+            security_headers: SecurityHeadersConfig::default(),
+            frontend_dir: None,
+            avatar_cache_size: 1000,
+            avatar_cache_ttl_secs: 3600,
+            public_stats_cache_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_allowlist_includes_frontend_and_discord_proxy() {
+        let config = create_test_server_config(false);
+        assert!(config.is_origin_allowed("http://localhost:3000"));
+        assert!(config.is_origin_allowed("https://123456.discordsays.com"));
+        assert!(
+            config.is_origin_allowed("https://staging.example.com"),
+            "Extra origins from ALLOWED_ORIGINS should be allowed"
+        );
+        assert_eq!(
+            config.allowed_origins.len(),
+            3,
+            "Blank entries in ALLOWED_ORIGINS should be ignored"
+        );
+    }
+
+    #[test]
+    fn test_origin_matching_is_normalized() {
+        let config = create_test_server_config(false);
+        assert!(config.is_origin_allowed("HTTP://LOCALHOST:3000/"));
+    }
+
+    #[test]
+    fn test_unlisted_origins_are_rejected() {
+        let config = create_test_server_config(false);
+        assert!(!config.is_origin_allowed("https://evil.example.com"));
+        assert!(
+            !config.is_origin_allowed("https://999999.discordsays.com"),
+            "Other Discord applications' activity origins should be rejected"
+        );
+    }
+
+    #[test]
+    fn test_allow_any_origin_opt_out() {
+        let config = create_test_server_config(true);
+        assert!(config.is_origin_allowed("https://evil.example.com"));
+    }
+
+    #[test]
+    fn test_parse_admin_user_ids() {
+        assert_eq!(
+            parse_user_ids(" 123, 456 ,,").unwrap(),
+            vec![123, 456],
+            "Whitespace and blank entries should be ignored"
+        );
+        assert!(parse_user_ids("").unwrap().is_empty());
+        assert!(
+            parse_user_ids("123,abc").is_err(),
+            "A typo in ADMIN_USER_IDS should fail startup rather than be skipped"
+        );
+    }
+
+    #[test]
+    fn test_extra_scopes_extend_the_base_ones() {
+        let discord = DiscordConfig {
+            client_id: "123456".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "http://localhost:3000".to_string(),
+            extra_scopes: parse_scopes("guilds.members.read, identify"),
+            bot_token: None,
+        };
+        assert_eq!(
+            discord.scopes(),
+            vec!["identify", "guilds", "guilds.members.read"],
+            "Base scopes come first and aren't repeated"
+        );
+        assert!(parse_scopes(" ,").is_empty());
+    }
+}
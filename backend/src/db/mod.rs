@@ -1,6 +1,25 @@
-use sqlx::{postgres::PgPoolOptions, PgPool, Result};
+use sqlx::{
+    migrate::{MigrateError, Migrator},
+    postgres::PgPoolOptions,
+    Connection, PgConnection, PgPool, Result,
+};
 
+pub mod plans;
 pub mod queries;
+pub mod timing;
+
+/// Embedded database migrations, shared by startup and the readiness probe
+///
+/// Migrations applied by a newer build are tolerated, so replicas still on the
+/// previous release keep starting while a deploy rolls out. That relies on
+/// migrations staying backwards compatible (add, then remove a release later).
+pub static MIGRATOR: Migrator = Migrator {
+    ignore_missing: true,
+    ..sqlx::migrate!("./migrations")
+};
+
+/// Advisory lock key held while applying migrations
+const MIGRATION_LOCK_KEY: i64 = 0x5350_454c_4c4d_4947;
 
 pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<PgPool> {
     PgPoolOptions::new()
@@ -8,3 +27,30 @@ pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<PgP
         .connect(database_url)
         .await
 }
+
+/// Apply pending migrations, waiting for any other instance that's already at it
+///
+/// Uses its own connection rather than the pool: the advisory lock belongs to the
+/// session, so closing the connection releases it even if a migration fails and
+/// no pooled connection is left holding it.
+pub async fn run_migrations(database_url: &str) -> std::result::Result<(), MigrateError> {
+    let mut conn = PgConnection::connect(database_url).await?;
+
+    let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .fetch_one(&mut conn)
+        .await?;
+    if !acquired {
+        tracing::info!("Another instance is applying migrations, waiting for it to finish");
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut conn)
+            .await?;
+    }
+
+    let result = MIGRATOR.run(&mut conn).await;
+    if let Err(e) = conn.close().await {
+        tracing::warn!("Failed to close the migration connection: {}", e);
+    }
+    result
+}
@@ -0,0 +1,155 @@
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// The hot queries, as in `queries`, with representative arguments in place of
+/// their parameters
+const KEY_QUERIES: [(&str, &str); 6] = [
+    (
+        "get_active_game_for_lobby",
+        "SELECT * FROM games
+         WHERE channel_id = 0 AND state IN ('waiting', 'active')
+         ORDER BY created_at DESC
+         LIMIT 1",
+    ),
+    (
+        "load_game_state players",
+        "SELECT * FROM game_players
+         WHERE game_id = '00000000-0000-0000-0000-000000000000'
+         ORDER BY team, joined_at",
+    ),
+    (
+        "get_game_moves_page",
+        "SELECT * FROM game_moves
+         WHERE game_id = '00000000-0000-0000-0000-000000000000' AND round_number = 1
+         ORDER BY timestamp, id
+         LIMIT 51",
+    ),
+    (
+        "get_lifetime_standings",
+        "SELECT user_id,
+                COALESCE(total_wins, 0)::BIGINT AS wins,
+                COALESCE(total_score, 0)::BIGINT AS total_score
+         FROM users
+         WHERE total_games > 0
+         ORDER BY total_score DESC, wins DESC, user_id
+         LIMIT 50",
+    ),
+    (
+        "get_season_standings",
+        "SELECT ss.user_id, u.username FROM season_stats ss
+         JOIN users u ON u.user_id = ss.user_id
+         WHERE ss.season_id = 0
+         ORDER BY ss.total_score DESC, ss.wins DESC, ss.user_id
+         LIMIT 50",
+    ),
+    (
+        "get_guild_standings",
+        "SELECT gs.user_id, u.username FROM guild_stats gs
+         JOIN users u ON u.user_id = gs.user_id
+         WHERE gs.guild_id = 0
+         ORDER BY gs.total_score DESC, gs.wins DESC, gs.user_id
+         LIMIT 50",
+    ),
+];
+
+/// EXPLAIN the hot queries and warn about any that scan a whole table
+///
+/// Sequential scans are priced out of the running for the check, so small
+/// development tables don't hide a missing index: a table is only scanned if no
+/// index can serve the query. Failures are logged and don't stop startup.
+pub async fn check_query_plans(pool: &PgPool) {
+    if let Err(e) = explain_key_queries(pool).await {
+        tracing::warn!("Failed to check query plans: {}", e);
+    }
+}
+
+async fn explain_key_queries(pool: &PgPool) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET LOCAL enable_seqscan = off")
+        .execute(&mut *tx)
+        .await?;
+
+    let mut scanning = 0;
+    for (name, sql) in KEY_QUERIES {
+        let plan: Value = sqlx::query_scalar(&format!("EXPLAIN (FORMAT JSON) {}", sql))
+            .fetch_one(&mut *tx)
+            .await?;
+        let tables = seq_scans(&plan);
+        if !tables.is_empty() {
+            scanning += 1;
+            tracing::warn!(
+                "Query {} scans {} sequentially; it needs an index",
+                name,
+                tables.join(", ")
+            );
+        }
+    }
+    tx.rollback().await?;
+
+    if scanning == 0 {
+        tracing::info!("Query plans checked: every hot query uses an index");
+    }
+    Ok(())
+}
+
+/// The tables a JSON query plan reads with sequential scans
+fn seq_scans(plan: &Value) -> Vec<String> {
+    let mut tables = Vec::new();
+    collect_seq_scans(plan, &mut tables);
+    tables.sort();
+    tables.dedup();
+    tables
+}
+
+fn collect_seq_scans(node: &Value, tables: &mut Vec<String>) {
+    match node {
+        Value::Array(items) => {
+            for item in items {
+                collect_seq_scans(item, tables);
+            }
+        }
+        Value::Object(fields) => {
+            if fields.get("Node Type").and_then(Value::as_str) == Some("Seq Scan") {
+                if let Some(table) = fields.get("Relation Name").and_then(Value::as_str) {
+                    tables.push(table.to_string());
+                }
+            }
+            for child in ["Plan", "Plans"] {
+                if let Some(child) = fields.get(child) {
+                    collect_seq_scans(child, tables);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_seq_scans_are_found_anywhere_in_the_plan() {
+        let plan = json!([{
+            "Plan": {
+                "Node Type": "Limit",
+                "Plans": [{
+                    "Node Type": "Nested Loop",
+                    "Plans": [
+                        {"Node Type": "Seq Scan", "Relation Name": "season_stats"},
+                        {"Node Type": "Index Scan", "Relation Name": "users"},
+                        {"Node Type": "Seq Scan", "Relation Name": "season_stats"}
+                    ]
+                }]
+            }
+        }]);
+        assert_eq!(seq_scans(&plan), vec!["season_stats".to_string()]);
+
+        let indexed = json!([{
+            "Plan": {"Node Type": "Index Scan", "Relation Name": "games"}
+        }]);
+        assert!(seq_scans(&indexed).is_empty());
+    }
+}
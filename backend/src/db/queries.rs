@@ -1,35 +1,81 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use sqlx::{PgPool, Result};
 use tracing;
 use uuid::Uuid;
 
 use crate::{
-    encryption,
+    auth, avatars,
+    db::timing::timed,
+    encryption::Encrypted,
+    game::{letter_bag::LetterBag, result::GameResult, rules::GameRules},
     models::{
-        Game, GameBoard, GameDbState, GameMode, GameMove, GamePlayer, GamePlayerRecord, GameState,
-        GameStatus, GridCell, User, UserGuildProfile,
+        analytics::{DailyActivity, RetentionCohort},
+        api_key::ApiKey,
+        invite::PendingInvite,
+        migration::AppliedMigration,
+        quest::{QuestUpdate, UserQuest},
+        shop::{InventoryItem, PurchaseOutcome, ShopItem, Wallet},
+        BlitzClock, CoopState, DuelState, Game, GameBoard, GameBoardRevision, GameDbState,
+        GameEvent, GameListFilter, GameListing, GameMode, GameMove, GameMoveFilter, GameNight,
+        GameNightAttendee, GameNightEvent, GameNightStatus, GameNightUpdate, GamePlayer,
+        GamePlayerRecord, GamePlayerSummary, GameSetup, GameState, GameStatus, Grid, GridCell,
+        GuildSettings, GuildStanding, HotSeats, PendingMove, PlayerBoard, PlayerFinalScore, Puzzle,
+        PuzzleRushStanding, PuzzleRushState, RecentPlayer, Season, TimeAttackState,
+        UpcomingGameNight, User, UserGuildProfile, UserProfile, VersusRecord, VersusWord,
+        WordHabits, WordStat, WordStatsSort, WordTraits,
     },
 };
 
 const DEFAULT_TIMER_DURATION: i32 = 30_i32; // seconds
 const DEFAULT_TIMER_DISABLED: bool = false;
 
+// Every public query runs through `timed`, which logs and counts slow ones
+
+fn seal_refresh_token(token: &str, encryption_key: &str) -> Result<Encrypted<String>> {
+    Encrypted::seal(&token.to_string(), encryption_key)
+        .map_err(|e| sqlx::Error::Protocol(format!("Failed to encrypt refresh token: {}", e)))
+}
+
+/// Swap the stored refresh token ciphertext for its plaintext; a token that
+/// can't be decrypted (e.g., after a key change) is dropped, forcing a new sign-in
+fn open_refresh_token(user: &mut User, encryption_key: &str) {
+    user.refresh_token = user.refresh_token.take().and_then(|token| {
+        Encrypted::<String>::from_ciphertext(token)
+            .open(encryption_key)
+            .ok()
+    });
+}
+
 // User queries
 pub async fn get_user(pool: &PgPool, user_id: i64, encryption_key: &str) -> Result<Option<User>> {
-    let mut user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE user_id = $1")
-        .bind(user_id)
-        .fetch_optional(pool)
-        .await?;
+    timed("get_user", async move {
+        let mut user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
 
-    // Decrypt refresh token if present
-    if let Some(ref mut u) = user {
-        if let Some(ref encrypted_token) = u.refresh_token {
-            u.refresh_token = encryption::decrypt(encrypted_token, encryption_key).ok();
+        if let Some(ref mut u) = user {
+            open_refresh_token(u, encryption_key);
         }
-    }
 
-    Ok(user)
+        Ok(user)
+    })
+    .await
+}
+
+/// A user's stored Discord avatar URL; None if the user doesn't exist
+pub async fn get_user_avatar_url(pool: &PgPool, user_id: i64) -> Result<Option<Option<String>>> {
+    timed("get_user_avatar_url", async move {
+        let avatar_url = sqlx::query_scalar("SELECT avatar_url FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(avatar_url)
+    })
+    .await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_or_update_user(
     pool: &PgPool,
     user_id: i64,
@@ -38,47 +84,58 @@ pub async fn create_or_update_user(
     avatar_url: Option<&str>,
     refresh_token: Option<&str>,
     token_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    oauth_scopes: &[String],
     encryption_key: &str,
 ) -> Result<User> {
-    // Encrypt refresh token if present
-    let encrypted_token = if let Some(token) = refresh_token {
-        Some(encryption::encrypt(token, encryption_key).map_err(|e| {
-            sqlx::Error::Protocol(format!("Failed to encrypt refresh token: {}", e))
-        })?)
-    } else {
-        None
-    };
+    timed("create_or_update_user", async move {
+        let encrypted_token = refresh_token
+            .map(|token| seal_refresh_token(token, encryption_key))
+            .transpose()?;
 
-    let mut user = sqlx::query_as::<_, User>(
-        r#"
-        INSERT INTO users (user_id, username, global_name, avatar_url, refresh_token, token_expires_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT (user_id)
-        DO UPDATE SET
-            username = $2,
-            global_name = $3,
-            avatar_url = $4,
-            refresh_token = $5,
-            token_expires_at = $6,
-            updated_at = NOW()
-        RETURNING *
-        "#
-    )
-    .bind(user_id)
-    .bind(username)
-    .bind(global_name)
-    .bind(avatar_url)
-    .bind(encrypted_token.as_deref())
-    .bind(token_expires_at)
-    .fetch_one(pool)
-    .await?;
+        let mut user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (user_id, username, global_name, avatar_url, refresh_token_encrypted, token_expires_at, oauth_scopes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                username = $2,
+                global_name = $3,
+                avatar_url = $4,
+                refresh_token_encrypted = $5,
+                token_expires_at = $6,
+                oauth_scopes = $7,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(username)
+        .bind(global_name)
+        .bind(avatar_url)
+        .bind(encrypted_token)
+        .bind(token_expires_at)
+        .bind(oauth_scopes)
+        .fetch_one(pool)
+        .await?;
 
-    // Decrypt refresh token for the returned user
-    if let Some(ref encrypted) = user.refresh_token {
-        user.refresh_token = encryption::decrypt(encrypted, encryption_key).ok();
-    }
+        open_refresh_token(&mut user, encryption_key);
+
+        Ok(user)
+    })
+    .await
+}
 
-    Ok(user)
+/// Add a guest signed in through `/api/auth/guest`, so their games can be saved
+pub async fn create_guest_user(pool: &PgPool, user_id: i64, username: &str) -> Result<()> {
+    timed("create_guest_user", async move {
+        sqlx::query("INSERT INTO users (user_id, username, is_guest) VALUES ($1, $2, TRUE)")
+            .bind(user_id)
+            .bind(username)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
 }
 
 /// Update a user's refresh token and expiration time
@@ -90,94 +147,174 @@ pub async fn update_user_refresh_token(
     user_id: i64,
     refresh_token: &str,
     token_expires_at: chrono::DateTime<chrono::Utc>,
+    oauth_scopes: &[String],
     encryption_key: &str,
 ) -> Result<()> {
-    // Encrypt the new refresh token
-    let encrypted_token = encryption::encrypt(refresh_token, encryption_key)
-        .map_err(|e| sqlx::Error::Protocol(format!("Failed to encrypt refresh token: {}", e)))?;
+    timed("update_user_refresh_token", async move {
+        let encrypted_token = seal_refresh_token(refresh_token, encryption_key)?;
 
-    sqlx::query(
-        r#"
-        UPDATE users
-        SET refresh_token = $1,
-            token_expires_at = $2,
-            updated_at = NOW()
-        WHERE user_id = $3
-        "#,
-    )
-    .bind(&encrypted_token)
-    .bind(token_expires_at)
-    .bind(user_id)
-    .execute(pool)
-    .await?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET refresh_token_encrypted = $1,
+                token_expires_at = $2,
+                oauth_scopes = $3,
+                updated_at = NOW()
+            WHERE user_id = $4
+            "#,
+        )
+        .bind(&encrypted_token)
+        .bind(token_expires_at)
+        .bind(oauth_scopes)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
 
-    Ok(())
+        Ok(())
+    })
+    .await
 }
 
 /// Clear a user's OAuth tokens from the database
 ///
 /// Used for logout and token revocation operations.
 pub async fn clear_user_tokens(pool: &PgPool, user_id: i64) -> Result<()> {
-    sqlx::query(
-        r#"
-        UPDATE users
-        SET refresh_token = NULL,
-            token_expires_at = NULL,
-            updated_at = NOW()
-        WHERE user_id = $1
-        "#,
-    )
-    .bind(user_id)
-    .execute(pool)
-    .await?;
+    timed("clear_user_tokens", async move {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET refresh_token_encrypted = NULL,
+                token_expires_at = NULL,
+                oauth_scopes = '{}',
+                updated_at = NOW()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
 
-    Ok(())
+        Ok(())
+    })
+    .await
 }
 
 // Game queries
 // TODO: Game logic not yet fully implemented - these will be used when game state management is added
 #[allow(dead_code)]
 pub async fn create_game(pool: &PgPool, game: &Game) -> Result<Game> {
-    sqlx::query_as::<_, Game>(
-        r#"
-        INSERT INTO games (
-            game_id, guild_id, channel_id, game_mode, state,
-            current_round, max_rounds, timer_enabled, timer_duration
+    timed("create_game", async move {
+        sqlx::query_as::<_, Game>(
+            r#"
+            INSERT INTO games (
+                game_id, guild_id, channel_id, game_mode, state,
+                current_round, max_rounds, timer_enabled, timer_duration
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        RETURNING *
-        "#,
-    )
-    .bind(game.game_id)
-    .bind(game.guild_id)
-    .bind(game.channel_id)
-    .bind(&game.game_mode)
-    .bind(&game.state)
-    .bind(game.current_round)
-    .bind(game.max_rounds)
-    .bind(game.timer_enabled)
-    .bind(game.timer_duration)
-    .fetch_one(pool)
+        .bind(game.game_id)
+        .bind(game.guild_id)
+        .bind(game.channel_id)
+        .bind(&game.game_mode)
+        .bind(&game.state)
+        .bind(game.current_round)
+        .bind(game.max_rounds)
+        .bind(game.timer_enabled)
+        .bind(game.timer_duration)
+        .fetch_one(pool)
+        .await
+    })
     .await
 }
 
+#[allow(dead_code)]
 pub async fn get_game(pool: &PgPool, game_id: Uuid) -> Result<Option<Game>> {
-    sqlx::query_as::<_, Game>("SELECT * FROM games WHERE game_id = $1")
-        .bind(game_id)
-        .fetch_optional(pool)
-        .await
+    timed("get_game", async move {
+        sqlx::query_as::<_, Game>("SELECT * FROM games WHERE game_id = $1")
+            .bind(game_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
 }
 
+#[allow(dead_code)]
 pub async fn update_game_state(pool: &PgPool, game_id: Uuid, state: &str) -> Result<()> {
-    sqlx::query("UPDATE games SET state = $1 WHERE game_id = $2")
-        .bind(state)
-        .bind(game_id)
-        .execute(pool)
-        .await?;
-    Ok(())
+    timed("update_game_state", async move {
+        sqlx::query("UPDATE games SET state = $1 WHERE game_id = $2")
+            .bind(state)
+            .bind(game_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Games matching a filter, newest first, with how many players each has
+pub async fn list_games(
+    pool: &PgPool,
+    filter: &GameListFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<GameListing>> {
+    let states: Vec<String> = filter.states.iter().map(|s| s.to_string()).collect();
+    let guild_id = filter.guild_id;
+    let channel_id = filter.channel_id;
+    timed("list_games", async move {
+        sqlx::query_as::<_, GameListing>(
+            r#"
+            SELECT
+                g.game_id, g.guild_id, g.channel_id, g.game_mode, g.state,
+                g.current_round, g.max_rounds, g.current_turn_player, g.created_at, g.started_at,
+                (SELECT COUNT(*) FROM game_players gp WHERE gp.game_id = g.game_id)
+                    AS player_count
+            FROM games g
+            WHERE (cardinality($1::text[]) = 0 OR g.state = ANY($1))
+              AND ($2::bigint IS NULL OR g.guild_id = $2)
+              AND ($3::bigint IS NULL OR g.channel_id = $3)
+            ORDER BY g.created_at DESC, g.game_id
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(states)
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .await
 }
 
 // Game player queries
+
+/// The players of several games, in turn order within each game
+pub async fn get_game_player_summaries(
+    pool: &PgPool,
+    game_ids: &[Uuid],
+) -> Result<Vec<GamePlayerSummary>> {
+    timed("get_game_player_summaries", async move {
+        sqlx::query_as::<_, GamePlayerSummary>(
+            r#"
+            SELECT gp.game_id, gp.user_id, u.username, gp.score
+            FROM game_players gp
+            JOIN users u ON u.user_id = gp.user_id
+            WHERE gp.game_id = ANY($1)
+            ORDER BY gp.game_id, gp.team, gp.joined_at
+            "#,
+        )
+        .bind(game_ids)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+#[allow(dead_code)]
 pub async fn add_player_to_game(
     pool: &PgPool,
     game_id: Uuid,
@@ -185,54 +322,99 @@ pub async fn add_player_to_game(
     team: Option<i32>,
     is_bot: bool,
 ) -> Result<GamePlayerRecord> {
-    sqlx::query_as::<_, GamePlayerRecord>(
-        r#"
-        INSERT INTO game_players (game_id, user_id, team, is_bot)
-        VALUES ($1, $2, $3, $4)
-        RETURNING *
-        "#,
-    )
-    .bind(game_id)
-    .bind(user_id)
-    .bind(team)
-    .bind(is_bot)
-    .fetch_one(pool)
+    timed("add_player_to_game", async move {
+        sqlx::query_as::<_, GamePlayerRecord>(
+            r#"
+            INSERT INTO game_players (game_id, user_id, team, is_bot)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(game_id)
+        .bind(user_id)
+        .bind(team)
+        .bind(is_bot)
+        .fetch_one(pool)
+        .await
+    })
     .await
 }
 
+#[allow(dead_code)]
 pub async fn get_game_players(pool: &PgPool, game_id: Uuid) -> Result<Vec<GamePlayerRecord>> {
-    sqlx::query_as::<_, GamePlayerRecord>(
-        "SELECT * FROM game_players WHERE game_id = $1 ORDER BY joined_at",
-    )
-    .bind(game_id)
-    .fetch_all(pool)
+    timed("get_game_players", async move {
+        sqlx::query_as::<_, GamePlayerRecord>(
+            "SELECT * FROM game_players WHERE game_id = $1 ORDER BY joined_at",
+        )
+        .bind(game_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Each player's final score and best word, highest score first
+pub async fn get_final_scores(pool: &PgPool, game_id: Uuid) -> Result<Vec<PlayerFinalScore>> {
+    timed("get_final_scores", async move {
+        sqlx::query_as::<_, PlayerFinalScore>(
+            r#"
+            SELECT gp.user_id,
+                   u.username,
+                   COALESCE(gp.score, 0) AS score,
+                   COALESCE(gp.forfeited, FALSE) AS forfeited,
+                   best.word AS best_word,
+                   best.score AS best_word_score
+            FROM game_players gp
+            JOIN users u ON u.user_id = gp.user_id
+            LEFT JOIN LATERAL (
+                SELECT m.word, m.score FROM game_moves m
+                WHERE m.game_id = gp.game_id AND m.user_id = gp.user_id
+                ORDER BY m.score DESC, m.id
+                LIMIT 1
+            ) best ON TRUE
+            WHERE gp.game_id = $1
+            ORDER BY score DESC, gp.joined_at
+            "#,
+        )
+        .bind(game_id)
+        .fetch_all(pool)
+        .await
+    })
     .await
 }
 
 // Game board queries
+#[allow(dead_code)]
 pub async fn create_game_board(
     pool: &PgPool,
     game_id: Uuid,
     grid: serde_json::Value,
 ) -> Result<GameBoard> {
-    sqlx::query_as::<_, GameBoard>(
-        r#"
-        INSERT INTO game_boards (game_id, grid)
-        VALUES ($1, $2)
-        RETURNING *
-        "#,
-    )
-    .bind(game_id)
-    .bind(grid)
-    .fetch_one(pool)
+    timed("create_game_board", async move {
+        sqlx::query_as::<_, GameBoard>(
+            r#"
+            INSERT INTO game_boards (game_id, grid)
+            VALUES ($1, $2)
+            RETURNING *
+            "#,
+        )
+        .bind(game_id)
+        .bind(grid)
+        .fetch_one(pool)
+        .await
+    })
     .await
 }
 
+#[allow(dead_code)]
 pub async fn get_game_board(pool: &PgPool, game_id: Uuid) -> Result<Option<GameBoard>> {
-    sqlx::query_as::<_, GameBoard>("SELECT * FROM game_boards WHERE game_id = $1")
-        .bind(game_id)
-        .fetch_optional(pool)
-        .await
+    timed("get_game_board", async move {
+        sqlx::query_as::<_, GameBoard>("SELECT * FROM game_boards WHERE game_id = $1")
+            .bind(game_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
 }
 
 // =============================================================================
@@ -244,46 +426,84 @@ pub async fn get_game_board(pool: &PgPool, game_id: Uuid) -> Result<Option<GameB
 /// # Arguments
 /// * `pool` - Database connection pool
 /// * `lobby_id` - Lobby identifier (e.g., "channel:123456" or "custom:ABC123")
+/// * `guild_id` - Discord guild of a channel lobby, used for guild leaderboards
 /// * `created_by` - Discord user ID of the player who started the game
 /// * `total_rounds` - Number of rounds for the game (typically 5)
+/// * `setup` - Game mode and its mode-specific settings
 ///
 /// # Returns
 /// The UUID of the newly created game session
 pub async fn create_game_session(
     pool: &PgPool,
     lobby_id: &str,
+    guild_id: Option<i64>,
     created_by: i64,
     total_rounds: u8,
+    setup: &GameSetup,
 ) -> Result<Uuid> {
-    let game_id = Uuid::new_v4();
+    timed("create_game_session", async move {
+        let game_id = Uuid::new_v4();
+        let rules = serde_json::to_value(setup.rules)
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize rules: {}", e)))?;
+        let hot_seats = setup
+            .hot_seats
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize hot seats: {}", e)))?;
+        let puzzle_rush = setup
+            .puzzle_rush
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
+                sqlx::Error::Protocol(format!("Failed to serialize puzzle rush: {}", e))
+            })?;
+
+        // Parse lobby_id to extract channel_id (the lobby_id itself never carries the guild)
+        let (channel_id, parsed_guild_id) = parse_lobby_id(lobby_id)?;
+        let guild_id = guild_id.or(parsed_guild_id);
 
-    // Parse lobby_id to extract channel_id and guild_id
-    let (channel_id, guild_id) = parse_lobby_id(lobby_id)?;
-
-    sqlx::query(
-        r#"
-        INSERT INTO games (
-            game_id, guild_id, channel_id, game_mode, state,
-            current_round, max_rounds, current_turn_player,
-            timer_enabled, timer_duration
+        sqlx::query(
+            r#"
+            INSERT INTO games (
+                game_id, guild_id, channel_id, game_mode, state,
+                current_round, max_rounds, current_turn_player,
+                timer_enabled, timer_duration, coop_target_score, blitz_clock_secs, rules,
+                duel_seed, duel_ends_at, time_attack_ends_at, covered_tiles, hot_seats,
+                puzzle_rush
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                $19
+            )
+            "#,
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-        "#,
-    )
-    .bind(game_id)
-    .bind(guild_id)
-    .bind(channel_id)
-    .bind(GameMode::Multiplayer) // Default game mode
-    .bind(GameDbState::Waiting.to_string()) // Initial state
-    .bind(1_i32) // Start at round 1
-    .bind(total_rounds as i32)
-    .bind(created_by) // Creator is first turn player
-    .bind(DEFAULT_TIMER_DISABLED) // Timer disabled by default
-    .bind(DEFAULT_TIMER_DURATION) // Default timer duration
-    .execute(pool)
-    .await?;
+        .bind(game_id)
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(&setup.mode)
+        .bind(GameDbState::Waiting.to_string()) // Initial state
+        .bind(1_i32) // Start at round 1
+        .bind(total_rounds as i32)
+        .bind(created_by) // Creator is first turn player
+        .bind(DEFAULT_TIMER_DISABLED) // Timer disabled by default
+        .bind(DEFAULT_TIMER_DURATION) // Default timer duration
+        .bind(setup.coop_target_score)
+        .bind(setup.blitz_clock_secs)
+        .bind(rules)
+        .bind(setup.duel_seed)
+        .bind(setup.duel_ends_at)
+        .bind(setup.time_attack_ends_at)
+        .bind(setup.time_attack_ends_at.map(|_| 0_i32))
+        .bind(hot_seats)
+        .bind(puzzle_rush)
+        .execute(pool)
+        .await?;
 
-    Ok(game_id)
+        Ok(game_id)
+    })
+    .await
 }
 
 /// Parse a lobby_id string to extract channel_id and optional guild_id
@@ -336,7 +556,8 @@ fn encode_lobby_code_to_i64(code: &str) -> i64 {
     // Use a simple encoding: treat the code as base-36 and negate it
     // This ensures custom lobbies have negative channel_ids (distinguishable from Discord IDs)
     let mut value: i64 = 0;
-    for c in code.chars().take(6) {
+    // Up to 12 base-36 digits fit in an i64, which covers both short and long codes
+    for c in code.chars().take(12) {
         value = value * 36
             + match c {
                 '0'..='9' => (c as i64) - ('0' as i64),
@@ -363,29 +584,32 @@ pub async fn add_game_players_batch(
     game_id: Uuid,
     players: &[(i64, u8)],
 ) -> Result<()> {
-    // Use a transaction to ensure all players are added atomically
-    let mut tx = pool.begin().await?;
-
-    for (user_id, turn_order) in players {
-        sqlx::query(
-            r#"
-            INSERT INTO game_players (game_id, user_id, team, score, is_bot)
-            VALUES ($1, $2, $3, $4, $5)
-            ON CONFLICT (game_id, user_id) DO UPDATE SET
-                team = EXCLUDED.team
-            "#,
-        )
-        .bind(game_id)
-        .bind(*user_id)
-        .bind(*turn_order as i32) // TODO: Add dedicated turn_order column instead of reusing team
-        .bind(0_i32) // Initial score
-        .bind(false) // Not a bot
-        .execute(&mut *tx)
-        .await?;
-    }
+    timed("add_game_players_batch", async move {
+        // Use a transaction to ensure all players are added atomically
+        let mut tx = pool.begin().await?;
+
+        for (user_id, turn_order) in players {
+            sqlx::query(
+                r#"
+                INSERT INTO game_players (game_id, user_id, team, score, is_bot)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (game_id, user_id) DO UPDATE SET
+                    team = EXCLUDED.team
+                "#,
+            )
+            .bind(game_id)
+            .bind(*user_id)
+            .bind(*turn_order as i32) // TODO: Add dedicated turn_order column instead of reusing team
+            .bind(0_i32) // Initial score
+            .bind(false) // Not a bot
+            .execute(&mut *tx)
+            .await?;
+        }
 
-    tx.commit().await?;
-    Ok(())
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
 }
 
 /// Create or update a game board with the grid data
@@ -394,6 +618,7 @@ pub async fn add_game_players_batch(
 /// * `pool` - Database connection pool
 /// * `game_id` - The game this board belongs to
 /// * `grid_json` - The grid data as JSON
+/// * `letter_bag` - What's left in the letter bag as JSON, for letter bag games
 ///
 /// # Returns
 /// Result indicating success or failure
@@ -401,54 +626,135 @@ pub async fn create_or_update_game_board(
     pool: &PgPool,
     game_id: Uuid,
     grid_json: serde_json::Value,
+    letter_bag: Option<serde_json::Value>,
 ) -> Result<()> {
-    sqlx::query(
-        r#"
-        INSERT INTO game_boards (game_id, grid, used_words, round_number)
-        VALUES ($1, $2, '[]'::jsonb, 1)
-        ON CONFLICT (game_id) DO UPDATE SET
-            grid = $2,
-            updated_at = NOW()
-        "#,
-    )
-    .bind(game_id)
-    .bind(grid_json)
-    .execute(pool)
-    .await?;
+    timed("create_or_update_game_board", async move {
+        sqlx::query(
+            r#"
+            INSERT INTO game_boards (game_id, grid, used_words, round_number, letter_bag)
+            VALUES ($1, $2, '[]'::jsonb, 1, $3)
+            ON CONFLICT (game_id) DO UPDATE SET
+                grid = $2,
+                letter_bag = $3,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(game_id)
+        .bind(grid_json)
+        .bind(letter_bag)
+        .execute(pool)
+        .await?;
 
-    Ok(())
+        Ok(())
+    })
+    .await
+}
+
+/// Record the board as of a move (0 for the starting board)
+///
+/// Revisions are immutable, so retrying a flush never overwrites one.
+pub async fn insert_board_revision<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    game_id: Uuid,
+    move_number: i32,
+    grid: &serde_json::Value,
+) -> Result<()> {
+    timed("insert_board_revision", async move {
+        sqlx::query(
+            r#"
+            INSERT INTO game_board_history (game_id, move_number, grid)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (game_id, move_number) DO NOTHING
+            "#,
+        )
+        .bind(game_id)
+        .bind(move_number)
+        .bind(grid)
+        .execute(executor)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Every recorded board revision for a game, in move order
+pub async fn get_board_history(pool: &PgPool, game_id: Uuid) -> Result<Vec<GameBoardRevision>> {
+    timed("get_board_history", async move {
+        sqlx::query_as::<_, GameBoardRevision>(
+            "SELECT * FROM game_board_history WHERE game_id = $1 ORDER BY move_number",
+        )
+        .bind(game_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
 }
 
 /// Get the active game for a lobby and construct a GameState
 ///
+/// Used to rehydrate the in-memory game session cache (e.g., after a server restart).
+///
 /// # Arguments
 /// * `pool` - Database connection pool
 /// * `lobby_id` - Lobby identifier (e.g., "channel:123456" or "custom:ABC123")
 ///
 /// # Returns
-/// The active GameState if one exists, None otherwise
-pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<Option<GameState>> {
-    // Parse lobby_id to get channel_id
-    let (channel_id, _guild_id) = parse_lobby_id(lobby_id)?;
+/// The active GameState if one exists, along with the Discord user IDs of its players
+/// (index-aligned with `GameState::players`), None otherwise
+pub async fn get_active_game_for_lobby(
+    pool: &PgPool,
+    lobby_id: &str,
+) -> Result<Option<(GameState, Vec<i64>)>> {
+    timed("get_active_game_for_lobby", async move {
+        // Parse lobby_id to get channel_id
+        let (channel_id, _guild_id) = parse_lobby_id(lobby_id)?;
 
-    // Get the active game for this channel
-    let game = sqlx::query_as::<_, Game>(
-        r#"
-        SELECT * FROM games
-        WHERE channel_id = $1 AND state IN ('waiting', 'active')
-        ORDER BY created_at DESC
-        LIMIT 1
-        "#,
-    )
-    .bind(channel_id)
-    .fetch_optional(pool)
-    .await?;
+        // Get the active game for this channel
+        let game = sqlx::query_as::<_, Game>(
+            r#"
+            SELECT * FROM games
+            WHERE channel_id = $1 AND state IN ('waiting', 'active')
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await?;
 
-    let game = match game {
-        Some(g) => g,
-        None => return Ok(None),
-    };
+        match game {
+            Some(game) => load_game_state(pool, game).await.map(Some),
+            None => Ok(None),
+        }
+    })
+    .await
+}
+
+/// An active game by ID, constructed like `get_active_game_for_lobby`
+///
+/// Used to rebuild one specific game's session when the cached copy has drifted.
+pub async fn get_active_game(
+    pool: &PgPool,
+    game_id: Uuid,
+) -> Result<Option<(GameState, Vec<i64>)>> {
+    timed("get_active_game", async move {
+        let game = sqlx::query_as::<_, Game>(
+            "SELECT * FROM games WHERE game_id = $1 AND state IN ('waiting', 'active')",
+        )
+        .bind(game_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match game {
+            Some(game) => load_game_state(pool, game).await.map(Some),
+            None => Ok(None),
+        }
+    })
+    .await
+}
 
+/// Build the in-memory state of a stored game from its board, players and history
+async fn load_game_state(pool: &PgPool, game: Game) -> Result<(GameState, Vec<i64>)> {
     // Get the game board
     let board = sqlx::query_as::<_, GameBoard>("SELECT * FROM game_boards WHERE game_id = $1")
         .bind(game.game_id)
@@ -465,6 +771,9 @@ pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<
 
     // Get user info for each player
     let mut players = Vec::with_capacity(player_records.len());
+    let mut player_ids = Vec::with_capacity(player_records.len());
+    let mut clock_remaining_ms = std::collections::HashMap::new();
+    let mut duel_boards = std::collections::HashMap::new();
     for (idx, record) in player_records.iter().enumerate() {
         let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE user_id = $1")
             .bind(record.user_id)
@@ -472,14 +781,53 @@ pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<
             .await?;
 
         if let Some(u) = user {
+            let player_uuid = Uuid::new_v4(); // Generate a UUID for in-memory tracking
+            if let Some(secs) = game.blitz_clock_secs {
+                let remaining = record.clock_remaining_ms.unwrap_or(secs * 1000);
+                clock_remaining_ms.insert(player_uuid, remaining.max(0) as u64);
+            }
+            if let Some(board) = record.board.clone() {
+                let board: PlayerBoard = serde_json::from_value(board)
+                    .map_err(|e| sqlx::Error::Protocol(format!("Invalid duel board: {}", e)))?;
+                duel_boards.insert(player_uuid, board);
+            }
             players.push(GamePlayer {
-                user_id: Uuid::new_v4(), // Generate a UUID for in-memory tracking
+                user_id: player_uuid,
                 username: u.username,
-                avatar_url: u.avatar_url,
+                avatar_url: avatars::proxy_url(record.user_id, u.avatar_url.as_deref()),
                 score: record.score,
                 turn_order: record.team.unwrap_or(idx as i32) as u8,
                 is_connected: true, // Assume connected; WebSocket handler will update
+                forfeited: record.forfeited,
             });
+            player_ids.push(record.user_id);
+        }
+    }
+
+    // A hot-seat game's one player row stands in for all of its local players
+    let hot_seats: Option<HotSeats> = game
+        .hot_seats
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| sqlx::Error::Protocol(format!("Invalid hot seats: {}", e)))?;
+    if let Some(hot_seats) = &hot_seats {
+        let owner = players.first().cloned().zip(player_ids.first().copied());
+        if let Some((owner, owner_id)) = owner {
+            players = hot_seats
+                .seats
+                .iter()
+                .enumerate()
+                .map(|(idx, seat)| GamePlayer {
+                    user_id: Uuid::new_v4(),
+                    username: seat.name.clone(),
+                    score: seat.score,
+                    turn_order: idx as u8,
+                    forfeited: seat.forfeited,
+                    ..owner.clone()
+                })
+                .collect();
+            player_ids = vec![owner_id; players.len()];
         }
     }
 
@@ -497,20 +845,47 @@ pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<
         Vec::new()
     };
 
-    // Parse used words from JSON
+    // Games from before lobbies had rules play by the standard ones
+    let rules: GameRules = game
+        .rules
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| sqlx::Error::Protocol(format!("Invalid rules data: {}", e)))?
+        .unwrap_or_default();
+
+    // Only letter bag games store a bag
+    let letter_bag: Option<LetterBag> = board
+        .as_ref()
+        .and_then(|b| b.letter_bag.clone())
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| sqlx::Error::Protocol(format!("Invalid letter bag data: {}", e)))?;
+
+    // Parse used words from JSON (normalized to the lowercase form GameState tracks)
     let used_words: std::collections::HashSet<String> = if let Some(ref b) = board {
-        serde_json::from_value(b.used_words.clone()).map_err(|e| {
-            tracing::error!(
-                "Failed to deserialize used_words for game {}: {}",
-                game.game_id,
-                e
-            );
-            sqlx::Error::Protocol(format!("Invalid used_words data: {}", e))
-        })?
+        serde_json::from_value::<Vec<String>>(b.used_words.clone())
+            .map(|words| words.iter().map(|w| w.to_lowercase()).collect())
+            .map_err(|e| {
+                tracing::error!(
+                    "Failed to deserialize used_words for game {}: {}",
+                    game.game_id,
+                    e
+                );
+                sqlx::Error::Protocol(format!("Invalid used_words data: {}", e))
+            })?
     } else {
         std::collections::HashSet::new()
     };
 
+    // Continue numbering moves after the last recorded board revision
+    let move_count: i32 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(move_number), 0) FROM game_board_history WHERE game_id = $1",
+    )
+    .bind(game.game_id)
+    .fetch_one(pool)
+    .await?;
+
     // Convert game state to GameStatus
     let status = match game.state {
         GameDbState::Waiting => GameStatus::WaitingToStart,
@@ -523,16 +898,50 @@ pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<
     let round_submissions = players.iter().map(|p| (p.user_id, false)).collect();
 
     // Determine current player index
-    let current_player_index = if let Some(turn_player) = game.current_turn_player {
-        player_records
+    let current_player_index = if let Some(hot_seats) = &hot_seats {
+        hot_seats.current_seat.min(players.len().saturating_sub(1))
+    } else if let Some(turn_player) = game.current_turn_player {
+        player_ids
             .iter()
-            .position(|p| p.user_id == turn_player)
+            .position(|id| *id == turn_player)
             .unwrap_or(0)
     } else {
         0
     };
 
-    Ok(Some(GameState {
+    // Duel boards resume from the last flush; a player without one hadn't played yet
+    let duel = game
+        .duel_seed
+        .zip(game.duel_ends_at)
+        .map(|(seed, ends_at)| DuelState {
+            seed: seed as u64,
+            ends_at,
+            boards: players
+                .iter()
+                .map(|p| {
+                    let board = duel_boards
+                        .remove(&p.user_id)
+                        .unwrap_or_else(|| PlayerBoard::new(grid.clone()));
+                    (p.user_id, board)
+                })
+                .collect(),
+        });
+
+    // A puzzle rush resumes on the board and clock of the last flush
+    let puzzle_rush: Option<PuzzleRushState> = game
+        .puzzle_rush
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| sqlx::Error::Protocol(format!("Invalid puzzle rush: {}", e)))?;
+
+    // Blitz clocks resume from the last flush; the current turn's clock restarts now
+    let clock = game.blitz_clock_secs.map(|_| BlitzClock {
+        remaining_ms: clock_remaining_ms,
+        turn_started_at: Some(std::time::Instant::now()),
+    });
+
+    let game_state = GameState {
         game_id: game.game_id,
         grid,
         players,
@@ -543,169 +952,2469 @@ pub async fn get_active_game_for_lobby(pool: &PgPool, lobby_id: &str) -> Result<
         round_submissions,
         status,
         created_at: game.created_at,
-    }))
-}
+        mode: game.game_mode,
+        coop: game.coop_target_score.map(|target_score| CoopState {
+            target_score,
+            gems: game.coop_gems.max(0) as u32,
+        }),
+        clock,
+        // Move timing restarts for the current turn, like the blitz clock
+        turn_started_at: Some(std::time::Instant::now()),
+        move_count: move_count.max(0) as u32,
+        consecutive_passes: std::collections::HashMap::new(),
+        // Pass counts aren't persisted, so tie-breaks after a restart only see new passes
+        passes: std::collections::HashMap::new(),
+        end_reason: None,
+        letter_bag,
+        rules,
+        turns_taken: 0,
+        duel,
+        time_attack: game.time_attack_ends_at.map(|ends_at| TimeAttackState {
+            covered: game.covered_tiles.unwrap_or(0) as u32,
+            ends_at,
+        }),
+        puzzle_rush,
+    };
+
+    Ok((game_state, player_ids))
+}
+
+/// Update game state in the database
+pub async fn update_game_db_state(pool: &PgPool, game_id: Uuid, state: GameDbState) -> Result<()> {
+    timed("update_game_db_state", async move {
+        let state_str = match state {
+            GameDbState::Waiting => "waiting",
+            GameDbState::Active => "active",
+            GameDbState::Finished => "finished",
+            GameDbState::Cancelled => "cancelled",
+        };
+
+        sqlx::query("UPDATE games SET state = $1, started_at = NOW() WHERE game_id = $2")
+            .bind(state_str)
+            .bind(game_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Update game round and current turn player
+#[allow(dead_code)]
+pub async fn update_game_round(
+    pool: &PgPool,
+    game_id: Uuid,
+    round: i32,
+    current_player_id: i64,
+) -> Result<()> {
+    timed("update_game_round", async move {
+        sqlx::query(
+            r#"
+            UPDATE games
+            SET current_round = $1, current_turn_player = $2
+            WHERE game_id = $3
+            "#,
+        )
+        .bind(round)
+        .bind(current_player_id)
+        .bind(game_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Write a cached game session back to the database in a single transaction
+///
+/// Persists the round/turn position, every player's total score, the used word list,
+/// the board and letter bag (which change in letter bag games), and any moves played
+/// since the last flush. Called by the write-behind cache on
+/// round boundaries, at game end, and by the periodic flush task.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `game_state` - Snapshot of the in-memory game state
+/// * `player_ids` - Discord user IDs, index-aligned with `game_state.players`
+/// * `moves` - Moves applied in memory that haven't been recorded yet
+pub async fn flush_game_state(
+    pool: &PgPool,
+    game_state: &GameState,
+    player_ids: &[i64],
+    moves: &[PendingMove],
+    first_event_seq: i64,
+    events: &[GameEvent],
+) -> Result<()> {
+    timed("flush_game_state", async move {
+        let hot_seats = game_state
+            .hot_seats()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize hot seats: {}", e)))?;
+        let puzzle_rush = game_state
+            .puzzle_rush
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
+                sqlx::Error::Protocol(format!("Failed to serialize puzzle rush: {}", e))
+            })?;
+        // Local players' scores go into `hot_seats`; their owner's row has none
+        let player_rows = match hot_seats {
+            Some(_) => 0,
+            None => game_state.players.len(),
+        };
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE games
+            SET current_round = $1, current_turn_player = $2, coop_gems = $3,
+                covered_tiles = $4, hot_seats = COALESCE($5, hot_seats),
+                puzzle_rush = COALESCE($6, puzzle_rush)
+            WHERE game_id = $7
+            "#,
+        )
+        .bind(game_state.current_round as i32)
+        .bind(player_ids.get(game_state.current_player_index).copied())
+        .bind(game_state.coop.as_ref().map_or(0, |coop| coop.gems as i32))
+        .bind(game_state.time_attack.as_ref().map(|t| t.covered as i32))
+        .bind(hot_seats)
+        .bind(puzzle_rush)
+        .bind(game_state.game_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for (player, user_id) in game_state.players.iter().zip(player_ids).take(player_rows) {
+            let clock_remaining_ms = game_state
+                .clock
+                .as_ref()
+                .and_then(|clock| clock.remaining_ms.get(&player.user_id))
+                .map(|ms| (*ms).min(i32::MAX as u64) as i32);
+            let board = game_state
+                .player_board(player.user_id)
+                .map(serde_json::to_value)
+                .transpose()
+                .map_err(|e| {
+                    sqlx::Error::Protocol(format!("Failed to serialize duel board: {}", e))
+                })?;
+            sqlx::query(
+                r#"
+                UPDATE game_players SET score = $1, clock_remaining_ms = $2, board = $3
+                WHERE game_id = $4 AND user_id = $5
+                "#,
+            )
+            .bind(player.score)
+            .bind(clock_remaining_ms)
+            .bind(board)
+            .bind(game_state.game_id)
+            .bind(*user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let used_words: Vec<&String> = game_state.used_words.iter().collect();
+        let words_json = serde_json::to_value(used_words)
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize used words: {}", e)))?;
+        let grid_json = serde_json::to_value(&game_state.grid)
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize grid: {}", e)))?;
+        let letter_bag_json = game_state
+            .letter_bag
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize letter bag: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            UPDATE game_boards
+            SET used_words = $1, round_number = $2, grid = $3, letter_bag = $4, updated_at = NOW()
+            WHERE game_id = $5
+            "#,
+        )
+        .bind(words_json)
+        .bind(game_state.current_round as i32)
+        .bind(grid_json)
+        .bind(letter_bag_json)
+        .bind(game_state.game_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for game_move in moves {
+            let positions = serde_json::to_value(&game_move.positions).map_err(|e| {
+                sqlx::Error::Protocol(format!("Failed to serialize positions: {}", e))
+            })?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO game_moves
+                    (game_id, user_id, round_number, word, score, positions, move_time_ms)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(game_state.game_id)
+            .bind(game_move.user_id)
+            .bind(game_move.round_number)
+            .bind(&game_move.word)
+            .bind(game_move.score)
+            .bind(positions)
+            .bind(game_move.move_time_ms)
+            .execute(&mut *tx)
+            .await?;
+
+            // Guests' words are kept with the game but stay out of every stat
+            if !auth::is_guest_id(game_move.user_id) {
+                record_word_play(&mut tx, game_move).await?;
+                // Hot-seat moves are all saved under the owner, whoever made them
+                if game_state.mode != GameMode::HotSeat {
+                    record_word_habits(&mut tx, game_move).await?;
+                }
+            }
+
+            let board = serde_json::to_value(&game_move.board)
+                .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize board: {}", e)))?;
+            insert_board_revision(&mut *tx, game_state.game_id, game_move.move_number, &board)
+                .await?;
+        }
+
+        // Events after a taken-back word reuse its sequence numbers
+        for (seq, event) in (first_event_seq..).zip(events) {
+            let event = serde_json::to_value(event)
+                .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize event: {}", e)))?;
+            sqlx::query(
+                r#"
+                INSERT INTO game_events (game_id, seq, event)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (game_id, seq) DO UPDATE SET event = EXCLUDED.event
+                "#,
+            )
+            .bind(game_state.game_id)
+            .bind(seq)
+            .bind(event)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Delete a game with its players, boards, moves and events (the foreign keys
+/// cascade); returns whether there was one
+pub async fn delete_game(pool: &PgPool, game_id: Uuid) -> Result<bool> {
+    timed("delete_game", async move {
+        let result = sqlx::query("DELETE FROM games WHERE game_id = $1")
+            .bind(game_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    })
+    .await
+}
+
+/// Delete a game's stored events from `seq` on, when the word they started
+/// with was taken back
+pub async fn delete_game_events_from(pool: &PgPool, game_id: Uuid, seq: i64) -> Result<()> {
+    timed("delete_game_events_from", async move {
+        sqlx::query("DELETE FROM game_events WHERE game_id = $1 AND seq >= $2")
+            .bind(game_id)
+            .bind(seq)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Where a game's event log continues: one past its last stored event
+pub async fn next_game_event_seq(pool: &PgPool, game_id: Uuid) -> Result<i64> {
+    timed("next_game_event_seq", async move {
+        sqlx::query_scalar("SELECT COALESCE(MAX(seq) + 1, 0) FROM game_events WHERE game_id = $1")
+            .bind(game_id)
+            .fetch_one(pool)
+            .await
+    })
+    .await
+}
+
+/// A game's stored event log, oldest first
+pub async fn get_game_events(pool: &PgPool, game_id: Uuid) -> Result<Vec<GameEvent>> {
+    timed("get_game_events", async move {
+        let rows: Vec<serde_json::Value> =
+            sqlx::query_scalar("SELECT event FROM game_events WHERE game_id = $1 ORDER BY seq")
+                .bind(game_id)
+                .fetch_all(pool)
+                .await?;
+        rows.into_iter()
+            .map(|event| {
+                serde_json::from_value(event)
+                    .map_err(|e| sqlx::Error::Protocol(format!("Invalid game event: {}", e)))
+            })
+            .collect()
+    })
+    .await
+}
+
+/// Delete a flushed move that its player took back: its `game_moves` row, the
+/// board revision it recorded, and its play in the word stats
+///
+/// `distinct_players` is left alone, since a first play can't be told apart from
+/// a repeat once it's counted; so is the player's `longest_word`, since the one
+/// before it isn't kept.
+pub async fn delete_undone_move(
+    pool: &PgPool,
+    game_id: Uuid,
+    game_move: &PendingMove,
+) -> Result<()> {
+    timed("delete_undone_move", async move {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM game_moves
+            WHERE id = (
+                SELECT id FROM game_moves
+                WHERE game_id = $1 AND user_id = $2 AND word = $3
+                ORDER BY id DESC
+                LIMIT 1
+            )
+            "#,
+        )
+        .bind(game_id)
+        .bind(game_move.user_id)
+        .bind(&game_move.word)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM game_board_history WHERE game_id = $1 AND move_number = $2")
+            .bind(game_id)
+            .bind(game_move.move_number)
+            .execute(&mut *tx)
+            .await?;
+
+        // Guests' words were never counted
+        if auth::is_guest_id(game_move.user_id) {
+            tx.commit().await?;
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE word_stats
+            SET plays = plays - 1, total_score = total_score - $2
+            WHERE word = $1 AND plays > 0
+            "#,
+        )
+        .bind(&game_move.word)
+        .bind(game_move.score as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        let traits = WordTraits::of(&game_move.word);
+        sqlx::query(
+            r#"
+            UPDATE user_word_stats
+            SET words_played = words_played - 1,
+                total_letters = total_letters - $2,
+                q_words = q_words - $3,
+                z_words = z_words - $4
+            WHERE user_id = $1 AND words_played > 0
+                AND NOT EXISTS (
+                    SELECT 1 FROM games WHERE game_id = $5 AND game_mode = 'hotseat'
+                )
+            "#,
+        )
+        .bind(game_move.user_id)
+        .bind(traits.letters)
+        .bind(traits.has_q as i64)
+        .bind(traits.has_z as i64)
+        .bind(game_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(letter) = &traits.starting_letter {
+            sqlx::query(
+                r#"
+                UPDATE user_starting_letters SET plays = plays - 1
+                WHERE user_id = $1 AND letter = $2 AND plays > 0
+                    AND NOT EXISTS (
+                        SELECT 1 FROM games WHERE game_id = $3 AND game_mode = 'hotseat'
+                    )
+                "#,
+            )
+            .bind(game_move.user_id)
+            .bind(letter)
+            .bind(game_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Mark a game as finished with final results
+pub async fn finish_game(pool: &PgPool, game_id: Uuid, result: &GameResult) -> Result<()> {
+    timed("finish_game", async move {
+        let result_json = serde_json::to_value(result).unwrap_or_default();
+        let mut tx = pool.begin().await?;
+        let finished: Option<GameMode> = sqlx::query_scalar(
+            r#"
+            UPDATE games
+            SET state = 'finished', finished_at = NOW(), current_turn_player = $1, result = $2
+            WHERE game_id = $3 AND state <> 'finished'
+            RETURNING game_mode
+            "#,
+        )
+        .bind(result.winner())
+        .bind(result_json)
+        .bind(game_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // Only count a game towards player stats the first time it finishes. Hot-seat
+        // games are one Discord user playing everyone, so they don't count at all.
+        if finished.is_some_and(|mode| mode != GameMode::HotSeat) {
+            record_player_stats(&mut tx, game_id, result.winner()).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Add a finished game to its players' lifetime stats and to the current season,
+/// overall and in the game's guild; guests are left out
+async fn record_player_stats(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    game_id: Uuid,
+    winner: Option<i64>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE users u
+        SET
+            total_games = COALESCE(u.total_games, 0) + 1,
+            total_wins = COALESCE(u.total_wins, 0) + CASE WHEN gp.user_id = $2 THEN 1 ELSE 0 END,
+            total_score = COALESCE(u.total_score, 0) + COALESCE(gp.score, 0),
+            highest_word_score = GREATEST(COALESCE(u.highest_word_score, 0), best.score),
+            highest_word = CASE
+                WHEN best.score > COALESCE(u.highest_word_score, 0) THEN best.word
+                ELSE u.highest_word
+            END
+        FROM game_players gp
+        LEFT JOIN LATERAL (
+            SELECT m.word, m.score FROM game_moves m
+            WHERE m.game_id = gp.game_id AND m.user_id = gp.user_id
+            ORDER BY m.score DESC
+            LIMIT 1
+        ) best ON TRUE
+        WHERE gp.game_id = $1 AND u.user_id = gp.user_id AND NOT u.is_guest
+        "#,
+    )
+    .bind(game_id)
+    .bind(winner)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO season_stats (season_id, user_id, games_played, wins, total_score, highest_word_score)
+        SELECT
+            s.season_id,
+            gp.user_id,
+            1,
+            CASE WHEN gp.user_id = $2 THEN 1 ELSE 0 END,
+            COALESCE(gp.score, 0),
+            COALESCE(
+                (SELECT MAX(m.score) FROM game_moves m
+                 WHERE m.game_id = gp.game_id AND m.user_id = gp.user_id),
+                0
+            )
+        FROM game_players gp
+        JOIN users u ON u.user_id = gp.user_id AND NOT u.is_guest
+        JOIN seasons s ON s.starts_at <= NOW() AND s.ends_at > NOW()
+        WHERE gp.game_id = $1
+        ON CONFLICT (season_id, user_id)
+        DO UPDATE SET
+            games_played = season_stats.games_played + 1,
+            wins = season_stats.wins + EXCLUDED.wins,
+            total_score = season_stats.total_score + EXCLUDED.total_score,
+            highest_word_score = GREATEST(season_stats.highest_word_score, EXCLUDED.highest_word_score)
+        "#,
+    )
+    .bind(game_id)
+    .bind(winner)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO guild_stats (guild_id, user_id, games_played, wins, total_score)
+        SELECT
+            g.guild_id,
+            gp.user_id,
+            1,
+            CASE WHEN gp.user_id = $2 THEN 1 ELSE 0 END,
+            COALESCE(gp.score, 0)
+        FROM game_players gp
+        JOIN users u ON u.user_id = gp.user_id AND NOT u.is_guest
+        JOIN games g ON g.game_id = gp.game_id AND g.guild_id IS NOT NULL
+        WHERE gp.game_id = $1
+        ON CONFLICT (guild_id, user_id)
+        DO UPDATE SET
+            games_played = guild_stats.games_played + 1,
+            wins = guild_stats.wins + EXCLUDED.wins,
+            total_score = guild_stats.total_score + EXCLUDED.total_score
+        "#,
+    )
+    .bind(game_id)
+    .bind(winner)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO guild_season_stats (guild_id, season_id, user_id, games_played, wins, total_score)
+        SELECT
+            g.guild_id,
+            s.season_id,
+            gp.user_id,
+            1,
+            CASE WHEN gp.user_id = $2 THEN 1 ELSE 0 END,
+            COALESCE(gp.score, 0)
+        FROM game_players gp
+        JOIN users u ON u.user_id = gp.user_id AND NOT u.is_guest
+        JOIN games g ON g.game_id = gp.game_id AND g.guild_id IS NOT NULL
+        JOIN seasons s ON s.starts_at <= NOW() AND s.ends_at > NOW()
+        WHERE gp.game_id = $1
+        ON CONFLICT (guild_id, season_id, user_id)
+        DO UPDATE SET
+            games_played = guild_season_stats.games_played + 1,
+            wins = guild_season_stats.wins + EXCLUDED.wins,
+            total_score = guild_season_stats.total_score + EXCLUDED.total_score
+        "#,
+    )
+    .bind(game_id)
+    .bind(winner)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Record that a player resigned, so they stay out of the rotation after a restart
+pub async fn mark_player_forfeited(pool: &PgPool, game_id: Uuid, user_id: i64) -> Result<()> {
+    timed("mark_player_forfeited", async move {
+        sqlx::query("UPDATE game_players SET forfeited = TRUE WHERE game_id = $1 AND user_id = $2")
+            .bind(game_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+// Game move queries
+#[allow(dead_code)]
+pub async fn create_game_move(
+    pool: &PgPool,
+    game_id: Uuid,
+    user_id: i64,
+    round_number: i32,
+    word: &str,
+    score: i32,
+    positions: serde_json::Value,
+) -> Result<GameMove> {
+    timed("create_game_move", async move {
+        sqlx::query_as::<_, GameMove>(
+            r#"
+            INSERT INTO game_moves (game_id, user_id, round_number, word, score, positions)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(game_id)
+        .bind(user_id)
+        .bind(round_number)
+        .bind(word)
+        .bind(score)
+        .bind(positions)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+pub async fn get_game_moves(pool: &PgPool, game_id: Uuid) -> Result<Vec<GameMove>> {
+    timed("get_game_moves", async move {
+        sqlx::query_as::<_, GameMove>(
+            "SELECT * FROM game_moves WHERE game_id = $1 ORDER BY timestamp, id",
+        )
+        .bind(game_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// One page of a game's moves in play order, narrowed by player and round; the
+/// `(game_id, ...)` indexes on `game_moves` serve each combination
+pub async fn get_game_moves_page(
+    pool: &PgPool,
+    game_id: Uuid,
+    filter: GameMoveFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<GameMove>> {
+    timed("get_game_moves_page", async move {
+        sqlx::query_as::<_, GameMove>(
+            r#"
+            SELECT * FROM game_moves
+            WHERE game_id = $1
+              AND ($2::bigint IS NULL OR user_id = $2)
+              AND ($3::integer IS NULL OR round_number = $3)
+            ORDER BY timestamp, id
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(game_id)
+        .bind(filter.user_id)
+        .bind(filter.round)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Block list queries
+pub async fn get_blocked_users(pool: &PgPool, blocker_id: i64) -> Result<Vec<i64>> {
+    timed("get_blocked_users", async move {
+        sqlx::query_scalar("SELECT blocked_id FROM user_blocks WHERE blocker_id = $1")
+            .bind(blocker_id)
+            .fetch_all(pool)
+            .await
+    })
+    .await
+}
+
+pub async fn block_user(pool: &PgPool, blocker_id: i64, blocked_id: i64) -> Result<()> {
+    timed("block_user", async move {
+        sqlx::query(
+            r#"
+            INSERT INTO user_blocks (blocker_id, blocked_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn unblock_user(pool: &PgPool, blocker_id: i64, blocked_id: i64) -> Result<()> {
+    timed("unblock_user", async move {
+        sqlx::query("DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2")
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// The most recent distinct (non-bot) players a user has finished games with,
+/// leaving out anyone they've blocked
+pub async fn get_recent_players(
+    pool: &PgPool,
+    user_id: i64,
+    limit: i64,
+) -> Result<Vec<RecentPlayer>> {
+    timed("get_recent_players", async move {
+        sqlx::query_as::<_, RecentPlayer>(
+            r#"
+            SELECT u.user_id,
+                   u.username,
+                   u.avatar_url,
+                   COUNT(*) AS games_together,
+                   MAX(COALESCE(g.finished_at, g.created_at)) AS last_played_at
+            FROM game_players me
+            JOIN games g ON g.game_id = me.game_id AND g.state = 'finished'
+            JOIN game_players other
+                ON other.game_id = me.game_id
+                AND other.user_id <> me.user_id
+                AND NOT COALESCE(other.is_bot, FALSE)
+            JOIN users u ON u.user_id = other.user_id
+            WHERE me.user_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM user_blocks b
+                  WHERE b.blocker_id = $1 AND b.blocked_id = other.user_id
+              )
+            GROUP BY u.user_id, u.username, u.avatar_url
+            ORDER BY last_played_at DESC NULLS LAST
+            LIMIT $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Head-to-head queries
+
+/// Finished games `$1` and `$2` played on opposing sides (in `$3`'s guild, if set),
+/// with each one's `game_players` row as `a` and `b`
+///
+/// Co-op, time attack and adventure games have no opponents, and hot-seat games
+/// only one Discord player, so they're left out.
+const VERSUS_GAMES: &str = r#"
+    SELECT g.game_id, COALESCE(a.score, 0) AS score_a, a.forfeited AS forfeited_a,
+           COALESCE(b.score, 0) AS score_b, b.forfeited AS forfeited_b
+    FROM games g
+    JOIN game_players a ON a.game_id = g.game_id AND a.user_id = $1
+    JOIN game_players b ON b.game_id = g.game_id AND b.user_id = $2
+    WHERE g.state = 'finished'
+      AND g.game_mode NOT IN ('coop', 'timeattack', 'adventure', 'hotseat')
+      AND (a.team IS NULL OR b.team IS NULL OR a.team <> b.team)
+      AND ($3::BIGINT IS NULL OR g.guild_id = $3)
+"#;
+
+/// `user_a`'s wins, losses and draws against `user_b`; a forfeit loses
+/// whatever the score
+pub async fn get_versus_record(
+    pool: &PgPool,
+    user_a: i64,
+    user_b: i64,
+    guild_id: Option<i64>,
+) -> Result<VersusRecord> {
+    timed("get_versus_record", async move {
+        sqlx::query_as::<_, VersusRecord>(&format!(
+            r#"
+            WITH versus AS ({VERSUS_GAMES}),
+            outcomes AS (
+                SELECT score_a - score_b AS margin,
+                       CASE
+                           WHEN forfeited_a <> forfeited_b THEN
+                               CASE WHEN forfeited_b THEN 1 ELSE -1 END
+                           ELSE SIGN(score_a - score_b)
+                       END AS outcome
+                FROM versus
+            )
+            SELECT
+                COUNT(*) AS games,
+                COUNT(*) FILTER (WHERE outcome > 0) AS wins,
+                COUNT(*) FILTER (WHERE outcome < 0) AS losses,
+                COUNT(*) FILTER (WHERE outcome = 0) AS draws,
+                COALESCE(AVG(margin), 0)::FLOAT8 AS average_margin
+            FROM outcomes
+            "#
+        ))
+        .bind(user_a)
+        .bind(user_b)
+        .bind(guild_id)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+/// Each player's `per_player` highest-scoring words from their games against
+/// each other, best first
+pub async fn get_versus_best_words(
+    pool: &PgPool,
+    user_a: i64,
+    user_b: i64,
+    guild_id: Option<i64>,
+    per_player: i64,
+) -> Result<Vec<VersusWord>> {
+    timed("get_versus_best_words", async move {
+        sqlx::query_as::<_, VersusWord>(&format!(
+            r#"
+            WITH versus AS ({VERSUS_GAMES})
+            SELECT user_id, word, score, game_id
+            FROM (
+                SELECT m.user_id, m.word, m.score, m.game_id,
+                       ROW_NUMBER() OVER (
+                           PARTITION BY m.user_id ORDER BY m.score DESC, m.id
+                       ) AS rank
+                FROM game_moves m
+                JOIN versus v ON v.game_id = m.game_id
+                WHERE m.user_id IN ($1, $2)
+            ) ranked
+            WHERE rank <= $4
+            ORDER BY score DESC, rank
+            "#
+        ))
+        .bind(user_a)
+        .bind(user_b)
+        .bind(guild_id)
+        .bind(per_player)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Lobby invite queries
+
+/// Queue an invite for a user who isn't connected, replacing any earlier invite
+/// from the same sender to the same lobby. Returns false if the recipient has
+/// never signed in.
+pub async fn queue_lobby_invite(
+    pool: &PgPool,
+    from_user_id: i64,
+    to_user_id: i64,
+    lobby_code: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool> {
+    timed("queue_lobby_invite", async move {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO lobby_invites (from_user_id, to_user_id, lobby_code, expires_at)
+            SELECT $1, user_id, $3, $4 FROM users WHERE user_id = $2
+            ON CONFLICT (to_user_id, from_user_id, lobby_code)
+            DO UPDATE SET created_at = NOW(), expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(from_user_id)
+        .bind(to_user_id)
+        .bind(lobby_code)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    })
+    .await
+}
+
+/// Remove and return a user's queued invites that haven't expired, oldest first
+pub async fn take_lobby_invites(pool: &PgPool, user_id: i64) -> Result<Vec<PendingInvite>> {
+    timed("take_lobby_invites", async move {
+        sqlx::query_as::<_, PendingInvite>(
+            r#"
+            WITH taken AS (
+                DELETE FROM lobby_invites
+                WHERE to_user_id = $1
+                RETURNING from_user_id, lobby_code, created_at, expires_at
+            )
+            SELECT t.from_user_id,
+                   u.username AS from_username,
+                   u.avatar_url AS from_avatar_url,
+                   t.lobby_code
+            FROM taken t
+            JOIN users u ON u.user_id = t.from_user_id
+            WHERE t.expires_at > NOW()
+            ORDER BY t.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Drop queued invites to a lobby that's closing (its code can be reused) along
+/// with any that have expired
+pub async fn delete_stale_lobby_invites(pool: &PgPool, lobby_code: &str) -> Result<u64> {
+    timed("delete_stale_lobby_invites", async move {
+        let result =
+            sqlx::query("DELETE FROM lobby_invites WHERE lobby_code = $1 OR expires_at <= NOW()")
+                .bind(lobby_code)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected())
+    })
+    .await
+}
+
+// Profile customization queries
+pub async fn get_user_profile(pool: &PgPool, user_id: i64) -> Result<Option<UserProfile>> {
+    timed("get_user_profile", async move {
+        sqlx::query_as::<_, UserProfile>("SELECT * FROM user_profiles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
+}
+
+pub async fn upsert_user_profile(pool: &PgPool, profile: &UserProfile) -> Result<UserProfile> {
+    timed("upsert_user_profile", async move {
+        sqlx::query_as::<_, UserProfile>(
+            r#"
+            INSERT INTO user_profiles (user_id, title, name_color, badge)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                title = $2,
+                name_color = $3,
+                badge = $4,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(profile.user_id)
+        .bind(&profile.title)
+        .bind(&profile.name_color)
+        .bind(&profile.badge)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+// Quest queries
+
+/// A user's stored progress on the given quest periods (e.g., today and this week)
+pub async fn get_user_quests(
+    pool: &PgPool,
+    user_id: i64,
+    period_keys: &[String],
+) -> Result<Vec<UserQuest>> {
+    timed("get_user_quests", async move {
+        sqlx::query_as::<_, UserQuest>(
+            r#"
+            SELECT quest_id, period_key, progress, completed_at
+            FROM user_quests
+            WHERE user_id = $1 AND period_key = ANY($2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_keys)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Add quest progress and grant the rewards of any quest this completes
+///
+/// Returns the quests completed by this update; a quest is only ever rewarded once
+/// per period.
+pub async fn record_quest_progress(
+    pool: &PgPool,
+    user_id: i64,
+    updates: &[QuestUpdate],
+) -> Result<Vec<UserQuest>> {
+    timed("record_quest_progress", async move {
+        let mut tx = pool.begin().await?;
+        let mut completed = Vec::new();
+        for update in updates {
+            let def = update.quest.def;
+            let stored = sqlx::query_as::<_, UserQuest>(
+                r#"
+                INSERT INTO user_quests (user_id, quest_id, period_key, progress, completed_at)
+                VALUES ($1, $2, $3, LEAST($4, $5), CASE WHEN $4 >= $5 THEN NOW() END)
+                ON CONFLICT (user_id, quest_id, period_key)
+                DO UPDATE SET
+                    progress = LEAST(user_quests.progress + $4, $5),
+                    completed_at = CASE WHEN user_quests.progress + $4 >= $5 THEN NOW() END
+                WHERE user_quests.completed_at IS NULL
+                RETURNING quest_id, period_key, progress, completed_at
+                "#,
+            )
+            .bind(user_id)
+            .bind(def.id)
+            .bind(&update.quest.period_key)
+            .bind(update.increment)
+            .bind(def.target)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            // No row means the quest was already complete
+            let Some(stored) = stored.filter(|s| s.completed_at.is_some()) else {
+                continue;
+            };
+            sqlx::query("UPDATE users SET gems = gems + $2, xp = xp + $3 WHERE user_id = $1")
+                .bind(user_id)
+                .bind(def.reward.gems)
+                .bind(def.reward.xp)
+                .execute(&mut *tx)
+                .await?;
+            record_wallet_transaction(
+                &mut tx,
+                user_id,
+                def.reward.gems,
+                &format!("quest:{}", def.id),
+            )
+            .await?;
+            completed.push(stored);
+        }
+        tx.commit().await?;
+        Ok(completed)
+    })
+    .await
+}
+
+// Analytics queries
+
+/// Recompute the analytics views; readers keep seeing the previous data meanwhile
+pub async fn refresh_analytics(pool: &PgPool) -> Result<()> {
+    timed("refresh_analytics", async move {
+        sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY analytics_daily")
+            .execute(pool)
+            .await?;
+        sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY analytics_cohorts")
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Daily activity from `since` on, as of the last refresh (days without games are missing)
+pub async fn get_daily_activity(pool: &PgPool, since: NaiveDate) -> Result<Vec<DailyActivity>> {
+    timed("get_daily_activity", async move {
+        sqlx::query_as::<_, DailyActivity>(
+            r#"
+            SELECT day, games_played, active_players, avg_game_secs
+            FROM analytics_daily
+            WHERE day >= $1
+            ORDER BY day
+            "#,
+        )
+        .bind(since)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Signup cohorts for weeks starting on or after `since`, as of the last refresh
+pub async fn get_retention_cohorts(
+    pool: &PgPool,
+    since: NaiveDate,
+) -> Result<Vec<RetentionCohort>> {
+    timed("get_retention_cohorts", async move {
+        sqlx::query_as::<_, RetentionCohort>(
+            r#"
+            SELECT cohort_week, cohort_size, retained_day_1, retained_day_7, retained_day_30
+            FROM analytics_cohorts
+            WHERE cohort_week >= $1
+            ORDER BY cohort_week
+            "#,
+        )
+        .bind(since)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Wallet and shop queries
+
+/// A user's gem and XP totals
+pub async fn get_wallet(pool: &PgPool, user_id: i64) -> Result<Option<Wallet>> {
+    timed("get_wallet", async move {
+        sqlx::query_as::<_, Wallet>("SELECT gems, xp FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
+}
+
+/// Log a change to a user's gems; callers update the balance in the same transaction
+async fn record_wallet_transaction(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: i64,
+    amount: i64,
+    reason: &str,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    sqlx::query("INSERT INTO wallet_transactions (user_id, amount, reason) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(amount)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Items currently for sale, grouped by kind and cheapest first
+pub async fn list_shop_items(pool: &PgPool) -> Result<Vec<ShopItem>> {
+    timed("list_shop_items", async move {
+        sqlx::query_as::<_, ShopItem>(
+            r#"
+            SELECT item_id, kind, name, price
+            FROM shop_items
+            WHERE available
+            ORDER BY kind, price, item_id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Everything a user has bought, including items no longer for sale
+pub async fn get_user_inventory(pool: &PgPool, user_id: i64) -> Result<Vec<InventoryItem>> {
+    timed("get_user_inventory", async move {
+        sqlx::query_as::<_, InventoryItem>(
+            r#"
+            SELECT i.item_id, s.kind, s.name, i.purchased_at
+            FROM user_inventory i
+            JOIN shop_items s ON s.item_id = i.item_id
+            WHERE i.user_id = $1
+            ORDER BY i.purchased_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Buy an item with gems
+///
+/// The user's row is locked for the whole transaction, so concurrent purchases
+/// can't spend the same gems twice.
+pub async fn purchase_item(pool: &PgPool, user_id: i64, item_id: &str) -> Result<PurchaseOutcome> {
+    timed("purchase_item", async move {
+        let mut tx = pool.begin().await?;
+
+        let Some(item) = sqlx::query_as::<_, ShopItem>(
+            "SELECT item_id, kind, name, price FROM shop_items WHERE item_id = $1 AND available",
+        )
+        .bind(item_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(PurchaseOutcome::NotFound);
+        };
+
+        let balance: i64 =
+            sqlx::query_scalar("SELECT gems FROM users WHERE user_id = $1 FOR UPDATE")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM user_inventory WHERE user_id = $1 AND item_id = $2)",
+        )
+        .bind(user_id)
+        .bind(item_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if owned {
+            return Ok(PurchaseOutcome::AlreadyOwned);
+        }
+        if balance < item.price {
+            return Ok(PurchaseOutcome::InsufficientGems {
+                price: item.price,
+                balance,
+            });
+        }
+
+        sqlx::query(
+            "INSERT INTO user_inventory (user_id, item_id, price_paid) VALUES ($1, $2, $3)",
+        )
+        .bind(user_id)
+        .bind(item_id)
+        .bind(item.price)
+        .execute(&mut *tx)
+        .await?;
+        let wallet = sqlx::query_as::<_, Wallet>(
+            "UPDATE users SET gems = gems - $2 WHERE user_id = $1 RETURNING gems, xp",
+        )
+        .bind(user_id)
+        .bind(item.price)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_wallet_transaction(
+            &mut tx,
+            user_id,
+            -item.price,
+            &format!("purchase:{}", item_id),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(PurchaseOutcome::Purchased(wallet))
+    })
+    .await
+}
+
+// User guild profile queries
+pub async fn get_user_guild_profile(
+    pool: &PgPool,
+    user_id: i64,
+    guild_id: i64,
+) -> Result<Option<UserGuildProfile>> {
+    timed("get_user_guild_profile", async move {
+        sqlx::query_as::<_, UserGuildProfile>(
+            "SELECT * FROM user_guild_profiles WHERE user_id = $1 AND guild_id = $2",
+        )
+        .bind(user_id)
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .await
+}
+
+pub async fn create_or_update_guild_profile(
+    pool: &PgPool,
+    user_id: i64,
+    guild_id: i64,
+    nickname: Option<&str>,
+    roles: &[i64],
+) -> Result<UserGuildProfile> {
+    timed("create_or_update_guild_profile", async move {
+        sqlx::query_as::<_, UserGuildProfile>(
+            r#"
+            INSERT INTO user_guild_profiles (user_id, guild_id, nickname, roles)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, guild_id)
+            DO UPDATE SET
+                nickname = $3,
+                roles = $4,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(guild_id)
+        .bind(nickname)
+        .bind(roles)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+// Guild settings queries
+pub async fn get_guild_settings(pool: &PgPool, guild_id: i64) -> Result<Option<GuildSettings>> {
+    timed("get_guild_settings", async move {
+        sqlx::query_as::<_, GuildSettings>("SELECT * FROM guild_settings WHERE guild_id = $1")
+            .bind(guild_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
+}
+
+pub async fn upsert_guild_settings(
+    pool: &PgPool,
+    settings: &GuildSettings,
+    updated_by: i64,
+) -> Result<GuildSettings> {
+    timed("upsert_guild_settings", async move {
+        sqlx::query_as::<_, GuildSettings>(
+            r#"
+            INSERT INTO guild_settings
+                (guild_id, default_language, default_rounds, profanity_filter, allowed_modes, updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (guild_id)
+            DO UPDATE SET
+                default_language = $2,
+                default_rounds = $3,
+                profanity_filter = $4,
+                allowed_modes = $5,
+                updated_by = $6
+            RETURNING *
+            "#,
+        )
+        .bind(settings.guild_id)
+        .bind(&settings.default_language)
+        .bind(settings.default_rounds)
+        .bind(settings.profanity_filter)
+        .bind(&settings.allowed_modes)
+        .bind(updated_by)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+// Game night queries
+
+/// A guild's weekly game nights, by channel
+pub async fn get_game_nights(pool: &PgPool, guild_id: i64) -> Result<Vec<GameNight>> {
+    timed("get_game_nights", async move {
+        sqlx::query_as::<_, GameNight>(
+            "SELECT * FROM game_nights WHERE guild_id = $1 ORDER BY weekday, start_time, channel_id",
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Every guild's weekly game nights
+pub async fn get_all_game_nights(pool: &PgPool) -> Result<Vec<GameNight>> {
+    timed("get_all_game_nights", async move {
+        sqlx::query_as::<_, GameNight>("SELECT * FROM game_nights")
+            .fetch_all(pool)
+            .await
+    })
+    .await
+}
+
+/// Set up (or replace) a channel's game night
+///
+/// Occurrences not announced yet are dropped, since their time may have changed;
+/// the next one is created again from the new setup. Announced ones go ahead.
+pub async fn upsert_game_night(
+    pool: &PgPool,
+    guild_id: i64,
+    channel_id: i64,
+    update: &GameNightUpdate,
+    ping_role_id: Option<i64>,
+    created_by: i64,
+) -> Result<GameNight> {
+    timed("upsert_game_night", async move {
+        let mut tx = pool.begin().await?;
+        let game_night = sqlx::query_as::<_, GameNight>(
+            r#"
+            INSERT INTO game_nights
+                (guild_id, channel_id, weekday, start_time, mode, target_score,
+                 webhook_url, ping_role_id, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (guild_id, channel_id)
+            DO UPDATE SET
+                weekday = $3,
+                start_time = $4,
+                mode = $5,
+                target_score = $6,
+                webhook_url = $7,
+                ping_role_id = $8,
+                created_by = $9,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(update.weekday)
+        .bind(update.start_time)
+        .bind(&update.mode)
+        .bind(update.target_score)
+        .bind(&update.webhook_url)
+        .bind(ping_role_id)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        delete_unannounced_game_night_events(&mut tx, game_night.game_night_id).await?;
+        tx.commit().await?;
+        Ok(game_night)
+    })
+    .await
+}
+
+/// Stop a channel's game night; returns false if it had none
+///
+/// Past occurrences stay in the guild's history.
+pub async fn delete_game_night(pool: &PgPool, guild_id: i64, channel_id: i64) -> Result<bool> {
+    timed("delete_game_night", async move {
+        let mut tx = pool.begin().await?;
+        let game_night_id: Option<i32> = sqlx::query_scalar(
+            "DELETE FROM game_nights WHERE guild_id = $1 AND channel_id = $2 RETURNING game_night_id",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(game_night_id) = game_night_id {
+            delete_unannounced_game_night_events(&mut tx, game_night_id).await?;
+        }
+        tx.commit().await?;
+        Ok(game_night_id.is_some())
+    })
+    .await
+}
+
+async fn delete_unannounced_game_night_events(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    game_night_id: i32,
+) -> Result<()> {
+    // A deleted game night's events have already lost their game_night_id
+    sqlx::query(
+        r#"
+        DELETE FROM game_night_events
+        WHERE (game_night_id = $1 OR game_night_id IS NULL)
+            AND status = 'scheduled' AND announced_at IS NULL
+        "#,
+    )
+    .bind(game_night_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Add an occurrence of a game night, unless it already exists
+pub async fn create_game_night_event(
+    pool: &PgPool,
+    game_night: &GameNight,
+    starts_at: DateTime<Utc>,
+) -> Result<()> {
+    timed("create_game_night_event", async move {
+        sqlx::query(
+            r#"
+            INSERT INTO game_night_events (game_night_id, guild_id, channel_id, mode, starts_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (game_night_id, starts_at) DO NOTHING
+            "#,
+        )
+        .bind(game_night.game_night_id)
+        .bind(game_night.guild_id)
+        .bind(game_night.channel_id)
+        .bind(&game_night.mode)
+        .bind(starts_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Game night occurrences still to start that start no later than `until`
+pub async fn get_upcoming_game_nights(
+    pool: &PgPool,
+    until: DateTime<Utc>,
+) -> Result<Vec<UpcomingGameNight>> {
+    timed("get_upcoming_game_nights", async move {
+        sqlx::query_as::<_, UpcomingGameNight>(
+            r#"
+            SELECT e.event_id, e.guild_id, e.channel_id, e.mode, n.target_score, e.starts_at,
+                   e.announced_at, n.webhook_url, n.ping_role_id, n.created_by
+            FROM game_night_events e
+            JOIN game_nights n ON n.game_night_id = e.game_night_id
+            WHERE e.status = 'scheduled' AND e.starts_at > NOW() AND e.starts_at <= $1
+            ORDER BY e.starts_at
+            "#,
+        )
+        .bind(until)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+pub async fn mark_game_night_announced(pool: &PgPool, event_id: i64) -> Result<()> {
+    timed("mark_game_night_announced", async move {
+        sqlx::query("UPDATE game_night_events SET announced_at = NOW() WHERE event_id = $1")
+            .bind(event_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Link a game night occurrence to its game, with everyone who played in it
+pub async fn record_game_night_start(
+    pool: &PgPool,
+    event_id: i64,
+    game_id: Uuid,
+    user_ids: &[i64],
+) -> Result<()> {
+    timed("record_game_night_start", async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "UPDATE game_night_events SET status = 'started', game_id = $2 WHERE event_id = $1",
+        )
+        .bind(event_id)
+        .bind(game_id)
+        .execute(&mut *tx)
+        .await?;
+        // Hot-seat and guest players have no account to record against
+        sqlx::query(
+            r#"
+            INSERT INTO game_night_attendance (event_id, user_id)
+            SELECT $1, user_id FROM users WHERE user_id = ANY($2)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(event_id)
+        .bind(user_ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    })
+    .await
+}
+
+pub async fn set_game_night_status(
+    pool: &PgPool,
+    event_id: i64,
+    status: GameNightStatus,
+) -> Result<()> {
+    timed("set_game_night_status", async move {
+        sqlx::query("UPDATE game_night_events SET status = $2 WHERE event_id = $1")
+            .bind(event_id)
+            .bind(status)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Mark occurrences that should have started before `cutoff` but never did as
+/// missed; returns how many there were
+pub async fn mark_missed_game_nights(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+    timed("mark_missed_game_nights", async move {
+        let result = sqlx::query(
+            "UPDATE game_night_events SET status = 'missed' WHERE status = 'scheduled' AND starts_at < $1",
+        )
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    })
+    .await
+}
+
+/// A guild's game night occurrences, most recent first, with the winner of
+/// each finished game
+pub async fn get_game_night_events(
+    pool: &PgPool,
+    guild_id: i64,
+    limit: i64,
+) -> Result<Vec<GameNightEvent>> {
+    timed("get_game_night_events", async move {
+        sqlx::query_as::<_, GameNightEvent>(
+            r#"
+            SELECT e.event_id, e.channel_id, e.mode, e.starts_at, e.status, e.game_id,
+                   CASE WHEN g.state = 'finished' THEN g.current_turn_player END AS winner_id
+            FROM game_night_events e
+            LEFT JOIN games g ON g.game_id = e.game_id
+            WHERE e.guild_id = $1
+            ORDER BY e.starts_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(guild_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Who attended each of the given occurrences, with their scores, highest first
+pub async fn get_game_night_attendance(
+    pool: &PgPool,
+    event_ids: &[i64],
+) -> Result<Vec<GameNightAttendee>> {
+    timed("get_game_night_attendance", async move {
+        sqlx::query_as::<_, GameNightAttendee>(
+            r#"
+            SELECT a.event_id, a.user_id, u.username, gp.score
+            FROM game_night_attendance a
+            JOIN game_night_events e ON e.event_id = a.event_id
+            JOIN users u ON u.user_id = a.user_id
+            LEFT JOIN game_players gp ON gp.game_id = e.game_id AND gp.user_id = a.user_id
+            WHERE a.event_id = ANY($1)
+            ORDER BY a.event_id, gp.score DESC NULLS LAST, u.username
+            "#,
+        )
+        .bind(event_ids)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Guild leaderboard queries
+
+/// Top players across all finished games in a guild, by total score
+///
+/// With a season, only games that finished during it count. Read from the
+/// `guild_stats` and `guild_season_stats` rollups kept by `finish_game`.
+pub async fn get_guild_standings(
+    pool: &PgPool,
+    guild_id: i64,
+    limit: i64,
+    season: Option<&Season>,
+) -> Result<Vec<GuildStanding>> {
+    timed("get_guild_standings", async move {
+        match season {
+            Some(season) => {
+                sqlx::query_as::<_, GuildStanding>(
+                    r#"
+                    SELECT
+                        gs.user_id,
+                        u.username,
+                        u.avatar_url,
+                        gs.games_played::BIGINT AS games_played,
+                        gs.wins::BIGINT AS wins,
+                        gs.total_score
+                    FROM guild_season_stats gs
+                    JOIN users u ON u.user_id = gs.user_id
+                    WHERE gs.guild_id = $1 AND gs.season_id = $3
+                    ORDER BY gs.total_score DESC, gs.wins DESC, gs.user_id
+                    LIMIT $2
+                    "#,
+                )
+                .bind(guild_id)
+                .bind(limit)
+                .bind(season.season_id)
+                .fetch_all(pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, GuildStanding>(
+                    r#"
+                    SELECT
+                        gs.user_id,
+                        u.username,
+                        u.avatar_url,
+                        gs.games_played::BIGINT AS games_played,
+                        gs.wins::BIGINT AS wins,
+                        gs.total_score
+                    FROM guild_stats gs
+                    JOIN users u ON u.user_id = gs.user_id
+                    WHERE gs.guild_id = $1
+                    ORDER BY gs.total_score DESC, gs.wins DESC, gs.user_id
+                    LIMIT $2
+                    "#,
+                )
+                .bind(guild_id)
+                .bind(limit)
+                .fetch_all(pool)
+                .await
+            }
+        }
+    })
+    .await
+}
+
+/// Top players across every guild in one season, by season score
+pub async fn get_season_standings(
+    pool: &PgPool,
+    season_id: i32,
+    limit: i64,
+) -> Result<Vec<GuildStanding>> {
+    timed("get_season_standings", async move {
+        sqlx::query_as::<_, GuildStanding>(
+            r#"
+            SELECT
+                ss.user_id,
+                u.username,
+                u.avatar_url,
+                ss.games_played::BIGINT AS games_played,
+                ss.wins::BIGINT AS wins,
+                ss.total_score
+            FROM season_stats ss
+            JOIN users u ON u.user_id = ss.user_id
+            WHERE ss.season_id = $1
+            ORDER BY ss.total_score DESC, ss.wins DESC, ss.user_id
+            LIMIT $2
+            "#,
+        )
+        .bind(season_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Top players across every guild by lifetime score
+pub async fn get_lifetime_standings(pool: &PgPool, limit: i64) -> Result<Vec<GuildStanding>> {
+    timed("get_lifetime_standings", async move {
+        sqlx::query_as::<_, GuildStanding>(
+            r#"
+            SELECT
+                user_id,
+                username,
+                avatar_url,
+                COALESCE(total_games, 0)::BIGINT AS games_played,
+                COALESCE(total_wins, 0)::BIGINT AS wins,
+                COALESCE(total_score, 0)::BIGINT AS total_score
+            FROM users
+            WHERE total_games > 0
+            ORDER BY total_score DESC, wins DESC, user_id
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Puzzle rush queries
+
+/// Record a finished puzzle rush for the leaderboard
+pub async fn record_puzzle_rush_run(
+    pool: &PgPool,
+    game_id: Uuid,
+    user_id: i64,
+    streak: u32,
+    points: i32,
+) -> Result<()> {
+    timed("record_puzzle_rush_run", async move {
+        sqlx::query(
+            r#"
+            INSERT INTO puzzle_rush_runs (game_id, user_id, streak, points)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (game_id) DO NOTHING
+            "#,
+        )
+        .bind(game_id)
+        .bind(user_id)
+        .bind(streak.min(i32::MAX as u32) as i32)
+        .bind(points)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Every player's best puzzle rush, longest streak first, then most points,
+/// then whoever got there first
+pub async fn get_puzzle_rush_standings(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<PuzzleRushStanding>> {
+    timed("get_puzzle_rush_standings", async move {
+        sqlx::query_as::<_, PuzzleRushStanding>(
+            r#"
+            SELECT * FROM (
+                SELECT DISTINCT ON (r.user_id)
+                    r.user_id, u.username, u.avatar_url, r.streak, r.points, r.finished_at
+                FROM puzzle_rush_runs r
+                JOIN users u ON u.user_id = r.user_id
+                ORDER BY r.user_id, r.streak DESC, r.points DESC, r.finished_at
+            ) best
+            ORDER BY streak DESC, points DESC, finished_at
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+// Season queries
+
+/// The season in progress, if any
+pub async fn get_current_season(pool: &PgPool) -> Result<Option<Season>> {
+    timed("get_current_season", async move {
+        sqlx::query_as::<_, Season>(
+            r#"
+            SELECT * FROM seasons
+            WHERE starts_at <= NOW() AND ends_at > NOW()
+            ORDER BY starts_at DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(pool)
+        .await
+    })
+    .await
+}
 
-/// Update game state in the database
-pub async fn update_game_db_state(pool: &PgPool, game_id: Uuid, state: GameDbState) -> Result<()> {
-    let state_str = match state {
-        GameDbState::Waiting => "waiting",
-        GameDbState::Active => "active",
-        GameDbState::Finished => "finished",
-        GameDbState::Cancelled => "cancelled",
-    };
+pub async fn get_season(pool: &PgPool, season_id: i32) -> Result<Option<Season>> {
+    timed("get_season", async move {
+        sqlx::query_as::<_, Season>("SELECT * FROM seasons WHERE season_id = $1")
+            .bind(season_id)
+            .fetch_optional(pool)
+            .await
+    })
+    .await
+}
 
-    sqlx::query("UPDATE games SET state = $1, started_at = NOW() WHERE game_id = $2")
-        .bind(state_str)
-        .bind(game_id)
-        .execute(pool)
-        .await?;
-    Ok(())
+/// Every season, newest first
+pub async fn list_seasons(pool: &PgPool) -> Result<Vec<Season>> {
+    timed("list_seasons", async move {
+        sqlx::query_as::<_, Season>("SELECT * FROM seasons ORDER BY starts_at DESC")
+            .fetch_all(pool)
+            .await
+    })
+    .await
 }
 
-/// Update game round and current turn player
-pub async fn update_game_round(
+/// The season that ends last, to schedule the next one after it
+pub async fn get_latest_season(pool: &PgPool) -> Result<Option<Season>> {
+    timed("get_latest_season", async move {
+        sqlx::query_as::<_, Season>("SELECT * FROM seasons ORDER BY ends_at DESC LIMIT 1")
+            .fetch_optional(pool)
+            .await
+    })
+    .await
+}
+
+/// Open a season, numbered after the ones before it ("Season 3")
+pub async fn create_season(
     pool: &PgPool,
-    game_id: Uuid,
-    round: i32,
-    current_player_id: i64,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+) -> Result<Season> {
+    timed("create_season", async move {
+        sqlx::query_as::<_, Season>(
+            r#"
+            INSERT INTO seasons (name, starts_at, ends_at)
+            SELECT 'Season ' || (COUNT(*) + 1), $1, $2 FROM seasons
+            RETURNING *
+            "#,
+        )
+        .bind(starts_at)
+        .bind(ends_at)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+/// Seasons that have ended but haven't had their summary posted, oldest first
+pub async fn get_unsummarized_seasons(pool: &PgPool) -> Result<Vec<Season>> {
+    timed("get_unsummarized_seasons", async move {
+        sqlx::query_as::<_, Season>(
+            "SELECT * FROM seasons WHERE ends_at <= NOW() AND summarized_at IS NULL ORDER BY ends_at",
+        )
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+pub async fn mark_season_summarized(pool: &PgPool, season_id: i32) -> Result<()> {
+    timed("mark_season_summarized", async move {
+        sqlx::query("UPDATE seasons SET summarized_at = NOW() WHERE season_id = $1")
+            .bind(season_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await
+}
+
+// Word analytics queries
+
+/// Count a scored word in the community word stats
+async fn record_word_play(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    game_move: &PendingMove,
 ) -> Result<()> {
+    let first_play_by_user = sqlx::query(
+        r#"
+        INSERT INTO word_stat_players (word, user_id)
+        VALUES ($1, $2)
+        ON CONFLICT (word, user_id) DO NOTHING
+        "#,
+    )
+    .bind(&game_move.word)
+    .bind(game_move.user_id)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
     sqlx::query(
         r#"
-        UPDATE games
-        SET current_round = $1, current_turn_player = $2
-        WHERE game_id = $3
+        INSERT INTO word_stats (word, plays, total_score, distinct_players, last_played_at)
+        VALUES ($1, 1, $2, $3, NOW())
+        ON CONFLICT (word) DO UPDATE SET
+            plays = word_stats.plays + 1,
+            total_score = word_stats.total_score + EXCLUDED.total_score,
+            distinct_players = word_stats.distinct_players + EXCLUDED.distinct_players,
+            last_played_at = NOW()
         "#,
     )
-    .bind(round)
-    .bind(current_player_id)
-    .bind(game_id)
-    .execute(pool)
+    .bind(&game_move.word)
+    .bind(game_move.score as i64)
+    .bind(first_play_by_user as i32)
+    .execute(&mut **tx)
     .await?;
     Ok(())
 }
 
-/// Update a player's score in the database
-pub async fn update_player_score(
-    pool: &PgPool,
-    game_id: Uuid,
-    user_id: i64,
-    score: i32,
+/// Count a scored word in its player's word habits
+async fn record_word_habits(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    game_move: &PendingMove,
 ) -> Result<()> {
-    sqlx::query("UPDATE game_players SET score = $1 WHERE game_id = $2 AND user_id = $3")
-        .bind(score)
-        .bind(game_id)
-        .bind(user_id)
-        .execute(pool)
+    let traits = WordTraits::of(&game_move.word);
+    sqlx::query(
+        r#"
+        INSERT INTO user_word_stats
+            (user_id, words_played, total_letters, q_words, z_words, longest_word)
+        VALUES ($1, 1, $2, $3, $4, $5)
+        ON CONFLICT (user_id) DO UPDATE SET
+            words_played = user_word_stats.words_played + 1,
+            total_letters = user_word_stats.total_letters + EXCLUDED.total_letters,
+            q_words = user_word_stats.q_words + EXCLUDED.q_words,
+            z_words = user_word_stats.z_words + EXCLUDED.z_words,
+            longest_word = CASE
+                WHEN LENGTH(EXCLUDED.longest_word) > COALESCE(LENGTH(user_word_stats.longest_word), 0)
+                THEN EXCLUDED.longest_word
+                ELSE user_word_stats.longest_word
+            END
+        "#,
+    )
+    .bind(game_move.user_id)
+    .bind(traits.letters)
+    .bind(traits.has_q as i64)
+    .bind(traits.has_z as i64)
+    .bind(&traits.word)
+    .execute(&mut **tx)
+    .await?;
+
+    if let Some(letter) = &traits.starting_letter {
+        sqlx::query(
+            r#"
+            INSERT INTO user_starting_letters (user_id, letter, plays)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (user_id, letter) DO UPDATE SET
+                plays = user_starting_letters.plays + 1
+            "#,
+        )
+        .bind(game_move.user_id)
+        .bind(letter)
+        .execute(&mut **tx)
         .await?;
+    }
     Ok(())
 }
 
-/// Update used words for a game board
-pub async fn update_game_board_used_words(
+/// A player's word habits; all zeroes if they haven't scored a word yet
+pub async fn get_word_habits(pool: &PgPool, user_id: i64) -> Result<WordHabits> {
+    timed("get_word_habits", async move {
+        let habits = sqlx::query_as::<_, WordHabits>(
+            r#"
+            SELECT
+                s.words_played,
+                s.total_letters::FLOAT8 / GREATEST(s.words_played, 1) AS average_word_length,
+                (
+                    SELECT l.letter::TEXT FROM user_starting_letters l
+                    WHERE l.user_id = s.user_id AND l.plays > 0
+                    ORDER BY l.plays DESC, l.letter
+                    LIMIT 1
+                ) AS favorite_starting_letter,
+                s.q_words,
+                s.z_words,
+                s.longest_word
+            FROM user_word_stats s
+            WHERE s.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(habits.unwrap_or_default())
+    })
+    .await
+}
+
+/// Most-played or highest-scoring words across all games
+pub async fn get_top_words(
     pool: &PgPool,
-    game_id: Uuid,
-    used_words: &[String],
-) -> Result<()> {
-    let words_json = serde_json::to_value(used_words)
-        .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize used words: {}", e)))?;
+    sort: WordStatsSort,
+    limit: i64,
+) -> Result<Vec<WordStat>> {
+    timed("get_top_words", async move {
+        let order_by = match sort {
+            WordStatsSort::Plays => "plays DESC, average_score DESC",
+            WordStatsSort::Score => "average_score DESC, plays DESC",
+        };
+        sqlx::query_as::<_, WordStat>(&format!(
+            r#"
+            SELECT
+                word,
+                plays,
+                total_score::FLOAT8 / GREATEST(plays, 1) AS average_score,
+                distinct_players
+            FROM word_stats
+            ORDER BY {}, word
+            LIMIT $1
+            "#,
+            order_by
+        ))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
 
-    sqlx::query("UPDATE game_boards SET used_words = $1, updated_at = NOW() WHERE game_id = $2")
-        .bind(words_json)
-        .bind(game_id)
-        .execute(pool)
-        .await?;
-    Ok(())
+/// Play counts of every word played at least `min_plays` times
+pub async fn get_word_plays(pool: &PgPool, min_plays: i64) -> Result<Vec<(String, i64)>> {
+    timed("get_word_plays", async move {
+        sqlx::query_as::<_, (String, i64)>("SELECT word, plays FROM word_stats WHERE plays >= $1")
+            .bind(min_plays)
+            .fetch_all(pool)
+            .await
+    })
+    .await
 }
 
-/// Mark a game as finished with final results
-pub async fn finish_game(pool: &PgPool, game_id: Uuid, winner_id: Option<i64>) -> Result<()> {
-    sqlx::query(
-        r#"
-        UPDATE games
-        SET state = 'finished', finished_at = NOW(), current_turn_player = $1
-        WHERE game_id = $2
-        "#,
-    )
-    .bind(winner_id)
-    .bind(game_id)
-    .execute(pool)
-    .await?;
-    Ok(())
+// Account export and deletion queries
+
+/// Everything stored about a user, as one JSON document; None if they don't exist
+///
+/// The refresh token is left out: it's a credential, not data about the user.
+pub async fn export_user_data(pool: &PgPool, user_id: i64) -> Result<Option<serde_json::Value>> {
+    timed("export_user_data", async move {
+        sqlx::query_scalar(
+            r#"
+            SELECT jsonb_build_object(
+                'user', to_jsonb(u) - 'refresh_token_encrypted',
+                'profile', (SELECT to_jsonb(p) FROM user_profiles p WHERE p.user_id = u.user_id),
+                'guild_profiles', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(gp) ORDER BY gp.guild_id)
+                    FROM user_guild_profiles gp WHERE gp.user_id = u.user_id
+                ), '[]'),
+                'inventory', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(i) ORDER BY i.purchased_at)
+                    FROM user_inventory i WHERE i.user_id = u.user_id
+                ), '[]'),
+                'wallet_transactions', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(t) ORDER BY t.created_at)
+                    FROM wallet_transactions t WHERE t.user_id = u.user_id
+                ), '[]'),
+                'quests', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(q) ORDER BY q.period_key, q.quest_id)
+                    FROM user_quests q WHERE q.user_id = u.user_id
+                ), '[]'),
+                'word_stats', (SELECT to_jsonb(w) FROM user_word_stats w WHERE w.user_id = u.user_id),
+                'starting_letters', COALESCE((
+                    SELECT jsonb_object_agg(l.letter, l.plays)
+                    FROM user_starting_letters l WHERE l.user_id = u.user_id
+                ), '{}'),
+                'season_stats', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(s) ORDER BY s.season_id)
+                    FROM season_stats s WHERE s.user_id = u.user_id
+                ), '[]'),
+                'guild_stats', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(gs) ORDER BY gs.guild_id)
+                    FROM guild_stats gs WHERE gs.user_id = u.user_id
+                ), '[]'),
+                'adventure_progress', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(a) ORDER BY a.level)
+                    FROM adventure_progress a WHERE a.user_id = u.user_id
+                ), '[]'),
+                'blocked_users', COALESCE((
+                    SELECT jsonb_agg(
+                        jsonb_build_object('user_id', b.blocked_id, 'created_at', b.created_at)
+                        ORDER BY b.created_at
+                    )
+                    FROM user_blocks b WHERE b.blocker_id = u.user_id
+                ), '[]'),
+                'lobby_invites', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(li) ORDER BY li.created_at)
+                    FROM lobby_invites li
+                    WHERE li.from_user_id = u.user_id OR li.to_user_id = u.user_id
+                ), '[]'),
+                'games', COALESCE((
+                    SELECT jsonb_agg(
+                        jsonb_build_object(
+                            'game_id', g.game_id,
+                            'guild_id', g.guild_id,
+                            'channel_id', g.channel_id,
+                            'game_mode', g.game_mode,
+                            'state', g.state,
+                            'created_at', g.created_at,
+                            'finished_at', g.finished_at,
+                            'result', g.result,
+                            'score', p.score,
+                            'forfeited', p.forfeited
+                        )
+                        ORDER BY g.created_at
+                    )
+                    FROM game_players p JOIN games g ON g.game_id = p.game_id
+                    WHERE p.user_id = u.user_id
+                ), '[]'),
+                'moves', COALESCE((
+                    SELECT jsonb_agg(
+                        jsonb_build_object(
+                            'game_id', m.game_id,
+                            'round_number', m.round_number,
+                            'word', m.word,
+                            'score', m.score,
+                            'positions', m.positions,
+                            'timestamp', m.timestamp,
+                            'move_time_ms', m.move_time_ms
+                        )
+                        ORDER BY m.timestamp
+                    )
+                    FROM game_moves m WHERE m.user_id = u.user_id
+                ), '[]'),
+                'words_played', COALESCE((
+                    SELECT jsonb_agg(w.word ORDER BY w.word)
+                    FROM word_stat_players w WHERE w.user_id = u.user_id
+                ), '[]')
+            )
+            FROM users u
+            WHERE u.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .await
 }
 
-// Game move queries
-pub async fn create_game_move(
+/// Start the deletion grace period and sign the user out everywhere
+///
+/// Returns when deletion was requested (the first request, if asked twice), or
+/// None if the user doesn't exist.
+pub async fn request_account_deletion(
     pool: &PgPool,
-    game_id: Uuid,
     user_id: i64,
-    round_number: i32,
-    word: &str,
-    score: i32,
-    positions: serde_json::Value,
-) -> Result<GameMove> {
-    sqlx::query_as::<_, GameMove>(
-        r#"
-        INSERT INTO game_moves (game_id, user_id, round_number, word, score, positions)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING *
-        "#,
-    )
-    .bind(game_id)
-    .bind(user_id)
-    .bind(round_number)
-    .bind(word)
-    .bind(score)
-    .bind(positions)
-    .fetch_one(pool)
+) -> Result<Option<DateTime<Utc>>> {
+    timed("request_account_deletion", async move {
+        sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET deletion_requested_at = COALESCE(deletion_requested_at, NOW()),
+                refresh_token_encrypted = NULL,
+                token_expires_at = NULL,
+                oauth_scopes = '{}',
+                updated_at = NOW()
+            WHERE user_id = $1
+            RETURNING deletion_requested_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    })
     .await
 }
 
-pub async fn get_game_moves(pool: &PgPool, game_id: Uuid) -> Result<Vec<GameMove>> {
-    sqlx::query_as::<_, GameMove>("SELECT * FROM game_moves WHERE game_id = $1 ORDER BY timestamp")
-        .bind(game_id)
+/// Undo a pending deletion; false if none was pending (or it already happened)
+pub async fn cancel_account_deletion(pool: &PgPool, user_id: i64) -> Result<bool> {
+    timed("cancel_account_deletion", async move {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET deletion_requested_at = NULL,
+                updated_at = NOW()
+            WHERE user_id = $1 AND deletion_requested_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    })
+    .await
+}
+
+/// Users whose deletion was requested at or before `cutoff`
+pub async fn get_accounts_due_for_deletion(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<i64>> {
+    timed("get_accounts_due_for_deletion", async move {
+        sqlx::query_scalar(
+            "SELECT user_id FROM users WHERE deletion_requested_at <= $1 ORDER BY deletion_requested_at",
+        )
+        .bind(cutoff)
         .fetch_all(pool)
         .await
+    })
+    .await
 }
 
-// User guild profile queries
-#[allow(dead_code)]
-pub async fn get_user_guild_profile(
+/// Delete an account for good, returning the placeholder ID its games now use
+///
+/// Game and move rows (and wins recorded in results) move to a fresh "Deleted
+/// player" with a negative ID, so opponents' history and scores stay intact.
+/// Everything else about the user is deleted.
+pub async fn delete_account(pool: &PgPool, user_id: i64) -> Result<i64> {
+    timed("delete_account", async move {
+        let mut tx = pool.begin().await?;
+
+        let placeholder_id: i64 = sqlx::query_scalar("SELECT -nextval('deleted_user_ids')")
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO users (user_id, username) VALUES ($1, 'Deleted player')")
+            .bind(placeholder_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for statement in [
+            "UPDATE game_players SET user_id = $2 WHERE user_id = $1",
+            "UPDATE game_moves SET user_id = $2 WHERE user_id = $1",
+            "UPDATE games SET current_turn_player = $2 WHERE current_turn_player = $1",
+            r#"
+            UPDATE games
+            SET result = jsonb_set(
+                result,
+                '{winners}',
+                (
+                    SELECT jsonb_agg(CASE WHEN w = to_jsonb($1) THEN to_jsonb($2) ELSE w END)
+                    FROM jsonb_array_elements(result->'winners') w
+                )
+            )
+            WHERE result->'winners' @> jsonb_build_array($1)
+            "#,
+        ] {
+            sqlx::query(statement)
+                .bind(user_id)
+                .bind(placeholder_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE guild_settings SET updated_by = NULL WHERE updated_by = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        // These predate ON DELETE CASCADE; every later table cascades from users
+        for statement in [
+            "DELETE FROM adventure_progress WHERE user_id = $1",
+            "DELETE FROM leaderboard WHERE user_id = $1",
+            "DELETE FROM users WHERE user_id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(placeholder_id)
+    })
+    .await
+}
+
+// Puzzle queries
+
+/// Share a hand-crafted board
+pub async fn create_puzzle(
     pool: &PgPool,
-    user_id: i64,
-    guild_id: i64,
-) -> Result<Option<UserGuildProfile>> {
-    sqlx::query_as::<_, UserGuildProfile>(
-        "SELECT * FROM user_guild_profiles WHERE user_id = $1 AND guild_id = $2",
-    )
-    .bind(user_id)
-    .bind(guild_id)
-    .fetch_optional(pool)
+    author_id: i64,
+    title: &str,
+    grid: &Grid,
+) -> Result<Puzzle> {
+    let grid = serde_json::to_value(grid)
+        .map_err(|e| sqlx::Error::Protocol(format!("Failed to serialize grid: {}", e)))?;
+    timed("create_puzzle", async move {
+        sqlx::query_as(
+            r#"
+            WITH puzzle AS (
+                INSERT INTO puzzles (author_id, title, grid)
+                VALUES ($1, $2, $3)
+                RETURNING *
+            )
+            SELECT p.puzzle_id, p.author_id, u.username AS author_name, p.title, p.grid,
+                   p.featured_at, p.created_at
+            FROM puzzle p
+            JOIN users u ON u.user_id = p.author_id
+            "#,
+        )
+        .bind(author_id)
+        .bind(title)
+        .bind(grid)
+        .fetch_one(pool)
+        .await
+    })
     .await
 }
 
-#[allow(dead_code)]
-pub async fn create_or_update_guild_profile(
+pub async fn get_puzzle(pool: &PgPool, puzzle_id: i64) -> Result<Option<Puzzle>> {
+    timed("get_puzzle", async move {
+        sqlx::query_as(
+            r#"
+            SELECT p.puzzle_id, p.author_id, u.username AS author_name, p.title, p.grid,
+                   p.featured_at, p.created_at
+            FROM puzzles p
+            JOIN users u ON u.user_id = p.author_id
+            WHERE p.puzzle_id = $1
+            "#,
+        )
+        .bind(puzzle_id)
+        .fetch_optional(pool)
+        .await
+    })
+    .await
+}
+
+/// Puzzles newest first, or only featured ones, most recently featured first
+pub async fn list_puzzles(
+    pool: &PgPool,
+    featured: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Puzzle>> {
+    timed("list_puzzles", async move {
+        sqlx::query_as(
+            r#"
+            SELECT p.puzzle_id, p.author_id, u.username AS author_name, p.title, p.grid,
+                   p.featured_at, p.created_at
+            FROM puzzles p
+            JOIN users u ON u.user_id = p.author_id
+            WHERE NOT $1 OR p.featured_at IS NOT NULL
+            ORDER BY CASE WHEN $1 THEN p.featured_at END DESC, p.created_at DESC, p.puzzle_id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(featured)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Feature a puzzle (again), making it the puzzle of the week; false if it
+/// doesn't exist
+pub async fn feature_puzzle(pool: &PgPool, puzzle_id: i64) -> Result<bool> {
+    timed("feature_puzzle", async move {
+        let result = sqlx::query("UPDATE puzzles SET featured_at = NOW() WHERE puzzle_id = $1")
+            .bind(puzzle_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    })
+    .await
+}
+
+// API key queries
+
+/// Store a new API key for the user, by its hash
+pub async fn create_api_key(
     pool: &PgPool,
     user_id: i64,
-    guild_id: i64,
-    nickname: Option<&str>,
-) -> Result<UserGuildProfile> {
-    sqlx::query_as::<_, UserGuildProfile>(
-        r#"
-        INSERT INTO user_guild_profiles (user_id, guild_id, nickname)
-        VALUES ($1, $2, $3)
-        ON CONFLICT (user_id, guild_id)
-        DO UPDATE SET
-            nickname = $3,
-            updated_at = NOW()
-        RETURNING *
-        "#,
-    )
-    .bind(user_id)
-    .bind(guild_id)
-    .bind(nickname)
-    .fetch_one(pool)
+    name: &str,
+    key_prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+) -> Result<ApiKey> {
+    timed("create_api_key", async move {
+        sqlx::query_as(
+            r#"
+            INSERT INTO api_keys (user_id, name, key_prefix, key_hash, scopes)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING key_id, user_id, name, key_prefix, scopes, created_at, last_used_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .fetch_one(pool)
+        .await
+    })
+    .await
+}
+
+/// The user's API keys that haven't been revoked, newest first
+pub async fn list_api_keys(pool: &PgPool, user_id: i64) -> Result<Vec<ApiKey>> {
+    timed("list_api_keys", async move {
+        sqlx::query_as(
+            r#"
+            SELECT key_id, user_id, name, key_prefix, scopes, created_at, last_used_at
+            FROM api_keys
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Revoke one of the user's API keys; false if they have no such live key
+pub async fn revoke_api_key(pool: &PgPool, user_id: i64, key_id: i64) -> Result<bool> {
+    timed("revoke_api_key", async move {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE key_id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    })
+    .await
+}
+
+/// The live API key with this hash, marking it used
+pub async fn use_api_key(pool: &PgPool, key_hash: &str) -> Result<Option<ApiKey>> {
+    timed("use_api_key", async move {
+        sqlx::query_as(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            RETURNING key_id, user_id, name, key_prefix, scopes, created_at, last_used_at
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(pool)
+        .await
+    })
+    .await
+}
+
+// =============================================================================
+// Health Check Queries
+// =============================================================================
+
+/// Round-trip a trivial query to verify database connectivity
+pub async fn ping(pool: &PgPool) -> Result<()> {
+    timed("ping", async move {
+        sqlx::query("SELECT 1").execute(pool).await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Versions of all successfully applied migrations
+pub async fn get_applied_migration_versions(pool: &PgPool) -> Result<Vec<i64>> {
+    timed("get_applied_migration_versions", async move {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT version FROM _sqlx_migrations WHERE success = TRUE ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await
+    })
+    .await
+}
+
+/// Every row of the migrations table, including failed ones
+pub async fn list_applied_migrations(pool: &PgPool) -> Result<Vec<AppliedMigration>> {
+    timed("list_applied_migrations", async move {
+        sqlx::query_as::<_, AppliedMigration>(
+            r#"
+            SELECT version, description, installed_on, success, checksum, execution_time
+            FROM _sqlx_migrations
+            ORDER BY version
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    })
     .await
 }
 
@@ -781,20 +3490,29 @@ mod tests {
     }
 
     #[test]
-    fn test_encode_lobby_code_truncates_to_six_chars() {
-        // Codes longer than 6 characters should be truncated
-        let short = "ABC123";
-        let long = "ABC123XYZ";
+    fn test_encode_lobby_code_truncates_to_twelve_chars() {
+        // Codes longer than 12 characters should be truncated
+        let code = "ABC123XYZ789";
+        let longer = "ABC123XYZ789QQ";
 
-        let encoded_short = encode_lobby_code_to_i64(short);
-        let encoded_long = encode_lobby_code_to_i64(long);
+        let encoded = encode_lobby_code_to_i64(code);
+        let encoded_longer = encode_lobby_code_to_i64(longer);
 
         assert_eq!(
-            encoded_short, encoded_long,
-            "Codes should be truncated to 6 characters"
+            encoded, encoded_longer,
+            "Codes should be truncated to 12 characters"
         );
     }
 
+    #[test]
+    fn test_encode_long_lobby_codes_are_distinct() {
+        // Long (8 character) codes must not collide with their 6 character prefix
+        let short = encode_lobby_code_to_i64("ABC123");
+        let long = encode_lobby_code_to_i64("ABC123XY");
+        assert_ne!(short, long, "Long codes should encode all characters");
+        assert!(long < 0, "Long codes should still encode as negative");
+    }
+
     #[test]
     fn test_encode_lobby_code_special_chars_treated_as_zero() {
         // Special characters should be treated as 0
@@ -0,0 +1,133 @@
+use std::{
+    future::Future,
+    sync::{LazyLock, OnceLock},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serde::Serialize;
+
+/// Queries at least this slow are logged unless SLOW_QUERY_MS says otherwise
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(200);
+
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+static SLOW_QUERIES: LazyLock<SlowQueries> = LazyLock::new(SlowQueries::default);
+
+/// Set the slow query threshold; only the first call has any effect
+pub fn set_slow_query_threshold(threshold: Duration) {
+    let _ = SLOW_QUERY_THRESHOLD.set(threshold);
+}
+
+pub fn slow_query_threshold() -> Duration {
+    SLOW_QUERY_THRESHOLD
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD)
+}
+
+/// Slow queries seen since startup, by query function
+pub fn slow_query_report() -> Vec<SlowQueryStat> {
+    SLOW_QUERIES.report()
+}
+
+/// How often one query function has been slow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlowQueryStat {
+    pub query: &'static str,
+    pub count: u64,
+    pub slowest_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct SlowQueries {
+    by_query: DashMap<&'static str, (u64, Duration)>,
+}
+
+impl SlowQueries {
+    fn record(&self, query: &'static str, elapsed: Duration) {
+        let mut entry = self.by_query.entry(query).or_default();
+        entry.0 += 1;
+        entry.1 = entry.1.max(elapsed);
+    }
+
+    /// Most frequently slow first
+    fn report(&self) -> Vec<SlowQueryStat> {
+        let mut stats: Vec<SlowQueryStat> = self
+            .by_query
+            .iter()
+            .map(|entry| SlowQueryStat {
+                query: entry.key(),
+                count: entry.0,
+                slowest_ms: entry.1.as_millis() as u64,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.count.cmp(&a.count).then(a.query.cmp(b.query)));
+        stats
+    }
+}
+
+/// Run a query function, logging and counting it if it's slow
+///
+/// The warning is logged inside the caller's span, so queries made while handling
+/// a WebSocket message carry its request ID, lobby_id and game_id.
+pub async fn timed<T>(
+    query: &'static str,
+    fut: impl Future<Output = sqlx::Result<T>>,
+) -> sqlx::Result<T> {
+    let started = Instant::now();
+    let result = fut.await;
+    let elapsed = started.elapsed();
+    if elapsed >= slow_query_threshold() {
+        SLOW_QUERIES.record(query, elapsed);
+        tracing::warn!(
+            query,
+            elapsed_ms = elapsed.as_millis() as u64,
+            ok = result.is_ok(),
+            "Slow query"
+        );
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_and_orders_slow_queries() {
+        let slow = SlowQueries::default();
+        slow.record("get_user", Duration::from_millis(250));
+        slow.record("finish_game", Duration::from_millis(900));
+        slow.record("finish_game", Duration::from_millis(300));
+
+        assert_eq!(
+            slow.report(),
+            vec![
+                SlowQueryStat {
+                    query: "finish_game",
+                    count: 2,
+                    slowest_ms: 900,
+                },
+                SlowQueryStat {
+                    query: "get_user",
+                    count: 1,
+                    slowest_ms: 250,
+                },
+            ],
+            "Most frequently slow query first, keeping its slowest time"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timed_passes_results_through() {
+        let value = timed("fast_query", async { Ok(7) }).await;
+        assert_eq!(value.unwrap(), 7);
+        assert!(
+            !slow_query_report().iter().any(|s| s.query == "fast_query"),
+            "Fast queries aren't counted"
+        );
+
+        let error = timed::<()>("failing_query", async { Err(sqlx::Error::RowNotFound) }).await;
+        assert!(matches!(error, Err(sqlx::Error::RowNotFound)));
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use rust_embed::RustEmbed;
+
+use super::{
+    packs::{DictionaryPack, DictionaryPacks},
+    Dictionary, DictionaryBackend,
+};
+
+/// The default word list; `backend/dictionary.txt` must exist when compiling
+/// with the `embedded-dictionary` feature
+const WORD_LIST: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/dictionary.txt"));
+
+/// The bundled theme packs (only `<name>.txt` files become packs)
+#[derive(RustEmbed)]
+#[folder = "dictionaries/packs"]
+struct Packs;
+
+/// The compiled-in word list, used when DICTIONARY_PATH can't be read
+pub fn dictionary(backend: DictionaryBackend) -> Result<Dictionary> {
+    let dictionary = Dictionary::from_lists(WORD_LIST, "").with_backend(backend)?;
+    tracing::info!(
+        "Loaded {} words from the embedded dictionary ({:?} backend)",
+        dictionary.len(),
+        backend
+    );
+    Ok(dictionary)
+}
+
+/// The compiled-in theme packs, used when DICTIONARY_PACKS_DIR can't be read
+pub fn packs() -> DictionaryPacks {
+    let packs: Vec<DictionaryPack> = Packs::iter()
+        .filter_map(|path| {
+            let name = path.strip_suffix(".txt")?.to_lowercase();
+            let file = Packs::get(&path)?;
+            let content = String::from_utf8_lossy(&file.data);
+            Some(DictionaryPack::parse(name, &content))
+        })
+        .collect();
+    tracing::info!("Loaded {} embedded dictionary packs", packs.len());
+    DictionaryPacks::from_packs(packs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_packs_match_pack_directory() {
+        let packs = packs();
+        assert!(
+            packs.get("science").is_some(),
+            "Packs are keyed by file name like load_dir"
+        );
+        assert!(
+            packs.list().iter().all(|pack| !pack.is_empty()),
+            "Every bundled pack has words"
+        );
+        assert_eq!(
+            packs
+                .get("espanol")
+                .and_then(|pack| pack.language.as_deref()),
+            Some("es")
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::{collections::BTreeSet, fmt, path::PathBuf};
+
+use anyhow::{Context, Result};
+use tokio::fs;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Shortest word the server accepts (matches the loader, which skips single letters)
+pub const DEFAULT_MIN_LENGTH: usize = 2;
+/// Longest word that fits on the 5x5 board
+pub const DEFAULT_MAX_LENGTH: usize = 25;
+
+const USAGE: &str = "Usage: spell-cast-backend import-dictionary <input>... --output <path> \
+                     [--min-length <n>] [--max-length <n>]";
+
+/// Length limits applied while importing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportOptions {
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_LENGTH,
+            max_length: DEFAULT_MAX_LENGTH,
+        }
+    }
+}
+
+/// Why a raw line didn't make it into the word list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// Empty line or `#` comment
+    Blank,
+    TooShort,
+    TooLong,
+    /// Anything but A-Z after normalization (digits, apostrophes, spaces, ...)
+    DisallowedCharacters,
+}
+
+/// Counts reported after an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    pub lines: usize,
+    pub blank: usize,
+    pub too_short: usize,
+    pub too_long: usize,
+    pub disallowed: usize,
+    pub duplicates: usize,
+    /// Accepted words that had accents or other diacritics removed
+    pub diacritics_stripped: usize,
+    pub accepted: usize,
+}
+
+impl fmt::Display for ImportStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Lines read:            {}", self.lines)?;
+        writeln!(f, "Blank/comments:        {}", self.blank)?;
+        writeln!(f, "Too short:             {}", self.too_short)?;
+        writeln!(f, "Too long:              {}", self.too_long)?;
+        writeln!(f, "Disallowed characters: {}", self.disallowed)?;
+        writeln!(f, "Duplicates:            {}", self.duplicates)?;
+        writeln!(f, "Diacritics stripped:   {}", self.diacritics_stripped)?;
+        write!(f, "Words written:         {}", self.accepted)
+    }
+}
+
+/// Normalize one raw line into a dictionary word
+///
+/// Only the first tab-separated field is used, so lists with definitions or
+/// frequency columns import as-is. Diacritics are stripped (CAFÉ -> CAFE) and the
+/// word is uppercased; the bool is true if anything was stripped.
+pub fn normalize(line: &str, options: &ImportOptions) -> Result<(String, bool), Rejection> {
+    let field = line.split('\t').next().unwrap_or_default().trim();
+    if field.is_empty() || field.starts_with('#') {
+        return Err(Rejection::Blank);
+    }
+
+    let decomposed: String = field.nfd().collect();
+    let stripped: String = decomposed
+        .chars()
+        .filter(|c| !is_combining_mark(*c))
+        .collect();
+    let had_diacritics = stripped.len() != decomposed.len();
+
+    let word = stripped.to_uppercase();
+    if !word.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(Rejection::DisallowedCharacters);
+    }
+    if word.len() < options.min_length {
+        return Err(Rejection::TooShort);
+    }
+    if word.len() > options.max_length {
+        return Err(Rejection::TooLong);
+    }
+    Ok((word, had_diacritics))
+}
+
+/// Merges raw word lists into the canonical format the server loads
+pub struct Importer {
+    options: ImportOptions,
+    words: BTreeSet<String>,
+    stats: ImportStats,
+}
+
+impl Importer {
+    pub fn new(options: ImportOptions) -> Self {
+        Self {
+            options,
+            words: BTreeSet::new(),
+            stats: ImportStats::default(),
+        }
+    }
+
+    /// Add every line of a raw list (SOWPODS, ENABLE, a wiktionary title dump, ...)
+    pub fn add_list(&mut self, content: &str) {
+        for line in content.lines() {
+            self.stats.lines += 1;
+            match normalize(line, &self.options) {
+                Ok((word, had_diacritics)) => {
+                    if self.words.insert(word) {
+                        self.stats.accepted += 1;
+                        if had_diacritics {
+                            self.stats.diacritics_stripped += 1;
+                        }
+                    } else {
+                        self.stats.duplicates += 1;
+                    }
+                }
+                Err(Rejection::Blank) => self.stats.blank += 1,
+                Err(Rejection::TooShort) => self.stats.too_short += 1,
+                Err(Rejection::TooLong) => self.stats.too_long += 1,
+                Err(Rejection::DisallowedCharacters) => self.stats.disallowed += 1,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> ImportStats {
+        self.stats
+    }
+
+    /// Uppercase words, sorted, one per line
+    pub fn to_canonical(&self) -> String {
+        let mut output = String::with_capacity(self.words.len() * 8);
+        for word in &self.words {
+            output.push_str(word);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Parsed `import-dictionary` arguments
+#[derive(Debug, PartialEq, Eq)]
+struct ImportArgs {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    options: ImportOptions,
+}
+
+fn parse_args(args: &[String]) -> Result<ImportArgs> {
+    let mut inputs = Vec::new();
+    let mut output = None;
+    let mut options = ImportOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .with_context(|| format!("{} needs a value\n{}", flag, USAGE))
+        };
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(PathBuf::from(value(arg)?)),
+            "--min-length" => {
+                options.min_length = value(arg)?
+                    .parse()
+                    .context("--min-length must be a number")?
+            }
+            "--max-length" => {
+                options.max_length = value(arg)?
+                    .parse()
+                    .context("--max-length must be a number")?
+            }
+            flag if flag.starts_with('-') => anyhow::bail!("Unknown option {}\n{}", flag, USAGE),
+            path => inputs.push(PathBuf::from(path)),
+        }
+    }
+
+    anyhow::ensure!(!inputs.is_empty(), "No input word lists given\n{}", USAGE);
+    let output = output.with_context(|| format!("--output is required\n{}", USAGE))?;
+    anyhow::ensure!(
+        options.min_length <= options.max_length,
+        "--min-length can't be greater than --max-length"
+    );
+    Ok(ImportArgs {
+        inputs,
+        output,
+        options,
+    })
+}
+
+/// `spell-cast-backend import-dictionary`: normalize raw lists into a dictionary file
+pub async fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    let mut importer = Importer::new(args.options);
+    for input in &args.inputs {
+        let bytes = fs::read(input)
+            .await
+            .with_context(|| format!("Failed to read {}", input.display()))?;
+        // Some dumps are Latin-1; invalid bytes become U+FFFD and are rejected
+        importer.add_list(&String::from_utf8_lossy(&bytes));
+    }
+
+    fs::write(&args.output, importer.to_canonical())
+        .await
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    println!("{}", importer.stats());
+    println!("Wrote {}", args.output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_casing_and_diacritics() {
+        let options = ImportOptions::default();
+        assert_eq!(
+            normalize("  cat ", &options),
+            Ok(("CAT".to_string(), false))
+        );
+        assert_eq!(
+            normalize("Café", &options),
+            Ok(("CAFE".to_string(), true)),
+            "Accents are stripped"
+        );
+        assert_eq!(
+            normalize("straße", &options),
+            Ok(("STRASSE".to_string(), false)),
+            "Uppercasing expands the sharp s"
+        );
+        assert_eq!(
+            normalize("aa\tn. rough lava", &options),
+            Ok(("AA".to_string(), false)),
+            "Only the first tab-separated field is the word"
+        );
+    }
+
+    #[test]
+    fn test_normalize_rejections() {
+        let options = ImportOptions {
+            min_length: 3,
+            max_length: 5,
+        };
+        assert_eq!(normalize("", &options), Err(Rejection::Blank));
+        assert_eq!(normalize("# SOWPODS", &options), Err(Rejection::Blank));
+        assert_eq!(normalize("ab", &options), Err(Rejection::TooShort));
+        assert_eq!(normalize("abcdef", &options), Err(Rejection::TooLong));
+        for word in ["don't", "ice cream", "x-ray", "b52", "naïve😀"] {
+            assert_eq!(
+                normalize(word, &options),
+                Err(Rejection::DisallowedCharacters),
+                "{} has characters that can't be tiles",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn test_importer_deduplicates_and_sorts() {
+        let mut importer = Importer::new(ImportOptions::default());
+        importer.add_list("dog\ncat\n\nCafé\n");
+        importer.add_list("CAT\ncafe\nq\nit's\n");
+
+        assert_eq!(importer.to_canonical(), "CAFE\nCAT\nDOG\n");
+        assert_eq!(
+            importer.stats(),
+            ImportStats {
+                lines: 8,
+                blank: 1,
+                too_short: 1,
+                too_long: 0,
+                disallowed: 1,
+                duplicates: 2,
+                diacritics_stripped: 1,
+                accepted: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_canonical_output_loads_unchanged() {
+        let mut importer = Importer::new(ImportOptions::default());
+        importer.add_list("zebra\nÉclair\nox\n");
+        let dict = super::super::Dictionary::from_lists(&importer.to_canonical(), "");
+        assert_eq!(dict.len(), 3);
+        assert!(dict.contains("ECLAIR"));
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(&args(&[
+            "sowpods.txt",
+            "enable.txt",
+            "-o",
+            "dictionary.txt",
+            "--max-length",
+            "15",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            ImportArgs {
+                inputs: vec![PathBuf::from("sowpods.txt"), PathBuf::from("enable.txt")],
+                output: PathBuf::from("dictionary.txt"),
+                options: ImportOptions {
+                    min_length: DEFAULT_MIN_LENGTH,
+                    max_length: 15,
+                },
+            }
+        );
+
+        assert!(
+            parse_args(&args(&["in.txt"])).is_err(),
+            "Output is required"
+        );
+        assert!(
+            parse_args(&args(&["-o", "out.txt"])).is_err(),
+            "Needs an input"
+        );
+        assert!(parse_args(&args(&["in.txt", "-o"])).is_err());
+        assert!(parse_args(&args(&["in.txt", "-o", "out.txt", "--bogus"])).is_err());
+        assert!(parse_args(&args(&[
+            "in.txt",
+            "-o",
+            "out.txt",
+            "--min-length",
+            "9",
+            "--max-length",
+            "3"
+        ]))
+        .is_err());
+    }
+}
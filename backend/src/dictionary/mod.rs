@@ -1,50 +1,288 @@
-use std::{collections::HashSet, path::Path};
+#[cfg(feature = "embedded-dictionary")]
+pub mod embedded;
+pub mod import;
+pub mod packs;
+
+use std::{collections::HashMap, path::Path, str::FromStr, sync::OnceLock};
 
 use anyhow::Result;
+use fst::{IntoStreamer, Streamer};
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 
+/// How common a dictionary word is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordTier {
+    /// Everyday words from the common list
+    Common,
+    /// Premium/obscure words that only appear in the extended list
+    Extended,
+}
+
+/// Which dictionary tiers a lobby accepts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictionaryStrictness {
+    /// Only common words are playable
+    Common,
+    /// Common and extended words are playable
+    #[default]
+    Extended,
+}
+
+impl DictionaryStrictness {
+    /// Check whether a word of the given tier is playable at this strictness
+    pub fn accepts(self, tier: WordTier) -> bool {
+        match self {
+            Self::Common => tier == WordTier::Common,
+            Self::Extended => true,
+        }
+    }
+}
+
+/// Per-word dictionary metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordInfo {
+    pub tier: WordTier,
+}
+
+impl WordInfo {
+    /// Encoding of the tier as an FST value
+    fn to_fst_value(self) -> u64 {
+        match self.tier {
+            WordTier::Common => 0,
+            WordTier::Extended => 1,
+        }
+    }
+
+    fn from_fst_value(value: u64) -> Self {
+        let tier = match value {
+            0 => WordTier::Common,
+            _ => WordTier::Extended,
+        };
+        Self { tier }
+    }
+}
+
+/// How the word list is stored in memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictionaryBackend {
+    /// One heap-allocated String per word; fastest to build
+    #[default]
+    Hash,
+    /// Finite state transducer (`fst` crate); a fraction of the memory, with prefix search
+    Fst,
+}
+
+impl FromStr for DictionaryBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "hash" | "hashmap" | "hashset" => Ok(Self::Hash),
+            "fst" => Ok(Self::Fst),
+            other => anyhow::bail!("Unknown dictionary backend: {}", other),
+        }
+    }
+}
+
+enum WordStore {
+    Hash(HashMap<String, WordInfo>),
+    Fst(fst::Map<Vec<u8>>),
+}
+
 pub struct Dictionary {
-    words: HashSet<String>,
+    words: WordStore,
 }
 
 impl Dictionary {
-    /// Load dictionary from a file
-    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(path).await?;
-        let words: HashSet<String> = content
-            .lines()
-            .map(|line| line.trim().to_uppercase())
-            .filter(|word| !word.is_empty() && word.len() >= 2)
-            .collect();
+    /// Load the common word list, plus an optional extended list of obscure words
+    pub async fn load<P: AsRef<Path>>(
+        common_path: P,
+        extended_path: Option<P>,
+        backend: DictionaryBackend,
+    ) -> Result<Self> {
+        let common = fs::read_to_string(common_path).await?;
+        let extended = match extended_path {
+            Some(path) => fs::read_to_string(path).await?,
+            None => String::new(),
+        };
 
-        tracing::info!("Loaded {} words into dictionary", words.len());
+        let dictionary = Self::from_lists(&common, &extended).with_backend(backend)?;
+        tracing::info!(
+            "Loaded {} words into dictionary ({} common), using ~{} KiB ({:?} backend)",
+            dictionary.len(),
+            dictionary.common_len(),
+            dictionary.memory_usage() / 1024,
+            backend
+        );
 
+        Ok(dictionary)
+    }
+
+    /// Build a dictionary from newline-separated word lists.
+    /// Words on both lists count as common.
+    pub fn from_lists(common: &str, extended: &str) -> Self {
+        let mut words = HashMap::new();
+        for word in parse_words(extended) {
+            words.insert(
+                word,
+                WordInfo {
+                    tier: WordTier::Extended,
+                },
+            );
+        }
+        for word in parse_words(common) {
+            words.insert(
+                word,
+                WordInfo {
+                    tier: WordTier::Common,
+                },
+            );
+        }
+        Self {
+            words: WordStore::Hash(words),
+        }
+    }
+
+    /// Convert to the given storage backend
+    pub fn with_backend(self, backend: DictionaryBackend) -> Result<Self> {
+        let words = match (self.words, backend) {
+            (WordStore::Hash(words), DictionaryBackend::Fst) => {
+                // FST keys must be inserted in lexicographic order
+                let mut entries: Vec<(String, WordInfo)> = words.into_iter().collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                let map = fst::Map::from_iter(
+                    entries
+                        .into_iter()
+                        .map(|(word, info)| (word, info.to_fst_value())),
+                )?;
+                WordStore::Fst(map)
+            }
+            (words, _) => words,
+        };
         Ok(Self { words })
     }
 
     /// Create an empty dictionary (for testing)
     pub fn empty() -> Self {
         Self {
-            words: HashSet::new(),
+            words: WordStore::Hash(HashMap::new()),
         }
     }
 
-    /// Check if a word exists in the dictionary
+    /// Check if a word exists in the dictionary (in any tier)
     pub fn contains(&self, word: &str) -> bool {
-        self.words.contains(&word.to_uppercase())
+        self.lookup(word).is_some()
+    }
+
+    /// Metadata for a word, if it's in the dictionary
+    pub fn lookup(&self, word: &str) -> Option<WordInfo> {
+        let word = word.to_uppercase();
+        match &self.words {
+            WordStore::Hash(words) => words.get(&word).copied(),
+            WordStore::Fst(map) => map.get(&word).map(WordInfo::from_fst_value),
+        }
+    }
+
+    /// Check if any dictionary word starts with `prefix`
+    ///
+    /// Linear in the word count for the hash backend; the FST answers with one seek.
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        let prefix = prefix.to_uppercase();
+        match &self.words {
+            WordStore::Hash(words) => words.keys().any(|word| word.starts_with(&prefix)),
+            WordStore::Fst(map) => {
+                let mut stream = map.range().ge(&prefix).into_stream();
+                stream
+                    .next()
+                    .is_some_and(|(word, _)| word.starts_with(prefix.as_bytes()))
+            }
+        }
     }
 
     /// Get the number of words in the dictionary
     pub fn len(&self) -> usize {
-        self.words.len()
+        match &self.words {
+            WordStore::Hash(words) => words.len(),
+            WordStore::Fst(map) => map.len(),
+        }
+    }
+
+    /// Get the number of common-tier words
+    pub fn common_len(&self) -> usize {
+        match &self.words {
+            WordStore::Hash(words) => words
+                .values()
+                .filter(|info| info.tier == WordTier::Common)
+                .count(),
+            WordStore::Fst(map) => {
+                let mut stream = map.stream();
+                let mut count = 0;
+                while let Some((_, value)) = stream.next() {
+                    if WordInfo::from_fst_value(value).tier == WordTier::Common {
+                        count += 1;
+                    }
+                }
+                count
+            }
+        }
     }
 
     /// Check if dictionary is empty
     pub fn is_empty(&self) -> bool {
-        self.words.is_empty()
+        self.len() == 0
+    }
+
+    /// Approximate heap memory used by the word list, in bytes
+    pub fn memory_usage(&self) -> usize {
+        match &self.words {
+            WordStore::Hash(words) => {
+                // Each bucket holds the key's String header and the value, plus a
+                // control byte; the key's characters live in their own allocation
+                let bucket = std::mem::size_of::<(String, WordInfo)>() + 1;
+                let keys: usize = words.keys().map(|word| word.capacity()).sum();
+                words.capacity() * bucket + keys
+            }
+            WordStore::Fst(map) => map.as_fst().as_bytes().len(),
+        }
     }
 }
 
+/// The server's dictionary, filled in by a background load after startup so the
+/// listener can bind without waiting on a large word list
+#[derive(Default)]
+pub struct SharedDictionary {
+    dictionary: OnceLock<Dictionary>,
+}
+
+impl SharedDictionary {
+    /// The dictionary, or None while it's still loading
+    pub fn get(&self) -> Option<&Dictionary> {
+        self.dictionary.get()
+    }
+
+    /// Install the loaded dictionary; later calls are ignored
+    pub fn set(&self, dictionary: Dictionary) {
+        if self.dictionary.set(dictionary).is_err() {
+            tracing::warn!("Dictionary was already loaded; ignoring reload");
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.dictionary.get().is_none()
+    }
+}
+
+fn parse_words(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .lines()
+        .map(|line| line.trim().to_uppercase())
+        .filter(|word| !word.is_empty() && word.len() >= 2)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +293,110 @@ mod tests {
         assert!(dict.is_empty());
         assert!(!dict.contains("TEST"));
     }
+
+    #[test]
+    fn test_word_tiers() {
+        let dict = Dictionary::from_lists("cat\ndog\n", "qat\ndog\nx\n");
+
+        assert_eq!(dict.len(), 3, "Single letters are skipped");
+        assert_eq!(dict.common_len(), 2);
+        assert_eq!(
+            dict.lookup("Cat").map(|info| info.tier),
+            Some(WordTier::Common)
+        );
+        assert_eq!(
+            dict.lookup("qat").map(|info| info.tier),
+            Some(WordTier::Extended)
+        );
+        assert_eq!(
+            dict.lookup("DOG").map(|info| info.tier),
+            Some(WordTier::Common),
+            "Words on both lists count as common"
+        );
+        assert!(dict.lookup("zzz").is_none());
+    }
+
+    #[test]
+    fn test_fst_backend_matches_hash_backend() {
+        let common = "cat\ncater\ndog\n";
+        let extended = "qat\nzax\n";
+        let hash = Dictionary::from_lists(common, extended);
+        let fst = Dictionary::from_lists(common, extended)
+            .with_backend(DictionaryBackend::Fst)
+            .unwrap();
+
+        for word in ["cat", "CATER", "dog", "qat", "zax", "ca", "cats", "zzz"] {
+            assert_eq!(
+                hash.lookup(word),
+                fst.lookup(word),
+                "Backends disagree on {}",
+                word
+            );
+        }
+        assert_eq!(fst.len(), 5);
+        assert_eq!(fst.common_len(), 3);
+        assert!(fst.memory_usage() > 0);
+    }
+
+    #[test]
+    fn test_has_prefix() {
+        for backend in [DictionaryBackend::Hash, DictionaryBackend::Fst] {
+            let dict = Dictionary::from_lists("cater\ndog\n", "")
+                .with_backend(backend)
+                .unwrap();
+            assert!(dict.has_prefix("cat"), "{:?}: prefix of a word", backend);
+            assert!(dict.has_prefix("CATER"), "{:?}: a whole word", backend);
+            assert!(
+                !dict.has_prefix("caterpillar"),
+                "{:?}: longer than any word",
+                backend
+            );
+            assert!(!dict.has_prefix("do g"), "{:?}: no match", backend);
+            assert!(!dict.has_prefix("e"), "{:?}: between words", backend);
+        }
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!(
+            "FST".parse::<DictionaryBackend>().unwrap(),
+            DictionaryBackend::Fst
+        );
+        assert_eq!(
+            "hashset".parse::<DictionaryBackend>().unwrap(),
+            DictionaryBackend::Hash
+        );
+        assert!("trie".parse::<DictionaryBackend>().is_err());
+    }
+
+    #[test]
+    fn test_shared_dictionary_loading() {
+        let shared = SharedDictionary::default();
+        assert!(shared.is_loading());
+        assert!(shared.get().is_none(), "Nothing to validate against yet");
+
+        shared.set(Dictionary::from_lists("cat\n", ""));
+        assert!(!shared.is_loading());
+        assert!(shared.get().unwrap().contains("CAT"));
+
+        shared.set(Dictionary::empty());
+        assert!(
+            shared.get().unwrap().contains("CAT"),
+            "The first loaded dictionary is kept"
+        );
+    }
+
+    #[test]
+    fn test_strictness_accepts_tiers() {
+        assert!(DictionaryStrictness::Common.accepts(WordTier::Common));
+        assert!(
+            !DictionaryStrictness::Common.accepts(WordTier::Extended),
+            "Strict lobbies reject obscure words"
+        );
+        assert!(DictionaryStrictness::Extended.accepts(WordTier::Extended));
+        assert_eq!(
+            DictionaryStrictness::default(),
+            DictionaryStrictness::Extended
+        );
+    }
 }
@@ -0,0 +1,296 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use super::{parse_words, Dictionary, WordInfo, WordTier};
+use crate::models::guild_settings::is_valid_language_tag;
+
+/// Pack of names (places, people, brands) that lobbies allowing proper nouns
+/// accept; it isn't offered as a theme
+pub const PROPER_NOUNS_PACK: &str = "proper_nouns";
+
+/// How a lobby's theme pack combines with the base dictionary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackMode {
+    /// Pack words are playable in addition to the base dictionary
+    #[default]
+    Layer,
+    /// Only pack words are playable
+    Replace,
+}
+
+/// A lobby's chosen theme pack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSelection {
+    pub name: String,
+    pub mode: PackMode,
+}
+
+/// A themed word list (e.g., science, gaming, kid-friendly) or a dictionary in
+/// another language
+pub struct DictionaryPack {
+    pub name: String,
+    /// From a leading `# ` comment line in the pack file
+    pub description: Option<String>,
+    /// From a leading `# language: <tag>` line; lobbies using the pack get their
+    /// messages in this language
+    pub language: Option<String>,
+    words: HashSet<String>,
+}
+
+impl DictionaryPack {
+    /// Parse a pack file; it may start with a `# description` comment and a
+    /// `# language: <tag>` line, in either order
+    pub fn parse(name: String, content: &str) -> Self {
+        let mut description = None;
+        let mut language = None;
+        let header = content
+            .lines()
+            .map_while(|line| line.trim().strip_prefix('#'))
+            .map(str::trim);
+        for line in header {
+            match line.strip_prefix("language:").map(str::trim) {
+                Some(tag) => {
+                    language = Some(tag)
+                        .filter(|tag| is_valid_language_tag(tag))
+                        .map(String::from);
+                }
+                None if description.is_none() => {
+                    description = Some(line.to_string()).filter(|line| !line.is_empty());
+                }
+                None => {}
+            }
+        }
+        let words = parse_words(content)
+            .filter(|word| !word.starts_with('#'))
+            .collect();
+        Self {
+            name,
+            description,
+            language,
+            words,
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_uppercase())
+    }
+
+    /// Check if any pack word starts with `prefix` (linear in the pack size)
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        let prefix = prefix.to_uppercase();
+        self.words.iter().any(|word| word.starts_with(&prefix))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// All theme packs available to hosts, keyed by name
+#[derive(Default)]
+pub struct DictionaryPacks {
+    packs: HashMap<String, DictionaryPack>,
+}
+
+impl DictionaryPacks {
+    /// Load every `<name>.txt` file in a directory as a pack called `<name>`
+    pub async fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut packs = HashMap::new();
+        let mut entries = fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("txt") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let content = fs::read_to_string(&path).await?;
+            let pack = DictionaryPack::parse(name.to_lowercase(), &content);
+            packs.insert(pack.name.clone(), pack);
+        }
+
+        tracing::info!("Loaded {} dictionary packs", packs.len());
+        Ok(Self { packs })
+    }
+
+    /// Build from already-parsed packs (embedded packs and tests)
+    pub fn from_packs(packs: Vec<DictionaryPack>) -> Self {
+        Self {
+            packs: packs.into_iter().map(|p| (p.name.clone(), p)).collect(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DictionaryPack> {
+        self.packs.get(name)
+    }
+
+    /// A pack hosts can pick as their lobby's theme
+    pub fn theme(&self, name: &str) -> Option<&DictionaryPack> {
+        self.get(name).filter(|pack| pack.name != PROPER_NOUNS_PACK)
+    }
+
+    /// All theme packs, sorted by name
+    pub fn list(&self) -> Vec<&DictionaryPack> {
+        let mut packs: Vec<&DictionaryPack> = self
+            .packs
+            .values()
+            .filter(|pack| pack.name != PROPER_NOUNS_PACK)
+            .collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        packs
+    }
+
+    pub fn len(&self) -> usize {
+        self.packs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packs.is_empty()
+    }
+
+    /// Look a word up in the base dictionary combined with a lobby's pack.
+    /// Pack words always count as common. A selection naming a pack that no
+    /// longer exists falls back to the base dictionary.
+    pub fn lookup(
+        &self,
+        base: &Dictionary,
+        selection: Option<&PackSelection>,
+        word: &str,
+    ) -> Option<WordInfo> {
+        let Some((pack, mode)) = selection.and_then(|s| Some((self.get(&s.name)?, s.mode))) else {
+            return base.lookup(word);
+        };
+
+        if pack.contains(word) {
+            return Some(WordInfo {
+                tier: WordTier::Common,
+            });
+        }
+        match mode {
+            PackMode::Layer => base.lookup(word),
+            PackMode::Replace => None,
+        }
+    }
+
+    /// Look a word up in the proper noun list; names count as common
+    pub fn lookup_proper_noun(&self, word: &str) -> Option<WordInfo> {
+        self.get(PROPER_NOUNS_PACK)
+            .filter(|pack| pack.contains(word))
+            .map(|_| WordInfo {
+                tier: WordTier::Common,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_packs() -> DictionaryPacks {
+        DictionaryPacks::from_packs(vec![DictionaryPack::parse(
+            "science".to_string(),
+            "# Lab and space words\nquark\nneutron\n",
+        )])
+    }
+
+    fn select(mode: PackMode) -> PackSelection {
+        PackSelection {
+            name: "science".to_string(),
+            mode,
+        }
+    }
+
+    #[test]
+    fn test_pack_parsing() {
+        let packs = test_packs();
+        let pack = packs.get("science").unwrap();
+        assert_eq!(pack.description.as_deref(), Some("Lab and space words"));
+        assert_eq!(pack.len(), 2, "The description line is not a word");
+        assert!(pack.contains("Quark"));
+        assert_eq!(pack.language, None);
+    }
+
+    #[test]
+    fn test_pack_language_header() {
+        let pack = DictionaryPack::parse(
+            "espanol".to_string(),
+            "# language: es\n# Palabras en español\ngato\nperro\n",
+        );
+        assert_eq!(pack.language.as_deref(), Some("es"));
+        assert_eq!(pack.description.as_deref(), Some("Palabras en español"));
+        assert_eq!(pack.len(), 2, "Header lines are not words");
+
+        let pack = DictionaryPack::parse("bad".to_string(), "# Mots\n# language: fr FR!\nchat\n");
+        assert_eq!(pack.language, None, "Malformed tags are ignored");
+        assert_eq!(pack.description.as_deref(), Some("Mots"));
+    }
+
+    #[test]
+    fn test_layered_pack_adds_words() {
+        let base = Dictionary::from_lists("cat\n", "qat\n");
+        let packs = test_packs();
+        let layer = select(PackMode::Layer);
+
+        assert!(packs.lookup(&base, Some(&layer), "quark").is_some());
+        assert!(
+            packs.lookup(&base, Some(&layer), "cat").is_some(),
+            "Base words stay playable"
+        );
+        assert_eq!(
+            packs.lookup(&base, Some(&layer), "qat").map(|i| i.tier),
+            Some(WordTier::Extended)
+        );
+        assert!(packs.lookup(&base, None, "quark").is_none());
+    }
+
+    #[test]
+    fn test_replacing_pack_restricts_words() {
+        let base = Dictionary::from_lists("cat\n", "");
+        let packs = test_packs();
+        let replace = select(PackMode::Replace);
+
+        assert!(packs.lookup(&base, Some(&replace), "neutron").is_some());
+        assert!(
+            packs.lookup(&base, Some(&replace), "cat").is_none(),
+            "Only pack words are playable when the pack replaces the base list"
+        );
+
+        let missing = PackSelection {
+            name: "removed".to_string(),
+            mode: PackMode::Replace,
+        };
+        assert!(
+            packs.lookup(&base, Some(&missing), "cat").is_some(),
+            "A missing pack falls back to the base dictionary"
+        );
+    }
+
+    #[test]
+    fn test_proper_nouns_are_not_a_theme() {
+        let packs = DictionaryPacks::from_packs(vec![
+            DictionaryPack::parse("science".to_string(), "quark\n"),
+            DictionaryPack::parse(PROPER_NOUNS_PACK.to_string(), "# Names\nparis\n"),
+        ]);
+        assert!(packs.lookup_proper_noun("Paris").is_some());
+        assert!(packs.lookup_proper_noun("quark").is_none());
+        assert!(packs.theme(PROPER_NOUNS_PACK).is_none());
+        assert!(packs.theme("science").is_some());
+        assert_eq!(
+            packs.list().len(),
+            1,
+            "Hosts only see the theme packs to choose from"
+        );
+    }
+}
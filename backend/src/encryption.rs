@@ -1,12 +1,26 @@
+use std::{fmt, marker::PhantomData, str::FromStr};
+
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
 
-/// Encrypts data using AES-256-GCM
-pub fn encrypt(data: &str, key: &str) -> Result<String> {
+/// Header of ciphertext written by this build: AES-256-GCM, 12-byte nonce first
+///
+/// Ciphertext without a header predates versioning and uses the same scheme.
+/// A new algorithm gets a new header, and `decrypt` keeps reading the old ones.
+const CURRENT_VERSION: &str = "v1";
+const NONCE_LEN: usize = 12;
+
+fn cipher(key: &str) -> Result<Aes256Gcm> {
     // Decode the base64-encoded key
     let key_bytes = BASE64
         .decode(key)
@@ -16,51 +30,45 @@ pub fn encrypt(data: &str, key: &str) -> Result<String> {
         anyhow::bail!("Encryption key must be 32 bytes");
     }
 
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes).context("Failed to create cipher")?;
+    Aes256Gcm::new_from_slice(&key_bytes).context("Failed to create cipher")
+}
 
-    // Generate a random 12-byte nonce
-    let nonce_bytes = aes_gcm::aead::rand_core::RngCore::next_u64(&mut OsRng);
-    let nonce_bytes2 = aes_gcm::aead::rand_core::RngCore::next_u32(&mut OsRng);
-    let mut nonce_array = [0u8; 12];
-    nonce_array[0..8].copy_from_slice(&nonce_bytes.to_le_bytes());
-    nonce_array[8..12].copy_from_slice(&nonce_bytes2.to_le_bytes());
-    let nonce = Nonce::from_slice(&nonce_array);
+/// Encrypts data using AES-256-GCM, as `v1:<base64 of nonce + ciphertext>`
+pub fn encrypt(data: &str, key: &str) -> Result<String> {
+    let cipher = cipher(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
 
-    // Encrypt the data
     let ciphertext = cipher
-        .encrypt(nonce, data.as_bytes())
+        .encrypt(&nonce, data.as_bytes())
         .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
 
     // Prepend nonce to ciphertext and encode as base64
-    let mut result = nonce_array.to_vec();
+    let mut result = nonce.to_vec();
     result.extend_from_slice(&ciphertext);
-    Ok(BASE64.encode(result))
+    Ok(format!("{}:{}", CURRENT_VERSION, BASE64.encode(result)))
 }
 
-/// Decrypts data using AES-256-GCM
+/// Decrypts data written by `encrypt`, in any version this build knows
 pub fn decrypt(encrypted_data: &str, key: &str) -> Result<String> {
-    // Decode the base64-encoded key
-    let key_bytes = BASE64
-        .decode(key)
-        .context("Failed to decode encryption key")?;
-
-    if key_bytes.len() != 32 {
-        anyhow::bail!("Encryption key must be 32 bytes");
-    }
-
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes).context("Failed to create cipher")?;
+    // Base64 never contains ':', so unversioned data can't be mistaken for a header
+    let body = match encrypted_data.split_once(':') {
+        Some((CURRENT_VERSION, body)) => body,
+        Some((version, _)) => anyhow::bail!("Unknown ciphertext version: {}", version),
+        None => encrypted_data,
+    };
+    let cipher = cipher(key)?;
 
     // Decode the base64-encoded encrypted data
     let encrypted_bytes = BASE64
-        .decode(encrypted_data)
+        .decode(body)
         .context("Failed to decode encrypted data")?;
 
-    if encrypted_bytes.len() < 12 {
+    if encrypted_bytes.len() < NONCE_LEN {
         anyhow::bail!("Invalid encrypted data: too short");
     }
 
     // Extract nonce and ciphertext
-    let (nonce_bytes, ciphertext) = encrypted_bytes.split_at(12);
+    let (nonce_bytes, ciphertext) = encrypted_bytes.split_at(NONCE_LEN);
     let nonce = Nonce::from_slice(nonce_bytes);
 
     // Decrypt the data
@@ -71,6 +79,79 @@ pub fn decrypt(encrypted_data: &str, key: &str) -> Result<String> {
     String::from_utf8(plaintext).context("Failed to convert decrypted data to string")
 }
 
+/// A value stored encrypted at rest, such as a refresh token or other PII
+///
+/// Holds only the ciphertext, so the plaintext can't end up in a query or a log
+/// by accident. Binds to and decodes from a TEXT column.
+pub struct Encrypted<T> {
+    ciphertext: String,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Encrypted<T> {
+    /// Wrap ciphertext read from the database
+    pub fn from_ciphertext(ciphertext: String) -> Self {
+        Self {
+            ciphertext,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Display> Encrypted<T> {
+    pub fn seal(value: &T, key: &str) -> Result<Self> {
+        Ok(Self::from_ciphertext(encrypt(&value.to_string(), key)?))
+    }
+}
+
+impl<T> Encrypted<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    pub fn open(&self, key: &str) -> Result<T> {
+        decrypt(&self.ciphertext, key)?
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse decrypted value: {}", e))
+    }
+}
+
+impl<T> Clone for Encrypted<T> {
+    fn clone(&self) -> Self {
+        Self::from_ciphertext(self.ciphertext.clone())
+    }
+}
+
+impl<T> fmt::Debug for Encrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Encrypted(..)")
+    }
+}
+
+impl<T> Type<Postgres> for Encrypted<T> {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<T> Encode<'_, Postgres> for Encrypted<T> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <String as Encode<Postgres>>::encode_by_ref(&self.ciphertext, buf)
+    }
+}
+
+impl<T> Decode<'_, Postgres> for Encrypted<T> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        Ok(Self::from_ciphertext(<String as Decode<Postgres>>::decode(
+            value,
+        )?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,4 +183,38 @@ mod tests {
         assert_eq!(decrypt(&encrypted1, &key).unwrap(), original);
         assert_eq!(decrypt(&encrypted2, &key).unwrap(), original);
     }
+
+    #[test]
+    fn test_ciphertext_versions() {
+        let key = BASE64.encode([0u8; 32]);
+        let encrypted = encrypt("token", &key).unwrap();
+        assert!(encrypted.starts_with("v1:"));
+
+        let unversioned = encrypted.trim_start_matches("v1:");
+        assert_eq!(
+            decrypt(unversioned, &key).unwrap(),
+            "token",
+            "Ciphertext from before version headers still decrypts"
+        );
+        assert!(
+            decrypt(&format!("v9:{}", unversioned), &key).is_err(),
+            "Unknown versions are refused rather than guessed at"
+        );
+    }
+
+    #[test]
+    fn test_encrypted_wrapper() {
+        let key = BASE64.encode([7u8; 32]);
+        let sealed = Encrypted::seal(&"refresh".to_string(), &key).unwrap();
+        assert_eq!(sealed.open(&key).unwrap(), "refresh");
+        assert!(sealed.open(&BASE64.encode([8u8; 32])).is_err());
+        assert_eq!(
+            format!("{:?}", sealed),
+            "Encrypted(..)",
+            "Debug output never shows the ciphertext"
+        );
+
+        let sealed = Encrypted::<i64>::seal(&42, &key).unwrap();
+        assert_eq!(sealed.clone().open(&key).unwrap(), 42);
+    }
 }
@@ -0,0 +1,566 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tokio::sync::mpsc::error::SendError;
+
+use crate::{
+    game::validator::PathError,
+    locale,
+    websocket::{messages::ServerMessage, outbound::OutboundMessage},
+};
+
+/// Crate-wide error type for HTTP handlers and WebSocket message handling
+///
+/// Every variant maps to a stable, machine-readable `code()` that clients can branch
+/// on. The `Display` text is the human-readable message and never includes internal
+/// details; those are only logged.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    // Authentication
+    #[error("Authentication required")]
+    Unauthorized,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Avatar not found")]
+    AvatarNotFound,
+    #[error("Discord API request failed")]
+    DiscordApi,
+    #[error("Invite link is invalid or has expired")]
+    InvalidInvite,
+    #[error("Sign-in request is invalid or has expired, please try again")]
+    InvalidOAuthState,
+    #[error("Authorization code was already used")]
+    AuthorizationCodeReused,
+    #[error("This account is scheduled for deletion; use your restore link to keep it")]
+    AccountPendingDeletion,
+    #[error("Restore link is invalid or has expired")]
+    InvalidRestoreToken,
+    #[error("Invalid user ID")]
+    InvalidUserId,
+    #[error("Admin access required")]
+    NotAdmin,
+    #[error("API key is invalid or has been revoked")]
+    InvalidApiKey,
+    /// The scope (e.g., "read:games") the API key would need
+    #[error("This API key doesn't have the {0} scope")]
+    MissingApiScope(&'static str),
+    #[error("API key not found")]
+    ApiKeyNotFound,
+    #[error("Guest access is not enabled on this server")]
+    GuestAccessDisabled,
+    /// The action (e.g., "buy items") needs a Discord account
+    #[error("Guests can't {0}, sign in with Discord first")]
+    Guest(&'static str),
+    #[error("You need the Manage Server permission to do that")]
+    MissingGuildPermission,
+    #[error("Origin not allowed")]
+    OriginNotAllowed,
+    #[error("Too many open connections")]
+    TooManyConnections,
+    #[error("Too many requests, please slow down")]
+    RateLimited,
+
+    // Lobby
+    #[error("Lobby not found")]
+    LobbyNotFound,
+    #[error("Lobby with code '{0}' not found")]
+    LobbyCodeNotFound(String),
+    #[error("You must be in a lobby to do that")]
+    NotInLobby,
+    #[error("You are not a member of this lobby")]
+    NotLobbyMember,
+    #[error("This lobby is full")]
+    LobbyFull,
+    #[error("Could not allocate a lobby code, please try again")]
+    LobbyCodesExhausted,
+    #[error("Tables are only available in channel lobbies")]
+    NotInChannelLobby,
+    #[error("Invites are only available in custom lobbies")]
+    NotInCustomLobby,
+    #[error("Table not found")]
+    TableNotFound,
+    #[error("This channel already has the maximum number of tables")]
+    TooManyTables,
+    /// The action (e.g., "start the game") is reserved for the lobby host
+    #[error("Only the lobby host can {0}")]
+    NotHost(&'static str),
+    /// The action (e.g., "submit words") needs a player connection
+    #[error("Spectators can't {0}")]
+    Spectating(&'static str),
+
+    // Game
+    #[error("A game is already in progress or starting in this lobby")]
+    GameInProgress,
+    #[error("At least 2 players are required to start a game (currently {0})")]
+    NotEnoughPlayers(usize),
+    #[error("Maximum 6 players allowed (currently {0})")]
+    TooManyPlayers(usize),
+    #[error("Duels are played by exactly 2 players (currently {0})")]
+    DuelNeedsTwoPlayers(usize),
+    #[error("Puzzle rushes are played alone (currently {0} players)")]
+    PuzzleRushIsSolo(usize),
+    #[error("Local players need different names of 1-32 characters")]
+    InvalidLocalPlayers,
+    #[error("This game mode is disabled for this server")]
+    ModeNotAllowed,
+    #[error("This game mode can't be started from a lobby yet")]
+    UnsupportedMode,
+    #[error("Co-op target score must be positive")]
+    InvalidCoopTarget,
+    #[error("Games can only be scheduled to start within the next 24 hours")]
+    InvalidScheduleTime,
+    #[error("No game is scheduled in this lobby")]
+    NoScheduledGame,
+    #[error("No active game in this lobby")]
+    NoActiveGame,
+    #[error("Game not found")]
+    GameNotFound,
+    #[error("This game isn't over yet")]
+    GameNotFinished,
+    #[error("Season not found")]
+    SeasonNotFound,
+    #[error("Puzzle not found")]
+    PuzzleNotFound,
+    #[error("This channel has no game night")]
+    GameNightNotFound,
+    #[error("The dictionary is still loading, please try again shortly")]
+    DictionaryLoading,
+    #[error("Dictionary pack not found")]
+    DictionaryPackNotFound,
+    #[error("That word is only allowed with the extended dictionary")]
+    WordTooObscure,
+    #[error("Word is too short for this lobby's rules")]
+    WordTooShort,
+    #[error("Solving this board takes a word worth at least {0} points")]
+    BelowRushTarget(i32),
+    #[error("Solo games need the board_checksum of the board the word was found on")]
+    BoardChecksumRequired,
+    #[error("The board has changed since this word was found")]
+    StaleBoard,
+    #[error("More than {0} words a minute isn't plausible, please slow down")]
+    ImplausibleWordRate(u32),
+    #[error("Your clock has run out")]
+    OutOfTime,
+    #[error("It's not your turn")]
+    NotYourTurn,
+    #[error("There are no turns to pass in this game")]
+    NoTurns,
+    #[error("You're not playing in this game")]
+    NotPlaying,
+    #[error("Word already used")]
+    WordAlreadyUsed,
+    #[error("Invalid path")]
+    InvalidPath,
+    #[error("Word not found in dictionary")]
+    WordNotInDictionary,
+    #[error("This game doesn't allow taking words back")]
+    UndoNotAllowed,
+    #[error("There's no word of yours to take back")]
+    NothingToUndo,
+
+    // Shop
+    #[error("Item not found")]
+    ItemNotFound,
+    #[error("You already own this item")]
+    ItemAlreadyOwned,
+    #[error("Not enough gems (costs {price}, you have {balance})")]
+    InsufficientGems { price: i64, balance: i64 },
+
+    // Malformed requests
+    #[error("Invalid message format: {0}")]
+    InvalidMessage(String),
+    /// A client-supplied field failed `websocket::validate`
+    #[error("Invalid {field}: {reason}")]
+    InvalidField {
+        field: &'static str,
+        reason: &'static str,
+    },
+    #[error("Invalid lobby ID")]
+    InvalidLobbyId,
+    #[error("Invalid game ID")]
+    InvalidGameId,
+    #[error("Invalid guild ID")]
+    InvalidGuildId,
+    #[error("Invalid settings: {0}")]
+    InvalidSettings(String),
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+    #[error("Invalid API key request: {0}")]
+    InvalidApiKeyRequest(String),
+    #[error("Invalid puzzle: {0}")]
+    InvalidPuzzle(String),
+
+    // Internal failures
+    #[error("Database error")]
+    Database(#[from] sqlx::Error),
+    #[error("Failed to serialize data")]
+    Serialization(#[from] serde_json::Error),
+    /// The client's outbound channel is closed, so nothing more can be sent to it
+    #[error("Connection closed")]
+    ConnectionClosed,
+    #[error("Internal server error")]
+    Internal,
+}
+
+impl ApiError {
+    /// Stable, machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized => "unauthorized",
+            Self::UserNotFound => "user_not_found",
+            Self::AvatarNotFound => "avatar_not_found",
+            Self::DiscordApi => "discord_error",
+            Self::InvalidInvite => "invalid_invite",
+            Self::InvalidOAuthState => "invalid_oauth_state",
+            Self::AuthorizationCodeReused => "authorization_code_reused",
+            Self::AccountPendingDeletion => "account_pending_deletion",
+            Self::InvalidRestoreToken => "invalid_restore_token",
+            Self::InvalidUserId => "invalid_user_id",
+            Self::NotAdmin => "not_admin",
+            Self::InvalidApiKey => "invalid_api_key",
+            Self::MissingApiScope(_) => "missing_api_scope",
+            Self::ApiKeyNotFound => "api_key_not_found",
+            Self::GuestAccessDisabled => "guest_access_disabled",
+            Self::Guest(_) => "guest_not_allowed",
+            Self::MissingGuildPermission => "missing_guild_permission",
+            Self::OriginNotAllowed => "origin_not_allowed",
+            Self::TooManyConnections => "too_many_connections",
+            Self::RateLimited => "rate_limited",
+            Self::LobbyNotFound | Self::LobbyCodeNotFound(_) => "lobby_not_found",
+            Self::NotInLobby => "not_in_lobby",
+            Self::NotLobbyMember => "not_lobby_member",
+            Self::LobbyFull => "lobby_full",
+            Self::LobbyCodesExhausted => "lobby_codes_exhausted",
+            Self::NotInChannelLobby => "not_in_channel_lobby",
+            Self::NotInCustomLobby => "not_in_custom_lobby",
+            Self::TableNotFound => "table_not_found",
+            Self::TooManyTables => "too_many_tables",
+            Self::NotHost(_) => "not_host",
+            Self::Spectating(_) => "spectating",
+            Self::GameInProgress => "game_in_progress",
+            Self::NotEnoughPlayers(_) => "not_enough_players",
+            Self::TooManyPlayers(_) => "too_many_players",
+            Self::DuelNeedsTwoPlayers(_) => "duel_needs_two_players",
+            Self::PuzzleRushIsSolo(_) => "puzzle_rush_is_solo",
+            Self::InvalidLocalPlayers => "invalid_local_players",
+            Self::ModeNotAllowed => "mode_not_allowed",
+            Self::UnsupportedMode => "unsupported_mode",
+            Self::InvalidCoopTarget => "invalid_coop_target",
+            Self::InvalidScheduleTime => "invalid_schedule_time",
+            Self::NoScheduledGame => "no_scheduled_game",
+            Self::NoActiveGame => "no_active_game",
+            Self::GameNotFound => "game_not_found",
+            Self::GameNotFinished => "game_not_finished",
+            Self::SeasonNotFound => "season_not_found",
+            Self::PuzzleNotFound => "puzzle_not_found",
+            Self::GameNightNotFound => "game_night_not_found",
+            Self::ItemNotFound => "item_not_found",
+            Self::ItemAlreadyOwned => "item_already_owned",
+            Self::InsufficientGems { .. } => "insufficient_gems",
+            Self::OutOfTime => "out_of_time",
+            Self::WordTooObscure => "word_too_obscure",
+            Self::WordTooShort => "word_too_short",
+            Self::BelowRushTarget(_) => "below_rush_target",
+            Self::BoardChecksumRequired => "board_checksum_required",
+            Self::StaleBoard => "stale_board",
+            Self::ImplausibleWordRate(_) => "implausible_word_rate",
+            Self::DictionaryPackNotFound => "dictionary_pack_not_found",
+            Self::DictionaryLoading => "dictionary_loading",
+            Self::NotYourTurn => "not_your_turn",
+            Self::NoTurns => "no_turns",
+            Self::NotPlaying => "not_playing",
+            Self::WordAlreadyUsed => "word_already_used",
+            Self::InvalidPath => "invalid_path",
+            Self::WordNotInDictionary => "word_not_in_dictionary",
+            Self::UndoNotAllowed => "undo_not_allowed",
+            Self::NothingToUndo => "nothing_to_undo",
+            Self::InvalidMessage(_) => "invalid_message",
+            Self::InvalidField { .. } => "invalid_field",
+            Self::InvalidLobbyId => "invalid_lobby_id",
+            Self::InvalidGameId => "invalid_game_id",
+            Self::InvalidGuildId => "invalid_guild_id",
+            Self::InvalidSettings(_) => "invalid_settings",
+            Self::InvalidProfile(_) => "invalid_profile",
+            Self::InvalidApiKeyRequest(_) => "invalid_api_key_request",
+            Self::InvalidPuzzle(_) => "invalid_puzzle",
+            Self::Database(_) => "database_error",
+            Self::Serialization(_) => "serialization_error",
+            Self::ConnectionClosed => "connection_closed",
+            Self::Internal => "internal_error",
+        }
+    }
+
+    /// HTTP status used when the error is returned from an HTTP handler
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Unauthorized | Self::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            Self::NotHost(_)
+            | Self::Spectating(_)
+            | Self::NotLobbyMember
+            | Self::NotAdmin
+            | Self::Guest(_)
+            | Self::MissingApiScope(_)
+            | Self::MissingGuildPermission
+            | Self::OriginNotAllowed => StatusCode::FORBIDDEN,
+            Self::UserNotFound
+            | Self::GuestAccessDisabled
+            | Self::ApiKeyNotFound
+            | Self::AvatarNotFound
+            | Self::LobbyNotFound
+            | Self::LobbyCodeNotFound(_)
+            | Self::NoActiveGame
+            | Self::NoScheduledGame
+            | Self::GameNotFound
+            | Self::SeasonNotFound
+            | Self::PuzzleNotFound
+            | Self::GameNightNotFound
+            | Self::ItemNotFound
+            | Self::TableNotFound
+            | Self::DictionaryPackNotFound => StatusCode::NOT_FOUND,
+            Self::GameInProgress
+            | Self::GameNotFinished
+            | Self::NotYourTurn
+            | Self::NoTurns
+            | Self::NotPlaying
+            | Self::OutOfTime
+            | Self::NotInLobby
+            | Self::NotInChannelLobby
+            | Self::NotInCustomLobby
+            | Self::TooManyTables
+            | Self::ItemAlreadyOwned
+            | Self::InsufficientGems { .. }
+            | Self::AccountPendingDeletion
+            | Self::NothingToUndo
+            | Self::StaleBoard
+            | Self::LobbyFull => StatusCode::CONFLICT,
+            Self::NotEnoughPlayers(_)
+            | Self::TooManyPlayers(_)
+            | Self::DuelNeedsTwoPlayers(_)
+            | Self::PuzzleRushIsSolo(_)
+            | Self::InvalidLocalPlayers
+            | Self::ModeNotAllowed
+            | Self::UnsupportedMode
+            | Self::InvalidCoopTarget
+            | Self::InvalidScheduleTime
+            | Self::WordAlreadyUsed
+            | Self::InvalidPath
+            | Self::WordNotInDictionary
+            | Self::WordTooObscure
+            | Self::WordTooShort
+            | Self::BelowRushTarget(_)
+            | Self::BoardChecksumRequired
+            | Self::UndoNotAllowed
+            | Self::InvalidMessage(_)
+            | Self::InvalidField { .. }
+            | Self::InvalidLobbyId
+            | Self::InvalidGameId
+            | Self::InvalidGuildId
+            | Self::InvalidSettings(_)
+            | Self::InvalidProfile(_)
+            | Self::InvalidApiKeyRequest(_)
+            | Self::InvalidPuzzle(_)
+            | Self::InvalidInvite
+            | Self::InvalidOAuthState
+            | Self::AuthorizationCodeReused
+            | Self::InvalidRestoreToken
+            | Self::InvalidUserId => StatusCode::BAD_REQUEST,
+            Self::TooManyConnections | Self::RateLimited | Self::ImplausibleWordRate(_) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::DiscordApi => StatusCode::BAD_GATEWAY,
+            Self::LobbyCodesExhausted | Self::DictionaryLoading => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Database(_)
+            | Self::Serialization(_)
+            | Self::ConnectionClosed
+            | Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// True for failures caused by the server rather than the request
+    pub fn is_internal(&self) -> bool {
+        self.status_code().is_server_error()
+    }
+
+    /// The message shown to players in a lobby using `language`, in that
+    /// language where it's translated and in English otherwise
+    pub fn localized_message(&self, language: &str) -> String {
+        locale::translate(language, self.code())
+            .map(String::from)
+            .unwrap_or_else(|| self.to_string())
+    }
+
+    /// Report this error to a client whose lobby uses `language`
+    pub fn into_server_message(self, language: &str) -> ServerMessage {
+        let message = self.localized_message(language);
+        if self.is_invalid_word() {
+            return ServerMessage::InvalidWord {
+                code: self.code().to_string(),
+                reason: message,
+            };
+        }
+        match self {
+            Self::InvalidField { field, .. } => {
+                ServerMessage::field_error(self.code(), message, field)
+            }
+            _ => ServerMessage::game_error(self.code(), message),
+        }
+    }
+
+    /// True for rejected word submissions, which have their own message type
+    fn is_invalid_word(&self) -> bool {
+        matches!(
+            self,
+            Self::WordAlreadyUsed
+                | Self::InvalidPath
+                | Self::WordNotInDictionary
+                | Self::WordTooObscure
+                | Self::WordTooShort
+                | Self::BelowRushTarget(_)
+                | Self::BoardChecksumRequired
+                | Self::StaleBoard
+                | Self::ImplausibleWordRate(_)
+        )
+    }
+}
+
+impl From<SendError<ServerMessage>> for ApiError {
+    fn from(_: SendError<ServerMessage>) -> Self {
+        Self::ConnectionClosed
+    }
+}
+
+impl From<SendError<OutboundMessage>> for ApiError {
+    fn from(_: SendError<OutboundMessage>) -> Self {
+        Self::ConnectionClosed
+    }
+}
+
+impl From<PathError> for ApiError {
+    fn from(err: PathError) -> Self {
+        tracing::debug!("Rejected word path: {}", err);
+        Self::InvalidPath
+    }
+}
+
+impl From<ApiError> for ServerMessage {
+    fn from(err: ApiError) -> Self {
+        err.into_server_message(locale::DEFAULT_LANGUAGE)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.is_internal() {
+            tracing::error!("Request failed ({}): {:?}", self.code(), self);
+        }
+
+        let body = Json(json!({
+            "code": self.code(),
+            "message": self.to_string(),
+        }));
+        (self.status_code(), body).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        // The frontend branches on these strings, so they must not drift
+        assert_eq!(ApiError::NotHost("start the game").code(), "not_host");
+        assert_eq!(ApiError::NotYourTurn.code(), "not_your_turn");
+        assert_eq!(
+            ApiError::LobbyCodeNotFound("ABC123".to_string()).code(),
+            ApiError::LobbyNotFound.code(),
+            "Both lobby lookups should share a code"
+        );
+        assert_eq!(
+            ApiError::Database(sqlx::Error::RowNotFound).code(),
+            "database_error"
+        );
+    }
+
+    #[test]
+    fn test_internal_errors_hide_details() {
+        let err = ApiError::Database(sqlx::Error::Protocol("secret detail".to_string()));
+        assert!(err.is_internal());
+        assert!(
+            !err.to_string().contains("secret detail"),
+            "Internal error details should only be logged, never sent to clients"
+        );
+    }
+
+    #[test]
+    fn test_converts_to_game_error_message() {
+        let msg: ServerMessage = ApiError::NotEnoughPlayers(1).into();
+        match msg {
+            ServerMessage::GameError { code, message, .. } => {
+                assert_eq!(code, "not_enough_players");
+                assert!(message.contains("currently 1"));
+            }
+            other => panic!("Expected GameError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_field_names_the_field() {
+        let msg: ServerMessage = ApiError::InvalidField {
+            field: "channel_id",
+            reason: "must be a Discord ID",
+        }
+        .into();
+        match msg {
+            ServerMessage::GameError { code, field, .. } => {
+                assert_eq!(code, "invalid_field");
+                assert_eq!(field.as_deref(), Some("channel_id"));
+            }
+            other => panic!("Expected GameError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_word_rejections_convert_to_invalid_word() {
+        let msg: ServerMessage = ApiError::WordNotInDictionary.into();
+        match msg {
+            ServerMessage::InvalidWord { code, reason } => {
+                assert_eq!(code, "word_not_in_dictionary");
+                assert_eq!(reason, "Word not found in dictionary");
+            }
+            other => panic!("Expected InvalidWord, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_localized_rejections() {
+        match ApiError::WordNotInDictionary.into_server_message("es-MX") {
+            ServerMessage::InvalidWord { code, reason } => {
+                assert_eq!(code, "word_not_in_dictionary", "Codes are never translated");
+                assert_eq!(reason, "Palabra no encontrada en el diccionario");
+            }
+            other => panic!("Expected InvalidWord, got {:?}", other),
+        }
+        assert_eq!(
+            ApiError::LobbyFull.localized_message("es"),
+            "This lobby is full",
+            "Untranslated messages fall back to English"
+        );
+    }
+
+    #[test]
+    fn test_http_status_codes() {
+        assert_eq!(
+            ApiError::Unauthorized.status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(ApiError::UserNotFound.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            ApiError::Internal.status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use tower_http::{compression::CompressionLayer, services::ServeDir};
+
+/// Frontend directory served when FRONTEND_DIR isn't set (and assets aren't embedded)
+#[cfg(not(feature = "embedded-frontend"))]
+const DEFAULT_FRONTEND_DIR: &str = "../frontend";
+
+/// Cache-Control for files with a content hash in their name: they never change
+const CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Cache-Control for everything else (index.html, unhashed scripts): revalidate
+/// every time so a deploy is picked up right away
+const CACHE_REVALIDATE: &str = "no-cache";
+
+/// The static frontend, mounted as the router's fallback
+///
+/// Responses are gzip/brotli compressed when the client accepts it and get
+/// Cache-Control headers based on whether the file name is content-hashed. With
+/// the `embedded-frontend` feature the bundle compiled into the binary is served,
+/// unless `frontend_dir` points somewhere on disk.
+pub fn service(frontend_dir: Option<&str>) -> Router {
+    let router = match frontend_dir {
+        Some(dir) => disk_router(dir),
+        None => default_router(),
+    };
+    router
+        .layer(middleware::from_fn(set_cache_control))
+        .layer(CompressionLayer::new())
+}
+
+fn disk_router(dir: &str) -> Router {
+    tracing::info!("Serving frontend from {}", dir);
+    Router::new().fallback_service(ServeDir::new(dir))
+}
+
+#[cfg(not(feature = "embedded-frontend"))]
+fn default_router() -> Router {
+    disk_router(DEFAULT_FRONTEND_DIR)
+}
+
+#[cfg(feature = "embedded-frontend")]
+fn default_router() -> Router {
+    tracing::info!("Serving embedded frontend");
+    Router::new().fallback(embedded::serve)
+}
+
+async fn set_cache_control(request: Request, next: Next) -> Response {
+    let cache_control = cache_control_for(request.uri().path());
+    let mut response = next.run(request).await;
+    if response.status().is_success() || response.status().is_redirection() {
+        response
+            .headers_mut()
+            .entry(header::CACHE_CONTROL)
+            .or_insert(HeaderValue::from_static(cache_control));
+    }
+    response
+}
+
+/// Pick a Cache-Control value for a request path
+pub fn cache_control_for(path: &str) -> &'static str {
+    if is_content_hashed(path) {
+        CACHE_IMMUTABLE
+    } else {
+        CACHE_REVALIDATE
+    }
+}
+
+/// Check for a bundler content hash in the file name, e.g. `main-3f2a1b9c.js` or
+/// `index.BdU7mX2q.css`: at least 8 alphanumeric (or `_`) characters after the last
+/// `-` or `.` before the extension, including at least one digit
+fn is_content_hashed(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let Some((stem, _extension)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    let Some(hash) = stem.rsplit(['-', '.']).next().filter(|hash| *hash != stem) else {
+        return false;
+    };
+    hash.len() >= 8
+        && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && hash.chars().any(|c| c.is_ascii_digit())
+}
+
+#[cfg(feature = "embedded-frontend")]
+mod embedded {
+    use axum::{
+        body::Body,
+        http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+        response::{IntoResponse, Response},
+    };
+    use rust_embed::RustEmbed;
+
+    /// The Vite build output; run `npm run build` in `frontend/` before compiling
+    #[derive(RustEmbed)]
+    #[folder = "../frontend/dist"]
+    struct Assets;
+
+    pub async fn serve(uri: Uri, headers: HeaderMap) -> Response {
+        let path = uri.path().trim_start_matches('/');
+        let path = if path.is_empty() { "index.html" } else { path };
+        let Some(file) = Assets::get(path) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        let etag = format!("\"{}\"", hex(&file.metadata.sha256_hash()));
+        let not_modified = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == etag);
+        let etag = HeaderValue::from_str(&etag).expect("hex ETag is a valid header value");
+        if not_modified {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+
+        let content_type = HeaderValue::from_str(file.metadata.mimetype())
+            .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+        (
+            [(header::CONTENT_TYPE, content_type), (header::ETAG, etag)],
+            Body::from(file.data.into_owned()),
+        )
+            .into_response()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_assets_are_cached_forever() {
+        assert_eq!(
+            cache_control_for("/assets/main-3f2a1b9c.js"),
+            CACHE_IMMUTABLE
+        );
+        assert_eq!(
+            cache_control_for("/assets/index.BdU7mX2q.css"),
+            CACHE_IMMUTABLE
+        );
+        assert_eq!(
+            cache_control_for("/assets/logo-a1b2c3d4e5.png"),
+            CACHE_IMMUTABLE
+        );
+    }
+
+    #[test]
+    fn test_unhashed_files_are_revalidated() {
+        assert_eq!(cache_control_for("/"), CACHE_REVALIDATE);
+        assert_eq!(cache_control_for("/index.html"), CACHE_REVALIDATE);
+        assert_eq!(cache_control_for("/js/main.js"), CACHE_REVALIDATE);
+        assert_eq!(
+            cache_control_for("/js/discord-sdk.js"),
+            CACHE_REVALIDATE,
+            "A short word after a dash isn't a hash"
+        );
+        assert_eq!(
+            cache_control_for("/js/websocket-client.js"),
+            CACHE_REVALIDATE,
+            "A long word without digits isn't a hash"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        rules::GameRules,
+        solver::{best_word, BestWord, WordList},
+    },
+    models::{GameMove, Grid, Position},
+};
+
+/// One move of a finished game next to the best word its player could have found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveAnalysis {
+    /// 1 for the game's first move
+    pub move_number: u32,
+    pub user_id: i64,
+    pub round_number: i32,
+    pub word: String,
+    pub score: i32,
+    pub positions: Vec<Position>,
+    /// The board the word was played on
+    pub board: Grid,
+    /// None if the solver found nothing left to play
+    pub best: Option<BestWord>,
+}
+
+/// How close a player came to the best word over a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerAccuracy {
+    pub user_id: i64,
+    pub moves: u32,
+    pub points: i64,
+    /// What the best word of every move would have scored in total
+    pub best_points: i64,
+    /// `points` as a percentage of `best_points`, 0-100
+    pub accuracy: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameAnalysis {
+    pub moves: Vec<MoveAnalysis>,
+    /// Ordered by user ID
+    pub players: Vec<PlayerAccuracy>,
+}
+
+/// Replay a finished game, solving each board it was played on
+///
+/// `boards` is the board history in move order, so `boards[i]` is the board
+/// before the `i`th move; if the history is shorter than the moves (games from
+/// before it was recorded), the last known board stands in for the rest. Each
+/// move is solved with the words played before it counted as used. A played word
+/// the solver can't beat, e.g. from a theme pack it doesn't know about, counts
+/// as the best for its move.
+pub fn analyze_game(
+    moves: &[GameMove],
+    boards: &[Grid],
+    words: &impl WordList,
+    rules: &GameRules,
+) -> GameAnalysis {
+    let mut used = HashSet::new();
+    let mut totals: BTreeMap<i64, (u32, i64, i64)> = BTreeMap::new();
+    let mut analysis = Vec::with_capacity(moves.len());
+
+    for (index, game_move) in moves.iter().enumerate() {
+        let Some(board) = boards.get(index).or(boards.last()) else {
+            break;
+        };
+        let best = best_word(board, words, &used, rules);
+        used.insert(game_move.word.to_lowercase());
+
+        let best_score = best
+            .as_ref()
+            .map_or(game_move.score, |b| b.score.max(game_move.score));
+        let total = totals.entry(game_move.user_id).or_default();
+        total.0 += 1;
+        total.1 += game_move.score as i64;
+        total.2 += best_score as i64;
+
+        analysis.push(MoveAnalysis {
+            move_number: index as u32 + 1,
+            user_id: game_move.user_id,
+            round_number: game_move.round_number,
+            word: game_move.word.clone(),
+            score: game_move.score,
+            positions: serde_json::from_value(game_move.positions.clone()).unwrap_or_default(),
+            board: board.clone(),
+            best,
+        });
+    }
+
+    let players = totals
+        .into_iter()
+        .map(|(user_id, (moves, points, best_points))| PlayerAccuracy {
+            user_id,
+            moves,
+            points,
+            best_points,
+            accuracy: accuracy(points, best_points),
+        })
+        .collect();
+
+    GameAnalysis {
+        moves: analysis,
+        players,
+    }
+}
+
+fn accuracy(points: i64, best_points: i64) -> u32 {
+    if best_points <= 0 {
+        return 100;
+    }
+    ((points.max(0) * 100 + best_points / 2) / best_points).min(100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dictionary::Dictionary, models::GridCell};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn grid(rows: &[&str]) -> Grid {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|letter| GridCell {
+                        letter,
+                        value: 1,
+                        multiplier: None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn create_test_move(user_id: i64, word: &str, score: i32) -> GameMove {
+        GameMove {
+            id: 1,
+            game_id: Uuid::new_v4(),
+            user_id,
+            round_number: 1,
+            word: word.to_string(),
+            score,
+            positions: serde_json::json!([]),
+            timestamp: Utc::now(),
+            move_time_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_moves_are_compared_with_the_best_word() {
+        let dictionary = Dictionary::from_lists("at\ncat\nta\n", "");
+        let board = grid(&["AA", "TC"]);
+        let moves = vec![
+            create_test_move(111, "AT", 2),
+            create_test_move(222, "CAT", 3),
+            create_test_move(111, "TA", 2),
+        ];
+
+        let analysis = analyze_game(&moves, &[board], &dictionary, &GameRules::default());
+        assert_eq!(analysis.moves.len(), 3);
+        assert_eq!(
+            analysis.moves[0].best.as_ref().unwrap().word,
+            "CAT",
+            "CAT was on the board and unplayed"
+        );
+        assert_eq!(analysis.moves[1].move_number, 2);
+        assert_eq!(
+            analysis.moves[2].best.as_ref().unwrap().score,
+            2,
+            "CAT counts as used once it's been played"
+        );
+
+        assert_eq!(
+            analysis.players[0],
+            PlayerAccuracy {
+                user_id: 111,
+                moves: 2,
+                points: 4,
+                best_points: 5,
+                accuracy: 80,
+            }
+        );
+        assert_eq!(analysis.players[1].accuracy, 100);
+    }
+
+    #[test]
+    fn test_unknown_words_count_as_the_best() {
+        let dictionary = Dictionary::from_lists("at\n", "");
+        let board = grid(&["AT"]);
+        let moves = vec![create_test_move(111, "ZZZ", 30)];
+
+        let analysis = analyze_game(&moves, &[board], &dictionary, &GameRules::default());
+        assert_eq!(analysis.players[0].best_points, 30);
+        assert_eq!(
+            analysis.players[0].accuracy, 100,
+            "Beating the solver doesn't go over 100%"
+        );
+    }
+
+    #[test]
+    fn test_no_boards_means_no_analysis() {
+        let dictionary = Dictionary::from_lists("at\n", "");
+        let moves = vec![create_test_move(111, "AT", 2)];
+        let analysis = analyze_game(&moves, &[], &dictionary, &GameRules::default());
+        assert!(analysis.moves.is_empty());
+        assert!(analysis.players.is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::Multiplier;
+
+/// How a lobby's boards are drawn, chosen by the host
+///
+/// Every theme gives each kind of tile its own shape, so boards read the same
+/// without telling colours apart; themes only change the palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardTheme {
+    #[default]
+    Classic,
+    /// The Okabe-Ito palette, told apart with any common colour vision deficiency
+    ColorblindSafe,
+    /// Bright marks on a dark board
+    HighContrast,
+}
+
+/// The shape a marked tile is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleHint {
+    Circle,
+    Triangle,
+    Star,
+    Hexagon,
+}
+
+/// How to draw one kind of marked tile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileStyle {
+    pub style_hint: StyleHint,
+    /// CSS hex colour
+    pub color: String,
+}
+
+/// A theme's tile styles, keyed the way grid cells name their multipliers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardStyles {
+    pub theme: BoardTheme,
+    #[serde(rename = "DL")]
+    pub double_letter: TileStyle,
+    #[serde(rename = "TL")]
+    pub triple_letter: TileStyle,
+    #[serde(rename = "DW")]
+    pub double_word: TileStyle,
+    /// Tiles that earn gems in co-op games
+    pub gem: TileStyle,
+}
+
+impl BoardStyles {
+    /// The style of a multiplier tile
+    pub fn multiplier(&self, multiplier: &Multiplier) -> &TileStyle {
+        match multiplier {
+            Multiplier::DoubleLetter => &self.double_letter,
+            Multiplier::TripleLetter => &self.triple_letter,
+            Multiplier::DoubleWord => &self.double_word,
+        }
+    }
+}
+
+impl BoardTheme {
+    /// The theme's tile styles
+    pub fn styles(self) -> BoardStyles {
+        let [double_letter, triple_letter, double_word, gem] = match self {
+            Self::Classic => ["#60A5FA", "#34D399", "#F472B6", "#A855F7"],
+            Self::ColorblindSafe => ["#0072B2", "#E69F00", "#CC79A7", "#009E73"],
+            Self::HighContrast => ["#FFFFFF", "#FFD700", "#00FFFF", "#FF00FF"],
+        };
+        let style = |style_hint, color: &str| TileStyle {
+            style_hint,
+            color: color.to_string(),
+        };
+        BoardStyles {
+            theme: self,
+            double_letter: style(StyleHint::Circle, double_letter),
+            triple_letter: style(StyleHint::Triangle, triple_letter),
+            double_word: style(StyleHint::Star, double_word),
+            gem: style(StyleHint::Hexagon, gem),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_every_tile_kind_has_its_own_shape() {
+        for theme in [
+            BoardTheme::Classic,
+            BoardTheme::ColorblindSafe,
+            BoardTheme::HighContrast,
+        ] {
+            let styles = theme.styles();
+            let tiles = [
+                &styles.double_letter,
+                &styles.triple_letter,
+                &styles.double_word,
+                &styles.gem,
+            ];
+            let shapes: HashSet<StyleHint> = tiles.iter().map(|tile| tile.style_hint).collect();
+            assert_eq!(shapes.len(), tiles.len(), "{:?} reuses a shape", theme);
+            let colors: HashSet<&str> = tiles.iter().map(|tile| tile.color.as_str()).collect();
+            assert_eq!(colors.len(), tiles.len(), "{:?} reuses a colour", theme);
+        }
+        assert_eq!(
+            BoardTheme::Classic.styles().double_word.style_hint,
+            BoardTheme::HighContrast.styles().double_word.style_hint,
+            "Shapes mean the same thing in every theme"
+        );
+    }
+
+    #[test]
+    fn test_styles_are_keyed_like_grid_cells() {
+        let styles = BoardTheme::ColorblindSafe.styles();
+        let json = serde_json::to_value(&styles).unwrap();
+        assert_eq!(json["theme"], "colorblind_safe");
+        assert_eq!(json["DL"]["style_hint"], "circle");
+        assert_eq!(json["DW"]["style_hint"], "star");
+        assert_eq!(json["gem"]["color"], "#009E73");
+        assert_eq!(
+            styles.multiplier(&Multiplier::TripleLetter).style_hint,
+            StyleHint::Triangle
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// Best words on the board shown to spectators each turn
+pub const COMMENTARY_BEST_MOVES: usize = 3;
+/// Roughly how far a round of play can move one player's score against another's
+const SCORE_SWING_PER_ROUND: f64 = 15.0;
+
+/// A player's estimated chance of winning from here
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WinProbability {
+    pub user_id: i64,
+    /// 0-100
+    pub percent: u32,
+}
+
+/// Estimate each player's chance of winning from their scores and the rounds left
+///
+/// A softmax over the scores whose spread grows with the square root of the rounds
+/// still to play, so an early lead counts for little and a late one for a lot. With
+/// no rounds left the leaders share it. `scores` holds the players still in the
+/// game as `(user_id, score)`, in the order they're returned.
+pub fn win_probabilities(scores: &[(i64, i32)], rounds_left: u32) -> Vec<WinProbability> {
+    let Some(leader) = scores.iter().map(|(_, score)| *score).max() else {
+        return Vec::new();
+    };
+
+    let weights: Vec<f64> = if rounds_left == 0 {
+        scores
+            .iter()
+            .map(|(_, score)| if *score == leader { 1.0 } else { 0.0 })
+            .collect()
+    } else {
+        let spread = SCORE_SWING_PER_ROUND * (rounds_left as f64).sqrt();
+        scores
+            .iter()
+            .map(|(_, score)| ((*score - leader) as f64 / spread).exp())
+            .collect()
+    };
+    let total: f64 = weights.iter().sum();
+
+    scores
+        .iter()
+        .zip(weights)
+        .map(|((user_id, _), weight)| WinProbability {
+            user_id: *user_id,
+            percent: (weight / total * 100.0).round() as u32,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn percents(scores: &[(i64, i32)], rounds_left: u32) -> Vec<u32> {
+        win_probabilities(scores, rounds_left)
+            .into_iter()
+            .map(|p| p.percent)
+            .collect()
+    }
+
+    #[test]
+    fn test_level_scores_are_even() {
+        assert_eq!(percents(&[(1, 20), (2, 20)], 3), vec![50, 50]);
+        assert_eq!(percents(&[(1, 0), (2, 0), (3, 0), (4, 0)], 5), vec![25; 4]);
+        assert!(percents(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_leads_matter_more_late() {
+        let early = percents(&[(1, 30), (2, 10)], 5);
+        let late = percents(&[(1, 30), (2, 10)], 1);
+        assert!(early[0] > 50, "The leader is favoured");
+        assert!(
+            late[0] > early[0],
+            "The same lead is worth more with fewer rounds left"
+        );
+        assert_eq!(
+            percents(&[(1, 30), (2, 10)], 0),
+            vec![100, 0],
+            "With no rounds left the leader has won"
+        );
+        assert_eq!(percents(&[(1, 30), (2, 30), (3, 5)], 0), vec![50, 50, 0]);
+    }
+}
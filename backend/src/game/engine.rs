@@ -0,0 +1,653 @@
+use std::{collections::HashSet, time::Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    error::ApiError,
+    game::{scorer::Scorer, session::GameSession},
+    models::{GameEvent, GameMode, GameOverReason, GameState, GameStatus, Grid, Position},
+    websocket::messages::ServerMessage,
+};
+
+/// How a game mode plays: who may submit a word and on which board, what a
+/// scored word does, how play moves on and when the game is over
+///
+/// The WebSocket handler runs the parts every mode shares (dictionary lookup,
+/// scoring, move history, broadcasts) and asks the game's engine about the rest,
+/// so a new mode is a new implementation here rather than branches there.
+pub trait GameModeEngine: Send + Sync {
+    /// Whether players take turns; without them everyone plays at once until
+    /// the game ends on its own
+    fn has_turns(&self) -> bool {
+        true
+    }
+
+    /// The index in `state.players` a word from `user_id` is played for, or why
+    /// they can't play one right now
+    fn check_submitter(
+        &self,
+        session: &GameSession,
+        user_id: i64,
+        now: Instant,
+        wall_now: DateTime<Utc>,
+    ) -> Result<usize, ApiError> {
+        let _ = (now, wall_now);
+        let index = session.acting_index(user_id).ok_or(ApiError::NotPlaying)?;
+        if session.current_player_id() != Some(user_id) {
+            return Err(ApiError::NotYourTurn);
+        }
+        Ok(index)
+    }
+
+    /// The board a player's words go on
+    fn board<'a>(&self, state: &'a GameState, index: usize) -> Option<BoardView<'a>> {
+        let _ = index;
+        Some(BoardView {
+            grid: &state.grid,
+            used_words: &state.used_words,
+        })
+    }
+
+    /// Why a word that passed every other check isn't worth playing for its
+    /// score; only puzzle rushes set a bar
+    fn check_score(&self, state: &GameState, score: i32) -> Result<(), ApiError> {
+        let _ = (state, score);
+        Ok(())
+    }
+
+    /// The events a checked and scored word produces
+    fn play_word(&self, state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+        shared_word_events(state, word)
+    }
+
+    /// Whether a word can be taken back just after it's played
+    fn offers_undo(&self, state: &GameState) -> bool {
+        state.rules.undo
+    }
+
+    /// Whether scored words count towards quests; words that can be taken back
+    /// don't
+    fn counts_for_quests(&self, state: &GameState) -> bool {
+        !self.offers_undo(state)
+    }
+
+    /// Time the player spent on a word, for the move history
+    fn move_time_ms(&self, state: &GameState, now: Instant) -> Option<i32> {
+        state.move_time_ms(now)
+    }
+
+    /// When a player's words are kept from the rest of the lobby, what the lobby
+    /// sees instead; None when everyone sees every word
+    fn private_progress(
+        &self,
+        state: &GameState,
+        index: usize,
+        user_id: i64,
+    ) -> Option<ServerMessage> {
+        let _ = (state, index, user_id);
+        None
+    }
+
+    /// The event that ends the current turn, charging the player's clock; None
+    /// in modes without turns
+    fn end_turn(&self, state: &GameState, now: Instant) -> Option<GameEvent> {
+        Some(GameEvent::TurnEnded {
+            clock_ms: state.turn_clock_ms(now),
+        })
+    }
+
+    /// Why a game still in progress has ended on its own, without anyone moving
+    fn completion(&self, state: &GameState, now: DateTime<Utc>) -> Option<GameOverReason> {
+        let _ = (state, now);
+        None
+    }
+}
+
+/// The board a word is checked against and scored on
+pub struct BoardView<'a> {
+    pub grid: &'a Grid,
+    /// Words already played on it, lowercased
+    pub used_words: &'a HashSet<String>,
+}
+
+impl BoardView<'_> {
+    pub fn is_word_used(&self, word: &str) -> bool {
+        self.used_words.contains(&word.to_lowercase())
+    }
+}
+
+/// A word that passed every check, with its score
+pub struct ScoredWord<'a> {
+    /// The seat it's played for
+    pub player: usize,
+    pub word: &'a str,
+    pub positions: &'a [Position],
+    pub score: i32,
+    pub move_time_ms: Option<i32>,
+}
+
+impl ScoredWord<'_> {
+    fn played(&self) -> GameEvent {
+        GameEvent::WordPlayed {
+            player: self.player,
+            word: self.word.to_string(),
+            positions: self.positions.to_vec(),
+            score: self.score,
+            move_time_ms: self.move_time_ms,
+        }
+    }
+}
+
+/// A word played on the board everyone shares, with replacement tiles drawn in
+/// letter bag games
+pub fn shared_word_events(state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+    let mut events = vec![word.played()];
+    if let Some(tiles) = state.draw_refills(word.positions) {
+        events.push(GameEvent::GridRefilled { tiles });
+    }
+    events
+}
+
+/// The engine that runs games of `mode`
+pub fn engine_for(mode: &GameMode) -> &'static dyn GameModeEngine {
+    match mode {
+        GameMode::Multiplayer | GameMode::TwoVTwo | GameMode::Adventure => &TurnBasedEngine,
+        GameMode::Coop => &CoopEngine,
+        GameMode::Blitz => &BlitzEngine,
+        GameMode::Duel => &DuelEngine,
+        GameMode::PuzzleRush => &PuzzleRushEngine,
+        GameMode::TimeAttack => &TimeAttackEngine,
+        GameMode::HotSeat => &HotSeatEngine,
+    }
+}
+
+/// Players take turns on a shared board, round after round
+pub struct TurnBasedEngine;
+
+impl GameModeEngine for TurnBasedEngine {}
+
+/// Turns, with the team sharing one score and earning gems for long words
+pub struct CoopEngine;
+
+impl GameModeEngine for CoopEngine {
+    fn play_word(&self, state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+        let gems = Scorer::gems_for_word(word.positions.len());
+        let mut events = shared_word_events(state, word);
+        if state.rules.gems && gems > 0 {
+            events.push(GameEvent::GemsEarned { gems });
+        }
+        events
+    }
+}
+
+/// Turns, with a chess-style clock per player
+pub struct BlitzEngine;
+
+impl GameModeEngine for BlitzEngine {
+    fn check_submitter(
+        &self,
+        session: &GameSession,
+        user_id: i64,
+        now: Instant,
+        wall_now: DateTime<Utc>,
+    ) -> Result<usize, ApiError> {
+        let index = TurnBasedEngine.check_submitter(session, user_id, now, wall_now)?;
+        // The clock task ends the turn shortly; don't accept a word in the meantime
+        if session.state.current_player_out_of_time(now) {
+            return Err(ApiError::OutOfTime);
+        }
+        Ok(index)
+    }
+}
+
+/// Turns between local players on one connection
+pub struct HotSeatEngine;
+
+impl GameModeEngine for HotSeatEngine {
+    /// One person playing every seat mustn't farm quests with it
+    fn counts_for_quests(&self, _state: &GameState) -> bool {
+        false
+    }
+}
+
+/// Everyone races the clock on their own copy of the board
+pub struct DuelEngine;
+
+impl GameModeEngine for DuelEngine {
+    fn has_turns(&self) -> bool {
+        false
+    }
+
+    fn check_submitter(
+        &self,
+        session: &GameSession,
+        user_id: i64,
+        _now: Instant,
+        wall_now: DateTime<Utc>,
+    ) -> Result<usize, ApiError> {
+        check_racer(session, user_id, wall_now)
+    }
+
+    fn board<'a>(&self, state: &'a GameState, index: usize) -> Option<BoardView<'a>> {
+        let board = state.player_board(state.players.get(index)?.user_id)?;
+        Some(BoardView {
+            grid: &board.grid,
+            used_words: &board.used_words,
+        })
+    }
+
+    /// A duel board refills itself from the duel's seed as the word is applied
+    fn play_word(&self, _state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+        vec![word.played()]
+    }
+
+    fn offers_undo(&self, _state: &GameState) -> bool {
+        false
+    }
+
+    /// Nobody waits on anybody in a duel, so there's no move time to record
+    fn move_time_ms(&self, _state: &GameState, _now: Instant) -> Option<i32> {
+        None
+    }
+
+    /// The word stays between the player and their board until the duel is
+    /// over; the lobby sees their score
+    fn private_progress(
+        &self,
+        state: &GameState,
+        index: usize,
+        user_id: i64,
+    ) -> Option<ServerMessage> {
+        let player = state.players.get(index)?;
+        let board = state.player_board(player.user_id)?;
+        Some(ServerMessage::DuelScore {
+            user_id,
+            score: player.score,
+            words_played: board.used_words.len(),
+        })
+    }
+
+    fn end_turn(&self, _state: &GameState, _now: Instant) -> Option<GameEvent> {
+        None
+    }
+
+    fn completion(&self, state: &GameState, now: DateTime<Utc>) -> Option<GameOverReason> {
+        state.time_up(now).then_some(GameOverReason::OutOfTime)
+    }
+}
+
+/// The whole team races the clock to use every tile on a shared board
+pub struct TimeAttackEngine;
+
+impl GameModeEngine for TimeAttackEngine {
+    fn has_turns(&self) -> bool {
+        false
+    }
+
+    fn check_submitter(
+        &self,
+        session: &GameSession,
+        user_id: i64,
+        _now: Instant,
+        wall_now: DateTime<Utc>,
+    ) -> Result<usize, ApiError> {
+        check_racer(session, user_id, wall_now)
+    }
+
+    fn play_word(&self, state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+        let positions = word.positions.to_vec();
+        let mut events = shared_word_events(state, word);
+        let cleared = state.time_attack.as_ref().is_some_and(|time_attack| {
+            let mut covered = time_attack.clone();
+            covered.cover(&positions);
+            covered.is_cleared()
+        });
+        events.push(GameEvent::TilesCovered { positions });
+        if cleared {
+            events.push(GameEvent::GameFinished {
+                reason: GameOverReason::BoardCleared,
+            });
+        }
+        events
+    }
+
+    fn end_turn(&self, _state: &GameState, _now: Instant) -> Option<GameEvent> {
+        None
+    }
+
+    fn completion(&self, state: &GameState, now: DateTime<Utc>) -> Option<GameOverReason> {
+        state.time_up(now).then_some(GameOverReason::OutOfTime)
+    }
+}
+
+/// One player racing each board's clock; every word they play solves the board
+///
+/// The WebSocket handler deals the next board, which needs the dictionary to
+/// skip boards without a qualifying word.
+pub struct PuzzleRushEngine;
+
+impl GameModeEngine for PuzzleRushEngine {
+    fn has_turns(&self) -> bool {
+        false
+    }
+
+    fn check_submitter(
+        &self,
+        session: &GameSession,
+        user_id: i64,
+        _now: Instant,
+        wall_now: DateTime<Utc>,
+    ) -> Result<usize, ApiError> {
+        check_racer(session, user_id, wall_now)
+    }
+
+    fn check_score(&self, state: &GameState, score: i32) -> Result<(), ApiError> {
+        match state.puzzle_rush.as_ref() {
+            Some(rush) if score < rush.target_score => {
+                Err(ApiError::BelowRushTarget(rush.target_score))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn play_word(&self, _state: &GameState, word: ScoredWord) -> Vec<GameEvent> {
+        vec![word.played()]
+    }
+
+    fn end_turn(&self, _state: &GameState, _now: Instant) -> Option<GameEvent> {
+        None
+    }
+
+    fn completion(&self, state: &GameState, now: DateTime<Utc>) -> Option<GameOverReason> {
+        state.time_up(now).then_some(GameOverReason::OutOfTime)
+    }
+}
+
+/// In modes played against the clock, anyone who hasn't resigned can play until
+/// time is up
+fn check_racer(
+    session: &GameSession,
+    user_id: i64,
+    wall_now: DateTime<Utc>,
+) -> Result<usize, ApiError> {
+    // The clock task ends the game shortly; don't accept a word in the meantime
+    if session.state.status != GameStatus::InProgress || session.state.time_up(wall_now) {
+        return Err(ApiError::OutOfTime);
+    }
+    let index = session.player_index(user_id).ok_or(ApiError::NotPlaying)?;
+    if session.state.players[index].forfeited {
+        return Err(ApiError::NotPlaying);
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{GamePlayer, GridCell, PuzzleRushState};
+
+    fn grid() -> Grid {
+        (0..5)
+            .map(|_| {
+                (0..5)
+                    .map(|_| GridCell {
+                        letter: 'A',
+                        value: 1,
+                        multiplier: None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn players() -> Vec<GamePlayer> {
+        vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+        ]
+    }
+
+    fn session(mut state: GameState) -> GameSession {
+        state.status = GameStatus::InProgress;
+        GameSession::new("channel:123".to_string(), vec![111, 222], state)
+    }
+
+    fn row(len: usize) -> Vec<Position> {
+        (0..len).map(|col| Position { row: 0, col }).collect()
+    }
+
+    fn scored<'a>(player: usize, word: &'a str, positions: &'a [Position]) -> ScoredWord<'a> {
+        ScoredWord {
+            player,
+            word,
+            positions,
+            score: word.len() as i32,
+            move_time_ms: None,
+        }
+    }
+
+    fn apply(state: &mut GameState, events: &[GameEvent]) {
+        for event in events {
+            state.apply(event);
+        }
+    }
+
+    #[test]
+    fn test_every_mode_has_an_engine() {
+        for mode in GameMode::ALL {
+            let engine = engine_for(&mode);
+            let realtime = matches!(
+                mode,
+                GameMode::Duel | GameMode::TimeAttack | GameMode::PuzzleRush
+            );
+            assert_eq!(engine.has_turns(), !realtime, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_turn_based_submitters() {
+        let session = session(GameState::new(Uuid::new_v4(), grid(), players(), 3));
+        let engine = engine_for(&session.state.mode);
+        let (now, wall_now) = (Instant::now(), Utc::now());
+
+        assert_eq!(
+            engine
+                .check_submitter(&session, 111, now, wall_now)
+                .unwrap(),
+            0
+        );
+        assert!(matches!(
+            engine.check_submitter(&session, 222, now, wall_now),
+            Err(ApiError::NotYourTurn)
+        ));
+        assert!(matches!(
+            engine.check_submitter(&session, 333, now, wall_now),
+            Err(ApiError::NotPlaying)
+        ));
+    }
+
+    #[test]
+    fn test_blitz_rejects_words_after_the_clock_runs_out() {
+        let state = GameState::new_blitz(
+            Uuid::new_v4(),
+            grid(),
+            players(),
+            3,
+            Duration::from_secs(10),
+        );
+        let mut session = session(state);
+        let started = Instant::now();
+        session.state.start_turn_clock(started);
+        let engine = engine_for(&GameMode::Blitz);
+
+        assert!(engine
+            .check_submitter(&session, 111, started, Utc::now())
+            .is_ok());
+        assert!(matches!(
+            engine.check_submitter(&session, 111, started + Duration::from_secs(11), Utc::now()),
+            Err(ApiError::OutOfTime)
+        ));
+    }
+
+    #[test]
+    fn test_coop_words_earn_gems() {
+        let mut state = GameState::new_coop(Uuid::new_v4(), grid(), players(), 3, 100);
+        state.rules.gems = true;
+        let positions = row(6);
+        let events = engine_for(&GameMode::Coop).play_word(&state, scored(1, "aaaaaa", &positions));
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::GemsEarned { gems: 1 })
+        ));
+
+        apply(&mut state, &events);
+        assert_eq!(state.players[1].score, 6);
+        assert!(state.is_word_used("AAAAAA"));
+        assert_eq!(state.coop.unwrap().gems, 1);
+
+        let mut state = GameState::new_coop(Uuid::new_v4(), grid(), players(), 3, 100);
+        state.rules.gems = true;
+        let positions = row(3);
+        assert_eq!(
+            engine_for(&GameMode::Coop)
+                .play_word(&state, scored(1, "aaa", &positions))
+                .len(),
+            1,
+            "Short words earn no gems"
+        );
+    }
+
+    #[test]
+    fn test_duel_words_stay_on_the_players_board() {
+        let ends_at = Utc::now() + chrono::Duration::minutes(3);
+        let mut session = session(GameState::new_duel(
+            Uuid::new_v4(),
+            grid(),
+            players(),
+            7,
+            ends_at,
+        ));
+        let engine = engine_for(&GameMode::Duel);
+        let now = Instant::now();
+
+        assert_eq!(
+            engine
+                .check_submitter(&session, 222, now, Utc::now())
+                .unwrap(),
+            1,
+            "Nobody waits for a turn"
+        );
+        let positions = row(3);
+        let events = engine.play_word(&session.state, scored(1, "aaa", &positions));
+        apply(&mut session.state, &events);
+        assert!(
+            !session.state.is_word_used("aaa"),
+            "The shared board is untouched"
+        );
+        assert!(engine.board(&session.state, 1).unwrap().is_word_used("AAA"));
+        assert!(!engine.board(&session.state, 0).unwrap().is_word_used("aaa"));
+        assert!(matches!(
+            engine.private_progress(&session.state, 1, 222),
+            Some(ServerMessage::DuelScore {
+                score: 3,
+                words_played: 1,
+                ..
+            })
+        ));
+        assert!(engine.end_turn(&session.state, now).is_none());
+
+        assert_eq!(
+            engine.completion(&session.state, ends_at),
+            Some(GameOverReason::OutOfTime)
+        );
+        assert!(matches!(
+            engine.check_submitter(&session, 222, now, ends_at),
+            Err(ApiError::OutOfTime)
+        ));
+    }
+
+    #[test]
+    fn test_time_attack_ends_when_the_board_is_cleared() {
+        let ends_at = Utc::now() + chrono::Duration::minutes(3);
+        let mut state = GameState::new_time_attack(Uuid::new_v4(), grid(), players(), ends_at);
+        state.status = GameStatus::InProgress;
+        let engine = engine_for(&GameMode::TimeAttack);
+
+        let all_but_one: Vec<Position> = (0..24)
+            .map(|i| Position {
+                row: i / 5,
+                col: i % 5,
+            })
+            .collect();
+        let events = engine.play_word(&state, scored(0, "aaaa", &all_but_one));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameFinished { .. })));
+        apply(&mut state, &events);
+
+        let last = [Position { row: 4, col: 4 }];
+        let events = engine.play_word(&state, scored(1, "aaa", &last));
+        assert_eq!(
+            events.last(),
+            Some(&GameEvent::GameFinished {
+                reason: GameOverReason::BoardCleared
+            })
+        );
+        apply(&mut state, &events);
+        assert_eq!(state.status, GameStatus::Finished);
+    }
+
+    #[test]
+    fn test_puzzle_rush_boards_need_the_target_and_get_shorter_clocks() {
+        let now = Utc::now();
+        let rush = PuzzleRushState::new(7, 4, 60, 0, now);
+        let mut session = session(GameState::new_puzzle_rush(
+            Uuid::new_v4(),
+            grid(),
+            players()[..1].to_vec(),
+            rush,
+        ));
+        let engine = engine_for(&GameMode::PuzzleRush);
+        assert!(matches!(
+            engine.check_score(&session.state, 3),
+            Err(ApiError::BelowRushTarget(4))
+        ));
+        assert!(engine.check_score(&session.state, 4).is_ok());
+
+        let positions = row(4);
+        let mut events = engine.play_word(&session.state, scored(0, "aaaa", &positions));
+        let rush = session.state.puzzle_rush.as_ref().unwrap();
+        let ends_at = rush.next_ends_at(now);
+        assert_eq!(ends_at, now + chrono::Duration::seconds(55));
+        events.push(GameEvent::RushBoardDealt {
+            board: 3,
+            grid: grid(),
+            ends_at,
+        });
+        apply(&mut session.state, &events);
+
+        let rush = session.state.puzzle_rush.as_ref().unwrap();
+        assert_eq!((rush.board, rush.streak), (3, 1));
+        assert_eq!(session.state.players[0].score, 4);
+        assert!(
+            !session.state.is_word_used("aaaa"),
+            "Each board starts with no words used"
+        );
+        assert!(engine.completion(&session.state, now).is_none());
+        assert_eq!(
+            engine.completion(&session.state, ends_at),
+            Some(GameOverReason::OutOfTime)
+        );
+    }
+
+    #[test]
+    fn test_hot_seat_words_never_count_for_quests() {
+        let state = GameState::new_hot_seat(Uuid::new_v4(), grid(), players(), 3);
+        assert!(!engine_for(&GameMode::HotSeat).counts_for_quests(&state));
+        assert!(engine_for(&GameMode::Multiplayer).counts_for_quests(&state));
+    }
+}
@@ -1,25 +1,147 @@
-use rand::Rng;
+use rand::{rngs::StdRng, seq::index::sample, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    game::letter_bag::LetterBag,
     models::{Grid, GridCell, Multiplier},
     utils::letters::{get_cumulative_distribution, get_letter_value},
 };
 
+/// Rows and columns of every board
+pub const GRID_SIZE: usize = 5;
+
+/// How multipliers are placed on a new board, chosen per lobby
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiplierLayout {
+    /// SpellCast's own pattern: exactly one double letter and one double word
+    Classic,
+    /// 3-5 double letters and 2-3 triple letters, anywhere
+    #[default]
+    Chaos,
+    /// Plain letters only
+    None,
+}
+
+/// Where a lobby's letters come from, chosen per lobby
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LetterMode {
+    /// Each letter drawn independently, weighted by English letter frequency
+    #[default]
+    Weighted,
+    /// Drawn from a finite Scrabble-style bag per game; played tiles are
+    /// replaced from what's left in it
+    Bag,
+}
+
+impl MultiplierLayout {
+    /// Place this layout's multipliers on a board that has none
+    fn place(self, grid: &mut Grid, rng: &mut impl Rng) {
+        match self {
+            Self::Classic => place_multipliers(
+                grid,
+                rng,
+                &[(Multiplier::DoubleLetter, 1), (Multiplier::DoubleWord, 1)],
+            ),
+            Self::Chaos => {
+                let double_letters = rng.random_range(3..=5);
+                let triple_letters = rng.random_range(2..=3);
+                place_multipliers(
+                    grid,
+                    rng,
+                    &[
+                        (Multiplier::DoubleLetter, double_letters),
+                        (Multiplier::TripleLetter, triple_letters),
+                    ],
+                );
+            }
+            Self::None => {}
+        }
+    }
+}
+
+/// Put each multiplier on its own randomly chosen cell
+fn place_multipliers(grid: &mut Grid, rng: &mut impl Rng, counts: &[(Multiplier, usize)]) {
+    let cells: Vec<(usize, usize)> = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| (0..cells.len()).map(move |col| (row, col)))
+        .collect();
+    let multipliers = counts
+        .iter()
+        .flat_map(|(multiplier, count)| std::iter::repeat_n(multiplier, *count));
+    let total = counts.iter().map(|(_, count)| count).sum::<usize>();
+
+    for (index, multiplier) in sample(rng, cells.len(), total.min(cells.len()))
+        .into_iter()
+        .zip(multipliers)
+    {
+        let (row, col) = cells[index];
+        grid[row][col].multiplier = Some(multiplier.clone());
+    }
+}
+
 pub struct GridGenerator;
 
 impl GridGenerator {
     /// Generate a new 5x5 grid with weighted letter distribution
-    pub fn generate() -> Grid {
-        let mut rng = rand::rng();
+    pub fn generate(layout: MultiplierLayout) -> Grid {
+        Self::generate_sized(layout, GRID_SIZE, &mut rand::rng())
+    }
+
+    /// Generate a `size`x`size` grid like `generate`, from the given RNG
+    ///
+    /// Only the `simulate` command deals other sizes; a seeded RNG makes its
+    /// runs repeatable.
+    pub fn generate_sized(layout: MultiplierLayout, size: usize, rng: &mut impl Rng) -> Grid {
         let cumulative_dist = get_cumulative_distribution();
         let total = cumulative_dist.last().unwrap().1;
+        Self::fill(layout, size, rng, |rng| {
+            Self::random_letter(&cumulative_dist, total, rng)
+        })
+    }
 
-        let mut grid = Vec::with_capacity(5);
+    /// Generate a new 5x5 grid with letters drawn from a bag, which keeps the rest
+    pub fn generate_from_bag(layout: MultiplierLayout, bag: &mut LetterBag) -> Grid {
+        // A full bag holds far more tiles than a board; the fallback never fires in play
+        Self::fill(layout, GRID_SIZE, &mut rand::rng(), |rng| {
+            bag.draw(rng).unwrap_or('E')
+        })
+    }
 
-        for _ in 0..5 {
-            let mut row = Vec::with_capacity(5);
-            for _ in 0..5 {
-                let letter = Self::random_letter(&cumulative_dist, total, &mut rng);
+    /// The `index`th letter of the sequence `seed` picks, weighted like `generate`
+    ///
+    /// Duel boards refill from one shared sequence, so both players' boards get
+    /// the same new letters in the same order.
+    pub fn seeded_letter(seed: u64, index: u32) -> char {
+        let cumulative_dist = get_cumulative_distribution();
+        let total = cumulative_dist.last().unwrap().1;
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(index as u64));
+        Self::random_letter(&cumulative_dist, total, &mut rng)
+    }
+
+    /// The `index`th board of the sequence `seed` picks, weighted like `generate`
+    ///
+    /// Puzzle rushes deal from a seeded sequence, so a run's boards can be
+    /// dealt again from the seed alone.
+    pub fn seeded_board(layout: MultiplierLayout, seed: u64, index: u32) -> Grid {
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(index as u64));
+        Self::generate_sized(layout, GRID_SIZE, &mut rng)
+    }
+
+    fn fill<R: Rng>(
+        layout: MultiplierLayout,
+        size: usize,
+        rng: &mut R,
+        mut next_letter: impl FnMut(&mut R) -> char,
+    ) -> Grid {
+        let mut grid = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            let mut row = Vec::with_capacity(size);
+            for _ in 0..size {
+                let letter = next_letter(rng);
                 row.push(GridCell {
                     letter,
                     value: get_letter_value(letter),
@@ -29,8 +151,7 @@ impl GridGenerator {
             grid.push(row);
         }
 
-        // Add multipliers
-        Self::add_multipliers(&mut grid, &mut rng);
+        layout.place(&mut grid, rng);
 
         grid
     }
@@ -46,49 +167,217 @@ impl GridGenerator {
 
         'E' // Fallback
     }
+}
 
-    fn add_multipliers(grid: &mut Grid, rng: &mut impl Rng) {
-        // Add 3-5 double letter multipliers
-        let dl_count = rng.random_range(3..=5);
-        for _ in 0..dl_count {
-            let row = rng.random_range(0..5);
-            let col = rng.random_range(0..5);
-            if grid[row][col].multiplier.is_none() {
-                grid[row][col].multiplier = Some(Multiplier::DoubleLetter);
-            }
-        }
-
-        // Add 2-3 triple letter multipliers
-        let tl_count = rng.random_range(2..=3);
-        for _ in 0..tl_count {
-            let row = rng.random_range(0..5);
-            let col = rng.random_range(0..5);
-            if grid[row][col].multiplier.is_none() {
-                grid[row][col].multiplier = Some(Multiplier::TripleLetter);
-            }
+/// A hand-made board, checked to have the shape of a dealt one: `GRID_SIZE`
+/// rows of `GRID_SIZE` letters
+///
+/// Letters are uppercased and given their standard values, whatever values
+/// were sent; multipliers are kept as placed.
+pub fn normalize_board(mut grid: Grid) -> Result<Grid, String> {
+    if grid.len() != GRID_SIZE || grid.iter().any(|row| row.len() != GRID_SIZE) {
+        return Err(format!("the board must be {0}x{0}", GRID_SIZE));
+    }
+    for cell in grid.iter_mut().flatten() {
+        if !cell.letter.is_alphabetic() {
+            return Err(format!("{:?} isn't a letter", cell.letter));
         }
+        cell.letter = cell.letter.to_uppercase().next().unwrap_or(cell.letter);
+        cell.value = get_letter_value(cell.letter);
     }
+    Ok(grid)
+}
+
+/// Checksum of a board, so clients can detect that their copy is stale
+///
+/// 32-bit FNV-1a over the canonical form: rows separated by `/`, cells by `,`, and
+/// each cell written as letter, value and multiplier (`DL`, `TL`, `DW` or nothing),
+/// e.g. `A1,B3DL/...`. Returned as 8 lowercase hex digits.
+pub fn board_checksum(grid: &[Vec<GridCell>]) -> String {
+    const FNV_OFFSET: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    let canonical = grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    let multiplier = match cell.multiplier {
+                        Some(Multiplier::DoubleLetter) => "DL",
+                        Some(Multiplier::TripleLetter) => "TL",
+                        Some(Multiplier::DoubleWord) => "DW",
+                        None => "",
+                    };
+                    format!("{}{}{}", cell.letter, cell.value, multiplier)
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let hash = canonical.bytes().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(FNV_PRIME)
+    });
+    format!("{:08x}", hash)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_board_checksum_is_stable() {
+        let cell = |letter, value, multiplier| GridCell {
+            letter,
+            value,
+            multiplier,
+        };
+        let grid = vec![vec![
+            cell('A', 1, None),
+            cell('B', 3, Some(Multiplier::DoubleLetter)),
+        ]];
+
+        // FNV-1a of "A1,B3DL"; must not drift, since clients compare against it
+        assert_eq!(board_checksum(&grid), "747db2b6");
+        let mut double_word = grid.clone();
+        double_word[0][1].multiplier = Some(Multiplier::DoubleWord);
+        assert_ne!(board_checksum(&grid), board_checksum(&double_word));
+
+        let mut changed = grid.clone();
+        changed[0][1].multiplier = Some(Multiplier::TripleLetter);
+        assert_ne!(
+            board_checksum(&grid),
+            board_checksum(&changed),
+            "Any change to the board changes the checksum"
+        );
+    }
+
     #[test]
     fn test_grid_generation() {
-        let grid = GridGenerator::generate();
+        let grid = GridGenerator::generate(MultiplierLayout::Chaos);
         assert_eq!(grid.len(), 5);
         assert!(grid.iter().all(|row| row.len() == 5));
     }
 
+    #[test]
+    fn test_sized_generation_is_seeded() {
+        let grid = GridGenerator::generate_sized(
+            MultiplierLayout::Classic,
+            7,
+            &mut StdRng::seed_from_u64(3),
+        );
+        assert_eq!(grid.len(), 7);
+        assert!(grid.iter().all(|row| row.len() == 7));
+        assert_eq!(
+            grid,
+            GridGenerator::generate_sized(
+                MultiplierLayout::Classic,
+                7,
+                &mut StdRng::seed_from_u64(3)
+            ),
+            "Same seed, same board"
+        );
+    }
+
+    fn count(grid: &Grid, multiplier: Multiplier) -> usize {
+        grid.iter()
+            .flatten()
+            .filter(|cell| cell.multiplier.as_ref() == Some(&multiplier))
+            .count()
+    }
+
+    #[test]
+    fn test_classic_layout() {
+        for _ in 0..200 {
+            let grid = GridGenerator::generate(MultiplierLayout::Classic);
+            assert_eq!(count(&grid, Multiplier::DoubleLetter), 1);
+            assert_eq!(count(&grid, Multiplier::DoubleWord), 1);
+            assert_eq!(
+                count(&grid, Multiplier::TripleLetter),
+                0,
+                "Classic boards have no triple letters"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chaos_layout() {
+        for _ in 0..200 {
+            let grid = GridGenerator::generate(MultiplierLayout::Chaos);
+            assert!((3..=5).contains(&count(&grid, Multiplier::DoubleLetter)));
+            assert!((2..=3).contains(&count(&grid, Multiplier::TripleLetter)));
+            assert_eq!(count(&grid, Multiplier::DoubleWord), 0);
+        }
+    }
+
+    #[test]
+    fn test_no_multiplier_layout() {
+        let grid = GridGenerator::generate(MultiplierLayout::None);
+        assert!(grid.iter().flatten().all(|cell| cell.multiplier.is_none()));
+        assert_eq!(grid.len(), GRID_SIZE);
+    }
+
+    #[test]
+    fn test_bag_grid_draws_from_bag() {
+        let mut bag = LetterBag::scrabble();
+        let grid = GridGenerator::generate_from_bag(MultiplierLayout::Classic, &mut bag);
+        assert_eq!(
+            bag.remaining(),
+            98 - (GRID_SIZE * GRID_SIZE) as u32,
+            "The board's tiles are no longer in the bag"
+        );
+        let zs = grid
+            .iter()
+            .flatten()
+            .filter(|cell| cell.letter == 'Z')
+            .count() as u32;
+        assert_eq!(
+            zs + bag.count('Z'),
+            1,
+            "A bag has a single Z between board and bag"
+        );
+    }
+
+    #[test]
+    fn test_layout_names() {
+        let layout: MultiplierLayout = serde_json::from_str(r#""classic""#).unwrap();
+        assert_eq!(layout, MultiplierLayout::Classic);
+        assert_eq!(
+            MultiplierLayout::default(),
+            MultiplierLayout::Chaos,
+            "Lobbies keep the original random layout unless the host picks one"
+        );
+    }
+
     #[test]
     fn test_grid_has_multipliers() {
-        let grid = GridGenerator::generate();
+        let grid = GridGenerator::generate(MultiplierLayout::Chaos);
         let multiplier_count = grid
             .iter()
             .flatten()
             .filter(|cell| cell.multiplier.is_some())
             .count();
-        assert!(multiplier_count >= 5 && multiplier_count <= 8);
+        assert!((5..=8).contains(&multiplier_count));
+    }
+
+    #[test]
+    fn test_normalize_board() {
+        let mut grid = GridGenerator::generate(MultiplierLayout::Classic);
+        grid[0][0] = GridCell {
+            letter: 'q',
+            value: 99,
+            multiplier: Some(Multiplier::DoubleWord),
+        };
+        let board = normalize_board(grid.clone()).unwrap();
+        assert_eq!(board[0][0].letter, 'Q');
+        assert_eq!(board[0][0].value, get_letter_value('Q'));
+        assert_eq!(board[0][0].multiplier, Some(Multiplier::DoubleWord));
+
+        let mut short = grid.clone();
+        short[2].pop();
+        assert!(normalize_board(short).is_err());
+        grid[1][1].letter = '7';
+        assert!(normalize_board(grid).is_err());
     }
 }
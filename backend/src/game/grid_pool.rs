@@ -0,0 +1,226 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+
+use crate::{
+    game::{
+        grid::{GridGenerator, LetterMode, MultiplierLayout, GRID_SIZE},
+        letter_bag::LetterBag,
+    },
+    locale::DEFAULT_LANGUAGE,
+    models::Grid,
+    AppState,
+};
+
+/// How often the pool is topped up even if nothing was taken
+pub const GRID_POOL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The kind of board a lobby needs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BoardKey {
+    pub size: usize,
+    /// Lowercased language tag, as from `Lobby::language`
+    pub language: String,
+    pub layout: MultiplierLayout,
+}
+
+impl BoardKey {
+    pub fn new(language: &str, layout: MultiplierLayout) -> Self {
+        Self {
+            size: GRID_SIZE,
+            language: language.to_ascii_lowercase(),
+            layout,
+        }
+    }
+}
+
+/// Generate a board of the given kind, running whatever checks a board must pass
+///
+/// Called off the async runtime by the pool, and inline when the pool is empty.
+pub fn generate_board(key: &BoardKey) -> Grid {
+    GridGenerator::generate(key.layout)
+}
+
+/// Boards generated ahead of time so starting a game doesn't wait on generation
+///
+/// Holds up to `target` boards per kind. Kinds are added as lobbies ask for them,
+/// so a language or layout is only pooled once someone has played with it.
+pub struct GridPool {
+    boards: DashMap<BoardKey, VecDeque<Grid>>,
+    target: usize,
+    refill: Notify,
+}
+
+impl GridPool {
+    /// A pool of `target` boards per kind; 0 disables pooling
+    pub fn new(target: usize) -> Self {
+        let boards = DashMap::new();
+        if target > 0 {
+            boards.insert(
+                BoardKey::new(DEFAULT_LANGUAGE, MultiplierLayout::default()),
+                VecDeque::new(),
+            );
+        }
+        Self {
+            boards,
+            target,
+            refill: Notify::new(),
+        }
+    }
+
+    /// A pooled board of the given kind, if one is ready
+    pub fn take(&self, key: &BoardKey) -> Option<Grid> {
+        if self.target == 0 {
+            return None;
+        }
+        let board = self.boards.entry(key.clone()).or_default().pop_front();
+        self.refill.notify_one();
+        board
+    }
+
+    /// A pooled board, or a freshly generated one when the pool has run dry
+    pub fn board_for(&self, key: &BoardKey) -> Grid {
+        self.take(key).unwrap_or_else(|| {
+            tracing::debug!("Grid pool empty for {:?}; generating inline", key);
+            generate_board(key)
+        })
+    }
+
+    /// A board for a new game, and in bag mode the bag it was drawn from
+    ///
+    /// Bag boards aren't pooled: the rest of the bag has to stay with its board,
+    /// and drawing 25 tiles is quick anyway.
+    pub fn deal(&self, key: &BoardKey, letters: LetterMode) -> (Grid, Option<LetterBag>) {
+        match letters {
+            LetterMode::Weighted => (self.board_for(key), None),
+            LetterMode::Bag => {
+                let mut bag = LetterBag::scrabble();
+                let board = GridGenerator::generate_from_bag(key.layout, &mut bag);
+                (board, Some(bag))
+            }
+        }
+    }
+
+    /// How many boards each kind is short of its target
+    fn shortfall(&self) -> Vec<(BoardKey, usize)> {
+        self.boards
+            .iter()
+            .filter(|entry| entry.len() < self.target)
+            .map(|entry| (entry.key().clone(), self.target - entry.len()))
+            .collect()
+    }
+
+    fn push(&self, key: BoardKey, board: Grid) {
+        let mut boards = self.boards.entry(key).or_default();
+        if boards.len() < self.target {
+            boards.push_back(board);
+        }
+    }
+}
+
+/// Background task that keeps the grid pool full
+///
+/// Wakes whenever a board is taken, and on an interval as a safety net. Boards are
+/// generated on the blocking pool so slow quality checks don't stall other tasks.
+pub async fn grid_pool_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(GRID_POOL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = state.grid_pool.refill.notified() => {}
+        }
+        state.record_heartbeat("grid_pool", GRID_POOL_INTERVAL);
+
+        for (key, missing) in state.grid_pool.shortfall() {
+            for _ in 0..missing {
+                let board_key = key.clone();
+                match tokio::task::spawn_blocking(move || generate_board(&board_key)).await {
+                    Ok(board) => state.grid_pool.push(key.clone(), board),
+                    Err(e) => {
+                        tracing::error!("Grid generation for {:?} failed: {}", key, e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en() -> BoardKey {
+        BoardKey::new("en", MultiplierLayout::default())
+    }
+
+    fn fill(pool: &GridPool) {
+        for (key, missing) in pool.shortfall() {
+            for _ in 0..missing {
+                pool.push(key.clone(), generate_board(&key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_starts_with_default_language() {
+        let pool = GridPool::new(2);
+        assert_eq!(pool.shortfall(), vec![(en(), 2)]);
+
+        fill(&pool);
+        assert!(
+            pool.shortfall().is_empty(),
+            "A full pool has nothing to make"
+        );
+        pool.push(en(), generate_board(&en()));
+        assert_eq!(
+            pool.boards.get(&en()).unwrap().len(),
+            2,
+            "The pool never grows past its target"
+        );
+    }
+
+    #[test]
+    fn test_take_tracks_new_languages() {
+        let pool = GridPool::new(2);
+        fill(&pool);
+
+        let es = BoardKey::new("ES", MultiplierLayout::Chaos);
+        assert_eq!(es.language, "es", "Language tags are compared lowercased");
+        assert!(pool.take(&es).is_none(), "Nothing is pooled for es yet");
+        assert_eq!(pool.shortfall(), vec![(es.clone(), 2)]);
+
+        fill(&pool);
+        let board = pool.take(&es).expect("es boards are pooled once asked for");
+        assert_eq!(board.len(), GRID_SIZE);
+        assert_eq!(pool.shortfall(), vec![(es, 1)]);
+    }
+
+    #[test]
+    fn test_disabled_pool_generates_inline() {
+        let pool = GridPool::new(0);
+        let key = BoardKey::new("en", MultiplierLayout::None);
+        assert!(pool.shortfall().is_empty());
+        assert!(pool.take(&key).is_none());
+        assert_eq!(pool.board_for(&key).len(), GRID_SIZE);
+        assert!(pool.shortfall().is_empty(), "A disabled pool never fills");
+    }
+
+    #[test]
+    fn test_bag_boards_bypass_the_pool() {
+        let pool = GridPool::new(2);
+        fill(&pool);
+
+        let (board, bag) = pool.deal(&en(), LetterMode::Bag);
+        let bag = bag.expect("Bag games get their bag");
+        assert_eq!(bag.remaining(), 98 - (GRID_SIZE * GRID_SIZE) as u32);
+        assert_eq!(board.len(), GRID_SIZE);
+        assert!(pool.shortfall().is_empty(), "No pooled board was used");
+
+        let (_, bag) = pool.deal(&en(), LetterMode::Weighted);
+        assert!(bag.is_none());
+        assert_eq!(pool.shortfall(), vec![(en(), 1)]);
+    }
+}
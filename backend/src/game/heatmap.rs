@@ -0,0 +1,82 @@
+use crate::models::{GameMove, Position};
+
+/// Count how many times each tile was used across a game's moves
+///
+/// Returns a `rows` x `cols` grid of counts. Moves whose stored positions can't be
+/// parsed, and positions outside the grid, are ignored.
+pub fn tile_heatmap(moves: &[GameMove], rows: usize, cols: usize) -> Vec<Vec<u32>> {
+    let mut heatmap = vec![vec![0; cols]; rows];
+
+    for game_move in moves {
+        let Ok(positions) = serde_json::from_value::<Vec<Position>>(game_move.positions.clone())
+        else {
+            tracing::warn!(
+                "Skipping move {} in heatmap: invalid positions",
+                game_move.id
+            );
+            continue;
+        };
+        for pos in positions {
+            if let Some(count) = heatmap
+                .get_mut(pos.row)
+                .and_then(|row| row.get_mut(pos.col))
+            {
+                *count += 1;
+            }
+        }
+    }
+
+    heatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn create_test_move(id: i32, positions: serde_json::Value) -> GameMove {
+        GameMove {
+            id,
+            game_id: Uuid::new_v4(),
+            user_id: 111,
+            round_number: 1,
+            word: "TEST".to_string(),
+            score: 5,
+            positions,
+            timestamp: Utc::now(),
+            move_time_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_heatmap_counts_tile_usage() {
+        let moves = vec![
+            create_test_move(
+                1,
+                serde_json::json!([{"row": 0, "col": 0}, {"row": 0, "col": 1}]),
+            ),
+            create_test_move(
+                2,
+                serde_json::json!([{"row": 0, "col": 1}, {"row": 1, "col": 1}]),
+            ),
+        ];
+
+        let heatmap = tile_heatmap(&moves, 2, 2);
+        assert_eq!(heatmap, vec![vec![1, 2], vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_heatmap_ignores_bad_positions() {
+        let moves = vec![
+            create_test_move(1, serde_json::json!("not positions")),
+            create_test_move(2, serde_json::json!([{"row": 9, "col": 0}])),
+        ];
+
+        let heatmap = tile_heatmap(&moves, 2, 2);
+        assert!(
+            heatmap.iter().flatten().all(|count| *count == 0),
+            "Unparseable and out-of-range positions should not be counted"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Tiles in a fresh bag: Scrabble's English distribution without the blanks
+const SCRABBLE_TILES: [(char, u32); 26] = [
+    ('A', 9),
+    ('B', 2),
+    ('C', 2),
+    ('D', 4),
+    ('E', 12),
+    ('F', 2),
+    ('G', 3),
+    ('H', 2),
+    ('I', 9),
+    ('J', 1),
+    ('K', 1),
+    ('L', 4),
+    ('M', 2),
+    ('N', 6),
+    ('O', 8),
+    ('P', 2),
+    ('Q', 1),
+    ('R', 6),
+    ('S', 4),
+    ('T', 6),
+    ('U', 4),
+    ('V', 2),
+    ('W', 2),
+    ('X', 1),
+    ('Y', 2),
+    ('Z', 1),
+];
+
+/// A finite pool of letter tiles for one game
+///
+/// The board is dealt from the bag and played tiles are replaced from what's left,
+/// so once the Z has been drawn there are no more. Serializes as the remaining
+/// count per letter, including letters that have run out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LetterBag {
+    counts: BTreeMap<char, u32>,
+}
+
+impl LetterBag {
+    /// A full bag of 98 tiles
+    pub fn scrabble() -> Self {
+        Self {
+            counts: SCRABBLE_TILES.into_iter().collect(),
+        }
+    }
+
+    /// Tiles left to draw
+    pub fn remaining(&self) -> u32 {
+        self.counts.values().sum()
+    }
+
+    /// Tiles of one letter left to draw
+    pub fn count(&self, letter: char) -> u32 {
+        self.counts.get(&letter).copied().unwrap_or(0)
+    }
+
+    /// Draw a random tile, each remaining tile equally likely; None once empty
+    pub fn draw(&mut self, rng: &mut impl Rng) -> Option<char> {
+        let remaining = self.remaining();
+        if remaining == 0 {
+            return None;
+        }
+        let mut pick = rng.random_range(0..remaining);
+        for (letter, count) in self.counts.iter_mut() {
+            if pick < *count {
+                *count -= 1;
+                return Some(*letter);
+            }
+            pick -= *count;
+        }
+        None
+    }
+
+    /// Take a particular tile out of the bag, as when a draw is replayed; false if
+    /// there are none left
+    pub fn take(&mut self, letter: char) -> bool {
+        match self.counts.get_mut(&letter) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrabble_bag() {
+        let bag = LetterBag::scrabble();
+        assert_eq!(bag.remaining(), 98);
+        assert_eq!(bag.count('E'), 12);
+        assert_eq!(bag.count('Z'), 1);
+        assert_eq!(bag.count('?'), 0, "No blanks");
+    }
+
+    #[test]
+    fn test_draw_empties_the_bag() {
+        let mut bag = LetterBag::scrabble();
+        let mut rng = rand::rng();
+        let mut drawn = BTreeMap::new();
+        while let Some(letter) = bag.draw(&mut rng) {
+            *drawn.entry(letter).or_insert(0) += 1;
+        }
+        assert_eq!(bag.remaining(), 0);
+        assert_eq!(
+            drawn,
+            LetterBag::scrabble().counts,
+            "Every tile is drawn exactly once"
+        );
+        assert_eq!(bag.draw(&mut rng), None);
+    }
+
+    #[test]
+    fn test_take_a_replayed_draw() {
+        let mut bag = LetterBag::scrabble();
+        assert!(bag.take('Z'));
+        assert_eq!(bag.count('Z'), 0);
+        assert_eq!(bag.remaining(), 97);
+        assert!(!bag.take('Z'), "There was only one");
+    }
+
+    #[test]
+    fn test_bag_serializes_remaining_counts() {
+        let mut bag = LetterBag::scrabble();
+        bag.counts.insert('Z', 0);
+        let json = serde_json::to_value(&bag).unwrap();
+        assert_eq!(json["E"], 12);
+        assert_eq!(json["Z"], 0, "Letters that ran out are still listed");
+
+        let parsed: LetterBag = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, bag);
+    }
+}
@@ -1,6 +1,22 @@
 // Game engine modules
 // TODO: Implement game logic
 
+pub mod analysis;
+pub mod board_theme;
+pub mod commentary;
+pub mod engine;
 pub mod grid;
+pub mod grid_pool;
+pub mod heatmap;
+pub mod letter_bag;
+pub mod pacing;
+pub mod puzzle_rush;
+pub mod result;
+pub mod result_card;
+pub mod rules;
 pub mod scorer;
+pub mod session;
+pub mod simulate;
+pub mod solver;
 pub mod validator;
+pub mod word_plays;
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::GameMove;
+
+/// How quickly a player made their moves over a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerPacing {
+    pub user_id: i64,
+    /// Timed moves only; moves from before move timing existed are skipped
+    pub moves: u32,
+    pub average_move_ms: u64,
+    pub fastest_move_ms: u64,
+    pub slowest_move_ms: u64,
+}
+
+/// Per-player move time stats for a game, ordered by user ID
+pub fn move_pacing(moves: &[GameMove]) -> Vec<PlayerPacing> {
+    let mut times: BTreeMap<i64, Vec<u64>> = BTreeMap::new();
+    for game_move in moves {
+        if let Some(ms) = game_move.move_time_ms {
+            times
+                .entry(game_move.user_id)
+                .or_default()
+                .push(ms.max(0) as u64);
+        }
+    }
+
+    times
+        .into_iter()
+        .map(|(user_id, times)| PlayerPacing {
+            user_id,
+            moves: times.len() as u32,
+            average_move_ms: times.iter().sum::<u64>() / times.len() as u64,
+            fastest_move_ms: times.iter().copied().min().unwrap_or(0),
+            slowest_move_ms: times.iter().copied().max().unwrap_or(0),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn create_test_move(user_id: i64, move_time_ms: Option<i32>) -> GameMove {
+        GameMove {
+            id: 1,
+            game_id: Uuid::new_v4(),
+            user_id,
+            round_number: 1,
+            word: "TEST".to_string(),
+            score: 5,
+            positions: serde_json::json!([]),
+            timestamp: Utc::now(),
+            move_time_ms,
+        }
+    }
+
+    #[test]
+    fn test_move_pacing_per_player() {
+        let moves = vec![
+            create_test_move(222, Some(4_000)),
+            create_test_move(111, Some(10_000)),
+            create_test_move(111, Some(20_000)),
+            create_test_move(111, None),
+        ];
+
+        let pacing = move_pacing(&moves);
+        assert_eq!(pacing.len(), 2);
+        assert_eq!(
+            pacing[0],
+            PlayerPacing {
+                user_id: 111,
+                moves: 2,
+                average_move_ms: 15_000,
+                fastest_move_ms: 10_000,
+                slowest_move_ms: 20_000,
+            },
+            "Untimed moves don't count toward the average"
+        );
+        assert_eq!(pacing[1].user_id, 222);
+        assert_eq!(pacing[1].average_move_ms, 4_000);
+    }
+
+    #[test]
+    fn test_move_pacing_without_timed_moves() {
+        let moves = vec![create_test_move(111, None)];
+        assert!(
+            move_pacing(&moves).is_empty(),
+            "Players with no timed moves are left out"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+use crate::{
+    game::{
+        grid::{GridGenerator, MultiplierLayout},
+        rules::GameRules,
+        solver::{best_word, WordList},
+    },
+    models::Grid,
+};
+
+/// Boards of the sequence tried for one with a qualifying word before dealing
+/// the last of them anyway
+const MAX_BOARD_TRIES: u32 = 50;
+
+/// The first board from position `from` on in the sequence `seed` picks with a
+/// word worth `target_score` under `rules`, and its position
+///
+/// Nearly every board has one; after `MAX_BOARD_TRIES` boards without, the last
+/// one tried is dealt rather than searching on.
+pub fn next_board(
+    layout: MultiplierLayout,
+    seed: u64,
+    from: u32,
+    target_score: i32,
+    words: &impl WordList,
+    rules: &GameRules,
+) -> (u32, Grid) {
+    let last = from.saturating_add(MAX_BOARD_TRIES - 1);
+    for index in from..last {
+        let grid = GridGenerator::seeded_board(layout, seed, index);
+        let solvable = best_word(&grid, words, &HashSet::new(), rules)
+            .is_some_and(|best| best.score >= target_score);
+        if solvable {
+            return (index, grid);
+        }
+    }
+    (last, GridGenerator::seeded_board(layout, seed, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Dictionary;
+
+    #[test]
+    fn test_boards_have_a_qualifying_word() {
+        let dictionary = Dictionary::from_lists("eat\ntea\nate\nsea\ntoe\nrat\nten\n", "");
+        let rules = GameRules::default();
+
+        let (index, grid) = next_board(MultiplierLayout::None, 7, 0, 3, &dictionary, &rules);
+        let best = best_word(&grid, &dictionary, &HashSet::new(), &rules).unwrap();
+        assert!(best.score >= 3);
+        assert_eq!(
+            next_board(MultiplierLayout::None, 7, 0, 3, &dictionary, &rules),
+            (index, grid),
+            "The seed deals the same boards again"
+        );
+
+        // No board has a word worth that much, so the search gives up
+        let (index, _) = next_board(MultiplierLayout::None, 7, 10, 1000, &dictionary, &rules);
+        assert_eq!(index, 10 + MAX_BOARD_TRIES - 1);
+    }
+}
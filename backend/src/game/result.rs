@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CoopOutcome, GameMove};
+
+/// How a game was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOutcome {
+    /// One player won outright or on a tie-breaker
+    Win,
+    /// Players were still level after every tie-breaker and share the win
+    Draw,
+    CoopWin,
+    CoopLose,
+}
+
+/// The rule that separated players tied on score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    FewestPasses,
+    HighestWord,
+}
+
+/// Final result of a game, stored in `games.result`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub outcome: GameOutcome,
+    /// The winner, or everyone sharing a draw; empty in co-op
+    pub winners: Vec<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tie_break: Option<TieBreak>,
+    /// Percentage of the board a time attack team used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage_percent: Option<u8>,
+    /// Names of the winning local players in a hot-seat game, whose `winners`
+    /// is empty since they share one Discord user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seat_winners: Vec<String>,
+}
+
+impl GameResult {
+    /// Co-op teams win or lose together, so there are no individual winners
+    pub fn coop(outcome: CoopOutcome) -> Self {
+        Self {
+            outcome: match outcome {
+                CoopOutcome::Win => GameOutcome::CoopWin,
+                CoopOutcome::Lose => GameOutcome::CoopLose,
+            },
+            winners: Vec::new(),
+            tie_break: None,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        }
+    }
+
+    /// Time attack teams win by clearing the board, and otherwise score by how
+    /// much of it they covered
+    pub fn time_attack(coverage_percent: u8) -> Self {
+        Self {
+            coverage_percent: Some(coverage_percent),
+            ..Self::coop(match coverage_percent {
+                100 => CoopOutcome::Win,
+                _ => CoopOutcome::Lose,
+            })
+        }
+    }
+
+    /// A hot-seat result from one decided over seat indices, with the winning
+    /// seats named
+    pub fn hot_seat(by_seat: GameResult, names: &[String]) -> Self {
+        Self {
+            seat_winners: by_seat
+                .winners
+                .iter()
+                .filter_map(|seat| names.get(*seat as usize).cloned())
+                .collect(),
+            winners: Vec::new(),
+            ..by_seat
+        }
+    }
+
+    /// The single winner, if there is one
+    pub fn winner(&self) -> Option<i64> {
+        match self.outcome {
+            GameOutcome::Win => self.winners.first().copied(),
+            _ => None,
+        }
+    }
+}
+
+/// A player's final numbers, for deciding the winner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalStanding {
+    pub user_id: i64,
+    pub score: i32,
+    /// Turns passed over the whole game
+    pub passes: u32,
+    /// Score of the player's highest-scoring word
+    pub best_word: i32,
+    /// Resigned players can't win unless everyone resigned
+    pub forfeited: bool,
+}
+
+/// Pick the winner of a competitive game
+///
+/// The highest score among players who didn't resign wins. Players tied on score
+/// are separated by fewest passes, then by highest single word; anyone still level
+/// shares a draw.
+pub fn decide_result(standings: &[FinalStanding]) -> GameResult {
+    let mut contenders: Vec<&FinalStanding> = standings.iter().filter(|s| !s.forfeited).collect();
+    if contenders.is_empty() {
+        contenders = standings.iter().collect();
+    }
+
+    let Some(top_score) = contenders.iter().map(|s| s.score).max() else {
+        return GameResult {
+            outcome: GameOutcome::Draw,
+            winners: Vec::new(),
+            tie_break: None,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        };
+    };
+    contenders.retain(|s| s.score == top_score);
+    let mut tied = contenders;
+
+    let mut tie_break = None;
+    if tied.len() > 1 {
+        tie_break = Some(TieBreak::FewestPasses);
+        let fewest = tied.iter().map(|s| s.passes).min().unwrap_or(0);
+        tied.retain(|s| s.passes == fewest);
+    }
+    if tied.len() > 1 {
+        tie_break = Some(TieBreak::HighestWord);
+        let best = tied.iter().map(|s| s.best_word).max().unwrap_or(0);
+        tied.retain(|s| s.best_word == best);
+    }
+
+    let winners = tied.iter().map(|s| s.user_id).collect();
+    if tied.len() > 1 {
+        GameResult {
+            outcome: GameOutcome::Draw,
+            winners,
+            tie_break: None,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        }
+    } else {
+        GameResult {
+            outcome: GameOutcome::Win,
+            winners,
+            tie_break,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        }
+    }
+}
+
+/// Each player's highest-scoring word in a game
+pub fn best_word_scores(moves: &[GameMove]) -> HashMap<i64, i32> {
+    let mut best = HashMap::new();
+    for game_move in moves {
+        let entry = best.entry(game_move.user_id).or_insert(game_move.score);
+        *entry = (*entry).max(game_move.score);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(user_id: i64, score: i32, passes: u32, best_word: i32) -> FinalStanding {
+        FinalStanding {
+            user_id,
+            score,
+            passes,
+            best_word,
+            forfeited: false,
+        }
+    }
+
+    #[test]
+    fn test_highest_score_wins_outright() {
+        let result = decide_result(&[standing(1, 40, 3, 10), standing(2, 55, 0, 12)]);
+        assert_eq!(result.outcome, GameOutcome::Win);
+        assert_eq!(result.winner(), Some(2));
+        assert_eq!(result.tie_break, None, "No tie to break");
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_passes_then_best_word() {
+        let result = decide_result(&[
+            standing(1, 50, 2, 30),
+            standing(2, 50, 1, 10),
+            standing(3, 20, 0, 40),
+        ]);
+        assert_eq!(
+            result.winner(),
+            Some(2),
+            "Fewest passes among the tied players"
+        );
+        assert_eq!(result.tie_break, Some(TieBreak::FewestPasses));
+
+        let result = decide_result(&[standing(1, 50, 1, 30), standing(2, 50, 1, 10)]);
+        assert_eq!(
+            result.winner(),
+            Some(1),
+            "Same passes, so the best word decides"
+        );
+        assert_eq!(result.tie_break, Some(TieBreak::HighestWord));
+    }
+
+    #[test]
+    fn test_full_tie_is_a_shared_draw() {
+        let result = decide_result(&[
+            standing(1, 50, 1, 30),
+            standing(2, 50, 1, 30),
+            standing(3, 10, 0, 10),
+        ]);
+        assert_eq!(result.outcome, GameOutcome::Draw);
+        assert_eq!(result.winners, vec![1, 2]);
+        assert_eq!(result.winner(), None, "A draw has no single winner");
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["outcome"], "draw");
+        assert!(json.get("tie_break").is_none());
+    }
+
+    #[test]
+    fn test_resigned_players_cannot_win() {
+        let resigned = FinalStanding {
+            forfeited: true,
+            ..standing(1, 90, 0, 40)
+        };
+        let result = decide_result(&[resigned, standing(2, 10, 3, 5)]);
+        assert_eq!(
+            result.winner(),
+            Some(2),
+            "The last player standing wins despite the lower score"
+        );
+    }
+
+    #[test]
+    fn test_coop_results_have_no_winners() {
+        let result = GameResult::coop(CoopOutcome::Win);
+        assert_eq!(result.outcome, GameOutcome::CoopWin);
+        assert!(result.winners.is_empty());
+        assert_eq!(result.winner(), None);
+    }
+
+    #[test]
+    fn test_hot_seat_winners_are_named_seats() {
+        let names = vec!["Ann".to_string(), "Bo".to_string()];
+        let result = GameResult::hot_seat(
+            decide_result(&[standing(0, 30, 0, 10), standing(1, 45, 0, 12)]),
+            &names,
+        );
+        assert_eq!(result.outcome, GameOutcome::Win);
+        assert_eq!(result.seat_winners, vec!["Bo".to_string()]);
+        assert_eq!(result.winner(), None, "No Discord user beat another");
+
+        let draw = GameResult::hot_seat(
+            decide_result(&[standing(0, 30, 0, 10), standing(1, 30, 0, 10)]),
+            &names,
+        );
+        assert_eq!(draw.outcome, GameOutcome::Draw);
+        assert_eq!(draw.seat_winners, names);
+    }
+
+    #[test]
+    fn test_time_attack_is_won_by_clearing_the_board() {
+        let cleared = GameResult::time_attack(100);
+        assert_eq!(cleared.outcome, GameOutcome::CoopWin);
+        assert_eq!(cleared.coverage_percent, Some(100));
+
+        let short = GameResult::time_attack(88);
+        assert_eq!(short.outcome, GameOutcome::CoopLose);
+        let json = serde_json::to_value(&short).unwrap();
+        assert_eq!(json["coverage_percent"], 88);
+        assert!(serde_json::to_value(GameResult::coop(CoopOutcome::Win))
+            .unwrap()
+            .get("coverage_percent")
+            .is_none());
+    }
+}
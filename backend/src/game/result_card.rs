@@ -0,0 +1,213 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::result::{GameOutcome, GameResult},
+    models::{GameMode, PlayerFinalScore},
+};
+
+/// One line of a result card's scoreboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardStanding {
+    /// 1 for the top score; tied players share a rank
+    pub rank: u32,
+    pub user_id: i64,
+    pub username: String,
+    pub score: i32,
+    pub winner: bool,
+}
+
+/// The game's highest-scoring word
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardWord {
+    pub user_id: i64,
+    pub username: String,
+    pub word: String,
+    pub score: i32,
+}
+
+/// A finished game's result, ready for the client to share into its channel
+///
+/// Built from what the server stored, so a client can post it but not change it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCard {
+    pub game_id: String,
+    pub game_mode: GameMode,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// One line summing up the result, e.g. "alice wins with 120 points"
+    pub title: String,
+    /// The best word, if anyone scored one
+    pub description: Option<String>,
+    pub standings: Vec<CardStanding>,
+    pub best_word: Option<CardWord>,
+}
+
+impl ResultCard {
+    /// `scores` must be highest first, as `get_final_scores` returns them
+    pub fn new(
+        game_id: String,
+        game_mode: GameMode,
+        finished_at: Option<DateTime<Utc>>,
+        result: Option<&GameResult>,
+        scores: &[PlayerFinalScore],
+    ) -> Self {
+        let winners = result.map(|r| r.winners.as_slice()).unwrap_or_default();
+        let mut standings: Vec<CardStanding> = Vec::with_capacity(scores.len());
+        for (index, player) in scores.iter().enumerate() {
+            let rank = match standings.last() {
+                Some(last) if last.score == player.score => last.rank,
+                _ => index as u32 + 1,
+            };
+            standings.push(CardStanding {
+                rank,
+                user_id: player.user_id,
+                username: player.username.clone(),
+                score: player.score,
+                winner: winners.contains(&player.user_id),
+            });
+        }
+
+        let best_word = scores
+            .iter()
+            .filter_map(|player| {
+                Some(CardWord {
+                    user_id: player.user_id,
+                    username: player.username.clone(),
+                    word: player.best_word.clone()?,
+                    score: player.best_word_score?,
+                })
+            })
+            .fold(None, |best: Option<CardWord>, word| match best {
+                Some(best) if best.score >= word.score => Some(best),
+                _ => Some(word),
+            });
+
+        Self {
+            game_id,
+            game_mode,
+            finished_at,
+            title: title(result, &standings),
+            description: best_word.as_ref().map(|word| {
+                format!(
+                    "Best word: {} ({} points) by {}",
+                    word.word, word.score, word.username
+                )
+            }),
+            standings,
+            best_word,
+        }
+    }
+}
+
+fn title(result: Option<&GameResult>, standings: &[CardStanding]) -> String {
+    let team_score: i32 = standings.iter().map(|s| s.score).sum();
+    let winners: Vec<&CardStanding> = standings.iter().filter(|s| s.winner).collect();
+    if let Some(percent) = result.and_then(|r| r.coverage_percent) {
+        return match percent {
+            100 => "The team cleared the board".to_string(),
+            _ => format!("The team covered {}% of the board", percent),
+        };
+    }
+    if let Some(result) = result.filter(|r| !r.seat_winners.is_empty()) {
+        return match result.outcome {
+            GameOutcome::Draw => format!("{} draw", result.seat_winners.join(" and ")),
+            _ => format!("{} wins", result.seat_winners.join(" and ")),
+        };
+    }
+    match result.map(|r| r.outcome) {
+        Some(GameOutcome::Win) if !winners.is_empty() => {
+            format!(
+                "{} wins with {} points",
+                winners[0].username, winners[0].score
+            )
+        }
+        Some(GameOutcome::Draw) if !winners.is_empty() => {
+            let names: Vec<&str> = winners.iter().map(|w| w.username.as_str()).collect();
+            format!(
+                "{} draw on {} points",
+                names.join(" and "),
+                winners[0].score
+            )
+        }
+        Some(GameOutcome::CoopWin) => format!("The team won with {} points", team_score),
+        Some(GameOutcome::CoopLose) => format!("The team fell short on {} points", team_score),
+        _ => "Game over".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(user_id: i64, score: i32, best: Option<(&str, i32)>) -> PlayerFinalScore {
+        PlayerFinalScore {
+            user_id,
+            username: format!("player{}", user_id),
+            score,
+            forfeited: false,
+            best_word: best.map(|(word, _)| word.to_string()),
+            best_word_score: best.map(|(_, score)| score),
+        }
+    }
+
+    fn card(result: Option<GameResult>, scores: &[PlayerFinalScore]) -> ResultCard {
+        ResultCard::new(
+            "game".to_string(),
+            GameMode::Multiplayer,
+            None,
+            result.as_ref(),
+            scores,
+        )
+    }
+
+    #[test]
+    fn test_win_card() {
+        let scores = vec![
+            player(1, 120, Some(("QUIZ", 40))),
+            player(2, 90, Some(("JINX", 44))),
+            player(3, 90, None),
+        ];
+        let result = GameResult {
+            outcome: GameOutcome::Win,
+            winners: vec![1],
+            tie_break: None,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        };
+
+        let card = card(Some(result), &scores);
+        assert_eq!(card.title, "player1 wins with 120 points");
+        let ranks: Vec<u32> = card.standings.iter().map(|s| s.rank).collect();
+        assert_eq!(ranks, vec![1, 2, 2], "Tied scores share a rank");
+        assert!(card.standings[0].winner);
+        assert_eq!(card.best_word.as_ref().unwrap().word, "JINX");
+        assert_eq!(
+            card.description.as_deref(),
+            Some("Best word: JINX (44 points) by player2"),
+            "The best word can come from someone who didn't win"
+        );
+    }
+
+    #[test]
+    fn test_draw_and_coop_cards() {
+        let scores = vec![player(1, 50, None), player(2, 50, None)];
+        let draw = GameResult {
+            outcome: GameOutcome::Draw,
+            winners: vec![1, 2],
+            tie_break: None,
+            coverage_percent: None,
+            seat_winners: Vec::new(),
+        };
+        assert_eq!(
+            card(Some(draw), &scores).title,
+            "player1 and player2 draw on 50 points"
+        );
+
+        let coop = GameResult::coop(crate::models::CoopOutcome::Win);
+        let coop_card = card(Some(coop), &scores);
+        assert_eq!(coop_card.title, "The team won with 100 points");
+        assert!(coop_card.description.is_none(), "Nobody scored a word");
+
+        assert_eq!(card(None, &scores).title, "Game over");
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Shortest word any lobby accepts
+pub const MIN_WORD_LENGTH: u8 = 2;
+/// Longest minimum word length a host can ask for
+pub const MAX_MIN_WORD_LENGTH: u8 = 5;
+/// Most points a host can give for a long word
+pub const MAX_LONG_WORD_POINTS: i32 = 100;
+/// How long a player has to take back a word in games that allow undo
+pub const UNDO_WINDOW: Duration = Duration::from_secs(10);
+
+/// A flat bonus for every word of at least `min_length` letters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LongWordBonus {
+    pub min_length: u8,
+    pub points: i32,
+}
+
+/// House rules a host sets for their lobby's games
+///
+/// Fixed when a game starts and stored with it, so a host changing them
+/// mid-game only affects the next one. Omitted fields keep the standard rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    /// Shortest playable word
+    pub min_word_length: u8,
+    /// Whether names from the proper noun list are playable
+    pub allow_proper_nouns: bool,
+    /// A flat long-word bonus in place of the standard bonus that grows with length
+    pub long_word_bonus: Option<LongWordBonus>,
+    /// Whether long words add gems to the co-op pool
+    pub gems: bool,
+    /// Casual play: a player can take back their word within `UNDO_WINDOW`, as
+    /// long as nobody has moved since
+    pub undo: bool,
+    /// Bonus points for words the community rarely plays, going by word stats
+    pub obscurity_bonus: bool,
+    /// Whether players are told the most points still on the board, so they
+    /// know if catching up is possible
+    pub show_max_remaining: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            min_word_length: MIN_WORD_LENGTH,
+            allow_proper_nouns: false,
+            long_word_bonus: None,
+            gems: true,
+            undo: false,
+            obscurity_bonus: false,
+            show_max_remaining: false,
+        }
+    }
+}
+
+impl GameRules {
+    /// Check every rule is within the range hosts may choose from
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_WORD_LENGTH..=MAX_MIN_WORD_LENGTH).contains(&self.min_word_length) {
+            return Err(format!(
+                "min_word_length must be between {} and {}",
+                MIN_WORD_LENGTH, MAX_MIN_WORD_LENGTH
+            ));
+        }
+        if let Some(bonus) = self.long_word_bonus {
+            if bonus.min_length < MIN_WORD_LENGTH {
+                return Err(format!(
+                    "long_word_bonus.min_length must be at least {}",
+                    MIN_WORD_LENGTH
+                ));
+            }
+            if !(0..=MAX_LONG_WORD_POINTS).contains(&bonus.points) {
+                return Err(format!(
+                    "long_word_bonus.points must be between 0 and {}",
+                    MAX_LONG_WORD_POINTS
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a word of `length` letters is long enough to play
+    pub fn allows_length(&self, length: usize) -> bool {
+        length >= self.min_word_length as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let rules = GameRules::default();
+        assert!(rules.validate().is_ok());
+        assert!(rules.allows_length(2), "Two-letter words play by default");
+        assert!(rules.gems);
+        assert!(!rules.allow_proper_nouns);
+        assert!(!rules.undo, "Words are final unless the host turns on undo");
+        assert!(!rules.obscurity_bonus);
+    }
+
+    #[test]
+    fn test_partial_rules_keep_defaults() {
+        let rules: GameRules = serde_json::from_str(r#"{"min_word_length": 4}"#).unwrap();
+        assert!(!rules.allows_length(3));
+        assert!(rules.allows_length(4));
+        assert!(rules.gems, "Omitted rules keep the standard value");
+    }
+
+    #[test]
+    fn test_rule_ranges() {
+        let too_short = GameRules {
+            min_word_length: 1,
+            ..Default::default()
+        };
+        assert!(too_short.validate().is_err());
+
+        let too_long = GameRules {
+            min_word_length: MAX_MIN_WORD_LENGTH + 1,
+            ..Default::default()
+        };
+        assert!(too_long.validate().is_err());
+
+        let bonus = |min_length, points| GameRules {
+            long_word_bonus: Some(LongWordBonus { min_length, points }),
+            ..Default::default()
+        };
+        assert!(bonus(6, 10).validate().is_ok());
+        assert!(
+            bonus(6, -5).validate().is_err(),
+            "Bonuses can't take points"
+        );
+        assert!(bonus(6, MAX_LONG_WORD_POINTS + 1).validate().is_err());
+        assert!(bonus(1, 10).validate().is_err());
+    }
+}
@@ -1,30 +1,122 @@
-use crate::models::{Grid, Multiplier, Position};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        rules::GameRules,
+        validator::{cell_at, PathError},
+    },
+    models::{Grid, Multiplier, Position},
+};
+
+/// How a word's score was made up, so clients can show where the points came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Letter values with their multipliers, and the word multiplier
+    pub letters: i32,
+    /// The long-word bonus, standard or the house rule's
+    pub length_bonus: i32,
+    /// Points for a word the community rarely plays, under the obscurity rule
+    pub obscurity_bonus: i32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> i32 {
+        self.letters
+            .saturating_add(self.length_bonus)
+            .saturating_add(self.obscurity_bonus)
+    }
+}
+
+/// Bonus for a word played fewer than `RARE_WORD_PLAYS` times across all games
+pub const RARE_WORD_BONUS: i32 = 10;
+pub const RARE_WORD_PLAYS: i64 = 10;
+/// Bonus for a word played fewer than `UNCOMMON_WORD_PLAYS` times
+pub const UNCOMMON_WORD_BONUS: i32 = 5;
+pub const UNCOMMON_WORD_PLAYS: i64 = 100;
+/// Shortest word that can earn the obscurity bonus, so memorised lists of
+/// two- and three-letter words don't
+pub const OBSCURITY_MIN_LENGTH: usize = 4;
 
 pub struct Scorer;
 
 impl Scorer {
     /// Calculate the score for a word given its positions on the grid
-    pub fn calculate_score(grid: &Grid, positions: &[Position]) -> i32 {
-        let mut total_score = 0;
+    ///
+    /// Positions come straight from clients, so each is checked against the
+    /// grid's actual dimensions; one off the board is `PathError::OutOfBounds`.
+    pub fn calculate_score(
+        grid: &Grid,
+        positions: &[Position],
+        rules: &GameRules,
+    ) -> Result<i32, PathError> {
+        Self::score_breakdown(grid, positions, rules, None).map(|breakdown| breakdown.total())
+    }
+
+    /// Score a word part by part
+    ///
+    /// `plays` is how often the community has played the word, when known; it
+    /// only counts in games with the obscurity bonus rule.
+    pub fn score_breakdown(
+        grid: &Grid,
+        positions: &[Position],
+        rules: &GameRules,
+        plays: Option<i64>,
+    ) -> Result<ScoreBreakdown, PathError> {
+        let mut total_score: i32 = 0;
+        let mut word_multiplier: i32 = 1;
 
         for pos in positions {
-            let cell = &grid[pos.row][pos.col];
+            let cell = cell_at(grid, pos)?;
             let base_value = cell.value as i32;
 
             let letter_score = match &cell.multiplier {
                 Some(Multiplier::DoubleLetter) => base_value * 2,
                 Some(Multiplier::TripleLetter) => base_value * 3,
+                Some(Multiplier::DoubleWord) => {
+                    word_multiplier = word_multiplier.saturating_mul(2);
+                    base_value
+                }
                 None => base_value,
             };
 
-            total_score += letter_score;
+            total_score = total_score.saturating_add(letter_score);
         }
 
-        // Bonus for longer words
-        let length_bonus = Self::length_bonus(positions.len());
-        total_score += length_bonus;
+        // Bonus for longer words, which the word multiplier doesn't double
+        let length_bonus = match rules.long_word_bonus {
+            Some(bonus) if positions.len() >= bonus.min_length as usize => bonus.points,
+            Some(_) => 0,
+            None => Self::length_bonus(positions.len()),
+        };
+        let obscurity_bonus = match plays {
+            Some(plays) if rules.obscurity_bonus && positions.len() >= OBSCURITY_MIN_LENGTH => {
+                Self::obscurity_bonus(plays)
+            }
+            _ => 0,
+        };
+        Ok(ScoreBreakdown {
+            letters: total_score.saturating_mul(word_multiplier),
+            length_bonus,
+            obscurity_bonus,
+        })
+    }
 
-        total_score
+    /// Bonus points for a word played `plays` times across all games
+    fn obscurity_bonus(plays: i64) -> i32 {
+        match plays {
+            ..RARE_WORD_PLAYS => RARE_WORD_BONUS,
+            RARE_WORD_PLAYS..UNCOMMON_WORD_PLAYS => UNCOMMON_WORD_BONUS,
+            _ => 0,
+        }
+    }
+
+    /// Gems a long word adds to the shared co-op pool
+    pub fn gems_for_word(length: usize) -> u32 {
+        match length {
+            0..=5 => 0,
+            6 | 7 => 1,
+            8.. => 2,
+        }
     }
 
     /// Calculate bonus points based on word length
@@ -43,7 +135,7 @@ impl Scorer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::GridCell;
+    use crate::{game::rules::LongWordBonus, models::GridCell};
 
     #[test]
     fn test_length_bonus() {
@@ -53,6 +145,14 @@ mod tests {
         assert_eq!(Scorer::length_bonus(8), 50);
     }
 
+    #[test]
+    fn test_gems_for_word() {
+        assert_eq!(Scorer::gems_for_word(5), 0, "Short words earn no gems");
+        assert_eq!(Scorer::gems_for_word(6), 1);
+        assert_eq!(Scorer::gems_for_word(7), 1);
+        assert_eq!(Scorer::gems_for_word(9), 2);
+    }
+
     #[test]
     fn test_score_calculation() {
         let grid = vec![vec![
@@ -71,7 +171,131 @@ mod tests {
         let positions = vec![Position { row: 0, col: 0 }, Position { row: 0, col: 1 }];
 
         // H(4) + E(1*2) = 6, no length bonus for 2 letters
-        let score = Scorer::calculate_score(&grid, &positions);
-        assert_eq!(score, 6);
+        let score = Scorer::calculate_score(&grid, &positions, &GameRules::default());
+        assert_eq!(score, Ok(6));
+    }
+
+    #[test]
+    fn test_double_word_doubles_letters_not_bonus() {
+        let cell = |letter, value, multiplier| GridCell {
+            letter,
+            value,
+            multiplier,
+        };
+        let grid = vec![vec![
+            cell('C', 5, Some(Multiplier::DoubleWord)),
+            cell('A', 1, Some(Multiplier::DoubleLetter)),
+            cell('T', 2, None),
+            cell('S', 2, None),
+        ]];
+        let positions: Vec<Position> = (0..4).map(|col| Position { row: 0, col }).collect();
+
+        // (C5 + A1*2 + T2 + S2) * 2 = 22, plus the 4-letter bonus of 5
+        assert_eq!(
+            Scorer::calculate_score(&grid, &positions, &GameRules::default()),
+            Ok(27)
+        );
+    }
+
+    #[test]
+    fn test_off_grid_positions_are_errors() {
+        let grid = vec![vec![GridCell {
+            letter: 'A',
+            value: 1,
+            multiplier: None,
+        }]];
+        let rules = GameRules::default();
+        for (row, col) in [(0, 1), (1, 0), (usize::MAX, usize::MAX)] {
+            assert_eq!(
+                Scorer::calculate_score(
+                    &grid,
+                    &[Position { row: 0, col: 0 }, Position { row, col }],
+                    &rules
+                ),
+                Err(PathError::OutOfBounds { row, col })
+            );
+        }
+        assert!(
+            Scorer::calculate_score(&Vec::new(), &[Position { row: 0, col: 0 }], &rules).is_err()
+        );
+    }
+
+    #[test]
+    fn test_flat_long_word_bonus() {
+        let grid = vec![(0..6)
+            .map(|_| GridCell {
+                letter: 'A',
+                value: 1,
+                multiplier: None,
+            })
+            .collect()];
+        let word =
+            |len| -> Vec<Position> { (0..len).map(|col| Position { row: 0, col }).collect() };
+        let rules = GameRules {
+            long_word_bonus: Some(LongWordBonus {
+                min_length: 6,
+                points: 10,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(Scorer::calculate_score(&grid, &word(6), &rules), Ok(16));
+        assert_eq!(
+            Scorer::calculate_score(&grid, &word(5), &rules),
+            Ok(5),
+            "Shorter words get no bonus, not the standard one"
+        );
+        assert_eq!(
+            Scorer::calculate_score(&grid, &word(5), &GameRules::default()),
+            Ok(15)
+        );
+    }
+
+    #[test]
+    fn test_obscurity_bonus() {
+        let grid = vec![(0..5)
+            .map(|_| GridCell {
+                letter: 'A',
+                value: 1,
+                multiplier: None,
+            })
+            .collect()];
+        let word =
+            |len| -> Vec<Position> { (0..len).map(|col| Position { row: 0, col }).collect() };
+        let rules = GameRules {
+            obscurity_bonus: true,
+            ..Default::default()
+        };
+        let bonus = |len, rules: &GameRules, plays| {
+            Scorer::score_breakdown(&grid, &word(len), rules, plays)
+                .unwrap()
+                .obscurity_bonus
+        };
+
+        assert_eq!(bonus(4, &rules, Some(0)), RARE_WORD_BONUS);
+        assert_eq!(bonus(4, &rules, Some(RARE_WORD_PLAYS)), UNCOMMON_WORD_BONUS);
+        assert_eq!(bonus(4, &rules, Some(UNCOMMON_WORD_PLAYS)), 0);
+        assert_eq!(
+            bonus(4, &rules, None),
+            0,
+            "Unknown play counts earn nothing"
+        );
+        assert_eq!(bonus(3, &rules, Some(0)), 0, "Short words earn nothing");
+        assert_eq!(
+            bonus(4, &GameRules::default(), Some(0)),
+            0,
+            "The bonus is a house rule"
+        );
+
+        let breakdown = Scorer::score_breakdown(&grid, &word(5), &rules, Some(3)).unwrap();
+        assert_eq!(
+            breakdown,
+            ScoreBreakdown {
+                letters: 5,
+                length_bonus: 10,
+                obscurity_bonus: RARE_WORD_BONUS,
+            }
+        );
+        assert_eq!(breakdown.total(), 25);
     }
 }
@@ -0,0 +1,1007 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::ApiError,
+    game::{
+        commentary::COMMENTARY_BEST_MOVES,
+        engine::BoardView,
+        rules::UNDO_WINDOW,
+        solver::{self, BestWord},
+    },
+    models::{
+        GameEvent, GameMode, GameState, GameStatus, Grid, PendingMove, PlayerBoard, TurnAdvance,
+    },
+    websocket::{messages::ServerMessage, reminders::TurnReminders},
+    AppState, GAME_FLUSH_INTERVAL,
+};
+
+/// In-memory game session data
+///
+/// While a game is running, the cached `GameState` is the source of truth. It
+/// changes by committing events, which are kept in the session's log; mutations
+/// only set the dirty flag, and the state, moves and events are written back to
+/// Postgres on round boundaries, at game end, and periodically by
+/// `game_flush_task` (write-behind).
+pub struct GameSession {
+    pub game_id: Uuid,
+    /// The lobby this game is being played in
+    pub lobby_id: String,
+    /// Discord user IDs, index-aligned with `state.players`
+    pub players: Vec<i64>,
+    pub state: GameState,
+    /// Moves applied in memory that haven't been written to `game_moves` yet
+    pub pending_moves: Vec<PendingMove>,
+    /// Events committed since the session was created, oldest first
+    pub events: Vec<GameEvent>,
+    /// The game as it was before `events`, for replays
+    start: GameState,
+    /// Events in `game_events` from before the session was created, where `events`
+    /// continues the sequence
+    pub event_seq_base: i64,
+    /// How many of `events` have been written to `game_events`
+    events_flushed: usize,
+    /// Set when `state` has changes that haven't been flushed to the database
+    pub dirty: bool,
+    /// When the session was last written to the database
+    pub last_flushed: Instant,
+    /// Held for the whole of a flush, so one save of the game finishes before the
+    /// next takes its batch and an older snapshot never lands after a newer one
+    pub flush_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Each player's latest accepted word, by client `submission_id`, with the
+    /// WordScored it was answered with
+    submissions: HashMap<i64, (String, ServerMessage)>,
+    /// The last word played, while it can still be taken back
+    last_move: Option<UndoableMove>,
+    /// Reminders sent to the current player for this turn
+    pub turn_reminders: TurnReminders,
+    /// When each solo player's words over the last minute were accepted
+    recent_words: HashMap<i64, VecDeque<Instant>>,
+    /// The words left on the game's latest boards, worked out off the session
+    /// lock for the `show_max_remaining` rule and spectators' commentary
+    remaining_words: VecDeque<RemainingWords>,
+}
+
+/// The words left on one version of a board
+struct RemainingWords {
+    grid: Grid,
+    used_words: HashSet<String>,
+    /// Best first, from `solver::remaining_word_scores`
+    scores: Vec<i32>,
+    /// The few best words, for commentary
+    best: Vec<BestWord>,
+}
+
+/// A word that can be taken back, in games whose rules allow undo
+struct UndoableMove {
+    game_move: PendingMove,
+    played_at: Instant,
+    /// The game just before the word was played
+    before: GameState,
+    /// Length of the event log just before the word
+    events_before: usize,
+}
+
+/// A word that was taken back
+#[derive(Debug)]
+pub struct UndoneMove {
+    pub game_move: PendingMove,
+    /// Whether the move had already been written to the database, and so has to
+    /// be deleted there too
+    pub flushed: bool,
+    /// The first stored event taken back with it, if any were already written
+    pub flushed_events_from: Option<i64>,
+}
+
+/// What committing a command's events did
+#[derive(Debug, Default)]
+pub struct Committed {
+    /// The move recorded for a word, for the move history
+    pub game_move: Option<PendingMove>,
+    /// How play moved on, if the events ended a turn or the game
+    pub advance: Option<TurnAdvance>,
+}
+
+/// Everything a flush writes
+struct FlushBatch {
+    state: GameState,
+    player_ids: Vec<i64>,
+    moves: Vec<PendingMove>,
+    /// Sequence number of the first of `events`
+    first_event_seq: i64,
+    events: Vec<GameEvent>,
+}
+
+impl GameSession {
+    pub fn new(lobby_id: String, players: Vec<i64>, state: GameState) -> Self {
+        Self {
+            game_id: state.game_id,
+            lobby_id,
+            players,
+            start: state.clone(),
+            state,
+            pending_moves: Vec::new(),
+            events: Vec::new(),
+            event_seq_base: 0,
+            events_flushed: 0,
+            dirty: false,
+            last_flushed: Instant::now(),
+            flush_lock: Arc::default(),
+            submissions: HashMap::new(),
+            last_move: None,
+            turn_reminders: TurnReminders::default(),
+            recent_words: HashMap::new(),
+            remaining_words: VecDeque::new(),
+        }
+    }
+
+    /// Discord user ID of the player whose turn it is
+    pub fn current_player_id(&self) -> Option<i64> {
+        self.players.get(self.state.current_player_index).copied()
+    }
+
+    /// Index of a player in `state.players` by Discord user ID
+    pub fn player_index(&self, user_id: i64) -> Option<usize> {
+        self.players.iter().position(|id| *id == user_id)
+    }
+
+    /// Index of the player a user acts as: their own, or in hot-seat games whichever
+    /// of their local players is up
+    pub fn acting_index(&self, user_id: i64) -> Option<usize> {
+        if self.state.mode != GameMode::HotSeat {
+            return self.player_index(user_id);
+        }
+        let index = self.state.current_player_index;
+        (self.players.get(index) == Some(&user_id)).then_some(index)
+    }
+
+    /// A duel player's own board, by Discord user ID; None outside duels
+    pub fn duel_board(&self, user_id: i64) -> Option<&PlayerBoard> {
+        let player = self.state.players.get(self.player_index(user_id)?)?;
+        self.state.player_board(player.user_id)
+    }
+
+    /// Apply a command's events to the game and append them to the log, queueing
+    /// a move for the next flush when a word was played
+    pub fn commit(&mut self, events: Vec<GameEvent>) -> Committed {
+        let mut committed = Committed::default();
+        let mut word = None;
+        for event in events {
+            let round = self.state.current_round as i32;
+            if let Some(advance) = self.state.apply(&event) {
+                committed.advance = Some(advance);
+            }
+            if matches!(event, GameEvent::WordPlayed { .. }) {
+                word = Some((event.clone(), round, self.state.move_count as i32));
+            }
+            self.events.push(event);
+        }
+
+        // The move's board is the one after the whole command, refills included
+        if let Some((
+            GameEvent::WordPlayed {
+                player,
+                word,
+                positions,
+                score,
+                move_time_ms,
+            },
+            round_number,
+            move_number,
+        )) = word
+        {
+            let board = self.move_board(player);
+            let game_move = PendingMove {
+                user_id: self.players.get(player).copied().unwrap_or_default(),
+                round_number,
+                word: word.to_uppercase(),
+                score,
+                positions,
+                move_time_ms,
+                move_number,
+                board,
+            };
+            self.record_move(game_move.clone());
+            committed.game_move = Some(game_move);
+        }
+        self.mark_dirty();
+        committed
+    }
+
+    /// Commit the rest of a word's command once its move has been recorded, for
+    /// what has to be worked out off the session lock (the next puzzle rush
+    /// board). The move's board becomes the one after these events too.
+    pub fn finish_move(&mut self, events: Vec<GameEvent>, game_move: &mut PendingMove) {
+        self.commit(events);
+        let player = self.player_index(game_move.user_id).unwrap_or_default();
+        game_move.board = self.move_board(player);
+        if let Some(pending) = self
+            .pending_moves
+            .iter_mut()
+            .rfind(|pending| pending.move_number == game_move.move_number)
+        {
+            pending.board = game_move.board.clone();
+        }
+    }
+
+    /// The board a player's word was played on, as it is now
+    fn move_board(&self, player: usize) -> Grid {
+        self.state
+            .players
+            .get(player)
+            .and_then(|p| self.state.player_board(p.user_id))
+            .map_or_else(|| self.state.grid.clone(), |board| board.grid.clone())
+    }
+
+    /// The game rebuilt from its event log, which matches `state` unless the two
+    /// have drifted apart
+    pub fn replay(&self) -> GameState {
+        GameState::replay(&self.start, &self.events)
+    }
+
+    /// Flag the session as having unsaved changes
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Count a solo player's word against `max_per_minute`, turning it away if
+    /// they've already had that many accepted in the last minute
+    ///
+    /// Nobody else sees a solo board, so this is what stands in for an opponent
+    /// noticing words coming in faster than anyone could find them.
+    pub fn count_solo_word(
+        &mut self,
+        user_id: i64,
+        now: Instant,
+        max_per_minute: u32,
+    ) -> Result<(), ApiError> {
+        let times = self.recent_words.entry(user_id).or_default();
+        while times
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            times.pop_front();
+        }
+        if times.len() >= max_per_minute as usize {
+            return Err(ApiError::ImplausibleWordRate(max_per_minute));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+
+    /// Remember the WordScored a submission was answered with, replacing the
+    /// player's previous one
+    pub fn remember_submission(
+        &mut self,
+        user_id: i64,
+        submission_id: String,
+        reply: ServerMessage,
+    ) {
+        self.submissions.insert(user_id, (submission_id, reply));
+    }
+
+    /// The original WordScored if this is a retry of the player's latest accepted word
+    ///
+    /// The turn has usually moved on by the time a retry arrives, so the id is
+    /// kept until the player's next accepted word rather than just for the turn.
+    pub fn replay_submission(&self, user_id: i64, submission_id: &str) -> Option<ServerMessage> {
+        self.submissions
+            .get(&user_id)
+            .filter(|(id, _)| id == submission_id)
+            .map(|(_, reply)| reply.clone())
+    }
+
+    /// Let the player take back the word they just played, given the game as it
+    /// was before it; replaces any earlier word that could still be undone
+    pub fn offer_undo(&mut self, game_move: PendingMove, before: GameState, now: Instant) {
+        let events_before = self
+            .events
+            .iter()
+            .rposition(|event| matches!(event, GameEvent::WordPlayed { .. }))
+            .unwrap_or(self.events.len());
+        self.last_move = Some(UndoableMove {
+            game_move,
+            played_at: now,
+            before,
+            events_before,
+        });
+    }
+
+    /// Take back the player's last word, restoring the board, scores, turn and clock
+    /// from before it
+    ///
+    /// Only allowed within `UNDO_WINDOW`, and only while the turn the word ended is
+    /// the last one: once anyone else has played, passed or run out of time, the word
+    /// stands. Resignations and connection changes since the word are kept.
+    pub fn undo_last_move(&mut self, user_id: i64, now: Instant) -> Result<UndoneMove, ApiError> {
+        let undoable = self.last_move.as_ref().is_some_and(|last| {
+            last.game_move.user_id == user_id
+                && now.saturating_duration_since(last.played_at) <= UNDO_WINDOW
+                && self.state.status == GameStatus::InProgress
+                && self.state.move_count == last.game_move.move_number as u32
+                && self.state.turns_taken == last.before.turns_taken + 1
+        });
+        if !undoable {
+            return Err(ApiError::NothingToUndo);
+        }
+        let UndoableMove {
+            game_move,
+            mut before,
+            events_before,
+            ..
+        } = self.last_move.take().expect("checked above");
+
+        for (restored, current) in before.players.iter_mut().zip(&self.state.players) {
+            restored.is_connected = current.is_connected;
+            restored.forfeited = current.forfeited;
+        }
+        self.state = before;
+
+        // The word's events leave the log; resignations since it stay
+        let undone: Vec<GameEvent> = self.events.drain(events_before..).collect();
+        self.events.extend(
+            undone
+                .into_iter()
+                .filter(|event| matches!(event, GameEvent::PlayerResigned { .. })),
+        );
+        let flushed_events_from = (self.events_flushed > events_before)
+            .then_some(self.event_seq_base + events_before as i64);
+        self.events_flushed = self.events_flushed.min(events_before);
+
+        let flushed = match self.pending_moves.last() {
+            Some(pending) if pending.move_number == game_move.move_number => {
+                self.pending_moves.pop();
+                false
+            }
+            _ => true,
+        };
+        self.submissions.remove(&user_id);
+        self.mark_dirty();
+        Ok(UndoneMove {
+            game_move,
+            flushed,
+            flushed_events_from,
+        })
+    }
+
+    /// The scores of the words left on a board as it is now, best first, if
+    /// they've been worked out
+    pub fn remaining_scores(&self, board: &BoardView) -> Option<&[i32]> {
+        self.remaining_words(board)
+            .map(|remaining| remaining.scores.as_slice())
+    }
+
+    /// The best few words left on a board as it is now, if they've been worked
+    /// out
+    pub fn best_remaining_words(&self, board: &BoardView) -> Option<&[BestWord]> {
+        self.remaining_words(board)
+            .map(|remaining| remaining.best.as_slice())
+    }
+
+    fn remaining_words(&self, board: &BoardView) -> Option<&RemainingWords> {
+        self.remaining_words.iter().find(|remaining| {
+            remaining.grid == *board.grid && remaining.used_words == *board.used_words
+        })
+    }
+
+    /// Keep the words left on a board, every unused word at its best path as
+    /// `solver::best_words` lists them. Only the latest few boards are kept:
+    /// one for each player, and the one spectators see.
+    pub fn keep_remaining_words(
+        &mut self,
+        grid: Grid,
+        used_words: HashSet<String>,
+        mut words: Vec<BestWord>,
+    ) {
+        if self
+            .remaining_words(&BoardView {
+                grid: &grid,
+                used_words: &used_words,
+            })
+            .is_some()
+        {
+            return;
+        }
+        if self.remaining_words.len() > self.players.len() {
+            self.remaining_words.pop_front();
+        }
+        let scores = solver::remaining_word_scores(&words);
+        words.truncate(COMMENTARY_BEST_MOVES);
+        self.remaining_words.push_back(RemainingWords {
+            grid,
+            used_words,
+            scores,
+            best: words,
+        });
+    }
+
+    /// Queue a move for the next flush
+    pub fn record_move(&mut self, game_move: PendingMove) {
+        self.pending_moves.push(game_move);
+        self.mark_dirty();
+    }
+
+    /// Snapshot of the cached game for debugging desync reports
+    ///
+    /// The seeds that pick a duel's refills and a puzzle rush's boards are left
+    /// out, as anyone holding them could work out what's coming.
+    pub fn debug_dump(&self) -> serde_json::Value {
+        let mut dump = serde_json::json!({
+            "game_id": self.game_id,
+            "lobby_id": self.lobby_id,
+            "players": self.players,
+            "current_player_id": self.current_player_id(),
+            "dirty": self.dirty,
+            "secs_since_flush": self.last_flushed.elapsed().as_secs(),
+            "pending_moves": self.pending_moves,
+            "events": self.events,
+            "state": self.state,
+        });
+        for seeded in ["duel", "puzzle_rush"] {
+            if let Some(seeded) = dump["state"][seeded].as_object_mut() {
+                seeded.remove("seed");
+            }
+        }
+        dump
+    }
+
+    /// Take everything that needs to be written, clearing the dirty flag.
+    /// Returns None if the session has no unsaved changes.
+    fn take_flush_batch(&mut self) -> Option<FlushBatch> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        let first_event_seq = self.event_seq_base + self.events_flushed as i64;
+        let events = self.events[self.events_flushed..].to_vec();
+        self.events_flushed = self.events.len();
+        Some(FlushBatch {
+            state: self.state.clone(),
+            player_ids: self.players.clone(),
+            moves: std::mem::take(&mut self.pending_moves),
+            first_event_seq,
+            events,
+        })
+    }
+
+    /// Put a batch back after a failed flush so the next attempt retries it
+    fn restore_flush_batch(&mut self, batch: FlushBatch) {
+        let mut moves = batch.moves;
+        moves.append(&mut self.pending_moves);
+        self.pending_moves = moves;
+        let unflushed = (batch.first_event_seq - self.event_seq_base).max(0) as usize;
+        self.events_flushed = self.events_flushed.min(unflushed);
+        self.dirty = true;
+    }
+}
+
+/// Write a game session to the database if it has unsaved changes.
+///
+/// The session lock is only held while taking the batch, never across the DB write.
+/// Saves of the same game run one at a time: a flush waits for any in flight to
+/// finish (or fail and restore its batch) before taking its own.
+/// Returns false if the write failed (the batch is restored for a later retry).
+#[tracing::instrument(skip(state))]
+pub async fn flush_game_session(state: &AppState, game_id: Uuid) -> bool {
+    let Some(flush_lock) = state
+        .active_games
+        .get(&game_id)
+        .map(|session| session.flush_lock.clone())
+    else {
+        return true;
+    };
+    let _flushing = flush_lock.lock().await;
+
+    let batch = match state.active_games.get_mut(&game_id) {
+        Some(mut session) => session.take_flush_batch(),
+        None => return true,
+    };
+
+    let Some(batch) = batch else {
+        return true;
+    };
+
+    match db::queries::flush_game_state(
+        &state.db,
+        &batch.state,
+        &batch.player_ids,
+        &batch.moves,
+        batch.first_event_seq,
+        &batch.events,
+    )
+    .await
+    {
+        Ok(()) => {
+            if let Some(mut session) = state.active_games.get_mut(&game_id) {
+                session.last_flushed = Instant::now();
+            }
+            tracing::debug!(
+                "Flushed game {} ({} moves, {} events)",
+                game_id,
+                batch.moves.len(),
+                batch.events.len()
+            );
+            true
+        }
+        Err(e) => {
+            tracing::error!("Failed to flush game {}: {}", game_id, e);
+            if let Some(mut session) = state.active_games.get_mut(&game_id) {
+                session.restore_flush_batch(batch);
+            }
+            false
+        }
+    }
+}
+
+/// Background task that periodically flushes dirty game sessions.
+///
+/// Round boundaries and game end flush eagerly; this is the safety net that bounds
+/// how much play can be lost if the process dies mid-round.
+pub async fn game_flush_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(GAME_FLUSH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("game_flush", GAME_FLUSH_INTERVAL);
+
+        let dirty_games: Vec<Uuid> = state
+            .active_games
+            .iter()
+            .filter(|session| session.dirty)
+            .map(|session| session.game_id)
+            .collect();
+
+        for game_id in dirty_games {
+            flush_game_session(&state, game_id).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GamePlayer, GridCell, Position, PuzzleRushState};
+
+    fn create_test_session() -> GameSession {
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+        ];
+        let state = GameState::new(Uuid::new_v4(), vec![], players, 5);
+        GameSession::new("channel:123".to_string(), vec![111, 222], state)
+    }
+
+    fn create_test_move(user_id: i64, word: &str) -> PendingMove {
+        PendingMove {
+            user_id,
+            round_number: 1,
+            word: word.to_string(),
+            score: 5,
+            positions: vec![Position { row: 0, col: 0 }],
+            move_time_ms: Some(1_000),
+            move_number: 1,
+            board: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_solo_word_rate() {
+        let mut session = create_test_session();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(session.count_solo_word(111, now, 3).is_ok());
+        }
+        assert!(matches!(
+            session.count_solo_word(111, now + Duration::from_secs(59), 3),
+            Err(ApiError::ImplausibleWordRate(3))
+        ));
+        assert!(
+            session.count_solo_word(222, now, 3).is_ok(),
+            "Each player is counted on their own"
+        );
+        assert!(
+            session
+                .count_solo_word(111, now + Duration::from_secs(60), 3)
+                .is_ok(),
+            "Words older than a minute no longer count"
+        );
+    }
+
+    #[test]
+    fn test_retried_submission_replays_its_result() {
+        let mut session = create_test_session();
+        let reply = ServerMessage::GameCreated {
+            game_id: "g1".to_string(),
+        };
+        assert!(session.replay_submission(111, "s1").is_none());
+
+        session.remember_submission(111, "s1".to_string(), reply);
+        assert!(matches!(
+            session.replay_submission(111, "s1"),
+            Some(ServerMessage::GameCreated { .. })
+        ));
+        assert!(
+            session.replay_submission(222, "s1").is_none(),
+            "Submission ids are per player"
+        );
+
+        session.remember_submission(
+            111,
+            "s2".to_string(),
+            ServerMessage::GameCreated {
+                game_id: "g2".to_string(),
+            },
+        );
+        assert!(
+            session.replay_submission(111, "s1").is_none(),
+            "Only the latest accepted word is remembered"
+        );
+        assert!(session.replay_submission(111, "s2").is_some());
+    }
+
+    /// Play a word for the current player the way the handler does, offering undo
+    fn play_word(session: &mut GameSession, user_id: i64, word: &str, now: Instant) {
+        session.state.status = GameStatus::InProgress;
+        let before = session.state.clone();
+        let player = session.player_index(user_id).unwrap();
+        let committed = session.commit(vec![GameEvent::WordPlayed {
+            player,
+            word: word.to_string(),
+            positions: vec![Position { row: 0, col: 0 }],
+            score: 5,
+            move_time_ms: Some(1_000),
+        }]);
+        session.offer_undo(committed.game_move.unwrap(), before, now);
+        session.commit(vec![GameEvent::TurnEnded { clock_ms: None }]);
+    }
+
+    #[test]
+    fn test_commit_logs_events_and_queues_the_move() {
+        let mut session = create_test_session();
+        play_word(&mut session, 111, "cat", Instant::now());
+
+        assert_eq!(session.events.len(), 2);
+        assert_eq!(session.pending_moves.len(), 1);
+        let game_move = &session.pending_moves[0];
+        assert_eq!(game_move.word, "CAT");
+        assert_eq!(game_move.user_id, 111);
+        assert_eq!(game_move.move_number, 1);
+        assert!(session.dirty);
+
+        let replayed = session.replay();
+        assert_eq!(replayed.players[0].score, 5);
+        assert!(replayed.is_word_used("cat"));
+        assert_eq!(
+            replayed.current_player_index,
+            session.state.current_player_index
+        );
+    }
+
+    #[test]
+    fn test_undo_takes_the_word_out_of_the_log() {
+        let mut session = create_test_session();
+        let now = Instant::now();
+        session.commit(vec![GameEvent::TurnPassed {
+            player: 0,
+            penalty: 0,
+        }]);
+        session.commit(vec![GameEvent::TurnEnded { clock_ms: None }]);
+        session.commit(vec![GameEvent::TurnEnded { clock_ms: None }]);
+        play_word(&mut session, 111, "cat", now);
+        session.take_flush_batch();
+        session.commit(vec![GameEvent::PlayerResigned { player: 1 }]);
+
+        let undone = session.undo_last_move(111, now).unwrap();
+        assert_eq!(
+            undone.flushed_events_from,
+            Some(3),
+            "The word's stored events are deleted"
+        );
+        assert_eq!(
+            session.events.last(),
+            Some(&GameEvent::PlayerResigned { player: 1 }),
+            "Resignations since the word stay"
+        );
+        assert_eq!(session.events.len(), 4);
+        assert_eq!(session.replay().players[0].score, 0);
+
+        let batch = session.take_flush_batch().unwrap();
+        assert_eq!(
+            batch.first_event_seq, 3,
+            "Stored events are rewritten from there"
+        );
+        assert_eq!(batch.events.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_restores_the_game() {
+        let mut session = create_test_session();
+        let now = Instant::now();
+        play_word(&mut session, 111, "cat", now);
+        session.state.players[0].is_connected = false;
+        assert!(
+            matches!(
+                session.undo_last_move(222, now),
+                Err(ApiError::NothingToUndo)
+            ),
+            "Only the player who played the word can take it back"
+        );
+
+        let undone = session.undo_last_move(111, now).unwrap();
+        assert_eq!(undone.game_move.word, "CAT");
+        assert!(!undone.flushed, "The move never reached the database");
+        assert!(session.pending_moves.is_empty());
+        assert_eq!(
+            session.current_player_id(),
+            Some(111),
+            "The turn comes back"
+        );
+        assert_eq!(session.state.players[0].score, 0);
+        assert!(!session.state.is_word_used("cat"));
+        assert_eq!(session.state.move_count, 0);
+        assert!(
+            !session.state.players[0].is_connected,
+            "Connection changes since the word are kept"
+        );
+        assert!(
+            session.undo_last_move(111, now).is_err(),
+            "A word can only be taken back once"
+        );
+    }
+
+    #[test]
+    fn test_undo_after_flush_or_too_late() {
+        let mut session = create_test_session();
+        let now = Instant::now();
+        play_word(&mut session, 111, "cat", now);
+        session.take_flush_batch();
+        assert!(session.undo_last_move(111, now + UNDO_WINDOW * 2).is_err());
+
+        let mut session = create_test_session();
+        play_word(&mut session, 111, "cat", now);
+        session.take_flush_batch();
+        let undone = session.undo_last_move(111, now + UNDO_WINDOW).unwrap();
+        assert!(
+            undone.flushed,
+            "Flushed moves are deleted from the database"
+        );
+        assert!(session.dirty);
+    }
+
+    #[test]
+    fn test_undo_after_next_turn_is_refused() {
+        let mut session = create_test_session();
+        let now = Instant::now();
+        play_word(&mut session, 111, "cat", now);
+        // Player 2 passes
+        session.state.advance_turn();
+        assert!(
+            matches!(
+                session.undo_last_move(111, now),
+                Err(ApiError::NothingToUndo)
+            ),
+            "The word stands once someone else has moved"
+        );
+    }
+
+    #[test]
+    fn test_new_session_is_clean() {
+        let session = create_test_session();
+        assert!(!session.dirty, "A new session should not be dirty");
+        assert_eq!(session.game_id, session.state.game_id);
+    }
+
+    #[test]
+    fn test_current_player_id_follows_turn() {
+        let mut session = create_test_session();
+        assert_eq!(session.current_player_id(), Some(111));
+
+        session.state.advance_turn();
+        assert_eq!(session.current_player_id(), Some(222));
+    }
+
+    #[test]
+    fn test_player_index_lookup() {
+        let session = create_test_session();
+        assert_eq!(session.player_index(222), Some(1));
+        assert_eq!(session.player_index(999), None);
+    }
+
+    #[test]
+    fn test_hot_seat_owner_acts_as_the_current_seat() {
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Ann".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Bo".to_string(), None, 1),
+        ];
+        let state = GameState::new_hot_seat(Uuid::new_v4(), vec![], players, 5);
+        let mut session = GameSession::new("channel:123".to_string(), vec![111, 111], state);
+        assert_eq!(session.acting_index(111), Some(0));
+
+        session.state.advance_turn();
+        assert_eq!(session.acting_index(111), Some(1));
+        assert_eq!(session.acting_index(222), None);
+        assert_eq!(
+            create_test_session().acting_index(222),
+            Some(1),
+            "Everyone else acts as themselves"
+        );
+    }
+
+    #[test]
+    fn test_record_move_marks_dirty() {
+        let mut session = create_test_session();
+        session.record_move(create_test_move(111, "CAT"));
+
+        assert!(
+            session.dirty,
+            "Recording a move should mark the session dirty"
+        );
+        assert_eq!(session.pending_moves.len(), 1);
+    }
+
+    #[test]
+    fn test_take_flush_batch_clears_pending_state() {
+        let mut session = create_test_session();
+        assert!(
+            session.take_flush_batch().is_none(),
+            "Clean session should have nothing to flush"
+        );
+
+        session.record_move(create_test_move(111, "CAT"));
+        let batch = session.take_flush_batch().unwrap();
+
+        assert_eq!(batch.player_ids, vec![111, 222]);
+        assert_eq!(batch.moves.len(), 1);
+        assert!(!session.dirty, "Dirty flag should be cleared once taken");
+        assert!(session.pending_moves.is_empty());
+    }
+
+    #[test]
+    fn test_restore_flush_batch_keeps_move_order() {
+        let mut session = create_test_session();
+        session.record_move(create_test_move(111, "CAT"));
+        session.commit(vec![GameEvent::TurnEnded { clock_ms: None }]);
+        let batch = session.take_flush_batch().unwrap();
+
+        // A move played while the failed flush was in flight
+        session.record_move(create_test_move(222, "DOG"));
+        session.restore_flush_batch(batch);
+        assert_eq!(
+            session.events_flushed, 0,
+            "Unwritten events are retried too"
+        );
+
+        assert!(session.dirty);
+        let words: Vec<&str> = session
+            .pending_moves
+            .iter()
+            .map(|m| m.word.as_str())
+            .collect();
+        assert_eq!(words, vec!["CAT", "DOG"]);
+    }
+
+    #[test]
+    fn test_finished_move_gets_the_board_after_it() {
+        let mut session = create_test_session();
+        let mut game_move = session
+            .commit(vec![GameEvent::WordPlayed {
+                player: 0,
+                word: "cat".to_string(),
+                positions: vec![Position { row: 0, col: 0 }],
+                score: 5,
+                move_time_ms: None,
+            }])
+            .game_move
+            .unwrap();
+        assert!(game_move.board.is_empty());
+
+        let dealt = vec![vec![GridCell {
+            letter: 'Q',
+            value: 10,
+            multiplier: None,
+        }]];
+        session.finish_move(
+            vec![GameEvent::BoardReset {
+                grid: dealt.clone(),
+                letter_bag: None,
+            }],
+            &mut game_move,
+        );
+        assert_eq!(game_move.board, dealt);
+        assert_eq!(
+            session.pending_moves.last().unwrap().board,
+            dealt,
+            "The flushed move carries the new board"
+        );
+    }
+
+    #[test]
+    fn test_remaining_words_follow_the_board() {
+        let mut session = create_test_session();
+        let grid = session.state.grid.clone();
+        let mut used = HashSet::new();
+        let words: Vec<BestWord> = [("cat", 12), ("at", 7), ("ta", 7), ("a", 1)]
+            .map(|(word, score)| BestWord {
+                word: word.to_string(),
+                positions: Vec::new(),
+                score,
+            })
+            .into();
+        session.keep_remaining_words(grid.clone(), used.clone(), words);
+        let board = BoardView {
+            grid: &grid,
+            used_words: &used,
+        };
+        assert_eq!(session.remaining_scores(&board), Some(&[12, 7, 7, 1][..]));
+        let best: Vec<&str> = session
+            .best_remaining_words(&board)
+            .unwrap()
+            .iter()
+            .map(|best| best.word.as_str())
+            .collect();
+        assert_eq!(
+            best,
+            ["cat", "at", "ta"],
+            "Only the best few are kept in full"
+        );
+
+        used.insert("cat".to_string());
+        let played = BoardView {
+            grid: &grid,
+            used_words: &used,
+        };
+        assert!(
+            session.remaining_scores(&played).is_none(),
+            "A played word changes what's left"
+        );
+
+        for word in ["at", "ta", "a"] {
+            let used = HashSet::from([word.to_string()]);
+            session.keep_remaining_words(grid.clone(), used, Vec::new());
+        }
+        assert_eq!(
+            session.remaining_words.len(),
+            3,
+            "One board per player and the spectators' are kept"
+        );
+    }
+
+    #[test]
+    fn test_debug_dump_includes_unflushed_state() {
+        let mut session = create_test_session();
+        session.record_move(create_test_move(111, "CAT"));
+
+        let dump = session.debug_dump();
+        assert_eq!(dump["players"], serde_json::json!([111, 222]));
+        assert_eq!(dump["current_player_id"], 111);
+        assert_eq!(dump["dirty"], true);
+        assert_eq!(dump["pending_moves"][0]["word"], "CAT");
+        assert!(
+            dump["state"]["grid"].is_array(),
+            "Full game state should be embedded in the dump"
+        );
+    }
+
+    #[test]
+    fn test_debug_dump_leaves_out_the_rush_seed() {
+        let players = vec![GamePlayer::new(
+            Uuid::new_v4(),
+            "Player1".to_string(),
+            None,
+            0,
+        )];
+        let rush = PuzzleRushState::new(42, 15, 60, 0, chrono::Utc::now());
+        let state = GameState::new_puzzle_rush(Uuid::new_v4(), vec![], players, rush);
+        let session = GameSession::new("channel:123".to_string(), vec![111], state);
+
+        let dump = session.debug_dump();
+        assert!(dump["state"]["puzzle_rush"].is_object());
+        assert!(dump["state"]["puzzle_rush"].get("seed").is_none());
+    }
+}
@@ -0,0 +1,622 @@
+use std::{collections::HashSet, fmt, num::NonZeroUsize, str::FromStr, thread};
+
+use anyhow::{Context, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::{
+    dictionary::{Dictionary, DictionaryBackend},
+    game::{
+        grid::{GridGenerator, MultiplierLayout, GRID_SIZE},
+        rules::{GameRules, LongWordBonus},
+        solver::{self, WordList},
+    },
+};
+
+const DEFAULT_GAMES: usize = 1000;
+const DEFAULT_ROUNDS: u32 = 5;
+/// Smallest and largest boards a simulation can deal
+const MIN_BOARD_SIZE: usize = 3;
+const MAX_BOARD_SIZE: usize = 8;
+/// Buckets in the printed word score histogram
+const HISTOGRAM_BUCKETS: i32 = 10;
+
+const USAGE: &str = "Usage: spell-cast-backend simulate [--dictionary <path>] [--games <n>] \
+                     [--rounds <n>] [--bots <level>,...] [--layout <classic|chaos|none>,...] \
+                     [--board-size <n>,...] [--min-word-length <n>] \
+                     [--long-word-bonus <length>:<points>] [--seed <n>] [--json]";
+
+/// How a simulated player picks its word, named like `games.bot_difficulty`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotDifficulty {
+    /// Any of the 20 best words left
+    Easy,
+    /// Any of the 5 best words left
+    Medium,
+    /// Always the best word left
+    Hard,
+}
+
+impl BotDifficulty {
+    /// How many of the best remaining words the bot chooses between
+    fn choices(self) -> usize {
+        match self {
+            Self::Easy => 20,
+            Self::Medium => 5,
+            Self::Hard => 1,
+        }
+    }
+}
+
+impl FromStr for BotDifficulty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            other => anyhow::bail!("Unknown bot difficulty {} (easy, medium or hard)", other),
+        }
+    }
+}
+
+/// One combination of settings to play every simulated game under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RuleSet {
+    pub layout: MultiplierLayout,
+    pub board_size: usize,
+    pub rules: GameRules,
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} layout, {}x{} board, words of {}+ letters",
+            self.layout, self.board_size, self.board_size, self.rules.min_word_length
+        )?;
+        match self.rules.long_word_bonus {
+            Some(bonus) => write!(f, ", +{} for {}+ letters", bonus.points, bonus.min_length),
+            None => write!(f, ", no long word bonus"),
+        }
+    }
+}
+
+/// Parsed `simulate` arguments
+#[derive(Debug, PartialEq, Eq)]
+struct SimulateArgs {
+    dictionary: Option<String>,
+    games: usize,
+    rounds: u32,
+    bots: Vec<BotDifficulty>,
+    rule_sets: Vec<RuleSet>,
+    seed: Option<u64>,
+    json: bool,
+}
+
+/// A comma-separated list, each item parsed with `parse`
+fn list<T>(value: &str, parse: impl Fn(&str) -> Result<T>) -> Result<Vec<T>> {
+    value.split(',').map(|item| parse(item.trim())).collect()
+}
+
+fn parse_layout(value: &str) -> Result<MultiplierLayout> {
+    match value {
+        "classic" => Ok(MultiplierLayout::Classic),
+        "chaos" => Ok(MultiplierLayout::Chaos),
+        "none" => Ok(MultiplierLayout::None),
+        other => anyhow::bail!("Unknown layout {} (classic, chaos or none)", other),
+    }
+}
+
+fn parse_board_size(value: &str) -> Result<usize> {
+    let size: usize = value.parse().context("--board-size must be a number")?;
+    anyhow::ensure!(
+        (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size),
+        "--board-size must be between {} and {}",
+        MIN_BOARD_SIZE,
+        MAX_BOARD_SIZE
+    );
+    Ok(size)
+}
+
+fn parse_long_word_bonus(value: &str) -> Result<Option<LongWordBonus>> {
+    if value == "none" {
+        return Ok(None);
+    }
+    let (length, points) = value
+        .split_once(':')
+        .context("--long-word-bonus must be <length>:<points>, or none")?;
+    Ok(Some(LongWordBonus {
+        min_length: length
+            .parse()
+            .context("--long-word-bonus length must be a number")?,
+        points: points
+            .parse()
+            .context("--long-word-bonus points must be a number")?,
+    }))
+}
+
+fn parse_args(args: &[String]) -> Result<SimulateArgs> {
+    let mut dictionary = None;
+    let mut games = DEFAULT_GAMES;
+    let mut rounds = DEFAULT_ROUNDS;
+    let mut bots = vec![BotDifficulty::Hard; 2];
+    let mut layouts = vec![MultiplierLayout::default()];
+    let mut board_sizes = vec![GRID_SIZE];
+    let mut rules = GameRules::default();
+    let mut seed = None;
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .with_context(|| format!("{} needs a value\n{}", flag, USAGE))
+        };
+        match arg.as_str() {
+            "--dictionary" => dictionary = Some(value(arg)?.clone()),
+            "--games" => games = value(arg)?.parse().context("--games must be a number")?,
+            "--rounds" => rounds = value(arg)?.parse().context("--rounds must be a number")?,
+            "--bots" => bots = list(value(arg)?, str::parse)?,
+            "--layout" => layouts = list(value(arg)?, parse_layout)?,
+            "--board-size" => board_sizes = list(value(arg)?, parse_board_size)?,
+            "--min-word-length" => {
+                rules.min_word_length = value(arg)?
+                    .parse()
+                    .context("--min-word-length must be a number")?
+            }
+            "--long-word-bonus" => rules.long_word_bonus = parse_long_word_bonus(value(arg)?)?,
+            "--seed" => seed = Some(value(arg)?.parse().context("--seed must be a number")?),
+            "--json" => json = true,
+            other => anyhow::bail!("Unknown option {}\n{}", other, USAGE),
+        }
+    }
+
+    anyhow::ensure!(games > 0, "--games must be at least 1");
+    anyhow::ensure!(rounds > 0, "--rounds must be at least 1");
+    anyhow::ensure!(!bots.is_empty(), "--bots needs at least one bot");
+    rules.validate().map_err(anyhow::Error::msg)?;
+
+    let rule_sets = layouts
+        .iter()
+        .flat_map(|&layout| {
+            board_sizes.iter().map(move |&board_size| RuleSet {
+                layout,
+                board_size,
+                rules,
+            })
+        })
+        .collect();
+    Ok(SimulateArgs {
+        dictionary,
+        games,
+        rounds,
+        bots,
+        rule_sets,
+        seed,
+        json,
+    })
+}
+
+/// What happened in one simulated game
+#[derive(Debug, Default)]
+struct GameOutcome {
+    /// Final score per seat
+    totals: Vec<i32>,
+    /// Score and length of every word played
+    words: Vec<(i32, usize)>,
+    /// Turns a bot had no word left to play
+    passes: usize,
+}
+
+/// Play one game: every bot takes a word per round from the same board
+///
+/// Like a weighted-letters lobby, the board is dealt once and never refills,
+/// and a word can only be played once per game. The board only needs solving
+/// once; each turn picks from what's left of that list.
+fn play_game(
+    words: &impl WordList,
+    rule_set: &RuleSet,
+    bots: &[BotDifficulty],
+    rounds: u32,
+    rng: &mut impl Rng,
+) -> GameOutcome {
+    let grid = GridGenerator::generate_sized(rule_set.layout, rule_set.board_size, rng);
+    let mut candidates =
+        solver::best_words(&grid, words, &HashSet::new(), &rule_set.rules, usize::MAX);
+
+    let mut outcome = GameOutcome {
+        totals: vec![0; bots.len()],
+        ..Default::default()
+    };
+    for _ in 0..rounds {
+        for (seat, bot) in bots.iter().enumerate() {
+            if candidates.is_empty() {
+                outcome.passes += 1;
+                continue;
+            }
+            let pick = rng.random_range(0..bot.choices().min(candidates.len()));
+            let word = candidates.remove(pick);
+            outcome.totals[seat] += word.score;
+            outcome.words.push((word.score, word.word.chars().count()));
+        }
+    }
+    outcome
+}
+
+/// Play `games` games under one rule set, spread across the available cores
+///
+/// Game `n` is seeded with `seed + n`, so a run is repeatable whatever the
+/// core count, and every rule set deals its boards from the same letters.
+fn simulate<W: WordList + Sync>(
+    words: &W,
+    rule_set: &RuleSet,
+    bots: &[BotDifficulty],
+    rounds: u32,
+    games: usize,
+    seed: u64,
+) -> Vec<GameOutcome> {
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(games);
+    let per_thread = games.div_ceil(threads);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                let first = thread * per_thread;
+                let last = (first + per_thread).min(games);
+                scope.spawn(move || {
+                    (first..last)
+                        .map(|game| {
+                            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(game as u64));
+                            play_game(words, rule_set, bots, rounds, &mut rng)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("simulation thread panicked"))
+            .collect()
+    })
+}
+
+/// Summary statistics of a set of scores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub min: i32,
+    pub p10: i32,
+    pub p50: i32,
+    pub p90: i32,
+    pub max: i32,
+}
+
+impl Distribution {
+    fn of(values: &[i32]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        // Nearest-rank percentile
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Self {
+            count: sorted.len(),
+            mean: sorted.iter().map(|&v| v as f64).sum::<f64>() / sorted.len() as f64,
+            min: sorted[0],
+            p10: percentile(10),
+            p50: percentile(50),
+            p90: percentile(90),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean {:.1}, min {}, p10 {}, median {}, p90 {}, max {}",
+            self.mean, self.min, self.p10, self.p50, self.p90, self.max
+        )
+    }
+}
+
+/// Results of every game played under one rule set
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub rule_set: RuleSet,
+    pub games: usize,
+    /// Every seat's final score in every game
+    pub final_scores: Distribution,
+    pub word_scores: Distribution,
+    /// Winner's lead over the runner-up, for games with two or more bots
+    pub winning_margins: Distribution,
+    /// Share of games each seat won outright
+    pub win_rate_by_seat: Vec<f64>,
+    /// Share of games with a tie for first
+    pub tie_rate: f64,
+    /// Share of words played by length; index 0 is the shortest playable
+    pub word_lengths: Vec<f64>,
+    /// Share of words in each score bucket, from 0 up
+    pub word_score_histogram: Vec<f64>,
+    /// Points covered by each histogram bucket
+    pub word_score_bucket: i32,
+    pub passes: usize,
+}
+
+impl Summary {
+    fn of(rule_set: RuleSet, seats: usize, outcomes: &[GameOutcome]) -> Self {
+        let finals: Vec<i32> = outcomes.iter().flat_map(|o| o.totals.clone()).collect();
+        let word_scores: Vec<i32> = outcomes
+            .iter()
+            .flat_map(|o| o.words.iter().map(|&(score, _)| score))
+            .collect();
+        let games = outcomes.len().max(1) as f64;
+        let words = word_scores.len().max(1) as f64;
+
+        let mut wins = vec![0usize; seats];
+        let mut ties = 0;
+        let mut margins = Vec::new();
+        for outcome in outcomes {
+            let mut ranked = outcome.totals.clone();
+            ranked.sort_unstable_by(|a, b| b.cmp(a));
+            if let [first, second, ..] = ranked[..] {
+                margins.push(first - second);
+                if first == second {
+                    ties += 1;
+                    continue;
+                }
+            }
+            if let Some(seat) = outcome.totals.iter().position(|&t| t == ranked[0]) {
+                wins[seat] += 1;
+            }
+        }
+
+        let min_length = rule_set.rules.min_word_length as usize;
+        let mut lengths = Vec::new();
+        for (_, length) in outcomes.iter().flat_map(|o| &o.words) {
+            let index = length.saturating_sub(min_length);
+            if lengths.len() <= index {
+                lengths.resize(index + 1, 0usize);
+            }
+            lengths[index] += 1;
+        }
+
+        let word_dist = Distribution::of(&word_scores);
+        let bucket = (word_dist.max / HISTOGRAM_BUCKETS + 1).max(1);
+        let mut histogram = vec![0usize; (word_dist.max / bucket + 1) as usize];
+        for score in &word_scores {
+            histogram[((*score).max(0) / bucket) as usize] += 1;
+        }
+
+        Self {
+            rule_set,
+            games: outcomes.len(),
+            final_scores: Distribution::of(&finals),
+            word_scores: word_dist,
+            winning_margins: Distribution::of(&margins),
+            win_rate_by_seat: wins.iter().map(|&w| w as f64 / games).collect(),
+            tie_rate: ties as f64 / games,
+            word_lengths: lengths.iter().map(|&n| n as f64 / words).collect(),
+            word_score_histogram: histogram.iter().map(|&n| n as f64 / words).collect(),
+            word_score_bucket: bucket,
+            passes: outcomes.iter().map(|o| o.passes).sum(),
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.rule_set)?;
+        writeln!(f, "  Games:           {}", self.games)?;
+        writeln!(f, "  Final scores:    {}", self.final_scores)?;
+        writeln!(f, "  Word scores:     {}", self.word_scores)?;
+        if self.win_rate_by_seat.len() > 1 {
+            writeln!(f, "  Winning margins: {}", self.winning_margins)?;
+            let seats: Vec<String> = self
+                .win_rate_by_seat
+                .iter()
+                .enumerate()
+                .map(|(seat, rate)| format!("seat {} {:.1}%", seat + 1, rate * 100.0))
+                .collect();
+            writeln!(
+                f,
+                "  Wins:            {}, tied {:.1}%",
+                seats.join(", "),
+                self.tie_rate * 100.0
+            )?;
+        }
+        if self.passes > 0 {
+            writeln!(
+                f,
+                "  Passes:          {} (board ran out of words)",
+                self.passes
+            )?;
+        }
+        let min_length = self.rule_set.rules.min_word_length as usize;
+        let lengths: Vec<String> = self
+            .word_lengths
+            .iter()
+            .enumerate()
+            .filter(|(_, share)| **share > 0.0)
+            .map(|(i, share)| format!("{}: {:.1}%", min_length + i, share * 100.0))
+            .collect();
+        writeln!(f, "  Word lengths:    {}", lengths.join(", "))?;
+        writeln!(f, "  Word score histogram:")?;
+        let bucket = self.word_score_bucket;
+        for (i, share) in self.word_score_histogram.iter().enumerate() {
+            let low = i as i32 * bucket;
+            writeln!(
+                f,
+                "    {:>4}-{:<4} {:>5.1}% {}",
+                low,
+                low + bucket - 1,
+                share * 100.0,
+                "#".repeat((share * 50.0).round() as usize)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `spell-cast-backend simulate`: play bot-vs-bot games under each rule set
+/// and print the score distributions, for tuning the scoring constants
+pub async fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    let path = args.dictionary.clone().unwrap_or_else(|| {
+        std::env::var("DICTIONARY_PATH").unwrap_or_else(|_| "./dictionary.txt".to_string())
+    });
+    // Solving leans on prefix search, which only the FST backend does quickly
+    let dictionary = Dictionary::load(&path, None, DictionaryBackend::Fst)
+        .await
+        .with_context(|| format!("Failed to load dictionary {}", path))?;
+    anyhow::ensure!(!dictionary.is_empty(), "{} has no words", path);
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let summaries: Vec<Summary> = args
+        .rule_sets
+        .iter()
+        .map(|rule_set| {
+            let outcomes = simulate(
+                &dictionary,
+                rule_set,
+                &args.bots,
+                args.rounds,
+                args.games,
+                seed,
+            );
+            Summary::of(*rule_set, args.bots.len(), &outcomes)
+        })
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&summaries)?);
+        return Ok(());
+    }
+    println!(
+        "{} games per rule set, {} rounds, bots {:?}, seed {}\n",
+        args.games, args.rounds, args.bots, seed
+    );
+    for summary in &summaries {
+        println!("{}", summary);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args_defaults() {
+        let parsed = parse_args(&[]).unwrap();
+        assert_eq!(parsed.games, DEFAULT_GAMES);
+        assert_eq!(parsed.bots, vec![BotDifficulty::Hard; 2]);
+        assert_eq!(parsed.rule_sets.len(), 1);
+        assert_eq!(parsed.rule_sets[0].board_size, GRID_SIZE);
+        assert_eq!(parsed.rule_sets[0].rules, GameRules::default());
+    }
+
+    #[test]
+    fn test_parse_args_crosses_layouts_and_sizes() {
+        let parsed = parse_args(&args(&[
+            "--layout",
+            "classic,none",
+            "--board-size",
+            "4,6",
+            "--long-word-bonus",
+            "6:10",
+            "--bots",
+            "easy,medium,hard",
+            "--seed",
+            "7",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.rule_sets.len(), 4, "Every layout at every size");
+        assert_eq!(parsed.rule_sets[3].layout, MultiplierLayout::None);
+        assert_eq!(parsed.rule_sets[3].board_size, 6);
+        assert!(parsed.rule_sets.iter().all(|set| set.rules.long_word_bonus
+            == Some(LongWordBonus {
+                min_length: 6,
+                points: 10
+            })));
+        assert_eq!(parsed.bots.len(), 3);
+        assert_eq!(parsed.seed, Some(7));
+    }
+
+    #[test]
+    fn test_parse_args_rejects_bad_values() {
+        for bad in [
+            &["--games", "0"][..],
+            &["--board-size", "2"],
+            &["--board-size", "9"],
+            &["--layout", "diagonal"],
+            &["--bots", "godlike"],
+            &["--long-word-bonus", "6"],
+            &["--long-word-bonus", "6:1000"],
+            &["--min-word-length", "1"],
+            &["--rounds"],
+            &["--turbo"],
+        ] {
+            assert!(parse_args(&args(bad)).is_err(), "{:?} should fail", bad);
+        }
+    }
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let dist = Distribution::of(&(1..=10).collect::<Vec<_>>());
+        assert_eq!(dist.count, 10);
+        assert_eq!(dist.mean, 5.5);
+        assert_eq!(
+            (dist.min, dist.p10, dist.p50, dist.p90, dist.max),
+            (1, 1, 5, 9, 10)
+        );
+        assert_eq!(Distribution::of(&[]), Distribution::default());
+    }
+
+    #[test]
+    fn test_simulation_is_repeatable() {
+        let dictionary = Dictionary::from_lists(
+            "AT\nTA\nTE\nET\nEAT\nTEA\nATE\nTEN\nNET\nANT\nTAN\nRAT\nTAR\nART\nSEAT\nEAST",
+            "",
+        );
+        let rule_set = RuleSet {
+            layout: MultiplierLayout::Classic,
+            board_size: 4,
+            rules: GameRules::default(),
+        };
+        let bots = [BotDifficulty::Hard, BotDifficulty::Easy];
+
+        let run = || simulate(&dictionary, &rule_set, &bots, 3, 20, 42);
+        let first = run();
+        assert_eq!(first.len(), 20);
+        let totals = |outcomes: &[GameOutcome]| {
+            outcomes
+                .iter()
+                .map(|o| o.totals.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(totals(&first), totals(&run()), "Same seed, same games");
+
+        let summary = Summary::of(rule_set, bots.len(), &first);
+        assert_eq!(summary.games, 20);
+        assert_eq!(
+            summary.word_scores.count + summary.passes,
+            20 * 3 * 2,
+            "Every turn either plays a word or passes"
+        );
+        let shares = summary.win_rate_by_seat.iter().sum::<f64>() + summary.tie_rate;
+        assert!((shares - 1.0).abs() < 1e-9);
+    }
+}
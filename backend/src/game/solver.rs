@@ -0,0 +1,441 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dictionary::{
+        packs::{DictionaryPacks, PackMode, PackSelection},
+        Dictionary, DictionaryStrictness,
+    },
+    game::{rules::GameRules, scorer::Scorer},
+    models::{Grid, Position},
+};
+
+/// Shortest word the dictionary holds
+const MIN_WORD_LEN: usize = 2;
+
+/// The word lookups the solver needs
+pub trait WordList {
+    /// Check whether `word` is playable
+    fn is_word(&self, word: &str) -> bool;
+    /// Check whether any playable word starts with `prefix`
+    fn has_prefix(&self, prefix: &str) -> bool;
+}
+
+impl WordList for Dictionary {
+    fn is_word(&self, word: &str) -> bool {
+        self.contains(word)
+    }
+
+    fn has_prefix(&self, prefix: &str) -> bool {
+        Dictionary::has_prefix(self, prefix)
+    }
+}
+
+/// The words playable in a lobby: the base dictionary at the lobby's strictness,
+/// combined with its theme pack
+pub struct LobbyWords<'a> {
+    pub base: &'a Dictionary,
+    pub packs: &'a DictionaryPacks,
+    pub selection: Option<&'a PackSelection>,
+    pub strictness: DictionaryStrictness,
+}
+
+impl WordList for LobbyWords<'_> {
+    fn is_word(&self, word: &str) -> bool {
+        self.packs
+            .lookup(self.base, self.selection, word)
+            .is_some_and(|info| self.strictness.accepts(info.tier))
+    }
+
+    /// Prefixes aren't filtered by strictness, so a strict lobby may search a
+    /// little more than it needs to but never misses a word
+    fn has_prefix(&self, prefix: &str) -> bool {
+        let pack = self
+            .selection
+            .and_then(|s| Some((self.packs.get(&s.name)?, s.mode)));
+        match pack {
+            None => self.base.has_prefix(prefix),
+            Some((pack, PackMode::Layer)) => {
+                pack.has_prefix(prefix) || self.base.has_prefix(prefix)
+            }
+            Some((pack, PackMode::Replace)) => pack.has_prefix(prefix),
+        }
+    }
+}
+
+/// Find up to `limit` playable words on the grid that haven't been used yet
+///
+/// Words are traced like submissions: adjacent tiles (including diagonals), each
+/// tile at most once. Paths are pruned as soon as no word starts with them, so the
+/// search is fast with the FST backend and stops early once `limit` words are found.
+/// `used` holds lowercase words, as in `GameState::used_words`.
+pub fn find_unused_words(
+    grid: &Grid,
+    words: &impl WordList,
+    used: &HashSet<String>,
+    limit: usize,
+) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    search(grid, words, |word, _| {
+        if found.len() >= limit {
+            return ControlFlow::Break(());
+        }
+        if word.chars().count() >= MIN_WORD_LEN
+            && !used.contains(&word.to_lowercase())
+            && !found.iter().any(|w| w == word)
+        {
+            found.push(word.to_string());
+        }
+        if found.len() >= limit {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    found
+}
+
+/// The highest-scoring word a player could have played
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BestWord {
+    pub word: String,
+    pub positions: Vec<Position>,
+    pub score: i32,
+}
+
+/// Find the highest-scoring unused word on the grid under a game's rules
+///
+/// None if the board has no playable word left.
+pub fn best_word(
+    grid: &Grid,
+    words: &impl WordList,
+    used: &HashSet<String>,
+    rules: &GameRules,
+) -> Option<BestWord> {
+    best_words(grid, words, used, rules, 1).into_iter().next()
+}
+
+/// Find the `limit` highest-scoring unused words on the grid, best first
+///
+/// Unlike `find_unused_words` this visits every path, since the same word can
+/// score differently depending on which tiles it's traced through; each word is
+/// listed once, at its best path. Equal scores are ordered alphabetically.
+pub fn best_words(
+    grid: &Grid,
+    words: &impl WordList,
+    used: &HashSet<String>,
+    rules: &GameRules,
+    limit: usize,
+) -> Vec<BestWord> {
+    let mut best: HashMap<String, BestWord> = HashMap::new();
+    search(grid, words, |word, path| {
+        let length = word.chars().count();
+        if length >= MIN_WORD_LEN
+            && rules.allows_length(length)
+            && !used.contains(&word.to_lowercase())
+        {
+            // Paths come from the grid itself, so they always score
+            let score = Scorer::calculate_score(grid, path, rules).unwrap_or_default();
+            if best.get(word).is_none_or(|b| score > b.score) {
+                best.insert(
+                    word.to_string(),
+                    BestWord {
+                        word: word.to_string(),
+                        positions: path.to_vec(),
+                        score,
+                    },
+                );
+            }
+        }
+        ControlFlow::Continue(())
+    });
+
+    let mut best: Vec<BestWord> = best.into_values().collect();
+    best.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.word.cmp(&b.word)));
+    best.truncate(limit);
+    best
+}
+
+/// The scores of the words still on the board as it stands, best first, from
+/// every unused word on it as `best_words` lists them
+///
+/// Words that share a tile are all counted, even though playing one replaces
+/// tiles the others need: picking the best set that doesn't overlap is a hard
+/// search, so the scores are only good for a ceiling on what's left.
+pub fn remaining_word_scores(best: &[BestWord]) -> Vec<i32> {
+    best.iter().map(|word| word.score).collect()
+}
+
+/// The most points a player can still score from `remaining_word_scores`: one
+/// word a turn for the turns they have left, or every word in modes without
+/// turns. An upper bound, since the best words may not all fit on the board at
+/// once.
+pub fn max_remaining_score(scores: &[i32], turns_left: Option<usize>) -> i32 {
+    scores
+        .iter()
+        .take(turns_left.unwrap_or(usize::MAX))
+        .fold(0, |total, score| total.saturating_add(*score))
+}
+
+/// Walk every path on the grid that spells a playable word, calling `on_word`
+/// with the word and its tiles until it breaks
+fn search<W: WordList>(
+    grid: &Grid,
+    words: &W,
+    on_word: impl FnMut(&str, &[Position]) -> ControlFlow<()>,
+) {
+    let mut search = Search {
+        grid,
+        words,
+        visited: vec![vec![false; grid.first().map_or(0, |row| row.len())]; grid.len()],
+        prefix: String::new(),
+        path: Vec::new(),
+        on_word,
+        done: false,
+    };
+    for (row, cells) in grid.iter().enumerate() {
+        for col in 0..cells.len() {
+            if search.done {
+                return;
+            }
+            search.visit(row, col);
+        }
+    }
+}
+
+struct Search<'a, W, F> {
+    grid: &'a Grid,
+    words: &'a W,
+    visited: Vec<Vec<bool>>,
+    prefix: String,
+    path: Vec<Position>,
+    on_word: F,
+    done: bool,
+}
+
+impl<W, F> Search<'_, W, F>
+where
+    W: WordList,
+    F: FnMut(&str, &[Position]) -> ControlFlow<()>,
+{
+    fn visit(&mut self, row: usize, col: usize) {
+        let Some(cell) = self.grid.get(row).and_then(|r| r.get(col)) else {
+            return;
+        };
+        if self.visited[row][col] {
+            return;
+        }
+
+        let prefix_len = self.prefix.len();
+        self.prefix.extend(cell.letter.to_uppercase());
+        if self.words.has_prefix(&self.prefix) {
+            self.visited[row][col] = true;
+            self.path.push(Position { row, col });
+            if self.words.is_word(&self.prefix)
+                && (self.on_word)(&self.prefix, &self.path).is_break()
+            {
+                self.done = true;
+            }
+
+            for d_row in -1i32..=1 {
+                for d_col in -1i32..=1 {
+                    if self.done {
+                        break;
+                    }
+                    let (Some(next_row), Some(next_col)) = (
+                        row.checked_add_signed(d_row as isize),
+                        col.checked_add_signed(d_col as isize),
+                    ) else {
+                        continue;
+                    };
+                    self.visit(next_row, next_col);
+                }
+            }
+            self.path.pop();
+            self.visited[row][col] = false;
+        }
+        self.prefix.truncate(prefix_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GridCell, Multiplier};
+
+    fn grid(rows: &[&str]) -> Grid {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|letter| GridCell {
+                        letter,
+                        value: 1,
+                        multiplier: None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_finds_words_along_adjacent_paths() {
+        let dictionary = Dictionary::from_lists("cat\nact\ntac\ndog\n", "");
+        // C A
+        // X T
+        let board = grid(&["CA", "XT"]);
+
+        let mut found = find_unused_words(&board, &dictionary, &HashSet::new(), 10);
+        found.sort();
+        assert_eq!(found, vec!["ACT", "CAT", "TAC"], "DOG isn't on the board");
+    }
+
+    #[test]
+    fn test_skips_used_words_and_stops_at_limit() {
+        let dictionary = Dictionary::from_lists("cat\nact\ntac\n", "");
+        let board = grid(&["CA", "XT"]);
+        let used: HashSet<String> = ["cat".to_string(), "act".to_string()].into();
+
+        assert_eq!(
+            find_unused_words(&board, &dictionary, &used, 10),
+            vec!["TAC"]
+        );
+        assert_eq!(
+            find_unused_words(&board, &dictionary, &HashSet::new(), 1).len(),
+            1,
+            "The search stops once the limit is reached"
+        );
+
+        let all_used: HashSet<String> = ["cat", "act", "tac"].map(String::from).into();
+        assert!(
+            find_unused_words(&board, &dictionary, &all_used, 10).is_empty(),
+            "Every word on the board has been played"
+        );
+    }
+
+    #[test]
+    fn test_tiles_are_not_reused() {
+        let dictionary = Dictionary::from_lists("aa\n", "");
+        let board = grid(&["A"]);
+        assert!(find_unused_words(&board, &dictionary, &HashSet::new(), 10).is_empty());
+    }
+
+    #[test]
+    fn test_best_word_prefers_the_highest_scoring_path() {
+        let dictionary = Dictionary::from_lists("at\ncat\n", "");
+        // A A
+        // T C
+        let mut board = grid(&["AA", "TC"]);
+        board[0][1].multiplier = Some(Multiplier::TripleLetter);
+        let rules = GameRules::default();
+
+        let best = best_word(&board, &dictionary, &HashSet::new(), &rules).unwrap();
+        assert_eq!(best.word, "CAT");
+        assert_eq!(
+            best.positions,
+            vec![
+                Position { row: 1, col: 1 },
+                Position { row: 0, col: 1 },
+                Position { row: 1, col: 0 },
+            ],
+            "The word is traced through the triple letter"
+        );
+        assert_eq!(best.score, 5);
+    }
+
+    #[test]
+    fn test_best_word_follows_rules_and_used_words() {
+        let dictionary = Dictionary::from_lists("at\ncat\n", "");
+        let board = grid(&["AA", "TC"]);
+        let used: HashSet<String> = ["cat".to_string()].into();
+
+        let best = best_word(&board, &dictionary, &used, &GameRules::default()).unwrap();
+        assert_eq!(best.word, "AT", "CAT has already been played");
+
+        let three_letters = GameRules {
+            min_word_length: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            best_word(&board, &dictionary, &used, &three_letters),
+            None,
+            "AT is too short for the lobby's rules"
+        );
+    }
+
+    #[test]
+    fn test_best_words_are_ranked_and_distinct() {
+        let dictionary = Dictionary::from_lists("at\nta\ncat\n", "");
+        let board = grid(&["AA", "TC"]);
+
+        let best = best_words(
+            &board,
+            &dictionary,
+            &HashSet::new(),
+            &GameRules::default(),
+            5,
+        );
+        let ranked: Vec<&str> = best.iter().map(|b| b.word.as_str()).collect();
+        assert_eq!(
+            ranked,
+            vec!["CAT", "AT", "TA"],
+            "Each word appears once, ties alphabetically"
+        );
+        assert_eq!(
+            best_words(
+                &board,
+                &dictionary,
+                &HashSet::new(),
+                &GameRules::default(),
+                1
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_remaining_scores_are_a_ceiling() {
+        let word = |word: &str, score, tiles: &[(usize, usize)]| BestWord {
+            word: word.to_string(),
+            positions: tiles
+                .iter()
+                .map(|&(row, col)| Position { row, col })
+                .collect(),
+            score,
+        };
+        // A overlaps both B and C, which don't overlap each other
+        let best = [
+            word("a", 30, &[(0, 0), (0, 1), (0, 2)]),
+            word("b", 20, &[(0, 0), (1, 0)]),
+            word("c", 20, &[(0, 2), (1, 2)]),
+        ];
+        let scores = remaining_word_scores(&best);
+        assert_eq!(scores, vec![30, 20, 20]);
+        assert!(
+            max_remaining_score(&scores, Some(2)) >= 40,
+            "B and C can both be played in two turns"
+        );
+        assert!(remaining_word_scores(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_max_remaining_score_is_one_word_a_turn() {
+        let scores = [12, 7, 3];
+        assert_eq!(max_remaining_score(&scores, Some(2)), 19);
+        assert_eq!(
+            max_remaining_score(&scores, Some(5)),
+            22,
+            "There are only so many words to play"
+        );
+        assert_eq!(max_remaining_score(&scores, Some(0)), 0);
+        assert_eq!(
+            max_remaining_score(&scores, None),
+            22,
+            "Without turns every word can be played"
+        );
+    }
+}
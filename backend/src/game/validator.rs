@@ -1,8 +1,35 @@
 use std::collections::HashSet;
 
-use crate::models::{Grid, Position};
+use crate::models::{Grid, GridCell, Position};
+
+/// Why a list of positions isn't a playable path on a grid
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PathError {
+    #[error("no tiles selected")]
+    Empty,
+    /// Past the edge of the grid as it is, whatever its size
+    #[error("tile ({row}, {col}) is off the board")]
+    OutOfBounds { row: usize, col: usize },
+    #[error("tile ({row}, {col}) isn't next to the one before it")]
+    NotAdjacent { row: usize, col: usize },
+    #[error("tile ({row}, {col}) is used twice")]
+    Repeated { row: usize, col: usize },
+}
+
+/// The tile at a position, checked against the grid's actual dimensions (rows
+/// may differ in length)
+pub fn cell_at<'a>(grid: &'a Grid, pos: &Position) -> Result<&'a GridCell, PathError> {
+    grid.get(pos.row)
+        .and_then(|row| row.get(pos.col))
+        .ok_or(PathError::OutOfBounds {
+            row: pos.row,
+            col: pos.col,
+        })
+}
 
 pub struct WordValidator {
+    // TODO: Word checks currently go through the shared Dictionary in AppState
+    #[allow(dead_code)]
     dictionary: HashSet<String>,
 }
 
@@ -12,50 +39,51 @@ impl WordValidator {
     }
 
     /// Check if word exists in dictionary
+    #[allow(dead_code)]
     pub fn is_valid_word(&self, word: &str) -> bool {
         self.dictionary.contains(&word.to_uppercase())
     }
 
-    /// Validate that positions form a valid path on the grid
-    pub fn is_valid_path(&self, _grid: &Grid, positions: &[Position]) -> bool {
+    /// Check that positions form a path on the grid: at least one tile, every
+    /// tile on the board and next to the one before it, none used twice
+    pub fn validate_path(&self, grid: &Grid, positions: &[Position]) -> Result<(), PathError> {
         if positions.is_empty() {
-            return false;
+            return Err(PathError::Empty);
         }
 
-        // Check that each position is adjacent to the previous one
-        for window in positions.windows(2) {
-            if !Self::are_adjacent(&window[0], &window[1]) {
-                return false;
+        let mut seen = HashSet::with_capacity(positions.len());
+        for (i, pos) in positions.iter().enumerate() {
+            cell_at(grid, pos)?;
+            if i > 0 && !Self::are_adjacent(&positions[i - 1], pos) {
+                return Err(PathError::NotAdjacent {
+                    row: pos.row,
+                    col: pos.col,
+                });
+            }
+            if !seen.insert(pos) {
+                return Err(PathError::Repeated {
+                    row: pos.row,
+                    col: pos.col,
+                });
             }
         }
-
-        // Check that no position is used twice
-        let unique_positions: HashSet<_> = positions.iter().collect();
-        if unique_positions.len() != positions.len() {
-            return false;
-        }
-
-        // Check that all positions are within bounds
-        positions.iter().all(|pos| {
-            let num_rows = _grid.len();
-            let num_cols = if num_rows > 0 { _grid[0].len() } else { 0 };
-            pos.row < num_rows && pos.col < num_cols
-        })
+        Ok(())
     }
 
     /// Check if two positions are adjacent (including diagonals)
     fn are_adjacent(pos1: &Position, pos2: &Position) -> bool {
-        let row_diff = (pos1.row as i32 - pos2.row as i32).abs();
-        let col_diff = (pos1.col as i32 - pos2.col as i32).abs();
+        let row_diff = pos1.row.abs_diff(pos2.row);
+        let col_diff = pos1.col.abs_diff(pos2.col);
 
         row_diff <= 1 && col_diff <= 1 && (row_diff + col_diff > 0)
     }
 
     /// Extract word from grid positions
-    pub fn extract_word(&self, grid: &Grid, positions: &[Position]) -> String {
+    #[allow(dead_code)]
+    pub fn extract_word(&self, grid: &Grid, positions: &[Position]) -> Result<String, PathError> {
         positions
             .iter()
-            .map(|pos| grid[pos.row][pos.col].letter)
+            .map(|pos| cell_at(grid, pos).map(|cell| cell.letter))
             .collect()
     }
 }
@@ -64,6 +92,25 @@ impl WordValidator {
 mod tests {
     use super::*;
 
+    fn grid(rows: &[usize]) -> Grid {
+        rows.iter()
+            .map(|&len| {
+                vec![
+                    GridCell {
+                        letter: 'A',
+                        value: 1,
+                        multiplier: None,
+                    };
+                    len
+                ]
+            })
+            .collect()
+    }
+
+    fn at(row: usize, col: usize) -> Position {
+        Position { row, col }
+    }
+
     #[test]
     fn test_adjacent_positions() {
         let pos1 = Position { row: 0, col: 0 };
@@ -75,4 +122,69 @@ mod tests {
         assert!(WordValidator::are_adjacent(&pos2, &pos3));
         assert!(!WordValidator::are_adjacent(&pos1, &pos4));
     }
+
+    #[test]
+    fn test_validate_path_errors() {
+        let validator = WordValidator::new(HashSet::new());
+        let grid = grid(&[3, 3, 3]);
+
+        assert_eq!(
+            validator.validate_path(&grid, &[at(0, 0), at(1, 1), at(2, 2)]),
+            Ok(())
+        );
+        assert_eq!(validator.validate_path(&grid, &[]), Err(PathError::Empty));
+        assert_eq!(
+            validator.validate_path(&grid, &[at(0, 0), at(2, 2)]),
+            Err(PathError::NotAdjacent { row: 2, col: 2 })
+        );
+        assert_eq!(
+            validator.validate_path(&grid, &[at(0, 0), at(0, 1), at(0, 0)]),
+            Err(PathError::Repeated { row: 0, col: 0 })
+        );
+    }
+
+    #[test]
+    fn test_bounds_follow_the_grid_not_a_fixed_size() {
+        let validator = WordValidator::new(HashSet::new());
+
+        // A 6x6 board has a sixth row and column
+        let large = grid(&[6; 6]);
+        assert_eq!(
+            validator.validate_path(&large, &[at(5, 4), at(5, 5)]),
+            Ok(())
+        );
+
+        // A 3x3 board doesn't have a fourth
+        let small = grid(&[3; 3]);
+        assert_eq!(
+            validator.validate_path(&small, &[at(2, 2), at(3, 3)]),
+            Err(PathError::OutOfBounds { row: 3, col: 3 })
+        );
+
+        // Each row is checked against its own length
+        let ragged = grid(&[3, 1]);
+        assert_eq!(
+            validator.validate_path(&ragged, &[at(0, 1), at(1, 1)]),
+            Err(PathError::OutOfBounds { row: 1, col: 1 })
+        );
+    }
+
+    #[test]
+    fn test_far_off_positions_are_rejected_without_panicking() {
+        let validator = WordValidator::new(HashSet::new());
+        let grid = grid(&[2]);
+        let far = at(usize::MAX, 1 << 31);
+
+        assert!(!WordValidator::are_adjacent(&at(0, 0), &far));
+        assert!(matches!(
+            validator.validate_path(&grid, std::slice::from_ref(&far)),
+            Err(PathError::OutOfBounds { .. })
+        ));
+        assert!(validator.validate_path(&Vec::new(), &[at(0, 0)]).is_err());
+        assert!(validator.extract_word(&grid, &[far]).is_err());
+        assert_eq!(
+            validator.extract_word(&grid, &[at(0, 1)]).as_deref(),
+            Ok("A")
+        );
+    }
 }
@@ -0,0 +1,75 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::{db, game::scorer::RARE_WORD_PLAYS, AppState};
+
+/// How often play counts are reloaded from the word stats
+pub const WORD_PLAYS_INTERVAL: Duration = Duration::from_secs(600);
+
+/// How often each word has been played across all games, for the obscurity bonus
+///
+/// Only words played at least `RARE_WORD_PLAYS` times are kept; any other word
+/// is rare. Until the first load nothing is known, so no bonus is given rather
+/// than every word looking rare after a restart.
+#[derive(Default)]
+pub struct WordPlays {
+    plays: RwLock<Option<Arc<HashMap<String, i64>>>>,
+}
+
+impl WordPlays {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often a word has been played, if the counts have been loaded
+    pub fn plays(&self, word: &str) -> Option<i64> {
+        let plays = self.plays.read().ok()?.clone()?;
+        Some(plays.get(word).copied().unwrap_or(0))
+    }
+
+    fn replace(&self, plays: HashMap<String, i64>) {
+        if let Ok(mut current) = self.plays.write() {
+            *current = Some(Arc::new(plays));
+        }
+    }
+}
+
+/// Background task that reloads word play counts
+pub async fn word_plays_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(WORD_PLAYS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("word_plays", WORD_PLAYS_INTERVAL);
+
+        match db::queries::get_word_plays(&state.db, RARE_WORD_PLAYS).await {
+            Ok(plays) => {
+                tracing::debug!("Loaded play counts for {} words", plays.len());
+                state.word_plays.replace(plays.into_iter().collect());
+            }
+            Err(e) => tracing::error!("Failed to load word play counts: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unloaded_counts_are_unknown() {
+        let word_plays = WordPlays::new();
+        assert_eq!(word_plays.plays("QUIXOTIC"), None);
+
+        word_plays.replace(HashMap::from([("CAT".to_string(), 500)]));
+        assert_eq!(word_plays.plays("CAT"), Some(500));
+        assert_eq!(
+            word_plays.plays("QUIXOTIC"),
+            Some(0),
+            "Words missing from the counts are rare"
+        );
+    }
+}
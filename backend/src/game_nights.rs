@@ -0,0 +1,271 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    models::{GameMode, GameNightStatus, UpcomingGameNight},
+    websocket::{
+        self, handler::get_or_create_channel_lobby, messages::ServerMessage,
+        schedule::ScheduledStart,
+    },
+    AppState,
+};
+
+/// How often game nights are checked for occurrences to set up or announce
+pub const GAME_NIGHT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// How long before a game night starts that it's announced and its lobby opens
+const ANNOUNCE_AHEAD_MINUTES: i64 = 60;
+/// How long past its start an occurrence that never started counts as missed
+const MISSED_AFTER_MINUTES: i64 = 60;
+const DISCORD_API: &str = "https://discord.com/api/v10";
+
+/// Background task that runs guild game nights: adds each one's next
+/// occurrence, and an hour before it starts announces it and opens the
+/// channel's lobby with the game scheduled
+pub async fn game_night_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(GAME_NIGHT_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("game_nights", GAME_NIGHT_CHECK_INTERVAL);
+
+        if let Err(e) = run_game_nights(&state).await {
+            tracing::error!("Game night check failed: {}", e);
+        }
+    }
+}
+
+async fn run_game_nights(state: &AppState) -> sqlx::Result<()> {
+    let now = Utc::now();
+    for game_night in db::queries::get_all_game_nights(&state.db).await? {
+        db::queries::create_game_night_event(
+            &state.db,
+            &game_night,
+            game_night.next_occurrence(now),
+        )
+        .await?;
+    }
+
+    let missed = db::queries::mark_missed_game_nights(
+        &state.db,
+        now - chrono::Duration::minutes(MISSED_AFTER_MINUTES),
+    )
+    .await?;
+    if missed > 0 {
+        tracing::warn!("{} game night(s) passed without starting", missed);
+    }
+
+    let until = now + chrono::Duration::minutes(ANNOUNCE_AHEAD_MINUTES);
+    for game_night in db::queries::get_upcoming_game_nights(&state.db, until).await? {
+        // Also reopens the lobby if the server restarted since the announcement
+        if !open_lobby(state, &game_night).await {
+            db::queries::set_game_night_status(
+                &state.db,
+                game_night.event_id,
+                GameNightStatus::Cancelled,
+            )
+            .await?;
+            continue;
+        }
+        // A failed announcement is retried on the next check
+        if game_night.announced_at.is_none() && announce(state, &game_night).await {
+            db::queries::mark_game_night_announced(&state.db, game_night.event_id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Open the channel's lobby with the game night's game scheduled in it; false if
+/// it can't be played there at all
+///
+/// A lobby with a game the host scheduled themselves is left alone, and the game
+/// night goes ahead if the host starts a game in time.
+async fn open_lobby(state: &AppState, game_night: &UpcomingGameNight) -> bool {
+    let lobby_id = get_or_create_channel_lobby(
+        state,
+        &game_night.channel_id.to_string(),
+        Some(game_night.guild_id.to_string()),
+    )
+    .await;
+
+    let mut schedule = match ScheduledStart::new(
+        game_night.starts_at,
+        game_night.mode.clone(),
+        game_night.target_score,
+        game_night.created_by,
+        Utc::now(),
+    ) {
+        Ok(schedule) => schedule,
+        // The setup was checked when it was saved, so only the start time can be
+        // off: it has just passed, and the occurrence will count as missed
+        Err(e) => {
+            tracing::debug!(
+                "Game night {} can't be scheduled: {}",
+                game_night.event_id,
+                e
+            );
+            return true;
+        }
+    };
+    schedule.game_night_event = Some(game_night.event_id);
+
+    {
+        let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) else {
+            return true;
+        };
+        if !lobby.allows_mode(&schedule.mode) {
+            tracing::warn!(
+                "Game night {} cancelled: {:?} isn't allowed in guild {}",
+                game_night.event_id,
+                schedule.mode,
+                game_night.guild_id
+            );
+            return false;
+        }
+        if lobby.scheduled_start.is_some() {
+            return true;
+        }
+        lobby.scheduled_start = Some(schedule.clone());
+    }
+
+    tracing::info!(
+        "Opened lobby {} for game night {} at {}",
+        lobby_id,
+        game_night.event_id,
+        game_night.starts_at
+    );
+    websocket::broadcast_to_lobby(state, &lobby_id, ServerMessage::GameScheduled { schedule })
+        .await;
+    true
+}
+
+/// Post a game night's announcement to its webhook, or to the channel through
+/// the bot; returns false if it didn't go through
+///
+/// With neither set up there's nowhere to post, and the lobby opening quietly
+/// counts as the announcement.
+async fn announce(state: &AppState, game_night: &UpcomingGameNight) -> bool {
+    let request = match (&game_night.webhook_url, &state.config.discord.bot_token) {
+        (Some(url), _) => state.http_client.post(url),
+        (None, Some(token)) => state
+            .http_client
+            .post(format!(
+                "{}/channels/{}/messages",
+                DISCORD_API, game_night.channel_id
+            ))
+            .header("Authorization", format!("Bot {}", token)),
+        (None, None) => return true,
+    };
+    // Only the game night's own role is pinged
+    let roles: Vec<String> = game_night.ping_role_id.iter().map(i64::to_string).collect();
+    let body = json!({
+        "content": announcement_message(game_night),
+        "allowed_mentions": { "parse": [], "roles": roles },
+    });
+    match request.json(&body).send().await {
+        Ok(response) if response.status().is_success() => true,
+        Ok(response) => {
+            tracing::warn!(
+                "Game night {} announcement returned {}",
+                game_night.event_id,
+                response.status()
+            );
+            false
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Game night {} announcement failed: {}",
+                game_night.event_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// The post announcing a game night, with a start time Discord shows in each
+/// reader's own time zone
+pub fn announcement_message(game_night: &UpcomingGameNight) -> String {
+    let mut message = String::new();
+    if let Some(role) = game_night.ping_role_id {
+        message.push_str(&format!("<@&{}> ", role));
+    }
+    message.push_str(&format!(
+        "🎲 **Game night!** A {} game starts in <#{}> <t:{}:R> (<t:{}:t>). Join the lobby and ready up to play.",
+        mode_name(&game_night.mode),
+        game_night.channel_id,
+        game_night.starts_at.timestamp(),
+        game_night.starts_at.timestamp()
+    ));
+    message
+}
+
+fn mode_name(mode: &GameMode) -> &'static str {
+    match mode {
+        GameMode::Coop => "co-op",
+        GameMode::Blitz => "blitz",
+        GameMode::Duel => "duel",
+        GameMode::TimeAttack => "time attack",
+        _ => "multiplayer",
+    }
+}
+
+/// Record that a game night's game started, and who was playing
+pub async fn record_start(state: &AppState, event_id: i64, game_id: Uuid, user_ids: &[i64]) {
+    match db::queries::record_game_night_start(&state.db, event_id, game_id, user_ids).await {
+        Ok(()) => tracing::info!("Game night {} started as game {}", event_id, game_id),
+        Err(e) => tracing::error!(
+            "Failed to record the start of game night {}: {}",
+            event_id,
+            e
+        ),
+    }
+}
+
+/// Record that a game night was called off, or didn't have enough players
+pub async fn record_cancelled(state: &AppState, event_id: i64) {
+    if let Err(e) =
+        db::queries::set_game_night_status(&state.db, event_id, GameNightStatus::Cancelled).await
+    {
+        tracing::error!(
+            "Failed to record game night {} as cancelled: {}",
+            event_id,
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn game_night(ping_role_id: Option<i64>) -> UpcomingGameNight {
+        UpcomingGameNight {
+            event_id: 1,
+            guild_id: 10,
+            channel_id: 20,
+            mode: GameMode::Coop,
+            target_score: None,
+            starts_at: Utc.with_ymd_and_hms(2026, 10, 15, 19, 30, 0).unwrap(),
+            announced_at: None,
+            webhook_url: None,
+            ping_role_id,
+            created_by: 30,
+        }
+    }
+
+    #[test]
+    fn test_announcement_message() {
+        let message = announcement_message(&game_night(None));
+        assert!(message.starts_with("🎲 **Game night!** A co-op game starts in <#20>"));
+        assert!(message.contains("<t:1792092600:R>"));
+
+        let message = announcement_message(&game_night(Some(40)));
+        assert!(message.starts_with("<@&40> 🎲"), "The role is pinged first");
+    }
+}
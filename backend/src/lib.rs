@@ -0,0 +1,2537 @@
+pub mod accounts;
+pub mod analytics;
+pub mod auth;
+pub mod avatars;
+pub mod config;
+pub mod db;
+pub mod dictionary;
+pub mod encryption;
+pub mod error;
+pub mod frontend;
+pub mod game;
+pub mod game_nights;
+pub mod locale;
+pub mod models;
+pub mod public_stats;
+pub mod render;
+pub mod routes;
+pub mod seasons;
+pub mod security_headers;
+pub mod utils;
+pub mod websocket;
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use auth::ReplayGuard;
+use avatars::AvatarCache;
+use axum::{routing::get, Router};
+use config::{Config, GameConfig};
+use dashmap::DashMap;
+use dictionary::{
+    packs::{DictionaryPacks, PackSelection},
+    Dictionary, DictionaryStrictness, SharedDictionary,
+};
+use error::ApiError;
+use game::{
+    board_theme::BoardTheme,
+    grid::{LetterMode, MultiplierLayout},
+    grid_pool::{BoardKey, GridPool},
+    rules::GameRules,
+    session::GameSession,
+    word_plays::WordPlays,
+};
+use models::{GameMode, GuildSettings, LobbyPuzzle, PlayerProfile};
+use public_stats::{PublicStatsCache, PUBLIC_STATS_CACHE_SIZE};
+use render::{ResultCardCache, RESULT_CARD_CACHE_SIZE};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    trace::TraceLayer,
+};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+use websocket::{
+    activity::{Activity, ActivityFeed},
+    game_actor::GameActors,
+    limits::{ConnectionLimiter, RateLimiter},
+    messages::{LobbyCountdown, LobbyPlayerInfo, LobbyType, ServerMessage, TableInfo},
+    outbound::{ClientSender, OutboundMessage},
+    presence::PresenceIndex,
+    schedule::ScheduledStart,
+};
+
+/// Grace period before removing disconnected players (seconds)
+pub const PLAYER_DISCONNECT_GRACE_PERIOD: Duration = Duration::from_secs(60);
+/// Grace period before removing empty lobbies (seconds)
+pub const LOBBY_EMPTY_GRACE_PERIOD: Duration = Duration::from_secs(120);
+/// Most tables (the main lobby included) a channel lobby can be split into
+pub const MAX_TABLES_PER_CHANNEL: u32 = 8;
+/// How long after disconnecting a user is offered a rejoin to their last lobby
+pub const LAST_LOBBY_TTL: Duration = Duration::from_secs(600);
+/// How long an invite waits for a disconnected user to come back
+pub const QUEUED_INVITE_TTL: Duration = Duration::from_secs(3600);
+/// Allowed characters for lobby codes - excludes I, O, 0, 1 for readability
+pub const LOBBY_CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Length of generated lobby codes
+pub const LOBBY_CODE_LENGTH: usize = 6;
+/// Length of lobby codes once the short code space is getting crowded
+pub const LOBBY_CODE_LONG_LENGTH: usize = 8;
+/// Switch to long codes once this many short codes are in use (~0.1% of the code space)
+pub const LOBBY_CODE_LONG_THRESHOLD: usize = 1_000_000;
+/// Attempts at each code length before giving up on finding an unused code
+pub const LOBBY_CODE_MAX_ATTEMPTS: usize = 16;
+/// How often dirty in-memory game sessions are flushed to the database
+pub const GAME_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the lobby cleanup task runs
+pub const LOBBY_CLEANUP_INTERVAL: Duration = Duration::from_secs(15);
+/// How often each game's actor checks its clocks, and the actors are checked
+/// for games without one
+pub const BLITZ_CLOCK_INTERVAL: Duration = Duration::from_secs(1);
+/// How often idle lobbies broadcast their live state
+pub const LOBBY_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a typing indicator lasts without being refreshed
+pub const PLAYER_TYPING_TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of missed intervals before a background task is reported as stalled
+pub const HEARTBEAT_MISSED_INTERVALS: u32 = 3;
+
+/// Connection state for a lobby player
+#[derive(Debug, Clone)]
+pub enum PlayerConnectionState {
+    /// Player is actively connected with an open WebSocket
+    Connected,
+    /// Player's WebSocket dropped, waiting for reconnection within grace period
+    /// Player is still visible in the lobby during this state
+    AwaitingReconnect { since: Instant },
+}
+
+/// Information about a connected lobby player
+#[derive(Debug, Clone)]
+pub struct LobbyPlayer {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub tx: ClientSender,
+    pub connection_state: PlayerConnectionState,
+    /// Player marked themselves ready for the next game
+    pub ready: bool,
+    /// When the player last said they were typing
+    pub typing_since: Option<Instant>,
+    /// Players muted for this lobby visit; cleared when they leave the lobby
+    pub muted: HashSet<i64>,
+    /// Joined from a spectator connection: watches without a seat, and is never
+    /// dealt into games or made host
+    pub spectator: bool,
+}
+
+impl LobbyPlayer {
+    /// Returns true if the player has an active WebSocket connection
+    pub fn is_connected(&self) -> bool {
+        matches!(self.connection_state, PlayerConnectionState::Connected)
+    }
+
+    /// Returns true if the player's typing indicator is still fresh
+    pub fn is_typing(&self, now: Instant) -> bool {
+        self.typing_since
+            .is_some_and(|since| now.duration_since(since) < PLAYER_TYPING_TIMEOUT)
+    }
+
+    /// Returns true if this player shouldn't see social messages (typing, chat)
+    /// from `sender`, because they muted or blocked them
+    pub fn hides(&self, sender: i64, blocks: &BlockLists) -> bool {
+        self.muted.contains(&sender) || blocks.is_blocked(self.user_id, sender)
+    }
+
+    /// A message as this player should see it, without social signals from
+    /// players they hide
+    pub fn filter_social(&self, message: &ServerMessage, blocks: &BlockLists) -> ServerMessage {
+        let mut message = message.clone();
+        if let ServerMessage::LobbyHeartbeat { typing, .. } = &mut message {
+            typing.retain(|id| id.parse().map_or(true, |id| !self.hides(id, blocks)));
+        }
+        message
+    }
+
+    /// Returns true if the player should be visible in the lobby
+    /// (both connected and awaiting reconnect players are visible)
+    pub fn is_visible(&self) -> bool {
+        // Players are visible in both Connected and AwaitingReconnect states
+        // They only become invisible when removed by the background cleanup task
+        true
+    }
+}
+
+/// A user waiting for a seat in a full channel lobby
+#[derive(Debug, Clone)]
+pub struct QueuedPlayer {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub tx: ClientSender,
+}
+
+/// A game lobby that players can join
+#[derive(Debug)]
+pub struct Lobby {
+    pub lobby_id: String,
+    pub lobby_type: LobbyType,
+    /// For custom lobbies, a short shareable code (e.g., "ABC123")
+    pub lobby_code: Option<String>,
+    /// For channel lobbies, the Discord channel ID
+    pub channel_id: Option<String>,
+    /// For channel lobbies, the Discord guild ID
+    pub guild_id: Option<String>,
+    /// Players in the lobby, keyed by user_id
+    pub players: DashMap<i64, LobbyPlayer>,
+    /// The user ID of the lobby host (first player to join or assigned after host leaves)
+    pub host_id: Option<i64>,
+    /// The active game ID if a game is in progress
+    pub active_game_id: Option<Uuid>,
+    /// Flag to prevent race conditions when starting a game
+    /// Set atomically when game start begins, cleared on completion or failure
+    pub game_starting: AtomicBool,
+    /// When the lobby was created
+    pub created_at: Instant,
+    /// When the lobby became empty (for cleanup grace period)
+    pub empty_since: Option<Instant>,
+    /// Seats held by the host for invited users, keyed by user_id, with their expiry
+    pub reserved_seats: HashMap<i64, Instant>,
+    /// Admin console connections receiving a copy of this lobby's broadcasts
+    pub observers: DashMap<Uuid, mpsc::Sender<ServerMessage>>,
+    /// For channel lobbies in a guild, the guild's community-wide defaults
+    pub guild_settings: Option<GuildSettings>,
+    /// Which dictionary tiers are playable, chosen by the host
+    pub dictionary_strictness: DictionaryStrictness,
+    /// Theme pack chosen by the host, if any
+    pub dictionary_pack: Option<PackSelection>,
+    /// How multipliers are placed on the lobby's boards, chosen by the host
+    pub multiplier_layout: MultiplierLayout,
+    /// Where the lobby's letters come from, chosen by the host
+    pub letter_mode: LetterMode,
+    /// How the lobby's boards are drawn, chosen by the host
+    pub board_theme: BoardTheme,
+    /// House rules for the lobby's next games, chosen by the host
+    pub rules: GameRules,
+    /// A shared puzzle the lobby's next games are dealt instead of a random
+    /// board, chosen by the host
+    pub puzzle: Option<LobbyPuzzle>,
+    /// For channel lobbies, which table of the channel this is (1 is the main lobby)
+    pub table_number: u32,
+    /// Users waiting for a seat, in arrival order (channel lobbies only)
+    pub waiting_queue: VecDeque<QueuedPlayer>,
+    /// Recent joins, leaves, games and notable words, sent to players as they join
+    pub activity: ActivityFeed,
+    /// A game the host has set to start later
+    pub scheduled_start: Option<ScheduledStart>,
+}
+
+impl Lobby {
+    /// Create a new channel-based lobby
+    pub fn new_channel(channel_id: String, guild_id: Option<String>) -> Self {
+        Self::new_table(channel_id, guild_id, 1)
+    }
+
+    /// Create one table of a channel lobby, with its own players and game
+    pub fn new_table(channel_id: String, guild_id: Option<String>, table_number: u32) -> Self {
+        Self {
+            lobby_id: Self::table_lobby_id(&channel_id, table_number),
+            lobby_type: LobbyType::Channel,
+            lobby_code: None,
+            channel_id: Some(channel_id),
+            guild_id,
+            players: DashMap::new(),
+            host_id: None,
+            active_game_id: None,
+            game_starting: AtomicBool::new(false),
+            created_at: Instant::now(),
+            empty_since: None,
+            reserved_seats: HashMap::new(),
+            observers: DashMap::new(),
+            guild_settings: None,
+            dictionary_strictness: DictionaryStrictness::default(),
+            dictionary_pack: None,
+            multiplier_layout: MultiplierLayout::default(),
+            letter_mode: LetterMode::default(),
+            board_theme: BoardTheme::default(),
+            rules: GameRules::default(),
+            puzzle: None,
+            table_number,
+            waiting_queue: VecDeque::new(),
+            activity: ActivityFeed::default(),
+            scheduled_start: None,
+        }
+    }
+
+    /// The lobby_id of a channel's table; table 1 is the channel lobby itself
+    pub fn table_lobby_id(channel_id: &str, table_number: u32) -> String {
+        if table_number <= 1 {
+            format!("channel:{}", channel_id)
+        } else {
+            format!("channel:{}:table:{}", channel_id, table_number)
+        }
+    }
+
+    /// Summary for the table browser
+    pub fn table_info(&self) -> TableInfo {
+        TableInfo {
+            lobby_id: self.lobby_id.clone(),
+            table_number: self.table_number,
+            player_count: self.seated_player_count(),
+            host_username: self
+                .host_id
+                .and_then(|host_id| self.players.get(&host_id))
+                .map(|host| host.username.clone()),
+            in_game: self.active_game_id.is_some(),
+        }
+    }
+
+    /// Create a new custom lobby with a code reserved via `reserve_lobby_code`
+    pub fn new_custom(lobby_code: String) -> Self {
+        Self {
+            lobby_id: format!("custom:{}", lobby_code),
+            lobby_type: LobbyType::Custom,
+            lobby_code: Some(lobby_code),
+            channel_id: None,
+            guild_id: None,
+            players: DashMap::new(),
+            host_id: None,
+            active_game_id: None,
+            game_starting: AtomicBool::new(false),
+            created_at: Instant::now(),
+            empty_since: None,
+            reserved_seats: HashMap::new(),
+            observers: DashMap::new(),
+            guild_settings: None,
+            dictionary_strictness: DictionaryStrictness::default(),
+            dictionary_pack: None,
+            multiplier_layout: MultiplierLayout::default(),
+            letter_mode: LetterMode::default(),
+            board_theme: BoardTheme::default(),
+            rules: GameRules::default(),
+            puzzle: None,
+            table_number: 1,
+            waiting_queue: VecDeque::new(),
+            activity: ActivityFeed::default(),
+            scheduled_start: None,
+        }
+    }
+
+    /// Add an entry to the lobby's activity feed
+    pub fn record_activity(&mut self, activity: Activity) {
+        self.activity.record(activity, chrono::Utc::now());
+    }
+
+    /// Check if a user is the host of this lobby
+    pub fn is_host(&self, user_id: i64) -> bool {
+        self.host_id == Some(user_id)
+    }
+
+    /// Add a user to the back of the waiting queue, or refresh their connection if
+    /// they're already waiting. Returns their 1-based position.
+    pub fn enqueue(&mut self, player: QueuedPlayer) -> usize {
+        if let Some(index) = self
+            .waiting_queue
+            .iter()
+            .position(|queued| queued.user_id == player.user_id)
+        {
+            self.waiting_queue[index] = player;
+            return index + 1;
+        }
+        self.waiting_queue.push_back(player);
+        self.waiting_queue.len()
+    }
+
+    /// Remove a user from the waiting queue; false if they weren't in it
+    pub fn leave_queue(&mut self, user_id: i64) -> bool {
+        let before = self.waiting_queue.len();
+        self.waiting_queue
+            .retain(|queued| queued.user_id != user_id);
+        self.waiting_queue.len() != before
+    }
+
+    /// Seat queued users, in order, while there's room and no game is running or
+    /// starting (seats only change between games). Returns the users promoted.
+    pub fn promote_from_queue(&mut self, max_players: usize, now: Instant) -> Vec<QueuedPlayer> {
+        let mut promoted = Vec::new();
+        if self.active_game_id.is_some() || self.game_starting.load(Ordering::SeqCst) {
+            return promoted;
+        }
+
+        while let Some(next) = self.waiting_queue.front() {
+            if !self.has_room_for(next.user_id, max_players, now) {
+                break;
+            }
+            let Some(queued) = self.waiting_queue.pop_front() else {
+                break;
+            };
+            self.players.insert(
+                queued.user_id,
+                LobbyPlayer {
+                    user_id: queued.user_id,
+                    username: queued.username.clone(),
+                    avatar_url: queued.avatar_url.clone(),
+                    tx: queued.tx.clone(),
+                    connection_state: PlayerConnectionState::Connected,
+                    ready: false,
+                    typing_since: None,
+                    muted: HashSet::new(),
+                    spectator: false,
+                },
+            );
+            self.reserved_seats.remove(&queued.user_id);
+            if self.host_id.is_none() {
+                self.host_id = Some(queued.user_id);
+            }
+            self.empty_since = None;
+            promoted.push(queued);
+        }
+        promoted
+    }
+
+    /// Check if the lobby has an active game in progress
+    pub fn has_active_game(&self) -> bool {
+        self.active_game_id.is_some()
+    }
+
+    /// Atomically try to start a game.
+    /// Returns true if this caller can proceed with game creation,
+    /// false if a game is already active or another start is in progress.
+    ///
+    /// This prevents race conditions where two simultaneous StartGame requests
+    /// could both pass the has_active_game() check before either sets active_game_id.
+    pub fn try_start_game(&self) -> bool {
+        // If there's already an active game, fail fast
+        if self.active_game_id.is_some() {
+            return false;
+        }
+        // Atomically set game_starting from false to true
+        // compare_exchange returns Ok if the swap succeeded (value was false)
+        let acquired = self
+            .game_starting
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+
+        // Double-check active_game_id after acquiring the flag to prevent race condition
+        // where another thread sets active_game_id between our check and the flag acquisition
+        if acquired && self.active_game_id.is_some() {
+            // Another thread set active_game_id, release the flag and return false
+            self.game_starting.store(false, Ordering::SeqCst);
+            return false;
+        }
+
+        acquired
+    }
+
+    /// Clear the game_starting flag (called on failure or when game_id is set)
+    pub fn clear_game_starting(&self) {
+        self.game_starting.store(false, Ordering::SeqCst);
+    }
+
+    /// Count of actively connected players (excludes disconnected ones in grace
+    /// period, and spectators)
+    pub fn connected_player_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| p.is_connected() && !p.spectator)
+            .count()
+    }
+
+    /// Players holding a seat, connected or not; spectators don't take one
+    pub fn seated_player_count(&self) -> usize {
+        self.players.iter().filter(|p| !p.spectator).count()
+    }
+
+    /// Connected players who are ready for the next game
+    pub fn ready_player_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| p.is_connected() && p.ready && !p.spectator)
+            .count()
+    }
+
+    /// Player list for `LobbyPlayerList`, with host, connection and ready flags
+    ///
+    /// Includes ALL visible players (connected + awaiting reconnect); players only
+    /// disappear when removed by background cleanup after the grace period.
+    pub fn player_infos(&self, profiles: &PlayerProfiles) -> Vec<LobbyPlayerInfo> {
+        let mut players: Vec<LobbyPlayerInfo> = self
+            .players
+            .iter()
+            .filter(|p| p.is_visible())
+            .map(|entry| LobbyPlayerInfo {
+                user_id: entry.user_id.to_string(),
+                username: entry.username.clone(),
+                avatar_url: entry.avatar_url.clone(),
+                is_host: self.is_host(entry.user_id),
+                is_connected: entry.is_connected(),
+                awaiting_reconnect: matches!(
+                    entry.connection_state,
+                    PlayerConnectionState::AwaitingReconnect { .. }
+                ),
+                ready: entry.ready,
+                spectator: entry.spectator,
+                profile: profiles.get(entry.user_id),
+            })
+            .collect();
+        players.sort_by_key(|p| !p.is_host);
+        players
+    }
+
+    /// Reset everyone's ready flag, e.g. once a game starts
+    pub fn clear_ready(&self) {
+        for mut player in self.players.iter_mut() {
+            player.ready = false;
+        }
+    }
+
+    /// Live lobby state for the periodic `LobbyHeartbeat`
+    pub fn heartbeat(&self, now: Instant) -> ServerMessage {
+        let mut ready = Vec::new();
+        let mut typing = Vec::new();
+        let mut reconnecting = Vec::new();
+        let mut players: Vec<_> = self.players.iter().collect();
+        players.sort_by_key(|p| p.user_id);
+
+        for player in &players {
+            if player.ready {
+                ready.push(player.user_id.to_string());
+            }
+            if player.is_typing(now) {
+                typing.push(player.user_id.to_string());
+            }
+            if let PlayerConnectionState::AwaitingReconnect { since } = &player.connection_state {
+                let released_at = *since + PLAYER_DISCONNECT_GRACE_PERIOD;
+                reconnecting.push(LobbyCountdown {
+                    user_id: player.user_id.to_string(),
+                    secs_left: released_at.saturating_duration_since(now).as_secs(),
+                });
+            }
+        }
+
+        let mut reserved_seats: Vec<LobbyCountdown> = self
+            .reserved_seats
+            .iter()
+            .filter(|(user_id, expires_at)| {
+                **expires_at > now && !self.players.contains_key(user_id)
+            })
+            .map(|(user_id, expires_at)| LobbyCountdown {
+                user_id: user_id.to_string(),
+                secs_left: expires_at.saturating_duration_since(now).as_secs(),
+            })
+            .collect();
+        reserved_seats.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+
+        ServerMessage::LobbyHeartbeat {
+            connected_count: players.iter().filter(|p| p.is_connected()).count(),
+            ready,
+            typing,
+            reconnecting,
+            reserved_seats,
+            scheduled_start: self
+                .scheduled_start
+                .as_ref()
+                .map(|schedule| schedule.countdown(chrono::Utc::now())),
+        }
+    }
+
+    /// Check if lobby has any players (connected or disconnected in grace period)
+    pub fn has_any_players(&self) -> bool {
+        !self.players.is_empty()
+    }
+
+    /// Rounds for a new game: the guild's default, or `fallback` outside a configured guild
+    pub fn default_rounds(&self, fallback: u8) -> u8 {
+        self.guild_settings
+            .as_ref()
+            .map_or(fallback, |settings| settings.default_rounds as u8)
+    }
+
+    /// Check the guild's allowed modes (every mode is allowed outside a guild)
+    pub fn allows_mode(&self, mode: &GameMode) -> bool {
+        self.guild_settings
+            .as_ref()
+            .is_none_or(|settings| settings.allows_mode(mode))
+    }
+
+    /// Language for the lobby's rejections and announcements: the theme pack's,
+    /// if it declares one, else the guild's default
+    pub fn language(&self, packs: &DictionaryPacks) -> String {
+        self.dictionary_pack
+            .as_ref()
+            .and_then(|selection| packs.get(&selection.name))
+            .and_then(|pack| pack.language.clone())
+            .or_else(|| {
+                self.guild_settings
+                    .as_ref()
+                    .map(|settings| settings.default_language.clone())
+            })
+            .unwrap_or_else(|| locale::DEFAULT_LANGUAGE.to_string())
+    }
+
+    /// The kind of board the lobby's next game is dealt
+    pub fn board_key(&self, packs: &DictionaryPacks) -> BoardKey {
+        BoardKey::new(&self.language(packs), self.multiplier_layout)
+    }
+
+    /// Hold a seat for a user until `expires_at`, replacing any earlier reservation
+    pub fn reserve_seat(&mut self, user_id: i64, expires_at: Instant) {
+        self.reserved_seats.insert(user_id, expires_at);
+    }
+
+    /// Number of unexpired reservations for users who haven't joined yet
+    pub fn open_reservations(&self, now: Instant) -> usize {
+        self.reserved_seats
+            .iter()
+            .filter(|(user_id, expires_at)| {
+                **expires_at > now && !self.players.contains_key(user_id)
+            })
+            .count()
+    }
+
+    /// Check whether a user can take a seat without exceeding `max_players`
+    ///
+    /// Players already in the lobby and holders of an unexpired reservation are always
+    /// admitted; everyone else needs a seat not covered by someone else's reservation.
+    pub fn has_room_for(&self, user_id: i64, max_players: usize, now: Instant) -> bool {
+        if self.players.contains_key(&user_id) {
+            return true;
+        }
+        if self
+            .reserved_seats
+            .get(&user_id)
+            .is_some_and(|expires_at| *expires_at > now)
+        {
+            return true;
+        }
+        self.seated_player_count() + self.open_reservations(now) < max_players
+    }
+
+    /// Drop reservations that have expired or whose user has taken their seat
+    pub fn prune_reservations(&mut self, now: Instant) {
+        let players = &self.players;
+        self.reserved_seats
+            .retain(|user_id, expires_at| *expires_at > now && !players.contains_key(user_id));
+    }
+
+    /// Forward a copy of a broadcast to admin observers
+    ///
+    /// Uses `try_send` so a slow console can never hold up the players' broadcast.
+    pub fn notify_observers(&self, message: &ServerMessage) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let observed = ServerMessage::AdminObserved {
+            lobby_id: self.lobby_id.clone(),
+            message: Box::new(message.clone()),
+        };
+        for observer in self.observers.iter() {
+            let _ = observer.try_send(observed.clone());
+        }
+    }
+
+    /// Snapshot of the in-memory lobby for debugging desync reports
+    pub fn debug_dump(&self) -> serde_json::Value {
+        let mut players: Vec<serde_json::Value> = self
+            .players
+            .iter()
+            .map(|p| {
+                let disconnected_secs = match &p.connection_state {
+                    PlayerConnectionState::Connected => None,
+                    PlayerConnectionState::AwaitingReconnect { since } => {
+                        Some(since.elapsed().as_secs())
+                    }
+                };
+                serde_json::json!({
+                    "user_id": p.user_id,
+                    "username": p.username,
+                    "connected": p.is_connected(),
+                    "disconnected_secs": disconnected_secs,
+                    "send_queue_capacity": p.tx.capacity(),
+                })
+            })
+            .collect();
+        players.sort_by_key(|p| p["user_id"].as_i64());
+        let mut reserved_seats: Vec<i64> = self.reserved_seats.keys().copied().collect();
+        reserved_seats.sort_unstable();
+
+        serde_json::json!({
+            "lobby_id": self.lobby_id,
+            "lobby_type": self.lobby_type,
+            "lobby_code": self.lobby_code,
+            "channel_id": self.channel_id,
+            "guild_id": self.guild_id,
+            "host_id": self.host_id,
+            "active_game_id": self.active_game_id,
+            "game_starting": self.game_starting.load(Ordering::SeqCst),
+            "age_secs": self.created_at.elapsed().as_secs(),
+            "empty_secs": self.empty_since.map(|t| t.elapsed().as_secs()),
+            "reserved_seats": reserved_seats,
+            "observer_count": self.observers.len(),
+            "waiting_queue": self.waiting_queue.iter().map(|q| q.user_id).collect::<Vec<_>>(),
+            "dictionary_strictness": self.dictionary_strictness,
+            "dictionary_pack": self.dictionary_pack,
+            "multiplier_layout": self.multiplier_layout,
+            "letter_mode": self.letter_mode,
+            "board_theme": self.board_theme,
+            "rules": self.rules,
+            "activity_entries": self.activity.recent().len(),
+            "players": players,
+        })
+    }
+}
+
+/// Generate a short, readable lobby code of the given length
+fn generate_lobby_code(length: usize) -> String {
+    use rand::Rng;
+    let mut rng = rand::rng();
+    (0..length)
+        .map(|_| {
+            let idx = rng.random_range(0..LOBBY_CODE_CHARSET.len());
+            LOBBY_CODE_CHARSET[idx] as char
+        })
+        .collect()
+}
+
+/// Generate a lobby code that isn't in use and claim it in the code index.
+///
+/// The index entry is inserted atomically, so concurrent callers can never be handed
+/// the same code. Codes only become available again once the owning lobby is deleted
+/// and its index entry removed. Long codes are used when the short code space is
+/// crowded, or as a fallback if every short attempt collides. Returns None if no free
+/// code could be found.
+fn reserve_lobby_code(code_index: &DashMap<String, String>) -> Option<String> {
+    reserve_lobby_code_with(code_index, generate_lobby_code)
+}
+
+fn reserve_lobby_code_with(
+    code_index: &DashMap<String, String>,
+    mut generate: impl FnMut(usize) -> String,
+) -> Option<String> {
+    let lengths: &[usize] = if code_index.len() >= LOBBY_CODE_LONG_THRESHOLD {
+        &[LOBBY_CODE_LONG_LENGTH]
+    } else {
+        &[LOBBY_CODE_LENGTH, LOBBY_CODE_LONG_LENGTH]
+    };
+
+    for &length in lengths {
+        for _ in 0..LOBBY_CODE_MAX_ATTEMPTS {
+            let code = generate(length);
+            if let dashmap::Entry::Vacant(entry) = code_index.entry(code.clone()) {
+                entry.insert(format!("custom:{}", code));
+                return Some(code);
+            }
+            tracing::debug!("Lobby code {} already in use, retrying", code);
+        }
+    }
+
+    tracing::error!(
+        "Failed to find an unused lobby code ({} codes in use)",
+        code_index.len()
+    );
+    None
+}
+
+/// The lobby each user was in when their socket last dropped
+///
+/// Used to offer `RejoinAvailable` when they reconnect, so the client doesn't have
+/// to remember which lobby it was in.
+pub struct LastLobbies {
+    entries: DashMap<i64, (String, Instant)>,
+    ttl: Duration,
+}
+
+impl LastLobbies {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Remember the lobby a user just disconnected from
+    pub fn remember(&self, user_id: i64, lobby_id: &str, now: Instant) {
+        self.entries.insert(user_id, (lobby_id.to_string(), now));
+    }
+
+    /// Forget a user's last lobby (they joined or left a lobby themselves)
+    pub fn forget(&self, user_id: i64) {
+        self.entries.remove(&user_id);
+    }
+
+    /// The user's last lobby_id, if it was remembered within the TTL
+    pub fn get(&self, user_id: i64, now: Instant) -> Option<String> {
+        self.entries
+            .get(&user_id)
+            .filter(|entry| now.duration_since(entry.1) < self.ttl)
+            .map(|entry| entry.0.clone())
+    }
+
+    /// Drop entries older than the TTL
+    pub fn prune(&self, now: Instant) {
+        self.entries
+            .retain(|_, (_, remembered_at)| now.duration_since(*remembered_at) < self.ttl);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Connected users' equipped cosmetics, cached from `user_profiles` when they
+/// connect and updated by the profile API
+///
+/// Only users with something equipped have an entry.
+#[derive(Default)]
+pub struct PlayerProfiles {
+    entries: DashMap<i64, PlayerProfile>,
+}
+
+impl PlayerProfiles {
+    pub fn set(&self, user_id: i64, profile: PlayerProfile) {
+        if profile.is_empty() {
+            self.entries.remove(&user_id);
+        } else {
+            self.entries.insert(user_id, profile);
+        }
+    }
+
+    /// A user's cosmetics (empty if they haven't equipped any)
+    pub fn get(&self, user_id: i64) -> PlayerProfile {
+        self.entries
+            .get(&user_id)
+            .map(|profile| profile.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Each user's block list, cached from `user_blocks` when they connect
+///
+/// Only users who have blocked someone have an entry.
+#[derive(Default)]
+pub struct BlockLists {
+    entries: DashMap<i64, HashSet<i64>>,
+}
+
+impl BlockLists {
+    /// Replace a user's block list (e.g., freshly loaded from the database)
+    pub fn set(&self, blocker: i64, blocked: impl IntoIterator<Item = i64>) {
+        let blocked: HashSet<i64> = blocked.into_iter().collect();
+        if blocked.is_empty() {
+            self.entries.remove(&blocker);
+        } else {
+            self.entries.insert(blocker, blocked);
+        }
+    }
+
+    pub fn block(&self, blocker: i64, blocked: i64) {
+        self.entries.entry(blocker).or_default().insert(blocked);
+    }
+
+    pub fn unblock(&self, blocker: i64, blocked: i64) {
+        self.entries.remove_if_mut(&blocker, |_, list| {
+            list.remove(&blocked);
+            list.is_empty()
+        });
+    }
+
+    pub fn is_blocked(&self, blocker: i64, blocked: i64) -> bool {
+        self.entries
+            .get(&blocker)
+            .is_some_and(|list| list.contains(&blocked))
+    }
+
+    /// Everyone a user has blocked, sorted
+    pub fn blocked_by(&self, blocker: i64) -> Vec<i64> {
+        let mut blocked: Vec<i64> = self
+            .entries
+            .get(&blocker)
+            .map(|list| list.iter().copied().collect())
+            .unwrap_or_default();
+        blocked.sort_unstable();
+        blocked
+    }
+}
+
+/// Every open WebSocket connection, by user, for messages addressed to a user
+/// rather than a lobby (invites, presence, turn reminders)
+#[derive(Default)]
+pub struct UserConnections {
+    entries: DashMap<i64, HashMap<Uuid, ClientSender>>,
+    /// When each connected user last connected or sent a message, on any connection
+    last_active: DashMap<i64, Instant>,
+}
+
+impl UserConnections {
+    pub fn add(&self, user_id: i64, connection_id: Uuid, tx: ClientSender) {
+        self.entries
+            .entry(user_id)
+            .or_default()
+            .insert(connection_id, tx);
+        self.touch(user_id, Instant::now());
+    }
+
+    pub fn remove(&self, user_id: i64, connection_id: Uuid) {
+        self.entries.remove_if_mut(&user_id, |_, connections| {
+            connections.remove(&connection_id);
+            connections.is_empty()
+        });
+        if !self.is_connected(user_id) {
+            self.last_active.remove(&user_id);
+        }
+    }
+
+    /// Record that a user sent something
+    pub fn touch(&self, user_id: i64, now: Instant) {
+        self.last_active.insert(user_id, now);
+    }
+
+    /// How long since a user last sent anything; None if they aren't connected
+    pub fn idle_for(&self, user_id: i64, now: Instant) -> Option<Duration> {
+        self.last_active
+            .get(&user_id)
+            .map(|since| now.saturating_duration_since(*since))
+    }
+
+    pub fn is_connected(&self, user_id: i64) -> bool {
+        self.entries.contains_key(&user_id)
+    }
+
+    /// Send a message to every connection a user has open; false if none took it
+    pub fn send(&self, user_id: i64, message: &ServerMessage) -> bool {
+        let Some(connections) = self.entries.get(&user_id) else {
+            return false;
+        };
+        let shared = OutboundMessage::shared(message);
+        connections
+            .values()
+            .filter(|tx| tx.try_send(shared.clone()).is_ok())
+            .count()
+            > 0
+    }
+}
+
+/// Last check-in from a periodic background task
+#[derive(Debug, Clone, Copy)]
+pub struct TaskHeartbeat {
+    /// When the task last completed a tick
+    pub last_beat: Instant,
+    /// How often the task is expected to tick
+    pub interval: Duration,
+}
+
+impl TaskHeartbeat {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            last_beat: Instant::now(),
+            interval,
+        }
+    }
+
+    /// A task is stalled once it has missed several consecutive ticks
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        now.duration_since(self.last_beat) > self.interval * HEARTBEAT_MISSED_INTERVALS
+    }
+}
+
+/// Application state shared across all handlers
+pub struct AppState {
+    pub config: Config,
+    pub db: PgPool,
+    /// Loaded in the background after startup
+    pub dictionary: SharedDictionary,
+    /// Theme packs hosts can choose per lobby
+    pub dictionary_packs: DictionaryPacks,
+    /// Write-behind cache of running games, keyed by game_id
+    pub active_games: DashMap<Uuid, GameSession>,
+    /// The task that makes every change to each running game
+    pub game_actors: GameActors,
+    /// Boards generated ahead of time for new games
+    pub grid_pool: GridPool,
+    /// Community play counts behind the obscurity bonus
+    pub word_plays: WordPlays,
+    /// All lobbies keyed by lobby_id (e.g., "channel:123" or "custom:ABC123")
+    pub lobbies: DashMap<String, Lobby>,
+    /// Index from lobby_code to lobby_id for quick custom lobby lookup
+    pub lobby_code_index: DashMap<String, String>,
+    pub http_client: reqwest::Client,
+    /// Avatars fetched from Discord, served by `/api/avatars`
+    pub avatar_cache: AvatarCache,
+    /// Result card images served by `/api/games/{id}/result-card.png`
+    pub result_card_cache: ResultCardCache,
+    /// Responses of the public stats endpoints
+    pub public_stats_cache: PublicStatsCache,
+    /// Heartbeats from background tasks, keyed by task name (reported by health probes)
+    pub task_heartbeats: DashMap<&'static str, TaskHeartbeat>,
+    /// Open WebSocket connections per user and per IP
+    pub connection_limiter: ConnectionLimiter,
+    /// Per-user limit on CreateCustomLobby
+    pub lobby_creation_limiter: RateLimiter,
+    /// OAuth states and authorization codes already exchanged
+    pub oauth_replay_guard: ReplayGuard,
+    pub invite_limiter: RateLimiter,
+    /// Per-IP limit on the public stats endpoints
+    pub public_stats_limiter: RateLimiter<IpAddr>,
+    /// Each user's lobby at their last disconnect, for rejoin offers
+    pub last_lobbies: LastLobbies,
+    /// Block lists of connected users, for filtering social messages
+    pub block_lists: BlockLists,
+    /// Cosmetics of connected users, included in player broadcasts
+    pub player_profiles: PlayerProfiles,
+    /// Open connections by user, for invites and presence updates
+    pub user_connections: UserConnections,
+    /// Which lobby each user is in, and who follows their presence
+    pub presence: PresenceIndex,
+}
+
+impl AppState {
+    /// Record that a background task completed a tick
+    pub fn record_heartbeat(&self, task: &'static str, interval: Duration) {
+        self.task_heartbeats
+            .entry(task)
+            .and_modify(|beat| beat.last_beat = Instant::now())
+            .or_insert_with(|| TaskHeartbeat::new(interval));
+    }
+
+    /// A `RejoinAvailable` offer for a reconnecting user, if their last lobby still exists
+    pub fn rejoin_offer(&self, user_id: i64) -> Option<ServerMessage> {
+        let lobby_id = self.last_lobbies.get(user_id, Instant::now())?;
+        let lobby = self.lobbies.get(&lobby_id)?;
+        Some(ServerMessage::RejoinAvailable {
+            lobby_id,
+            lobby_type: lobby.lobby_type.clone(),
+            lobby_code: lobby.lobby_code.clone(),
+        })
+    }
+
+    /// Every table of a channel lobby, in table order
+    pub fn channel_tables(&self, channel_id: &str) -> Vec<TableInfo> {
+        let mut tables: Vec<TableInfo> = self
+            .lobbies
+            .iter()
+            .filter(|lobby| lobby.channel_id.as_deref() == Some(channel_id))
+            .map(|lobby| lobby.table_info())
+            .collect();
+        tables.sort_by_key(|table| table.table_number);
+        tables
+    }
+
+    /// The table of a channel the user is already seated at, if any
+    pub fn find_player_table(&self, channel_id: &str, user_id: i64) -> Option<String> {
+        self.lobbies
+            .iter()
+            .find(|lobby| {
+                lobby.channel_id.as_deref() == Some(channel_id)
+                    && lobby.players.contains_key(&user_id)
+            })
+            .map(|lobby| lobby.lobby_id.clone())
+    }
+
+    /// Open the lowest free table number in a channel, copying the channel's guild
+    /// settings. Returns the new table's lobby_id.
+    pub fn create_table(
+        &self,
+        channel_id: &str,
+        guild_id: Option<String>,
+        guild_settings: Option<GuildSettings>,
+    ) -> Result<String, ApiError> {
+        for table_number in 2..=MAX_TABLES_PER_CHANNEL {
+            let lobby_id = Lobby::table_lobby_id(channel_id, table_number);
+            if let dashmap::Entry::Vacant(entry) = self.lobbies.entry(lobby_id.clone()) {
+                let mut lobby = Lobby::new_table(channel_id.to_string(), guild_id, table_number);
+                lobby.guild_settings = guild_settings;
+                entry.insert(lobby);
+                tracing::info!("Created table {} in channel {}", table_number, channel_id);
+                return Ok(lobby_id);
+            }
+        }
+        Err(ApiError::TooManyTables)
+    }
+
+    /// Find a custom lobby's lobby_id by its code
+    pub fn find_lobby_by_code(&self, lobby_code: &str) -> Option<String> {
+        // Normalize the code (uppercase, trim)
+        let normalized_code = lobby_code.trim().to_uppercase();
+        self.lobby_code_index
+            .get(&normalized_code)
+            .map(|r| r.value().clone())
+    }
+
+    /// Hold a seat in a lobby for a user for `ttl`
+    ///
+    /// Fails with `LobbyFull` if every free seat is already taken or reserved.
+    pub fn reserve_seat(
+        &self,
+        lobby_id: &str,
+        user_id: i64,
+        ttl: Duration,
+    ) -> Result<(), ApiError> {
+        let mut lobby = self
+            .lobbies
+            .get_mut(lobby_id)
+            .ok_or(ApiError::LobbyNotFound)?;
+        let now = Instant::now();
+        if !lobby.has_room_for(user_id, self.config.game.max_players, now) {
+            return Err(ApiError::LobbyFull);
+        }
+        lobby.reserve_seat(user_id, now + ttl);
+        Ok(())
+    }
+}
+
+/// Start the server: load config, the dictionary and the database, spawn the
+/// background tasks and serve until the listener fails
+pub async fn run() -> Result<()> {
+    // Initialize tracing
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "spell_cast_backend=debug,tower_http=debug".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    // Offline commands run instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import-dictionary") {
+        return dictionary::import::run(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("simulate") {
+        return game::simulate::run(&args[1..]).await;
+    }
+    let migrate_only = args.iter().any(|arg| arg == "--migrate-only");
+
+    tracing::info!("Starting Spell Cast backend server...");
+
+    // Load configuration
+    let config = Config::from_env()?;
+    tracing::info!("Configuration loaded");
+
+    // Run migrations; with several replicas starting at once, one applies them
+    // and the rest wait
+    db::run_migrations(config.database_url()).await?;
+    tracing::info!("Database migrations completed");
+    if migrate_only {
+        return Ok(());
+    }
+
+    // Connect to database
+    db::timing::set_slow_query_threshold(Duration::from_millis(config.database.slow_query_ms));
+    let db = db::create_pool(config.database_url(), config.database.max_connections).await?;
+    tracing::info!("Connected to database");
+    if config.database.explain_queries {
+        db::plans::check_query_plans(&db).await;
+    }
+
+    // Theme packs are optional; without the directory hosts see the embedded
+    // packs (embedded-dictionary feature) or an empty list
+    let dictionary_packs = match DictionaryPacks::load_dir(&config.game.dictionary_packs_dir).await
+    {
+        Ok(packs) => packs,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to load dictionary packs from {}: {}",
+                config.game.dictionary_packs_dir,
+                e
+            );
+            default_dictionary_packs()
+        }
+    };
+
+    // Create shared HTTP client for reusing connections
+    let http_client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    tracing::info!("HTTP client initialized");
+
+    // Create application state
+    let state = Arc::new(AppState {
+        config: config.clone(),
+        db,
+        dictionary: SharedDictionary::default(),
+        dictionary_packs,
+        active_games: DashMap::new(),
+        game_actors: GameActors::new(),
+        grid_pool: GridPool::new(config.game.grid_pool_size),
+        word_plays: WordPlays::new(),
+        lobbies: DashMap::new(),
+        lobby_code_index: DashMap::new(),
+        http_client,
+        avatar_cache: AvatarCache::new(
+            config.server.avatar_cache_size,
+            Duration::from_secs(config.server.avatar_cache_ttl_secs),
+        ),
+        result_card_cache: ResultCardCache::new(RESULT_CARD_CACHE_SIZE),
+        public_stats_cache: PublicStatsCache::new(
+            PUBLIC_STATS_CACHE_SIZE,
+            Duration::from_secs(config.server.public_stats_cache_secs),
+        ),
+        task_heartbeats: DashMap::new(),
+        connection_limiter: ConnectionLimiter::new(
+            config.limits.max_connections_per_user,
+            config.limits.max_connections_per_ip,
+        ),
+        lobby_creation_limiter: RateLimiter::new(
+            config.limits.lobby_creations_per_minute,
+            Duration::from_secs(60),
+        ),
+        invite_limiter: RateLimiter::new(config.limits.invites_per_minute, Duration::from_secs(60)),
+        public_stats_limiter: RateLimiter::new(
+            config.limits.public_stats_per_minute,
+            Duration::from_secs(60),
+        ),
+        oauth_replay_guard: ReplayGuard::new(
+            auth::OAUTH_STATE_TTL.to_std().expect("positive state TTL"),
+        ),
+        last_lobbies: LastLobbies::new(LAST_LOBBY_TTL),
+        block_lists: BlockLists::default(),
+        player_profiles: PlayerProfiles::default(),
+        user_connections: UserConnections::default(),
+        presence: PresenceIndex::default(),
+    });
+
+    // Load the dictionary in the background so the listener binds right away;
+    // readiness reports it as loading and word submissions are rejected until then
+    let dictionary_state = state.clone();
+    tokio::spawn(async move {
+        let dictionary = load_dictionary(&dictionary_state.config.game).await;
+        dictionary_state.dictionary.set(dictionary);
+    });
+
+    // Spawn background task to clean up stale players and empty lobbies
+    let cleanup_state = state.clone();
+    tokio::spawn(async move {
+        lobby_cleanup_task(cleanup_state).await;
+    });
+
+    // Spawn background task to start scheduled games when their time comes
+    let scheduler_state = state.clone();
+    tokio::spawn(async move {
+        websocket::schedule::game_scheduler_task(scheduler_state).await;
+    });
+
+    // Spawn background task to keep idle lobby screens live (countdowns, ready, typing)
+    let heartbeat_state = state.clone();
+    tokio::spawn(async move {
+        lobby_heartbeat_task(heartbeat_state).await;
+    });
+
+    // Spawn background task to flush cached game state to the database
+    let flush_state = state.clone();
+    tokio::spawn(async move {
+        game::session::game_flush_task(flush_state).await;
+    });
+
+    // Spawn background task to close finished leaderboard seasons and open the next
+    let season_state = state.clone();
+    tokio::spawn(async move {
+        seasons::season_rollover_task(season_state).await;
+    });
+
+    // Spawn background task to announce guild game nights and open their lobbies
+    let game_night_state = state.clone();
+    tokio::spawn(async move {
+        game_nights::game_night_task(game_night_state).await;
+    });
+
+    // Spawn background task to recompute the ops dashboard aggregates
+    let analytics_state = state.clone();
+    tokio::spawn(async move {
+        analytics::analytics_refresh_task(analytics_state).await;
+    });
+
+    // Spawn background task to delete accounts whose deletion grace period is over
+    let deletion_state = state.clone();
+    tokio::spawn(async move {
+        accounts::account_deletion_task(deletion_state).await;
+    });
+
+    // Spawn background task to keep pre-generated boards ready for new games
+    let grid_pool_state = state.clone();
+    tokio::spawn(async move {
+        game::grid_pool::grid_pool_task(grid_pool_state).await;
+    });
+
+    // Spawn background task to reload how often each word has been played
+    let word_plays_state = state.clone();
+    tokio::spawn(async move {
+        game::word_plays::word_plays_task(word_plays_state).await;
+    });
+
+    // Spawn background task to keep an actor running for every active game
+    let actor_state = state.clone();
+    tokio::spawn(async move {
+        websocket::game_actor::game_actor_task(actor_state).await;
+    });
+
+    // Spawn background task to remind idle players when it's their turn
+    let reminder_state = state.clone();
+    tokio::spawn(async move {
+        websocket::reminders::turn_reminder_task(reminder_state).await;
+    });
+
+    // Configure CORS (the WebSocket upgrade checks the same allowlist itself)
+    let allow_origin = if config.server.allow_any_origin {
+        tracing::warn!("CORS_ALLOW_ANY_ORIGIN is set; origin checks are disabled");
+        AllowOrigin::any()
+    } else {
+        let server_config = config.server.clone();
+        // The public stats endpoints are meant to be embedded on other sites
+        AllowOrigin::predicate(move |origin, parts| {
+            public_stats::is_public_stats(parts.uri.path())
+                || origin
+                    .to_str()
+                    .is_ok_and(|origin| server_config.is_origin_allowed(origin))
+        })
+    };
+    let cors = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    // Serve frontend static files
+    let frontend_service = frontend::service(config.server.frontend_dir.as_deref());
+
+    // Build router
+    let app = Router::new()
+        // WebSocket endpoint
+        .route("/ws", get(websocket::handle_websocket))
+        // API routes
+        .merge(routes::create_routes(&state))
+        // Serve frontend at /play and static assets at root
+        //.nest_service("/play", frontend_service.clone())
+        .fallback_service(frontend_service);
+    let app = security_headers::apply(app, &config.server.security_headers)
+        .layer(cors)
+        .layer(TraceLayer::new_for_http())
+        .with_state(state);
+
+    // Start server
+    let addr = config.server_addr();
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    tracing::info!("Server listening on {}", addr);
+    tracing::info!("WebSocket endpoint: ws://{}/ws", addr);
+    tracing::info!("Health check: http://{}/health", addr);
+    tracing::info!("Readiness probe: http://{}/health/ready", addr);
+    tracing::info!("Game frontend: http://{}/", addr);
+
+    // Connection info is needed for per-IP connection limits
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "embedded-dictionary"))]
+fn default_dictionary_packs() -> DictionaryPacks {
+    DictionaryPacks::default()
+}
+
+#[cfg(feature = "embedded-dictionary")]
+fn default_dictionary_packs() -> DictionaryPacks {
+    dictionary::embedded::packs()
+}
+
+/// Load the word list, falling back to the embedded one (with the
+/// `embedded-dictionary` feature) or an empty dictionary if it can't be read
+async fn load_dictionary(config: &GameConfig) -> Dictionary {
+    match Dictionary::load(
+        &config.dictionary_path,
+        config.extended_dictionary_path.as_ref(),
+        config.dictionary_backend,
+    )
+    .await
+    {
+        Ok(dict) => {
+            tracing::info!("Dictionary loaded successfully");
+            dict
+        }
+        Err(e) => {
+            #[cfg(feature = "embedded-dictionary")]
+            {
+                tracing::info!(
+                    "No word list at {} ({}); using the embedded dictionary",
+                    config.dictionary_path,
+                    e
+                );
+                match dictionary::embedded::dictionary(config.dictionary_backend) {
+                    Ok(dict) => return dict,
+                    Err(e) => tracing::warn!("Failed to build embedded dictionary: {}", e),
+                }
+            }
+            tracing::warn!(
+                "Failed to load dictionary: {}. Using empty dictionary for now.",
+                e
+            );
+            tracing::warn!(
+                "Download a word list to {} for full functionality",
+                config.dictionary_path
+            );
+            Dictionary::empty()
+        }
+    }
+}
+
+/// Background task that periodically cleans up stale disconnected players and empty lobbies
+async fn lobby_cleanup_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(LOBBY_CLEANUP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("lobby_cleanup", LOBBY_CLEANUP_INTERVAL);
+        state.lobby_creation_limiter.prune();
+        state.invite_limiter.prune();
+        state.public_stats_limiter.prune();
+        state.public_stats_cache.prune(Instant::now());
+        state.oauth_replay_guard.prune(Instant::now());
+        state.last_lobbies.prune(Instant::now());
+
+        let now = Instant::now();
+        let mut lobbies_to_remove = Vec::new();
+        let mut players_to_remove: Vec<(String, i64)> = Vec::new();
+
+        // Release seat reservations that were never claimed
+        for mut lobby in state.lobbies.iter_mut() {
+            lobby.prune_reservations(now);
+        }
+
+        // Scan all lobbies
+        for lobby_ref in state.lobbies.iter() {
+            let lobby_id = lobby_ref.key().clone();
+            let lobby = lobby_ref.value();
+
+            // Find players that have exceeded the grace period
+            for player_ref in lobby.players.iter() {
+                if let PlayerConnectionState::AwaitingReconnect { since } =
+                    &player_ref.connection_state
+                {
+                    if now.duration_since(*since) > PLAYER_DISCONNECT_GRACE_PERIOD {
+                        players_to_remove.push((lobby_id.clone(), player_ref.user_id));
+                    }
+                }
+            }
+
+            // Check if lobby should be removed (empty beyond grace period); one
+            // with a game scheduled is kept for it
+            if let (Some(empty_since), None) = (lobby.empty_since, &lobby.scheduled_start) {
+                if now.duration_since(empty_since) > LOBBY_EMPTY_GRACE_PERIOD {
+                    lobbies_to_remove.push(lobby_id.clone());
+                }
+            }
+        }
+
+        // Remove stale players
+        for (lobby_id, user_id) in players_to_remove {
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                if let Some((_, player)) = lobby.players.remove(&user_id) {
+                    lobby.record_activity(Activity::PlayerLeft {
+                        user_id,
+                        username: player.username,
+                    });
+                }
+                // Broadcast updated player list to all connected clients
+                // Note: More efficient would be to batch these broadcasts per lobby,
+                // but the complexity trade-off is acceptable for now
+                drop(lobby);
+                websocket::broadcast_lobby_player_list(&state, &lobby_id).await;
+                websocket::promote_queued_players(&state, &lobby_id).await;
+                tracing::info!(
+                    "Removed stale disconnected player {} from lobby {} (grace period expired)",
+                    user_id,
+                    lobby_id
+                );
+            }
+        }
+
+        // Remove stale lobbies
+        for lobby_id in lobbies_to_remove {
+            if let Some((_, lobby)) = state.lobbies.remove(&lobby_id) {
+                // Release the code now that its lobby is gone
+                if let Some(code) = lobby.lobby_code {
+                    state
+                        .lobby_code_index
+                        .remove_if(&code, |_, owner| owner == &lobby_id);
+                    if let Err(e) = db::queries::delete_stale_lobby_invites(&state.db, &code).await
+                    {
+                        tracing::warn!("Failed to delete invites to lobby {}: {}", code, e);
+                    }
+                }
+                tracing::info!("Removed empty lobby {} (grace period expired)", lobby_id);
+            }
+        }
+    }
+}
+
+/// Background task that periodically sends `LobbyHeartbeat` to lobbies without a game
+async fn lobby_heartbeat_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(LOBBY_HEARTBEAT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("lobby_heartbeat", LOBBY_HEARTBEAT_INTERVAL);
+
+        let now = Instant::now();
+        for lobby in state.lobbies.iter() {
+            if lobby.active_game_id.is_none() && lobby.connected_player_count() > 0 {
+                websocket::broadcast_lobby_heartbeat(&lobby, &state.block_lists, now);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dictionary::packs::{DictionaryPack, PackMode};
+
+    #[test]
+    fn test_generate_lobby_code_length() {
+        // Generate multiple codes and verify they are always 6 characters
+        for _ in 0..100 {
+            let code = generate_lobby_code(LOBBY_CODE_LENGTH);
+            assert_eq!(
+                code.len(),
+                LOBBY_CODE_LENGTH,
+                "Generated lobby code '{}' should be exactly {} characters",
+                code,
+                LOBBY_CODE_LENGTH
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_lobby_code_charset() {
+        // Generate multiple codes and verify all characters are from allowed charset
+        for _ in 0..100 {
+            let code = generate_lobby_code(LOBBY_CODE_LENGTH);
+            for c in code.chars() {
+                assert!(
+                    LOBBY_CODE_CHARSET.contains(&(c as u8)),
+                    "Character '{}' in code '{}' is not in allowed charset",
+                    c,
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_lobby_code_uppercase() {
+        // Generate multiple codes and verify all alphabetic characters are uppercase
+        for _ in 0..100 {
+            let code = generate_lobby_code(LOBBY_CODE_LENGTH);
+            for c in code.chars() {
+                if c.is_alphabetic() {
+                    assert!(
+                        c.is_uppercase(),
+                        "Character '{}' in code '{}' should be uppercase",
+                        c,
+                        code
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reserve_lobby_code_claims_index_entry() {
+        // A reserved code should immediately be visible in the index
+        let index = DashMap::new();
+        let code = reserve_lobby_code(&index).expect("empty index should have free codes");
+
+        assert_eq!(code.len(), LOBBY_CODE_LENGTH);
+        assert_eq!(
+            index.get(&code).map(|id| id.clone()),
+            Some(format!("custom:{}", code)),
+            "Reserved code should map to its lobby id"
+        );
+    }
+
+    #[test]
+    fn test_reserve_lobby_code_retries_on_collision() {
+        // Simulate the generator producing an in-use code before a free one
+        let index = DashMap::new();
+        index.insert("AAAAAA".to_string(), "custom:AAAAAA".to_string());
+
+        let mut candidates = vec!["BBBBBB", "AAAAAA"];
+        let code = reserve_lobby_code_with(&index, |_| candidates.pop().unwrap().to_string());
+
+        assert_eq!(code.as_deref(), Some("BBBBBB"));
+        assert_eq!(
+            index.get("AAAAAA").map(|id| id.clone()).as_deref(),
+            Some("custom:AAAAAA"),
+            "The existing lobby's code must not be overwritten"
+        );
+    }
+
+    #[test]
+    fn test_reserve_lobby_code_falls_back_to_long_codes() {
+        // If every short code collides, a longer code should be used instead
+        let index = DashMap::new();
+        index.insert("AAAAAA".to_string(), "custom:AAAAAA".to_string());
+
+        let code = reserve_lobby_code_with(&index, |length| {
+            if length == LOBBY_CODE_LENGTH {
+                "AAAAAA".to_string()
+            } else {
+                "BBBBBBBB".to_string()
+            }
+        });
+        assert_eq!(code.as_deref(), Some("BBBBBBBB"));
+    }
+
+    #[test]
+    fn test_reserve_lobby_code_gives_up_when_exhausted() {
+        let index = DashMap::new();
+        index.insert("AAAAAA".to_string(), "custom:AAAAAA".to_string());
+
+        let code = reserve_lobby_code_with(&index, |_| "AAAAAA".to_string());
+        assert!(code.is_none(), "Should give up rather than reuse a code");
+    }
+
+    // Helper function to create a test player
+    fn create_test_player(user_id: i64, connection_state: PlayerConnectionState) -> LobbyPlayer {
+        let (tx, _rx) = ClientSender::channel(1, Duration::from_secs(10));
+        LobbyPlayer {
+            user_id,
+            username: format!("TestUser{}", user_id),
+            avatar_url: None,
+            tx,
+            connection_state,
+            ready: false,
+            typing_since: None,
+            muted: HashSet::new(),
+            spectator: false,
+        }
+    }
+
+    #[test]
+    fn test_player_is_connected_when_connected() {
+        // Verify that a player with Connected state returns true for is_connected()
+        let player = create_test_player(1, PlayerConnectionState::Connected);
+        assert!(
+            player.is_connected(),
+            "Player with Connected state should return true for is_connected()"
+        );
+    }
+
+    #[test]
+    fn test_player_is_not_connected_when_awaiting_reconnect() {
+        // Verify that a player awaiting reconnection returns false for is_connected()
+        let player = create_test_player(
+            1,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        assert!(
+            !player.is_connected(),
+            "Player awaiting reconnection should return false for is_connected()"
+        );
+    }
+
+    #[test]
+    fn test_player_is_visible_when_connected() {
+        // Verify that a connected player is visible in the lobby
+        let player = create_test_player(1, PlayerConnectionState::Connected);
+        assert!(
+            player.is_visible(),
+            "Connected player should be visible in the lobby"
+        );
+    }
+
+    #[test]
+    fn test_player_is_visible_when_awaiting_reconnect() {
+        // Verify that a player awaiting reconnection is still visible during grace period
+        // This tests the key feature: disconnected players remain visible during grace period
+        let player = create_test_player(
+            1,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        assert!(
+            player.is_visible(),
+            "Player awaiting reconnection should remain visible during grace period"
+        );
+    }
+
+    #[test]
+    fn test_lobby_connected_player_count_only_counts_connected() {
+        // Verify that connected_player_count only counts actively connected players
+        let lobby = Lobby::new_channel("test_channel".to_string(), Some("test_guild".to_string()));
+
+        // Add a connected player
+        let connected_player = create_test_player(1, PlayerConnectionState::Connected);
+        lobby.players.insert(1, connected_player);
+
+        // Add a player awaiting reconnection
+        let disconnected_player = create_test_player(
+            2,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        lobby.players.insert(2, disconnected_player);
+
+        assert_eq!(
+            lobby.connected_player_count(),
+            1,
+            "Only the connected player should be counted"
+        );
+        assert!(
+            lobby.has_any_players(),
+            "Lobby should report having players (both connected and disconnected)"
+        );
+    }
+
+    #[test]
+    fn test_player_reconnection_updates_connection_state() {
+        // Verify that when a player reconnects, their connection state is updated
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // Initially add a player awaiting reconnection
+        let disconnected_player = create_test_player(
+            1,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        lobby.players.insert(1, disconnected_player);
+
+        // Verify player is not connected initially
+        {
+            let player = lobby.players.get(&1).unwrap();
+            assert!(
+                !player.is_connected(),
+                "Player should not be connected initially"
+            );
+        }
+
+        // Simulate reconnection by updating the player's connection state
+        {
+            let mut player = lobby.players.get_mut(&1).unwrap();
+            player.connection_state = PlayerConnectionState::Connected;
+        }
+
+        // Verify player is now connected
+        {
+            let player = lobby.players.get(&1).unwrap();
+            assert!(
+                player.is_connected(),
+                "Player should be connected after reconnection"
+            );
+        }
+
+        // Verify connected count is now 1
+        assert_eq!(
+            lobby.connected_player_count(),
+            1,
+            "Connected player count should be 1 after reconnection"
+        );
+    }
+
+    #[test]
+    fn test_all_players_visible_regardless_of_connection_state() {
+        // Verify that all players in a lobby are visible, regardless of connection state
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // Add multiple players with different states
+        let connected1 = create_test_player(1, PlayerConnectionState::Connected);
+        let connected2 = create_test_player(2, PlayerConnectionState::Connected);
+        let disconnected1 = create_test_player(
+            3,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        let disconnected2 = create_test_player(
+            4,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+
+        lobby.players.insert(1, connected1);
+        lobby.players.insert(2, connected2);
+        lobby.players.insert(3, disconnected1);
+        lobby.players.insert(4, disconnected2);
+
+        // All players should be visible - since is_visible() returns true for all players,
+        // the visible count equals the total player count
+        let visible_count = lobby.players.len();
+
+        assert_eq!(
+            visible_count, 4,
+            "All 4 players should be visible regardless of connection state"
+        );
+
+        // Verify connected count is only 2
+        assert_eq!(
+            lobby.connected_player_count(),
+            2,
+            "Only 2 players should be counted as connected"
+        );
+    }
+
+    #[test]
+    fn test_lobby_empty_since_tracks_when_all_players_disconnected() {
+        // Verify that empty_since can be used to track when all players disconnected
+        let mut lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // Initially no empty_since
+        assert!(
+            lobby.empty_since.is_none(),
+            "New lobby should not have empty_since set"
+        );
+
+        // Add a player who disconnects
+        let player = create_test_player(
+            1,
+            PlayerConnectionState::AwaitingReconnect {
+                since: Instant::now(),
+            },
+        );
+        lobby.players.insert(1, player);
+
+        // Simulate marking lobby as having no active connections
+        let all_awaiting = lobby.players.iter().all(|p| !p.is_connected());
+        assert!(all_awaiting, "All players should be awaiting reconnection");
+
+        if all_awaiting {
+            lobby.empty_since = Some(Instant::now());
+        }
+
+        assert!(
+            lobby.empty_since.is_some(),
+            "Lobby should have empty_since set when all players are disconnected"
+        );
+    }
+
+    // ==========================================================================
+    // Tests for try_start_game race condition prevention
+    // ==========================================================================
+
+    #[test]
+    fn test_try_start_game_succeeds_when_no_active_game() {
+        // Verify that try_start_game succeeds when there's no active game
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        assert!(
+            lobby.try_start_game(),
+            "try_start_game should succeed when no game is active"
+        );
+
+        // Verify the game_starting flag is now set
+        assert!(
+            lobby.game_starting.load(Ordering::SeqCst),
+            "game_starting flag should be set after successful try_start_game"
+        );
+    }
+
+    #[test]
+    fn test_try_start_game_fails_when_game_already_starting() {
+        // Verify that concurrent try_start_game calls are rejected
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // First call succeeds
+        assert!(
+            lobby.try_start_game(),
+            "First try_start_game should succeed"
+        );
+
+        // Second call fails because game_starting is already set
+        assert!(
+            !lobby.try_start_game(),
+            "Second try_start_game should fail - another start is in progress"
+        );
+    }
+
+    #[test]
+    fn test_try_start_game_fails_when_active_game_exists() {
+        // Verify that try_start_game fails when there's an active game
+        let mut lobby = Lobby::new_channel("test_channel".to_string(), None);
+        lobby.active_game_id = Some(Uuid::new_v4());
+
+        assert!(
+            !lobby.try_start_game(),
+            "try_start_game should fail when active game exists"
+        );
+    }
+
+    #[test]
+    fn test_clear_game_starting_resets_flag() {
+        // Verify that clear_game_starting properly resets the flag
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // Set the game_starting flag
+        lobby.try_start_game();
+        assert!(
+            lobby.game_starting.load(Ordering::SeqCst),
+            "game_starting should be set"
+        );
+
+        // Clear it
+        lobby.clear_game_starting();
+        assert!(
+            !lobby.game_starting.load(Ordering::SeqCst),
+            "game_starting should be cleared"
+        );
+
+        // Now try_start_game should succeed again
+        assert!(
+            lobby.try_start_game(),
+            "try_start_game should succeed after clearing the flag"
+        );
+    }
+
+    #[test]
+    fn test_try_start_game_race_condition_prevention() {
+        // Simulate the race condition scenario described in the PR feedback:
+        // Two concurrent start requests should not both succeed
+        use std::sync::atomic::AtomicUsize;
+        use std::thread;
+
+        let lobby = std::sync::Arc::new(Lobby::new_channel("test_channel".to_string(), None));
+        let successful_starts = std::sync::Arc::new(AtomicUsize::new(0));
+
+        let mut handles = vec![];
+
+        // Spawn multiple threads trying to start the game simultaneously
+        for _ in 0..10 {
+            let lobby_clone = lobby.clone();
+            let counter_clone = successful_starts.clone();
+            handles.push(thread::spawn(move || {
+                if lobby_clone.try_start_game() {
+                    counter_clone.fetch_add(1, Ordering::SeqCst);
+                }
+            }));
+        }
+
+        // Wait for all threads to complete
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Only one thread should have succeeded
+        assert_eq!(
+            successful_starts.load(Ordering::SeqCst),
+            1,
+            "Only one concurrent start request should succeed"
+        );
+    }
+
+    #[test]
+    fn test_lobby_is_host_validation() {
+        // Verify that is_host correctly identifies the lobby host
+        let mut lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // No host initially
+        assert!(!lobby.is_host(1), "No user should be host initially");
+        assert!(!lobby.is_host(2), "No user should be host initially");
+
+        // Set host
+        lobby.host_id = Some(1);
+
+        assert!(lobby.is_host(1), "User 1 should be the host");
+        assert!(!lobby.is_host(2), "User 2 should not be the host");
+    }
+
+    #[test]
+    fn test_lobby_player_count_validation() {
+        // Verify player count validation scenarios
+        let lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // Empty lobby
+        assert_eq!(
+            lobby.connected_player_count(),
+            0,
+            "Empty lobby should have 0 connected players"
+        );
+
+        // Add 1 player
+        let player1 = create_test_player(1, PlayerConnectionState::Connected);
+        lobby.players.insert(1, player1);
+        assert_eq!(
+            lobby.connected_player_count(),
+            1,
+            "Lobby with 1 connected player"
+        );
+
+        // Add more players up to 6
+        for i in 2..=6 {
+            let player = create_test_player(i, PlayerConnectionState::Connected);
+            lobby.players.insert(i, player);
+        }
+        assert_eq!(
+            lobby.connected_player_count(),
+            6,
+            "Lobby should have 6 connected players"
+        );
+
+        // Add a 7th player
+        let player7 = create_test_player(7, PlayerConnectionState::Connected);
+        lobby.players.insert(7, player7);
+        assert_eq!(
+            lobby.connected_player_count(),
+            7,
+            "Lobby should have 7 connected players (exceeds max)"
+        );
+    }
+
+    #[test]
+    fn test_task_heartbeat_fresh_is_not_stalled() {
+        let beat = TaskHeartbeat::new(Duration::from_secs(15));
+        assert!(
+            !beat.is_stalled(Instant::now()),
+            "A heartbeat that just fired should not be stalled"
+        );
+    }
+
+    #[test]
+    fn test_task_heartbeat_stalls_after_missed_intervals() {
+        let beat = TaskHeartbeat::new(Duration::from_secs(15));
+
+        // Two missed ticks is still within tolerance
+        let later = beat.last_beat + Duration::from_secs(30);
+        assert!(
+            !beat.is_stalled(later),
+            "Two missed ticks should be tolerated"
+        );
+
+        // Past the allowed number of missed ticks
+        let much_later = beat.last_beat + Duration::from_secs(46);
+        assert!(
+            beat.is_stalled(much_later),
+            "Heartbeat should be stalled after {} missed intervals",
+            HEARTBEAT_MISSED_INTERVALS
+        );
+    }
+
+    #[test]
+    fn test_lobby_has_active_game() {
+        // Verify has_active_game correctly detects active games
+        let mut lobby = Lobby::new_channel("test_channel".to_string(), None);
+
+        // No active game initially
+        assert!(!lobby.has_active_game(), "No active game initially");
+
+        // Set active game
+        lobby.active_game_id = Some(Uuid::new_v4());
+        assert!(
+            lobby.has_active_game(),
+            "Should detect active game after setting"
+        );
+    }
+
+    #[test]
+    fn test_lobby_debug_dump_reports_player_states() {
+        // Verify the debug dump reflects connection state and host for each player
+        let mut lobby = Lobby::new_channel("test_channel".to_string(), None);
+        lobby.host_id = Some(1);
+        lobby
+            .players
+            .insert(1, create_test_player(1, PlayerConnectionState::Connected));
+        lobby.players.insert(
+            2,
+            create_test_player(
+                2,
+                PlayerConnectionState::AwaitingReconnect {
+                    since: Instant::now(),
+                },
+            ),
+        );
+
+        let dump = lobby.debug_dump();
+        assert_eq!(dump["lobby_id"], "channel:test_channel");
+        assert_eq!(dump["host_id"], 1);
+        assert_eq!(dump["players"][0]["connected"], true);
+        assert_eq!(dump["players"][1]["connected"], false);
+        assert!(
+            dump["players"][1]["disconnected_secs"].is_u64(),
+            "Disconnected players should report how long they've been gone"
+        );
+    }
+
+    #[test]
+    fn test_reserved_seats_count_toward_capacity() {
+        // A lobby of 3 with 2 players and 1 reservation is full to everyone but the invitee
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+        for user_id in [1, 2] {
+            lobby.players.insert(
+                user_id,
+                create_test_player(user_id, PlayerConnectionState::Connected),
+            );
+        }
+        lobby.reserve_seat(3, now + Duration::from_secs(60));
+
+        assert_eq!(lobby.open_reservations(now), 1);
+        assert!(
+            !lobby.has_room_for(4, 3, now),
+            "Reserved seats should not be available to other players"
+        );
+        assert!(
+            lobby.has_room_for(3, 3, now),
+            "The user holding the reservation should be admitted"
+        );
+        assert!(
+            lobby.has_room_for(1, 3, now),
+            "Players already in the lobby should always be admitted (reconnect)"
+        );
+    }
+
+    #[test]
+    fn test_spectators_take_no_seat() {
+        let lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+        let mut player = create_test_player(1, PlayerConnectionState::Connected);
+        player.ready = true;
+        lobby.players.insert(1, player);
+        let mut spectator = create_test_player(2, PlayerConnectionState::Connected);
+        spectator.ready = true;
+        spectator.spectator = true;
+        lobby.players.insert(2, spectator);
+
+        assert_eq!(lobby.seated_player_count(), 1);
+        assert_eq!(lobby.connected_player_count(), 1);
+        assert_eq!(lobby.ready_player_count(), 1);
+        assert!(
+            lobby.has_room_for(3, 2, now),
+            "The spectator leaves the second seat free"
+        );
+    }
+
+    fn create_queued_player(user_id: i64) -> QueuedPlayer {
+        let (tx, _rx) = ClientSender::channel(1, Duration::from_secs(10));
+        QueuedPlayer {
+            user_id,
+            username: format!("TestUser{}", user_id),
+            avatar_url: None,
+            tx,
+        }
+    }
+
+    #[test]
+    fn test_waiting_queue_positions() {
+        let mut lobby = Lobby::new_channel("123".to_string(), None);
+        assert_eq!(lobby.enqueue(create_queued_player(7)), 1);
+        assert_eq!(lobby.enqueue(create_queued_player(8)), 2);
+        assert_eq!(
+            lobby.enqueue(create_queued_player(7)),
+            1,
+            "Re-queuing (e.g., after a reconnect) keeps the original spot"
+        );
+
+        assert!(lobby.leave_queue(7));
+        assert!(!lobby.leave_queue(7), "Already left the queue");
+        assert_eq!(lobby.waiting_queue.front().map(|q| q.user_id), Some(8));
+    }
+
+    #[test]
+    fn test_queue_promotion_waits_for_game_and_seats() {
+        let mut lobby = Lobby::new_channel("123".to_string(), None);
+        let now = Instant::now();
+        for user_id in 1..=2 {
+            lobby.players.insert(
+                user_id,
+                create_test_player(user_id, PlayerConnectionState::Connected),
+            );
+        }
+        lobby.enqueue(create_queued_player(7));
+        lobby.enqueue(create_queued_player(8));
+
+        lobby.active_game_id = Some(Uuid::new_v4());
+        lobby.players.remove(&2);
+        assert!(
+            lobby.promote_from_queue(2, now).is_empty(),
+            "Seats can't change while a game is running"
+        );
+
+        lobby.active_game_id = None;
+        let promoted: Vec<i64> = lobby
+            .promote_from_queue(2, now)
+            .iter()
+            .map(|q| q.user_id)
+            .collect();
+        assert_eq!(
+            promoted,
+            vec![7],
+            "Only as many users as there are free seats"
+        );
+        assert!(lobby.players.contains_key(&7));
+        assert_eq!(lobby.waiting_queue.len(), 1);
+    }
+
+    #[test]
+    fn test_table_lobby_ids() {
+        assert_eq!(
+            Lobby::table_lobby_id("123", 1),
+            "channel:123",
+            "Table 1 is the channel lobby itself"
+        );
+        assert_eq!(Lobby::table_lobby_id("123", 3), "channel:123:table:3");
+
+        let table = Lobby::new_table("123".to_string(), Some("456".to_string()), 2);
+        assert_eq!(table.lobby_id, "channel:123:table:2");
+        assert_eq!(table.channel_id.as_deref(), Some("123"));
+        assert_eq!(table.lobby_type, LobbyType::Channel);
+        assert_eq!(Lobby::new_channel("123".to_string(), None).table_number, 1);
+    }
+
+    #[test]
+    fn test_table_info_summary() {
+        let mut table = Lobby::new_table("123".to_string(), None, 2);
+        table
+            .players
+            .insert(1, create_test_player(1, PlayerConnectionState::Connected));
+        table
+            .players
+            .insert(2, create_test_player(2, PlayerConnectionState::Connected));
+        table.host_id = Some(2);
+
+        let info = table.table_info();
+        assert_eq!(info.table_number, 2);
+        assert_eq!(info.player_count, 2);
+        assert_eq!(info.host_username.as_deref(), Some("TestUser2"));
+        assert!(!info.in_game);
+
+        table.active_game_id = Some(Uuid::new_v4());
+        assert!(table.table_info().in_game);
+    }
+
+    #[test]
+    fn test_last_lobby_expires_after_ttl() {
+        let last_lobbies = LastLobbies::new(Duration::from_secs(60));
+        let now = Instant::now();
+        last_lobbies.remember(1, "custom:ABC123", now);
+
+        assert_eq!(
+            last_lobbies
+                .get(1, now + Duration::from_secs(59))
+                .as_deref(),
+            Some("custom:ABC123")
+        );
+        assert!(
+            last_lobbies.get(1, now + Duration::from_secs(60)).is_none(),
+            "No rejoin offer once the TTL has passed"
+        );
+
+        last_lobbies.prune(now + Duration::from_secs(60));
+        assert!(last_lobbies.is_empty(), "Pruning drops expired entries");
+    }
+
+    #[test]
+    fn test_last_lobby_forget_and_replace() {
+        let last_lobbies = LastLobbies::new(Duration::from_secs(60));
+        let now = Instant::now();
+        last_lobbies.remember(1, "custom:ABC123", now);
+        last_lobbies.remember(1, "channel:42", now);
+        assert_eq!(
+            last_lobbies.get(1, now).as_deref(),
+            Some("channel:42"),
+            "Only the most recent lobby is remembered"
+        );
+        assert_eq!(last_lobbies.len(), 1);
+
+        last_lobbies.forget(1);
+        assert!(last_lobbies.get(1, now).is_none());
+    }
+
+    #[test]
+    fn test_player_infos_include_flags() {
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        lobby.host_id = Some(2);
+        let mut ready_player = create_test_player(1, PlayerConnectionState::Connected);
+        ready_player.ready = true;
+        lobby.players.insert(1, ready_player);
+        lobby.players.insert(
+            2,
+            create_test_player(
+                2,
+                PlayerConnectionState::AwaitingReconnect {
+                    since: Instant::now(),
+                },
+            ),
+        );
+
+        let profiles = PlayerProfiles::default();
+        profiles.set(
+            1,
+            PlayerProfile {
+                title: Some("Veteran".to_string()),
+                ..Default::default()
+            },
+        );
+        let players = lobby.player_infos(&profiles);
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].user_id, "2", "The host is listed first");
+        assert!(players[0].is_host);
+        assert!(!players[0].is_connected);
+        assert!(
+            players[0].awaiting_reconnect,
+            "A disconnected host stays listed while they can still reconnect"
+        );
+
+        let guest = &players[1];
+        assert!(!guest.is_host);
+        assert!(guest.is_connected && !guest.awaiting_reconnect);
+        assert!(guest.ready);
+        assert_eq!(guest.profile.title.as_deref(), Some("Veteran"));
+        assert!(
+            players[0].profile.is_empty(),
+            "Players without a profile get empty cosmetics"
+        );
+    }
+
+    #[test]
+    fn test_lobby_heartbeat_reports_live_state() {
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+
+        let mut ready_player = create_test_player(1, PlayerConnectionState::Connected);
+        ready_player.ready = true;
+        ready_player.typing_since = Some(now - Duration::from_secs(1));
+        lobby.players.insert(1, ready_player);
+
+        // Disconnected 18 seconds ago, so 42 seconds are left to reconnect
+        let mut stale_typist = create_test_player(
+            2,
+            PlayerConnectionState::AwaitingReconnect {
+                since: now - Duration::from_secs(18),
+            },
+        );
+        stale_typist.typing_since = Some(now - PLAYER_TYPING_TIMEOUT);
+        lobby.players.insert(2, stale_typist);
+        lobby.reserve_seat(3, now + Duration::from_secs(30));
+
+        let ServerMessage::LobbyHeartbeat {
+            connected_count,
+            ready,
+            typing,
+            reconnecting,
+            reserved_seats,
+            scheduled_start,
+        } = lobby.heartbeat(now)
+        else {
+            panic!("Expected a LobbyHeartbeat");
+        };
+
+        assert_eq!(connected_count, 1);
+        assert_eq!(ready, vec!["1".to_string()]);
+        assert_eq!(
+            typing,
+            vec!["1".to_string()],
+            "Typing indicators expire if they aren't refreshed"
+        );
+        assert_eq!(
+            reconnecting,
+            vec![LobbyCountdown {
+                user_id: "2".to_string(),
+                secs_left: 42,
+            }]
+        );
+        assert_eq!(
+            reserved_seats,
+            vec![LobbyCountdown {
+                user_id: "3".to_string(),
+                secs_left: 30,
+            }]
+        );
+
+        assert_eq!(scheduled_start, None);
+
+        lobby.clear_ready();
+        assert!(lobby.players.iter().all(|p| !p.ready));
+    }
+
+    #[test]
+    fn test_block_lists() {
+        let blocks = BlockLists::default();
+        blocks.set(1, [3, 2]);
+        assert!(blocks.is_blocked(1, 2));
+        assert!(!blocks.is_blocked(2, 1), "Blocking is one-way");
+        assert_eq!(blocks.blocked_by(1), vec![2, 3]);
+
+        blocks.unblock(1, 2);
+        blocks.unblock(1, 3);
+        assert!(blocks.blocked_by(1).is_empty());
+        assert!(
+            blocks.entries.is_empty(),
+            "Users with an empty block list don't keep an entry"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_connections() {
+        let connections = UserConnections::default();
+        let (first, mut first_rx) = ClientSender::channel(4, Duration::from_secs(10));
+        let (second, mut second_rx) = ClientSender::channel(4, Duration::from_secs(10));
+        let (first_id, second_id) = (Uuid::new_v4(), Uuid::new_v4());
+        connections.add(1, first_id, first);
+        connections.add(1, second_id, second);
+
+        let message = ServerMessage::LobbyCreated {
+            lobby_code: "ABC234".to_string(),
+        };
+        assert!(connections.send(1, &message));
+        assert!(
+            first_rx.recv().await.is_some() && second_rx.recv().await.is_some(),
+            "Every connection a user has open gets the message"
+        );
+        assert!(!connections.send(2, &message), "User 2 isn't connected");
+
+        connections.remove(1, first_id);
+        assert!(connections.is_connected(1));
+        connections.remove(1, second_id);
+        assert!(!connections.is_connected(1));
+        assert!(
+            connections.entries.is_empty(),
+            "Users without connections don't keep an entry"
+        );
+    }
+
+    #[test]
+    fn test_muted_and_blocked_typing_is_hidden() {
+        let lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+        for user_id in 1..=3 {
+            let mut player = create_test_player(user_id, PlayerConnectionState::Connected);
+            player.typing_since = Some(now);
+            lobby.players.insert(user_id, player);
+        }
+        let blocks = BlockLists::default();
+        blocks.block(1, 2);
+        lobby.players.get_mut(&1).unwrap().muted.insert(3);
+
+        let heartbeat = lobby.heartbeat(now);
+        let typing_seen_by = |user_id: i64| {
+            let viewer = lobby.players.get(&user_id).unwrap();
+            match viewer.filter_social(&heartbeat, &blocks) {
+                ServerMessage::LobbyHeartbeat { typing, .. } => typing,
+                _ => panic!("Expected a LobbyHeartbeat"),
+            }
+        };
+
+        assert_eq!(
+            typing_seen_by(1),
+            vec!["1".to_string()],
+            "Player 1 blocked 2 and muted 3"
+        );
+        assert_eq!(
+            typing_seen_by(2),
+            vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            "Being blocked doesn't hide anything from you"
+        );
+    }
+
+    #[test]
+    fn test_expired_reservations_free_their_seat() {
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+        lobby
+            .players
+            .insert(1, create_test_player(1, PlayerConnectionState::Connected));
+        lobby.reserve_seat(2, now + Duration::from_secs(60));
+
+        let later = now + Duration::from_secs(61);
+        assert!(!lobby.has_room_for(3, 2, now));
+        assert!(
+            lobby.has_room_for(3, 2, later),
+            "An expired reservation should no longer hold a seat"
+        );
+        assert!(
+            !lobby.has_room_for(2, 1, later),
+            "An expired reservation should not let its user bypass capacity"
+        );
+
+        lobby.prune_reservations(later);
+        assert!(lobby.reserved_seats.is_empty());
+    }
+
+    #[test]
+    fn test_prune_reservations_drops_claimed_seats() {
+        // Once the invitee joins, their reservation shouldn't count as an extra seat
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        let now = Instant::now();
+        lobby.reserve_seat(1, now + Duration::from_secs(60));
+        lobby
+            .players
+            .insert(1, create_test_player(1, PlayerConnectionState::Connected));
+
+        assert_eq!(lobby.open_reservations(now), 0);
+        lobby.prune_reservations(now);
+        assert!(lobby.reserved_seats.is_empty());
+    }
+
+    #[test]
+    fn test_lobby_uses_guild_settings_for_new_games() {
+        let mut lobby = Lobby::new_channel("123".to_string(), Some("456".to_string()));
+        assert_eq!(
+            lobby.default_rounds(5),
+            5,
+            "Without guild settings the fallback should be used"
+        );
+        assert!(lobby.allows_mode(&GameMode::Adventure));
+
+        let mut settings = GuildSettings::defaults(456, 5);
+        settings.default_rounds = 3;
+        settings.allowed_modes = vec![GameMode::Multiplayer];
+        lobby.guild_settings = Some(settings);
+
+        assert_eq!(lobby.default_rounds(5), 3);
+        assert!(lobby.allows_mode(&GameMode::Multiplayer));
+        assert!(!lobby.allows_mode(&GameMode::Adventure));
+    }
+
+    #[test]
+    fn test_lobby_language() {
+        let packs = DictionaryPacks::from_packs(vec![
+            DictionaryPack::parse("espanol".to_string(), "# language: es\ngato\n"),
+            DictionaryPack::parse("science".to_string(), "quark\n"),
+        ]);
+        let mut lobby = Lobby::new_channel("123".to_string(), Some("456".to_string()));
+        assert_eq!(lobby.language(&packs), "en");
+
+        let mut settings = GuildSettings::defaults(456, 5);
+        settings.default_language = "pt-BR".to_string();
+        lobby.guild_settings = Some(settings);
+        assert_eq!(lobby.language(&packs), "pt-BR", "The guild default applies");
+
+        lobby.dictionary_pack = Some(PackSelection {
+            name: "espanol".to_string(),
+            mode: PackMode::Replace,
+        });
+        assert_eq!(
+            lobby.language(&packs),
+            "es",
+            "A pack in another language overrides the guild default"
+        );
+
+        lobby.dictionary_pack = Some(PackSelection {
+            name: "science".to_string(),
+            mode: PackMode::Layer,
+        });
+        assert_eq!(
+            lobby.language(&packs),
+            "pt-BR",
+            "Packs without a language keep the guild's"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+/// Language used when a lobby doesn't pick one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Message key for the announcement sent when a lobby's language changes
+pub const LANGUAGE_ANNOUNCEMENT: &str = "language_announcement";
+
+/// Translations of player-facing messages, keyed by language tag, then by
+/// error code or message key
+///
+/// Messages without a translation fall back to English (an error's `Display`
+/// text). Regional tags (e.g. `pt-BR`) only need the messages that differ from their
+/// base language; everything else is looked up under the base tag.
+const CATALOG: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[(LANGUAGE_ANNOUNCEMENT, "Words are now checked in English")],
+    ),
+    (
+        "es",
+        &[
+            (
+                LANGUAGE_ANNOUNCEMENT,
+                "Las palabras se comprueban en español",
+            ),
+            (
+                "word_not_in_dictionary",
+                "Palabra no encontrada en el diccionario",
+            ),
+            ("word_already_used", "Esa palabra ya se usó"),
+            ("invalid_path", "Las letras deben ser contiguas"),
+            ("word_too_obscure", "Palabra demasiado rara para esta sala"),
+            ("word_too_short", "Palabra demasiado corta para esta sala"),
+            ("not_your_turn", "No es tu turno"),
+            ("out_of_time", "Se acabó tu tiempo"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            (
+                LANGUAGE_ANNOUNCEMENT,
+                "Les mots sont maintenant vérifiés en français",
+            ),
+            (
+                "word_not_in_dictionary",
+                "Mot introuvable dans le dictionnaire",
+            ),
+            ("word_already_used", "Ce mot a déjà été joué"),
+            ("invalid_path", "Les lettres doivent se toucher"),
+            ("word_too_obscure", "Mot trop rare pour ce salon"),
+            ("word_too_short", "Mot trop court pour ce salon"),
+            ("not_your_turn", "Ce n'est pas votre tour"),
+            ("out_of_time", "Votre temps est écoulé"),
+        ],
+    ),
+    (
+        "de",
+        &[
+            (
+                LANGUAGE_ANNOUNCEMENT,
+                "Wörter werden jetzt auf Deutsch geprüft",
+            ),
+            (
+                "word_not_in_dictionary",
+                "Wort nicht im Wörterbuch gefunden",
+            ),
+            ("word_already_used", "Dieses Wort wurde schon gespielt"),
+            ("invalid_path", "Die Buchstaben müssen aneinandergrenzen"),
+            ("word_too_obscure", "Wort zu ungewöhnlich für diese Lobby"),
+            ("word_too_short", "Wort zu kurz für diese Lobby"),
+            ("not_your_turn", "Du bist nicht am Zug"),
+            ("out_of_time", "Deine Zeit ist abgelaufen"),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            (
+                LANGUAGE_ANNOUNCEMENT,
+                "As palavras são agora verificadas em português",
+            ),
+            (
+                "word_not_in_dictionary",
+                "Palavra não encontrada no dicionário",
+            ),
+            ("word_already_used", "Essa palavra já foi usada"),
+            ("invalid_path", "As letras têm de estar ligadas"),
+            ("word_too_obscure", "Palavra demasiado rara para esta sala"),
+            ("word_too_short", "Palavra demasiado curta para esta sala"),
+            ("not_your_turn", "Não é a tua vez"),
+            ("out_of_time", "O teu tempo acabou"),
+        ],
+    ),
+    (
+        "pt-BR",
+        &[
+            (
+                LANGUAGE_ANNOUNCEMENT,
+                "As palavras agora são verificadas em português",
+            ),
+            ("invalid_path", "As letras precisam estar conectadas"),
+            ("word_too_obscure", "Palavra rara demais para esta sala"),
+            ("word_too_short", "Palavra curta demais para esta sala"),
+            ("not_your_turn", "Não é a sua vez"),
+            ("out_of_time", "Seu tempo acabou"),
+        ],
+    ),
+];
+
+/// Tags to try for a language, most specific first: `es-MX` tries `es-MX`, then `es`
+fn fallback_chain(language: &str) -> impl Iterator<Item = &str> {
+    let mut tag = Some(language);
+    std::iter::from_fn(move || {
+        let current = tag?;
+        tag = current.rsplit_once('-').map(|(base, _)| base);
+        Some(current)
+    })
+}
+
+/// A message in the given language (or its base language), if translated
+pub fn translate(language: &str, key: &str) -> Option<&'static str> {
+    fallback_chain(language).find_map(|tag| {
+        CATALOG
+            .iter()
+            .find(|(catalog_tag, _)| catalog_tag.eq_ignore_ascii_case(tag))
+            .and_then(|(_, messages)| messages.iter().find(|(k, _)| *k == key))
+            .map(|(_, text)| *text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_falls_back_to_base_language() {
+        assert_eq!(
+            translate("es", "word_not_in_dictionary"),
+            Some("Palabra no encontrada en el diccionario")
+        );
+        assert_eq!(
+            translate("es-MX", "word_not_in_dictionary"),
+            translate("es", "word_not_in_dictionary"),
+            "Regions without their own catalog use the base language"
+        );
+        assert_eq!(
+            translate("ES", "word_not_in_dictionary"),
+            translate("es", "word_not_in_dictionary"),
+            "Tags are case-insensitive"
+        );
+    }
+
+    #[test]
+    fn test_translate_prefers_regional_wording() {
+        assert_eq!(translate("pt-BR", "not_your_turn"), Some("Não é a sua vez"));
+        assert_eq!(translate("pt", "not_your_turn"), Some("Não é a tua vez"));
+        assert_eq!(
+            translate("pt-BR", "word_not_in_dictionary"),
+            translate("pt", "word_not_in_dictionary"),
+            "Messages a region doesn't override come from the base language"
+        );
+    }
+
+    #[test]
+    fn test_untranslated_messages() {
+        assert_eq!(translate("en", "word_not_in_dictionary"), None);
+        assert_eq!(translate("xx", "not_your_turn"), None);
+        assert_eq!(translate("es", "no_such_key"), None);
+    }
+
+    #[test]
+    fn test_every_language_has_an_announcement() {
+        for (tag, _) in CATALOG {
+            assert!(
+                translate(tag, LANGUAGE_ANNOUNCEMENT).is_some(),
+                "{} has no language announcement",
+                tag
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// One UTC day of finished games (`analytics_daily`)
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct DailyActivity {
+    pub day: NaiveDate,
+    pub games_played: i64,
+    /// Distinct (non-bot) players in the day's finished games
+    pub active_players: i64,
+    /// None for days whose games have no recorded start time
+    pub avg_game_secs: Option<f64>,
+}
+
+impl DailyActivity {
+    fn empty(day: NaiveDate) -> Self {
+        Self {
+            day,
+            games_played: 0,
+            active_players: 0,
+            avg_game_secs: None,
+        }
+    }
+
+    /// Every day from `since` through `until`, with zeroes for days without games
+    pub fn fill_gaps(rows: Vec<Self>, since: NaiveDate, until: NaiveDate) -> Vec<Self> {
+        let mut rows = rows.into_iter().peekable();
+        let mut days = Vec::new();
+        let mut day = since;
+        while day <= until {
+            // Skip anything out of order or before the window
+            while rows.next_if(|row| row.day < day).is_some() {}
+            days.push(
+                rows.next_if(|row| row.day == day)
+                    .unwrap_or_else(|| Self::empty(day)),
+            );
+            day += Duration::days(1);
+        }
+        days
+    }
+}
+
+/// Players who signed up in one week and how many kept playing
+/// (`analytics_cohorts`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct RetentionCohort {
+    /// Monday of the signup week
+    pub cohort_week: NaiveDate,
+    pub cohort_size: i64,
+    /// Players who finished a game at least this many days after signing up
+    pub retained_day_1: i64,
+    pub retained_day_7: i64,
+    pub retained_day_30: i64,
+}
+
+/// Totals over the whole window of an overview
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalyticsSummary {
+    pub games_played: i64,
+    pub avg_daily_active_players: f64,
+    pub peak_daily_active_players: i64,
+    /// Average over every game in the window, not over days
+    pub avg_game_secs: Option<f64>,
+}
+
+impl AnalyticsSummary {
+    pub fn new(days: &[DailyActivity]) -> Self {
+        let games_played = days.iter().map(|d| d.games_played).sum();
+        let active: i64 = days.iter().map(|d| d.active_players).sum();
+        let (timed_games, total_secs) = days
+            .iter()
+            .filter_map(|d| Some((d.games_played, d.avg_game_secs? * d.games_played as f64)))
+            .fold((0, 0.0), |(games, secs), (g, s)| (games + g, secs + s));
+        Self {
+            games_played,
+            avg_daily_active_players: if days.is_empty() {
+                0.0
+            } else {
+                active as f64 / days.len() as f64
+            },
+            peak_daily_active_players: days.iter().map(|d| d.active_players).max().unwrap_or(0),
+            avg_game_secs: (timed_games > 0).then(|| total_secs / timed_games as f64),
+        }
+    }
+}
+
+/// Response of `GET /api/admin/analytics/overview`
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsOverview {
+    pub since: NaiveDate,
+    pub summary: AnalyticsSummary,
+    /// One entry per day, oldest first
+    pub daily: Vec<DailyActivity>,
+    /// Signup cohorts in the window, oldest first
+    pub cohorts: Vec<RetentionCohort>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn activity(
+        day: u32,
+        games_played: i64,
+        active_players: i64,
+        avg: Option<f64>,
+    ) -> DailyActivity {
+        DailyActivity {
+            day: date(day),
+            games_played,
+            active_players,
+            avg_game_secs: avg,
+        }
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        let rows = vec![
+            activity(1, 9, 9, Some(60.0)),
+            activity(3, 2, 4, Some(300.0)),
+            activity(5, 1, 2, None),
+        ];
+        let days = DailyActivity::fill_gaps(rows, date(2), date(5));
+
+        assert_eq!(
+            days.iter().map(|d| d.day).collect::<Vec<_>>(),
+            vec![date(2), date(3), date(4), date(5)],
+            "One entry per day in the window"
+        );
+        assert_eq!(
+            days[0],
+            DailyActivity::empty(date(2)),
+            "Rows before the window are dropped"
+        );
+        assert_eq!(days[1].games_played, 2);
+        assert_eq!(days[2].games_played, 0);
+        assert_eq!(days[3].active_players, 2);
+    }
+
+    #[test]
+    fn test_summary_weights_game_length_by_games() {
+        let summary = AnalyticsSummary::new(&[
+            activity(1, 3, 6, Some(100.0)),
+            activity(2, 1, 2, Some(500.0)),
+            activity(3, 0, 0, None),
+        ]);
+
+        assert_eq!(summary.games_played, 4);
+        assert_eq!(summary.peak_daily_active_players, 6);
+        assert!((summary.avg_daily_active_players - 8.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            summary.avg_game_secs,
+            Some(200.0),
+            "Three 100s games and one 500s game average to 200s"
+        );
+
+        let empty = AnalyticsSummary::new(&[]);
+        assert_eq!(empty.avg_game_secs, None);
+        assert_eq!(empty.avg_daily_active_players, 0.0);
+    }
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// What an API key may read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApiScope {
+    /// Guild leaderboards
+    #[serde(rename = "read:leaderboard")]
+    ReadLeaderboard,
+    /// Finished games and their replays
+    #[serde(rename = "read:games")]
+    ReadGames,
+}
+
+impl ApiScope {
+    pub const ALL: [Self; 2] = [Self::ReadLeaderboard, Self::ReadGames];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReadLeaderboard => "read:leaderboard",
+            Self::ReadGames => "read:games",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == scope)
+    }
+}
+
+/// An API key, without the key itself (`api_keys`)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApiKey {
+    pub key_id: i64,
+    #[serde(skip)]
+    pub user_id: i64,
+    pub name: String,
+    /// The start of the key, to tell keys apart
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Scopes stored under a name this build doesn't know grant nothing
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.iter().any(|s| s == scope.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_names() {
+        for scope in ApiScope::ALL {
+            assert_eq!(ApiScope::parse(scope.as_str()), Some(scope));
+            assert_eq!(
+                serde_json::to_value(scope).unwrap(),
+                scope.as_str(),
+                "Scopes are sent and stored by the same name"
+            );
+        }
+        assert_eq!(ApiScope::parse("write:games"), None);
+    }
+}
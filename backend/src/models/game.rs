@@ -1,21 +1,84 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use super::game_event::{GameEvent, RefilledTile};
+use crate::{
+    game::{
+        grid::{GridGenerator, GRID_SIZE},
+        letter_bag::LetterBag,
+        rules::GameRules,
+    },
+    utils::letters::get_letter_value,
+};
+
 // =============================================================================
 // Database Models (for SQLx persistence)
 // =============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
 pub enum GameMode {
     Multiplayer,
     #[serde(rename = "2v2")]
     TwoVTwo,
     Adventure,
+    /// All players share one score and try to reach a target together
+    Coop,
+    /// Each player has a chess-style clock for the whole game
+    Blitz,
+    /// Two players race the clock on identical boards of their own, at the same time
+    Duel,
+    /// All players race the clock together to use every tile on the board
+    TimeAttack,
+    /// Several local players take turns on one connection
+    HotSeat,
+    /// One player solves a run of boards, each against a shorter clock; the
+    /// number solved is their score
+    PuzzleRush,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 9] = [
+        GameMode::Multiplayer,
+        GameMode::TwoVTwo,
+        GameMode::Adventure,
+        GameMode::Coop,
+        GameMode::Blitz,
+        GameMode::Duel,
+        GameMode::TimeAttack,
+        GameMode::HotSeat,
+        GameMode::PuzzleRush,
+    ];
+
+    /// Fewest players a game of this mode can start with; co-op and time attack
+    /// can be played solo, and puzzle rushes only are
+    pub fn min_players(&self) -> usize {
+        match self {
+            GameMode::Coop | GameMode::TimeAttack | GameMode::PuzzleRush => 1,
+            _ => 2,
+        }
+    }
+
+    /// Whether a lobby game of this mode can be set to start later; hot-seat
+    /// games need their local players named at the start, puzzle rushes are
+    /// played on the spot, and 2v2 and adventure have their own setup flows
+    pub fn can_be_scheduled(&self) -> bool {
+        matches!(
+            self,
+            GameMode::Multiplayer
+                | GameMode::Coop
+                | GameMode::Blitz
+                | GameMode::Duel
+                | GameMode::TimeAttack
+        )
+    }
 }
 
 /// Database persistence state for games
@@ -55,6 +118,87 @@ pub struct Game {
     pub created_at: DateTime<Utc>,
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
+    /// Team score to beat in co-op games
+    pub coop_target_score: Option<i32>,
+    /// Shared gem pool in co-op games
+    pub coop_gems: i32,
+    /// Starting clock per player in blitz games
+    pub blitz_clock_secs: Option<i32>,
+    /// House rules the game was started with; NULL for games from before lobbies
+    /// had rules
+    pub rules: Option<serde_json::Value>,
+    /// Seed of the letters that refill duel boards
+    pub duel_seed: Option<i64>,
+    /// When a duel's time is up
+    pub duel_ends_at: Option<DateTime<Utc>>,
+    /// When a time attack's time is up
+    pub time_attack_ends_at: Option<DateTime<Utc>>,
+    /// Tiles a time attack team has used, as of the last flush
+    pub covered_tiles: Option<i32>,
+    /// `HotSeats` of a hot-seat game, as of the last flush
+    pub hot_seats: Option<serde_json::Value>,
+    /// `PuzzleRushState` of a puzzle rush, as of the last flush
+    pub puzzle_rush: Option<serde_json::Value>,
+    /// The `GameResult`, once the game has finished
+    pub result: Option<serde_json::Value>,
+}
+
+/// Mode-specific settings stored when a game is created
+#[derive(Debug, Clone)]
+pub struct GameSetup {
+    pub mode: GameMode,
+    /// Team score to beat in co-op games
+    pub coop_target_score: Option<i32>,
+    /// Starting clock per player in blitz games
+    pub blitz_clock_secs: Option<i32>,
+    /// Seed of the letters that refill duel boards
+    pub duel_seed: Option<i64>,
+    /// When a duel's time is up
+    pub duel_ends_at: Option<DateTime<Utc>>,
+    /// When a time attack's time is up
+    pub time_attack_ends_at: Option<DateTime<Utc>>,
+    /// Names of the local players in a hot-seat game, in turn order
+    pub hot_seats: Option<HotSeats>,
+    /// The first board of a puzzle rush and its clock
+    pub puzzle_rush: Option<PuzzleRushState>,
+    pub rules: GameRules,
+}
+
+/// The local players of a hot-seat game
+///
+/// They all play on the owner's connection, so the game is stored under the owner
+/// with a single `game_players` row and the seats kept here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotSeats {
+    pub seats: Vec<HotSeat>,
+    /// Index of the seat whose turn it is
+    pub current_seat: usize,
+}
+
+impl HotSeats {
+    /// Fresh seats for the given names, in turn order
+    pub fn new(names: Vec<String>) -> Self {
+        Self {
+            seats: names
+                .into_iter()
+                .map(|name| HotSeat {
+                    name,
+                    score: 0,
+                    forfeited: false,
+                })
+                .collect(),
+            current_seat: 0,
+        }
+    }
+}
+
+/// One local player in a hot-seat game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotSeat {
+    pub name: String,
+    pub score: i32,
+    #[serde(default)]
+    pub forfeited: bool,
 }
 
 /// Database model for game players
@@ -68,6 +212,73 @@ pub struct GamePlayerRecord {
     pub is_bot: bool,
     pub bot_difficulty: Option<String>,
     pub joined_at: DateTime<Utc>,
+    /// Blitz clock left at the last flush
+    pub clock_remaining_ms: Option<i32>,
+    pub forfeited: bool,
+    /// The player's own `PlayerBoard` in duels, as of the last flush
+    pub board: Option<serde_json::Value>,
+}
+
+/// A player's final score in a game with their best word, for result cards
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PlayerFinalScore {
+    pub user_id: i64,
+    pub username: String,
+    pub score: i32,
+    pub forfeited: bool,
+    /// The player's highest-scoring word; null if they never scored one
+    pub best_word: Option<String>,
+    pub best_word_score: Option<i32>,
+}
+
+/// A stored game with its player count, as listed by `list_games`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameListing {
+    pub game_id: Uuid,
+    pub guild_id: Option<i64>,
+    pub channel_id: i64,
+    pub game_mode: GameMode,
+    pub state: GameDbState,
+    pub current_round: i32,
+    pub max_rounds: i32,
+    pub current_turn_player: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub player_count: i64,
+}
+
+/// A game's player with their name and stored score
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GamePlayerSummary {
+    pub game_id: Uuid,
+    pub user_id: i64,
+    pub username: String,
+    pub score: i32,
+}
+
+/// Which games `list_games` returns; empty or unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct GameListFilter {
+    pub states: Vec<GameDbState>,
+    pub guild_id: Option<i64>,
+    pub channel_id: Option<i64>,
+}
+
+impl GameListFilter {
+    /// Games that haven't finished: waiting for players or in progress
+    pub fn live() -> Self {
+        Self {
+            states: vec![GameDbState::Waiting, GameDbState::Active],
+            ..Default::default()
+        }
+    }
+}
+
+/// Which of a game's moves `get_game_moves_page` returns; unset fields match every move
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameMoveFilter {
+    pub user_id: Option<i64>,
+    pub round: Option<i32>,
 }
 
 // =============================================================================
@@ -75,10 +286,11 @@ pub struct GamePlayerRecord {
 // =============================================================================
 
 /// Current status of an active game
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GameStatus {
     /// Game created but not yet started
+    #[default]
     WaitingToStart,
     /// Game is actively being played
     InProgress,
@@ -88,12 +300,6 @@ pub enum GameStatus {
     Finished,
 }
 
-impl Default for GameStatus {
-    fn default() -> Self {
-        Self::WaitingToStart
-    }
-}
-
 /// Player information for live game state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamePlayer {
@@ -109,6 +315,9 @@ pub struct GamePlayer {
     pub turn_order: u8,
     /// Whether the player is currently connected
     pub is_connected: bool,
+    /// Resigned; out of the turn rotation and can't win
+    #[serde(default)]
+    pub forfeited: bool,
 }
 
 impl GamePlayer {
@@ -125,6 +334,7 @@ impl GamePlayer {
             score: 0,
             turn_order,
             is_connected: true,
+            forfeited: false,
         }
     }
 }
@@ -152,6 +362,283 @@ pub struct GameState {
     pub status: GameStatus,
     /// When the game was created
     pub created_at: DateTime<Utc>,
+    pub mode: GameMode,
+    /// Shared team progress, only present in co-op games
+    pub coop: Option<CoopState>,
+    /// Per-player clocks, only present in blitz games
+    pub clock: Option<BlitzClock>,
+    /// When the current turn started, for move timing
+    #[serde(skip)]
+    pub turn_started_at: Option<Instant>,
+    /// Moves played so far; board history is keyed by move number
+    #[serde(default)]
+    pub move_count: u32,
+    /// Passes in a row per player since they last scored a word
+    #[serde(default)]
+    pub consecutive_passes: HashMap<Uuid, u32>,
+    /// Turns passed per player over the whole game, for tie-breaks
+    #[serde(default)]
+    pub passes: HashMap<Uuid, u32>,
+    /// Why the game ended, once it has
+    #[serde(default)]
+    pub end_reason: Option<GameOverReason>,
+    /// Tiles left to draw, only present in letter bag games
+    #[serde(default)]
+    pub letter_bag: Option<LetterBag>,
+    /// The lobby's house rules as of the game's start
+    #[serde(default)]
+    pub rules: GameRules,
+    /// Turns ended so far, whether by a word, a pass or running out of time
+    #[serde(default)]
+    pub turns_taken: u32,
+    /// Per-player boards, only present in duels
+    #[serde(default)]
+    pub duel: Option<DuelState>,
+    /// Tiles used so far, only present in time attack games
+    #[serde(default)]
+    pub time_attack: Option<TimeAttackState>,
+    /// Boards solved and the current board's clock, only present in puzzle rushes
+    #[serde(default)]
+    pub puzzle_rush: Option<PuzzleRushState>,
+}
+
+/// Chess-style clocks for a blitz game
+///
+/// Only the current player's clock runs. Time is charged when their turn ends, and a
+/// player whose clock reaches zero forfeits the rest of their turns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlitzClock {
+    /// Time left per player, as of the start of the current turn
+    pub remaining_ms: HashMap<Uuid, u64>,
+    /// When the current turn's clock started running
+    #[serde(skip)]
+    pub turn_started_at: Option<Instant>,
+}
+
+impl BlitzClock {
+    /// Give every player the same starting time
+    pub fn new(players: &[GamePlayer], per_player: Duration) -> Self {
+        let per_player_ms = per_player.as_millis() as u64;
+        Self {
+            remaining_ms: players.iter().map(|p| (p.user_id, per_player_ms)).collect(),
+            turn_started_at: None,
+        }
+    }
+}
+
+/// A duel player's own board
+///
+/// Both players start from the same board. Played tiles are replaced with the
+/// next letters of the duel's seeded sequence, so the boards only drift apart as
+/// the players' words do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerBoard {
+    pub grid: Vec<Vec<GridCell>>,
+    /// Words this player has played; the other player may still play them
+    pub used_words: HashSet<String>,
+    /// Replacement letters taken from the sequence so far
+    pub draws: u32,
+}
+
+impl PlayerBoard {
+    pub fn new(grid: Vec<Vec<GridCell>>) -> Self {
+        Self {
+            grid,
+            used_words: HashSet::new(),
+            draws: 0,
+        }
+    }
+
+    /// Record a played word and refill its tiles from the sequence `seed` picks,
+    /// keeping their multipliers
+    pub fn play(&mut self, word: &str, positions: &[Position], seed: u64) {
+        self.used_words.insert(word.to_lowercase());
+        for pos in positions {
+            let Some(cell) = self
+                .grid
+                .get_mut(pos.row)
+                .and_then(|row| row.get_mut(pos.col))
+            else {
+                continue;
+            };
+            cell.letter = GridGenerator::seeded_letter(seed, self.draws);
+            cell.value = get_letter_value(cell.letter);
+            self.draws += 1;
+        }
+    }
+}
+
+/// The boards and time limit of a duel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuelState {
+    /// Picks the letters that refill played tiles
+    pub seed: u64,
+    /// When the duel ends, whatever the scores
+    pub ends_at: DateTime<Utc>,
+    /// Each player's board, by in-memory player ID
+    pub boards: HashMap<Uuid, PlayerBoard>,
+}
+
+/// Shared progress in a time attack game
+///
+/// The team wins by using every tile on the board at least once before time is up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeAttackState {
+    /// Tiles used at least once, as bit `row * GRID_SIZE + col`
+    pub covered: u32,
+    /// When the game ends, however much of the board is covered
+    pub ends_at: DateTime<Utc>,
+}
+
+impl TimeAttackState {
+    pub fn new(ends_at: DateTime<Utc>) -> Self {
+        Self {
+            covered: 0,
+            ends_at,
+        }
+    }
+
+    /// Mark the tiles a word went through as used
+    pub fn cover(&mut self, positions: &[Position]) {
+        for pos in positions {
+            if pos.row < GRID_SIZE && pos.col < GRID_SIZE {
+                self.covered |= 1 << (pos.row * GRID_SIZE + pos.col);
+            }
+        }
+    }
+
+    /// Check whether a tile has been used
+    pub fn is_covered(&self, pos: &Position) -> bool {
+        pos.row < GRID_SIZE
+            && pos.col < GRID_SIZE
+            && self.covered & (1 << (pos.row * GRID_SIZE + pos.col)) != 0
+    }
+
+    /// Tiles used so far
+    pub fn covered_count(&self) -> u32 {
+        self.covered.count_ones()
+    }
+
+    /// Share of the board used so far, rounded down so only a cleared board is 100
+    pub fn coverage_percent(&self) -> u8 {
+        (self.covered_count() * 100 / (GRID_SIZE * GRID_SIZE) as u32) as u8
+    }
+
+    /// Check whether every tile has been used
+    pub fn is_cleared(&self) -> bool {
+        self.covered_count() as usize == GRID_SIZE * GRID_SIZE
+    }
+}
+
+/// Seconds taken off each puzzle rush board's clock for every board solved
+pub const PUZZLE_RUSH_SHRINK_SECS: u32 = 5;
+/// The least time a puzzle rush board gets, however long the streak
+pub const PUZZLE_RUSH_MIN_SECS: u32 = 15;
+
+/// A player's run through a puzzle rush
+///
+/// Boards come from a sequence the seed picks, skipping any without a word worth
+/// the target score. A word worth that much solves the board and deals the next
+/// one with less time on its clock; the run ends when a board's time is up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleRushState {
+    /// Picks the sequence of boards
+    pub seed: u64,
+    /// Points a word needs to solve a board
+    pub target_score: i32,
+    /// Position of the current board in the sequence
+    pub board: u32,
+    /// Boards solved so far, which is the run's score
+    pub streak: u32,
+    /// Time the first board gets
+    pub start_secs: u32,
+    /// When the current board's time is up
+    pub ends_at: DateTime<Utc>,
+}
+
+impl PuzzleRushState {
+    /// A run starting on the `board`th board of the sequence, with its clock
+    /// starting `now`
+    pub fn new(
+        seed: u64,
+        target_score: i32,
+        start_secs: u32,
+        board: u32,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut rush = Self {
+            seed,
+            target_score,
+            board,
+            streak: 0,
+            start_secs,
+            ends_at: now,
+        };
+        rush.ends_at = now + chrono::Duration::seconds(rush.board_secs(0) as i64);
+        rush
+    }
+
+    /// Time a board gets once `streak` boards have been solved
+    pub fn board_secs(&self, streak: u32) -> u32 {
+        let floor = PUZZLE_RUSH_MIN_SECS.min(self.start_secs);
+        self.start_secs
+            .saturating_sub(streak.saturating_mul(PUZZLE_RUSH_SHRINK_SECS))
+            .max(floor)
+    }
+
+    /// When the board after the current one runs out, if it's dealt `now`
+    pub fn next_ends_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now + chrono::Duration::seconds(self.board_secs(self.streak + 1) as i64)
+    }
+}
+
+/// Shared progress in a co-op game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoopState {
+    /// Team score needed to win
+    pub target_score: i32,
+    /// Gems earned by the whole team
+    pub gems: u32,
+}
+
+impl CoopState {
+    /// Target points per player turn when the host doesn't choose a target
+    pub const DEFAULT_TARGET_PER_TURN: i32 = 20;
+
+    /// Default target for a game with `players` players over `rounds` rounds
+    pub fn default_target(players: usize, rounds: u8) -> i32 {
+        Self::DEFAULT_TARGET_PER_TURN * players as i32 * rounds as i32
+    }
+}
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOverReason {
+    /// Every round was played
+    RoundsComplete,
+    /// A co-op team reached its target score
+    TargetReached,
+    /// Every blitz clock ran out, or a duel's or time attack's time was up
+    OutOfTime,
+    /// A time attack team used every tile on the board
+    BoardCleared,
+    /// Every connected player passed on their last turn
+    AllPlayersPassed,
+    /// No (or too few) unused words were left on the board
+    BoardExhausted,
+    /// Everyone else resigned
+    Resignation,
+}
+
+/// Result of a co-op game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoopOutcome {
+    /// The team reached the target score
+    Win,
+    /// The rounds ran out before the target was reached
+    Lose,
 }
 
 impl GameState {
@@ -175,6 +662,322 @@ impl GameState {
             round_submissions,
             status: GameStatus::WaitingToStart,
             created_at: Utc::now(),
+            mode: GameMode::Multiplayer,
+            coop: None,
+            clock: None,
+            turn_started_at: None,
+            move_count: 0,
+            consecutive_passes: HashMap::new(),
+            passes: HashMap::new(),
+            end_reason: None,
+            letter_bag: None,
+            rules: GameRules::default(),
+            turns_taken: 0,
+            duel: None,
+            time_attack: None,
+            puzzle_rush: None,
+        }
+    }
+
+    /// Create a blitz game where each player gets `clock_per_player` for the whole game
+    pub fn new_blitz(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        total_rounds: u8,
+        clock_per_player: Duration,
+    ) -> Self {
+        Self {
+            mode: GameMode::Blitz,
+            clock: Some(BlitzClock::new(&players, clock_per_player)),
+            ..Self::new(game_id, grid, players, total_rounds)
+        }
+    }
+
+    /// Create a duel: every player gets their own copy of `grid` to play until `ends_at`
+    pub fn new_duel(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        seed: u64,
+        ends_at: DateTime<Utc>,
+    ) -> Self {
+        let boards = players
+            .iter()
+            .map(|p| (p.user_id, PlayerBoard::new(grid.clone())))
+            .collect();
+        Self {
+            mode: GameMode::Duel,
+            duel: Some(DuelState {
+                seed,
+                ends_at,
+                boards,
+            }),
+            ..Self::new(game_id, grid, players, 1)
+        }
+    }
+
+    /// A duel player's own board; None outside duels
+    pub fn player_board(&self, player_id: Uuid) -> Option<&PlayerBoard> {
+        self.duel.as_ref()?.boards.get(&player_id)
+    }
+
+    /// Score a word on a duel player's own board, refilling its tiles. Returns
+    /// false, changing nothing, outside duels.
+    pub fn play_duel_word(
+        &mut self,
+        player_id: Uuid,
+        word: &str,
+        positions: &[Position],
+        score: i32,
+    ) -> bool {
+        let Some(duel) = self.duel.as_mut() else {
+            return false;
+        };
+        let seed = duel.seed;
+        let Some(board) = duel.boards.get_mut(&player_id) else {
+            return false;
+        };
+        board.play(word, positions, seed);
+        if let Some(player) = self.get_player_mut(player_id) {
+            player.score += score;
+        }
+        true
+    }
+
+    /// Create a time attack game: the team has until `ends_at` to use every tile
+    pub fn new_time_attack(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        ends_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            mode: GameMode::TimeAttack,
+            time_attack: Some(TimeAttackState::new(ends_at)),
+            ..Self::new(game_id, grid, players, 1)
+        }
+    }
+
+    /// Create a puzzle rush: one player solving boards from `grid` on, until one
+    /// runs out of time
+    pub fn new_puzzle_rush(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        rush: PuzzleRushState,
+    ) -> Self {
+        Self {
+            mode: GameMode::PuzzleRush,
+            puzzle_rush: Some(rush),
+            ..Self::new(game_id, grid, players, 1)
+        }
+    }
+
+    /// Deal a puzzle rush's next board, the `board`th of its sequence, after
+    /// the current one was solved
+    pub fn deal_rush_board(&mut self, board: u32, grid: &Grid, ends_at: DateTime<Utc>) {
+        let Some(rush) = self.puzzle_rush.as_mut() else {
+            return;
+        };
+        rush.board = board;
+        rush.streak += 1;
+        rush.ends_at = ends_at;
+        self.grid = grid.clone();
+        // Words may come up again on a new board
+        self.used_words.clear();
+    }
+
+    /// Create a hot-seat game, where every player is a local player on one connection
+    pub fn new_hot_seat(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        total_rounds: u8,
+    ) -> Self {
+        Self {
+            mode: GameMode::HotSeat,
+            ..Self::new(game_id, grid, players, total_rounds)
+        }
+    }
+
+    /// The local players' names, scores and turn for storage; None outside
+    /// hot-seat games
+    pub fn hot_seats(&self) -> Option<HotSeats> {
+        (self.mode == GameMode::HotSeat).then(|| HotSeats {
+            seats: self
+                .players
+                .iter()
+                .map(|p| HotSeat {
+                    name: p.username.clone(),
+                    score: p.score,
+                    forfeited: p.forfeited,
+                })
+                .collect(),
+            current_seat: self.current_player_index,
+        })
+    }
+
+    /// Check whether the game has one player, who nobody else watches play
+    pub fn is_solo(&self) -> bool {
+        self.players.len() == 1
+    }
+
+    /// Check whether players take turns; in duels, time attacks and puzzle rushes
+    /// everyone plays at once until time is up
+    pub fn has_turns(&self) -> bool {
+        self.duel.is_none() && self.time_attack.is_none() && self.puzzle_rush.is_none()
+    }
+
+    /// When the game ends on the clock, in duels, time attacks and puzzle rushes
+    /// (where it's the current board's time)
+    pub fn ends_at(&self) -> Option<DateTime<Utc>> {
+        self.duel
+            .as_ref()
+            .map(|duel| duel.ends_at)
+            .or_else(|| self.time_attack.as_ref().map(|t| t.ends_at))
+            .or_else(|| self.puzzle_rush.as_ref().map(|rush| rush.ends_at))
+    }
+
+    /// Check whether a game played against the clock has run out of time
+    pub fn time_up(&self, now: DateTime<Utc>) -> bool {
+        self.status == GameStatus::InProgress
+            && self.ends_at().is_some_and(|ends_at| now >= ends_at)
+    }
+
+    /// Mark a word's tiles as used in a time attack, finishing the game once the
+    /// board is cleared. Returns whether that happened.
+    pub fn cover_tiles(&mut self, positions: &[Position]) -> bool {
+        let Some(time_attack) = self.time_attack.as_mut() else {
+            return false;
+        };
+        time_attack.cover(positions);
+        if !time_attack.is_cleared() {
+            return false;
+        }
+        self.finish(GameOverReason::BoardCleared);
+        true
+    }
+
+    /// Start timing the current turn, including the player's clock in blitz games
+    pub fn start_turn_clock(&mut self, now: Instant) {
+        self.turn_started_at = Some(now);
+        if let Some(clock) = self.clock.as_mut() {
+            clock.turn_started_at = Some(now);
+        }
+    }
+
+    /// Count a move and return its number (the first move is 1; 0 is the starting board)
+    pub fn next_move_number(&mut self) -> i32 {
+        self.move_count += 1;
+        self.move_count as i32
+    }
+
+    /// Time spent on the current turn so far, in milliseconds
+    pub fn move_time_ms(&self, now: Instant) -> Option<i32> {
+        let started = self.turn_started_at?;
+        let elapsed = now.saturating_duration_since(started).as_millis();
+        Some(elapsed.min(i32::MAX as u128) as i32)
+    }
+
+    /// Stop the current player's clock and deduct the time their turn took
+    pub fn charge_turn_clock(&mut self, now: Instant) {
+        if let Some(elapsed_ms) = self.turn_clock_ms(now) {
+            self.spend_clock(elapsed_ms);
+        }
+    }
+
+    /// Time the current turn has run on the player's blitz clock; None outside
+    /// blitz games
+    pub fn turn_clock_ms(&self, now: Instant) -> Option<u64> {
+        let started = self.clock.as_ref()?.turn_started_at?;
+        Some(now.saturating_duration_since(started).as_millis() as u64)
+    }
+
+    /// Stop the current player's clock, deducting `elapsed_ms` from it
+    pub(super) fn spend_clock(&mut self, elapsed_ms: u64) {
+        let Some(player_id) = self.current_player().map(|p| p.user_id) else {
+            return;
+        };
+        let Some(clock) = self.clock.as_mut() else {
+            return;
+        };
+        clock.turn_started_at = None;
+        if let Some(remaining) = clock.remaining_ms.get_mut(&player_id) {
+            *remaining = remaining.saturating_sub(elapsed_ms);
+        }
+    }
+
+    /// Time left on a player's clock, counting the current turn if it's theirs.
+    /// None outside blitz games.
+    pub fn clock_remaining(&self, player_id: Uuid, now: Instant) -> Option<Duration> {
+        let clock = self.clock.as_ref()?;
+        let mut remaining_ms = clock.remaining_ms.get(&player_id).copied()?;
+        if self.current_player().map(|p| p.user_id) == Some(player_id) {
+            if let Some(started) = clock.turn_started_at {
+                let elapsed_ms = now.saturating_duration_since(started).as_millis() as u64;
+                remaining_ms = remaining_ms.saturating_sub(elapsed_ms);
+            }
+        }
+        Some(Duration::from_millis(remaining_ms))
+    }
+
+    /// Check whether the current player's clock has run out mid-turn
+    pub fn current_player_out_of_time(&self, now: Instant) -> bool {
+        self.current_player()
+            .and_then(|p| self.clock_remaining(p.user_id, now))
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Check whether a player has used up their clock (as of their last turn)
+    fn is_out_of_time(&self, player_id: Uuid) -> bool {
+        self.clock.as_ref().is_some_and(|clock| {
+            clock
+                .remaining_ms
+                .get(&player_id)
+                .is_some_and(|remaining| *remaining == 0)
+        })
+    }
+
+    /// Create a co-op game where the players share a score and must reach `target_score`
+    pub fn new_coop(
+        game_id: Uuid,
+        grid: Vec<Vec<GridCell>>,
+        players: Vec<GamePlayer>,
+        total_rounds: u8,
+        target_score: i32,
+    ) -> Self {
+        Self {
+            mode: GameMode::Coop,
+            coop: Some(CoopState {
+                target_score,
+                gems: 0,
+            }),
+            ..Self::new(game_id, grid, players, total_rounds)
+        }
+    }
+
+    /// Combined score of all players (the score that matters in co-op)
+    pub fn team_score(&self) -> i32 {
+        self.players.iter().map(|p| p.score).sum()
+    }
+
+    /// Check whether a co-op team has reached its target
+    pub fn coop_target_reached(&self) -> bool {
+        self.coop
+            .as_ref()
+            .is_some_and(|coop| self.team_score() >= coop.target_score)
+    }
+
+    /// Outcome of a co-op game; None while it's still being played or outside co-op
+    pub fn coop_outcome(&self) -> Option<CoopOutcome> {
+        self.coop.as_ref()?;
+        if self.coop_target_reached() {
+            Some(CoopOutcome::Win)
+        } else if self.status == GameStatus::Finished {
+            Some(CoopOutcome::Lose)
+        } else {
+            None
         }
     }
 
@@ -215,6 +1018,62 @@ impl GameState {
         self.used_words.insert(word.to_lowercase());
     }
 
+    /// Replace the tiles a word used with tiles drawn from the letter bag, keeping
+    /// their multipliers. Returns false, leaving the board alone, outside letter bag
+    /// games; once the bag runs out, played tiles stay as they are.
+    pub fn refill_played_tiles(&mut self, positions: &[Position]) -> bool {
+        let Some(tiles) = self.draw_refills(positions) else {
+            return false;
+        };
+        self.place_tiles(&tiles);
+        true
+    }
+
+    /// Draw replacements for the tiles a word used from (a copy of) the letter
+    /// bag, without changing the game; None outside letter bag games
+    pub fn draw_refills(&self, positions: &[Position]) -> Option<Vec<RefilledTile>> {
+        let mut bag = self.letter_bag.clone()?;
+        let mut rng = rand::rng();
+        let mut tiles = Vec::new();
+        for pos in positions {
+            if self
+                .grid
+                .get(pos.row)
+                .and_then(|row| row.get(pos.col))
+                .is_none()
+            {
+                continue;
+            }
+            let Some(letter) = bag.draw(&mut rng) else {
+                break;
+            };
+            tiles.push(RefilledTile {
+                position: pos.clone(),
+                letter,
+            });
+        }
+        Some(tiles)
+    }
+
+    /// Put tiles drawn from the letter bag on the board, keeping the multipliers
+    pub(super) fn place_tiles(&mut self, tiles: &[RefilledTile]) {
+        for tile in tiles {
+            let pos = &tile.position;
+            let Some(cell) = self
+                .grid
+                .get_mut(pos.row)
+                .and_then(|row| row.get_mut(pos.col))
+            else {
+                continue;
+            };
+            if let Some(bag) = self.letter_bag.as_mut() {
+                bag.take(tile.letter);
+            }
+            cell.letter = tile.letter;
+            cell.value = get_letter_value(tile.letter);
+        }
+    }
+
     /// Check if all connected players have submitted this round
     pub fn is_round_complete(&self) -> bool {
         self.players.iter().filter(|p| p.is_connected).all(|p| {
@@ -242,13 +1101,185 @@ impl GameState {
         self.current_round > self.total_rounds || self.status == GameStatus::Finished
     }
 
+    /// How many more turns a player gets, counting this round's if they haven't
+    /// played it yet
+    pub fn turns_left(&self, index: usize) -> usize {
+        let Some(player) = self.players.get(index) else {
+            return 0;
+        };
+        if player.forfeited || self.is_game_finished() {
+            return 0;
+        }
+        let played = self
+            .round_submissions
+            .get(&player.user_id)
+            .copied()
+            .unwrap_or(false);
+        usize::from(self.total_rounds - self.current_round) + usize::from(!played)
+    }
+
     /// Get the number of connected players
     pub fn connected_player_count(&self) -> usize {
         self.players.iter().filter(|p| p.is_connected).count()
     }
+
+    /// Record the current player passing, deducting up to `penalty` points (scores
+    /// never go below zero). Returns the points actually deducted.
+    pub fn record_pass(&mut self, penalty: u32) -> i32 {
+        let deducted = self.pass_penalty(penalty);
+        self.apply(&GameEvent::TurnPassed {
+            player: self.current_player_index,
+            penalty: deducted,
+        });
+        deducted
+    }
+
+    /// Points the current player loses for passing, up to `penalty` (scores never
+    /// go below zero)
+    pub fn pass_penalty(&self, penalty: u32) -> i32 {
+        self.current_player().map_or(0, |player| {
+            player.score.clamp(0, penalty.min(i32::MAX as u32) as i32)
+        })
+    }
+
+    /// A player scored a word, ending their run of passes
+    pub fn reset_passes(&mut self, player_id: Uuid) {
+        self.consecutive_passes.remove(&player_id);
+    }
+
+    /// Resign a player from the game. Returns false if they weren't playing.
+    pub fn resign(&mut self, player_id: Uuid) -> bool {
+        match self.get_player_mut(player_id) {
+            Some(player) if !player.forfeited => {
+                player.forfeited = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Players who haven't resigned
+    pub fn active_player_count(&self) -> usize {
+        self.players.iter().filter(|p| !p.forfeited).count()
+    }
+
+    /// Check whether resignations have decided the game: one player is left
+    /// (or nobody, in a solo game)
+    pub fn resignations_end_game(&self) -> bool {
+        let active = self.active_player_count();
+        active == 0 || (active == 1 && self.players.len() > 1)
+    }
+
+    /// Check whether every connected player passed on their most recent turn
+    pub fn all_players_passed(&self) -> bool {
+        let mut connected = self
+            .players
+            .iter()
+            .filter(|p| p.is_connected && !p.forfeited)
+            .peekable();
+        connected.peek().is_some()
+            && connected.all(|p| {
+                self.consecutive_passes
+                    .get(&p.user_id)
+                    .is_some_and(|n| *n > 0)
+            })
+    }
+
+    /// End the game for the given reason
+    pub fn finish(&mut self, reason: GameOverReason) -> TurnAdvance {
+        self.status = GameStatus::Finished;
+        self.end_reason = Some(reason);
+        TurnAdvance::GameFinished
+    }
+
+    /// Finish the current player's turn and move play forward.
+    ///
+    /// Marks the current player as having played this round, hands the turn to the
+    /// next player, and rolls over to a new round once everyone has played. Players
+    /// who resigned, and blitz players who are out of time, are skipped.
+    pub fn advance_turn(&mut self) -> TurnAdvance {
+        self.turns_taken += 1;
+        if let Some(player_id) = self.current_player().map(|p| p.user_id) {
+            self.mark_player_submitted(player_id);
+        }
+
+        // A co-op team that reaches its target wins without playing out the rounds,
+        // a blitz game ends once nobody has time left, and a game where everyone
+        // passes in a row is over early
+        if self.players.is_empty() {
+            return self.finish(GameOverReason::RoundsComplete);
+        }
+        if self.coop_target_reached() {
+            return self.finish(GameOverReason::TargetReached);
+        }
+        if self.resignations_end_game() {
+            return self.finish(GameOverReason::Resignation);
+        }
+        if self.clock.is_some()
+            && self
+                .players
+                .iter()
+                .all(|p| p.forfeited || self.is_out_of_time(p.user_id))
+        {
+            return self.finish(GameOverReason::OutOfTime);
+        }
+        if self.all_players_passed() {
+            return self.finish(GameOverReason::AllPlayersPassed);
+        }
+
+        let mut advance = TurnAdvance::NextTurn;
+        loop {
+            self.current_player_index = (self.current_player_index + 1) % self.players.len();
+            if self.is_round_complete() {
+                self.current_round += 1;
+                self.current_player_index = 0;
+                self.reset_round_submissions();
+
+                if self.is_game_finished() {
+                    return self.finish(GameOverReason::RoundsComplete);
+                }
+                advance = TurnAdvance::RoundEnded;
+            }
+
+            // Someone is still playing with time left (checked above), so this terminates
+            let player = &self.players[self.current_player_index];
+            let player_id = player.user_id;
+            if !player.forfeited && !self.is_out_of_time(player_id) {
+                return advance;
+            }
+            self.mark_player_submitted(player_id);
+        }
+    }
+}
+
+/// What happened when the turn was advanced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnAdvance {
+    /// Same round, next player's turn
+    NextTurn,
+    /// The round finished and a new one has begun
+    RoundEnded,
+    /// The final round finished and the game is over
+    GameFinished,
+}
+
+/// A move that has been applied in memory but not yet written to `game_moves`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMove {
+    pub user_id: i64,
+    pub round_number: i32,
+    pub word: String,
+    pub score: i32,
+    pub positions: Vec<Position>,
+    /// From the start of the turn to the submission
+    pub move_time_ms: Option<i32>,
+    pub move_number: i32,
+    /// The board after this move, for `game_board_history`
+    pub board: Vec<Vec<GridCell>>,
 }
 
 // Legacy type alias for backwards compatibility
+#[allow(dead_code)]
 pub type GamePersistenceState = GameDbState;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -259,6 +1290,17 @@ pub struct GameBoard {
     pub round_number: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Tiles left to draw in letter bag games
+    pub letter_bag: Option<serde_json::Value>,
+}
+
+/// The board as it was after a given move (0 is the starting board)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameBoardRevision {
+    pub game_id: Uuid,
+    pub move_number: i32,
+    pub grid: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -271,6 +1313,7 @@ pub struct GameMove {
     pub score: i32,
     pub positions: serde_json::Value,
     pub timestamp: DateTime<Utc>,
+    pub move_time_ms: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
@@ -285,9 +1328,12 @@ pub enum Multiplier {
     DoubleLetter,
     #[serde(rename = "TL")]
     TripleLetter,
+    /// Doubles the letter total of any word through it
+    #[serde(rename = "DW")]
+    DoubleWord,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GridCell {
     pub letter: char,
     pub value: u8,
@@ -452,6 +1498,30 @@ mod tests {
         assert!(game_state.is_word_used("Test"));
     }
 
+    #[test]
+    fn test_refill_played_tiles_from_bag() {
+        let mut game_state =
+            GameState::new(Uuid::new_v4(), create_test_grid(), create_test_players(), 5);
+        let played = [Position { row: 0, col: 1 }, Position { row: 1, col: 1 }];
+        assert!(
+            !game_state.refill_played_tiles(&played),
+            "Weighted games keep their board"
+        );
+        assert_eq!(game_state.grid[0][1].letter, 'B');
+
+        game_state.letter_bag = Some(LetterBag::scrabble());
+        assert!(game_state.refill_played_tiles(&played));
+        assert_eq!(game_state.letter_bag.as_ref().unwrap().remaining(), 96);
+        let cell = &game_state.grid[1][1];
+        assert_eq!(cell.value, get_letter_value(cell.letter));
+        assert_eq!(
+            cell.multiplier,
+            Some(Multiplier::TripleLetter),
+            "Replaced tiles keep their multiplier"
+        );
+        assert_eq!(game_state.grid[0][0].letter, 'A', "Unplayed tiles stay put");
+    }
+
     #[test]
     fn test_game_state_round_submissions() {
         let grid = create_test_grid();
@@ -504,6 +1574,477 @@ mod tests {
         assert_eq!(game_state.connected_player_count(), 1);
     }
 
+    #[test]
+    fn test_advance_turn_rotates_players() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let player2_id = players[1].user_id;
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 5);
+
+        assert_eq!(game_state.advance_turn(), TurnAdvance::NextTurn);
+        assert!(game_state.is_player_turn(player2_id));
+        assert_eq!(game_state.current_round, 1);
+    }
+
+    #[test]
+    fn test_advance_turn_ends_round_after_everyone_plays() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let player1_id = players[0].user_id;
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 5);
+
+        game_state.advance_turn();
+        assert_eq!(game_state.advance_turn(), TurnAdvance::RoundEnded);
+        assert_eq!(game_state.current_round, 2);
+        // New round starts with the first player and fresh submissions
+        assert!(game_state.is_player_turn(player1_id));
+        assert!(!game_state.is_round_complete());
+    }
+
+    #[test]
+    fn test_advance_turn_finishes_game_after_last_round() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 1);
+
+        game_state.advance_turn();
+        assert_eq!(game_state.advance_turn(), TurnAdvance::GameFinished);
+        assert_eq!(game_state.status, GameStatus::Finished);
+        assert!(game_state.is_game_finished());
+        assert_eq!(game_state.end_reason, Some(GameOverReason::RoundsComplete));
+    }
+
+    #[test]
+    fn test_turns_left_counts_this_round_until_played() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 3);
+        assert_eq!(game_state.turns_left(0), 3);
+        assert_eq!(game_state.turns_left(1), 3);
+
+        game_state.advance_turn();
+        assert_eq!(game_state.turns_left(0), 2);
+        assert_eq!(game_state.turns_left(1), 3);
+
+        game_state.advance_turn();
+        assert_eq!(game_state.turns_left(0), 2, "A new round has started");
+        assert_eq!(game_state.turns_left(5), 0);
+    }
+
+    #[test]
+    fn test_resigned_players_are_skipped() {
+        let grid = create_test_grid();
+        let mut players = create_test_players();
+        players.push(GamePlayer::new(
+            Uuid::new_v4(),
+            "Player3".to_string(),
+            None,
+            2,
+        ));
+        let player1_id = players[0].user_id;
+        let player2_id = players[1].user_id;
+        let player3_id = players[2].user_id;
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 5);
+
+        assert!(game_state.resign(player2_id));
+        assert!(!game_state.resign(player2_id), "Can only resign once");
+        assert!(!game_state.resignations_end_game(), "Two players remain");
+
+        assert_eq!(game_state.advance_turn(), TurnAdvance::NextTurn);
+        assert!(
+            game_state.is_player_turn(player3_id),
+            "Player 2 is out of the rotation"
+        );
+        assert_eq!(game_state.advance_turn(), TurnAdvance::RoundEnded);
+        assert!(game_state.is_player_turn(player1_id));
+
+        // The last opponent resigns on their own turn
+        game_state.advance_turn();
+        game_state.resign(player3_id);
+        assert_eq!(game_state.advance_turn(), TurnAdvance::GameFinished);
+        assert_eq!(game_state.end_reason, Some(GameOverReason::Resignation));
+    }
+
+    #[test]
+    fn test_pass_penalty_never_goes_below_zero() {
+        let grid = create_test_grid();
+        let mut players = create_test_players();
+        players[0].score = 3;
+        let player1_id = players[0].user_id;
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 5);
+
+        assert_eq!(
+            game_state.record_pass(5),
+            3,
+            "Only the points the player has"
+        );
+        assert_eq!(game_state.get_player(player1_id).unwrap().score, 0);
+        assert_eq!(game_state.consecutive_passes.get(&player1_id), Some(&1));
+
+        game_state.reset_passes(player1_id);
+        assert!(game_state.consecutive_passes.is_empty());
+        assert_eq!(
+            game_state.passes.get(&player1_id),
+            Some(&1),
+            "Scoring a word doesn't undo past passes"
+        );
+    }
+
+    #[test]
+    fn test_game_ends_when_everyone_passes_in_a_row() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let player1_id = players[0].user_id;
+        let mut game_state = GameState::new(Uuid::new_v4(), grid, players, 5);
+
+        // Player 1 passes, player 2 scores, player 1 passes again
+        game_state.record_pass(0);
+        assert_eq!(game_state.advance_turn(), TurnAdvance::NextTurn);
+        assert_eq!(game_state.advance_turn(), TurnAdvance::RoundEnded);
+        assert!(game_state.is_player_turn(player1_id));
+        game_state.record_pass(0);
+        assert_eq!(
+            game_state.advance_turn(),
+            TurnAdvance::NextTurn,
+            "Player 2 didn't pass on their last turn"
+        );
+
+        game_state.record_pass(0);
+        assert!(game_state.all_players_passed());
+        assert_eq!(game_state.advance_turn(), TurnAdvance::GameFinished);
+        assert_eq!(
+            game_state.end_reason,
+            Some(GameOverReason::AllPlayersPassed)
+        );
+        assert!(game_state.current_round < game_state.total_rounds);
+    }
+
+    #[test]
+    fn test_coop_team_score_and_early_win() {
+        let grid = create_test_grid();
+        let mut players = create_test_players();
+        players[0].score = 30;
+        players[1].score = 25;
+        let mut game_state = GameState::new_coop(Uuid::new_v4(), grid, players, 5, 60);
+
+        assert_eq!(game_state.mode, GameMode::Coop);
+        assert_eq!(game_state.team_score(), 55, "Team score sums every player");
+        assert_eq!(game_state.coop_outcome(), None, "Still in progress");
+        assert_eq!(game_state.advance_turn(), TurnAdvance::NextTurn);
+
+        game_state.players[1].score += 10;
+        assert_eq!(
+            game_state.advance_turn(),
+            TurnAdvance::GameFinished,
+            "Reaching the target should end the game immediately"
+        );
+        assert_eq!(game_state.coop_outcome(), Some(CoopOutcome::Win));
+    }
+
+    #[test]
+    fn test_coop_loses_when_rounds_run_out() {
+        let grid = create_test_grid();
+        let players = create_test_players();
+        let mut game_state = GameState::new_coop(Uuid::new_v4(), grid, players, 1, 100);
+
+        game_state.advance_turn();
+        assert_eq!(game_state.advance_turn(), TurnAdvance::GameFinished);
+        assert_eq!(game_state.coop_outcome(), Some(CoopOutcome::Lose));
+    }
+
+    #[test]
+    fn test_competitive_games_have_no_coop_outcome() {
+        let mut game_state =
+            GameState::new(Uuid::new_v4(), create_test_grid(), create_test_players(), 1);
+        game_state.players[0].score = 1000;
+        assert!(!game_state.coop_target_reached());
+        assert_eq!(game_state.coop_outcome(), None);
+    }
+
+    #[test]
+    fn test_move_time_is_measured_from_turn_start() {
+        let mut game_state =
+            GameState::new(Uuid::new_v4(), create_test_grid(), create_test_players(), 1);
+        let start = Instant::now();
+        assert_eq!(
+            game_state.move_time_ms(start),
+            None,
+            "No timing before the first turn starts"
+        );
+
+        game_state.start_turn_clock(start);
+        assert_eq!(
+            game_state.move_time_ms(start + Duration::from_millis(2_500)),
+            Some(2_500),
+            "Turns are timed in every mode, not just blitz"
+        );
+    }
+
+    #[test]
+    fn test_move_numbers_start_after_the_starting_board() {
+        let mut game_state =
+            GameState::new(Uuid::new_v4(), create_test_grid(), create_test_players(), 1);
+        assert_eq!(
+            game_state.next_move_number(),
+            1,
+            "Move 0 is the starting board"
+        );
+        assert_eq!(game_state.next_move_number(), 2);
+        assert_eq!(game_state.move_count, 2);
+    }
+
+    #[test]
+    fn test_blitz_clock_only_runs_on_own_turn() {
+        let players = create_test_players();
+        let (first, second) = (players[0].user_id, players[1].user_id);
+        let mut game_state = GameState::new_blitz(
+            Uuid::new_v4(),
+            create_test_grid(),
+            players,
+            3,
+            Duration::from_secs(60),
+        );
+        let start = Instant::now();
+        game_state.start_turn_clock(start);
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(
+            game_state.clock_remaining(first, later),
+            Some(Duration::from_secs(50)),
+            "The current player's clock counts the running turn"
+        );
+        assert_eq!(
+            game_state.clock_remaining(second, later),
+            Some(Duration::from_secs(60)),
+            "Waiting players' clocks don't run"
+        );
+
+        game_state.charge_turn_clock(later);
+        game_state.advance_turn();
+        game_state.start_turn_clock(later);
+        assert_eq!(
+            game_state.clock_remaining(first, later + Duration::from_secs(5)),
+            Some(Duration::from_secs(50)),
+            "Charged time sticks after the turn ends"
+        );
+        assert!(!game_state.current_player_out_of_time(later + Duration::from_secs(59)));
+        assert!(game_state.current_player_out_of_time(later + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_blitz_skips_players_out_of_time() {
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+            GamePlayer::new(Uuid::new_v4(), "Player3".to_string(), None, 2),
+        ];
+        let flagged = players[1].user_id;
+        let mut game_state = GameState::new_blitz(
+            Uuid::new_v4(),
+            create_test_grid(),
+            players,
+            2,
+            Duration::from_secs(60),
+        );
+        game_state
+            .clock
+            .as_mut()
+            .unwrap()
+            .remaining_ms
+            .insert(flagged, 0);
+
+        assert_eq!(game_state.advance_turn(), TurnAdvance::NextTurn);
+        assert_eq!(
+            game_state.current_player_index, 2,
+            "A player with no time left forfeits their turn"
+        );
+        assert_eq!(game_state.advance_turn(), TurnAdvance::RoundEnded);
+        assert_eq!(game_state.current_player_index, 0);
+    }
+
+    #[test]
+    fn test_blitz_ends_when_everyone_is_out_of_time() {
+        let mut game_state = GameState::new_blitz(
+            Uuid::new_v4(),
+            create_test_grid(),
+            create_test_players(),
+            5,
+            Duration::from_secs(60),
+        );
+        for remaining in game_state.clock.as_mut().unwrap().remaining_ms.values_mut() {
+            *remaining = 0;
+        }
+        assert_eq!(game_state.advance_turn(), TurnAdvance::GameFinished);
+        assert_eq!(game_state.status, GameStatus::Finished);
+    }
+
+    #[test]
+    fn test_duel_players_play_their_own_boards() {
+        let players = create_test_players();
+        let (first, second) = (players[0].user_id, players[1].user_id);
+        let mut game_state =
+            GameState::new_duel(Uuid::new_v4(), create_test_grid(), players, 7, Utc::now());
+        assert_eq!(
+            game_state.player_board(first),
+            game_state.player_board(second)
+        );
+
+        let played = [Position { row: 0, col: 0 }, Position { row: 0, col: 1 }];
+        assert!(game_state.play_duel_word(first, "AB", &played, 4));
+        let board = game_state.player_board(first).unwrap();
+        assert!(board.used_words.contains("ab"));
+        assert_eq!(board.draws, 2);
+        assert_eq!(
+            board.grid[0][1].multiplier,
+            Some(Multiplier::DoubleLetter),
+            "Refilled tiles keep their multipliers"
+        );
+        assert_eq!(game_state.get_player(first).unwrap().score, 4);
+
+        let untouched = game_state.player_board(second).unwrap();
+        assert!(
+            untouched.used_words.is_empty(),
+            "Each board has its own words"
+        );
+        assert_eq!(untouched.grid, create_test_grid());
+        assert!(game_state.used_words.is_empty());
+    }
+
+    #[test]
+    fn test_duel_boards_refill_alike() {
+        let mut first = PlayerBoard::new(create_test_grid());
+        let mut second = PlayerBoard::new(create_test_grid());
+        first.play("AB", &[Position { row: 0, col: 0 }], 42);
+        second.play("CD", &[Position { row: 0, col: 0 }], 42);
+        assert_eq!(
+            first.grid, second.grid,
+            "The same tiles played get the same new letters"
+        );
+        assert_eq!(
+            GridGenerator::seeded_letter(42, 3),
+            GridGenerator::seeded_letter(42, 3)
+        );
+    }
+
+    #[test]
+    fn test_time_up() {
+        let ends_at = Utc::now();
+        let mut game_state = GameState::new_duel(
+            Uuid::new_v4(),
+            create_test_grid(),
+            create_test_players(),
+            7,
+            ends_at,
+        );
+        game_state.status = GameStatus::InProgress;
+        assert!(!game_state.time_up(ends_at - chrono::Duration::seconds(1)));
+        assert!(game_state.time_up(ends_at));
+
+        let mut classic = GameState::new(Uuid::new_v4(), create_test_grid(), vec![], 5);
+        classic.status = GameStatus::InProgress;
+        assert!(!classic.time_up(ends_at), "Other modes have no time limit");
+        assert!(!classic.play_duel_word(Uuid::new_v4(), "AB", &[], 4));
+    }
+
+    #[test]
+    fn test_puzzle_rush_clocks_shrink_to_the_floor() {
+        let now = Utc::now();
+        let rush = PuzzleRushState::new(1, 15, 60, 0, now);
+        assert_eq!(rush.ends_at, now + chrono::Duration::seconds(60));
+        assert_eq!(rush.board_secs(1), 55);
+        assert_eq!(rush.board_secs(100), PUZZLE_RUSH_MIN_SECS);
+        assert_eq!(
+            PuzzleRushState::new(1, 15, 10, 0, now).board_secs(3),
+            10,
+            "A short starting clock is never raised to the floor"
+        );
+    }
+
+    #[test]
+    fn test_time_attack_ends_once_every_tile_is_used() {
+        let mut game_state = GameState::new_time_attack(
+            Uuid::new_v4(),
+            create_test_grid(),
+            create_test_players(),
+            Utc::now(),
+        );
+        game_state.status = GameStatus::InProgress;
+        assert!(!game_state.has_turns());
+
+        let first_row: Vec<Position> = (0..GRID_SIZE).map(|col| Position { row: 0, col }).collect();
+        assert!(!game_state.cover_tiles(&first_row));
+        assert!(
+            !game_state.cover_tiles(&first_row[..2]),
+            "Tiles only count once"
+        );
+        let time_attack = game_state.time_attack.as_ref().unwrap();
+        assert_eq!(time_attack.covered_count(), GRID_SIZE as u32);
+        assert_eq!(time_attack.coverage_percent(), 20);
+        assert!(time_attack.is_covered(&Position { row: 0, col: 4 }));
+        assert!(!time_attack.is_covered(&Position { row: 1, col: 0 }));
+
+        let rest: Vec<Position> = (1..GRID_SIZE)
+            .flat_map(|row| (0..GRID_SIZE).map(move |col| Position { row, col }))
+            .collect();
+        assert!(game_state.cover_tiles(&rest));
+        assert_eq!(game_state.status, GameStatus::Finished);
+        assert_eq!(game_state.end_reason, Some(GameOverReason::BoardCleared));
+        assert_eq!(
+            game_state.time_attack.as_ref().unwrap().coverage_percent(),
+            100
+        );
+    }
+
+    #[test]
+    fn test_coverage_rounds_down_until_cleared() {
+        let mut time_attack = TimeAttackState::new(Utc::now());
+        let all_but_one: Vec<Position> = (0..GRID_SIZE * GRID_SIZE - 1)
+            .map(|i| Position {
+                row: i / GRID_SIZE,
+                col: i % GRID_SIZE,
+            })
+            .collect();
+        time_attack.cover(&all_but_one);
+        assert_eq!(time_attack.coverage_percent(), 96);
+        assert!(!time_attack.is_cleared());
+
+        let mut classic = GameState::new(Uuid::new_v4(), create_test_grid(), vec![], 5);
+        assert!(classic.has_turns());
+        assert!(
+            !classic.cover_tiles(&all_but_one),
+            "Other modes track no coverage"
+        );
+    }
+
+    #[test]
+    fn test_hot_seats_follow_the_local_players() {
+        let mut players = create_test_players();
+        players[1].score = 12;
+        players[1].forfeited = true;
+        let mut game_state =
+            GameState::new_hot_seat(Uuid::new_v4(), create_test_grid(), players, 3);
+        game_state.current_player_index = 1;
+
+        let hot_seats = game_state.hot_seats().unwrap();
+        assert_eq!(hot_seats.current_seat, 1);
+        assert_eq!(
+            hot_seats.seats[1],
+            HotSeat {
+                name: "Player2".to_string(),
+                score: 12,
+                forfeited: true,
+            }
+        );
+        assert_eq!(
+            HotSeats::new(vec!["Ann".to_string()]).seats[0].score,
+            0,
+            "Fresh seats start at zero"
+        );
+
+        let classic = GameState::new(Uuid::new_v4(), create_test_grid(), vec![], 5);
+        assert!(classic.hot_seats().is_none());
+    }
+
     #[test]
     fn test_grid_cell_serialization() {
         let cell = GridCell {
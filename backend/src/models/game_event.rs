@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{GameOverReason, GameState, Grid, Position, TurnAdvance};
+use crate::game::letter_bag::LetterBag;
+
+/// Something that happened in a game
+///
+/// Client commands are checked against the game and turned into events, and the
+/// cached `GameState` only changes by applying them, so a game's event log
+/// replays it from its starting state. Players are referred to by their seat,
+/// the index into `GameState::players`, which survives a reload from the
+/// database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// A player scored a word: on the shared board, or their own in a duel
+    WordPlayed {
+        player: usize,
+        word: String,
+        positions: Vec<Position>,
+        score: i32,
+        /// From the start of the turn to the submission
+        move_time_ms: Option<i32>,
+    },
+    /// Played tiles on the shared board were replaced from the letter bag
+    GridRefilled { tiles: Vec<RefilledTile> },
+    /// A co-op team earned gems for a long word
+    GemsEarned { gems: u32 },
+    /// A time attack word used these tiles
+    TilesCovered { positions: Vec<Position> },
+    /// The current player passed, losing `penalty` points
+    TurnPassed { player: usize, penalty: i32 },
+    /// The current turn ended, taking `clock_ms` off the player's blitz clock
+    TurnEnded { clock_ms: Option<u64> },
+    /// A player resigned
+    PlayerResigned { player: usize },
+    /// A puzzle rush board was solved, dealing the `board`th board of the
+    /// sequence with its own clock
+    RushBoardDealt {
+        board: u32,
+        grid: Grid,
+        ends_at: DateTime<Utc>,
+    },
+    /// An admin dealt a fresh board
+    BoardReset {
+        grid: Grid,
+        letter_bag: Option<LetterBag>,
+    },
+    /// The game ended
+    GameFinished { reason: GameOverReason },
+}
+
+/// A tile drawn from the letter bag onto the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefilledTile {
+    pub position: Position,
+    pub letter: char,
+}
+
+impl GameState {
+    /// Apply an event to the game. Returns how play moved on, for events that
+    /// end a turn or the game.
+    pub fn apply(&mut self, event: &GameEvent) -> Option<TurnAdvance> {
+        match event {
+            GameEvent::WordPlayed {
+                player,
+                word,
+                positions,
+                score,
+                ..
+            } => {
+                self.next_move_number();
+                let player_id = self.players.get(*player)?.user_id;
+                if !self.play_duel_word(player_id, word, positions, *score) {
+                    self.mark_word_used(word);
+                    self.players[*player].score += score;
+                    self.reset_passes(player_id);
+                }
+                None
+            }
+            GameEvent::GridRefilled { tiles } => {
+                self.place_tiles(tiles);
+                None
+            }
+            GameEvent::GemsEarned { gems } => {
+                if let Some(coop) = self.coop.as_mut() {
+                    coop.gems += gems;
+                }
+                None
+            }
+            GameEvent::TilesCovered { positions } => {
+                if let Some(time_attack) = self.time_attack.as_mut() {
+                    time_attack.cover(positions);
+                }
+                None
+            }
+            GameEvent::TurnPassed { player, penalty } => {
+                let player = self.players.get_mut(*player)?;
+                player.score -= penalty;
+                let player_id = player.user_id;
+                *self.consecutive_passes.entry(player_id).or_insert(0) += 1;
+                *self.passes.entry(player_id).or_insert(0) += 1;
+                None
+            }
+            GameEvent::TurnEnded { clock_ms } => {
+                if let Some(clock_ms) = clock_ms {
+                    self.spend_clock(*clock_ms);
+                }
+                Some(self.advance_turn())
+            }
+            GameEvent::PlayerResigned { player } => {
+                if let Some(player) = self.players.get_mut(*player) {
+                    player.forfeited = true;
+                }
+                None
+            }
+            GameEvent::RushBoardDealt {
+                board,
+                grid,
+                ends_at,
+            } => {
+                self.deal_rush_board(*board, grid, *ends_at);
+                None
+            }
+            GameEvent::BoardReset { grid, letter_bag } => {
+                self.grid = grid.clone();
+                self.letter_bag = letter_bag.clone();
+                self.next_move_number();
+                None
+            }
+            GameEvent::GameFinished { reason } => Some(self.finish(*reason)),
+        }
+    }
+
+    /// The game as it is after applying `events`, in order, to `start`
+    pub fn replay<'a>(start: &GameState, events: impl IntoIterator<Item = &'a GameEvent>) -> Self {
+        let mut state = start.clone();
+        for event in events {
+            state.apply(event);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{GamePlayer, GameStatus, GridCell};
+
+    fn state() -> GameState {
+        let grid = (0..5)
+            .map(|_| {
+                (0..5)
+                    .map(|_| GridCell {
+                        letter: 'A',
+                        value: 1,
+                        multiplier: None,
+                    })
+                    .collect()
+            })
+            .collect();
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+        ];
+        let mut state = GameState::new(Uuid::new_v4(), grid, players, 2);
+        state.status = GameStatus::InProgress;
+        state
+    }
+
+    fn word(player: usize, word: &str, score: i32) -> GameEvent {
+        GameEvent::WordPlayed {
+            player,
+            word: word.to_string(),
+            positions: (0..word.len())
+                .map(|col| Position { row: 0, col })
+                .collect(),
+            score,
+            move_time_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_events_drive_the_game() {
+        let mut game_state = state();
+        assert_eq!(game_state.apply(&word(0, "cat", 7)), None);
+        assert_eq!(game_state.players[0].score, 7);
+        assert!(game_state.is_word_used("CAT"));
+        assert_eq!(game_state.move_count, 1);
+
+        assert_eq!(
+            game_state.apply(&GameEvent::TurnEnded { clock_ms: None }),
+            Some(TurnAdvance::NextTurn)
+        );
+        game_state.apply(&GameEvent::TurnPassed {
+            player: 1,
+            penalty: 0,
+        });
+        assert_eq!(
+            game_state.apply(&GameEvent::TurnEnded { clock_ms: None }),
+            Some(TurnAdvance::RoundEnded)
+        );
+        assert_eq!(game_state.passes.values().sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn test_replay_rebuilds_the_game() {
+        let start = state();
+        let mut live = start.clone();
+        let events = vec![
+            word(0, "cat", 7),
+            GameEvent::GridRefilled {
+                tiles: vec![RefilledTile {
+                    position: Position { row: 0, col: 0 },
+                    letter: 'Q',
+                }],
+            },
+            GameEvent::TurnEnded { clock_ms: None },
+            GameEvent::PlayerResigned { player: 1 },
+            GameEvent::GameFinished {
+                reason: GameOverReason::Resignation,
+            },
+        ];
+        for event in &events {
+            live.apply(event);
+        }
+
+        let replayed = GameState::replay(&start, &events);
+        assert_eq!(replayed.grid, live.grid);
+        assert_eq!(replayed.grid[0][0].letter, 'Q');
+        assert_eq!(replayed.used_words, live.used_words);
+        assert_eq!(replayed.players[0].score, 7);
+        assert!(replayed.players[1].forfeited);
+        assert_eq!(replayed.status, GameStatus::Finished);
+        assert_eq!(replayed.end_reason, Some(GameOverReason::Resignation));
+    }
+
+    #[test]
+    fn test_events_round_trip_as_json() {
+        let event = GameEvent::TurnPassed {
+            player: 1,
+            penalty: 3,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "turn_passed");
+        assert_eq!(serde_json::from_value::<GameEvent>(json).unwrap(), event);
+    }
+}
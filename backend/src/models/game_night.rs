@@ -0,0 +1,242 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::GameMode;
+
+/// Webhook URLs game night announcements may be posted to
+const WEBHOOK_PREFIXES: [&str; 2] = [
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+];
+
+/// A channel's recurring weekly game night
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameNight {
+    pub game_night_id: i32,
+    pub guild_id: i64,
+    pub channel_id: i64,
+    /// Day of the week, 0 = Monday (UTC)
+    pub weekday: i16,
+    /// Start time of day (UTC)
+    pub start_time: NaiveTime,
+    pub mode: GameMode,
+    /// Co-op only: team score to beat
+    pub target_score: Option<i32>,
+    pub webhook_url: Option<String>,
+    /// Role mentioned in the announcement
+    pub ping_role_id: Option<i64>,
+    /// Discord user who set it up, and who the game is scheduled under
+    pub created_by: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GameNight {
+    /// When this game night next starts after `now`
+    pub fn next_occurrence(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        next_occurrence(self.weekday, self.start_time, now)
+    }
+}
+
+/// The first time after `now` that falls on `weekday` (0 = Monday) at `time`, UTC
+pub fn next_occurrence(weekday: i16, time: NaiveTime, now: DateTime<Utc>) -> DateTime<Utc> {
+    let days_ahead = (weekday as i64 - now.weekday().num_days_from_monday() as i64).rem_euclid(7);
+    let candidate = (now.date_naive() + Duration::days(days_ahead))
+        .and_time(time)
+        .and_utc();
+    if candidate > now {
+        candidate
+    } else {
+        candidate + Duration::weeks(1)
+    }
+}
+
+/// A guild admin's game night setup for a channel (replaces any existing one)
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameNightUpdate {
+    pub weekday: i16,
+    pub start_time: NaiveTime,
+    #[serde(default = "default_mode")]
+    pub mode: GameMode,
+    #[serde(default)]
+    pub target_score: Option<i32>,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Discord role ID, as a string
+    #[serde(default)]
+    pub ping_role_id: Option<String>,
+}
+
+fn default_mode() -> GameMode {
+    GameMode::Multiplayer
+}
+
+impl GameNightUpdate {
+    /// Check every field, returning the role to ping if one was given
+    pub fn validate(&self) -> Result<Option<i64>, String> {
+        if !(0..=6).contains(&self.weekday) {
+            return Err("weekday must be between 0 (Monday) and 6 (Sunday)".to_string());
+        }
+        if !self.mode.can_be_scheduled() {
+            return Err(format!("{:?} games can't be scheduled", self.mode));
+        }
+        if self.target_score.is_some_and(|target| target <= 0) {
+            return Err("target_score must be positive".to_string());
+        }
+        if let Some(url) = &self.webhook_url {
+            if !WEBHOOK_PREFIXES
+                .iter()
+                .any(|prefix| url.starts_with(prefix))
+            {
+                return Err("webhook_url must be a Discord webhook URL".to_string());
+            }
+        }
+        self.ping_role_id
+            .as_deref()
+            .map(|role| {
+                role.parse()
+                    .map_err(|_| format!("'{}' is not a Discord role ID", role))
+            })
+            .transpose()
+    }
+}
+
+/// How a game night occurrence went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum GameNightStatus {
+    /// Still to come
+    Scheduled,
+    /// Its game was played
+    Started,
+    /// Called off by the host, or too few players were ready
+    Cancelled,
+    /// Passed without the lobby being set up, e.g. the server was down
+    Missed,
+}
+
+/// An occurrence of a game night that hasn't started yet, with the setup it
+/// was created from
+#[derive(Debug, Clone, FromRow)]
+pub struct UpcomingGameNight {
+    pub event_id: i64,
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub mode: GameMode,
+    pub target_score: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub announced_at: Option<DateTime<Utc>>,
+    pub webhook_url: Option<String>,
+    pub ping_role_id: Option<i64>,
+    pub created_by: i64,
+}
+
+/// A past or upcoming game night occurrence, for the guild's history
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameNightEvent {
+    pub event_id: i64,
+    pub channel_id: i64,
+    pub mode: GameMode,
+    pub starts_at: DateTime<Utc>,
+    pub status: GameNightStatus,
+    pub game_id: Option<Uuid>,
+    /// Set once the game has finished
+    pub winner_id: Option<i64>,
+}
+
+/// A player who was there when a game night's game started
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameNightAttendee {
+    pub event_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    /// Final (or current) score in the event's game
+    pub score: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn update() -> GameNightUpdate {
+        GameNightUpdate {
+            weekday: 4,
+            start_time: NaiveTime::from_hms_opt(19, 30, 0).unwrap(),
+            mode: GameMode::Multiplayer,
+            target_score: None,
+            webhook_url: None,
+            ping_role_id: None,
+        }
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let evening = NaiveTime::from_hms_opt(19, 30, 0).unwrap();
+        // 2026-10-15 is a Thursday
+        let thursday_noon = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            next_occurrence(3, evening, thursday_noon),
+            Utc.with_ymd_and_hms(2026, 10, 15, 19, 30, 0).unwrap(),
+            "Later the same day"
+        );
+        assert_eq!(
+            next_occurrence(0, evening, thursday_noon),
+            Utc.with_ymd_and_hms(2026, 10, 19, 19, 30, 0).unwrap(),
+            "The following Monday"
+        );
+        let thursday_evening = Utc.with_ymd_and_hms(2026, 10, 15, 19, 30, 0).unwrap();
+        assert_eq!(
+            next_occurrence(3, evening, thursday_evening),
+            Utc.with_ymd_and_hms(2026, 10, 22, 19, 30, 0).unwrap(),
+            "One starting right now is already under way"
+        );
+    }
+
+    #[test]
+    fn test_update_validation() {
+        assert_eq!(update().validate(), Ok(None));
+        assert_eq!(
+            GameNightUpdate {
+                ping_role_id: Some("41771983423143936".to_string()),
+                webhook_url: Some("https://discord.com/api/webhooks/1/abc".to_string()),
+                ..update()
+            }
+            .validate(),
+            Ok(Some(41771983423143936))
+        );
+
+        assert!(GameNightUpdate {
+            weekday: 7,
+            ..update()
+        }
+        .validate()
+        .is_err());
+        assert!(GameNightUpdate {
+            mode: GameMode::HotSeat,
+            ..update()
+        }
+        .validate()
+        .is_err());
+        assert!(
+            GameNightUpdate {
+                webhook_url: Some("http://169.254.169.254/latest".to_string()),
+                ..update()
+            }
+            .validate()
+            .is_err(),
+            "Announcements only go to Discord"
+        );
+        assert!(GameNightUpdate {
+            ping_role_id: Some("everyone".to_string()),
+            ..update()
+        }
+        .validate()
+        .is_err());
+    }
+}
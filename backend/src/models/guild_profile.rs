@@ -10,6 +10,8 @@ pub struct UserGuildProfile {
     pub guild_id: i64,
     /// Guild-specific nickname (overrides global_name when present)
     pub nickname: Option<String>,
+    /// Role IDs from the user's guild member info (needs guilds.members.read)
+    pub roles: Vec<i64>,
     pub updated_at: DateTime<Utc>,
 }
 
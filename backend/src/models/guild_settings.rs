@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::GameMode;
+
+/// Longest accepted language tag (e.g., "en", "pt-BR", "zh-Hant-TW")
+const MAX_LANGUAGE_TAG_LENGTH: usize = 16;
+
+/// Whether a string looks like a BCP 47 language tag: alphanumeric parts joined by '-'
+pub fn is_valid_language_tag(language: &str) -> bool {
+    !language.is_empty()
+        && language.len() <= MAX_LANGUAGE_TAG_LENGTH
+        && language
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+/// Range of rounds a guild may choose as its default
+pub const MIN_ROUNDS: i16 = 1;
+pub const MAX_ROUNDS: i16 = 10;
+
+/// Community-wide defaults for all channel lobbies in a guild
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GuildSettings {
+    pub guild_id: i64,
+    /// Language tag for the dictionary and UI (e.g., "en")
+    pub default_language: String,
+    pub default_rounds: i16,
+    pub profanity_filter: bool,
+    /// Game modes lobbies in this guild may start
+    pub allowed_modes: Vec<GameMode>,
+    /// Discord user who last changed the settings
+    pub updated_by: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GuildSettings {
+    /// Settings for a guild that hasn't configured anything yet
+    pub fn defaults(guild_id: i64, default_rounds: u8) -> Self {
+        Self {
+            guild_id,
+            default_language: "en".to_string(),
+            default_rounds: (default_rounds as i16).clamp(MIN_ROUNDS, MAX_ROUNDS),
+            profanity_filter: true,
+            allowed_modes: GameMode::ALL.to_vec(),
+            updated_by: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn allows_mode(&self, mode: &GameMode) -> bool {
+        self.allowed_modes.contains(mode)
+    }
+
+    /// Apply a partial update, validating every provided field first
+    pub fn apply(&mut self, update: GuildSettingsUpdate) -> Result<(), String> {
+        if let Some(language) = &update.default_language {
+            if !is_valid_language_tag(language) {
+                return Err(format!("'{}' is not a valid language tag", language));
+            }
+        }
+        if let Some(rounds) = update.default_rounds {
+            if !(MIN_ROUNDS..=MAX_ROUNDS).contains(&rounds) {
+                return Err(format!(
+                    "default_rounds must be between {} and {}",
+                    MIN_ROUNDS, MAX_ROUNDS
+                ));
+            }
+        }
+        if update.allowed_modes.as_ref().is_some_and(Vec::is_empty) {
+            return Err("At least one game mode must be allowed".to_string());
+        }
+
+        if let Some(language) = update.default_language {
+            self.default_language = language;
+        }
+        if let Some(rounds) = update.default_rounds {
+            self.default_rounds = rounds;
+        }
+        if let Some(profanity_filter) = update.profanity_filter {
+            self.profanity_filter = profanity_filter;
+        }
+        if let Some(modes) = update.allowed_modes {
+            self.allowed_modes.clear();
+            for mode in modes {
+                if !self.allowed_modes.contains(&mode) {
+                    self.allowed_modes.push(mode);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partial update to a guild's settings; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GuildSettingsUpdate {
+    pub default_language: Option<String>,
+    pub default_rounds: Option<i16>,
+    pub profanity_filter: Option<bool>,
+    pub allowed_modes: Option<Vec<GameMode>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_allow_every_mode() {
+        let settings = GuildSettings::defaults(123, 5);
+        assert_eq!(settings.default_rounds, 5);
+        assert!(settings.profanity_filter);
+        for mode in GameMode::ALL {
+            assert!(settings.allows_mode(&mode), "{:?} should be allowed", mode);
+        }
+    }
+
+    #[test]
+    fn test_apply_partial_update() {
+        let mut settings = GuildSettings::defaults(123, 5);
+        settings
+            .apply(GuildSettingsUpdate {
+                default_rounds: Some(3),
+                allowed_modes: Some(vec![GameMode::Multiplayer]),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(settings.default_rounds, 3);
+        assert_eq!(
+            settings.default_language, "en",
+            "Omitted fields are unchanged"
+        );
+        assert!(!settings.allows_mode(&GameMode::Adventure));
+    }
+
+    #[test]
+    fn test_apply_rejects_invalid_values_without_changes() {
+        let mut settings = GuildSettings::defaults(123, 5);
+        let result = settings.apply(GuildSettingsUpdate {
+            default_language: Some("pt-BR".to_string()),
+            default_rounds: Some(99),
+            ..Default::default()
+        });
+
+        assert!(result.is_err());
+        assert_eq!(
+            settings.default_language, "en",
+            "A rejected update should not partially apply"
+        );
+
+        assert!(settings
+            .apply(GuildSettingsUpdate {
+                allowed_modes: Some(vec![]),
+                ..Default::default()
+            })
+            .is_err());
+        assert!(settings
+            .apply(GuildSettingsUpdate {
+                default_language: Some("en_US; DROP".to_string()),
+                ..Default::default()
+            })
+            .is_err());
+    }
+}
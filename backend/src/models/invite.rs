@@ -0,0 +1,10 @@
+use sqlx::FromRow;
+
+/// A queued invite waiting for its recipient to connect (`lobby_invites`)
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingInvite {
+    pub from_user_id: i64,
+    pub from_username: String,
+    pub from_avatar_url: Option<String>,
+    pub lobby_code: String,
+}
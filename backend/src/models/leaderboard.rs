@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A player's aggregate results across finished games in one guild
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GuildStanding {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub games_played: i64,
+    pub wins: i64,
+    pub total_score: i64,
+}
+
+/// A player's best puzzle rush: the longest streak, with the most points among
+/// equal streaks
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PuzzleRushStanding {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub streak: i32,
+    pub points: i32,
+    pub finished_at: DateTime<Utc>,
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{migrate::Migration, FromRow};
+
+/// A row of `_sqlx_migrations`
+#[derive(Debug, Clone, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: DateTime<Utc>,
+    pub success: bool,
+    pub checksum: Vec<u8>,
+    /// Nanoseconds
+    pub execution_time: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    /// Embedded in this build but not applied yet
+    Pending,
+    /// Started but didn't finish; needs fixing by hand before startup can migrate
+    Failed,
+    /// Applied from a different version of the file than this build embeds
+    ChecksumMismatch,
+    /// Applied by a newer build (normal mid-deploy, while replicas are mixed)
+    Unknown,
+}
+
+/// One migration as this build and the database see it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub installed_on: Option<DateTime<Utc>>,
+    pub execution_ms: Option<i64>,
+}
+
+impl MigrationStatus {
+    /// Every migration either embedded or applied, by version
+    pub fn list(embedded: &[Migration], applied: &[AppliedMigration]) -> Vec<Self> {
+        let mut statuses: Vec<Self> = embedded
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|migration| {
+                let row = applied.iter().find(|a| a.version == migration.version);
+                let state = match row {
+                    None => MigrationState::Pending,
+                    Some(row) if !row.success => MigrationState::Failed,
+                    Some(row) if *row.checksum != *migration.checksum => {
+                        MigrationState::ChecksumMismatch
+                    }
+                    Some(_) => MigrationState::Applied,
+                };
+                Self {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    state,
+                    installed_on: row.map(|r| r.installed_on),
+                    execution_ms: row.map(|r| r.execution_time / 1_000_000),
+                }
+            })
+            .collect();
+
+        statuses.extend(
+            applied
+                .iter()
+                .filter(|row| !embedded.iter().any(|m| m.version == row.version))
+                .map(|row| Self {
+                    version: row.version,
+                    description: row.description.clone(),
+                    state: if row.success {
+                        MigrationState::Unknown
+                    } else {
+                        MigrationState::Failed
+                    },
+                    installed_on: Some(row.installed_on),
+                    execution_ms: Some(row.execution_time / 1_000_000),
+                }),
+        );
+        statuses.sort_by_key(|s| s.version);
+        statuses
+    }
+}
+
+/// Response for `GET /api/admin/migrations`
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationsOverview {
+    /// Every embedded migration is applied and nothing failed or changed
+    pub up_to_date: bool,
+    pub migrations: Vec<MigrationStatus>,
+}
+
+impl MigrationsOverview {
+    pub fn new(migrations: Vec<MigrationStatus>) -> Self {
+        let up_to_date = migrations
+            .iter()
+            .all(|m| matches!(m.state, MigrationState::Applied | MigrationState::Unknown));
+        Self {
+            up_to_date,
+            migrations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use sqlx::migrate::MigrationType;
+
+    use super::*;
+
+    fn embedded(version: i64, sql: &'static str) -> Migration {
+        Migration::new(
+            version,
+            Cow::Owned(format!("migration {}", version)),
+            MigrationType::Simple,
+            Cow::Borrowed(sql),
+            false,
+        )
+    }
+
+    fn applied(migration: &Migration, success: bool) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            description: migration.description.to_string(),
+            installed_on: Utc::now(),
+            success,
+            checksum: migration.checksum.to_vec(),
+            execution_time: 12_000_000,
+        }
+    }
+
+    #[test]
+    fn test_migration_states() {
+        let first = embedded(1, "CREATE TABLE a ();");
+        let second = embedded(2, "CREATE TABLE b ();");
+        let third = embedded(3, "CREATE TABLE c ();");
+        let fourth = embedded(4, "CREATE TABLE d ();");
+
+        let mut edited = applied(&second, true);
+        edited.checksum = embedded(2, "CREATE TABLE b2 ();").checksum.to_vec();
+        let newer = applied(&embedded(6, "CREATE TABLE f ();"), true);
+        let rows = vec![applied(&first, true), edited, applied(&third, false), newer];
+
+        let statuses = MigrationStatus::list(&[first, second, third, fourth], &rows);
+        let states: Vec<(i64, MigrationState)> =
+            statuses.iter().map(|s| (s.version, s.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                (1, MigrationState::Applied),
+                (2, MigrationState::ChecksumMismatch),
+                (3, MigrationState::Failed),
+                (4, MigrationState::Pending),
+                (6, MigrationState::Unknown),
+            ],
+            "Applied-only migrations are listed in version order after the embedded ones"
+        );
+        assert_eq!(statuses[0].execution_ms, Some(12));
+        assert_eq!(
+            statuses[3].installed_on, None,
+            "Pending migrations have no install time"
+        );
+        assert!(!MigrationsOverview::new(statuses).up_to_date);
+    }
+
+    #[test]
+    fn test_newer_migrations_are_up_to_date() {
+        let first = embedded(1, "CREATE TABLE a ();");
+        let rows = vec![
+            applied(&first, true),
+            applied(&embedded(2, "CREATE TABLE b ();"), true),
+        ];
+        let overview = MigrationsOverview::new(MigrationStatus::list(&[first], &rows));
+        assert!(
+            overview.up_to_date,
+            "An older replica mid-deploy is still up to date"
+        );
+    }
+}
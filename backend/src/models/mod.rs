@@ -1,24 +1,76 @@
+pub mod analytics;
+pub mod api_key;
 pub mod game;
+pub mod game_event;
+pub mod game_night;
 pub mod guild_profile;
+pub mod guild_settings;
+pub mod invite;
+pub mod leaderboard;
+pub mod migration;
+pub mod profile;
+pub mod puzzle;
+pub mod quest;
+pub mod season;
+pub mod shop;
 pub mod user;
+pub mod word_stats;
 
+pub use api_key::{ApiKey, ApiScope};
 pub use game::{
+    // Blitz mode
+    BlitzClock,
+    // Co-op mode
+    CoopOutcome,
+    CoopState,
+    // Duel mode
+    DuelState,
     // Database models
     Game,
     GameBoard,
+    GameBoardRevision,
     GameDbState,
+    GameListFilter,
+    GameListing,
     GameMode,
     GameMove,
+    GameMoveFilter,
+    GameOverReason,
     // Live game state (for WebSocket/in-memory)
     GamePlayer,
     GamePlayerRecord,
+    GamePlayerSummary,
+    GameSetup,
     GameState,
     GameStatus,
     // Grid types
     Grid,
     GridCell,
+    // Hot-seat mode
+    HotSeats,
     Multiplier,
+    PendingMove,
+    PlayerBoard,
+    PlayerFinalScore,
     Position,
+    // Puzzle rush mode
+    PuzzleRushState,
+    // Time attack mode
+    TimeAttackState,
+    TurnAdvance,
+};
+pub use game_event::{GameEvent, RefilledTile};
+pub use game_night::{
+    GameNight, GameNightAttendee, GameNightEvent, GameNightStatus, GameNightUpdate,
+    UpcomingGameNight,
 };
 pub use guild_profile::UserGuildProfile;
-pub use user::User;
+pub use guild_settings::{GuildSettings, GuildSettingsUpdate};
+pub use leaderboard::{GuildStanding, PuzzleRushStanding};
+pub use profile::{PlayerProfile, ProfileSettings, ProfileUpdate, UserProfile};
+pub use puzzle::{LobbyPuzzle, Puzzle, PuzzleDraft};
+pub use quest::{QuestEvent, QuestStatus};
+pub use season::{Season, SeasonFilter};
+pub use shop::{InventoryItem, ShopListing, Wallet};
+pub use user::{RecentPlayer, User, UserStats, VersusRecord, VersusWord};
+pub use word_stats::{WordHabits, WordStat, WordStatsSort, WordTraits};
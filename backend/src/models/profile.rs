@@ -0,0 +1,412 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{InventoryItem, User, Wallet};
+
+/// A career milestone that unlocks a title or badge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Achievement {
+    GamesPlayed(i32),
+    Wins(i32),
+    /// Best single word score
+    WordScore(i32),
+    /// Points across every game
+    TotalScore(i64),
+}
+
+impl Achievement {
+    pub fn earned_by(self, user: &User) -> bool {
+        match self {
+            Self::GamesPlayed(games) => user.total_games >= games,
+            Self::Wins(wins) => user.total_wins >= wins,
+            Self::WordScore(score) => user.highest_word_score >= score,
+            Self::TotalScore(score) => user.total_score >= score,
+        }
+    }
+}
+
+/// A title or badge players can equip once its achievement is earned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cosmetic {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub achievement: Achievement,
+}
+
+/// Display titles, shown next to a player's name
+pub const TITLES: &[Cosmetic] = &[
+    Cosmetic {
+        id: "newcomer",
+        name: "Newcomer",
+        achievement: Achievement::GamesPlayed(0),
+    },
+    Cosmetic {
+        id: "regular",
+        name: "Regular",
+        achievement: Achievement::GamesPlayed(25),
+    },
+    Cosmetic {
+        id: "veteran",
+        name: "Veteran",
+        achievement: Achievement::GamesPlayed(100),
+    },
+    Cosmetic {
+        id: "champion",
+        name: "Champion",
+        achievement: Achievement::Wins(10),
+    },
+    Cosmetic {
+        id: "grandmaster",
+        name: "Grandmaster",
+        achievement: Achievement::Wins(100),
+    },
+    Cosmetic {
+        id: "wordsmith",
+        name: "Wordsmith",
+        achievement: Achievement::WordScore(40),
+    },
+];
+
+/// Badges, shown as an icon on a player's card (icons are chosen by the client from the id)
+pub const BADGES: &[Cosmetic] = &[
+    Cosmetic {
+        id: "first_win",
+        name: "First Victory",
+        achievement: Achievement::Wins(1),
+    },
+    Cosmetic {
+        id: "big_word",
+        name: "Big Word",
+        achievement: Achievement::WordScore(25),
+    },
+    Cosmetic {
+        id: "spellbinder",
+        name: "Spellbinder",
+        achievement: Achievement::WordScore(60),
+    },
+    Cosmetic {
+        id: "ten_thousand",
+        name: "Ten Thousand",
+        achievement: Achievement::TotalScore(10_000),
+    },
+];
+
+fn find(catalog: &'static [Cosmetic], id: &str) -> Option<&'static Cosmetic> {
+    catalog.iter().find(|cosmetic| cosmetic.id == id)
+}
+
+/// A player's equipped cosmetics, as stored in `user_profiles`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserProfile {
+    pub user_id: i64,
+    /// Title id from `TITLES`
+    pub title: Option<String>,
+    /// `#rrggbb`
+    pub name_color: Option<String>,
+    /// Badge id from `BADGES`
+    pub badge: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UserProfile {
+    /// A profile with nothing equipped
+    pub fn empty(user_id: i64) -> Self {
+        Self {
+            user_id,
+            title: None,
+            name_color: None,
+            badge: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Apply a partial update, validating every provided field first. Titles and
+    /// badges must be unlocked by `user`'s stats.
+    pub fn apply(&mut self, update: ProfileUpdate, user: &User) -> Result<(), String> {
+        let title = update
+            .title
+            .map(|id| equippable(TITLES, "title", id, user))
+            .transpose()?;
+        let badge = update
+            .badge
+            .map(|id| equippable(BADGES, "badge", id, user))
+            .transpose()?;
+        let name_color = update.name_color.map(parse_color).transpose()?;
+
+        if let Some(title) = title {
+            self.title = title;
+        }
+        if let Some(badge) = badge {
+            self.badge = badge;
+        }
+        if let Some(name_color) = name_color {
+            self.name_color = name_color;
+        }
+        Ok(())
+    }
+
+    /// What other players see
+    pub fn display(&self) -> PlayerProfile {
+        PlayerProfile {
+            title: self
+                .title
+                .as_deref()
+                .and_then(|id| find(TITLES, id))
+                .map(|title| title.name.to_string()),
+            name_color: self.name_color.clone(),
+            badge: self.badge.clone(),
+        }
+    }
+}
+
+/// Validate an id to equip; an empty string unequips
+fn equippable(
+    catalog: &'static [Cosmetic],
+    kind: &str,
+    id: String,
+    user: &User,
+) -> Result<Option<String>, String> {
+    if id.is_empty() {
+        return Ok(None);
+    }
+    let cosmetic = find(catalog, &id).ok_or_else(|| format!("Unknown {} '{}'", kind, id))?;
+    if !cosmetic.achievement.earned_by(user) {
+        return Err(format!(
+            "The {} '{}' isn't unlocked yet",
+            kind, cosmetic.name
+        ));
+    }
+    Ok(Some(id))
+}
+
+/// Validate a `#rrggbb` name color (stored lowercase); an empty string clears it
+fn parse_color(color: String) -> Result<Option<String>, String> {
+    if color.is_empty() {
+        return Ok(None);
+    }
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!("'{}' is not a #rrggbb color", color));
+    }
+    Ok(Some(color.to_lowercase()))
+}
+
+/// Partial profile update; omitted fields are unchanged and an empty string
+/// unequips/clears a field
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProfileUpdate {
+    pub title: Option<String>,
+    pub name_color: Option<String>,
+    pub badge: Option<String>,
+}
+
+/// Cosmetics included with a player in lobby and game broadcasts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    /// Title display name (e.g., "Veteran")
+    pub title: Option<String>,
+    pub name_color: Option<String>,
+    /// Badge id (e.g., "first_win")
+    pub badge: Option<String>,
+}
+
+impl PlayerProfile {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.name_color.is_none() && self.badge.is_none()
+    }
+}
+
+/// A title or badge as listed in the profile settings API
+#[derive(Debug, Clone, Serialize)]
+pub struct CosmeticInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// What it takes to unlock
+    pub requirement: Achievement,
+    pub unlocked: bool,
+}
+
+/// Response of the profile settings API: what's equipped and what can be, plus
+/// the user's balances and the shop items they own
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSettings {
+    pub title: Option<String>,
+    pub name_color: Option<String>,
+    pub badge: Option<String>,
+    pub titles: Vec<CosmeticInfo>,
+    pub badges: Vec<CosmeticInfo>,
+    pub wallet: Wallet,
+    pub inventory: Vec<InventoryItem>,
+}
+
+impl ProfileSettings {
+    pub fn new(
+        profile: &UserProfile,
+        user: &User,
+        wallet: Wallet,
+        inventory: Vec<InventoryItem>,
+    ) -> Self {
+        let list = |catalog: &'static [Cosmetic]| {
+            catalog
+                .iter()
+                .map(|cosmetic| CosmeticInfo {
+                    id: cosmetic.id,
+                    name: cosmetic.name,
+                    requirement: cosmetic.achievement,
+                    unlocked: cosmetic.achievement.earned_by(user),
+                })
+                .collect()
+        };
+        Self {
+            title: profile.title.clone(),
+            name_color: profile.name_color.clone(),
+            badge: profile.badge.clone(),
+            titles: list(TITLES),
+            badges: list(BADGES),
+            wallet,
+            inventory,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_user(total_games: i32, total_wins: i32, highest_word_score: i32) -> User {
+        User {
+            user_id: 1,
+            username: "player".to_string(),
+            global_name: None,
+            avatar_url: None,
+            total_games,
+            total_wins,
+            total_score: 0,
+            highest_word_score,
+            highest_word: None,
+            refresh_token: None,
+            token_expires_at: None,
+            oauth_scopes: Vec::new(),
+            deletion_requested_at: None,
+            is_guest: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_apply_equips_unlocked_cosmetics() {
+        let user = test_user(30, 1, 10);
+        let mut profile = UserProfile::empty(1);
+        profile
+            .apply(
+                ProfileUpdate {
+                    title: Some("regular".to_string()),
+                    name_color: Some("#FF8800".to_string()),
+                    badge: Some("first_win".to_string()),
+                },
+                &user,
+            )
+            .unwrap();
+
+        assert_eq!(
+            profile.display(),
+            PlayerProfile {
+                title: Some("Regular".to_string()),
+                name_color: Some("#ff8800".to_string()),
+                badge: Some("first_win".to_string()),
+            },
+            "Broadcasts carry the title's display name"
+        );
+
+        profile
+            .apply(
+                ProfileUpdate {
+                    badge: Some(String::new()),
+                    ..Default::default()
+                },
+                &user,
+            )
+            .unwrap();
+        assert_eq!(profile.badge, None, "An empty string unequips");
+        assert_eq!(
+            profile.title.as_deref(),
+            Some("regular"),
+            "Omitted fields are unchanged"
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_locked_or_invalid_without_changes() {
+        let user = test_user(0, 0, 0);
+        let mut profile = UserProfile::empty(1);
+
+        let locked = profile.apply(
+            ProfileUpdate {
+                name_color: Some("#00ff00".to_string()),
+                title: Some("veteran".to_string()),
+                ..Default::default()
+            },
+            &user,
+        );
+        assert!(locked.is_err(), "Veteran needs 100 games");
+        assert_eq!(
+            profile.name_color, None,
+            "A rejected update should not partially apply"
+        );
+
+        for update in [
+            ProfileUpdate {
+                title: Some("emperor".to_string()),
+                ..Default::default()
+            },
+            ProfileUpdate {
+                badge: Some("first_win".to_string()),
+                ..Default::default()
+            },
+            ProfileUpdate {
+                name_color: Some("red".to_string()),
+                ..Default::default()
+            },
+            ProfileUpdate {
+                name_color: Some("#12345g".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(
+                profile.apply(update.clone(), &user).is_err(),
+                "{:?} should be rejected",
+                update
+            );
+        }
+
+        profile
+            .apply(
+                ProfileUpdate {
+                    title: Some("newcomer".to_string()),
+                    ..Default::default()
+                },
+                &user,
+            )
+            .expect("Newcomer is unlocked from the start");
+    }
+
+    #[test]
+    fn test_profile_settings_lists_unlocks() {
+        let user = test_user(5, 12, 45);
+        let settings =
+            ProfileSettings::new(&UserProfile::empty(1), &user, Wallet::default(), Vec::new());
+        let unlocked: Vec<&str> = settings
+            .titles
+            .iter()
+            .filter(|title| title.unlocked)
+            .map(|title| title.id)
+            .collect();
+        assert_eq!(unlocked, vec!["newcomer", "champion", "wordsmith"]);
+        assert_eq!(settings.badges.len(), BADGES.len());
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::Grid;
+use crate::game::grid::normalize_board;
+
+/// Longest puzzle title, in characters
+pub const MAX_PUZZLE_TITLE_LEN: usize = 64;
+
+/// A hand-crafted board shared by a player (`puzzles`)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Puzzle {
+    pub puzzle_id: i64,
+    #[serde(skip)]
+    pub author_id: i64,
+    pub author_name: String,
+    pub title: String,
+    /// The board, in the same form as a game's grid
+    pub grid: serde_json::Value,
+    /// When an admin featured it; the most recently featured is the puzzle of
+    /// the week
+    pub featured_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Puzzle {
+    /// The puzzle ready for a lobby to deal, or None if what's stored isn't a
+    /// board
+    pub fn for_lobby(&self) -> Option<LobbyPuzzle> {
+        let grid = serde_json::from_value(self.grid.clone())
+            .inspect_err(|e| tracing::warn!("Puzzle {} has an invalid grid: {}", self.puzzle_id, e))
+            .ok()?;
+        Some(LobbyPuzzle {
+            puzzle_id: self.puzzle_id,
+            title: self.title.clone(),
+            grid,
+        })
+    }
+}
+
+/// The puzzle a lobby's games are dealt, chosen by the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LobbyPuzzle {
+    pub puzzle_id: i64,
+    pub title: String,
+    /// Only revealed when a game starts on it
+    #[serde(skip)]
+    pub grid: Grid,
+}
+
+/// A puzzle as submitted to `POST /api/puzzles`
+#[derive(Debug, Deserialize)]
+pub struct PuzzleDraft {
+    pub title: String,
+    pub grid: Grid,
+}
+
+impl PuzzleDraft {
+    /// The trimmed title and the normalized board, or why they can't be used
+    pub fn validate(self) -> Result<(String, Grid), String> {
+        let title = self.title.trim();
+        if title.is_empty() || title.chars().count() > MAX_PUZZLE_TITLE_LEN {
+            return Err(format!(
+                "title must be 1 to {} characters",
+                MAX_PUZZLE_TITLE_LEN
+            ));
+        }
+        Ok((title.to_string(), normalize_board(self.grid)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(title: &str, letters: &str) -> PuzzleDraft {
+        let row: Vec<String> = letters
+            .chars()
+            .map(|letter| format!(r#"{{"letter":"{}","value":1,"multiplier":null}}"#, letter))
+            .collect();
+        let grid = vec![format!("[{}]", row.join(",")); letters.len()].join(",");
+        serde_json::from_str(&format!(r#"{{"title":"{}","grid":[{}]}}"#, title, grid)).unwrap()
+    }
+
+    #[test]
+    fn test_draft_validation() {
+        let (title, grid) = draft(" Vowel soup ", "aeiou").validate().unwrap();
+        assert_eq!(title, "Vowel soup");
+        assert_eq!(grid[4][0].letter, 'A');
+
+        assert!(draft("", "aeiou").validate().is_err());
+        assert!(
+            draft("Tiny", "abc").validate().is_err(),
+            "Boards are always full size"
+        );
+    }
+}
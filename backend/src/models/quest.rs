@@ -0,0 +1,435 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Daily quests offered at a time
+const DAILY_QUEST_COUNT: usize = 3;
+/// Weekly quests offered at a time
+const WEEKLY_QUEST_COUNT: usize = 2;
+
+/// How often a quest rotates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestPeriod {
+    /// Resets at midnight UTC
+    Daily,
+    /// Resets Monday at midnight UTC
+    Weekly,
+}
+
+/// What counts towards a quest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestGoal {
+    PlayGames,
+    WinGames,
+    PlayWords,
+    /// Points from words (every word's score adds up)
+    ScorePoints,
+    /// Words scoring at least this much
+    WordScoreAtLeast(i32),
+    /// Words at least this long
+    WordLengthAtLeast(usize),
+    /// Words containing any of these letters
+    WordWithLetters(&'static str),
+}
+
+/// Gems and XP granted when a quest is completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestReward {
+    pub gems: i64,
+    pub xp: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuestDef {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub period: QuestPeriod,
+    pub goal: QuestGoal,
+    /// Progress needed to complete the quest
+    pub target: i64,
+    pub reward: QuestReward,
+}
+
+const fn quest(
+    id: &'static str,
+    description: &'static str,
+    period: QuestPeriod,
+    goal: QuestGoal,
+    target: i64,
+    gems: i64,
+    xp: i64,
+) -> QuestDef {
+    QuestDef {
+        id,
+        description,
+        period,
+        goal,
+        target,
+        reward: QuestReward { gems, xp },
+    }
+}
+
+/// Pool the daily quests rotate through
+pub const DAILY_QUESTS: &[QuestDef] = &[
+    quest(
+        "daily_play_3",
+        "Play 3 games",
+        QuestPeriod::Daily,
+        QuestGoal::PlayGames,
+        3,
+        20,
+        50,
+    ),
+    quest(
+        "daily_win_1",
+        "Win a game",
+        QuestPeriod::Daily,
+        QuestGoal::WinGames,
+        1,
+        30,
+        75,
+    ),
+    quest(
+        "daily_word_40",
+        "Score a 40+ point word",
+        QuestPeriod::Daily,
+        QuestGoal::WordScoreAtLeast(40),
+        1,
+        30,
+        75,
+    ),
+    quest(
+        "daily_q_or_z",
+        "Play a word with a Q or Z",
+        QuestPeriod::Daily,
+        QuestGoal::WordWithLetters("QZ"),
+        1,
+        20,
+        50,
+    ),
+    quest(
+        "daily_long_word",
+        "Play a word of 7 or more letters",
+        QuestPeriod::Daily,
+        QuestGoal::WordLengthAtLeast(7),
+        1,
+        25,
+        60,
+    ),
+    quest(
+        "daily_score_200",
+        "Score 200 points",
+        QuestPeriod::Daily,
+        QuestGoal::ScorePoints,
+        200,
+        20,
+        50,
+    ),
+    quest(
+        "daily_words_10",
+        "Play 10 words",
+        QuestPeriod::Daily,
+        QuestGoal::PlayWords,
+        10,
+        15,
+        40,
+    ),
+];
+
+/// Pool the weekly quests rotate through
+pub const WEEKLY_QUESTS: &[QuestDef] = &[
+    quest(
+        "weekly_play_15",
+        "Play 15 games",
+        QuestPeriod::Weekly,
+        QuestGoal::PlayGames,
+        15,
+        100,
+        250,
+    ),
+    quest(
+        "weekly_win_5",
+        "Win 5 games",
+        QuestPeriod::Weekly,
+        QuestGoal::WinGames,
+        5,
+        150,
+        300,
+    ),
+    quest(
+        "weekly_score_1500",
+        "Score 1,500 points",
+        QuestPeriod::Weekly,
+        QuestGoal::ScorePoints,
+        1500,
+        100,
+        250,
+    ),
+    quest(
+        "weekly_word_60",
+        "Score a 60+ point word",
+        QuestPeriod::Weekly,
+        QuestGoal::WordScoreAtLeast(60),
+        1,
+        150,
+        300,
+    ),
+    quest(
+        "weekly_q_or_z_5",
+        "Play 5 words with a Q or Z",
+        QuestPeriod::Weekly,
+        QuestGoal::WordWithLetters("QZ"),
+        5,
+        120,
+        250,
+    ),
+];
+
+/// Something a player did that can count towards quests
+#[derive(Debug, Clone, Copy)]
+pub enum QuestEvent<'a> {
+    GameFinished { won: bool },
+    WordScored { word: &'a str, score: i32 },
+}
+
+impl QuestGoal {
+    /// How much an event advances this goal
+    pub fn progress(self, event: &QuestEvent) -> i64 {
+        match (self, *event) {
+            (Self::PlayGames, QuestEvent::GameFinished { .. }) => 1,
+            (Self::WinGames, QuestEvent::GameFinished { won }) => won as i64,
+            (Self::PlayWords, QuestEvent::WordScored { .. }) => 1,
+            (Self::ScorePoints, QuestEvent::WordScored { score, .. }) => score.max(0) as i64,
+            (Self::WordScoreAtLeast(min), QuestEvent::WordScored { score, .. }) => {
+                (score >= min) as i64
+            }
+            (Self::WordLengthAtLeast(len), QuestEvent::WordScored { word, .. }) => {
+                (word.chars().count() >= len) as i64
+            }
+            (Self::WordWithLetters(letters), QuestEvent::WordScored { word, .. }) => {
+                let word = word.to_uppercase();
+                letters.chars().any(|letter| word.contains(letter)) as i64
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// A quest on offer right now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveQuest {
+    pub def: &'static QuestDef,
+    /// Identifies the day or week, so progress resets when the quest comes back
+    pub period_key: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// `count` consecutive quests from the pool, starting further along each period
+fn rotate(pool: &'static [QuestDef], period_index: i64, count: usize) -> Vec<&'static QuestDef> {
+    let start = (period_index * count as i64).rem_euclid(pool.len() as i64) as usize;
+    (0..count.min(pool.len()))
+        .map(|offset| &pool[(start + offset) % pool.len()])
+        .collect()
+}
+
+/// The daily and weekly quests on offer at `now`
+pub fn active_quests(now: DateTime<Utc>) -> Vec<ActiveQuest> {
+    let today = now.date_naive();
+    let tomorrow = (today + Duration::days(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let day_index = today.num_days_from_ce() as i64;
+
+    let week = today.iso_week();
+    let days_into_week = today.weekday().num_days_from_monday() as i64;
+    let next_monday = (today + Duration::days(7 - days_into_week))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let monday = today - Duration::days(days_into_week);
+    let week_index = monday.num_days_from_ce() as i64 / 7;
+
+    let daily = rotate(DAILY_QUESTS, day_index, DAILY_QUEST_COUNT)
+        .into_iter()
+        .map(|def| ActiveQuest {
+            def,
+            period_key: today.format("%Y-%m-%d").to_string(),
+            expires_at: tomorrow,
+        });
+    let weekly = rotate(WEEKLY_QUESTS, week_index, WEEKLY_QUEST_COUNT)
+        .into_iter()
+        .map(|def| ActiveQuest {
+            def,
+            period_key: format!("{}-W{:02}", week.year(), week.week()),
+            expires_at: next_monday,
+        });
+    daily.chain(weekly).collect()
+}
+
+/// Progress to add to one of a user's quests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestUpdate {
+    pub quest: ActiveQuest,
+    pub increment: i64,
+}
+
+/// How an event advances the quests on offer at `now` (quests it doesn't touch are left out)
+pub fn quest_updates(event: &QuestEvent, now: DateTime<Utc>) -> Vec<QuestUpdate> {
+    active_quests(now)
+        .into_iter()
+        .filter_map(|quest| {
+            let increment = quest.def.goal.progress(event);
+            (increment > 0).then_some(QuestUpdate { quest, increment })
+        })
+        .collect()
+}
+
+/// A user's stored progress on one quest for one period
+#[derive(Debug, Clone, FromRow)]
+pub struct UserQuest {
+    pub quest_id: String,
+    pub period_key: String,
+    pub progress: i64,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A quest as listed by `GET /api/users/me/quests` and in `QuestCompleted`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestStatus {
+    pub quest_id: String,
+    pub description: String,
+    pub period: QuestPeriod,
+    pub progress: i64,
+    pub target: i64,
+    pub completed: bool,
+    pub reward: QuestReward,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl QuestStatus {
+    pub fn new(quest: &ActiveQuest, stored: Option<&UserQuest>) -> Self {
+        Self {
+            quest_id: quest.def.id.to_string(),
+            description: quest.def.description.to_string(),
+            period: quest.def.period,
+            progress: stored.map_or(0, |s| s.progress.min(quest.def.target)),
+            target: quest.def.target,
+            completed: stored.is_some_and(|s| s.completed_at.is_some()),
+            reward: quest.def.reward,
+            expires_at: quest.expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_goal_progress() {
+        let word = |word, score| QuestEvent::WordScored { word, score };
+        assert_eq!(QuestGoal::ScorePoints.progress(&word("CAT", 12)), 12);
+        assert_eq!(
+            QuestGoal::WordScoreAtLeast(40).progress(&word("QUIZ", 39)),
+            0
+        );
+        assert_eq!(
+            QuestGoal::WordScoreAtLeast(40).progress(&word("QUIZ", 40)),
+            1
+        );
+        assert_eq!(
+            QuestGoal::WordWithLetters("QZ").progress(&word("zebra", 10)),
+            1
+        );
+        assert_eq!(
+            QuestGoal::WordWithLetters("QZ").progress(&word("CAT", 10)),
+            0
+        );
+        assert_eq!(
+            QuestGoal::WordLengthAtLeast(7).progress(&word("MAGICAL", 10)),
+            1
+        );
+        assert_eq!(
+            QuestGoal::PlayGames.progress(&word("CAT", 10)),
+            0,
+            "Words don't count as games"
+        );
+
+        let finished = |won| QuestEvent::GameFinished { won };
+        assert_eq!(QuestGoal::PlayGames.progress(&finished(false)), 1);
+        assert_eq!(QuestGoal::WinGames.progress(&finished(false)), 0);
+        assert_eq!(QuestGoal::WinGames.progress(&finished(true)), 1);
+    }
+
+    #[test]
+    fn test_active_quests_rotate_by_period() {
+        // A Wednesday
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 15, 30, 0).unwrap();
+        let quests = active_quests(now);
+        let daily: Vec<&ActiveQuest> = quests
+            .iter()
+            .filter(|q| q.def.period == QuestPeriod::Daily)
+            .collect();
+        let weekly: Vec<&ActiveQuest> = quests
+            .iter()
+            .filter(|q| q.def.period == QuestPeriod::Weekly)
+            .collect();
+
+        assert_eq!(daily.len(), DAILY_QUEST_COUNT);
+        assert_eq!(weekly.len(), WEEKLY_QUEST_COUNT);
+        assert_eq!(daily[0].period_key, "2026-10-14");
+        assert_eq!(
+            daily[0].expires_at,
+            Utc.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap()
+        );
+        assert_eq!(weekly[0].period_key, "2026-W42");
+        assert_eq!(
+            weekly[0].expires_at,
+            Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+            "Weekly quests reset on Monday"
+        );
+
+        let tomorrow = active_quests(now + Duration::days(1));
+        assert_ne!(
+            tomorrow[0].def.id, daily[0].def.id,
+            "A new set of daily quests each day"
+        );
+        let same_week: Vec<&str> = tomorrow
+            .iter()
+            .filter(|q| q.def.period == QuestPeriod::Weekly)
+            .map(|q| q.def.id)
+            .collect();
+        assert_eq!(
+            same_week,
+            weekly.iter().map(|q| q.def.id).collect::<Vec<_>>(),
+            "Weekly quests stay put until Monday"
+        );
+    }
+
+    #[test]
+    fn test_quest_updates_only_touched_quests() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        let updates = quest_updates(&QuestEvent::GameFinished { won: false }, now);
+        assert!(updates
+            .iter()
+            .all(|u| u.quest.def.goal == QuestGoal::PlayGames && u.increment == 1));
+    }
+
+    #[test]
+    fn test_quest_status_caps_progress() {
+        let now = Utc::now();
+        let quest = &active_quests(now)[0];
+        let stored = UserQuest {
+            quest_id: quest.def.id.to_string(),
+            period_key: quest.period_key.clone(),
+            progress: quest.def.target + 50,
+            completed_at: Some(now),
+        };
+        let status = QuestStatus::new(quest, Some(&stored));
+        assert_eq!(status.progress, status.target);
+        assert!(status.completed);
+        assert!(!QuestStatus::new(quest, None).completed);
+    }
+}
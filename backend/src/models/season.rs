@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A leaderboard season
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Season {
+    pub season_id: i32,
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// When the end-of-season summary was posted
+    pub summarized_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Start and end of the season that follows one ending at `last_end`
+///
+/// Seasons run back to back. If the server was down long enough that a whole
+/// season would already be over, the next one starts now instead of replaying
+/// empty seasons.
+pub fn next_season_window(
+    last_end: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    length: Duration,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = match last_end {
+        Some(end) if now < end + length => end,
+        _ => now,
+    };
+    (start, start + length)
+}
+
+/// Which season a leaderboard covers (`?season=current|all|<id>`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SeasonFilter {
+    /// The season in progress
+    #[default]
+    Current,
+    /// Lifetime stats
+    All,
+    Season(i32),
+}
+
+impl FromStr for SeasonFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "current" => Ok(Self::Current),
+            "all" | "lifetime" => Ok(Self::All),
+            other => other
+                .parse()
+                .map(Self::Season)
+                .map_err(|_| format!("Unknown season '{}'", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for SeasonFilter {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_season_window() {
+        let length = Duration::days(30);
+        let now = Utc::now();
+
+        assert_eq!(
+            next_season_window(None, now, length),
+            (now, now + length),
+            "The first season starts now"
+        );
+
+        let just_ended = now - Duration::hours(1);
+        assert_eq!(
+            next_season_window(Some(just_ended), now, length),
+            (just_ended, just_ended + length),
+            "Seasons run back to back"
+        );
+
+        let long_ago = now - Duration::days(90);
+        assert_eq!(
+            next_season_window(Some(long_ago), now, length),
+            (now, now + length),
+            "Seasons missed while the server was down are skipped"
+        );
+    }
+
+    #[test]
+    fn test_season_filter_parsing() {
+        assert_eq!("current".parse(), Ok(SeasonFilter::Current));
+        assert_eq!("ALL".parse(), Ok(SeasonFilter::All));
+        assert_eq!("7".parse(), Ok(SeasonFilter::Season(7)));
+        assert!("spring".parse::<SeasonFilter>().is_err());
+        assert_eq!(SeasonFilter::default(), SeasonFilter::Current);
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// What a shop item changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    TileSkin,
+    BoardTheme,
+    ReactionPack,
+}
+
+/// An item in the shop catalog (`shop_items`)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShopItem {
+    pub item_id: String,
+    pub kind: ItemKind,
+    pub name: String,
+    /// Price in gems
+    pub price: i64,
+}
+
+/// An item a user owns
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InventoryItem {
+    pub item_id: String,
+    pub kind: ItemKind,
+    pub name: String,
+    pub purchased_at: DateTime<Utc>,
+}
+
+/// A user's gem (spendable) and XP (earned only) balances
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Wallet {
+    pub gems: i64,
+    pub xp: i64,
+}
+
+/// How a purchase went; anything but `Purchased` leaves the wallet untouched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurchaseOutcome {
+    /// The new balances after paying
+    Purchased(Wallet),
+    /// No such item, or it's no longer sold
+    NotFound,
+    AlreadyOwned,
+    InsufficientGems {
+        price: i64,
+        balance: i64,
+    },
+}
+
+/// A catalog item as shown to one user
+#[derive(Debug, Clone, Serialize)]
+pub struct ShopListing {
+    #[serde(flatten)]
+    pub item: ShopItem,
+    pub owned: bool,
+    /// Whether the user has enough gems (always false once owned)
+    pub affordable: bool,
+}
+
+impl ShopListing {
+    /// The catalog with what the user already owns and can afford marked
+    pub fn list(items: Vec<ShopItem>, inventory: &[InventoryItem], wallet: Wallet) -> Vec<Self> {
+        items
+            .into_iter()
+            .map(|item| {
+                let owned = inventory.iter().any(|owned| owned.item_id == item.item_id);
+                Self {
+                    affordable: !owned && wallet.gems >= item.price,
+                    owned,
+                    item,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(item_id: &str, price: i64) -> ShopItem {
+        ShopItem {
+            item_id: item_id.to_string(),
+            kind: ItemKind::TileSkin,
+            name: item_id.to_string(),
+            price,
+        }
+    }
+
+    #[test]
+    fn test_listing_marks_owned_and_affordable() {
+        let inventory = vec![InventoryItem {
+            item_id: "tile_wood".to_string(),
+            kind: ItemKind::TileSkin,
+            name: "Wooden Tiles".to_string(),
+            purchased_at: Utc::now(),
+        }];
+        let wallet = Wallet { gems: 150, xp: 0 };
+        let listings = ShopListing::list(
+            vec![
+                item("tile_wood", 100),
+                item("tile_marble", 150),
+                item("tile_neon", 250),
+            ],
+            &inventory,
+            wallet,
+        );
+
+        assert!(listings[0].owned);
+        assert!(!listings[0].affordable, "Owned items can't be bought again");
+        assert!(!listings[1].owned);
+        assert!(listings[1].affordable, "Exactly enough gems is enough");
+        assert!(!listings[2].affordable);
+    }
+
+    #[test]
+    fn test_listing_serializes_flat() {
+        let listing = &ShopListing::list(vec![item("tile_neon", 250)], &[], Wallet::default())[0];
+        let json = serde_json::to_value(listing).unwrap();
+        assert_eq!(json["item_id"], "tile_neon");
+        assert_eq!(json["kind"], "tile_skin");
+        assert_eq!(json["owned"], false);
+    }
+}
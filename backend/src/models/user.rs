@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::WordHabits;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
@@ -15,8 +18,16 @@ pub struct User {
     pub total_score: i64,
     pub highest_word_score: i32,
     pub highest_word: Option<String>,
+    /// Decrypted by the user queries; stored encrypted in `refresh_token_encrypted`
+    #[sqlx(rename = "refresh_token_encrypted")]
     pub refresh_token: Option<String>,
     pub token_expires_at: Option<DateTime<Utc>>,
+    /// OAuth scopes granted on the user's latest sign-in or token refresh
+    pub oauth_scopes: Vec<String>,
+    /// Set by `DELETE /api/users/me`; the account is deleted once the grace period passes
+    pub deletion_requested_at: Option<DateTime<Utc>>,
+    /// Signed in through `/api/auth/guest`; left out of stats and leaderboards
+    pub is_guest: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -32,17 +43,52 @@ pub struct UserStats {
     pub win_rate: f32,
     pub highest_word_score: i32,
     pub highest_word: Option<String>,
+    pub words: WordHabits,
+}
+
+/// Someone a user has finished a game with (`game_players` history)
+#[derive(Debug, Clone, FromRow)]
+pub struct RecentPlayer {
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub games_together: i64,
+    pub last_played_at: Option<DateTime<Utc>>,
+}
+
+/// Two players' lifetime record in finished games they played on opposing sides,
+/// from the first player's point of view
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct VersusRecord {
+    pub games: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+    /// The first player's score minus the second's, averaged over their games
+    pub average_margin: f64,
+}
+
+/// A high-scoring word from a game two players played against each other
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct VersusWord {
+    #[serde(skip)]
+    pub user_id: i64,
+    pub word: String,
+    pub score: i32,
+    pub game_id: Uuid,
 }
 
 impl User {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.oauth_scopes.iter().any(|granted| granted == scope)
+    }
+
     /// Get the best display name for this user
     /// Priority: global_name > username
     pub fn display_name(&self) -> &str {
         self.global_name.as_deref().unwrap_or(&self.username)
     }
 
-    // TODO: These methods will be used for user stats endpoints
-    #[allow(dead_code)]
     pub fn win_rate(&self) -> f32 {
         if self.total_games == 0 {
             0.0
@@ -51,8 +97,7 @@ impl User {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn to_stats(&self) -> UserStats {
+    pub fn to_stats(&self, words: WordHabits) -> UserStats {
         UserStats {
             user_id: self.user_id,
             username: self.username.clone(),
@@ -63,6 +108,7 @@ impl User {
             win_rate: self.win_rate(),
             highest_word_score: self.highest_word_score,
             highest_word: self.highest_word.clone(),
+            words,
         }
     }
 }
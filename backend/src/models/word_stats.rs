@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Community-wide usage of one word
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WordStat {
+    pub word: String,
+    pub plays: i64,
+    pub average_score: f64,
+    pub distinct_players: i32,
+}
+
+/// Ordering for the top words list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordStatsSort {
+    /// Most-played words first
+    #[default]
+    Plays,
+    /// Highest average score first
+    Score,
+}
+
+/// One player's word choices across every game they've played, kept up to date
+/// as their moves are saved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct WordHabits {
+    pub words_played: i64,
+    pub average_word_length: f64,
+    pub favorite_starting_letter: Option<String>,
+    /// Words with a Q in them
+    pub q_words: i64,
+    /// Words with a Z in them
+    pub z_words: i64,
+    pub longest_word: Option<String>,
+}
+
+/// What one scored word adds to its player's `WordHabits`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordTraits {
+    /// The word in upper case, as stored for `longest_word`
+    pub word: String,
+    pub letters: i64,
+    pub starting_letter: Option<String>,
+    pub has_q: bool,
+    pub has_z: bool,
+}
+
+impl WordTraits {
+    pub fn of(word: &str) -> Self {
+        let word = word.to_uppercase();
+        Self {
+            letters: word.chars().count() as i64,
+            starting_letter: word.chars().next().map(String::from),
+            has_q: word.contains('Q'),
+            has_z: word.contains('Z'),
+            word,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_traits() {
+        let traits = WordTraits::of("quiz");
+        assert_eq!(traits.word, "QUIZ");
+        assert_eq!(traits.letters, 4);
+        assert_eq!(traits.starting_letter.as_deref(), Some("Q"));
+        assert!(traits.has_q && traits.has_z);
+
+        let plain = WordTraits::of("CAT");
+        assert_eq!(plain.starting_letter.as_deref(), Some("C"));
+        assert!(!plain.has_q && !plain.has_z);
+    }
+
+    #[test]
+    fn test_word_traits_empty_word() {
+        let traits = WordTraits::of("");
+        assert_eq!(traits.letters, 0);
+        assert_eq!(traits.starting_letter, None);
+    }
+}
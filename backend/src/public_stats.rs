@@ -0,0 +1,233 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+
+use crate::{
+    error::ApiError,
+    websocket::limits::{client_ip, RateLimiter},
+    AppState,
+};
+
+/// Distinct public stats responses (path and query) kept in memory
+pub const PUBLIC_STATS_CACHE_SIZE: usize = 256;
+/// Responses bigger than this aren't cached
+const MAX_CACHED_BODY: usize = 1024 * 1024;
+
+/// Whether a path is one of the public stats endpoints
+///
+/// These can be fetched from any site (CORS allows every origin for them), so
+/// communities can embed them, and are rate limited per IP and cached.
+pub fn is_public_stats(path: &str) -> bool {
+    matches!(
+        path,
+        "/api/leaderboard"
+            | "/api/leaderboard/puzzle-rush"
+            | "/api/seasons"
+            | "/api/stats/words/top"
+    )
+}
+
+/// Public stats JSON by request path and query, evicting the oldest once full
+///
+/// Entries older than the TTL are fetched again, so standings and word stats are
+/// at most that stale.
+pub struct PublicStatsCache {
+    entries: DashMap<String, (Bytes, Instant)>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl PublicStatsCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    pub fn get(&self, key: &str, now: Instant) -> Option<Bytes> {
+        let entry = self.entries.get(key)?;
+        (now.duration_since(entry.1) < self.ttl).then(|| entry.0.clone())
+    }
+
+    pub fn insert(&self, key: String, body: Bytes, now: Instant) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.1)
+                .map(|entry| entry.key().clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (body, now));
+    }
+
+    /// Forget responses past the TTL
+    pub fn prune(&self, now: Instant) {
+        self.entries
+            .retain(|_, entry| now.duration_since(entry.1) < self.ttl);
+    }
+}
+
+/// Middleware for the public stats endpoints: limit each client IP to
+/// `PUBLIC_STATS_PER_MINUTE` requests, and answer from the cache when it can
+pub async fn limit_and_cache(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !admit(
+        &state.public_stats_limiter,
+        request.headers(),
+        peer,
+        state.config.limits.trust_forwarded_for,
+    ) {
+        return ApiError::RateLimited.into_response();
+    }
+
+    let key = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), |pq| pq.to_string());
+    let now = Instant::now();
+    if let Some(body) = state.public_stats_cache.get(&key, now) {
+        return json_response(&state, body);
+    }
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    match to_bytes(response.into_body(), MAX_CACHED_BODY).await {
+        Ok(body) => {
+            state.public_stats_cache.insert(key, body.clone(), now);
+            json_response(&state, body)
+        }
+        Err(e) => {
+            tracing::error!("Failed to buffer public stats response {}: {}", key, e);
+            ApiError::Internal.into_response()
+        }
+    }
+}
+
+/// Count a request against its client IP's limit, or return false if it's over
+fn admit(
+    limiter: &RateLimiter<IpAddr>,
+    headers: &HeaderMap,
+    peer: SocketAddr,
+    trust_forwarded_for: bool,
+) -> bool {
+    limiter.check(client_ip(headers, peer, trust_forwarded_for))
+}
+
+/// A cached JSON body, which browsers and CDNs may keep as long as we do
+fn json_response(state: &AppState, body: Bytes) -> Response {
+    let mut response = Response::new(Body::from(body));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    if let Ok(value) = HeaderValue::from_str(&format!(
+        "public, max-age={}",
+        state.config.server.public_stats_cache_secs
+    )) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expires_entries() {
+        let cache = PublicStatsCache::new(10, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert("/api/seasons".to_string(), Bytes::from_static(b"[]"), now);
+
+        assert!(cache
+            .get("/api/seasons", now + Duration::from_secs(59))
+            .is_some());
+        assert!(
+            cache.get("/api/seasons?limit=5", now).is_none(),
+            "The query is part of the key"
+        );
+        assert!(cache
+            .get("/api/seasons", now + Duration::from_secs(60))
+            .is_none());
+
+        cache.prune(now + Duration::from_secs(60));
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let cache = PublicStatsCache::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        cache.insert("a".to_string(), Bytes::from_static(b"1"), now);
+        cache.insert(
+            "b".to_string(),
+            Bytes::from_static(b"2"),
+            now + Duration::from_secs(1),
+        );
+        cache.insert(
+            "c".to_string(),
+            Bytes::from_static(b"3"),
+            now + Duration::from_secs(2),
+        );
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get("a", now).is_none());
+    }
+
+    #[test]
+    fn test_forged_forwarded_for_shares_the_real_ips_limit() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let proxy = SocketAddr::from(([10, 0, 0, 1], 443));
+        let forwarded = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-forwarded-for", value.parse().unwrap());
+            headers
+        };
+
+        assert!(admit(&limiter, &forwarded("203.0.113.7"), proxy, true));
+        // The same client again, claiming to be someone new each time
+        for forged in ["1.2.3.4", "5.6.7.8"] {
+            let headers = forwarded(&format!("{}, 203.0.113.7", forged));
+            assert!(
+                !admit(&limiter, &headers, proxy, true),
+                "{} shouldn't get a fresh limit",
+                forged
+            );
+        }
+        assert!(
+            admit(&limiter, &forwarded("198.51.100.9"), proxy, true),
+            "Other clients behind the proxy have their own limit"
+        );
+    }
+
+    #[test]
+    fn test_public_stats_paths() {
+        assert!(is_public_stats("/api/leaderboard"));
+        assert!(is_public_stats("/api/stats/words/top"));
+        assert!(is_public_stats("/api/leaderboard/puzzle-rush"));
+        assert!(!is_public_stats("/api/guilds/1/leaderboard"));
+        assert!(!is_public_stats("/api/users/me/stats"));
+    }
+}
@@ -0,0 +1,168 @@
+use super::{
+    font::{self, ADVANCE},
+    png::{self, Image},
+};
+
+pub type Rgb = [u8; 3];
+
+/// An RGB image being drawn on; anything drawn off its edges is clipped
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: background.repeat(width as usize * height as usize),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn pixel(&self, x: u32, y: u32) -> Rgb {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Mix `color` into a pixel; `alpha` 255 paints over it entirely
+    fn blend(&mut self, x: i32, y: i32, color: Rgb, alpha: u8) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for (channel, value) in self.pixels[i..i + 3].iter_mut().zip(color) {
+            *channel = ((value as u32 * alpha as u32 + *channel as u32 * (255 - alpha as u32))
+                / 255) as u8;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: Rgb) {
+        for py in y..y + height as i32 {
+            for px in x..x + width as i32 {
+                self.blend(px, py, color, 255);
+            }
+        }
+    }
+
+    /// Fill the circle inscribed in the `size` square whose top left is (`x`, `y`)
+    pub fn fill_circle(&mut self, x: i32, y: i32, size: u32, color: Rgb) {
+        self.each_circle_pixel(x, y, size, |canvas, px, py, _, _| {
+            canvas.blend(px, py, color, 255)
+        });
+    }
+
+    /// Draw `image` scaled into a `size` circle, the way Discord shows avatars
+    pub fn draw_circle_image(&mut self, x: i32, y: i32, size: u32, image: &Image) {
+        self.each_circle_pixel(x, y, size, |canvas, px, py, dx, dy| {
+            let [r, g, b, a] = image.pixel(dx * image.width / size, dy * image.height / size);
+            canvas.blend(px, py, [r, g, b], a);
+        });
+    }
+
+    /// Call `paint` with the canvas and absolute then circle-relative coordinates
+    /// of every pixel inside a circle
+    fn each_circle_pixel(
+        &mut self,
+        x: i32,
+        y: i32,
+        size: u32,
+        mut paint: impl FnMut(&mut Self, i32, i32, u32, u32),
+    ) {
+        let radius = size as f64 / 2.0;
+        for dy in 0..size {
+            for dx in 0..size {
+                let (cx, cy) = (dx as f64 + 0.5 - radius, dy as f64 + 0.5 - radius);
+                if cx * cx + cy * cy <= radius * radius {
+                    paint(self, x + dx as i32, y + dy as i32, dx, dy);
+                }
+            }
+        }
+    }
+
+    /// Draw text with the built-in font, each font pixel `scale` pixels square,
+    /// and return how wide it came out
+    pub fn text(&mut self, x: i32, y: i32, text: &str, scale: u32, color: Rgb) -> u32 {
+        let text = font::normalize(text);
+        for (index, c) in text.chars().enumerate() {
+            let left = x + (index as u32 * ADVANCE * scale) as i32;
+            for gy in 0..font::GLYPH_HEIGHT {
+                for gx in 0..font::GLYPH_WIDTH {
+                    if font::pixel(c, gx, gy) {
+                        self.fill_rect(
+                            left + (gx * scale) as i32,
+                            y + (gy * scale) as i32,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+        font::text_width(&text, scale)
+    }
+
+    pub fn into_png(self) -> Vec<u8> {
+        png::encode_rgb(self.width, self.height, &self.pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+
+    #[test]
+    fn test_shapes_are_clipped() {
+        let mut canvas = Canvas::new(4, 4, BLACK);
+        canvas.fill_rect(-2, -2, 4, 4, WHITE);
+        assert_eq!(canvas.pixel(1, 1), WHITE);
+        assert_eq!(canvas.pixel(2, 2), BLACK);
+
+        canvas.fill_circle(2, 2, 10, WHITE);
+        assert_eq!(canvas.pixel(3, 3), WHITE, "Drawing off the edge is fine");
+    }
+
+    #[test]
+    fn test_circles_leave_their_corners() {
+        let mut canvas = Canvas::new(10, 10, BLACK);
+        canvas.fill_circle(0, 0, 10, WHITE);
+        assert_eq!(canvas.pixel(5, 5), WHITE);
+        assert_eq!(canvas.pixel(0, 0), BLACK);
+        assert_eq!(canvas.pixel(9, 9), BLACK);
+    }
+
+    #[test]
+    fn test_images_blend_by_alpha() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            rgba: vec![255, 0, 0, 255, 255, 255, 255, 0],
+        };
+        let mut canvas = Canvas::new(20, 20, BLACK);
+        canvas.draw_circle_image(0, 0, 20, &image);
+        assert_eq!(canvas.pixel(5, 10), [255, 0, 0]);
+        assert_eq!(
+            canvas.pixel(15, 10),
+            BLACK,
+            "Transparent pixels leave the background"
+        );
+    }
+
+    #[test]
+    fn test_text_is_drawn() {
+        let mut canvas = Canvas::new(20, 10, BLACK);
+        assert_eq!(canvas.text(0, 0, "-", 2, WHITE), 10);
+        assert_eq!(canvas.pixel(0, 6), WHITE, "The dash is on the fourth row");
+        assert_eq!(canvas.pixel(0, 0), BLACK);
+
+        let png = canvas.into_png();
+        let image = png::decode(&png).unwrap();
+        assert_eq!(image.pixel(9, 7), [255, 255, 255, 255]);
+    }
+}
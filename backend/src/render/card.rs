@@ -0,0 +1,231 @@
+use super::{
+    canvas::{Canvas, Rgb},
+    font,
+    png::Image,
+};
+use crate::game::result_card::{CardStanding, ResultCard};
+
+/// Half of the 1200x630 size link previews are designed for
+pub const CARD_WIDTH: u32 = 600;
+pub const CARD_HEIGHT: u32 = 315;
+
+const MARGIN: i32 = 24;
+const AVATAR_SIZE: u32 = 96;
+const STANDINGS_LEFT: i32 = MARGIN + AVATAR_SIZE as i32 + 24;
+const STANDINGS_TOP: i32 = 100;
+const STANDING_HEIGHT: i32 = 22;
+/// Scoreboard lines that fit above the best word; the rest are summed up
+const MAX_STANDINGS: usize = 5;
+const TILE_SIZE: u32 = 32;
+const MAX_TILES: usize = 12;
+
+const BACKGROUND: Rgb = [0x1e, 0x1f, 0x22];
+const ACCENT: Rgb = [0x58, 0x65, 0xf2];
+const TEXT: Rgb = [0xf2, 0xf3, 0xf5];
+const MUTED: Rgb = [0x94, 0x9b, 0xa4];
+const GOLD: Rgb = [0xf0, 0xb2, 0x32];
+const TILE: Rgb = [0xf4, 0xe4, 0xc1];
+const TILE_TEXT: Rgb = [0x3b, 0x2f, 0x1e];
+/// Backgrounds for players without an avatar, picked by user ID
+const AVATAR_COLORS: [Rgb; 5] = [
+    [0x58, 0x65, 0xf2],
+    [0x3b, 0xa5, 0x5d],
+    [0xfa, 0xa6, 0x1a],
+    [0xed, 0x42, 0x45],
+    [0xeb, 0x45, 0x9f],
+];
+
+/// Whose avatar goes on the card: the winner, or the top scorer when nobody won
+/// outright
+pub fn featured_player(card: &ResultCard) -> Option<&CardStanding> {
+    card.standings
+        .iter()
+        .find(|standing| standing.winner)
+        .or(card.standings.first())
+}
+
+/// Draw a result card as an image for link previews and webhooks
+///
+/// `avatar` is the featured player's; without one their initial is drawn instead.
+pub fn render_result_card(card: &ResultCard, avatar: Option<&Image>) -> Vec<u8> {
+    let mut canvas = Canvas::new(CARD_WIDTH, CARD_HEIGHT, BACKGROUND);
+    let content_width = CARD_WIDTH - 2 * MARGIN as u32;
+    canvas.fill_rect(0, 0, CARD_WIDTH, 6, ACCENT);
+    canvas.text(MARGIN, 22, "Spell Cast", 2, MUTED);
+    // Long titles drop to a smaller size before anything is cut off them
+    let title = font::normalize(&card.title);
+    if font::text_width(&title, 3) <= content_width {
+        canvas.text(MARGIN, 46, &title, 3, TEXT);
+    } else {
+        let title = font::fit(&title, 2, content_width);
+        canvas.text(MARGIN, 50, &title, 2, TEXT);
+    }
+
+    if let Some(player) = featured_player(card) {
+        draw_avatar(&mut canvas, player, avatar);
+    }
+    draw_standings(&mut canvas, &card.standings);
+
+    if let Some(best) = &card.best_word {
+        canvas.text(MARGIN, 232, "Best word", 2, MUTED);
+        let letters: Vec<char> = font::normalize(&best.word)
+            .chars()
+            .take(MAX_TILES)
+            .collect();
+        let mut x = MARGIN;
+        for letter in letters {
+            canvas.fill_rect(x, 252, TILE_SIZE, TILE_SIZE, TILE);
+            canvas.text(x + 9, 257, &letter.to_string(), 3, TILE_TEXT);
+            x += TILE_SIZE as i32 + 4;
+        }
+        let by = format!("{} pts by {}", best.score, best.username);
+        let room = (CARD_WIDTH as i32 - MARGIN - x - 8).max(0) as u32;
+        let by = font::fit(&font::normalize(&by), 2, room);
+        canvas.text(x + 8, 263, &by, 2, GOLD);
+    }
+
+    canvas.into_png()
+}
+
+fn draw_avatar(canvas: &mut Canvas, player: &CardStanding, avatar: Option<&Image>) {
+    let top = STANDINGS_TOP - 4;
+    match avatar {
+        Some(image) => canvas.draw_circle_image(MARGIN, top, AVATAR_SIZE, image),
+        None => {
+            let color = AVATAR_COLORS[player.user_id.unsigned_abs() as usize % AVATAR_COLORS.len()];
+            canvas.fill_circle(MARGIN, top, AVATAR_SIZE, color);
+            let initial = font::normalize(&player.username)
+                .chars()
+                .find(|c| c.is_alphanumeric())
+                .unwrap_or('?')
+                .to_string();
+            let scale = 6;
+            let x = MARGIN + (AVATAR_SIZE - font::text_width(&initial, scale)) as i32 / 2;
+            let y = top + (AVATAR_SIZE - font::GLYPH_HEIGHT * scale) as i32 / 2;
+            canvas.text(x, y, &initial, scale, TEXT);
+        }
+    }
+}
+
+fn draw_standings(canvas: &mut Canvas, standings: &[CardStanding]) {
+    let right = CARD_WIDTH as i32 - MARGIN;
+    let shown = if standings.len() > MAX_STANDINGS {
+        MAX_STANDINGS - 1
+    } else {
+        standings.len()
+    };
+
+    for (index, standing) in standings.iter().take(shown).enumerate() {
+        let y = STANDINGS_TOP + index as i32 * STANDING_HEIGHT;
+        let color = if standing.winner { GOLD } else { TEXT };
+        let score = standing.score.to_string();
+        let score_width = font::text_width(&score, 2);
+        canvas.text(right - score_width as i32, y, &score, 2, color);
+
+        let name_room = (right - STANDINGS_LEFT) as u32 - score_width - 24;
+        let name = format!("{}. {}", standing.rank, standing.username);
+        let name = font::fit(&font::normalize(&name), 2, name_room);
+        canvas.text(STANDINGS_LEFT, y, &name, 2, color);
+    }
+    if shown < standings.len() {
+        let y = STANDINGS_TOP + shown as i32 * STANDING_HEIGHT;
+        let more = format!("+{} more", standings.len() - shown);
+        canvas.text(STANDINGS_LEFT, y, &more, 2, MUTED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::result_card::CardWord,
+        models::GameMode,
+        render::png::{self, Image},
+    };
+
+    fn standing(rank: u32, user_id: i64, score: i32, winner: bool) -> CardStanding {
+        CardStanding {
+            rank,
+            user_id,
+            username: format!("player{}", user_id),
+            score,
+            winner,
+        }
+    }
+
+    fn card(standings: Vec<CardStanding>) -> ResultCard {
+        ResultCard {
+            game_id: "game".to_string(),
+            game_mode: GameMode::Multiplayer,
+            finished_at: None,
+            title: "player1 wins with 120 points".to_string(),
+            description: None,
+            standings,
+            best_word: Some(CardWord {
+                user_id: 2,
+                username: "player2".to_string(),
+                word: "QUIZ".to_string(),
+                score: 44,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_featured_player_is_the_winner() {
+        let win = card(vec![standing(1, 2, 90, false), standing(2, 1, 80, true)]);
+        assert_eq!(featured_player(&win).unwrap().user_id, 1);
+
+        let coop = card(vec![standing(1, 3, 50, false), standing(2, 4, 40, false)]);
+        assert_eq!(
+            featured_player(&coop).unwrap().user_id,
+            3,
+            "Without a winner the top scorer is shown"
+        );
+    }
+
+    #[test]
+    fn test_cards_render_to_png() {
+        let standings = (1..=8).map(|id| standing(id as u32, id, 100 - id as i32, id == 1));
+        let png = render_result_card(&card(standings.collect()), None);
+        let image = png::decode(&png).unwrap();
+        assert_eq!((image.width, image.height), (CARD_WIDTH, CARD_HEIGHT));
+        assert_eq!(
+            image.pixel(0, 0)[..3],
+            ACCENT,
+            "The accent bar runs along the top"
+        );
+        assert_eq!(
+            image.pixel(CARD_WIDTH - 1, CARD_HEIGHT - 1)[..3],
+            BACKGROUND
+        );
+        assert_eq!(
+            image.pixel(MARGIN as u32 + 2, 254)[..3],
+            TILE,
+            "The best word is laid out in tiles"
+        );
+    }
+
+    #[test]
+    fn test_avatars_are_drawn() {
+        let red = Image {
+            width: 1,
+            height: 1,
+            rgba: vec![255, 0, 0, 255],
+        };
+        let centre = (
+            MARGIN as u32 + AVATAR_SIZE / 2,
+            STANDINGS_TOP as u32 - 4 + AVATAR_SIZE / 2,
+        );
+
+        let card = card(vec![standing(1, 1, 120, true)]);
+        let with_avatar = png::decode(&render_result_card(&card, Some(&red))).unwrap();
+        assert_eq!(with_avatar.pixel(centre.0, centre.1), [255, 0, 0, 255]);
+
+        let without = png::decode(&render_result_card(&card, None)).unwrap();
+        assert_eq!(
+            without.pixel(centre.0 - 40, centre.1)[..3],
+            AVATAR_COLORS[1],
+            "Players without an avatar get a coloured circle"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Server-side image rendering, in pure Rust so it needs nothing installed
+
+mod canvas;
+mod card;
+mod font;
+pub mod png;
+
+use std::time::Instant;
+
+use axum::body::Bytes;
+use dashmap::DashMap;
+use uuid::Uuid;
+
+pub use card::{featured_player, render_result_card};
+
+/// Rendered result cards kept in memory
+pub const RESULT_CARD_CACHE_SIZE: usize = 256;
+
+/// Rendered result card PNGs by game, evicting the least recently used once full
+///
+/// A finished game's result doesn't change, so entries never expire.
+pub struct ResultCardCache {
+    entries: DashMap<Uuid, (Bytes, Instant)>,
+    capacity: usize,
+}
+
+impl ResultCardCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: DashMap::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn get(&self, game_id: Uuid, now: Instant) -> Option<Bytes> {
+        let mut entry = self.entries.get_mut(&game_id)?;
+        entry.1 = now;
+        Some(entry.0.clone())
+    }
+
+    pub fn insert(&self, game_id: Uuid, png: Bytes, now: Instant) {
+        if !self.entries.contains_key(&game_id) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.1)
+                .map(|entry| *entry.key());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(game_id, (png, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = ResultCardCache::new(2);
+        let now = Instant::now();
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        cache.insert(first, Bytes::from_static(b"1"), now);
+        cache.insert(
+            second,
+            Bytes::from_static(b"2"),
+            now + Duration::from_secs(1),
+        );
+        cache.get(first, now + Duration::from_secs(2));
+
+        cache.insert(
+            third,
+            Bytes::from_static(b"3"),
+            now + Duration::from_secs(3),
+        );
+        assert_eq!(cache.entries.len(), 2);
+        assert!(
+            cache.get(second, now).is_none(),
+            "The second card was used least recently"
+        );
+        assert_eq!(cache.get(first, now).unwrap(), Bytes::from_static(b"1"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::io::{Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Largest image `decode` accepts, so a hostile file can't make us allocate much
+const MAX_DECODE_DIMENSION: u32 = 1024;
+
+/// A decoded image, 4 bytes (RGBA) per pixel, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.rgba[i],
+            self.rgba[i + 1],
+            self.rgba[i + 2],
+            self.rgba[i + 3],
+        ]
+    }
+}
+
+/// Encode 3-byte RGB pixels, row by row, as a PNG
+pub fn encode_rgb(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgb.chunks(stride).take(height as usize) {
+        raw.push(0); // No filter
+        raw.extend_from_slice(row);
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec can't fail
+    let _ = encoder.write_all(&raw);
+    let compressed = encoder.finish().unwrap_or_default();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, not interlaced
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &compressed);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+/// Decode a PNG such as a Discord avatar
+///
+/// Handles the non-interlaced 8-bit formats (greyscale, RGB, palette, each with
+/// or without alpha); anything else, or anything malformed, is None.
+pub fn decode(png: &[u8]) -> Option<Image> {
+    let mut rest = png.strip_prefix(&SIGNATURE)?;
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut transparency: Vec<u8> = Vec::new();
+    let mut compressed = Vec::new();
+
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[0..4].try_into().ok()?) as usize;
+        let kind = &rest[4..8];
+        let data = rest.get(8..8 + len)?;
+        match kind {
+            b"IHDR" if data.len() == 13 => header = Some(data.to_vec()),
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => transparency = data.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + len..)?;
+    }
+
+    let header = header?;
+    let width = u32::from_be_bytes(header[0..4].try_into().ok()?);
+    let height = u32::from_be_bytes(header[4..8].try_into().ok()?);
+    let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+    if width == 0
+        || height == 0
+        || width > MAX_DECODE_DIMENSION
+        || height > MAX_DECODE_DIMENSION
+        || bit_depth != 8
+        || interlace != 0
+    {
+        return None;
+    }
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        _ => return None,
+    };
+
+    let stride = width as usize * channels;
+    let expected = (stride + 1) * height as usize;
+    let mut raw = Vec::with_capacity(expected);
+    ZlibDecoder::new(compressed.as_slice())
+        .take(expected as u64)
+        .read_to_end(&mut raw)
+        .ok()?;
+    if raw.len() != expected {
+        return None;
+    }
+    let pixels = unfilter(&raw, stride, channels)?;
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for px in pixels.chunks_exact(channels) {
+        let pixel = match color_type {
+            0 => [px[0], px[0], px[0], 255],
+            2 => [px[0], px[1], px[2], 255],
+            3 => {
+                let [r, g, b] = *palette.get(px[0] as usize)?;
+                let alpha = transparency.get(px[0] as usize).copied().unwrap_or(255);
+                [r, g, b, alpha]
+            }
+            4 => [px[0], px[0], px[0], px[1]],
+            _ => [px[0], px[1], px[2], px[3]],
+        };
+        rgba.extend_from_slice(&pixel);
+    }
+    Some(Image {
+        width,
+        height,
+        rgba,
+    })
+}
+
+/// Undo each row's filter, leaving the bare pixel bytes
+fn unfilter(raw: &[u8], stride: usize, bpp: usize) -> Option<Vec<u8>> {
+    let mut out = vec![0u8; raw.len() / (stride + 1) * stride];
+    for (y, row) in raw.chunks_exact(stride + 1).enumerate() {
+        let (filter, line) = (row[0], &row[1..]);
+        let (done, current) = out.split_at_mut(y * stride);
+        let previous = (y > 0).then(|| &done[(y - 1) * stride..]);
+        let current = &mut current[..stride];
+        for x in 0..stride {
+            let left = if x >= bpp { current[x - bpp] } else { 0 };
+            let up = previous.map_or(0, |p| p[x]);
+            let up_left = match previous {
+                Some(p) if x >= bpp => p[x - bpp],
+                _ => 0,
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            };
+            current[x] = line[x].wrapping_add(predicted);
+        }
+    }
+    Some(out)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let (pa, pb, pc) = (
+        (p - left as i16).abs(),
+        (p - up as i16).abs(),
+        (p - up_left as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoded_images_decode() {
+        let rgb: Vec<u8> = (0..4 * 3 * 3).map(|i| (i * 7) as u8).collect();
+        let png = encode_rgb(4, 3, &rgb);
+        assert!(png.starts_with(&SIGNATURE));
+
+        let image = decode(&png).unwrap();
+        assert_eq!((image.width, image.height), (4, 3));
+        assert_eq!(image.pixel(0, 0), [0, 7, 14, 255]);
+        assert_eq!(
+            image.pixel(3, 2),
+            [231, 238, 245, 255],
+            "Every pixel survives the round trip"
+        );
+    }
+
+    #[test]
+    fn test_filters_are_undone() {
+        // Two 2x1 greyscale rows: the first filtered Sub, the second Up
+        let raw = [1, 10, 5, 2, 1, 1];
+        assert_eq!(unfilter(&raw, 2, 1).unwrap(), vec![10, 15, 11, 16]);
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert!(unfilter(&[9, 0, 0], 2, 1).is_none(), "Unknown filter type");
+    }
+
+    #[test]
+    fn test_rejects_what_it_cant_read() {
+        assert!(decode(b"not a png").is_none());
+        let mut png = encode_rgb(1, 1, &[0, 0, 0]);
+        png.truncate(30);
+        assert!(decode(&png).is_none(), "Truncated image");
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Datelike, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    auth, db,
+    error::ApiError,
+    game::session::flush_game_session,
+    models::{
+        analytics::{AnalyticsOverview, AnalyticsSummary, DailyActivity},
+        migration::{MigrationStatus, MigrationsOverview},
+        GameEvent,
+    },
+    websocket::admin as console,
+    AppState,
+};
+
+const DEFAULT_ANALYTICS_DAYS: i64 = 30;
+const MAX_ANALYTICS_DAYS: i64 = 365;
+
+#[derive(Debug, Deserialize)]
+pub struct OverviewQuery {
+    /// How many days back to cover, including today
+    pub days: Option<i64>,
+}
+
+/// Outcome of a game repair, mirroring the admin console's confirmations
+#[derive(Debug, Default, Serialize)]
+pub struct GameRepairResponse {
+    pub lobby_id: String,
+    pub game_id: String,
+    /// Checksum of the newly dealt board (reset-board only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board_checksum: Option<String>,
+    /// Unflushed moves dropped while rebuilding (rebuild only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discarded_moves: Option<usize>,
+}
+
+fn require_admin(state: &AppState, user: &auth::AuthenticatedUser) -> Result<(), ApiError> {
+    if state.config.security.is_admin(user.user_id) {
+        Ok(())
+    } else {
+        Err(ApiError::NotAdmin)
+    }
+}
+
+/// Daily players, games and game length plus retention cohorts, for the ops dashboard
+///
+/// Reads the analytics views, so figures are as of the last scheduled refresh.
+/// Admins only.
+pub async fn analytics_overview(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OverviewQuery>,
+) -> Result<Json<AnalyticsOverview>, ApiError> {
+    require_admin(&state, &user)?;
+
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_ANALYTICS_DAYS)
+        .clamp(1, MAX_ANALYTICS_DAYS);
+    let today = Utc::now().date_naive();
+    let since = today - Duration::days(days - 1);
+    // Cohorts are whole weeks, so include the one the window starts in
+    let first_week = since - Duration::days(since.weekday().num_days_from_monday() as i64);
+
+    let daily = DailyActivity::fill_gaps(
+        db::queries::get_daily_activity(&state.db, since).await?,
+        since,
+        today,
+    );
+    let cohorts = db::queries::get_retention_cohorts(&state.db, first_week).await?;
+
+    Ok(Json(AnalyticsOverview {
+        since,
+        summary: AnalyticsSummary::new(&daily),
+        daily,
+        cohorts,
+    }))
+}
+
+/// Every migration this build embeds or the database has applied, with its state
+///
+/// Admins only.
+pub async fn migrations(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<MigrationsOverview>, ApiError> {
+    require_admin(&state, &user)?;
+
+    let applied = db::queries::list_applied_migrations(&state.db).await?;
+    let statuses = MigrationStatus::list(db::MIGRATOR.iter().as_slice(), &applied);
+    Ok(Json(MigrationsOverview::new(statuses)))
+}
+
+/// A game's event log, oldest first, for auditing what happened in it
+///
+/// A game still being played is flushed first, so the log is up to date.
+/// Admins only.
+pub async fn game_events(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<Vec<GameEvent>>, ApiError> {
+    require_admin(&state, &user)?;
+
+    flush_game_session(&state, game_id).await;
+    let events = db::queries::get_game_events(&state.db, game_id).await?;
+    Ok(Json(events))
+}
+
+/// Pass the current player's turn in a stuck game
+///
+/// Admins only; same as the console's `ForceAdvanceTurn`.
+pub async fn force_advance_turn(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<GameRepairResponse>, ApiError> {
+    require_admin(&state, &user)?;
+
+    let lobby_id = console::lobby_for_game(&state, game_id)?;
+    console::force_advance_turn(&state, &lobby_id, &user).await?;
+    Ok(Json(GameRepairResponse {
+        lobby_id,
+        game_id: game_id.to_string(),
+        ..Default::default()
+    }))
+}
+
+/// Deal a new board for a stuck game, keeping scores, turn and used words
+///
+/// Admins only; same as the console's `ResetBoard`.
+pub async fn reset_board(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<GameRepairResponse>, ApiError> {
+    require_admin(&state, &user)?;
+
+    let lobby_id = console::lobby_for_game(&state, game_id)?;
+    let (_, board_checksum) = console::reset_board(&state, &lobby_id, &user).await?;
+    Ok(Json(GameRepairResponse {
+        lobby_id,
+        game_id: game_id.to_string(),
+        board_checksum: Some(board_checksum),
+        ..Default::default()
+    }))
+}
+
+/// Reload a desynced game's in-memory state from the database
+///
+/// Admins only; same as the console's `RebuildGame`.
+pub async fn rebuild_game(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<GameRepairResponse>, ApiError> {
+    require_admin(&state, &user)?;
+
+    let lobby_id = console::lobby_for_game(&state, game_id)?;
+    let (_, discarded_moves) = console::rebuild_game(&state, &lobby_id, &user).await?;
+    Ok(Json(GameRepairResponse {
+        lobby_id,
+        game_id: game_id.to_string(),
+        discarded_moves: Some(discarded_moves),
+        ..Default::default()
+    }))
+}
+
+/// Feature a shared puzzle, making it the puzzle of the week
+///
+/// Admins only.
+pub async fn feature_puzzle(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(puzzle_id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&state, &user)?;
+
+    if !db::queries::feature_puzzle(&state.db, puzzle_id).await? {
+        return Err(ApiError::PuzzleNotFound);
+    }
+    tracing::info!("Admin {} featured puzzle {}", user.user_id, puzzle_id);
+    Ok(StatusCode::NO_CONTENT)
+}
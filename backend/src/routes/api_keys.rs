@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth, db,
+    error::ApiError,
+    models::{ApiKey, ApiScope},
+    AppState,
+};
+
+/// Live keys a user may hold at once
+const MAX_API_KEYS: usize = 10;
+const MAX_API_KEY_NAME_LEN: usize = 64;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    /// What the key is for, to tell keys apart
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+}
+
+impl CreateApiKeyRequest {
+    /// The trimmed name and the scopes as stored, or why they can't be used
+    fn validate(&self) -> Result<(String, Vec<String>), String> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LEN {
+            return Err(format!(
+                "name must be 1 to {} characters",
+                MAX_API_KEY_NAME_LEN
+            ));
+        }
+        if self.scopes.is_empty() {
+            return Err("at least one scope is required".to_string());
+        }
+        let mut scopes: Vec<String> = self
+            .scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+        Ok((name.to_string(), scopes))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    /// The key itself; only its hash is stored, so this is the one chance to
+    /// copy it
+    pub key: String,
+    #[serde(flatten)]
+    pub info: ApiKey,
+}
+
+/// The caller's API keys, without the keys themselves
+pub async fn list_api_keys(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ApiKey>>, ApiError> {
+    Ok(Json(
+        db::queries::list_api_keys(&state.db, user.user_id).await?,
+    ))
+}
+
+/// Create an API key, for a dashboard or bot to read with in `X-API-Key`
+pub async fn create_api_key(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), ApiError> {
+    if user.is_guest() {
+        return Err(ApiError::Guest("create API keys"));
+    }
+    let (name, scopes) = request.validate().map_err(ApiError::InvalidApiKeyRequest)?;
+    if db::queries::list_api_keys(&state.db, user.user_id)
+        .await?
+        .len()
+        >= MAX_API_KEYS
+    {
+        return Err(ApiError::InvalidApiKeyRequest(format!(
+            "at most {} keys can be active; revoke one first",
+            MAX_API_KEYS
+        )));
+    }
+
+    let (key, key_prefix) = auth::generate_api_key();
+    let info = db::queries::create_api_key(
+        &state.db,
+        user.user_id,
+        &name,
+        &key_prefix,
+        &auth::hash_api_key(&key),
+        &scopes,
+    )
+    .await?;
+
+    tracing::info!(
+        "User {} created API key {} with scopes {:?}",
+        user.user_id,
+        info.key_id,
+        info.scopes
+    );
+    Ok((StatusCode::CREATED, Json(CreatedApiKey { key, info })))
+}
+
+/// Revoke one of the caller's API keys; it stops working straight away
+pub async fn revoke_api_key(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(key_id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    if !db::queries::revoke_api_key(&state.db, user.user_id, key_id).await? {
+        return Err(ApiError::ApiKeyNotFound);
+    }
+    tracing::info!("User {} revoked API key {}", user.user_id, key_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> CreateApiKeyRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_create_request_validation() {
+        let (name, scopes) = request(
+            r#"{"name":"  Stats site ","scopes":["read:games","read:leaderboard","read:games"]}"#,
+        )
+        .validate()
+        .unwrap();
+        assert_eq!(name, "Stats site");
+        assert_eq!(scopes, vec!["read:games", "read:leaderboard"]);
+
+        assert!(request(r#"{"name":" ","scopes":["read:games"]}"#)
+            .validate()
+            .is_err());
+        assert!(request(r#"{"name":"Bot","scopes":[]}"#).validate().is_err());
+        assert!(
+            serde_json::from_str::<CreateApiKeyRequest>(
+                r#"{"name":"Bot","scopes":["write:games"]}"#
+            )
+            .is_err(),
+            "Unknown scopes are rejected"
+        );
+    }
+}
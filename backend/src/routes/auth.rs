@@ -1,13 +1,33 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
 
-use crate::{auth, db, AppState};
+use crate::{auth, avatars, db, error::ApiError, AppState};
 
 #[derive(Debug, Deserialize)]
 pub struct CodeExchangeRequest {
     pub code: String,
+    /// `state` from `/api/auth/state` that was passed to Discord's authorize
+    #[serde(default)]
+    pub state: Option<String>,
+    /// PKCE verifier for the `code_challenge` the state was issued for
+    #[serde(default)]
+    pub code_verifier: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OAuthStateRequest {
+    /// S256 PKCE challenge the client will send to Discord's authorize
+    #[serde(default)]
+    pub code_challenge: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OAuthStateResponse {
+    /// Pass to Discord's authorize as `state`, then back to `/api/auth/exchange`
+    pub state: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,6 +40,15 @@ pub struct TokenResponse {
     pub discord_access_token: Option<String>,
 }
 
+/// A guest session from `/api/auth/guest`
+#[derive(Debug, Serialize)]
+pub struct GuestTokenResponse {
+    pub access_token: String,
+    pub user: UserResponse,
+    /// Guests can't refresh; they sign in again as a new guest
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Discord user response from /users/@me endpoint
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiscordUser {
@@ -31,6 +60,12 @@ pub struct DiscordUser {
     pub global_name: Option<String>,
 }
 
+/// Scopes the client should request from Discord when authorizing
+#[derive(Debug, Serialize)]
+pub struct ScopesResponse {
+    pub scopes: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserResponse {
     pub user_id: i64,
@@ -47,31 +82,128 @@ struct DiscordTokenResponse {
     expires_in: i64,
     /// Stored in database for token refresh functionality
     refresh_token: String,
-    #[allow(dead_code)]
+    /// Space-separated scopes the user actually granted
     scope: String,
 }
 
+impl DiscordTokenResponse {
+    fn granted_scopes(&self) -> Vec<String> {
+        self.scope.split_whitespace().map(String::from).collect()
+    }
+}
+
+/// The scopes to request when authorizing: always identify and guilds, plus any
+/// optional ones this deployment enables (`DISCORD_EXTRA_SCOPES`)
+pub async fn get_scopes(State(state): State<Arc<AppState>>) -> Json<ScopesResponse> {
+    Json(ScopesResponse {
+        scopes: state.config.discord.scopes(),
+    })
+}
+
+/// Start a sign-in: issue a signed, single-use `state`, bound to the client's
+/// PKCE challenge if it sends one
+pub async fn create_state(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<OAuthStateRequest>>,
+) -> Result<Json<OAuthStateResponse>, ApiError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let code_challenge = payload.code_challenge.filter(|c| !c.is_empty());
+    if code_challenge
+        .as_deref()
+        .is_some_and(|c| !auth::is_valid_pkce_value(c))
+    {
+        return Err(ApiError::InvalidOAuthState);
+    }
+
+    let (oauth_state, expires_at) =
+        auth::generate_oauth_state(code_challenge.as_deref(), &state.config.security.jwt_secret)
+            .map_err(|e| {
+                tracing::error!("Failed to generate OAuth state: {}", e);
+                ApiError::Internal
+            })?;
+    Ok(Json(OAuthStateResponse {
+        state: oauth_state,
+        expires_at,
+    }))
+}
+
+/// Check an exchange's state and PKCE verifier, and that neither the state nor
+/// the code has been exchanged before
+///
+/// Both are optional unless `REQUIRE_PKCE` is set, so older clients keep working;
+/// a state that is sent must still be valid, and one bound to a challenge needs
+/// its verifier.
+fn check_authorization(state: &AppState, payload: &CodeExchangeRequest) -> Result<(), ApiError> {
+    let require_pkce = state.config.security.require_pkce;
+    let claims = match payload.state.as_deref().filter(|s| !s.is_empty()) {
+        Some(oauth_state) => Some(
+            auth::validate_oauth_state(oauth_state, &state.config.security.jwt_secret).map_err(
+                |e| {
+                    tracing::warn!("Rejected OAuth state: {}", e);
+                    ApiError::InvalidOAuthState
+                },
+            )?,
+        ),
+        None if require_pkce => return Err(ApiError::InvalidOAuthState),
+        None => None,
+    };
+
+    let now = Instant::now();
+    if let Some(claims) = &claims {
+        if !state
+            .oauth_replay_guard
+            .first_use(&format!("state:{}", claims.jti), now)
+        {
+            tracing::warn!("Rejected a reused OAuth state");
+            return Err(ApiError::InvalidOAuthState);
+        }
+    }
+
+    let code_challenge = claims.and_then(|c| c.code_challenge);
+    match (code_challenge, payload.code_verifier.as_deref()) {
+        (Some(challenge), Some(verifier)) if auth::verify_pkce(verifier, &challenge) => {}
+        (Some(_), _) => {
+            tracing::warn!("Rejected a code exchange with a missing or wrong PKCE verifier");
+            return Err(ApiError::InvalidOAuthState);
+        }
+        (None, _) if require_pkce => return Err(ApiError::InvalidOAuthState),
+        (None, _) => {}
+    }
+
+    if !state
+        .oauth_replay_guard
+        .first_use(&format!("code:{}", payload.code), now)
+    {
+        tracing::warn!("Rejected a reused authorization code");
+        return Err(ApiError::AuthorizationCodeReused);
+    }
+    Ok(())
+}
+
 /// Exchange Discord authorization code for access token and create user session
 pub async fn exchange_code(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<CodeExchangeRequest>,
-) -> Result<Json<TokenResponse>, StatusCode> {
+) -> Result<Json<TokenResponse>, ApiError> {
     tracing::info!("Exchanging authorization code for access token");
 
+    check_authorization(&state, &payload)?;
+
     // Step 1: Exchange authorization code for Discord access token
-    let discord_token = exchange_code_with_discord(&state, &payload.code)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to exchange code with Discord: {}", e);
-            StatusCode::UNAUTHORIZED
-        })?;
+    let discord_token =
+        exchange_code_with_discord(&state, &payload.code, payload.code_verifier.as_deref())
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to exchange code with Discord: {}", e);
+                ApiError::Unauthorized
+            })?;
 
     // Step 2: Get user info from Discord API
     let discord_user = get_discord_user_info(&discord_token.access_token, &state.http_client)
         .await
         .map_err(|e| {
             tracing::error!("Failed to get Discord user info: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::DiscordApi
         })?;
 
     // Step 3: Parse Discord user ID
@@ -80,9 +212,17 @@ pub async fn exchange_code(
     // Note: Very large IDs (> i64::MAX) will wrap to negative, but remain unique
     let user_id = discord_user.id.parse::<u64>().map_err(|e| {
         tracing::error!("Failed to parse Discord user ID: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })? as i64;
 
+    // Accounts in their deletion grace period are only brought back by the restore link
+    let existing =
+        db::queries::get_user(&state.db, user_id, &state.config.security.encryption_key).await?;
+    if existing.is_some_and(|u| u.deletion_requested_at.is_some()) {
+        tracing::info!("Refused sign-in for user {} pending deletion", user_id);
+        return Err(ApiError::AccountPendingDeletion);
+    }
+
     // Step 4: Create or update user in database
     let avatar_url = discord_user.avatar.as_ref().map(|avatar_hash| {
         format!(
@@ -94,6 +234,19 @@ pub async fn exchange_code(
     // Calculate token expiration time (Discord tokens expire in expires_in seconds)
     let token_expires_at = chrono::Utc::now() + chrono::Duration::seconds(discord_token.expires_in);
 
+    // Optional scopes may be declined; features that need them check what was granted
+    let granted_scopes = discord_token.granted_scopes();
+    let declined: Vec<&String> = state
+        .config
+        .discord
+        .extra_scopes
+        .iter()
+        .filter(|scope| !granted_scopes.contains(scope))
+        .collect();
+    if !declined.is_empty() {
+        tracing::info!("User {} did not grant scopes {:?}", user_id, declined);
+    }
+
     db::queries::create_or_update_user(
         &state.db,
         user_id,
@@ -102,12 +255,13 @@ pub async fn exchange_code(
         avatar_url.as_deref(),
         Some(&discord_token.refresh_token),
         Some(token_expires_at),
+        &granted_scopes,
         &state.config.security.encryption_key,
     )
     .await
     .map_err(|e| {
         tracing::error!("Failed to create/update user in database: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?;
 
     tracing::info!(
@@ -124,7 +278,7 @@ pub async fn exchange_code(
     )
     .map_err(|e| {
         tracing::error!("Failed to generate JWT token: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?;
 
     // Return both tokens:
@@ -140,14 +294,18 @@ pub async fn exchange_code(
 async fn exchange_code_with_discord(
     state: &AppState,
     code: &str,
+    code_verifier: Option<&str>,
 ) -> anyhow::Result<DiscordTokenResponse> {
     let client_id = state.config.discord.client_id.as_str();
     let client_secret = state.config.discord.client_secret.as_str();
-    let params = [
+    let mut params = vec![
         ("grant_type", "authorization_code"),
         ("code", code),
         ("redirect_uri", state.config.discord.redirect_uri.as_str()),
     ];
+    if let Some(code_verifier) = code_verifier {
+        params.push(("code_verifier", code_verifier));
+    }
 
     let response = state
         .http_client
@@ -229,7 +387,7 @@ async fn refresh_discord_token(
 }
 
 /// Revoke a Discord OAuth2 token
-async fn revoke_discord_token(state: &AppState, token: &str) -> anyhow::Result<()> {
+pub async fn revoke_discord_token(state: &AppState, token: &str) -> anyhow::Result<()> {
     let client_id = state.config.discord.client_id.as_str();
     let client_secret = state.config.discord.client_secret.as_str();
     let params = [("token", token)];
@@ -267,7 +425,7 @@ async fn revoke_discord_token(state: &AppState, token: &str) -> anyhow::Result<(
 pub async fn refresh_token(
     user: auth::AuthenticatedUser,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<TokenResponse>, StatusCode> {
+) -> Result<Json<TokenResponse>, ApiError> {
     tracing::info!(
         "Refreshing token for user: {} ({})",
         user.username,
@@ -283,17 +441,17 @@ pub async fn refresh_token(
     .await
     .map_err(|e| {
         tracing::error!("Database error fetching user for refresh: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?
     .ok_or_else(|| {
         tracing::warn!("User not found for token refresh: {}", user.user_id);
-        StatusCode::NOT_FOUND
+        ApiError::UserNotFound
     })?;
 
     // Step 2: Ensure we have a refresh token
     let current_refresh_token = db_user.refresh_token.ok_or_else(|| {
         tracing::warn!("No refresh token stored for user: {}", user.user_id);
-        StatusCode::UNAUTHORIZED
+        ApiError::Unauthorized
     })?;
 
     // Step 3: Refresh with Discord
@@ -302,7 +460,7 @@ pub async fn refresh_token(
         .map_err(|e| {
             tracing::error!("Failed to refresh Discord token: {}", e);
             // If refresh fails, user needs to re-authenticate
-            StatusCode::UNAUTHORIZED
+            ApiError::Unauthorized
         })?;
 
     // Step 4: Calculate new expiration time
@@ -314,12 +472,13 @@ pub async fn refresh_token(
         user.user_id,
         &discord_token.refresh_token,
         token_expires_at,
+        &discord_token.granted_scopes(),
         &state.config.security.encryption_key,
     )
     .await
     .map_err(|e| {
         tracing::error!("Failed to update refresh token in database: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?;
 
     tracing::info!(
@@ -336,7 +495,7 @@ pub async fn refresh_token(
     )
     .map_err(|e| {
         tracing::error!("Failed to generate JWT token: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?;
 
     Ok(Json(TokenResponse {
@@ -355,7 +514,7 @@ pub async fn refresh_token(
 pub async fn revoke_token(
     user: auth::AuthenticatedUser,
     State(state): State<Arc<AppState>>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, ApiError> {
     tracing::info!(
         "Revoking tokens for user: {} ({})",
         user.username,
@@ -371,7 +530,7 @@ pub async fn revoke_token(
     .await
     .map_err(|e| {
         tracing::error!("Database error fetching user for revoke: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?;
 
     // If we have a refresh token, revoke it with Discord
@@ -391,7 +550,7 @@ pub async fn revoke_token(
         .await
         .map_err(|e| {
             tracing::error!("Failed to clear tokens from database: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::Internal
         })?;
 
     tracing::info!(
@@ -409,14 +568,14 @@ pub async fn revoke_token(
 pub async fn logout(
     user: auth::AuthenticatedUser,
     State(state): State<Arc<AppState>>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, ApiError> {
     tracing::info!("Logging out user: {} ({})", user.username, user.user_id);
 
     db::queries::clear_user_tokens(&state.db, user.user_id)
         .await
         .map_err(|e| {
             tracing::error!("Failed to clear tokens for logout: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::Internal
         })?;
 
     tracing::info!(
@@ -427,11 +586,44 @@ pub async fn logout(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Sign in as a guest with a generated name, for play-testing outside Discord
+///
+/// Only available with `GUEST_ACCESS=true`. Guests can play every mode, but their
+/// games don't count towards stats, quests or leaderboards.
+pub async fn guest_sign_in(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<GuestTokenResponse>, ApiError> {
+    if !state.config.security.guest_access {
+        return Err(ApiError::GuestAccessDisabled);
+    }
+
+    let (user_id, username) = auth::new_guest_identity();
+    db::queries::create_guest_user(&state.db, user_id, &username).await?;
+    let (access_token, expires_at) =
+        auth::generate_guest_token(user_id, &username, &state.config.security.jwt_secret).map_err(
+            |e| {
+                tracing::error!("Failed to generate guest token: {}", e);
+                ApiError::Internal
+            },
+        )?;
+
+    tracing::info!("Guest {} ({}) signed in", username, user_id);
+    Ok(Json(GuestTokenResponse {
+        access_token,
+        user: UserResponse {
+            user_id,
+            username,
+            avatar_url: None,
+        },
+        expires_at,
+    }))
+}
+
 /// Get current user info from database
 pub async fn get_current_user(
     user: auth::AuthenticatedUser,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<UserResponse>, StatusCode> {
+) -> Result<Json<UserResponse>, ApiError> {
     tracing::debug!(
         "Getting user info for authenticated user: {} ({})",
         user.username,
@@ -447,17 +639,17 @@ pub async fn get_current_user(
     .await
     .map_err(|e| {
         tracing::error!("Database error fetching user: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::Internal
     })?
     .ok_or_else(|| {
         tracing::warn!("User not found in database: {}", user.user_id);
-        StatusCode::NOT_FOUND
+        ApiError::UserNotFound
     })?;
 
     Ok(Json(UserResponse {
         user_id: db_user.user_id,
         username: db_user.username,
-        avatar_url: db_user.avatar_url,
+        avatar_url: avatars::proxy_url(db_user.user_id, db_user.avatar_url.as_deref()),
     }))
 }
 
@@ -471,6 +663,10 @@ mod tests {
         let request: CodeExchangeRequest = serde_json::from_str(json).unwrap();
 
         assert_eq!(request.code, "test_auth_code_12345");
+        assert!(
+            request.state.is_none() && request.code_verifier.is_none(),
+            "Clients without PKCE can still send just the code"
+        );
     }
 
     #[test]
@@ -602,6 +798,8 @@ mod tests {
     fn test_code_exchange_request_debug() {
         let request = CodeExchangeRequest {
             code: "auth_code".to_string(),
+            state: None,
+            code_verifier: None,
         };
 
         let debug_str = format!("{:?}", request);
@@ -658,4 +856,20 @@ mod tests {
         assert_eq!(original.username, deserialized.username);
         assert_eq!(original.avatar_url, deserialized.avatar_url);
     }
+
+    #[test]
+    fn test_granted_scopes_parsing() {
+        let json = r#"{
+            "access_token": "a",
+            "token_type": "Bearer",
+            "expires_in": 604800,
+            "refresh_token": "r",
+            "scope": "identify guilds  guilds.members.read"
+        }"#;
+        let token: DiscordTokenResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            token.granted_scopes(),
+            vec!["identify", "guilds", "guilds.members.read"]
+        );
+    }
 }
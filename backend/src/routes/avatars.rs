@@ -0,0 +1,126 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    avatars::{avatar_version, upstream_url, CachedAvatar, MAX_AVATAR_BYTES},
+    db,
+    error::ApiError,
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct AvatarQuery {
+    /// Avatar hash from `avatars::proxy_url`; lets a cached copy be served
+    /// without looking the user up
+    pub v: Option<String>,
+}
+
+/// A user's Discord avatar, fetched once and served from memory until it expires
+///
+/// Clients get these URLs in place of Discord's CDN ones, so the activity works
+/// where the CDN is blocked and Discord never sees who is looking at whom.
+pub async fn get_avatar(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    Query(query): Query<AvatarQuery>,
+) -> Result<Response, ApiError> {
+    let now = Instant::now();
+    if let Some(version) = query.v.as_deref() {
+        if let Some(avatar) = state.avatar_cache.get(user_id, Some(version), now) {
+            return Ok(serve(&state, avatar));
+        }
+    }
+
+    let avatar = load_avatar(&state, user_id, now).await?;
+    Ok(serve(&state, avatar))
+}
+
+/// A user's current avatar, from the cache if it's still current or else
+/// fetched from Discord and cached
+pub(super) async fn load_avatar(
+    state: &AppState,
+    user_id: i64,
+    now: Instant,
+) -> Result<CachedAvatar, ApiError> {
+    let avatar_url = db::queries::get_user_avatar_url(&state.db, user_id)
+        .await?
+        .flatten()
+        .ok_or(ApiError::AvatarNotFound)?;
+    if let Some(avatar) = state
+        .avatar_cache
+        .get(user_id, Some(avatar_version(&avatar_url)), now)
+    {
+        return Ok(avatar);
+    }
+
+    let avatar = fetch(state, &avatar_url).await?;
+    state.avatar_cache.insert(user_id, avatar.clone(), now);
+    Ok(avatar)
+}
+
+/// Download an avatar from Discord's CDN, rejecting anything that isn't a
+/// reasonably sized image
+async fn fetch(state: &AppState, avatar_url: &str) -> Result<CachedAvatar, ApiError> {
+    let url = upstream_url(avatar_url).ok_or(ApiError::AvatarNotFound)?;
+    let response = state.http_client.get(&url).send().await.map_err(|e| {
+        tracing::warn!("Avatar fetch from {} failed: {}", url, e);
+        ApiError::DiscordApi
+    })?;
+
+    if !response.status().is_success() {
+        tracing::warn!("Avatar fetch from {} failed: {}", url, response.status());
+        return Err(ApiError::DiscordApi);
+    }
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("image/"))
+        .map(String::from)
+        .ok_or_else(|| {
+            tracing::warn!("Avatar at {} is not an image", url);
+            ApiError::DiscordApi
+        })?;
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_AVATAR_BYTES as u64)
+    {
+        tracing::warn!("Avatar at {} is too large", url);
+        return Err(ApiError::DiscordApi);
+    }
+
+    let body = response.bytes().await.map_err(|e| {
+        tracing::warn!("Avatar download from {} failed: {}", url, e);
+        ApiError::DiscordApi
+    })?;
+    if body.len() > MAX_AVATAR_BYTES {
+        tracing::warn!("Avatar at {} is too large", url);
+        return Err(ApiError::DiscordApi);
+    }
+
+    Ok(CachedAvatar {
+        version: avatar_version(avatar_url).to_string(),
+        content_type,
+        body,
+    })
+}
+
+fn serve(state: &AppState, avatar: CachedAvatar) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, avatar.content_type),
+            (
+                header::CACHE_CONTROL,
+                format!("public, max-age={}", state.avatar_cache.ttl().as_secs()),
+            ),
+        ],
+        avatar.body,
+    )
+        .into_response()
+}
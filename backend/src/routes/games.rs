@@ -0,0 +1,599 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    avatars::load_avatar,
+    guilds::{parse_guild_id, require_guild_manager},
+};
+use crate::{
+    auth, db,
+    dictionary::DictionaryStrictness,
+    error::ApiError,
+    game::{
+        analysis::{analyze_game, MoveAnalysis, PlayerAccuracy},
+        result::GameResult,
+        result_card::ResultCard,
+        rules::GameRules,
+        solver::LobbyWords,
+    },
+    models::{
+        ApiScope, GameDbState, GameListFilter, GameListing, GameMode, GameMove, GameMoveFilter,
+        Grid,
+    },
+    render::{featured_player, png, render_result_card},
+    AppState,
+};
+
+const DEFAULT_GAMES_PAGE: i64 = 25;
+const MAX_GAMES_PAGE: i64 = 100;
+const DEFAULT_MOVES_PAGE: i64 = 50;
+const MAX_MOVES_PAGE: i64 = 200;
+/// How long clients and Discord may cache a result card image
+const RESULT_CARD_MAX_AGE_SECS: u64 = 24 * 60 * 60;
+
+/// Which unfinished games to list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveGameState {
+    /// Waiting and active games
+    #[default]
+    Live,
+    Waiting,
+    Active,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GamesQuery {
+    #[serde(default)]
+    pub state: LiveGameState,
+    pub guild_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl GamesQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_GAMES_PAGE)
+            .clamp(1, MAX_GAMES_PAGE)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    fn filter(&self, guild_id: Option<i64>) -> GameListFilter {
+        let states = match self.state {
+            LiveGameState::Live => GameListFilter::live().states,
+            LiveGameState::Waiting => vec![GameDbState::Waiting],
+            LiveGameState::Active => vec![GameDbState::Active],
+        };
+        GameListFilter {
+            states,
+            guild_id,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LiveGameInfo {
+    pub game_id: String,
+    /// The lobby the game is being played in; null if no lobby on this server has
+    /// it loaded (e.g. it was abandoned before a restart)
+    pub lobby_id: Option<String>,
+    pub guild_id: Option<String>,
+    pub game_mode: GameMode,
+    /// `waiting` or `active`
+    pub state: String,
+    pub current_round: i32,
+    pub max_rounds: i32,
+    pub player_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+impl LiveGameInfo {
+    fn new(game: GameListing, lobby_id: Option<String>) -> Self {
+        Self {
+            game_id: game.game_id.to_string(),
+            lobby_id,
+            guild_id: game.guild_id.map(|id| id.to_string()),
+            game_mode: game.game_mode,
+            state: game.state.to_string(),
+            current_round: game.current_round,
+            max_rounds: game.max_rounds,
+            player_count: game.player_count,
+            created_at: game.created_at,
+            started_at: game.started_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GamesPage {
+    pub games: Vec<LiveGameInfo>,
+    /// Offset of the next page; null on the last one
+    pub next_offset: Option<i64>,
+}
+
+/// Waiting and active games, newest first, with their lobbies and player counts
+///
+/// Listing one guild's games requires Manage Server there (checked as for guild
+/// settings, via `X-Discord-Token`); listing every guild's is for admins only.
+pub async fn list_games(
+    user: auth::AuthenticatedUser,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GamesQuery>,
+) -> Result<Json<GamesPage>, ApiError> {
+    let guild_id = match &query.guild_id {
+        Some(guild_id) => {
+            let guild_id_num = parse_guild_id(guild_id)?;
+            require_guild_manager(&state, &user, &headers, guild_id).await?;
+            Some(guild_id_num)
+        }
+        None if state.config.security.is_admin(user.user_id) => None,
+        None => return Err(ApiError::NotAdmin),
+    };
+
+    // One extra row tells us whether there's another page
+    let limit = query.limit();
+    let offset = query.offset();
+    let mut games =
+        db::queries::list_games(&state.db, &query.filter(guild_id), limit + 1, offset).await?;
+    let next_offset = (games.len() as i64 > limit).then_some(offset + limit);
+    games.truncate(limit as usize);
+
+    let lobbies: HashMap<Uuid, String> = state
+        .lobbies
+        .iter()
+        .filter_map(|lobby| Some((lobby.active_game_id?, lobby.lobby_id.clone())))
+        .collect();
+    let games = games
+        .into_iter()
+        .map(|game| {
+            let lobby_id = lobbies.get(&game.game_id).cloned();
+            LiveGameInfo::new(game, lobby_id)
+        })
+        .collect();
+
+    Ok(Json(GamesPage { games, next_offset }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GameHistoryQuery {
+    pub guild_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinishedGameInfo {
+    pub game_id: String,
+    pub guild_id: Option<String>,
+    pub game_mode: GameMode,
+    pub max_rounds: i32,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// In turn order, with their final scores
+    pub players: Vec<FinishedGamePlayer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinishedGamePlayer {
+    pub user_id: String,
+    pub username: String,
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameHistoryPage {
+    pub games: Vec<FinishedGameInfo>,
+    /// Offset of the next page; null on the last one
+    pub next_offset: Option<i64>,
+}
+
+/// Finished games, newest first, with their players' scores
+///
+/// For users and for API keys with the `read:games` scope, so community
+/// dashboards can follow a guild's games.
+pub async fn game_history(
+    caller: auth::ApiCaller,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GameHistoryQuery>,
+) -> Result<Json<GameHistoryPage>, ApiError> {
+    caller.require(ApiScope::ReadGames)?;
+    let guild_id = query.guild_id.as_deref().map(parse_guild_id).transpose()?;
+    let filter = GameListFilter {
+        states: vec![GameDbState::Finished],
+        guild_id,
+        ..Default::default()
+    };
+
+    // One extra row tells us whether there's another page
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_GAMES_PAGE)
+        .clamp(1, MAX_GAMES_PAGE);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let mut games = db::queries::list_games(&state.db, &filter, limit + 1, offset).await?;
+    let next_offset = (games.len() as i64 > limit).then_some(offset + limit);
+    games.truncate(limit as usize);
+
+    let game_ids: Vec<Uuid> = games.iter().map(|game| game.game_id).collect();
+    let mut players: HashMap<Uuid, Vec<FinishedGamePlayer>> = HashMap::new();
+    for player in db::queries::get_game_player_summaries(&state.db, &game_ids).await? {
+        players
+            .entry(player.game_id)
+            .or_default()
+            .push(FinishedGamePlayer {
+                user_id: player.user_id.to_string(),
+                username: player.username,
+                score: player.score,
+            });
+    }
+    let games = games
+        .into_iter()
+        .map(|game| FinishedGameInfo {
+            game_id: game.game_id.to_string(),
+            guild_id: game.guild_id.map(|id| id.to_string()),
+            game_mode: game.game_mode,
+            max_rounds: game.max_rounds,
+            created_at: game.created_at,
+            started_at: game.started_at,
+            players: players.remove(&game.game_id).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(GameHistoryPage { games, next_offset }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GameMovesQuery {
+    /// Only this player's moves
+    pub player: Option<String>,
+    /// Only moves from this round
+    pub round: Option<i32>,
+    /// 1-based
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl GameMovesQuery {
+    fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_MOVES_PAGE)
+            .clamp(1, MAX_MOVES_PAGE)
+    }
+
+    fn filter(&self) -> Result<GameMoveFilter, ApiError> {
+        let user_id = self
+            .player
+            .as_deref()
+            .map(|id| id.parse().map_err(|_| ApiError::InvalidUserId))
+            .transpose()?;
+        Ok(GameMoveFilter {
+            user_id,
+            round: self.round,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameMoveInfo {
+    pub move_id: i32,
+    pub user_id: String,
+    pub round: i32,
+    pub word: String,
+    pub score: i32,
+    pub positions: serde_json::Value,
+    pub played_at: DateTime<Utc>,
+    pub move_time_ms: Option<i32>,
+}
+
+impl From<GameMove> for GameMoveInfo {
+    fn from(game_move: GameMove) -> Self {
+        Self {
+            move_id: game_move.id,
+            user_id: game_move.user_id.to_string(),
+            round: game_move.round_number,
+            word: game_move.word,
+            score: game_move.score,
+            positions: game_move.positions,
+            played_at: game_move.timestamp,
+            move_time_ms: game_move.move_time_ms,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameMovesPage {
+    pub moves: Vec<GameMoveInfo>,
+    pub page: i64,
+    /// The next page's number; null on the last one
+    pub next_page: Option<i64>,
+}
+
+/// A finished game's moves in play order, a page at a time, optionally only one
+/// player's (`?player=`) or one round's (`?round=`)
+///
+/// For replay viewers and stats jobs going through long games; like replays,
+/// only finished or cancelled games, and API keys need the `read:games` scope.
+pub async fn get_moves(
+    caller: auth::ApiCaller,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+    Query(query): Query<GameMovesQuery>,
+) -> Result<Json<GameMovesPage>, ApiError> {
+    caller.require(ApiScope::ReadGames)?;
+    let filter = query.filter()?;
+    let game = db::queries::get_game(&state.db, game_id)
+        .await?
+        .ok_or(ApiError::GameNotFound)?;
+    if matches!(game.state, GameDbState::Waiting | GameDbState::Active) {
+        return Err(ApiError::GameNotFinished);
+    }
+
+    // One extra row tells us whether there's another page
+    let page = query.page();
+    let limit = query.limit();
+    let offset = (page - 1).saturating_mul(limit);
+    let mut moves =
+        db::queries::get_game_moves_page(&state.db, game_id, filter, limit + 1, offset).await?;
+    let next_page = (moves.len() as i64 > limit).then_some(page + 1);
+    moves.truncate(limit as usize);
+
+    Ok(Json(GameMovesPage {
+        moves: moves.into_iter().map(GameMoveInfo::from).collect(),
+        page,
+        next_page,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameReplay {
+    pub game_id: String,
+    pub game_mode: GameMode,
+    pub rules: GameRules,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Every move in order, with the board it was played on and the best word
+    /// that board had to offer
+    pub moves: Vec<MoveAnalysis>,
+    /// How much of the best available score each player found
+    pub players: Vec<PlayerAccuracy>,
+}
+
+/// A finished game move by move, with engine analysis of each move
+///
+/// Every board is solved against the base dictionary at its default strictness,
+/// since a lobby's theme pack isn't stored with its games. Only finished or
+/// cancelled games can be replayed, so the analysis can't be used to cheat.
+/// API keys need the `read:games` scope.
+pub async fn get_replay(
+    caller: auth::ApiCaller,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<GameReplay>, ApiError> {
+    caller.require(ApiScope::ReadGames)?;
+    let game = db::queries::get_game(&state.db, game_id)
+        .await?
+        .ok_or(ApiError::GameNotFound)?;
+    if matches!(game.state, GameDbState::Waiting | GameDbState::Active) {
+        return Err(ApiError::GameNotFinished);
+    }
+    if state.dictionary.is_loading() {
+        return Err(ApiError::DictionaryLoading);
+    }
+
+    let rules = game
+        .rules
+        .and_then(|rules| serde_json::from_value::<GameRules>(rules).ok())
+        .unwrap_or_default();
+    let moves = db::queries::get_game_moves(&state.db, game_id).await?;
+    let boards: Vec<Grid> = db::queries::get_board_history(&state.db, game_id)
+        .await?
+        .into_iter()
+        .filter_map(|revision| {
+            serde_json::from_value(revision.grid)
+                .inspect_err(|e| {
+                    tracing::warn!(
+                        "Skipping board {} of game {} in replay: {}",
+                        revision.move_number,
+                        game_id,
+                        e
+                    )
+                })
+                .ok()
+        })
+        .collect();
+
+    // Solving every board is CPU-bound, so keep it off the async workers
+    let solver_state = state.clone();
+    let analysis = tokio::task::spawn_blocking(move || {
+        let words = LobbyWords {
+            base: solver_state.dictionary.get()?,
+            packs: &solver_state.dictionary_packs,
+            selection: None,
+            strictness: DictionaryStrictness::default(),
+        };
+        Some(analyze_game(&moves, &boards, &words, &rules))
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Replay analysis for game {} failed: {}", game_id, e);
+        ApiError::Internal
+    })?
+    .ok_or(ApiError::DictionaryLoading)?;
+
+    Ok(Json(GameReplay {
+        game_id: game_id.to_string(),
+        game_mode: game.game_mode,
+        rules,
+        finished_at: game.finished_at,
+        moves: analysis.moves,
+        players: analysis.players,
+    }))
+}
+
+/// A finished game's result card, for a player to share into the channel
+///
+/// Only the game's players can fetch it. The scores on it come from the
+/// database, so a client can't post a result that didn't happen.
+pub async fn get_result_card(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Json<ResultCard>, ApiError> {
+    let card = load_result_card(&state, game_id).await?;
+    if !card
+        .standings
+        .iter()
+        .any(|standing| standing.user_id == user.user_id)
+    {
+        return Err(ApiError::NotPlaying);
+    }
+    Ok(Json(card))
+}
+
+/// A finished game's result card as an image
+///
+/// Public, unlike the JSON card, so Discord can unfurl it in chats and webhooks
+/// can embed it; game IDs aren't guessable and the card shows nothing the
+/// players didn't see. Rendered once per game and then served from memory.
+pub async fn get_result_card_png(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let now = Instant::now();
+    let png = match state.result_card_cache.get(game_id, now) {
+        Some(png) => png,
+        None => {
+            let card = load_result_card(&state, game_id).await?;
+            // A missing avatar is drawn as an initial, but one Discord failed to
+            // serve is worth trying again, so that card isn't cached
+            let (avatar, cacheable) = match featured_player(&card) {
+                Some(player) => match load_avatar(&state, player.user_id, now).await {
+                    Ok(avatar) => (Some(avatar.body), true),
+                    Err(ApiError::AvatarNotFound) => (None, true),
+                    Err(_) => (None, false),
+                },
+                None => (None, true),
+            };
+
+            let png = tokio::task::spawn_blocking(move || {
+                let avatar = avatar.as_deref().and_then(png::decode);
+                Bytes::from(render_result_card(&card, avatar.as_ref()))
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Rendering result card for game {} failed: {}", game_id, e);
+                ApiError::Internal
+            })?;
+            if cacheable {
+                state.result_card_cache.insert(game_id, png.clone(), now);
+            }
+            png
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png".to_string()),
+            (
+                header::CACHE_CONTROL,
+                format!("public, max-age={}", RESULT_CARD_MAX_AGE_SECS),
+            ),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+/// Build a finished game's result card from what the database recorded
+async fn load_result_card(state: &AppState, game_id: Uuid) -> Result<ResultCard, ApiError> {
+    let game = db::queries::get_game(&state.db, game_id)
+        .await?
+        .ok_or(ApiError::GameNotFound)?;
+    if matches!(game.state, GameDbState::Waiting | GameDbState::Active) {
+        return Err(ApiError::GameNotFinished);
+    }
+
+    let scores = db::queries::get_final_scores(&state.db, game_id).await?;
+    let result = game
+        .result
+        .and_then(|result| serde_json::from_value::<GameResult>(result).ok());
+    Ok(ResultCard::new(
+        game_id.to_string(),
+        game.game_mode,
+        game.finished_at,
+        result.as_ref(),
+        &scores,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> GamesQuery {
+        serde_urlencoded::from_str(query).unwrap()
+    }
+
+    #[test]
+    fn test_games_query_defaults() {
+        let query = parse("");
+        assert_eq!(query.state, LiveGameState::Live);
+        assert_eq!(query.limit(), DEFAULT_GAMES_PAGE);
+        assert_eq!(query.offset(), 0);
+        assert_eq!(
+            query.filter(None).states.len(),
+            2,
+            "Waiting and active games are listed by default"
+        );
+    }
+
+    #[test]
+    fn test_games_query_filters() {
+        let query = parse("state=active&guild_id=123&limit=5000&offset=-4");
+        let filter = query.filter(Some(123));
+        assert!(matches!(filter.states[..], [GameDbState::Active]));
+        assert_eq!(filter.guild_id, Some(123));
+        assert_eq!(query.limit(), MAX_GAMES_PAGE, "Large pages are capped");
+        assert_eq!(query.offset(), 0, "Negative offsets start from the top");
+        assert!(serde_urlencoded::from_str::<GamesQuery>("state=finished").is_err());
+    }
+
+    #[test]
+    fn test_moves_query() {
+        let query: GameMovesQuery = serde_urlencoded::from_str("").unwrap();
+        assert_eq!(query.page(), 1);
+        assert_eq!(query.limit(), DEFAULT_MOVES_PAGE);
+        let filter = query.filter().unwrap();
+        assert_eq!((filter.user_id, filter.round), (None, None));
+
+        let query: GameMovesQuery =
+            serde_urlencoded::from_str("player=123&round=2&page=0&limit=1000").unwrap();
+        assert_eq!(query.page(), 1, "Pages start at 1");
+        assert_eq!(query.limit(), MAX_MOVES_PAGE);
+        let filter = query.filter().unwrap();
+        assert_eq!((filter.user_id, filter.round), (Some(123), Some(2)));
+
+        let query: GameMovesQuery = serde_urlencoded::from_str("player=me").unwrap();
+        assert!(matches!(query.filter(), Err(ApiError::InvalidUserId)));
+    }
+}
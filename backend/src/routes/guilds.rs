@@ -0,0 +1,534 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth, db,
+    error::ApiError,
+    models::{
+        GameNight, GameNightAttendee, GameNightEvent, GameNightUpdate, GuildSettings,
+        GuildSettingsUpdate, UserGuildProfile,
+    },
+    websocket::{self, messages::ServerMessage},
+    AppState,
+};
+
+/// Header carrying the caller's Discord OAuth token, used to check guild permissions
+const DISCORD_TOKEN_HEADER: &str = "x-discord-token";
+
+/// Optional scope needed to read the caller's nickname and roles in a guild
+const GUILD_MEMBERS_SCOPE: &str = "guilds.members.read";
+/// How long stored guild member info is used before it's fetched again
+const GUILD_PROFILE_REFRESH_MINUTES: i64 = 15;
+
+/// Discord permission bits that allow changing a guild's settings
+const PERMISSION_ADMINISTRATOR: u64 = 1 << 3;
+const PERMISSION_MANAGE_GUILD: u64 = 1 << 5;
+
+/// Game night occurrences listed in a guild's history by default, and at most
+const DEFAULT_GAME_NIGHT_HISTORY: i64 = 20;
+const MAX_GAME_NIGHT_HISTORY: i64 = 100;
+
+/// A guild from Discord's `GET /users/@me/guilds` (requires the `guilds` scope)
+#[derive(Debug, Deserialize)]
+struct DiscordPartialGuild {
+    id: String,
+    #[serde(default)]
+    owner: bool,
+    /// Permission bitfield for the user in this guild, as a decimal string
+    #[serde(default)]
+    permissions: String,
+}
+
+impl DiscordPartialGuild {
+    fn can_manage(&self) -> bool {
+        let permissions: u64 = self.permissions.parse().unwrap_or(0);
+        self.owner || permissions & (PERMISSION_ADMINISTRATOR | PERMISSION_MANAGE_GUILD) != 0
+    }
+}
+
+/// The caller's member info from Discord's `GET /users/@me/guilds/{id}/member`
+#[derive(Debug, Deserialize)]
+struct DiscordGuildMember {
+    nick: Option<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+impl DiscordGuildMember {
+    fn role_ids(&self) -> Vec<i64> {
+        self.roles.iter().filter_map(|id| id.parse().ok()).collect()
+    }
+}
+
+/// The caller's display info in a guild
+#[derive(Debug, Serialize)]
+pub struct GuildProfileResponse {
+    pub guild_id: String,
+    /// Guild nickname, else global name, else username
+    pub display_name: String,
+    pub nickname: Option<String>,
+    pub roles: Vec<String>,
+    /// Whether the caller granted guilds.members.read; without it nickname and
+    /// roles stay empty (or as last fetched)
+    pub member_info: bool,
+    /// When the member info was last fetched from Discord
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Whether stored member info is missing or old enough to fetch again
+fn needs_refresh(profile: Option<&UserGuildProfile>, now: DateTime<Utc>) -> bool {
+    profile.is_none_or(|p| now - p.updated_at >= Duration::minutes(GUILD_PROFILE_REFRESH_MINUTES))
+}
+
+pub(super) fn parse_guild_id(guild_id: &str) -> Result<i64, ApiError> {
+    guild_id.parse().map_err(|_| ApiError::InvalidGuildId)
+}
+
+/// Get a guild's settings (defaults if it hasn't configured any)
+pub async fn get_settings(
+    _user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+) -> Result<Json<GuildSettings>, ApiError> {
+    let guild_id = parse_guild_id(&guild_id)?;
+    let settings = db::queries::get_guild_settings(&state.db, guild_id)
+        .await?
+        .unwrap_or_else(|| GuildSettings::defaults(guild_id, state.config.game.default_rounds));
+    Ok(Json(settings))
+}
+
+/// Update a guild's settings
+///
+/// Requires Manage Server (or Administrator) in the guild, checked against Discord
+/// with the caller's OAuth token from the `X-Discord-Token` header. Channel lobbies
+/// already open in the guild pick up the new settings for their next game.
+pub async fn update_settings(
+    user: auth::AuthenticatedUser,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+    Json(update): Json<GuildSettingsUpdate>,
+) -> Result<Json<GuildSettings>, ApiError> {
+    let guild_id_num = parse_guild_id(&guild_id)?;
+    require_guild_manager(&state, &user, &headers, &guild_id).await?;
+
+    let mut settings = db::queries::get_guild_settings(&state.db, guild_id_num)
+        .await?
+        .unwrap_or_else(|| GuildSettings::defaults(guild_id_num, state.config.game.default_rounds));
+    settings.apply(update).map_err(ApiError::InvalidSettings)?;
+
+    let saved = db::queries::upsert_guild_settings(&state.db, &settings, user.user_id).await?;
+
+    let mut language_changes = Vec::new();
+    for mut lobby in state.lobbies.iter_mut() {
+        if lobby.guild_id.as_deref() == Some(guild_id.as_str()) {
+            let before = lobby.language(&state.dictionary_packs);
+            lobby.guild_settings = Some(saved.clone());
+            let after = lobby.language(&state.dictionary_packs);
+            if after != before {
+                language_changes.push((lobby.lobby_id.clone(), after));
+            }
+        }
+    }
+    for (lobby_id, language) in language_changes {
+        websocket::broadcast_to_lobby(&state, &lobby_id, ServerMessage::language_changed(language))
+            .await;
+    }
+
+    tracing::info!(
+        "User {} ({}) updated settings for guild {}",
+        user.username,
+        user.user_id,
+        guild_id
+    );
+
+    Ok(Json(saved))
+}
+
+/// The caller's nickname and roles in a guild
+///
+/// With the guilds.members.read scope and the caller's Discord token in
+/// `X-Discord-Token`, member info is fetched from Discord when the stored copy is
+/// missing or stale. Without them, or if Discord refuses, whatever was stored
+/// last is returned and `display_name` falls back to the caller's global name.
+pub async fn get_profile(
+    user: auth::AuthenticatedUser,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+) -> Result<Json<GuildProfileResponse>, ApiError> {
+    let guild_id_num = parse_guild_id(&guild_id)?;
+    let db_user = db::queries::get_user(
+        &state.db,
+        user.user_id,
+        &state.config.security.encryption_key,
+    )
+    .await?
+    .ok_or(ApiError::UserNotFound)?;
+    let mut profile =
+        db::queries::get_user_guild_profile(&state.db, user.user_id, guild_id_num).await?;
+
+    let member_info = db_user.has_scope(GUILD_MEMBERS_SCOPE);
+    let discord_token = headers
+        .get(DISCORD_TOKEN_HEADER)
+        .and_then(|h| h.to_str().ok());
+    if let (true, Some(discord_token)) = (member_info, discord_token) {
+        if needs_refresh(profile.as_ref(), Utc::now()) {
+            if let Some(member) = fetch_guild_member(&state, discord_token, &guild_id).await {
+                profile = Some(
+                    db::queries::create_or_update_guild_profile(
+                        &state.db,
+                        user.user_id,
+                        guild_id_num,
+                        member.nick.as_deref(),
+                        &member.role_ids(),
+                    )
+                    .await?,
+                );
+            }
+        }
+    }
+
+    Ok(Json(GuildProfileResponse {
+        guild_id,
+        display_name: match &profile {
+            Some(profile) => profile.display_name(&db_user).to_string(),
+            None => db_user.display_name().to_string(),
+        },
+        nickname: profile.as_ref().and_then(|p| p.nickname.clone()),
+        roles: profile
+            .as_ref()
+            .map(|p| p.roles.iter().map(|id| id.to_string()).collect())
+            .unwrap_or_default(),
+        member_info,
+        updated_at: profile.map(|p| p.updated_at),
+    }))
+}
+
+/// The caller's member info in a guild; None if Discord refuses (scope not
+/// granted on this token, not a member) or can't be reached
+async fn fetch_guild_member(
+    state: &AppState,
+    discord_token: &str,
+    guild_id: &str,
+) -> Option<DiscordGuildMember> {
+    let response = state
+        .http_client
+        .get(format!(
+            "https://discord.com/api/v10/users/@me/guilds/{}/member",
+            guild_id
+        ))
+        .header("Authorization", format!("Bearer {}", discord_token))
+        .send()
+        .await
+        .map_err(|e| tracing::warn!("Discord guild member request failed: {}", e))
+        .ok()?;
+
+    if !response.status().is_success() {
+        tracing::debug!(
+            "Discord guild member fetch for guild {} failed: {}",
+            guild_id,
+            response.status()
+        );
+        return None;
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| tracing::warn!("Failed to parse Discord guild member: {}", e))
+        .ok()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GameNightHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// A game night occurrence with who came and how they scored
+#[derive(Debug, Serialize)]
+pub struct GameNightEventInfo {
+    #[serde(flatten)]
+    pub event: GameNightEvent,
+    pub attendees: Vec<GameNightAttendee>,
+}
+
+/// A guild's weekly game nights
+pub async fn list_game_nights(
+    _user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+) -> Result<Json<Vec<GameNight>>, ApiError> {
+    let guild_id = parse_guild_id(&guild_id)?;
+    Ok(Json(
+        db::queries::get_game_nights(&state.db, guild_id).await?,
+    ))
+}
+
+/// Set up a weekly game night in a channel, replacing the one it has
+///
+/// Requires Manage Server, like the guild's settings. An hour before each game
+/// night it's announced (to `webhook_url`, or in the channel by the bot) and the
+/// channel's lobby opens with the game scheduled.
+pub async fn put_game_night(
+    user: auth::AuthenticatedUser,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Path((guild_id, channel_id)): Path<(String, String)>,
+    Json(update): Json<GameNightUpdate>,
+) -> Result<Json<GameNight>, ApiError> {
+    let guild_id_num = parse_guild_id(&guild_id)?;
+    let channel_id_num = parse_channel_id(&channel_id)?;
+    require_guild_manager(&state, &user, &headers, &guild_id).await?;
+    let ping_role_id = update.validate().map_err(ApiError::InvalidSettings)?;
+    if !channel_in_guild(&state, &channel_id, &guild_id).await {
+        return Err(ApiError::InvalidField {
+            field: "channel_id",
+            reason: "not a channel in this guild",
+        });
+    }
+
+    let game_night = db::queries::upsert_game_night(
+        &state.db,
+        guild_id_num,
+        channel_id_num,
+        &update,
+        ping_role_id,
+        user.user_id,
+    )
+    .await?;
+
+    tracing::info!(
+        "User {} ({}) set a game night in channel {} of guild {}",
+        user.username,
+        user.user_id,
+        channel_id,
+        guild_id
+    );
+
+    Ok(Json(game_night))
+}
+
+/// Stop a channel's weekly game night; one already announced still goes ahead
+pub async fn delete_game_night(
+    user: auth::AuthenticatedUser,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Path((guild_id, channel_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let guild_id_num = parse_guild_id(&guild_id)?;
+    let channel_id_num = parse_channel_id(&channel_id)?;
+    require_guild_manager(&state, &user, &headers, &guild_id).await?;
+
+    if !db::queries::delete_game_night(&state.db, guild_id_num, channel_id_num).await? {
+        return Err(ApiError::GameNightNotFound);
+    }
+
+    tracing::info!(
+        "User {} ({}) removed the game night in channel {} of guild {}",
+        user.username,
+        user.user_id,
+        channel_id,
+        guild_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A guild's game night history, most recent first: each occurrence's status,
+/// game and winner, and who attended with their scores
+pub async fn game_night_history(
+    _user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+    Query(query): Query<GameNightHistoryQuery>,
+) -> Result<Json<Vec<GameNightEventInfo>>, ApiError> {
+    let guild_id = parse_guild_id(&guild_id)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_GAME_NIGHT_HISTORY)
+        .clamp(1, MAX_GAME_NIGHT_HISTORY);
+
+    let events = db::queries::get_game_night_events(&state.db, guild_id, limit).await?;
+    let event_ids: Vec<i64> = events.iter().map(|event| event.event_id).collect();
+    let mut attendance = db::queries::get_game_night_attendance(&state.db, &event_ids).await?;
+
+    Ok(Json(
+        events
+            .into_iter()
+            .map(|event| {
+                let (attendees, rest) = attendance
+                    .drain(..)
+                    .partition(|attendee| attendee.event_id == event.event_id);
+                attendance = rest;
+                GameNightEventInfo { event, attendees }
+            })
+            .collect(),
+    ))
+}
+
+fn parse_channel_id(channel_id: &str) -> Result<i64, ApiError> {
+    channel_id.parse().map_err(|_| ApiError::InvalidField {
+        field: "channel_id",
+        reason: "must be a Discord ID",
+    })
+}
+
+/// Whether a channel belongs to the guild, asked of Discord through the bot so
+/// announcements can't be aimed at other servers; without a bot token (or if
+/// Discord can't be reached) the admin is taken at their word
+async fn channel_in_guild(state: &AppState, channel_id: &str, guild_id: &str) -> bool {
+    let Some(token) = &state.config.discord.bot_token else {
+        return true;
+    };
+    let response = match state
+        .http_client
+        .get(format!(
+            "https://discord.com/api/v10/channels/{}",
+            channel_id
+        ))
+        .header("Authorization", format!("Bot {}", token))
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("Discord channel request failed: {}", e);
+            return true;
+        }
+    };
+    if !response.status().is_success() {
+        // The bot can't see the channel, so it couldn't announce there either
+        return false;
+    }
+    match response.json::<serde_json::Value>().await {
+        Ok(channel) => channel["guild_id"].as_str() == Some(guild_id),
+        Err(e) => {
+            tracing::warn!("Failed to parse Discord channel: {}", e);
+            true
+        }
+    }
+}
+
+/// Check that the user can manage the guild; server-wide admins always can
+pub(super) async fn require_guild_manager(
+    state: &AppState,
+    user: &auth::AuthenticatedUser,
+    headers: &HeaderMap,
+    guild_id: &str,
+) -> Result<(), ApiError> {
+    if state.config.security.is_admin(user.user_id) {
+        return Ok(());
+    }
+
+    let discord_token = headers
+        .get(DISCORD_TOKEN_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .ok_or(ApiError::MissingGuildPermission)?;
+
+    let response = state
+        .http_client
+        .get("https://discord.com/api/v10/users/@me/guilds")
+        .header("Authorization", format!("Bearer {}", discord_token))
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("Discord guild list request failed: {}", e);
+            ApiError::DiscordApi
+        })?;
+
+    if !response.status().is_success() {
+        tracing::warn!(
+            "Discord guild list fetch failed for user {}: {}",
+            user.user_id,
+            response.status()
+        );
+        return Err(ApiError::MissingGuildPermission);
+    }
+
+    let guilds: Vec<DiscordPartialGuild> = response.json().await.map_err(|e| {
+        tracing::error!("Failed to parse Discord guild list: {}", e);
+        ApiError::DiscordApi
+    })?;
+
+    let can_manage = guilds
+        .iter()
+        .any(|guild| guild.id == guild_id && guild.can_manage());
+    if !can_manage {
+        return Err(ApiError::MissingGuildPermission);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guild(owner: bool, permissions: &str) -> DiscordPartialGuild {
+        DiscordPartialGuild {
+            id: "123".to_string(),
+            owner,
+            permissions: permissions.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_manage_guild_permission_check() {
+        assert!(guild(true, "0").can_manage(), "Owners can always manage");
+        assert!(guild(false, "32").can_manage(), "MANAGE_GUILD is enough");
+        assert!(guild(false, "8").can_manage(), "ADMINISTRATOR is enough");
+        assert!(
+            !guild(false, "2048").can_manage(),
+            "Other permissions (e.g., SEND_MESSAGES) are not enough"
+        );
+        assert!(!guild(false, "").can_manage());
+    }
+
+    #[test]
+    fn test_discord_guild_deserialization() {
+        let json =
+            r#"[{"id": "123", "name": "Test", "owner": false, "permissions": "2147483647"}]"#;
+        let guilds: Vec<DiscordPartialGuild> = serde_json::from_str(json).unwrap();
+        assert_eq!(guilds[0].id, "123");
+        assert!(guilds[0].can_manage());
+    }
+
+    #[test]
+    fn test_discord_guild_member_deserialization() {
+        let json = r#"{"nick": "Wordsmith", "roles": ["41771983423143936", "oops"], "joined_at": "2021-01-01T00:00:00Z"}"#;
+        let member: DiscordGuildMember = serde_json::from_str(json).unwrap();
+        assert_eq!(member.nick.as_deref(), Some("Wordsmith"));
+        assert_eq!(
+            member.role_ids(),
+            vec![41771983423143936],
+            "Unparseable role IDs are skipped"
+        );
+
+        let member: DiscordGuildMember = serde_json::from_str(r#"{"nick": null}"#).unwrap();
+        assert!(member.nick.is_none() && member.roles.is_empty());
+    }
+
+    #[test]
+    fn test_guild_profile_refresh() {
+        let now = Utc::now();
+        let profile = |age: i64| UserGuildProfile {
+            user_id: 1,
+            guild_id: 2,
+            nickname: None,
+            roles: Vec::new(),
+            updated_at: now - Duration::minutes(age),
+        };
+        assert!(needs_refresh(None, now), "Never fetched");
+        assert!(!needs_refresh(Some(&profile(5)), now));
+        assert!(needs_refresh(
+            Some(&profile(GUILD_PROFILE_REFRESH_MINUTES)),
+            now
+        ));
+    }
+}
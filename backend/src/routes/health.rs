@@ -1,5 +1,9 @@
-use axum::Json;
-use serde_json::{json, Value};
+use std::{sync::Arc, time::Instant};
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde_json::{json, Map, Value};
+
+use crate::{db, AppState};
 
 /// Health check endpoint
 pub async fn health_check() -> Json<Value> {
@@ -9,3 +13,115 @@ pub async fn health_check() -> Json<Value> {
         "version": env!("CARGO_PKG_VERSION")
     }))
 }
+
+/// Liveness probe
+///
+/// Reports whether the process is healthy enough to keep running. Fails only when a
+/// background task has stopped ticking, since restarting is the only way to recover.
+pub async fn liveness(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Value>) {
+    let (tasks, tasks_ok) = task_report(&state);
+    let status = if tasks_ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if tasks_ok { "alive" } else { "stalled" },
+            "tasks": tasks,
+        })),
+    )
+}
+
+/// Readiness probe
+///
+/// Reports whether this instance can serve games: the database is reachable, all
+/// embedded migrations have been applied, and the dictionary has finished its
+/// background load. Also lists the queries that have been slow since startup.
+pub async fn readiness(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Value>) {
+    let database = match db::queries::ping(&state.db).await {
+        Ok(()) => json!({ "ok": true }),
+        Err(e) => {
+            tracing::warn!("Readiness check: database unreachable: {}", e);
+            json!({ "ok": false, "error": "database unreachable" })
+        }
+    };
+
+    let expected: Vec<i64> = db::MIGRATOR.iter().map(|m| m.version).collect();
+    let migrations = match db::queries::get_applied_migration_versions(&state.db).await {
+        Ok(applied) => {
+            let pending: Vec<i64> = expected
+                .iter()
+                .filter(|v| !applied.contains(v))
+                .copied()
+                .collect();
+            json!({
+                "ok": pending.is_empty(),
+                "applied": applied.len(),
+                "expected": expected.len(),
+                "pending": pending,
+            })
+        }
+        Err(e) => {
+            tracing::warn!("Readiness check: failed to read migrations: {}", e);
+            json!({ "ok": false, "error": "failed to read migration status" })
+        }
+    };
+
+    let dictionary = match state.dictionary.get() {
+        Some(dictionary) => json!({ "ok": !dictionary.is_empty(), "words": dictionary.len() }),
+        None => json!({ "ok": false, "loading": true }),
+    };
+
+    let (tasks, _) = task_report(&state);
+    let slow_queries = json!({
+        "threshold_ms": db::timing::slow_query_threshold().as_millis() as u64,
+        "queries": db::timing::slow_query_report(),
+    });
+
+    let ready = [&database, &migrations, &dictionary]
+        .iter()
+        .all(|check| check["ok"] == true);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if ready { "ready" } else { "not_ready" },
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+                "dictionary": dictionary,
+            },
+            "tasks": tasks,
+            "slow_queries": slow_queries,
+        })),
+    )
+}
+
+/// Summarize background task heartbeats; the flag is false if any task has stalled
+fn task_report(state: &AppState) -> (Value, bool) {
+    let now = Instant::now();
+    let mut all_ok = true;
+    let mut tasks = Map::new();
+
+    for entry in state.task_heartbeats.iter() {
+        let stalled = entry.is_stalled(now);
+        all_ok &= !stalled;
+        tasks.insert(
+            entry.key().to_string(),
+            json!({
+                "ok": !stalled,
+                "seconds_since_heartbeat": now.duration_since(entry.last_beat).as_secs(),
+            }),
+        );
+    }
+
+    (Value::Object(tasks), all_ok)
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth, avatars, db,
+    error::ApiError,
+    models::{ApiScope, PuzzleRushStanding, Season, SeasonFilter},
+    websocket::messages::GuildStandingInfo,
+    AppState,
+};
+
+const DEFAULT_LEADERBOARD_SIZE: i64 = 20;
+const MAX_LEADERBOARD_SIZE: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// `current` (default), `all` for lifetime stats, or a season ID
+    #[serde(default)]
+    pub season: SeasonFilter,
+    pub limit: Option<i64>,
+}
+
+impl LeaderboardQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+            .clamp(1, MAX_LEADERBOARD_SIZE)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PuzzleRushLeaderboardQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardResponse {
+    /// The season the standings cover; null for lifetime stats (or before the
+    /// first season has started)
+    pub season: Option<Season>,
+    pub standings: Vec<GuildStandingInfo>,
+}
+
+/// The season a filter names; Ok(None) means lifetime stats
+async fn resolve_season(
+    state: &AppState,
+    filter: SeasonFilter,
+) -> Result<Option<Season>, ApiError> {
+    match filter {
+        SeasonFilter::All => Ok(None),
+        SeasonFilter::Current => Ok(db::queries::get_current_season(&state.db).await?),
+        SeasonFilter::Season(id) => db::queries::get_season(&state.db, id)
+            .await?
+            .map(Some)
+            .ok_or(ApiError::SeasonNotFound),
+    }
+}
+
+/// Every season, newest first
+///
+/// Public, so no authentication is required.
+pub async fn list_seasons(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Season>>, ApiError> {
+    Ok(Json(db::queries::list_seasons(&state.db).await?))
+}
+
+/// Top players across all guilds, for a season or lifetime
+///
+/// Public, so no authentication is required.
+pub async fn global_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>, ApiError> {
+    let season = resolve_season(&state, query.season).await?;
+    let standings = match (&season, query.season) {
+        (Some(season), _) => {
+            db::queries::get_season_standings(&state.db, season.season_id, query.limit()).await?
+        }
+        (None, SeasonFilter::All) => {
+            db::queries::get_lifetime_standings(&state.db, query.limit()).await?
+        }
+        // No season has started yet
+        (None, _) => Vec::new(),
+    };
+    Ok(Json(LeaderboardResponse {
+        season,
+        standings: GuildStandingInfo::rank_all(standings),
+    }))
+}
+
+/// Top players in one guild, for a season or lifetime
+///
+/// API keys need the `read:leaderboard` scope.
+pub async fn guild_leaderboard(
+    caller: auth::ApiCaller,
+    State(state): State<Arc<AppState>>,
+    Path(guild_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>, ApiError> {
+    caller.require(ApiScope::ReadLeaderboard)?;
+    let guild_id: i64 = guild_id.parse().map_err(|_| ApiError::InvalidGuildId)?;
+    let season = resolve_season(&state, query.season).await?;
+    let standings = match (&season, query.season) {
+        (None, SeasonFilter::Current) => Vec::new(),
+        _ => {
+            db::queries::get_guild_standings(&state.db, guild_id, query.limit(), season.as_ref())
+                .await?
+        }
+    };
+    Ok(Json(LeaderboardResponse {
+        season,
+        standings: GuildStandingInfo::rank_all(standings),
+    }))
+}
+
+/// A player's best puzzle rush
+#[derive(Debug, Serialize)]
+pub struct PuzzleRushStandingInfo {
+    /// 1-based; runs with equal streaks and points share a rank
+    pub rank: u32,
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    /// Boards solved
+    pub streak: i32,
+    /// Points the solving words scored, which break ties between equal streaks
+    pub points: i32,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl PuzzleRushStandingInfo {
+    /// Rank runs already sorted by streak then points, using competition ranking
+    fn rank_all(standings: Vec<PuzzleRushStanding>) -> Vec<Self> {
+        let mut ranked: Vec<Self> = Vec::with_capacity(standings.len());
+        for (idx, standing) in standings.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some(prev) if (prev.streak, prev.points) == (standing.streak, standing.points) => {
+                    prev.rank
+                }
+                _ => idx as u32 + 1,
+            };
+            ranked.push(Self {
+                rank,
+                user_id: standing.user_id.to_string(),
+                username: standing.username,
+                avatar_url: avatars::proxy_url(standing.user_id, standing.avatar_url.as_deref()),
+                streak: standing.streak,
+                points: standing.points,
+                finished_at: standing.finished_at,
+            });
+        }
+        ranked
+    }
+}
+
+/// Every player's best puzzle rush, longest streak first
+///
+/// Public, so no authentication is required.
+pub async fn puzzle_rush_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PuzzleRushLeaderboardQuery>,
+) -> Result<Json<Vec<PuzzleRushStandingInfo>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+        .clamp(1, MAX_LEADERBOARD_SIZE);
+    let standings = db::queries::get_puzzle_rush_standings(&state.db, limit).await?;
+    Ok(Json(PuzzleRushStandingInfo::rank_all(standings)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(user_id: i64, streak: i32, points: i32) -> PuzzleRushStanding {
+        PuzzleRushStanding {
+            user_id,
+            username: format!("Player{}", user_id),
+            avatar_url: None,
+            streak,
+            points,
+            finished_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_puzzle_rush_ranks() {
+        let ranked = PuzzleRushStandingInfo::rank_all(vec![
+            run(1, 9, 200),
+            run(2, 9, 200),
+            run(3, 9, 150),
+            run(4, 4, 300),
+        ]);
+        let ranks: Vec<u32> = ranked.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, vec![1, 1, 3, 4], "Points only break ties in streak");
+        assert_eq!(ranked[0].user_id, "1");
+    }
+}
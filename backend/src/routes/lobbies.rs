@@ -0,0 +1,128 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth, error::ApiError, AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct InviteQuery {
+    /// Discord user ID to reserve a seat for (host only)
+    pub seat_for: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InviteResponse {
+    pub lobby_code: String,
+    /// Signed token accepted by JoinCustomLobby in place of the code
+    pub token: String,
+    /// Shareable link that opens the frontend with the invite
+    pub url: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Create a signed, expiring invite for a custom lobby
+///
+/// Any player in the lobby can create an invite; reserving a seat for a specific
+/// user is limited to the host and holds the seat as `ReserveSeat` would.
+pub async fn create_invite(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    Query(query): Query<InviteQuery>,
+) -> Result<Json<InviteResponse>, ApiError> {
+    let lobby_id = state
+        .find_lobby_by_code(&code)
+        .ok_or_else(|| ApiError::LobbyCodeNotFound(code.clone()))?;
+
+    let (lobby_code, is_host) = {
+        let lobby = state
+            .lobbies
+            .get(&lobby_id)
+            .ok_or(ApiError::LobbyNotFound)?;
+        if !lobby.players.contains_key(&user.user_id) {
+            return Err(ApiError::NotLobbyMember);
+        }
+        (
+            lobby.lobby_code.clone().ok_or(ApiError::LobbyNotFound)?,
+            lobby.is_host(user.user_id),
+        )
+    };
+
+    let seat_for = query
+        .seat_for
+        .map(|id| id.parse::<i64>().map_err(|_| ApiError::InvalidUserId))
+        .transpose()?;
+    if let Some(seat_for) = seat_for {
+        if !is_host {
+            return Err(ApiError::NotHost("reserve seats"));
+        }
+        // Hold the seat so the lobby can't fill up before the invitee arrives
+        let ttl = Duration::from_secs(state.config.game.seat_reservation_secs);
+        state.reserve_seat(&lobby_id, seat_for, ttl)?;
+    }
+
+    let (token, expires_at) = auth::generate_invite_token(
+        &lobby_code,
+        seat_for,
+        user.user_id,
+        &state.config.security.jwt_secret,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to generate invite token: {}", e);
+        ApiError::Internal
+    })?;
+
+    tracing::info!(
+        "User {} ({}) created invite for lobby {} (seat for: {:?})",
+        user.username,
+        user.user_id,
+        lobby_id,
+        seat_for
+    );
+
+    let url = format!(
+        "{}/?invite={}",
+        state.config.server.frontend_url.trim_end_matches('/'),
+        token
+    );
+
+    Ok(Json(InviteResponse {
+        lobby_code,
+        token,
+        url,
+        expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_query_seat_is_optional() {
+        let query: InviteQuery = serde_urlencoded::from_str("").unwrap();
+        assert!(query.seat_for.is_none());
+
+        let query: InviteQuery = serde_urlencoded::from_str("seat_for=123456789").unwrap();
+        assert_eq!(query.seat_for.as_deref(), Some("123456789"));
+    }
+
+    #[test]
+    fn test_invite_response_serialization() {
+        let response = InviteResponse {
+            lobby_code: "ABC234".to_string(),
+            token: "a.b.c".to_string(),
+            url: "http://localhost:3000/?invite=a.b.c".to_string(),
+            expires_at: chrono::Utc::now(),
+        };
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["lobby_code"], "ABC234");
+        assert_eq!(json["url"], "http://localhost:3000/?invite=a.b.c");
+        assert!(json["expires_at"].is_string());
+    }
+}
@@ -1,23 +1,139 @@
+pub mod admin;
+pub mod api_keys;
 pub mod auth;
+pub mod avatars;
+pub mod games;
+pub mod guilds;
 pub mod health;
+pub mod leaderboard;
+pub mod lobbies;
+pub mod puzzles;
+pub mod shop;
+pub mod stats;
+pub mod users;
 
 use std::sync::Arc;
 
-use axum::{routing::get, Router};
+use axum::{middleware, routing::get, Router};
 
-use crate::AppState;
+use crate::{public_stats, AppState};
 
-pub fn create_routes() -> Router<Arc<AppState>> {
+pub fn create_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/health", get(health::health_check))
-        .nest("/api", api_routes())
+        .route("/health/live", get(health::liveness))
+        .route("/health/ready", get(health::readiness))
+        .nest("/api", api_routes().merge(public_stats_routes(state)))
+}
+
+/// Read-only stats anyone can fetch, rate limited per IP and cached (the paths
+/// are listed in `public_stats::is_public_stats` too, for CORS)
+fn public_stats_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/leaderboard", get(leaderboard::global_leaderboard))
+        .route(
+            "/leaderboard/puzzle-rush",
+            get(leaderboard::puzzle_rush_leaderboard),
+        )
+        .route("/seasons", get(leaderboard::list_seasons))
+        .route("/stats/words/top", get(stats::top_words))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            public_stats::limit_and_cache,
+        ))
 }
 
 fn api_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/auth/exchange", axum::routing::post(auth::exchange_code))
         .route("/auth/me", get(auth::get_current_user))
+        .route("/auth/scopes", get(auth::get_scopes))
+        .route("/auth/state", axum::routing::post(auth::create_state))
         .route("/auth/refresh", axum::routing::post(auth::refresh_token))
         .route("/auth/revoke", axum::routing::post(auth::revoke_token))
         .route("/auth/logout", axum::routing::post(auth::logout))
+        .route("/auth/guest", axum::routing::post(auth::guest_sign_in))
+        .route("/avatars/{user_id}", get(avatars::get_avatar))
+        .route("/admin/analytics/overview", get(admin::analytics_overview))
+        .route("/admin/migrations", get(admin::migrations))
+        .route("/admin/games/{game_id}/events", get(admin::game_events))
+        .route(
+            "/admin/games/{game_id}/advance-turn",
+            axum::routing::post(admin::force_advance_turn),
+        )
+        .route(
+            "/admin/games/{game_id}/reset-board",
+            axum::routing::post(admin::reset_board),
+        )
+        .route(
+            "/admin/games/{game_id}/rebuild",
+            axum::routing::post(admin::rebuild_game),
+        )
+        .route(
+            "/admin/puzzles/{puzzle_id}/feature",
+            axum::routing::post(admin::feature_puzzle),
+        )
+        .route("/games", get(games::list_games))
+        .route("/games/history", get(games::game_history))
+        .route("/games/{game_id}/moves", get(games::get_moves))
+        .route("/games/{game_id}/replay", get(games::get_replay))
+        .route("/games/{game_id}/result-card", get(games::get_result_card))
+        .route(
+            "/games/{game_id}/result-card.png",
+            get(games::get_result_card_png),
+        )
+        .route(
+            "/guilds/{guild_id}/settings",
+            get(guilds::get_settings).put(guilds::update_settings),
+        )
+        .route("/guilds/{guild_id}/profile", get(guilds::get_profile))
+        .route(
+            "/guilds/{guild_id}/game-nights",
+            get(guilds::list_game_nights),
+        )
+        .route(
+            "/guilds/{guild_id}/game-nights/events",
+            get(guilds::game_night_history),
+        )
+        .route(
+            "/guilds/{guild_id}/game-nights/{channel_id}",
+            axum::routing::put(guilds::put_game_night).delete(guilds::delete_game_night),
+        )
+        .route(
+            "/guilds/{guild_id}/leaderboard",
+            get(leaderboard::guild_leaderboard),
+        )
+        .route("/lobbies/{code}/invite", get(lobbies::create_invite))
+        .route(
+            "/puzzles",
+            get(puzzles::list_puzzles).post(puzzles::create_puzzle),
+        )
+        .route("/puzzles/{puzzle_id}", get(puzzles::get_puzzle))
+        .route("/shop", get(shop::get_shop))
+        .route(
+            "/shop/{item_id}/purchase",
+            axum::routing::post(shop::purchase),
+        )
+        .route(
+            "/users/me/profile",
+            get(users::get_profile).patch(users::update_profile),
+        )
+        .route("/users/me/stats", get(users::get_stats))
+        .route("/users/me/quests", get(users::get_quests))
+        .route("/users/me/recent-players", get(users::get_recent_players))
+        .route("/users/me/export", get(users::export_data))
+        .route(
+            "/users/me/api-keys",
+            get(api_keys::list_api_keys).post(api_keys::create_api_key),
+        )
+        .route(
+            "/users/me/api-keys/{key_id}",
+            axum::routing::delete(api_keys::revoke_api_key),
+        )
+        .route("/users/me", axum::routing::delete(users::delete_account))
+        .route("/users/{a}/versus/{b}", get(users::get_versus))
+        .route(
+            "/users/restore",
+            axum::routing::post(users::restore_account),
+        )
 }
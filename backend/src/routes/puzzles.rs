@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth, db,
+    error::ApiError,
+    models::{Puzzle, PuzzleDraft},
+    AppState,
+};
+
+const DEFAULT_PUZZLES_PAGE: i64 = 20;
+const MAX_PUZZLES_PAGE: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct PuzzlesQuery {
+    /// Only featured puzzles, most recently featured (the puzzle of the week)
+    /// first
+    #[serde(default)]
+    pub featured: bool,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl PuzzlesQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PUZZLES_PAGE)
+            .clamp(1, MAX_PUZZLES_PAGE)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PuzzleInfo {
+    #[serde(flatten)]
+    pub puzzle: Puzzle,
+    pub author_id: String,
+}
+
+impl From<Puzzle> for PuzzleInfo {
+    fn from(puzzle: Puzzle) -> Self {
+        Self {
+            author_id: puzzle.author_id.to_string(),
+            puzzle,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PuzzlesPage {
+    pub puzzles: Vec<PuzzleInfo>,
+    /// Offset of the next page; null on the last one
+    pub next_offset: Option<i64>,
+}
+
+/// Share a hand-crafted board, for any lobby's host to start games on
+pub async fn create_puzzle(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Json(draft): Json<PuzzleDraft>,
+) -> Result<(StatusCode, Json<PuzzleInfo>), ApiError> {
+    if user.is_guest() {
+        return Err(ApiError::Guest("share puzzles"));
+    }
+    let (title, grid) = draft.validate().map_err(ApiError::InvalidPuzzle)?;
+    let puzzle = db::queries::create_puzzle(&state.db, user.user_id, &title, &grid).await?;
+
+    tracing::info!(
+        "User {} shared puzzle {} ({:?})",
+        user.user_id,
+        puzzle.puzzle_id,
+        puzzle.title
+    );
+    Ok((StatusCode::CREATED, Json(puzzle.into())))
+}
+
+pub async fn get_puzzle(
+    _user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(puzzle_id): Path<i64>,
+) -> Result<Json<PuzzleInfo>, ApiError> {
+    let puzzle = db::queries::get_puzzle(&state.db, puzzle_id)
+        .await?
+        .ok_or(ApiError::PuzzleNotFound)?;
+    Ok(Json(puzzle.into()))
+}
+
+/// Shared puzzles, newest first, or the featured ones
+pub async fn list_puzzles(
+    _user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PuzzlesQuery>,
+) -> Result<Json<PuzzlesPage>, ApiError> {
+    // One extra row tells us whether there's another page
+    let limit = query.limit();
+    let offset = query.offset();
+    let mut puzzles =
+        db::queries::list_puzzles(&state.db, query.featured, limit + 1, offset).await?;
+    let next_offset = (puzzles.len() as i64 > limit).then_some(offset + limit);
+    puzzles.truncate(limit as usize);
+
+    Ok(Json(PuzzlesPage {
+        puzzles: puzzles.into_iter().map(PuzzleInfo::from).collect(),
+        next_offset,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzles_query() {
+        let query: PuzzlesQuery = serde_urlencoded::from_str("featured=true&limit=500").unwrap();
+        assert!(query.featured);
+        assert_eq!(query.limit(), MAX_PUZZLES_PAGE);
+
+        let query: PuzzlesQuery = serde_urlencoded::from_str("offset=-3").unwrap();
+        assert!(!query.featured);
+        assert_eq!(query.limit(), DEFAULT_PUZZLES_PAGE);
+        assert_eq!(query.offset(), 0);
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{
+    auth, db,
+    error::ApiError,
+    models::{shop::PurchaseOutcome, InventoryItem, ShopListing, Wallet},
+    AppState,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ShopResponse {
+    pub wallet: Wallet,
+    pub items: Vec<ShopListing>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurchaseResponse {
+    /// Balances after paying
+    pub wallet: Wallet,
+    pub inventory: Vec<InventoryItem>,
+}
+
+/// Everything for sale, marked with what the caller owns and can afford
+pub async fn get_shop(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ShopResponse>, ApiError> {
+    let wallet = db::queries::get_wallet(&state.db, user.user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    let inventory = db::queries::get_user_inventory(&state.db, user.user_id).await?;
+    let items = db::queries::list_shop_items(&state.db).await?;
+    Ok(Json(ShopResponse {
+        wallet,
+        items: ShopListing::list(items, &inventory, wallet),
+    }))
+}
+
+/// Buy an item with gems
+pub async fn purchase(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+) -> Result<Json<PurchaseResponse>, ApiError> {
+    if user.is_guest() {
+        return Err(ApiError::Guest("buy items"));
+    }
+    let wallet = match db::queries::purchase_item(&state.db, user.user_id, &item_id).await? {
+        PurchaseOutcome::Purchased(wallet) => wallet,
+        PurchaseOutcome::NotFound => return Err(ApiError::ItemNotFound),
+        PurchaseOutcome::AlreadyOwned => return Err(ApiError::ItemAlreadyOwned),
+        PurchaseOutcome::InsufficientGems { price, balance } => {
+            return Err(ApiError::InsufficientGems { price, balance })
+        }
+    };
+
+    tracing::info!(
+        "User {} bought {} ({} gems left)",
+        user.user_id,
+        item_id,
+        wallet.gems
+    );
+
+    let inventory = db::queries::get_user_inventory(&state.db, user.user_id).await?;
+    Ok(Json(PurchaseResponse { wallet, inventory }))
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    db,
+    error::ApiError,
+    models::{WordStat, WordStatsSort},
+    AppState,
+};
+
+const DEFAULT_TOP_WORDS: i64 = 20;
+const MAX_TOP_WORDS: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct TopWordsQuery {
+    /// `plays` (default) or `score`
+    #[serde(default)]
+    pub sort: WordStatsSort,
+    pub limit: Option<i64>,
+}
+
+impl TopWordsQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_TOP_WORDS)
+            .clamp(1, MAX_TOP_WORDS)
+    }
+}
+
+/// Most-played or highest-scoring words across the whole community
+///
+/// Public, so no authentication is required.
+pub async fn top_words(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TopWordsQuery>,
+) -> Result<Json<Vec<WordStat>>, ApiError> {
+    let words = db::queries::get_top_words(&state.db, query.sort, query.limit()).await?;
+    Ok(Json(words))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> TopWordsQuery {
+        serde_urlencoded::from_str(query).unwrap()
+    }
+
+    #[test]
+    fn test_top_words_query_defaults() {
+        let query = parse("");
+        assert_eq!(query.sort, WordStatsSort::Plays);
+        assert_eq!(query.limit(), DEFAULT_TOP_WORDS);
+    }
+
+    #[test]
+    fn test_top_words_query_clamps_limit() {
+        let query = parse("sort=score&limit=5000");
+        assert_eq!(query.sort, WordStatsSort::Score);
+        assert_eq!(query.limit(), MAX_TOP_WORDS, "Large limits are capped");
+        assert_eq!(parse("limit=0").limit(), 1);
+    }
+}
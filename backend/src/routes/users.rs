@@ -0,0 +1,433 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth, avatars, db,
+    error::ApiError,
+    models::{
+        quest::active_quests, ProfileSettings, ProfileUpdate, QuestStatus, RecentPlayer, User,
+        UserProfile, UserStats, VersusRecord, VersusWord, Wallet,
+    },
+    routes,
+    websocket::{self, messages::FriendPresence, presence::presence_of},
+    AppState,
+};
+
+const DEFAULT_RECENT_PLAYERS: i64 = 20;
+const MAX_RECENT_PLAYERS: i64 = 50;
+/// Best words listed for each player in a head-to-head
+const VERSUS_BEST_WORDS: i64 = 3;
+
+#[derive(Debug, Serialize)]
+pub struct QuestsResponse {
+    #[serde(flatten)]
+    pub wallet: Wallet,
+    /// Today's daily quests followed by this week's weekly quests
+    pub quests: Vec<QuestStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentPlayersQuery {
+    pub limit: Option<i64>,
+}
+
+/// Someone the caller played with recently, with where they are now so the
+/// caller can invite them (`invite_player`) or join their lobby
+#[derive(Debug, Serialize)]
+pub struct RecentPlayerInfo {
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub games_together: i64,
+    pub last_played_at: Option<DateTime<Utc>>,
+    pub presence: FriendPresence,
+}
+
+impl RecentPlayerInfo {
+    fn new(player: RecentPlayer, presence: FriendPresence) -> Self {
+        Self {
+            user_id: player.user_id.to_string(),
+            username: player.username,
+            avatar_url: avatars::proxy_url(player.user_id, player.avatar_url.as_deref()),
+            games_together: player.games_together,
+            last_played_at: player.last_played_at,
+            presence,
+        }
+    }
+}
+
+async fn load(state: &AppState, user_id: i64) -> Result<(User, UserProfile), ApiError> {
+    let user = db::queries::get_user(&state.db, user_id, &state.config.security.encryption_key)
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    let profile = db::queries::get_user_profile(&state.db, user_id)
+        .await?
+        .unwrap_or_else(|| UserProfile::empty(user_id));
+    Ok((user, profile))
+}
+
+async fn wallet(state: &AppState, user_id: i64) -> Result<Wallet, ApiError> {
+    db::queries::get_wallet(&state.db, user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)
+}
+
+async fn settings(
+    state: &AppState,
+    profile: &UserProfile,
+    user: &User,
+) -> Result<ProfileSettings, ApiError> {
+    let wallet = wallet(state, user.user_id).await?;
+    let inventory = db::queries::get_user_inventory(&state.db, user.user_id).await?;
+    Ok(ProfileSettings::new(profile, user, wallet, inventory))
+}
+
+/// The caller's equipped cosmetics, with every title and badge and whether it's
+/// unlocked, plus their wallet and inventory
+pub async fn get_profile(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ProfileSettings>, ApiError> {
+    let (user, profile) = load(&state, user.user_id).await?;
+    Ok(Json(settings(&state, &profile, &user).await?))
+}
+
+/// Equip a title, name color or badge
+///
+/// Titles and badges must be unlocked by the caller's stats. Lobbies the caller
+/// is in get an updated player list right away; games show the change in their
+/// next full state.
+pub async fn update_profile(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Json(update): Json<ProfileUpdate>,
+) -> Result<Json<ProfileSettings>, ApiError> {
+    if user.is_guest() {
+        return Err(ApiError::Guest("customize their profile"));
+    }
+    let (user, mut profile) = load(&state, user.user_id).await?;
+    profile
+        .apply(update, &user)
+        .map_err(ApiError::InvalidProfile)?;
+
+    let saved = db::queries::upsert_user_profile(&state.db, &profile).await?;
+    state.player_profiles.set(user.user_id, saved.display());
+
+    let lobby_ids: Vec<String> = state
+        .lobbies
+        .iter()
+        .filter(|lobby| lobby.players.contains_key(&user.user_id))
+        .map(|lobby| lobby.lobby_id.clone())
+        .collect();
+    for lobby_id in lobby_ids {
+        websocket::broadcast_lobby_player_list(&state, &lobby_id).await;
+    }
+
+    tracing::info!(
+        "User {} ({}) updated their profile",
+        user.username,
+        user.user_id
+    );
+
+    Ok(Json(settings(&state, &saved, &user).await?))
+}
+
+/// The caller's lifetime totals plus their word habits (average word length,
+/// favorite starting letter, Q and Z words, longest word) for the stats screen
+pub async fn get_stats(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<UserStats>, ApiError> {
+    let (user, _) = load(&state, user.user_id).await?;
+    let words = db::queries::get_word_habits(&state.db, user.user_id).await?;
+    Ok(Json(user.to_stats(words)))
+}
+
+/// The caller's daily and weekly quests with their progress, plus their gem and XP totals
+pub async fn get_quests(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<QuestsResponse>, ApiError> {
+    let wallet = wallet(&state, user.user_id).await?;
+
+    let active = active_quests(Utc::now());
+    let mut period_keys: Vec<String> = active.iter().map(|q| q.period_key.clone()).collect();
+    period_keys.dedup();
+    let stored = db::queries::get_user_quests(&state.db, user.user_id, &period_keys).await?;
+
+    let quests = active
+        .iter()
+        .map(|quest| {
+            let progress = stored
+                .iter()
+                .find(|s| s.quest_id == quest.def.id && s.period_key == quest.period_key);
+            QuestStatus::new(quest, progress)
+        })
+        .collect();
+    Ok(Json(QuestsResponse { wallet, quests }))
+}
+
+/// The distinct players the caller most recently finished games with, newest first
+pub async fn get_recent_players(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RecentPlayersQuery>,
+) -> Result<Json<Vec<RecentPlayerInfo>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECENT_PLAYERS)
+        .clamp(1, MAX_RECENT_PLAYERS);
+    let players = db::queries::get_recent_players(&state.db, user.user_id, limit).await?;
+    Ok(Json(
+        players
+            .into_iter()
+            .map(|player| {
+                let presence = presence_of(&state, player.user_id, user.user_id);
+                RecentPlayerInfo::new(player, presence)
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersusQuery {
+    /// Only count games played in this guild
+    pub guild_id: Option<String>,
+}
+
+/// One side of a head-to-head
+#[derive(Debug, Serialize)]
+pub struct VersusSide {
+    pub user_id: i64,
+    pub wins: i64,
+    /// Their highest-scoring words against the other player, best first
+    pub best_words: Vec<VersusWord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadToHead {
+    pub games: i64,
+    pub draws: i64,
+    /// `a`'s score minus `b`'s, averaged over their games
+    pub average_margin: f64,
+    pub a: VersusSide,
+    pub b: VersusSide,
+}
+
+impl HeadToHead {
+    fn new(user_a: i64, user_b: i64, record: VersusRecord, words: Vec<VersusWord>) -> Self {
+        let (words_a, words_b) = words.into_iter().partition(|w| w.user_id == user_a);
+        Self {
+            games: record.games,
+            draws: record.draws,
+            average_margin: record.average_margin,
+            a: VersusSide {
+                user_id: user_a,
+                wins: record.wins,
+                best_words: words_a,
+            },
+            b: VersusSide {
+                user_id: user_b,
+                wins: record.losses,
+                best_words: words_b,
+            },
+        }
+    }
+}
+
+/// A user ID from the path; `me` is the caller
+fn parse_user_id(user_id: &str, caller: i64) -> Result<i64, ApiError> {
+    match user_id {
+        "me" => Ok(caller),
+        id => id.parse().map_err(|_| ApiError::InvalidUserId),
+    }
+}
+
+/// Two players' lifetime record against each other: wins, draws, average margin
+/// and each one's best words, from the finished games they played on opposing
+/// sides (`?guild_id=` narrows it to one server)
+pub async fn get_versus(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+    Path((a, b)): Path<(String, String)>,
+    Query(query): Query<VersusQuery>,
+) -> Result<Json<HeadToHead>, ApiError> {
+    let user_a = parse_user_id(&a, user.user_id)?;
+    let user_b = parse_user_id(&b, user.user_id)?;
+    if user_a == user_b {
+        return Err(ApiError::InvalidUserId);
+    }
+    let guild_id = query
+        .guild_id
+        .as_deref()
+        .map(routes::guilds::parse_guild_id)
+        .transpose()?;
+
+    let record = db::queries::get_versus_record(&state.db, user_a, user_b, guild_id).await?;
+    let words =
+        db::queries::get_versus_best_words(&state.db, user_a, user_b, guild_id, VERSUS_BEST_WORDS)
+            .await?;
+    Ok(Json(HeadToHead::new(user_a, user_b, record, words)))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    /// When the account will be deleted unless it's restored first
+    pub deletes_at: DateTime<Utc>,
+    /// Pass to `POST /api/users/restore` to cancel the deletion
+    pub restore_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreAccountRequest {
+    pub token: String,
+}
+
+/// Everything stored about the caller, as a downloadable JSON document
+pub async fn export_data(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, ApiError> {
+    let export = db::queries::export_user_data(&state.db, user.user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    tracing::info!("User {} exported their data", user.user_id);
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"spell-cast-{}.json\"", user.user_id),
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+/// Schedule the caller's account for deletion after the grace period
+///
+/// The caller is signed out and their Discord token revoked right away. Once the
+/// grace period is over their games and moves are anonymized, so opponents keep
+/// their history, and everything else is deleted.
+pub async fn delete_account(
+    user: auth::AuthenticatedUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<AccountDeletionResponse>, ApiError> {
+    let (db_user, _) = load(&state, user.user_id).await?;
+    if let Some(refresh_token) = db_user.refresh_token {
+        if let Err(e) = routes::auth::revoke_discord_token(&state, &refresh_token).await {
+            tracing::warn!(
+                "Failed to revoke token with Discord (continuing anyway): {}",
+                e
+            );
+        }
+    }
+
+    let requested_at = db::queries::request_account_deletion(&state.db, user.user_id)
+        .await?
+        .ok_or(ApiError::UserNotFound)?;
+    let deletes_at =
+        requested_at + Duration::days(state.config.security.account_deletion_grace_days);
+    let restore_token = auth::generate_account_restore_token(
+        user.user_id,
+        deletes_at,
+        &state.config.security.jwt_secret,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to generate account restore token: {}", e);
+        ApiError::Internal
+    })?;
+
+    tracing::info!(
+        "User {} ({}) requested account deletion, due {}",
+        user.username,
+        user.user_id,
+        deletes_at
+    );
+    Ok(Json(AccountDeletionResponse {
+        deletes_at,
+        restore_token,
+    }))
+}
+
+/// Cancel a pending account deletion with the token `DELETE /api/users/me` returned
+///
+/// Needs no session, since deleting the account signed the user out; they sign
+/// in again afterwards as usual.
+pub async fn restore_account(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RestoreAccountRequest>,
+) -> Result<StatusCode, ApiError> {
+    let claims =
+        auth::validate_account_restore_token(&payload.token, &state.config.security.jwt_secret)
+            .map_err(|e| {
+                tracing::warn!("Rejected account restore token: {}", e);
+                ApiError::InvalidRestoreToken
+            })?;
+
+    if !db::queries::cancel_account_deletion(&state.db, claims.user_id).await? {
+        return Err(ApiError::InvalidRestoreToken);
+    }
+    tracing::info!("User {} cancelled their account deletion", claims.user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn word(user_id: i64, word: &str, score: i32) -> VersusWord {
+        VersusWord {
+            user_id,
+            word: word.to_string(),
+            score,
+            game_id: Uuid::nil(),
+        }
+    }
+
+    #[test]
+    fn test_parse_user_id() {
+        assert_eq!(parse_user_id("me", 7).unwrap(), 7);
+        assert_eq!(parse_user_id("123", 7).unwrap(), 123);
+        assert!(matches!(
+            parse_user_id("someone", 7),
+            Err(ApiError::InvalidUserId)
+        ));
+    }
+
+    #[test]
+    fn test_head_to_head_splits_sides() {
+        let record = VersusRecord {
+            games: 5,
+            wins: 3,
+            losses: 1,
+            draws: 1,
+            average_margin: 12.5,
+        };
+        let words = vec![
+            word(2, "QUARTZ", 40),
+            word(1, "JINX", 30),
+            word(2, "ZAP", 20),
+        ];
+
+        let versus = HeadToHead::new(1, 2, record, words);
+        assert_eq!((versus.games, versus.draws), (5, 1));
+        assert_eq!((versus.a.user_id, versus.a.wins), (1, 3));
+        assert_eq!((versus.b.user_id, versus.b.wins), (2, 1));
+        assert_eq!(versus.a.best_words, vec![word(1, "JINX", 30)]);
+        assert_eq!(
+            versus.b.best_words,
+            vec![word(2, "QUARTZ", 40), word(2, "ZAP", 20)],
+            "Each side keeps its words best first"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt::Write, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    db,
+    models::{season::next_season_window, Season},
+    websocket::messages::GuildStandingInfo,
+    AppState,
+};
+
+/// How often the rollover task checks whether the current season has ended
+pub const SEASON_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Players listed in an end-of-season summary
+const SUMMARY_SIZE: i64 = 10;
+
+/// Background task that closes seasons as they end: posts the final standings
+/// and opens the next season
+pub async fn season_rollover_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(SEASON_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("season_rollover", SEASON_CHECK_INTERVAL);
+
+        if let Err(e) = roll_over(&state).await {
+            tracing::error!("Season rollover failed: {}", e);
+        }
+    }
+}
+
+async fn roll_over(state: &AppState) -> sqlx::Result<()> {
+    if db::queries::get_current_season(&state.db).await?.is_none() {
+        let latest = db::queries::get_latest_season(&state.db).await?;
+        let length = chrono::Duration::days(state.config.game.season_length_days.max(1) as i64);
+        let (starts_at, ends_at) =
+            next_season_window(latest.map(|s| s.ends_at), Utc::now(), length);
+        let season = db::queries::create_season(&state.db, starts_at, ends_at).await?;
+        tracing::info!(
+            "{} started (ends {})",
+            season.name,
+            season.ends_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+
+    for season in db::queries::get_unsummarized_seasons(&state.db).await? {
+        let standings = GuildStandingInfo::rank_all(
+            db::queries::get_season_standings(&state.db, season.season_id, SUMMARY_SIZE).await?,
+        );
+        let summary = summary_message(&season, &standings);
+        tracing::info!("{} ended:\n{}", season.name, summary);
+
+        // Without a webhook the summary is only logged; a failed post is retried
+        // on the next check
+        if let Some(url) = &state.config.game.season_summary_webhook_url {
+            if !post_summary(state, url, &summary).await {
+                continue;
+            }
+        }
+        db::queries::mark_season_summarized(&state.db, season.season_id).await?;
+    }
+    Ok(())
+}
+
+/// Send a summary to a Discord webhook; returns false if it didn't go through
+async fn post_summary(state: &AppState, url: &str, summary: &str) -> bool {
+    let body = json!({
+        "content": summary,
+        // Never ping anyone, whatever their username looks like
+        "allowed_mentions": { "parse": [] },
+    });
+    match state.http_client.post(url).json(&body).send().await {
+        Ok(response) if response.status().is_success() => true,
+        Ok(response) => {
+            tracing::warn!("Season summary webhook returned {}", response.status());
+            false
+        }
+        Err(e) => {
+            tracing::warn!("Season summary webhook request failed: {}", e);
+            false
+        }
+    }
+}
+
+/// The end-of-season post: the season's dates and its top players
+pub fn summary_message(season: &Season, standings: &[GuildStandingInfo]) -> String {
+    let mut message = format!(
+        "🏆 **{} has ended!** ({} – {})\n",
+        season.name,
+        season.starts_at.format("%b %-d"),
+        season.ends_at.format("%b %-d, %Y")
+    );
+    if standings.is_empty() {
+        message.push_str("No games were played this season.");
+        return message;
+    }
+    for standing in standings {
+        let medal = match standing.rank {
+            1 => "🥇",
+            2 => "🥈",
+            3 => "🥉",
+            _ => "▫️",
+        };
+        let _ = writeln!(
+            message,
+            "{} #{} {} — {} pts, {} wins in {} games",
+            medal,
+            standing.rank,
+            standing.username,
+            standing.total_score,
+            standing.wins,
+            standing.games_played
+        );
+    }
+    message.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::models::GuildStanding;
+
+    fn season() -> Season {
+        Season {
+            season_id: 2,
+            name: "Season 2".to_string(),
+            starts_at: Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2026, 3, 31, 0, 0, 0).unwrap(),
+            summarized_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn standing(user_id: i64, username: &str, total_score: i64) -> GuildStanding {
+        GuildStanding {
+            user_id,
+            username: username.to_string(),
+            avatar_url: None,
+            games_played: 4,
+            wins: 1,
+            total_score,
+        }
+    }
+
+    #[test]
+    fn test_summary_lists_ranked_players() {
+        let standings = GuildStandingInfo::rank_all(vec![
+            standing(1, "alice", 300),
+            standing(2, "bob", 300),
+            standing(3, "carol", 120),
+        ]);
+        let summary = summary_message(&season(), &standings);
+
+        assert!(summary.starts_with("🏆 **Season 2 has ended!** (Mar 1 – Mar 31, 2026)"));
+        assert!(summary.contains("🥇 #1 alice — 300 pts, 1 wins in 4 games"));
+        assert!(
+            summary.contains("🥇 #1 bob"),
+            "Tied players share a rank and medal"
+        );
+        assert!(summary.contains("🥉 #3 carol"));
+        assert!(!summary.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_summary_without_games() {
+        let summary = summary_message(&season(), &[]);
+        assert!(summary.ends_with("No games were played this season."));
+    }
+}
@@ -0,0 +1,138 @@
+use axum::{
+    http::{header, HeaderName, HeaderValue},
+    Router,
+};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+use crate::config::SecurityHeadersConfig;
+
+/// CSP for the frontend running as a Discord Activity: everything is served from
+/// this origin, the game talks to it over a WebSocket, avatars come from Discord's
+/// CDN, and only Discord may frame the page
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self'; \
+    style-src 'self' 'unsafe-inline'; \
+    img-src 'self' data: https://cdn.discordapp.com https://media.discordapp.net; \
+    connect-src 'self' wss: https://discord.com; \
+    font-src 'self' data:; \
+    frame-ancestors 'self' https://discord.com https://*.discord.com https://*.discordsays.com; \
+    base-uri 'self'; \
+    form-action 'self'";
+pub const DEFAULT_REFERRER_POLICY: &str = "strict-origin-when-cross-origin";
+const HSTS: &str = "max-age=31536000; includeSubDomains";
+
+/// Add security headers to every response from `router`
+///
+/// API and WebSocket routes are never framed, so they also get
+/// `X-Frame-Options: DENY`. The frontend (the router's fallback) doesn't: it runs
+/// inside Discord's iframe, which the CSP `frame-ancestors` directive allows, and
+/// X-Frame-Options can't name more than one allowed origin.
+pub fn apply<S>(router: Router<S>, config: &SecurityHeadersConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let mut router = router
+        .route_layer(set_header(
+            header::X_FRAME_OPTIONS,
+            HeaderValue::from_static("DENY"),
+        ))
+        .layer(set_header(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ));
+
+    if let Some(value) = header_value("REFERRER_POLICY", &config.referrer_policy) {
+        router = router.layer(set_header(header::REFERRER_POLICY, value));
+    }
+    if let Some(value) = header_value("CONTENT_SECURITY_POLICY", &config.content_security_policy) {
+        router = router.layer(set_header(header::CONTENT_SECURITY_POLICY, value));
+    }
+    if config.hsts {
+        router = router.layer(set_header(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static(HSTS),
+        ));
+    }
+    router
+}
+
+/// Handlers that set a header themselves keep their value
+fn set_header(name: HeaderName, value: HeaderValue) -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::if_not_present(name, value)
+}
+
+/// Parse a configured header value; empty disables the header
+fn header_value(setting: &str, value: &str) -> Option<HeaderValue> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match HeaderValue::from_str(value) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring {}: not a valid header value", setting);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn test_router(config: &SecurityHeadersConfig) -> Router {
+        let router = Router::new()
+            .route("/api/test", get(|| async { "ok" }))
+            .fallback(|| async { "frontend" });
+        apply(router, config)
+    }
+
+    async fn get_headers(router: Router, uri: &str) -> axum::http::HeaderMap {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().headers().clone()
+    }
+
+    #[tokio::test]
+    async fn test_api_routes_cannot_be_framed() {
+        let config = SecurityHeadersConfig::default();
+        let headers = get_headers(test_router(&config), "/api/test").await;
+
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::REFERRER_POLICY], DEFAULT_REFERRER_POLICY);
+        assert!(
+            headers.get(header::STRICT_TRANSPORT_SECURITY).is_none(),
+            "HSTS is off unless configured"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_frontend_can_be_framed_by_discord() {
+        let config = SecurityHeadersConfig {
+            hsts: true,
+            ..SecurityHeadersConfig::default()
+        };
+        let headers = get_headers(test_router(&config), "/index.html").await;
+
+        assert!(
+            headers.get(header::X_FRAME_OPTIONS).is_none(),
+            "The Activity iframe relies on frame-ancestors instead"
+        );
+        let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+        assert!(csp.contains("frame-ancestors 'self' https://discord.com"));
+        assert_eq!(headers[header::STRICT_TRANSPORT_SECURITY], HSTS);
+    }
+
+    #[tokio::test]
+    async fn test_empty_csp_is_not_sent() {
+        let config = SecurityHeadersConfig {
+            content_security_policy: String::new(),
+            ..SecurityHeadersConfig::default()
+        };
+        let headers = get_headers(test_router(&config), "/").await;
+        assert!(headers.get(header::CONTENT_SECURITY_POLICY).is_none());
+    }
+}
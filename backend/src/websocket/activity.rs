@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Most entries a lobby's feed keeps; older ones are dropped
+pub const ACTIVITY_FEED_SIZE: usize = 50;
+/// Words scoring at least this much are called out in the feed
+pub const NOTABLE_WORD_SCORE: i32 = 30;
+
+/// Something that happened in a lobby
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Activity {
+    PlayerJoined {
+        user_id: i64,
+        username: String,
+    },
+    PlayerLeft {
+        user_id: i64,
+        username: String,
+    },
+    GameStarted {
+        game_id: String,
+        player_count: usize,
+    },
+    /// A game ended; `winner` is null for draws and co-op games
+    GameFinished {
+        game_id: String,
+        winner: Option<i64>,
+        winner_username: Option<String>,
+        /// For co-op games, whether the team won
+        #[serde(skip_serializing_if = "Option::is_none")]
+        team_won: Option<bool>,
+    },
+    NotableWord {
+        user_id: i64,
+        username: String,
+        word: String,
+        score: i32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub activity: Activity,
+}
+
+/// A lobby's recent activity, kept for as long as the lobby exists so late
+/// joiners can catch up on what they missed
+#[derive(Debug, Default)]
+pub struct ActivityFeed {
+    entries: VecDeque<ActivityEntry>,
+}
+
+impl ActivityFeed {
+    pub fn record(&mut self, activity: Activity, at: DateTime<Utc>) {
+        if self.entries.len() >= ACTIVITY_FEED_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(ActivityEntry { at, activity });
+    }
+
+    /// Every kept entry, oldest first
+    pub fn recent(&self) -> Vec<ActivityEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(user_id: i64) -> Activity {
+        Activity::PlayerJoined {
+            user_id,
+            username: format!("player{}", user_id),
+        }
+    }
+
+    #[test]
+    fn test_feed_keeps_the_latest_entries() {
+        let mut feed = ActivityFeed::default();
+        for user_id in 0..(ACTIVITY_FEED_SIZE as i64 + 5) {
+            feed.record(joined(user_id), Utc::now());
+        }
+
+        let recent = feed.recent();
+        assert_eq!(recent.len(), ACTIVITY_FEED_SIZE);
+        assert_eq!(recent[0].activity, joined(5), "The oldest entries go first");
+        assert_eq!(
+            recent.last().unwrap().activity,
+            joined(ACTIVITY_FEED_SIZE as i64 + 4)
+        );
+    }
+
+    #[test]
+    fn test_entries_serialize_flat() {
+        let entry = ActivityEntry {
+            at: Utc::now(),
+            activity: Activity::NotableWord {
+                user_id: 7,
+                username: "alice".to_string(),
+                word: "QUIXOTIC".to_string(),
+                score: 64,
+            },
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["kind"], "notable_word");
+        assert_eq!(json["word"], "QUIXOTIC");
+        assert!(json["at"].is_string());
+    }
+}
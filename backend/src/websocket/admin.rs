@@ -0,0 +1,684 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::extract::ws::{Message, WebSocket};
+use futures::{sink::SinkExt, stream::StreamExt};
+use tokio::sync::{mpsc, Mutex};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthenticatedUser,
+    db,
+    error::ApiError,
+    game::{
+        grid::{board_checksum, LetterMode},
+        session::GameSession,
+    },
+    models::{GameEvent, GameListing, GamePlayerSummary},
+    websocket::{
+        game_actor::{self, ActionOutcome, GameAction},
+        handler::{broadcast_to_lobby, game_state_message, require_game_session},
+        messages::{
+            AdminClientMessage, AdminGameInfo, AdminGamePlayerInfo, AdminLobbyInfo, ServerMessage,
+        },
+        trace,
+    },
+    AppState, Lobby, PlayerConnectionState,
+};
+
+/// An admin console connection and the lobbies it is observing
+struct AdminConsole {
+    /// Key under which this connection is registered in `Lobby::observers`
+    observer_id: Uuid,
+    observing: HashSet<String>,
+}
+
+/// Handle an admin console connection (`/ws?admin=1`)
+///
+/// The caller has already checked that the user is an admin. The console never joins
+/// a lobby as a player, so observing a lobby doesn't affect its player list or host.
+pub async fn handle_admin_socket(socket: WebSocket, state: Arc<AppState>, user: AuthenticatedUser) {
+    let (mut sender, mut receiver) = socket.split();
+    // Observed lobbies forward with try_send, so give the console some headroom
+    let (tx, mut rx) = mpsc::channel::<ServerMessage>(256);
+
+    let mut send_task = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            match serde_json::to_string(&msg) {
+                Ok(json) => {
+                    if sender.send(Message::Text(json.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to serialize message: {}", e);
+                }
+            }
+        }
+    });
+
+    let console = Arc::new(Mutex::new(AdminConsole {
+        observer_id: Uuid::new_v4(),
+        observing: HashSet::new(),
+    }));
+
+    let user_for_recv = user.clone();
+    let state_for_recv = state.clone();
+    let console_for_recv = console.clone();
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            match msg {
+                Message::Text(text) => {
+                    let request_id = trace::new_request_id();
+                    let span = tracing::info_span!(
+                        "admin_message",
+                        request_id = %request_id,
+                        user_id = user_for_recv.user_id,
+                    );
+                    trace::with_request_id(
+                        request_id,
+                        handle_admin_text_message(
+                            &text,
+                            &state_for_recv,
+                            &tx,
+                            &user_for_recv,
+                            &console_for_recv,
+                        ),
+                    )
+                    .instrument(span)
+                    .await;
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut send_task) => {
+            recv_task.abort();
+        }
+        _ = (&mut recv_task) => {
+            send_task.abort();
+        }
+    }
+
+    // Unregister from every lobby this console was observing
+    let console = console.lock().await;
+    for lobby_id in &console.observing {
+        if let Some(lobby) = state.lobbies.get(lobby_id) {
+            lobby.observers.remove(&console.observer_id);
+        }
+    }
+
+    tracing::info!(
+        "Admin console closed for {} ({})",
+        user.username,
+        user.user_id
+    );
+}
+
+/// Parse and dispatch a single admin console frame, reporting failures back
+async fn handle_admin_text_message(
+    text: &str,
+    state: &Arc<AppState>,
+    tx: &mpsc::Sender<ServerMessage>,
+    user: &AuthenticatedUser,
+    console: &Arc<Mutex<AdminConsole>>,
+) {
+    let result = match serde_json::from_str::<AdminClientMessage>(text) {
+        Ok(msg) => handle_admin_message(msg, state, tx, user, console).await,
+        Err(e) => Err(ApiError::InvalidMessage(e.to_string())),
+    };
+
+    if let Err(e) = result {
+        if e.is_internal() {
+            tracing::error!("Error handling admin message ({}): {:?}", e.code(), e);
+        } else {
+            tracing::debug!("Rejected admin message ({}): {}", e.code(), e);
+        }
+        if !matches!(e, ApiError::ConnectionClosed) {
+            let _ = tx.send(e.into()).await;
+        }
+    }
+}
+
+async fn handle_admin_message(
+    msg: AdminClientMessage,
+    state: &Arc<AppState>,
+    tx: &mpsc::Sender<ServerMessage>,
+    user: &AuthenticatedUser,
+    console: &Arc<Mutex<AdminConsole>>,
+) -> Result<(), ApiError> {
+    match msg {
+        AdminClientMessage::ListLobbies => {
+            let mut lobbies: Vec<AdminLobbyInfo> = state
+                .lobbies
+                .iter()
+                .map(|lobby| {
+                    let session = lobby
+                        .active_game_id
+                        .and_then(|game_id| state.active_games.get(&game_id));
+                    lobby_summary(&lobby, session.as_deref())
+                })
+                .collect();
+            lobbies.sort_by(|a, b| a.lobby_id.cmp(&b.lobby_id));
+
+            tx.send(ServerMessage::AdminLobbyList { lobbies }).await?;
+        }
+
+        AdminClientMessage::Observe { lobby_id } => {
+            let mut console = console.lock().await;
+            state
+                .lobbies
+                .get(&lobby_id)
+                .ok_or(ApiError::LobbyNotFound)?
+                .observers
+                .insert(console.observer_id, tx.clone());
+            console.observing.insert(lobby_id.clone());
+
+            tracing::info!(
+                "Admin {} ({}) is observing lobby {}",
+                user.username,
+                user.user_id,
+                lobby_id
+            );
+            tx.send(ServerMessage::AdminObserving {
+                lobby_id,
+                observing: true,
+            })
+            .await?;
+        }
+
+        AdminClientMessage::StopObserving { lobby_id } => {
+            let mut console = console.lock().await;
+            if let Some(lobby) = state.lobbies.get(&lobby_id) {
+                lobby.observers.remove(&console.observer_id);
+            }
+            console.observing.remove(&lobby_id);
+
+            tx.send(ServerMessage::AdminObserving {
+                lobby_id,
+                observing: false,
+            })
+            .await?;
+        }
+
+        AdminClientMessage::ForceAdvanceTurn { lobby_id } => {
+            let game_id = force_advance_turn(state, &lobby_id, user).await?;
+            tx.send(ServerMessage::AdminTurnAdvanced {
+                lobby_id,
+                game_id: game_id.to_string(),
+            })
+            .await?;
+        }
+
+        AdminClientMessage::ResetBoard { lobby_id } => {
+            let (game_id, board_checksum) = reset_board(state, &lobby_id, user).await?;
+            tx.send(ServerMessage::AdminBoardReset {
+                lobby_id,
+                game_id: game_id.to_string(),
+                board_checksum,
+            })
+            .await?;
+        }
+
+        AdminClientMessage::RebuildGame { lobby_id } => {
+            let (game_id, discarded_moves) = rebuild_game(state, &lobby_id, user).await?;
+            tx.send(ServerMessage::AdminGameRebuilt {
+                lobby_id,
+                game_id: game_id.to_string(),
+                discarded_moves,
+            })
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The lobby a game is being played in
+pub fn lobby_for_game(state: &AppState, game_id: Uuid) -> Result<String, ApiError> {
+    state
+        .lobbies
+        .iter()
+        .find(|lobby| lobby.active_game_id == Some(game_id))
+        .map(|lobby| lobby.lobby_id.clone())
+        .ok_or(ApiError::GameNotFound)
+}
+
+/// Pass the current player's turn in a stuck game
+pub async fn force_advance_turn(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    admin: &AuthenticatedUser,
+) -> Result<Uuid, ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+    let skipped_player = state
+        .active_games
+        .get(&game_id)
+        .and_then(|session| session.current_player_id());
+
+    tracing::warn!(
+        "Admin {} ({}) force-advanced the turn in lobby {} (skipped player: {:?})",
+        admin.username,
+        admin.user_id,
+        lobby_id,
+        skipped_player
+    );
+    game_actor::perform(state, lobby_id, admin, GameAction::ForceAdvanceTurn).await?;
+    Ok(game_id)
+}
+
+/// Deal a fresh board for the rest of the game, keeping scores, turn and used
+/// words; returns the new board's checksum
+///
+/// Every client in the lobby is sent the new board right away.
+pub async fn reset_board(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    admin: &AuthenticatedUser,
+) -> Result<(Uuid, String), ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+    let ActionOutcome::BoardReset { board_checksum } =
+        game_actor::perform(state, lobby_id, admin, GameAction::ResetBoard).await?
+    else {
+        return Err(ApiError::Internal);
+    };
+
+    tracing::warn!(
+        "Admin {} ({}) reset the board in lobby {} (game {})",
+        admin.username,
+        admin.user_id,
+        lobby_id,
+        game_id
+    );
+    resync_lobby(state, lobby_id, game_id).await;
+    Ok((game_id, board_checksum))
+}
+
+/// The game's part of a board reset, run by its actor: commit the new board and
+/// store it as the latest revision
+pub(super) async fn deal_fresh_board(
+    state: &AppState,
+    lobby_id: &str,
+    game_id: Uuid,
+) -> Result<String, ApiError> {
+    let board_key = state
+        .lobbies
+        .get(lobby_id)
+        .ok_or(ApiError::LobbyNotFound)?
+        .board_key(&state.dictionary_packs);
+    let (grid, letter_bag, move_number) = {
+        let mut session = state
+            .active_games
+            .get_mut(&game_id)
+            .ok_or(ApiError::NoActiveGame)?;
+        // Letter bag games get a fresh bag along with the board
+        let letters = if session.state.letter_bag.is_some() {
+            LetterMode::Bag
+        } else {
+            LetterMode::Weighted
+        };
+        let (grid, letter_bag) = state.grid_pool.deal(&board_key, letters);
+        session.commit(vec![GameEvent::BoardReset { grid, letter_bag }]);
+        let move_number = session.state.move_count as i32;
+        (
+            session.state.grid.clone(),
+            session.state.letter_bag.clone(),
+            move_number,
+        )
+    };
+
+    // The new board is what a rebuild or restart loads, and starts a new revision
+    let grid_json = serde_json::to_value(&grid)?;
+    let letter_bag_json = letter_bag.as_ref().map(serde_json::to_value).transpose()?;
+    db::queries::create_or_update_game_board(
+        &state.db,
+        game_id,
+        grid_json.clone(),
+        letter_bag_json,
+    )
+    .await?;
+    db::queries::insert_board_revision(&state.db, game_id, move_number, &grid_json).await?;
+    Ok(board_checksum(&grid))
+}
+
+/// Replace a lobby's cached game session with one loaded from the database;
+/// returns how many unflushed moves the old session had (they are dropped)
+///
+/// For games whose in-memory state has drifted from what was stored. Every
+/// client in the lobby is resynced to the rebuilt state.
+pub async fn rebuild_game(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    admin: &AuthenticatedUser,
+) -> Result<(Uuid, usize), ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+    let ActionOutcome::GameRebuilt { discarded_moves } =
+        game_actor::perform(state, lobby_id, admin, GameAction::RebuildGame).await?
+    else {
+        return Err(ApiError::Internal);
+    };
+
+    tracing::warn!(
+        "Admin {} ({}) rebuilt game {} in lobby {} from the database ({} unflushed moves dropped)",
+        admin.username,
+        admin.user_id,
+        game_id,
+        lobby_id,
+        discarded_moves
+    );
+    resync_lobby(state, lobby_id, game_id).await;
+    Ok((game_id, discarded_moves))
+}
+
+/// The game's part of a rebuild, run by its actor so no command is halfway
+/// through the session it replaces
+pub(super) async fn reload_game_session(
+    state: &AppState,
+    lobby_id: &str,
+    game_id: Uuid,
+) -> Result<usize, ApiError> {
+    // Wait out any save in flight, so it is in the database before the reload
+    // and a failed one can't put its batch back into the rebuilt session
+    let flush_lock = state
+        .active_games
+        .get(&game_id)
+        .map(|session| session.flush_lock.clone());
+    let _flushing = match flush_lock.clone() {
+        Some(flush_lock) => Some(flush_lock.lock_owned().await),
+        None => None,
+    };
+
+    let (game_state, player_ids) = db::queries::get_active_game(&state.db, game_id)
+        .await?
+        .ok_or(ApiError::GameNotFound)?;
+
+    // Swap in place so the game is never missing from the cache mid-rebuild
+    let mut session = GameSession::new(lobby_id.to_string(), player_ids, game_state);
+    session.event_seq_base = db::queries::next_game_event_seq(&state.db, game_id).await?;
+    if let Some(flush_lock) = flush_lock {
+        session.flush_lock = flush_lock;
+    }
+    Ok(state
+        .active_games
+        .insert(game_id, session)
+        .map_or(0, |old| old.pending_moves.len()))
+}
+
+/// Send every client in a lobby the full state of its game
+async fn resync_lobby(state: &AppState, lobby_id: &str, game_id: Uuid) {
+    let message = state
+        .active_games
+        .get(&game_id)
+        .map(|session| ServerMessage::ResyncState {
+            state: Box::new(game_state_message(state, &session, None)),
+        });
+    if let Some(message) = message {
+        broadcast_to_lobby(state, lobby_id, message).await;
+    }
+}
+
+/// Summarize a lobby and its running game for the admin lobby list
+fn lobby_summary(lobby: &Lobby, session: Option<&GameSession>) -> AdminLobbyInfo {
+    AdminLobbyInfo {
+        lobby_id: lobby.lobby_id.clone(),
+        lobby_type: lobby.lobby_type.clone(),
+        lobby_code: lobby.lobby_code.clone(),
+        guild_id: lobby.guild_id.clone(),
+        host_id: lobby.host_id.map(|id| id.to_string()),
+        player_count: lobby.players.len(),
+        connected_count: lobby.connected_player_count(),
+        observer_count: lobby.observers.len(),
+        game_id: lobby.active_game_id.map(|id| id.to_string()),
+        current_round: session.map(|s| s.state.current_round),
+        total_rounds: session.map(|s| s.state.total_rounds),
+        current_player: session
+            .and_then(|s| s.current_player_id())
+            .map(|id| id.to_string()),
+    }
+}
+
+/// Describe a stored game for the host's admin games list
+///
+/// Connection status comes from the lobby. While the game is running the session
+/// is used for round, turn and scores, since the stored ones lag until the next flush.
+pub fn admin_game_info(
+    game: &GameListing,
+    players: &[GamePlayerSummary],
+    lobby: Option<&Lobby>,
+    session: Option<&GameSession>,
+) -> AdminGameInfo {
+    let players = players
+        .iter()
+        .map(|player| {
+            let lobby_player = lobby.and_then(|l| l.players.get(&player.user_id));
+            let lobby_player = lobby_player.as_deref();
+            let live_score = session.and_then(|s| {
+                let index = s.player_index(player.user_id)?;
+                s.state.players.get(index).map(|p| p.score)
+            });
+            AdminGamePlayerInfo {
+                user_id: player.user_id.to_string(),
+                username: player.username.clone(),
+                score: live_score.unwrap_or(player.score),
+                is_connected: lobby_player.is_some_and(|p| p.is_connected()),
+                awaiting_reconnect: lobby_player.is_some_and(|p| {
+                    matches!(
+                        p.connection_state,
+                        PlayerConnectionState::AwaitingReconnect { .. }
+                    )
+                }),
+            }
+        })
+        .collect();
+
+    let (current_round, current_player) = match session {
+        Some(session) => (
+            session.state.current_round as i32,
+            session.current_player_id(),
+        ),
+        None => (game.current_round, game.current_turn_player),
+    };
+
+    AdminGameInfo {
+        game_id: game.game_id.to_string(),
+        state: game.state.to_string(),
+        created_at: game.created_at,
+        current_round,
+        max_rounds: game.max_rounds,
+        current_player: current_player.map(|id| id.to_string()),
+        players,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{GameDbState, GameMode, GamePlayer, GameState},
+        websocket::outbound::ClientSender,
+        LobbyPlayer, PlayerConnectionState,
+    };
+
+    fn create_test_player(user_id: i64) -> LobbyPlayer {
+        let (tx, _rx) = ClientSender::channel(1, std::time::Duration::from_secs(10));
+        LobbyPlayer {
+            user_id,
+            username: format!("Player{}", user_id),
+            avatar_url: None,
+            tx,
+            connection_state: PlayerConnectionState::Connected,
+            ready: false,
+            typing_since: None,
+            muted: HashSet::new(),
+            spectator: false,
+        }
+    }
+
+    #[test]
+    fn test_lobby_summary_without_game() {
+        let mut lobby = Lobby::new_channel("123".to_string(), Some("456".to_string()));
+        lobby.host_id = Some(1);
+        lobby.players.insert(1, create_test_player(1));
+
+        let summary = lobby_summary(&lobby, None);
+        assert_eq!(summary.lobby_id, "channel:123");
+        assert_eq!(summary.guild_id.as_deref(), Some("456"));
+        assert_eq!(summary.host_id.as_deref(), Some("1"));
+        assert_eq!(summary.player_count, 1);
+        assert_eq!(summary.connected_count, 1);
+        assert!(summary.game_id.is_none());
+        assert!(
+            summary.current_player.is_none(),
+            "A lobby without a game should have no current player"
+        );
+    }
+
+    #[test]
+    fn test_lobby_summary_reports_game_progress() {
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+        ];
+        let state = GameState::new(Uuid::new_v4(), vec![], players, 5);
+        let session = GameSession::new("custom:ABC123".to_string(), vec![111, 222], state);
+
+        let mut lobby = Lobby::new_custom("ABC123".to_string());
+        lobby.active_game_id = Some(session.game_id);
+        let (observer_tx, _observer_rx) = mpsc::channel(1);
+        lobby.observers.insert(Uuid::new_v4(), observer_tx);
+
+        let summary = lobby_summary(&lobby, Some(&session));
+        assert_eq!(summary.game_id, Some(session.game_id.to_string()));
+        assert_eq!(summary.current_round, Some(1));
+        assert_eq!(summary.total_rounds, Some(5));
+        assert_eq!(summary.current_player.as_deref(), Some("111"));
+        assert_eq!(summary.observer_count, 1);
+    }
+
+    #[test]
+    fn test_observers_receive_wrapped_broadcasts() {
+        let lobby = Lobby::new_custom("ABC123".to_string());
+        let (observer_tx, mut observer_rx) = mpsc::channel(1);
+        lobby.observers.insert(Uuid::new_v4(), observer_tx);
+
+        lobby.notify_observers(&ServerMessage::GameCreated {
+            game_id: "g1".to_string(),
+        });
+
+        match observer_rx.try_recv() {
+            Ok(ServerMessage::AdminObserved { lobby_id, message }) => {
+                assert_eq!(lobby_id, "custom:ABC123");
+                assert!(matches!(*message, ServerMessage::GameCreated { .. }));
+            }
+            other => panic!("Expected AdminObserved, got {:?}", other),
+        }
+
+        // A full observer queue must not block or error the broadcast
+        lobby.notify_observers(&ServerMessage::GameCreated {
+            game_id: "g2".to_string(),
+        });
+        lobby.notify_observers(&ServerMessage::GameCreated {
+            game_id: "g3".to_string(),
+        });
+    }
+
+    fn create_test_listing(game_id: Uuid) -> GameListing {
+        GameListing {
+            game_id,
+            guild_id: None,
+            channel_id: 123,
+            game_mode: GameMode::Multiplayer,
+            state: GameDbState::Active,
+            current_round: 2,
+            max_rounds: 5,
+            current_turn_player: Some(222),
+            created_at: chrono::Utc::now(),
+            started_at: None,
+            player_count: 2,
+        }
+    }
+
+    fn create_test_summaries(game_id: Uuid) -> Vec<GamePlayerSummary> {
+        [(111, 10), (222, 20)]
+            .into_iter()
+            .map(|(user_id, score)| GamePlayerSummary {
+                game_id,
+                user_id,
+                username: format!("Player{}", user_id),
+                score,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_admin_game_info_from_stored_game() {
+        let game_id = Uuid::new_v4();
+        let lobby = Lobby::new_channel("123".to_string(), None);
+        lobby.players.insert(111, create_test_player(111));
+        let mut away = create_test_player(222);
+        away.connection_state = PlayerConnectionState::AwaitingReconnect {
+            since: std::time::Instant::now(),
+        };
+        lobby.players.insert(222, away);
+
+        let info = admin_game_info(
+            &create_test_listing(game_id),
+            &create_test_summaries(game_id),
+            Some(&lobby),
+            None,
+        );
+        assert_eq!(info.state, "active");
+        assert_eq!(info.current_round, 2);
+        assert_eq!(info.current_player.as_deref(), Some("222"));
+        assert_eq!(info.players.len(), 2);
+        assert_eq!(info.players[0].username, "Player111");
+        assert_eq!(info.players[0].score, 10);
+        assert!(info.players[0].is_connected && !info.players[0].awaiting_reconnect);
+        assert!(!info.players[1].is_connected && info.players[1].awaiting_reconnect);
+
+        let info = admin_game_info(
+            &create_test_listing(game_id),
+            &create_test_summaries(game_id),
+            None,
+            None,
+        );
+        assert!(
+            info.players
+                .iter()
+                .all(|p| !p.is_connected && !p.awaiting_reconnect),
+            "Players not in the lobby are offline"
+        );
+    }
+
+    #[test]
+    fn test_admin_game_info_prefers_live_session() {
+        let players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player111".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player222".to_string(), None, 1),
+        ];
+        let mut state = GameState::new(Uuid::new_v4(), vec![], players, 5);
+        state.players[0].score = 35;
+        let session = GameSession::new("channel:123".to_string(), vec![111, 222], state);
+
+        let info = admin_game_info(
+            &create_test_listing(session.game_id),
+            &create_test_summaries(session.game_id),
+            None,
+            Some(&session),
+        );
+        assert_eq!(
+            info.players[0].score, 35,
+            "Unflushed scores come from the session"
+        );
+        assert_eq!(info.players[1].score, 0);
+        assert_eq!(info.current_round, 1);
+        assert_eq!(
+            info.current_player.as_deref(),
+            Some("111"),
+            "The stored turn holder may be stale"
+        );
+    }
+}
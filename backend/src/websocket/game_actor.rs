@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::MissedTickBehavior,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use super::{
+    admin,
+    handler::{self, require_game_session},
+    outbound::ClientSender,
+};
+use crate::{
+    auth::AuthenticatedUser, error::ApiError, models::Position, AppState, BLITZ_CLOCK_INTERVAL,
+};
+
+/// Commands a game's actor holds before senders wait for room
+const GAME_COMMAND_BUFFER: usize = 64;
+
+/// A change to a running game, made by its actor
+#[derive(Debug)]
+pub enum GameAction {
+    SubmitWord {
+        /// The submitting connection, for the replies only it gets
+        tx: ClientSender,
+        word: String,
+        positions: Vec<Position>,
+        /// Checksum of the board the word was found on
+        board_checksum: Option<String>,
+        submission_id: Option<String>,
+    },
+    UndoMove,
+    PassTurn,
+    Resign,
+    /// An admin ending the current turn of a stuck game
+    ForceAdvanceTurn,
+    /// An admin dealing a fresh board for a stuck game
+    ResetBoard,
+    /// An admin replacing a desynced game's session with what's stored
+    RebuildGame,
+}
+
+/// What an action reports back once its actor has carried it out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionOutcome {
+    Done,
+    /// The fresh board's checksum
+    BoardReset {
+        board_checksum: String,
+    },
+    /// How many unflushed moves the replaced session had
+    GameRebuilt {
+        discarded_moves: usize,
+    },
+}
+
+/// An action and who asked for it, queued for a game's actor
+struct GameCommand {
+    lobby_id: String,
+    user: AuthenticatedUser,
+    action: GameAction,
+    reply: oneshot::Sender<Result<ActionOutcome, ApiError>>,
+    /// The sender's span, so the action is logged under their request ID
+    span: tracing::Span,
+}
+
+/// The actor of each running game, keyed by game_id
+///
+/// Every change to a game's session is made by its actor, one command at a
+/// time, so two players' moves can't interleave between reading the session and
+/// writing it back, and the game's clocks are checked on the same task.
+/// Readers (game state messages, the flush, the admin console) still read the
+/// session from `AppState::active_games`.
+#[derive(Default)]
+pub struct GameActors {
+    senders: DashMap<Uuid, mpsc::Sender<GameCommand>>,
+}
+
+impl GameActors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the game has an actor running
+    pub fn is_running(&self, game_id: Uuid) -> bool {
+        self.senders
+            .get(&game_id)
+            .is_some_and(|sender| !sender.is_closed())
+    }
+
+    /// The game's command queue, starting its actor if it isn't running
+    fn sender(&self, state: &Arc<AppState>, game_id: Uuid) -> mpsc::Sender<GameCommand> {
+        match self.senders.entry(game_id) {
+            Entry::Occupied(entry) if !entry.get().is_closed() => entry.get().clone(),
+            entry => {
+                let (sender, commands) = mpsc::channel(GAME_COMMAND_BUFFER);
+                tokio::spawn(run_game_actor(state.clone(), game_id, commands));
+                tracing::debug!("Started the actor for game {}", game_id);
+                entry.insert(sender.clone());
+                sender
+            }
+        }
+    }
+
+    /// Forget an actor that has stopped
+    fn remove_stopped(&self, game_id: Uuid) {
+        self.senders
+            .remove_if(&game_id, |_, sender| sender.is_closed());
+    }
+}
+
+/// Have the actor of the lobby's game carry out `action` for `user`, and wait
+/// for it to be done
+pub async fn perform(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+    action: GameAction,
+) -> Result<ActionOutcome, ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+    let (reply, result) = oneshot::channel();
+    let command = GameCommand {
+        lobby_id: lobby_id.to_string(),
+        user: user.clone(),
+        action,
+        reply,
+        span: tracing::Span::current(),
+    };
+    // The actor stops once the game is over
+    state
+        .game_actors
+        .sender(state, game_id)
+        .send(command)
+        .await
+        .map_err(|_| ApiError::GameNotFound)?;
+    result.await.map_err(|_| ApiError::GameNotFound)?
+}
+
+/// Background task that keeps an actor running for every cached game, so clocks
+/// run out even while nobody is playing
+pub async fn game_actor_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(BLITZ_CLOCK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("game_actors", BLITZ_CLOCK_INTERVAL);
+
+        let game_ids: Vec<Uuid> = state
+            .active_games
+            .iter()
+            .map(|session| session.game_id)
+            .filter(|&game_id| !state.game_actors.is_running(game_id))
+            .collect();
+        for game_id in game_ids {
+            state.game_actors.sender(&state, game_id);
+        }
+    }
+}
+
+/// A game's actor: carries out its commands in order and checks its clocks,
+/// until the game leaves the cache
+async fn run_game_actor(
+    state: Arc<AppState>,
+    game_id: Uuid,
+    mut commands: mpsc::Receiver<GameCommand>,
+) {
+    let mut clock = tokio::time::interval(BLITZ_CLOCK_INTERVAL);
+    clock.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    // A new or reloaded game's boards haven't been solved yet
+    handler::refresh_remaining_scores(&state, game_id).await;
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let Some(command) = command else {
+                    break;
+                };
+                let GameCommand { lobby_id, user, action, reply, span } = command;
+                let result = run_command(&state, game_id, &lobby_id, &user, action)
+                    .instrument(span)
+                    .await;
+                // Before replying, so a caller that resyncs the lobby sends the
+                // new board's words
+                handler::refresh_remaining_scores(&state, game_id).await;
+                let _ = reply.send(result);
+            }
+            _ = clock.tick() => {
+                handler::check_game_clock(&state, game_id).await;
+            }
+        }
+        if !state.active_games.contains_key(&game_id) {
+            break;
+        }
+    }
+
+    // Whatever was queued behind the end of the game is turned away
+    commands.close();
+    while let Ok(command) = commands.try_recv() {
+        let _ = command.reply.send(Err(ApiError::GameNotFound));
+    }
+    drop(commands);
+    state.game_actors.remove_stopped(game_id);
+    tracing::debug!("Stopped the actor for game {}", game_id);
+}
+
+async fn run_command(
+    state: &Arc<AppState>,
+    game_id: Uuid,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+    action: GameAction,
+) -> Result<ActionOutcome, ApiError> {
+    // A new game may have started in the lobby since the command was sent
+    let lobby_game = state
+        .lobbies
+        .get(lobby_id)
+        .ok_or(ApiError::LobbyNotFound)?
+        .active_game_id;
+    if lobby_game != Some(game_id) {
+        return Err(ApiError::GameNotFound);
+    }
+
+    match action {
+        GameAction::SubmitWord {
+            tx,
+            word,
+            positions,
+            board_checksum,
+            submission_id,
+        } => {
+            let submission = handler::Submission {
+                word,
+                positions,
+                board_checksum,
+                submission_id,
+            };
+            handler::handle_submit_word(state, lobby_id, &tx, user, submission).await?;
+        }
+        GameAction::UndoMove => handler::handle_undo_move(state, lobby_id, user).await?,
+        GameAction::PassTurn => handler::handle_pass_turn(state, lobby_id, user).await?,
+        GameAction::Resign => handler::handle_resign(state, lobby_id, user).await?,
+        GameAction::ForceAdvanceTurn => handler::finish_turn(state, lobby_id, game_id).await,
+        GameAction::ResetBoard => {
+            let board_checksum = admin::deal_fresh_board(state, lobby_id, game_id).await?;
+            return Ok(ActionOutcome::BoardReset { board_checksum });
+        }
+        GameAction::RebuildGame => {
+            let discarded_moves = admin::reload_game_session(state, lobby_id, game_id).await?;
+            return Ok(ActionOutcome::GameRebuilt { discarded_moves });
+        }
+    }
+    Ok(ActionOutcome::Done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopped_actors_are_replaced() {
+        let actors = GameActors::new();
+        let game_id = Uuid::new_v4();
+        let (sender, commands) = mpsc::channel(GAME_COMMAND_BUFFER);
+        actors.senders.insert(game_id, sender);
+        assert!(actors.is_running(game_id));
+
+        // The actor drops its queue when it stops
+        drop(commands);
+        assert!(!actors.is_running(game_id));
+        actors.remove_stopped(game_id);
+        assert!(actors.senders.is_empty());
+    }
+}
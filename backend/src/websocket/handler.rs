@@ -1,50 +1,205 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ConnectInfo, Query, State, WebSocketUpgrade,
     },
+    http::{header, HeaderMap},
     response::IntoResponse,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
 use rand::seq::SliceRandom;
-use tokio::sync::mpsc;
+use serde::Deserialize;
+use tracing::Instrument;
+use uuid::Uuid;
 
 use crate::{
-    auth::AuthenticatedUser,
-    db,
-    game::{grid::GridGenerator, scorer::Scorer, validator::WordValidator},
-    websocket::messages::{
-        ClientMessage, GamePlayerInfo, LobbyPlayerInfo, LobbyType, ServerMessage,
+    auth::{self, AuthenticatedUser},
+    avatars, db,
+    dictionary::{packs::PackSelection, WordTier},
+    error::ApiError,
+    game::{
+        board_theme::BoardTheme,
+        commentary::{win_probabilities, COMMENTARY_BEST_MOVES},
+        engine::{engine_for, BoardView, ScoredWord},
+        grid::board_checksum,
+        heatmap::tile_heatmap,
+        pacing::move_pacing,
+        puzzle_rush::next_board,
+        result::{best_word_scores, decide_result, FinalStanding, GameOutcome, GameResult},
+        rules::GameRules,
+        scorer::Scorer,
+        session::{flush_game_session, GameSession},
+        solver::{self, best_words, find_unused_words, BestWord, LobbyWords},
+        validator::WordValidator,
+    },
+    game_nights, locale,
+    models::{
+        quest::quest_updates, CoopOutcome, CoopState, GameEvent, GameListFilter, GameMode,
+        GameMove, GameOverReason, GamePlayer, GameSetup, GameState, GameStatus, Grid,
+        GuildSettings, HotSeats, Position, PuzzleRushState, QuestEvent, QuestStatus, TurnAdvance,
     },
-    AppState, Lobby, LobbyPlayer, PlayerConnectionState,
+    reserve_lobby_code,
+    websocket::{
+        activity::{Activity, NOTABLE_WORD_SCORE},
+        admin,
+        game_actor::{self, GameAction},
+        limits::client_ip,
+        messages::{
+            ClientMessage, CoopStatus, DictionaryPackInfo, GamePlayerInfo, GuildStandingInfo,
+            InviteSender, LobbyType, PlayerClockInfo, PlayerInfo, PuzzleRushStatus, ScoreInfo,
+            ServerMessage, TimeAttackStatus,
+        },
+        outbound::{ClientSender, Delivery, OutboundMessage},
+        permissions::{self, ConnectionRole, Permission},
+        presence,
+        schedule::{ScheduleCancelReason, ScheduledStart},
+        trace, validate,
+    },
+    AppState, BlockLists, Lobby, LobbyPlayer, PlayerConnectionState, QueuedPlayer,
+    QUEUED_INVITE_TTL,
 };
 
+/// Number of players included in a GuildLeaderboardUpdate
+const GUILD_LEADERBOARD_SIZE: i64 = 10;
+/// Most games listed by AdminGetGames, newest first
+const ADMIN_GAMES_LIMIT: i64 = 100;
+/// Longest name of a local player in a hot-seat game
+const MAX_LOCAL_PLAYER_NAME_LEN: usize = 32;
+
 /// WebSocket upgrade handler with authentication
+///
+/// CORS doesn't apply to WebSocket upgrades, so the Origin header is checked against
+/// the configured allowlist here. Requests without an Origin (non-browser clients)
+/// are let through; they still need a valid token.
+///
+/// Concurrent connections are capped per user and per client IP; the slot is held
+/// for the lifetime of the socket. Incoming messages are capped at
+/// `WS_MAX_MESSAGE_BYTES`, and every field is checked by `validate` before a
+/// message is handled.
+///
+/// `?admin=1` opens the moderation console instead of a player connection; it is
+/// limited to the users listed in ADMIN_USER_IDS. `?spectate=1` opens a player
+/// connection that can only watch. What a player connection may do is settled
+/// here as its `ConnectionRole`, and every message is checked against it.
 pub async fn handle_websocket(
     user: AuthenticatedUser,
+    headers: HeaderMap,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Query(params): Query<WebSocketParams>,
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        let origin = origin.to_str().unwrap_or_default();
+        if !state.config.server.is_origin_allowed(origin) {
+            tracing::warn!(
+                "Rejected WebSocket upgrade from origin {:?} for user {}",
+                origin,
+                user.user_id
+            );
+            return Err(ApiError::OriginNotAllowed);
+        }
+    }
+
     tracing::info!(
         "WebSocket connection authenticated for user: {} ({})",
         user.username,
         user.user_id
     );
-    ws.on_upgrade(move |socket| handle_socket(socket, state, user))
+    let client_ip = client_ip(&headers, peer, state.config.limits.trust_forwarded_for);
+    let permit = state
+        .connection_limiter
+        .try_acquire(user.user_id, client_ip)
+        .inspect_err(|_| {
+            tracing::warn!(
+                "Rejected WebSocket connection for user {} from {}: connection limit reached",
+                user.user_id,
+                client_ip
+            );
+        })?;
+
+    let admin_mode = params.is_admin();
+    let role = ConnectionRole::resolve(
+        params.is_spectator(),
+        state.config.security.is_admin(user.user_id),
+    );
+    if admin_mode {
+        if !state.config.security.is_admin(user.user_id) {
+            tracing::warn!(
+                "Rejected admin console connection for non-admin user {} ({})",
+                user.username,
+                user.user_id
+            );
+            return Err(ApiError::NotAdmin);
+        }
+        tracing::info!(
+            "Admin console opened by {} ({})",
+            user.username,
+            user.user_id
+        );
+    }
+
+    // Frames and messages past the limit close the connection before they're parsed
+    let max_message_bytes = state.config.limits.max_message_bytes;
+    let ws = ws
+        .max_message_size(max_message_bytes)
+        .max_frame_size(max_message_bytes);
+    Ok(ws.on_upgrade(move |socket| async move {
+        if admin_mode {
+            admin::handle_admin_socket(socket, state, user).await;
+        } else {
+            handle_socket(socket, state, user, role).await;
+        }
+        drop(permit);
+    }))
+}
+
+/// Query parameters accepted on the WebSocket endpoint (besides the auth token)
+#[derive(Debug, Default, Deserialize)]
+pub struct WebSocketParams {
+    #[serde(default)]
+    admin: Option<String>,
+    #[serde(default)]
+    spectate: Option<String>,
+}
+
+impl WebSocketParams {
+    fn is_admin(&self) -> bool {
+        matches!(self.admin.as_deref(), Some("1" | "true"))
+    }
+
+    fn is_spectator(&self) -> bool {
+        matches!(self.spectate.as_deref(), Some("1" | "true"))
+    }
 }
 
 /// Context for a connected player, tracking their lobby membership
 struct PlayerContext {
     /// The lobby_id of the current lobby (if any)
     lobby_id: Option<String>,
+    /// What the connection may do, fixed at upgrade
+    role: ConnectionRole,
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user: AuthenticatedUser) {
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    user: AuthenticatedUser,
+    role: ConnectionRole,
+) {
     let (mut sender, mut receiver) = socket.split();
-    let (tx, mut rx) = mpsc::channel::<ServerMessage>(100);
+    let (tx, mut rx) = ClientSender::channel(
+        state.config.limits.outbound_queue_size,
+        Duration::from_secs(state.config.limits.slow_client_timeout_secs),
+    );
 
     tracing::info!(
         "WebSocket connection established for user: {} ({})",
@@ -52,24 +207,87 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user: Authentica
         user.user_id
     );
 
-    // Spawn a task to send messages to the client
+    // Track player's current lobby for cleanup on disconnect
+    let player_context = Arc::new(tokio::sync::Mutex::new(PlayerContext {
+        lobby_id: None,
+        role,
+    }));
+
+    // Spawn a task to send messages to the client. It ends (closing the connection)
+    // if the client stops reading for too long.
+    let state_for_send = state.clone();
+    let context_for_send = player_context.clone();
+    let user_id = user.user_id;
     let mut send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            match serde_json::to_string(&msg) {
-                Ok(json) => {
-                    if sender.send(Message::Text(json.into())).await.is_err() {
-                        break;
+        while let Some(delivery) = rx.recv().await {
+            let messages = match delivery {
+                Delivery::Message(message) => vec![message],
+                Delivery::Resync { dropped } => {
+                    tracing::warn!(
+                        "User {} fell behind, {} messages dropped; resyncing",
+                        user_id,
+                        dropped
+                    );
+                    let lobby_id = context_for_send.lock().await.lobby_id.clone();
+                    match lobby_id {
+                        Some(lobby_id) => {
+                            resync_messages(&state_for_send, &lobby_id, user_id).await
+                        }
+                        None => Vec::new(),
                     }
                 }
-                Err(e) => {
-                    tracing::error!("Failed to serialize message: {}", e);
+            };
+            for message in messages {
+                match message.into_text() {
+                    Ok(json) => {
+                        if sender.send(Message::Text(json)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to serialize message: {}", e);
+                    }
                 }
             }
         }
+        tracing::warn!(
+            "Closing connection for user {}: send queue stayed full",
+            user_id
+        );
     });
 
-    // Track player's current lobby for cleanup on disconnect
-    let player_context = Arc::new(tokio::sync::Mutex::new(PlayerContext { lobby_id: None }));
+    // Offer a way back into the lobby they dropped out of
+    if let Some(offer) = state.rejoin_offer(user.user_id) {
+        let _ = tx.send(offer).await;
+    }
+
+    match db::queries::get_blocked_users(&state.db, user.user_id).await {
+        Ok(blocked) => {
+            let any_blocked = !blocked.is_empty();
+            state.block_lists.set(user.user_id, blocked);
+            if any_blocked {
+                let _ = tx.send(block_list_message(&state, user.user_id)).await;
+            }
+        }
+        Err(e) => tracing::error!("Failed to load block list for {}: {}", user.user_id, e),
+    }
+
+    match db::queries::get_user_profile(&state.db, user.user_id).await {
+        Ok(profile) => state.player_profiles.set(
+            user.user_id,
+            profile.map(|p| p.display()).unwrap_or_default(),
+        ),
+        Err(e) => tracing::error!("Failed to load profile for {}: {}", user.user_id, e),
+    }
+
+    // Register before collecting queued invites, so an invite sent in between is
+    // delivered live instead of being queued and missed
+    let connection_id = Uuid::new_v4();
+    state
+        .user_connections
+        .add(user.user_id, connection_id, tx.clone());
+    presence::notify_presence(&state, user.user_id);
+    deliver_queued_invites(&state, &tx, user.user_id).await;
 
     // Handle incoming messages from the client
     let user_for_recv = user.clone();
@@ -78,32 +296,31 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user: Authentica
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
             match msg {
-                Message::Text(text) => match serde_json::from_str::<ClientMessage>(&text) {
-                    Ok(client_msg) => {
-                        if let Err(e) = handle_client_message(
-                            client_msg,
+                Message::Text(text) => {
+                    state_for_recv
+                        .user_connections
+                        .touch(user_for_recv.user_id, Instant::now());
+                    let request_id = trace::new_request_id();
+                    let span = tracing::info_span!(
+                        "ws_message",
+                        request_id = %request_id,
+                        user_id = user_for_recv.user_id,
+                        lobby_id = tracing::field::Empty,
+                        game_id = tracing::field::Empty,
+                    );
+                    trace::with_request_id(
+                        request_id,
+                        handle_text_message(
+                            &text,
                             &state_for_recv,
                             &tx,
                             &user_for_recv,
                             &context_for_recv,
-                        )
-                        .await
-                        {
-                            tracing::error!("Error handling message: {}", e);
-                            let error_msg = ServerMessage::Error {
-                                message: e.to_string(),
-                            };
-                            let _ = tx.send(error_msg).await;
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to parse message: {}", e);
-                        let error_msg = ServerMessage::Error {
-                            message: format!("Invalid message format: {}", e),
-                        };
-                        let _ = tx.send(error_msg).await;
-                    }
-                },
+                        ),
+                    )
+                    .instrument(span)
+                    .await;
+                }
                 Message::Close(_) => {
                     tracing::info!(
                         "Client disconnected: {} ({})",
@@ -127,11 +344,21 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user: Authentica
         }
     }
 
+    state.user_connections.remove(user.user_id, connection_id);
+
     // Mark player as awaiting reconnection (don't remove - they stay visible during grace period)
     let context = player_context.lock().await;
     if let Some(lobby_id) = &context.lobby_id {
         mark_player_awaiting_reconnect(&state, lobby_id, user.user_id).await;
+        state
+            .last_lobbies
+            .remember(user.user_id, lobby_id, Instant::now());
+        state.presence.leave_lobby(user.user_id, lobby_id);
+    }
+    if !state.user_connections.is_connected(user.user_id) {
+        state.presence.unwatch_all(user.user_id);
     }
+    presence::notify_presence(&state, user.user_id);
 
     tracing::info!(
         "WebSocket connection closed for user: {} ({})",
@@ -140,10 +367,95 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user: Authentica
     );
 }
 
+/// Parse and dispatch a single text frame, reporting failures back to the client.
+///
+/// Runs inside the per-message request span, so everything logged while handling the
+/// message (including DB calls and broadcasts) carries the same request ID.
+async fn handle_text_message(
+    text: &str,
+    state: &Arc<AppState>,
+    tx: &ClientSender,
+    user: &AuthenticatedUser,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+) {
+    let client_msg = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(client_msg) => client_msg,
+        Err(e) => {
+            tracing::warn!("Failed to parse message: {}", e);
+            let _ = tx
+                .send(ServerMessage::from(ApiError::InvalidMessage(e.to_string())))
+                .await;
+            return;
+        }
+    };
+    if let Err(e) = validate::validate(&client_msg) {
+        tracing::debug!("Rejected message ({}): {}", e.code(), e);
+        let _ = tx.send(ServerMessage::from(e)).await;
+        return;
+    }
+
+    // Scope the span to the sender's lobby and game so desyncs can be traced per lobby
+    if let Some(lobby_id) = player_context.lock().await.lobby_id.clone() {
+        let span = tracing::Span::current();
+        span.record("lobby_id", lobby_id.as_str());
+        if let Some(game_id) = state.lobbies.get(&lobby_id).and_then(|l| l.active_game_id) {
+            span.record("game_id", tracing::field::display(game_id));
+        }
+    }
+
+    if let Err(e) = handle_client_message(client_msg, state, tx, user, player_context).await {
+        if e.is_internal() {
+            tracing::error!("Error handling message ({}): {:?}", e.code(), e);
+        } else {
+            tracing::debug!("Rejected message ({}): {}", e.code(), e);
+        }
+        if !matches!(e, ApiError::ConnectionClosed) {
+            let lobby_id = player_context.lock().await.lobby_id.clone();
+            let language = lobby_id
+                .and_then(|id| state.lobbies.get(&id))
+                .map(|lobby| lobby.language(&state.dictionary_packs))
+                .unwrap_or_else(|| locale::DEFAULT_LANGUAGE.to_string());
+            let _ = tx.send(e.into_server_message(&language)).await;
+        }
+    }
+}
+
+/// Send invites that were queued while the user was offline, skipping ones from
+/// users they've blocked and ones whose lobby has closed since
+async fn deliver_queued_invites(state: &AppState, tx: &ClientSender, user_id: i64) {
+    let invites = match db::queries::take_lobby_invites(&state.db, user_id).await {
+        Ok(invites) => invites,
+        Err(e) => {
+            tracing::error!("Failed to load queued invites for {}: {}", user_id, e);
+            return;
+        }
+    };
+    for invite in invites {
+        if state.block_lists.is_blocked(user_id, invite.from_user_id)
+            || state.find_lobby_by_code(&invite.lobby_code).is_none()
+        {
+            continue;
+        }
+        let _ = tx
+            .send(ServerMessage::InviteReceived {
+                from: InviteSender {
+                    user_id: invite.from_user_id.to_string(),
+                    username: invite.from_username,
+                    avatar_url: avatars::proxy_url(
+                        invite.from_user_id,
+                        invite.from_avatar_url.as_deref(),
+                    ),
+                },
+                lobby_code: invite.lobby_code,
+            })
+            .await;
+    }
+}
+
 /// Fetch user's avatar URL from database
 async fn fetch_user_avatar(state: &AppState, user_id: i64) -> Option<String> {
     match db::queries::get_user(&state.db, user_id, &state.config.security.encryption_key).await {
-        Ok(Some(db_user)) => db_user.avatar_url,
+        Ok(Some(db_user)) => avatars::proxy_url(user_id, db_user.avatar_url.as_deref()),
         Ok(None) => {
             tracing::warn!("User {} not found in database", user_id);
             None
@@ -155,15 +467,17 @@ async fn fetch_user_avatar(state: &AppState, user_id: i64) -> Option<String> {
     }
 }
 
-/// Add a player to a lobby (or reconnect if already present)
-/// Returns (lobby_type, lobby_code, is_host) tuple if successful
+/// Add a player to a lobby (or reconnect if already present); a spectator
+/// joins without taking a seat or becoming host
+/// Returns (lobby_type, lobby_code, is_host, active_game_id) if successful
 async fn add_player_to_lobby(
     state: &AppState,
     lobby_id: &str,
     user: &AuthenticatedUser,
     avatar_url: Option<String>,
-    tx: mpsc::Sender<ServerMessage>,
-) -> Option<(LobbyType, Option<String>, bool, Option<String>)> {
+    tx: ClientSender,
+    spectator: bool,
+) -> Result<(LobbyType, Option<String>, bool, Option<String>), ApiError> {
     // Get the lobby
     let result = if let Some(mut lobby) = state.lobbies.get_mut(lobby_id) {
         // Check if player is already in lobby (reconnecting)
@@ -203,24 +517,48 @@ async fn add_player_to_lobby(
             let is_host = lobby.is_host(user.user_id);
             let active_game_id = lobby.active_game_id.map(|id| id.to_string()).clone();
 
-            Some((lobby_type, lobby_code, is_host, active_game_id))
+            Ok((lobby_type, lobby_code, is_host, active_game_id))
         } else {
-            // New player joining
+            // New player joining, unless the remaining seats are all reserved
+            if !spectator
+                && !lobby.has_room_for(user.user_id, state.config.game.max_players, Instant::now())
+            {
+                tracing::info!(
+                    "Player {} ({}) rejected from full lobby {}",
+                    user.username,
+                    user.user_id,
+                    lobby_id
+                );
+                return Err(ApiError::LobbyFull);
+            }
+            // Their reservation (if any) is now a real seat
+            if !spectator {
+                lobby.reserved_seats.remove(&user.user_id);
+            }
+
             let lobby_player = LobbyPlayer {
                 user_id: user.user_id,
                 username: user.username.clone(),
                 avatar_url,
                 tx,
                 connection_state: PlayerConnectionState::Connected,
+                ready: false,
+                typing_since: None,
+                muted: HashSet::new(),
+                spectator,
             };
 
             lobby.players.insert(user.user_id, lobby_player);
+            lobby.record_activity(Activity::PlayerJoined {
+                user_id: user.user_id,
+                username: user.username.clone(),
+            });
 
             // Clear empty_since since we have a player now
             lobby.empty_since = None;
 
             // Assign host if no current host
-            let is_host = if lobby.host_id.is_none() {
+            let is_host = if lobby.host_id.is_none() && !spectator {
                 lobby.host_id = Some(user.user_id);
                 tracing::info!(
                     "Player {} ({}) is now the host of lobby {}",
@@ -245,23 +583,57 @@ async fn add_player_to_lobby(
                 lobby_type
             );
 
-            Some((lobby_type, lobby_code, is_host, active_game_id))
+            Ok((lobby_type, lobby_code, is_host, active_game_id))
         }
     } else {
         tracing::warn!("Lobby {} not found when adding player", lobby_id);
-        None
+        Err(ApiError::LobbyNotFound)
     };
 
     // Broadcast updated player list (outside the lock)
-    if result.is_some() {
+    if result.is_ok() {
         broadcast_lobby_player_list(state, lobby_id).await;
     }
 
     result
 }
 
+/// Catch a player who has just joined up on what's been happening in the lobby
+async fn send_activity_feed(
+    state: &AppState,
+    lobby_id: &str,
+    tx: &ClientSender,
+) -> Result<(), ApiError> {
+    let entries = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| lobby.activity.recent())
+        .unwrap_or_default();
+    tx.send(ServerMessage::ActivityFeed { entries }).await?;
+    Ok(())
+}
+
+/// Point the player's context at a lobby they've joined, leaving the previous one
+async fn switch_player_lobby(
+    state: &AppState,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+    lobby_id: &str,
+    user_id: i64,
+) {
+    let mut context = player_context.lock().await;
+    if let Some(old_lobby_id) = &context.lobby_id {
+        if old_lobby_id != lobby_id {
+            remove_player_from_lobby(state, old_lobby_id, user_id).await;
+        }
+    }
+    context.lobby_id = Some(lobby_id.to_string());
+    state.last_lobbies.forget(user_id);
+    state.presence.set_lobby(user_id, lobby_id);
+    presence::notify_presence(state, user_id);
+}
+
 /// Get or create a channel lobby
-fn get_or_create_channel_lobby(
+pub(crate) async fn get_or_create_channel_lobby(
     state: &AppState,
     channel_id: &str,
     guild_id: Option<String>,
@@ -273,24 +645,44 @@ fn get_or_create_channel_lobby(
         return lobby_id;
     }
 
-    // Create new channel lobby
-    let lobby = Lobby::new_channel(channel_id.to_string(), guild_id);
-    state.lobbies.insert(lobby_id.clone(), lobby);
+    // Load the guild's defaults before the lobby exists so the first game uses them
+    let guild_settings = match &guild_id {
+        Some(guild_id) => load_guild_settings(state, guild_id).await,
+        None => None,
+    };
+
+    // Create new channel lobby (another join may have created it while we loaded)
+    state.lobbies.entry(lobby_id.clone()).or_insert_with(|| {
+        let mut lobby = Lobby::new_channel(channel_id.to_string(), guild_id);
+        lobby.guild_settings = guild_settings;
+        tracing::info!("Created new channel lobby: {}", lobby_id);
+        lobby
+    });
 
-    tracing::info!("Created new channel lobby: {}", lobby_id);
     lobby_id
 }
 
+/// Load a guild's settings, falling back to defaults if it has none or the lookup fails
+async fn load_guild_settings(state: &AppState, guild_id: &str) -> Option<GuildSettings> {
+    let guild_id: i64 = guild_id.parse().ok()?;
+    let defaults = || GuildSettings::defaults(guild_id, state.config.game.default_rounds);
+
+    match db::queries::get_guild_settings(&state.db, guild_id).await {
+        Ok(settings) => Some(settings.unwrap_or_else(defaults)),
+        Err(e) => {
+            tracing::error!("Failed to load settings for guild {}: {}", guild_id, e);
+            Some(defaults())
+        }
+    }
+}
+
 /// Create a new custom lobby
-fn create_custom_lobby(state: &AppState) -> (String, String) {
-    let lobby = Lobby::new_custom();
+fn create_custom_lobby(state: &AppState) -> Result<(String, String), ApiError> {
+    // Reserving the code also adds it to the code index for quick lookup
+    let lobby_code =
+        reserve_lobby_code(&state.lobby_code_index).ok_or(ApiError::LobbyCodesExhausted)?;
+    let lobby = Lobby::new_custom(lobby_code.clone());
     let lobby_id = lobby.lobby_id.clone();
-    let lobby_code = lobby.lobby_code.clone().unwrap();
-
-    // Add to code index for quick lookup
-    state
-        .lobby_code_index
-        .insert(lobby_code.clone(), lobby_id.clone());
 
     // Add to lobbies
     state.lobbies.insert(lobby_id.clone(), lobby);
@@ -301,22 +693,28 @@ fn create_custom_lobby(state: &AppState) -> (String, String) {
         lobby_code
     );
 
-    (lobby_id, lobby_code)
-}
-
-/// Find a custom lobby by its code
-fn find_lobby_by_code(state: &AppState, lobby_code: &str) -> Option<String> {
-    // Normalize the code (uppercase, trim)
-    let normalized_code = lobby_code.trim().to_uppercase();
-    state
-        .lobby_code_index
-        .get(&normalized_code)
-        .map(|r| r.value().clone())
+    Ok((lobby_id, lobby_code))
 }
 
 /// Mark a player as awaiting reconnection (starts grace period but player stays visible)
 /// This is called when a WebSocket drops unexpectedly (not an intentional leave)
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id, user_id = user_id))]
 async fn mark_player_awaiting_reconnect(state: &AppState, lobby_id: &str, user_id: i64) {
+    // Queued users don't hold a seat, so they simply lose their place in line
+    let left_queue = state
+        .lobbies
+        .get_mut(lobby_id)
+        .is_some_and(|mut lobby| lobby.leave_queue(user_id));
+    if left_queue {
+        tracing::info!(
+            "Queued user {} disconnected from lobby {}",
+            user_id,
+            lobby_id
+        );
+        send_queue_positions(state, lobby_id).await;
+        return;
+    }
+
     if let Some(lobby) = state.lobbies.get(lobby_id) {
         if let Some(mut player) = lobby.players.get_mut(&user_id) {
             player.connection_state = PlayerConnectionState::AwaitingReconnect {
@@ -352,9 +750,25 @@ async fn mark_player_awaiting_reconnect(state: &AppState, lobby_id: &str, user_i
 }
 
 /// Remove a player from their lobby immediately (e.g., when explicitly leaving)
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id, user_id = user_id))]
 async fn remove_player_from_lobby(state: &AppState, lobby_id: &str, user_id: i64) {
-    if let Some(lobby) = state.lobbies.get(lobby_id) {
-        lobby.players.remove(&user_id);
+    let left_queue = state
+        .lobbies
+        .get_mut(lobby_id)
+        .is_some_and(|mut lobby| lobby.leave_queue(user_id));
+    if left_queue {
+        tracing::info!("User {} left the queue for lobby {}", user_id, lobby_id);
+        send_queue_positions(state, lobby_id).await;
+        return;
+    }
+
+    if let Some(mut lobby) = state.lobbies.get_mut(lobby_id) {
+        if let Some((_, player)) = lobby.players.remove(&user_id) {
+            lobby.record_activity(Activity::PlayerLeft {
+                user_id,
+                username: player.username,
+            });
+        }
         let is_empty = lobby.players.is_empty();
 
         tracing::info!("Player {} removed from lobby {}", user_id, lobby_id);
@@ -373,200 +787,687 @@ async fn remove_player_from_lobby(state: &AppState, lobby_id: &str, user_id: i64
             // Broadcast updated player list to remaining clients
             broadcast_lobby_player_list(state, lobby_id).await;
         }
+        promote_queued_players(state, lobby_id).await;
     }
 }
 
-/// Broadcast the current lobby player list to all connected clients in a lobby
-pub async fn broadcast_lobby_player_list(state: &AppState, lobby_id: &str) {
-    if let Some(lobby) = state.lobbies.get(lobby_id) {
-        // Include ALL visible players (connected + awaiting reconnect)
-        // Players only disappear when removed by background cleanup after grace period
-        let players: Vec<LobbyPlayerInfo> = lobby
-            .players
-            .iter()
-            .filter(|p| p.is_visible())
-            .map(|entry| LobbyPlayerInfo {
-                user_id: entry.user_id.to_string(),
-                username: entry.username.clone(),
-                avatar_url: entry.avatar_url.clone(),
+/// Put a user in line for a seat in a full channel lobby
+async fn queue_for_lobby(
+    state: &AppState,
+    tx: &ClientSender,
+    user: &AuthenticatedUser,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+    lobby_id: &str,
+    avatar_url: Option<String>,
+) -> Result<(), ApiError> {
+    let (position, queue_length) = {
+        let mut lobby = state
+            .lobbies
+            .get_mut(lobby_id)
+            .ok_or(ApiError::LobbyNotFound)?;
+        let position = lobby.enqueue(QueuedPlayer {
+            user_id: user.user_id,
+            username: user.username.clone(),
+            avatar_url,
+            tx: tx.clone(),
+        });
+        (position, lobby.waiting_queue.len())
+    };
+    tracing::info!(
+        "User {} ({}) queued for full lobby {} at position {}",
+        user.username,
+        user.user_id,
+        lobby_id,
+        position
+    );
+
+    // Their context points at the lobby so leaving or disconnecting drops them from the queue
+    switch_player_lobby(state, player_context, lobby_id, user.user_id).await;
+    tx.send(ServerMessage::QueuePositionUpdate {
+        position,
+        queue_length,
+    })
+    .await?;
+    Ok(())
+}
+
+/// Tell everyone in a lobby's waiting queue where they now stand
+async fn send_queue_positions(state: &AppState, lobby_id: &str) {
+    let queued: Vec<ClientSender> = match state.lobbies.get(lobby_id) {
+        Some(lobby) => lobby.waiting_queue.iter().map(|q| q.tx.clone()).collect(),
+        None => return,
+    };
+    let queue_length = queued.len();
+    for (index, tx) in queued.into_iter().enumerate() {
+        let _ = tx
+            .send(ServerMessage::QueuePositionUpdate {
+                position: index + 1,
+                queue_length,
             })
-            .collect();
+            .await;
+    }
+}
 
-        let lobby_code = lobby.lobby_code.clone();
+/// Seat queued users if seats are free and no game is running, then update the
+/// positions of everyone still waiting
+pub async fn promote_queued_players(state: &AppState, lobby_id: &str) {
+    let (promoted, lobby_type, lobby_code) = match state.lobbies.get_mut(lobby_id) {
+        Some(mut lobby) if !lobby.waiting_queue.is_empty() => {
+            let promoted = lobby.promote_from_queue(state.config.game.max_players, Instant::now());
+            (promoted, lobby.lobby_type.clone(), lobby.lobby_code.clone())
+        }
+        _ => return,
+    };
+    if promoted.is_empty() {
+        return;
+    }
 
-        let message = ServerMessage::LobbyPlayerList {
-            players,
-            lobby_code,
-        };
+    for queued in &promoted {
+        tracing::info!(
+            "Queued user {} ({}) took a seat in lobby {}",
+            queued.username,
+            queued.user_id,
+            lobby_id
+        );
+        let _ = queued
+            .tx
+            .send(ServerMessage::LobbyJoined {
+                lobby_id: lobby_id.to_string(),
+                lobby_type: lobby_type.clone(),
+                lobby_code: lobby_code.clone(),
+            })
+            .await;
+        let _ = send_activity_feed(state, lobby_id, &queued.tx).await;
+    }
+    broadcast_lobby_player_list(state, lobby_id).await;
+    send_queue_positions(state, lobby_id).await;
+}
+
+/// The `LobbyPlayerList` for a lobby
+fn lobby_player_list(lobby: &Lobby, state: &AppState) -> ServerMessage {
+    ServerMessage::LobbyPlayerList {
+        players: lobby.player_infos(&state.player_profiles),
+        lobby_code: lobby.lobby_code.clone(),
+        dictionary_strictness: lobby.dictionary_strictness,
+        dictionary_pack: lobby.dictionary_pack.clone(),
+        multiplier_layout: lobby.multiplier_layout,
+        letter_mode: lobby.letter_mode,
+        board_theme: lobby.board_theme,
+        rules: lobby.rules,
+        language: lobby.language(&state.dictionary_packs),
+        puzzle: lobby.puzzle.clone(),
+    }
+}
+
+/// The `LobbyPreview` of a custom lobby for `user_id`, who isn't in it yet
+fn lobby_preview(lobby: &Lobby, state: &AppState, user_id: i64) -> Option<ServerMessage> {
+    let max_players = state.config.game.max_players;
+    Some(ServerMessage::LobbyPreview {
+        lobby_code: lobby.lobby_code.clone()?,
+        players: lobby.player_infos(&state.player_profiles),
+        max_players,
+        has_room: lobby.has_room_for(user_id, max_players, Instant::now()),
+        game_in_progress: lobby.active_game_id.is_some(),
+        scheduled_start: lobby.scheduled_start.clone(),
+        dictionary_strictness: lobby.dictionary_strictness,
+        dictionary_pack: lobby.dictionary_pack.clone(),
+        multiplier_layout: lobby.multiplier_layout,
+        letter_mode: lobby.letter_mode,
+        board_theme: lobby.board_theme,
+        rules: lobby.rules,
+        language: lobby.language(&state.dictionary_packs),
+    })
+}
+
+/// Broadcast the current lobby player list to all connected clients in a lobby
+pub async fn broadcast_lobby_player_list(state: &AppState, lobby_id: &str) {
+    if let Some(lobby) = state.lobbies.get(lobby_id) {
+        let message = lobby_player_list(&lobby, state);
 
         // Only send to actively connected players (awaiting reconnect players have dead tx)
+        let payload = OutboundMessage::shared(&message);
         for entry in lobby.players.iter() {
             if entry.is_connected() {
-                let _ = entry.tx.send(message.clone()).await;
+                let _ = entry.tx.send(payload.clone()).await;
             }
         }
+        lobby.notify_observers(&message);
+    }
+}
+
+/// Send a lobby's warm-state heartbeat to its connected players
+///
+/// Heartbeats are periodic and superseded by the next one, so they're the first
+/// thing a player's full queue drops.
+pub fn broadcast_lobby_heartbeat(lobby: &Lobby, blocks: &BlockLists, now: Instant) {
+    let message = lobby.heartbeat(now);
+    for entry in lobby.players.iter() {
+        if entry.is_connected() {
+            let _ = entry.tx.try_send(entry.filter_social(&message, blocks));
+        }
+    }
+    lobby.notify_observers(&message);
+}
+
+/// The user's current block list
+fn block_list_message(state: &AppState, user_id: i64) -> ServerMessage {
+    ServerMessage::BlockListUpdated {
+        blocked: state
+            .block_lists
+            .blocked_by(user_id)
+            .iter()
+            .map(|id| id.to_string())
+            .collect(),
+    }
+}
+
+/// Mute or unmute a player for the rest of this lobby visit.
+/// Returns the user's updated mute list.
+fn set_muted(
+    state: &AppState,
+    lobby_id: &str,
+    user_id: i64,
+    target: i64,
+    mute: bool,
+) -> Result<ServerMessage, ApiError> {
+    let lobby = state.lobbies.get(lobby_id).ok_or(ApiError::LobbyNotFound)?;
+    let mut player = lobby
+        .players
+        .get_mut(&user_id)
+        .ok_or(ApiError::NotInLobby)?;
+    if mute {
+        player.muted.insert(target);
+    } else {
+        player.muted.remove(&target);
     }
+
+    let mut muted: Vec<i64> = player.muted.iter().copied().collect();
+    muted.sort_unstable();
+    Ok(ServerMessage::MuteListUpdated {
+        muted: muted.iter().map(|id| id.to_string()).collect(),
+    })
 }
 
 /// Broadcast a message to all connected players in a lobby
-async fn broadcast_to_lobby(state: &AppState, lobby_id: &str, message: ServerMessage) {
+///
+/// The message is serialized once and every player's connection sends the same buffer.
+pub async fn broadcast_to_lobby(state: &AppState, lobby_id: &str, message: ServerMessage) {
     if let Some(lobby) = state.lobbies.get(lobby_id) {
+        let payload = OutboundMessage::shared(&message);
         for entry in lobby.players.iter() {
             if entry.is_connected() {
-                let _ = entry.tx.send(message.clone()).await;
+                let _ = entry.tx.send(payload.clone()).await;
             }
         }
+        lobby.notify_observers(&message);
     }
 }
 
-/// Send current game state to a player if there's an active game in their lobby
-/// Used when a player joins/rejoins a lobby with an active game
-async fn send_active_game_state_if_exists(
-    state: &AppState,
-    lobby_id: &str,
-    tx: &mpsc::Sender<ServerMessage>,
-) -> anyhow::Result<()> {
-    match db::queries::get_active_game_for_lobby(&state.db, lobby_id).await {
-        Ok(Some(game_state)) => {
-            // Get player user_ids from game_players table for proper mapping
-            let players = db::queries::get_game_players(&state.db, game_state.game_id)
-                .await
-                .unwrap_or_default();
+/// Find the running game for a lobby in the session cache.
+///
+/// If the lobby has an active game that isn't cached (e.g., the cache entry was lost),
+/// it is rehydrated from the database. Returns the game_id if a session is available.
+async fn get_or_load_game_session(state: &AppState, lobby_id: &str) -> Option<Uuid> {
+    let active_game_id = state.lobbies.get(lobby_id)?.active_game_id?;
 
-            // Map game players with real user_ids
-            let player_infos: Vec<crate::websocket::messages::PlayerInfo> = game_state
-                .players
-                .iter()
-                .enumerate()
-                .map(|(idx, p)| {
-                    let user_id = players
-                        .get(idx)
-                        .map(|pr| pr.user_id)
-                        .unwrap_or(0);
-                    crate::websocket::messages::PlayerInfo {
-                        user_id,
-                        username: p.username.clone(),
-                        avatar_url: p.avatar_url.clone(),
-                        score: p.score,
-                        team: None,
-                    }
-                })
-                .collect();
+    if state.active_games.contains_key(&active_game_id) {
+        return Some(active_game_id);
+    }
 
-            // Get current turn player's user_id
-            let current_turn = players
-                .get(game_state.current_player_index)
-                .map(|pr| pr.user_id);
-
-            tx.send(ServerMessage::GameState {
-                game_id: game_state.game_id.to_string(),
-                mode: crate::models::GameMode::Multiplayer,
-                round: game_state.current_round as i32,
-                max_rounds: game_state.total_rounds as i32,
-                grid: game_state.grid,
-                players: player_infos,
-                current_turn,
-                used_words: game_state.used_words.into_iter().collect(),
-                timer_enabled: false,
-                time_remaining: None,
-            })
-            .await?;
+    match db::queries::get_active_game_for_lobby(&state.db, lobby_id).await {
+        Ok(Some((game_state, player_ids))) if game_state.game_id == active_game_id => {
+            tracing::info!(
+                "Loaded game {} into session cache from database",
+                active_game_id
+            );
+            let mut session = GameSession::new(lobby_id.to_string(), player_ids, game_state);
+            session.event_seq_base = db::queries::next_game_event_seq(&state.db, active_game_id)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!(
+                        "Failed to find where game {} left off: {}",
+                        active_game_id,
+                        e
+                    );
+                    0
+                });
+            state.active_games.insert(active_game_id, session);
+            Some(active_game_id)
         }
-        Ok(None) => {
+        Ok(_) => {
             tracing::warn!("Lobby has active_game_id but no game found in DB");
+            None
         }
         Err(e) => {
             tracing::error!("Failed to fetch active game state: {}", e);
+            None
         }
     }
-    Ok(())
 }
 
-/// Handle the StartGame message - validates and starts a new game
-/// Returns Ok(GameStarted message) on success, or Err(GameError message) on failure
-async fn handle_start_game(
-    state: &AppState,
-    lobby_id: &str,
-    user: &AuthenticatedUser,
-) -> Result<ServerMessage, ServerMessage> {
-    // Get lobby and validate
-    let lobby = state
-        .lobbies
-        .get(lobby_id)
-        .ok_or_else(|| ServerMessage::GameError {
-            code: "lobby_not_found".to_string(),
-            message: "Lobby not found".to_string(),
-        })?;
+/// Build score info for every player in a game session, in turn order
+fn session_scores(session: &GameSession) -> Vec<ScoreInfo> {
+    session
+        .state
+        .players
+        .iter()
+        .zip(&session.players)
+        .map(|(player, user_id)| ScoreInfo {
+            user_id: *user_id,
+            username: player.username.clone(),
+            score: player.score,
+        })
+        .collect()
+}
 
-    // 1. Validate sender is lobby host
-    if !lobby.is_host(user.user_id) {
-        return Err(ServerMessage::GameError {
-            code: "not_host".to_string(),
-            message: "Only the lobby host can start the game".to_string(),
-        });
-    }
+/// Each player's final standing, keyed by user ID. `best_word` is left at zero
+/// since it comes from the stored moves.
+fn session_standings(session: &GameSession) -> HashMap<i64, FinalStanding> {
+    session
+        .state
+        .players
+        .iter()
+        .zip(&session.players)
+        .map(|(player, user_id)| {
+            let passes = session.state.passes.get(&player.user_id);
+            let standing = FinalStanding {
+                user_id: *user_id,
+                score: player.score,
+                passes: passes.copied().unwrap_or(0),
+                best_word: 0,
+                forfeited: player.forfeited,
+            };
+            (*user_id, standing)
+        })
+        .collect()
+}
 
-    // 2. Atomically try to start game (prevents race condition)
-    // This checks both has_active_game and sets game_starting flag atomically
-    if !lobby.try_start_game() {
-        return Err(ServerMessage::GameError {
-            code: "game_in_progress".to_string(),
-            message: "A game is already in progress or starting in this lobby".to_string(),
-        });
+/// The result of a hot-seat game, decided between its local players; None for
+/// other games
+fn hot_seat_result(session: &GameSession) -> Option<GameResult> {
+    if session.state.mode != GameMode::HotSeat {
+        return None;
+    }
+    // Every local player's moves are stored under the owner, so there's no best
+    // word to break ties with
+    let standings: Vec<FinalStanding> = session
+        .state
+        .players
+        .iter()
+        .enumerate()
+        .map(|(seat, player)| FinalStanding {
+            user_id: seat as i64,
+            score: player.score,
+            passes: session
+                .state
+                .passes
+                .get(&player.user_id)
+                .copied()
+                .unwrap_or(0),
+            best_word: 0,
+            forfeited: player.forfeited,
+        })
+        .collect();
+    let names: Vec<String> = session
+        .state
+        .players
+        .iter()
+        .map(|p| p.username.clone())
+        .collect();
+    Some(GameResult::hot_seat(decide_result(&standings), &names))
+}
+
+/// Build blitz clock info for every player, in turn order (empty outside blitz)
+fn session_clocks(session: &GameSession, now: Instant) -> Vec<PlayerClockInfo> {
+    session
+        .state
+        .players
+        .iter()
+        .zip(&session.players)
+        .filter_map(|(player, user_id)| {
+            let remaining = session.state.clock_remaining(player.user_id, now)?;
+            Some(PlayerClockInfo {
+                user_id: *user_id,
+                remaining_ms: remaining.as_millis() as u64,
+            })
+        })
+        .collect()
+}
+
+/// Send current game state to a player if there's an active game in their lobby
+/// Used when a player joins/rejoins a lobby with an active game
+async fn send_active_game_state_if_exists(
+    state: &AppState,
+    lobby_id: &str,
+    user_id: i64,
+    tx: &ClientSender,
+) -> Result<(), ApiError> {
+    let Some(game_id) = get_or_load_game_session(state, lobby_id).await else {
+        return Ok(());
+    };
+
+    let message = match state.active_games.get(&game_id) {
+        Some(session) => game_state_message(state, &session, Some(user_id)),
+        None => return Ok(()),
+    };
+
+    tx.send(message).await?;
+    Ok(())
+}
+
+/// The board theme of a lobby; the standard one if the lobby has gone
+fn lobby_board_theme(state: &AppState, lobby_id: &str) -> BoardTheme {
+    state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| lobby.board_theme)
+        .unwrap_or_default()
+}
+
+/// Full `GameState` snapshot of a cached session, as `viewer` sees it
+///
+/// In a duel each player sees their own board; everyone else sees the one the
+/// duel started from.
+pub(super) fn game_state_message(
+    state: &AppState,
+    session: &GameSession,
+    viewer: Option<i64>,
+) -> ServerMessage {
+    let game_state = &session.state;
+    let (grid, used_words) = match viewer.and_then(|id| session.duel_board(id)) {
+        Some(board) => (&board.grid, &board.used_words),
+        None => (&game_state.grid, &game_state.used_words),
+    };
+    let player_infos: Vec<PlayerInfo> = game_state
+        .players
+        .iter()
+        .zip(&session.players)
+        .map(|(p, user_id)| PlayerInfo {
+            user_id: *user_id,
+            username: p.username.clone(),
+            avatar_url: p.avatar_url.clone(),
+            score: p.score,
+            team: None,
+            profile: state.player_profiles.get(*user_id),
+        })
+        .collect();
+
+    ServerMessage::GameState {
+        game_id: game_state.game_id.to_string(),
+        mode: game_state.mode.clone(),
+        round: game_state.current_round as i32,
+        max_rounds: game_state.total_rounds as i32,
+        grid: grid.clone(),
+        players: player_infos,
+        current_turn: session.current_player_id(),
+        used_words: used_words.iter().cloned().collect(),
+        timer_enabled: false,
+        time_remaining: None,
+        coop: CoopStatus::from_state(game_state),
+        clocks: session_clocks(session, Instant::now()),
+        board_checksum: board_checksum(grid),
+        letter_bag: game_state.letter_bag.clone(),
+        rules: game_state.rules,
+        board_styles: lobby_board_theme(state, &session.lobby_id).styles(),
+        max_remaining_score: max_remaining_score(
+            session,
+            BoardView { grid, used_words },
+            viewer
+                .and_then(|id| session.acting_index(id))
+                .unwrap_or(game_state.current_player_index),
+        ),
+        duel_ends_at: game_state.duel.as_ref().map(|duel| duel.ends_at),
+        time_attack: TimeAttackStatus::from_state(game_state),
+        puzzle_rush: PuzzleRushStatus::from_state(game_state),
+    }
+}
+
+/// A full snapshot of a lobby (and its game, if one is running) for a client
+/// whose queue overflowed and lost messages
+async fn resync_messages(state: &AppState, lobby_id: &str, user_id: i64) -> Vec<OutboundMessage> {
+    let Some(player_list) = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| lobby_player_list(&lobby, state))
+    else {
+        return Vec::new();
+    };
+    let mut messages = vec![OutboundMessage::from(player_list)];
+
+    if let Some(game_id) = get_or_load_game_session(state, lobby_id).await {
+        if let Some(session) = state.active_games.get(&game_id) {
+            messages.push(
+                ServerMessage::ResyncState {
+                    state: Box::new(game_state_message(state, &session, Some(user_id))),
+                }
+                .into(),
+            );
+        }
+    }
+    messages
+}
+
+/// A ResyncState for a client whose board checksum doesn't match the server's, or
+/// None if their board is current
+async fn resync_if_stale(
+    state: &AppState,
+    lobby_id: &str,
+    user_id: i64,
+    checksum: &str,
+) -> Option<ServerMessage> {
+    let game_id = get_or_load_game_session(state, lobby_id).await?;
+    let session = state.active_games.get(&game_id)?;
+    let grid = session
+        .duel_board(user_id)
+        .map_or(&session.state.grid, |board| &board.grid);
+    if board_checksum(grid) == checksum {
+        return None;
+    }
+    tracing::warn!(
+        "Stale board in game {} (client {}), forcing a resync",
+        game_id,
+        checksum
+    );
+    Some(ServerMessage::ResyncState {
+        state: Box::new(game_state_message(state, &session, Some(user_id))),
+    })
+}
+
+/// Handle the StartGame message - validates and starts a new game
+/// Returns Ok(GameStarted message) on success, or the error to report to the host
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id, game_id = tracing::field::Empty))]
+pub(super) async fn handle_start_game(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+    mode: GameMode,
+    target_score: Option<i32>,
+    local_players: Vec<String>,
+) -> Result<ServerMessage, ApiError> {
+    // 2v2 and adventure have their own setup flows
+    if !matches!(
+        mode,
+        GameMode::Multiplayer
+            | GameMode::Coop
+            | GameMode::Blitz
+            | GameMode::Duel
+            | GameMode::TimeAttack
+            | GameMode::HotSeat
+            | GameMode::PuzzleRush
+    ) {
+        return Err(ApiError::UnsupportedMode);
+    }
+    if target_score.is_some_and(|target| target <= 0) {
+        return Err(ApiError::InvalidCoopTarget);
+    }
+    let local_players = match mode {
+        GameMode::HotSeat => Some(local_player_names(local_players)?),
+        _ => None,
+    };
+
+    // Get lobby and validate
+    let mut lobby = state.lobbies.get(lobby_id).ok_or(ApiError::LobbyNotFound)?;
+
+    // 1. The sender was checked against the start permission before getting here
+    // (the scheduler starts games on the host's behalf)
+
+    // 2. Atomically try to start game (prevents race condition)
+    // This checks both has_active_game and sets game_starting flag atomically
+    if !lobby.try_start_game() {
+        return Err(ApiError::GameInProgress);
     }
 
     // From this point on, we have the game_starting flag set.
     // We must clear it on any error path or set active_game_id on success.
 
-    // 3. Validate player count (2-6 players)
-    let connected_count = lobby.connected_player_count();
-    if connected_count < 2 {
+    // 3. Validate player count (2-6 players; co-op and time attack can also be played
+    // solo, and hot-seat players were counted above)
+    let connected_count = match &local_players {
+        Some(names) => names.len(),
+        None => lobby.connected_player_count(),
+    };
+    if connected_count < mode.min_players() {
         lobby.clear_game_starting();
-        return Err(ServerMessage::GameError {
-            code: "not_enough_players".to_string(),
-            message: format!(
-                "At least 2 players are required to start a game (currently {})",
-                connected_count
-            ),
-        });
+        return Err(ApiError::NotEnoughPlayers(connected_count));
     }
     if connected_count > 6 {
         lobby.clear_game_starting();
-        return Err(ServerMessage::GameError {
-            code: "too_many_players".to_string(),
-            message: format!("Maximum 6 players allowed (currently {})", connected_count),
-        });
+        return Err(ApiError::TooManyPlayers(connected_count));
+    }
+    if mode == GameMode::Duel && connected_count != 2 {
+        lobby.clear_game_starting();
+        return Err(ApiError::DuelNeedsTwoPlayers(connected_count));
+    }
+    if mode == GameMode::PuzzleRush && connected_count != 1 {
+        lobby.clear_game_starting();
+        return Err(ApiError::PuzzleRushIsSolo(connected_count));
     }
 
-    // 4. Generate 5x5 grid with multipliers
-    let grid = GridGenerator::generate();
+    // Guilds can restrict which modes their lobbies may play
+    if !lobby.allows_mode(&mode) {
+        lobby.clear_game_starting();
+        return Err(ApiError::ModeNotAllowed);
+    }
+    let total_rounds = lobby.default_rounds(state.config.game.default_rounds);
+    let rush_start = match mode {
+        GameMode::PuzzleRush => {
+            let seed = rand::random::<u64>();
+            let target_score = state.config.game.puzzle_rush_target_score;
+            // The lobby stays reserved by its game_starting flag while the board
+            // is searched for without holding it
+            let rules = lobby.rules;
+            drop(lobby);
+            let dealt = deal_rush_board(state, lobby_id, seed, 0, target_score, rules).await;
+            lobby = state.lobbies.get(lobby_id).ok_or(ApiError::LobbyNotFound)?;
+            match dealt {
+                Ok((board, grid)) => {
+                    let start_secs = state.config.game.puzzle_rush_secs;
+                    let rush = PuzzleRushState::new(
+                        seed,
+                        target_score,
+                        start_secs,
+                        board,
+                        chrono::Utc::now(),
+                    );
+                    Some((rush, grid))
+                }
+                Err(e) => {
+                    lobby.clear_game_starting();
+                    return Err(e);
+                }
+            }
+        }
+        _ => None,
+    };
+    let setup = GameSetup {
+        mode: mode.clone(),
+        coop_target_score: (mode == GameMode::Coop).then(|| {
+            target_score.unwrap_or_else(|| CoopState::default_target(connected_count, total_rounds))
+        }),
+        blitz_clock_secs: (mode == GameMode::Blitz)
+            .then_some(state.config.game.blitz_clock_secs.min(i32::MAX as u64) as i32),
+        duel_seed: (mode == GameMode::Duel).then(rand::random::<i64>),
+        duel_ends_at: (mode == GameMode::Duel).then(|| {
+            chrono::Utc::now() + chrono::Duration::seconds(state.config.game.duel_secs as i64)
+        }),
+        time_attack_ends_at: (mode == GameMode::TimeAttack).then(|| {
+            chrono::Utc::now()
+                + chrono::Duration::seconds(state.config.game.time_attack_secs as i64)
+        }),
+        // Duel, time attack and puzzle rush players play at once, so there's never
+        // a last word to take back
+        rules: GameRules {
+            undo: lobby.rules.undo
+                && !matches!(
+                    mode,
+                    GameMode::Duel | GameMode::TimeAttack | GameMode::PuzzleRush
+                ),
+            ..lobby.rules
+        },
+        hot_seats: local_players.clone().map(HotSeats::new),
+        puzzle_rush: rush_start.as_ref().map(|(rush, _)| rush.clone()),
+    };
+    let guild_id = lobby
+        .guild_id
+        .as_deref()
+        .and_then(|id| id.parse::<i64>().ok());
+
+    // 4. Deal a puzzle rush's first board, or the host's chosen puzzle (refilled
+    // with weighted letters), or take a pre-generated 5x5 grid for the lobby's
+    // language and multiplier layout, or deal one from a fresh letter bag
+    let (grid, letter_bag) = match (rush_start, &lobby.puzzle) {
+        (Some((_, grid)), _) => (grid, None),
+        (None, Some(puzzle)) => (puzzle.grid.clone(), None),
+        (None, None) => state
+            .grid_pool
+            .deal(&lobby.board_key(&state.dictionary_packs), lobby.letter_mode),
+    };
 
-    // 5. Collect and shuffle player order
-    let mut players_info: Vec<GamePlayerInfo> = lobby
-        .players
-        .iter()
-        .filter(|p| p.is_connected())
-        .map(|p| GamePlayerInfo {
-            user_id: p.user_id.to_string(),
-            username: p.username.clone(),
-            avatar_url: p.avatar_url.clone(),
-            turn_order: 0, // Will be assigned after shuffle
-        })
-        .collect();
+    // 5. Collect and shuffle player order; a hot-seat game's local players all
+    // belong to the host
+    let mut players_info: Vec<GamePlayerInfo> = match &local_players {
+        Some(names) => {
+            let avatar_url = lobby
+                .players
+                .get(&user.user_id)
+                .and_then(|host| host.avatar_url.clone());
+            names
+                .iter()
+                .map(|name| GamePlayerInfo {
+                    user_id: user.user_id.to_string(),
+                    username: name.clone(),
+                    avatar_url: avatar_url.clone(),
+                    turn_order: 0,
+                })
+                .collect()
+        }
+        None => lobby
+            .players
+            .iter()
+            .filter(|p| p.is_connected() && !p.spectator)
+            .map(|p| GamePlayerInfo {
+                user_id: p.user_id.to_string(),
+                username: p.username.clone(),
+                avatar_url: p.avatar_url.clone(),
+                turn_order: 0, // Will be assigned after shuffle
+            })
+            .collect(),
+    };
 
     // Drop the lobby ref before any operations that might await
     drop(lobby);
 
     // Helper to clear game_starting flag and return an error
-    let clear_and_err = |state: &AppState, lobby_id: &str, err: ServerMessage| {
+    let clear_and_err = |state: &AppState, lobby_id: &str, err: ApiError| {
         if let Some(lobby) = state.lobbies.get(lobby_id) {
             lobby.clear_game_starting();
         }
         err
     };
 
-    // Shuffle player order (using thread_rng in a non-async block)
-    {
+    // Shuffle player order (using thread_rng in a non-async block); local players
+    // keep the order they were named in
+    if local_players.is_none() {
         let mut rng = rand::rng();
         players_info.shuffle(&mut rng);
     }
@@ -583,8 +1484,6 @@ async fn handle_start_game(
         .unwrap_or_default();
 
     // 6. Persist game session to database
-    let total_rounds: u8 = 5; // Default 5 rounds
-
     // Collect player user_ids for database batch insert
     let player_tuples: Vec<(i64, u8)> = players_info
         .iter()
@@ -599,60 +1498,63 @@ async fn handle_start_game(
         .collect();
 
     // Create game session in database
-    let game_id = db::queries::create_game_session(&state.db, lobby_id, user.user_id, total_rounds)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to create game session: {}", e);
-            clear_and_err(
-                state,
-                lobby_id,
-                ServerMessage::GameError {
-                    code: "database_error".to_string(),
-                    message: "Failed to create game session".to_string(),
-                },
-            )
-        })?;
+    let game_id = db::queries::create_game_session(
+        &state.db,
+        lobby_id,
+        guild_id,
+        user.user_id,
+        total_rounds,
+        &setup,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to create game session: {}", e);
+        clear_and_err(state, lobby_id, e.into())
+    })?;
+    tracing::Span::current().record("game_id", tracing::field::display(game_id));
 
-    // Add players to game
-    db::queries::add_game_players_batch(&state.db, game_id, &player_tuples)
+    // Add players to game; a hot-seat game is stored under its owner alone
+    let player_rows = match local_players {
+        Some(_) => &player_tuples[..1],
+        None => &player_tuples[..],
+    };
+    db::queries::add_game_players_batch(&state.db, game_id, player_rows)
         .await
         .map_err(|e| {
             tracing::error!("Failed to add players to game: {}", e);
-            clear_and_err(
-                state,
-                lobby_id,
-                ServerMessage::GameError {
-                    code: "database_error".to_string(),
-                    message: "Failed to add players to game".to_string(),
-                },
-            )
+            clear_and_err(state, lobby_id, e.into())
         })?;
 
     // Save the grid to database
     let grid_json = serde_json::to_value(&grid).map_err(|e| {
         tracing::error!("Failed to serialize grid: {}", e);
-        clear_and_err(
-            state,
-            lobby_id,
-            ServerMessage::GameError {
-                code: "serialization_error".to_string(),
-                message: "Failed to serialize game grid".to_string(),
-            },
-        )
+        clear_and_err(state, lobby_id, e.into())
     })?;
 
-    db::queries::create_or_update_game_board(&state.db, game_id, grid_json)
+    let letter_bag_json = letter_bag
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| {
+            tracing::error!("Failed to serialize letter bag: {}", e);
+            clear_and_err(state, lobby_id, e.into())
+        })?;
+    db::queries::create_or_update_game_board(
+        &state.db,
+        game_id,
+        grid_json.clone(),
+        letter_bag_json,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to create game board: {}", e);
+        clear_and_err(state, lobby_id, e.into())
+    })?;
+    db::queries::insert_board_revision(&state.db, game_id, 0, &grid_json)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to create game board: {}", e);
-            clear_and_err(
-                state,
-                lobby_id,
-                ServerMessage::GameError {
-                    code: "database_error".to_string(),
-                    message: "Failed to create game board".to_string(),
-                },
-            )
+            tracing::error!("Failed to record starting board: {}", e);
+            clear_and_err(state, lobby_id, e.into())
         })?;
 
     // Update game state to active
@@ -660,20 +1562,87 @@ async fn handle_start_game(
         .await
         .map_err(|e| {
             tracing::error!("Failed to update game state: {}", e);
-            clear_and_err(
-                state,
-                lobby_id,
-                ServerMessage::GameError {
-                    code: "database_error".to_string(),
-                    message: "Failed to update game state".to_string(),
-                },
-            )
+            clear_and_err(state, lobby_id, e.into())
         })?;
 
-    // 7. Link game to lobby and clear game_starting flag
+    // 7. Cache the live game state; it is written back to the database on round
+    // boundaries and at game end rather than on every move
+    let game_players: Vec<GamePlayer> = players_info
+        .iter()
+        .map(|p| {
+            GamePlayer::new(
+                Uuid::new_v4(),
+                p.username.clone(),
+                p.avatar_url.clone(),
+                p.turn_order,
+            )
+        })
+        .collect();
+    let mut game_state = match (
+        setup.coop_target_score,
+        setup.blitz_clock_secs,
+        setup.duel_seed.zip(setup.duel_ends_at),
+        setup.time_attack_ends_at,
+        setup.puzzle_rush.clone(),
+    ) {
+        (Some(target), _, _, _, _) => {
+            GameState::new_coop(game_id, grid.clone(), game_players, total_rounds, target)
+        }
+        (None, Some(secs), _, _, _) => GameState::new_blitz(
+            game_id,
+            grid.clone(),
+            game_players,
+            total_rounds,
+            Duration::from_secs(secs as u64),
+        ),
+        (None, None, Some((seed, ends_at)), _, _) => {
+            GameState::new_duel(game_id, grid.clone(), game_players, seed as u64, ends_at)
+        }
+        (None, None, None, Some(ends_at), _) => {
+            GameState::new_time_attack(game_id, grid.clone(), game_players, ends_at)
+        }
+        (None, None, None, None, Some(rush)) => {
+            GameState::new_puzzle_rush(game_id, grid.clone(), game_players, rush)
+        }
+        (None, None, None, None, None) if setup.hot_seats.is_some() => {
+            GameState::new_hot_seat(game_id, grid.clone(), game_players, total_rounds)
+        }
+        (None, None, None, None, None) => {
+            GameState::new(game_id, grid.clone(), game_players, total_rounds)
+        }
+    };
+    game_state.status = GameStatus::InProgress;
+    game_state.letter_bag = letter_bag.clone();
+    game_state.rules = setup.rules;
+    game_state.start_turn_clock(Instant::now());
+    let coop = CoopStatus::from_state(&game_state);
+    let time_attack = TimeAttackStatus::from_state(&game_state);
+    let puzzle_rush = PuzzleRushStatus::from_state(&game_state);
+    let player_ids: Vec<i64> = player_tuples.iter().map(|(user_id, _)| *user_id).collect();
+    state.active_games.insert(
+        game_id,
+        GameSession::new(lobby_id.to_string(), player_ids.clone(), game_state),
+    );
+
+    // 8. Link game to lobby and clear game_starting flag
+    let mut game_night_event = None;
     if let Some(mut lobby) = state.lobbies.get_mut(lobby_id) {
         lobby.active_game_id = Some(game_id);
         lobby.clear_game_starting();
+        lobby.clear_ready();
+        // Starting early replaces the scheduled game
+        game_night_event = lobby
+            .scheduled_start
+            .take()
+            .and_then(|schedule| schedule.game_night_event);
+        lobby.record_activity(Activity::GameStarted {
+            game_id: game_id.to_string(),
+            player_count: players_info.len(),
+        });
+    }
+    presence::notify_lobby_presence(state, lobby_id);
+    if let Some(event_id) = game_night_event {
+        game_nights::record_start(state, event_id, game_id, &player_ids).await;
     }
 
     tracing::info!(
@@ -684,180 +1653,1946 @@ async fn handle_start_game(
         players_info.len()
     );
 
-    // 8. Return GameStarted message for broadcast
+    // 9. Return GameStarted message for broadcast
     Ok(ServerMessage::GameStarted {
         game_id: game_id.to_string(),
+        board_checksum: board_checksum(&grid),
         grid,
         players: players_info,
         current_player_id,
         total_rounds,
+        mode,
+        coop,
+        clock_secs: setup.blitz_clock_secs.map(|secs| secs as u64),
+        letter_bag,
+        rules: setup.rules,
+        board_styles: lobby_board_theme(state, lobby_id).styles(),
+        duel_ends_at: setup.duel_ends_at,
+        time_attack,
+        puzzle_rush,
     })
 }
 
-/// Handle individual client messages
-async fn handle_client_message(
-    msg: ClientMessage,
+/// Look up the cached session for a lobby's active game
+pub(super) async fn require_game_session(
+    state: &AppState,
+    lobby_id: &str,
+) -> Result<Uuid, ApiError> {
+    if !state.lobbies.contains_key(lobby_id) {
+        return Err(ApiError::LobbyNotFound);
+    }
+
+    get_or_load_game_session(state, lobby_id)
+        .await
+        .ok_or(ApiError::NoActiveGame)
+}
+
+/// The WordScored a player's submission was already answered with, if this is a retry
+fn replay_submission(
     state: &AppState,
-    tx: &mpsc::Sender<ServerMessage>,
+    lobby_id: &str,
     user: &AuthenticatedUser,
-    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
-) -> anyhow::Result<()> {
-    match msg {
-        ClientMessage::JoinChannelLobby {
-            channel_id,
-            guild_id,
-        } => {
-            tracing::info!(
-                "User {} ({}) joining channel lobby: {}, guild: {:?}",
-                user.username,
-                user.user_id,
-                channel_id,
-                guild_id
-            );
+    submission_id: &str,
+) -> Option<ServerMessage> {
+    let game_id = state.lobbies.get(lobby_id)?.active_game_id?;
+    let reply = state
+        .active_games
+        .get(&game_id)?
+        .replay_submission(user.user_id, submission_id)?;
+    tracing::info!(
+        "User {} ({}) resent submission {}; replaying its result",
+        user.username,
+        user.user_id,
+        submission_id
+    );
+    Some(reply)
+}
 
-            // Get or create the channel lobby
-            let lobby_id = get_or_create_channel_lobby(state, &channel_id, guild_id);
+/// A word as a player submitted it
+pub(super) struct Submission {
+    pub word: String,
+    pub positions: Vec<Position>,
+    /// Checksum of the board the word was found on
+    pub board_checksum: Option<String>,
+    /// The client's ID for the submission, so a retry gets the original result
+    pub submission_id: Option<String>,
+}
 
-            // Remove from previous lobby if different
-            {
-                let mut context = player_context.lock().await;
-                if let Some(old_lobby_id) = &context.lobby_id {
-                    if old_lobby_id != &lobby_id {
-                        remove_player_from_lobby(state, old_lobby_id, user.user_id).await;
-                    }
-                }
-                context.lobby_id = Some(lobby_id.clone());
-            }
+/// Handle the SubmitWord message - validates, scores and applies a word to the cached game
+/// Returns the rejection to send back to the submitting player on failure
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id))]
+pub(super) async fn handle_submit_word(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    tx: &ClientSender,
+    user: &AuthenticatedUser,
+    submission: Submission,
+) -> Result<(), ApiError> {
+    let Submission {
+        word,
+        positions,
+        board_checksum: checksum,
+        submission_id,
+    } = submission;
+    let game_id = require_game_session(state, lobby_id).await?;
+
+    // A puzzle rush's next board is dealt between two locks of the session, so
+    // no flush may take the word's move before the board is added to it
+    let rush_flush_lock = state
+        .active_games
+        .get(&game_id)
+        .filter(|session| session.state.puzzle_rush.is_some())
+        .map(|session| session.flush_lock.clone());
+    let flushing = match rush_flush_lock {
+        Some(flush_lock) => Some(flush_lock.lock_owned().await),
+        None => None,
+    };
 
-            // Fetch avatar and add to lobby (handles reconnection)
-            let avatar_url = fetch_user_avatar(state, user.user_id).await;
-            if let Some((lobby_type, lobby_code, _is_host, active_game_id)) =
-                add_player_to_lobby(state, &lobby_id, user, avatar_url, tx.clone()).await
-            {
-                // Send confirmation
-                tx.send(ServerMessage::LobbyJoined {
-                    lobby_id: lobby_id.clone(),
-                    lobby_type,
-                    lobby_code,
-                })
-                .await?;
+    let (index, mut game_move, tier, breakdown, word_score, mut refilled, rush, finished) = {
+        let mut session = state
+            .active_games
+            .get_mut(&game_id)
+            .ok_or(ApiError::GameNotFound)?;
+        let engine = engine_for(&session.state.mode);
+        let index =
+            engine.check_submitter(&session, user.user_id, Instant::now(), chrono::Utc::now())?;
+
+        let board = engine
+            .board(&session.state, index)
+            .ok_or(ApiError::NotPlaying)?;
+        if board.is_word_used(&word) {
+            return Err(ApiError::WordAlreadyUsed);
+        }
+        // Nobody else sees a solo player's board, so their words must name the
+        // board they were found on (a puzzle rush's changes with every word)
+        let solo = session.state.is_solo();
+        if solo && checksum.ok_or(ApiError::BoardChecksumRequired)? != board_checksum(board.grid) {
+            return Err(ApiError::StaleBoard);
+        }
 
-                // If game is active, send game state
-                if active_game_id.is_some() {
-                    tracing::info!("Player joined lobby with active game, sending game state");
-                    send_active_game_state_if_exists(state, &lobby_id, tx).await?;
-                }
-            }
+        // Validate path - use empty HashSet since we only need path validation here
+        let validator = WordValidator::new(HashSet::new());
+        validator.validate_path(board.grid, &positions)?;
+
+        let rules = session.state.rules;
+        if !rules.allows_length(positions.len()) {
+            return Err(ApiError::WordTooShort);
         }
 
-        ClientMessage::CreateCustomLobby => {
-            tracing::info!(
-                "User {} ({}) creating custom lobby",
-                user.username,
-                user.user_id
-            );
+        let tier = lookup_word(state, lobby_id, &word, &rules)?;
+
+        // Score word and apply it to the cached state (flushed on round boundary)
+        let breakdown = Scorer::score_breakdown(
+            board.grid,
+            &positions,
+            &rules,
+            rules
+                .obscurity_bonus
+                .then(|| state.word_plays.plays(&word))
+                .flatten(),
+        )?;
+        let word_score = breakdown.total();
+        engine.check_score(&session.state, word_score)?;
+        if solo {
+            let max_per_minute = state.config.limits.solo_words_per_minute;
+            session
+                .count_solo_word(user.user_id, Instant::now(), max_per_minute)
+                .inspect_err(|_| {
+                    tracing::warn!(
+                        "User {} is scoring words implausibly fast in game {}",
+                        user.user_id,
+                        game_id
+                    );
+                })?;
+        }
 
-            // Create the custom lobby
-            let (lobby_id, lobby_code) = create_custom_lobby(state);
+        let events = engine.play_word(
+            &session.state,
+            ScoredWord {
+                player: index,
+                word: &word,
+                positions: &positions,
+                score: word_score,
+                move_time_ms: engine.move_time_ms(&session.state, Instant::now()),
+            },
+        );
+        let refilled = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GridRefilled { .. }));
+        let finished = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameFinished { .. }));
+
+        // Games that allow undo keep the state from before the word to go back to
+        let before = engine
+            .offers_undo(&session.state)
+            .then(|| session.state.clone());
+        let game_move = session
+            .commit(events)
+            .game_move
+            .ok_or(ApiError::NotPlaying)?;
+        if let Some(before) = before {
+            session.offer_undo(game_move.clone(), before, Instant::now());
+        }
+        let rush = session
+            .state
+            .puzzle_rush
+            .as_ref()
+            .map(|rush| (rush.seed, rush.board, rush.target_score, rules));
+        (
+            index, game_move, tier, breakdown, word_score, refilled, rush, finished,
+        )
+    };
 
-            // Remove from previous lobby
-            {
-                let mut context = player_context.lock().await;
-                if let Some(old_lobby_id) = &context.lobby_id {
-                    remove_player_from_lobby(state, old_lobby_id, user.user_id).await;
+    // A word that solves a puzzle rush board deals the next one, which takes
+    // the dictionary to find
+    if let Some((seed, solved, target_score, rules)) = rush {
+        match deal_rush_board(state, lobby_id, seed, solved + 1, target_score, rules).await {
+            Ok((board, grid)) => {
+                let mut session = state
+                    .active_games
+                    .get_mut(&game_id)
+                    .ok_or(ApiError::GameNotFound)?;
+                // Unless the run has ended in the meantime
+                let ends_at = session
+                    .state
+                    .puzzle_rush
+                    .as_ref()
+                    .filter(|rush| rush.board == solved)
+                    .map(|rush| rush.next_ends_at(chrono::Utc::now()));
+                if let Some(ends_at) =
+                    ends_at.filter(|_| session.state.status == GameStatus::InProgress)
+                {
+                    let event = GameEvent::RushBoardDealt {
+                        board,
+                        grid,
+                        ends_at,
+                    };
+                    session.finish_move(vec![event], &mut game_move);
+                    refilled = true;
+                }
+            }
+            Err(e) => tracing::error!(
+                "Failed to deal the next puzzle rush board in game {}: {}",
+                game_id,
+                e
+            ),
+        }
+    }
+    drop(flushing);
+
+    refresh_remaining_scores(state, game_id).await;
+    let (scored, progress, counts_for_quests, finished) = {
+        let mut session = state
+            .active_games
+            .get_mut(&game_id)
+            .ok_or(ApiError::GameNotFound)?;
+        let engine = engine_for(&session.state.mode);
+        let progress = engine.private_progress(&session.state, index, user.user_id);
+        let max_remaining_score = engine
+            .board(&session.state, index)
+            .and_then(|board| max_remaining_score(&session, board, index));
+
+        let player = &session.state.players[index];
+        let player_info = PlayerInfo {
+            user_id: user.user_id,
+            username: user.username.clone(),
+            avatar_url: player.avatar_url.clone(),
+            score: player.score, // Send new total score for scoreboard update
+            team: None,
+            profile: state.player_profiles.get(user.user_id),
+        };
+        // A private board refills itself after every word, and doesn't draw from
+        // the shared letter bag
+        let (grid, letter_bag) = match progress {
+            Some(_) => (Some(game_move.board.clone()), None),
+            None => (
+                refilled.then(|| game_move.board.clone()),
+                session.state.letter_bag.clone(),
+            ),
+        };
+        let scored = ServerMessage::WordScored {
+            word: word.clone(),
+            score: game_move.score, // Send the word score, not total
+            player: player_info,
+            positions: game_move.positions,
+            tier,
+            breakdown,
+            max_remaining_score,
+            coop: CoopStatus::from_state(&session.state),
+            board_checksum: board_checksum(&game_move.board),
+            submission_id: submission_id.clone(),
+            grid,
+            letter_bag,
+            time_attack: TimeAttackStatus::from_state(&session.state),
+            puzzle_rush: PuzzleRushStatus::from_state(&session.state),
+        };
+        if let Some(submission_id) = submission_id {
+            session.remember_submission(user.user_id, submission_id, scored.clone());
+        }
+        (
+            scored,
+            progress,
+            engine.counts_for_quests(&session.state),
+            finished.then(|| session_scores(&session)),
+        )
+    };
+
+    match progress {
+        // The word stays private, so it isn't in the activity feed either
+        Some(progress) => {
+            tx.send(scored).await?;
+            broadcast_to_lobby(state, lobby_id, progress).await;
+        }
+        None => {
+            broadcast_to_lobby(state, lobby_id, scored).await;
+            if word_score >= NOTABLE_WORD_SCORE {
+                if let Some(mut lobby) = state.lobbies.get_mut(lobby_id) {
+                    lobby.record_activity(Activity::NotableWord {
+                        user_id: user.user_id,
+                        username: user.username.clone(),
+                        word: word.to_uppercase(),
+                        score: word_score,
+                    });
                 }
-                context.lobby_id = Some(lobby_id.clone());
             }
+        }
+    }
 
-            // Fetch avatar and add to lobby
-            let avatar_url = fetch_user_avatar(state, user.user_id).await;
+    match finished {
+        Some(scores) => finish_game(state, lobby_id, game_id, scores, None).await,
+        None => finish_turn(state, lobby_id, game_id).await,
+    }
 
-            // Send lobby created response first
-            tx.send(ServerMessage::LobbyCreated {
-                lobby_code: lobby_code.clone(),
-            })
-            .await?;
+    if !counts_for_quests {
+        return Ok(());
+    }
+    let event = QuestEvent::WordScored {
+        word: &word,
+        score: word_score,
+    };
+    record_quest_event(state, lobby_id, user.user_id, event).await;
+    Ok(())
+}
 
-            // Then add player and send joined confirmation
-            if let Some((lobby_type, lobby_code, _is_host, _active_game_id)) =
-                add_player_to_lobby(state, &lobby_id, user, avatar_url, tx.clone()).await
-            {
-                tx.send(ServerMessage::LobbyJoined {
-                    lobby_id,
-                    lobby_type,
-                    lobby_code,
-                })
-                .await?;
+/// The first board from position `from` on in a puzzle rush's sequence with a
+/// word worth `target_score`, checked against the lobby's dictionary and dealt
+/// with its multiplier layout
+///
+/// Finding it can take many solves, so they run on a blocking thread with the
+/// lobby's settings copied out; callers mustn't hold the lobby.
+async fn deal_rush_board(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    seed: u64,
+    from: u32,
+    target_score: i32,
+    rules: GameRules,
+) -> Result<(u32, Grid), ApiError> {
+    let (selection, strictness, layout) = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| {
+            (
+                lobby.dictionary_pack.clone(),
+                lobby.dictionary_strictness,
+                lobby.board_key(&state.dictionary_packs).layout,
+            )
+        })
+        .ok_or(ApiError::LobbyNotFound)?;
+    let solver_state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let words = LobbyWords {
+            base: solver_state
+                .dictionary
+                .get()
+                .ok_or(ApiError::DictionaryLoading)?,
+            packs: &solver_state.dictionary_packs,
+            selection: selection.as_ref(),
+            strictness,
+        };
+        Ok(next_board(layout, seed, from, target_score, &words, &rules))
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!("Dealing a puzzle rush board failed: {}", e);
+        ApiError::Internal
+    })?
+}
+
+/// Trimmed names of a hot-seat game's local players: 2-6 different names that
+/// aren't blank or too long
+fn local_player_names(names: Vec<String>) -> Result<Vec<String>, ApiError> {
+    let names: Vec<String> = names.iter().map(|name| name.trim().to_string()).collect();
+    if names.len() < 2 {
+        return Err(ApiError::NotEnoughPlayers(names.len()));
+    }
+    if names.len() > 6 {
+        return Err(ApiError::TooManyPlayers(names.len()));
+    }
+    let distinct: HashSet<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let usable = names
+        .iter()
+        .all(|name| !name.is_empty() && name.chars().count() <= MAX_LOCAL_PLAYER_NAME_LEN);
+    if !usable || distinct.len() != names.len() {
+        return Err(ApiError::InvalidLocalPlayers);
+    }
+    Ok(names)
+}
+
+/// Look a word up in the shared dictionary combined with the lobby's theme pack,
+/// and names if the rules allow them; returns its tier if the lobby accepts it
+fn lookup_word(
+    state: &AppState,
+    lobby_id: &str,
+    word: &str,
+    rules: &GameRules,
+) -> Result<WordTier, ApiError> {
+    let (strictness, pack) = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| (lobby.dictionary_strictness, lobby.dictionary_pack.clone()))
+        .unwrap_or_default();
+    let dictionary = state.dictionary.get().ok_or(ApiError::DictionaryLoading)?;
+    let tier = state
+        .dictionary_packs
+        .lookup(dictionary, pack.as_ref(), word)
+        .or_else(|| {
+            rules
+                .allow_proper_nouns
+                .then(|| state.dictionary_packs.lookup_proper_noun(word))
+                .flatten()
+        })
+        .ok_or(ApiError::WordNotInDictionary)?
+        .tier;
+    if !strictness.accepts(tier) {
+        return Err(ApiError::WordTooObscure);
+    }
+    Ok(tier)
+}
+
+/// Count an event towards the player's quests, telling them about any it completes
+async fn record_quest_event(state: &AppState, lobby_id: &str, user_id: i64, event: QuestEvent<'_>) {
+    // Guests don't keep quest progress
+    if auth::is_guest_id(user_id) {
+        return;
+    }
+    let updates = quest_updates(&event, chrono::Utc::now());
+    if updates.is_empty() {
+        return;
+    }
+    let completed = match db::queries::record_quest_progress(&state.db, user_id, &updates).await {
+        Ok(completed) if !completed.is_empty() => completed,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::error!(
+                "Failed to record quest progress for user {}: {}",
+                user_id,
+                e
+            );
+            return;
+        }
+    };
+    let wallet = match db::queries::get_wallet(&state.db, user_id).await {
+        Ok(wallet) => wallet.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Failed to load rewards for user {}: {}", user_id, e);
+            return;
+        }
+    };
+
+    let Some(tx) = state
+        .lobbies
+        .get(lobby_id)
+        .and_then(|lobby| lobby.players.get(&user_id).map(|p| p.tx.clone()))
+    else {
+        return;
+    };
+    for stored in completed {
+        let Some(update) = updates.iter().find(|u| u.quest.def.id == stored.quest_id) else {
+            continue;
+        };
+        tracing::info!("User {} completed quest {}", user_id, stored.quest_id);
+        let _ = tx
+            .send(ServerMessage::QuestCompleted {
+                quest: QuestStatus::new(&update.quest, Some(&stored)),
+                gems: wallet.gems,
+                xp: wallet.xp,
+            })
+            .await;
+    }
+}
+
+/// Handle the UndoMove message - a player takes back the word they just played
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id))]
+pub(super) async fn handle_undo_move(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<(), ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+
+    let undone = {
+        let mut session = state
+            .active_games
+            .get_mut(&game_id)
+            .ok_or(ApiError::GameNotFound)?;
+        if !session.state.rules.undo {
+            return Err(ApiError::UndoNotAllowed);
+        }
+        session.undo_last_move(user.user_id, Instant::now())?
+    };
+    refresh_remaining_scores(state, game_id).await;
+    let restored = state
+        .active_games
+        .get(&game_id)
+        .map(|session| game_state_message(state, &session, None));
+
+    // The restored scores and board reach the database with the next flush
+    if undone.flushed {
+        if let Err(e) = db::queries::delete_undone_move(&state.db, game_id, &undone.game_move).await
+        {
+            tracing::error!("Failed to delete undone move in game {}: {}", game_id, e);
+        }
+    }
+    if let Some(seq) = undone.flushed_events_from {
+        if let Err(e) = db::queries::delete_game_events_from(&state.db, game_id, seq).await {
+            tracing::error!("Failed to delete undone events in game {}: {}", game_id, e);
+        }
+    }
+
+    tracing::info!(
+        "User {} ({}) took back {} in game {}",
+        user.username,
+        user.user_id,
+        undone.game_move.word,
+        game_id
+    );
+    broadcast_to_lobby(
+        state,
+        lobby_id,
+        ServerMessage::MoveUndone {
+            user_id: user.user_id,
+            word: undone.game_move.word,
+            score: undone.game_move.score,
+        },
+    )
+    .await;
+    if let Some(restored) = restored {
+        broadcast_to_lobby(state, lobby_id, restored).await;
+    }
+    Ok(())
+}
+
+/// Handle the PassTurn message - the current player gives up their turn
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id))]
+pub(super) async fn handle_pass_turn(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<(), ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+
+    let passed = {
+        let Some(mut session) = state.active_games.get_mut(&game_id) else {
+            return Err(ApiError::GameNotFound);
+        };
+        if !engine_for(&session.state.mode).has_turns() {
+            return Err(ApiError::NoTurns);
+        }
+        if session.current_player_id() != Some(user.user_id) {
+            return Err(ApiError::NotYourTurn);
+        }
+        let player = session.state.current_player_index;
+        let penalty = session.state.pass_penalty(state.config.game.pass_penalty);
+        session.commit(vec![GameEvent::TurnPassed { player, penalty }]);
+        let player = &session.state.players[player];
+        ServerMessage::TurnPassed {
+            user_id: user.user_id,
+            penalty,
+            score: player.score,
+            consecutive_passes: session
+                .state
+                .consecutive_passes
+                .get(&player.user_id)
+                .copied()
+                .unwrap_or(0),
+        }
+    };
+    broadcast_to_lobby(state, lobby_id, passed).await;
+
+    finish_turn(state, lobby_id, game_id).await;
+    Ok(())
+}
+
+/// Handle the Resign message - the player forfeits and leaves the turn rotation.
+/// The game ends once only one player is left.
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id))]
+pub(super) async fn handle_resign(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<(), ApiError> {
+    let game_id = require_game_session(state, lobby_id).await?;
+
+    let (was_their_turn, game_over, username, hot_seat) = {
+        let mut session = state
+            .active_games
+            .get_mut(&game_id)
+            .ok_or(ApiError::GameNotFound)?;
+        // In a hot-seat game it's the local player whose turn it is who resigns
+        let index = session
+            .acting_index(user.user_id)
+            .ok_or(ApiError::NotPlaying)?;
+        if session.state.players[index].forfeited {
+            return Err(ApiError::NotPlaying);
+        }
+        session.commit(vec![GameEvent::PlayerResigned { player: index }]);
+        let hot_seat = session.state.mode == GameMode::HotSeat;
+        let username = match hot_seat {
+            true => session.state.players[index].username.clone(),
+            false => user.username.clone(),
+        };
+
+        // On their own turn the turn advance ends the game if it's decided; duels
+        // and time attacks have no turns
+        let was_their_turn = engine_for(&session.state.mode).has_turns()
+            && session.current_player_id() == Some(user.user_id);
+        let game_over = !was_their_turn && session.state.resignations_end_game();
+        if game_over {
+            session.commit(vec![GameEvent::GameFinished {
+                reason: GameOverReason::Resignation,
+            }]);
+        }
+        (
+            was_their_turn,
+            game_over.then(|| {
+                (
+                    session_scores(&session),
+                    CoopStatus::from_state(&session.state),
+                )
+            }),
+            username,
+            hot_seat,
+        )
+    };
+
+    // A local player's resignation is saved with the hot seats on the next flush
+    if !hot_seat {
+        if let Err(e) = db::queries::mark_player_forfeited(&state.db, game_id, user.user_id).await {
+            tracing::error!(
+                "Failed to record resignation of {} in game {}: {}",
+                user.user_id,
+                game_id,
+                e
+            );
+        }
+    }
+
+    broadcast_to_lobby(
+        state,
+        lobby_id,
+        ServerMessage::PlayerResigned {
+            user_id: user.user_id,
+            username,
+        },
+    )
+    .await;
+
+    if was_their_turn {
+        finish_turn(state, lobby_id, game_id).await;
+    } else if let Some((scores, coop)) = game_over {
+        finish_game(state, lobby_id, game_id, scores, coop).await;
+    }
+    Ok(())
+}
+
+/// Advance a cached game to the next turn and broadcast the result.
+///
+/// Round boundaries and game end flush the session to the database; ordinary turns
+/// only mark it dirty.
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id, game_id = %game_id))]
+pub(super) async fn finish_turn(state: &Arc<AppState>, lobby_id: &str, game_id: Uuid) {
+    let now = Instant::now();
+    let (mut advance, board) = {
+        let Some(mut session) = state.active_games.get_mut(&game_id) else {
+            return;
+        };
+        // Duels and time attacks end on their timer (or a resignation), never on
+        // a turn
+        let engine = engine_for(&session.state.mode);
+        if !engine.has_turns() {
+            return;
+        }
+        let Some(turn_ended) = engine.end_turn(&session.state, now) else {
+            return;
+        };
+        let Some(advance) = session.commit(vec![turn_ended]).advance else {
+            return;
+        };
+        session.state.start_turn_clock(now);
+        // The board to check for words, as of this move
+        let board = (advance != TurnAdvance::GameFinished).then(|| {
+            (
+                session.state.grid.clone(),
+                session.state.used_words.clone(),
+                session.state.move_count,
+            )
+        });
+        (advance, board)
+    };
+
+    // Searching the board is done off the session lock; the game only ends if
+    // nothing has been played on it in the meantime
+    let mut exhausted = None;
+    if let Some((grid, used_words, move_count)) = board {
+        if let Some(remaining) =
+            remaining_words_if_exhausted(state, lobby_id, grid, used_words).await
+        {
+            if let Some(mut session) = state.active_games.get_mut(&game_id) {
+                if session.state.move_count == move_count
+                    && session.state.status == GameStatus::InProgress
+                {
+                    session.commit(vec![GameEvent::GameFinished {
+                        reason: GameOverReason::BoardExhausted,
+                    }]);
+                    advance = TurnAdvance::GameFinished;
+                    exhausted = Some(remaining);
+                }
+            }
+        }
+    }
+
+    let (next_player, round, scores, coop, time_remaining) = {
+        let Some(session) = state.active_games.get(&game_id) else {
+            return;
+        };
+        let time_remaining = session
+            .state
+            .current_player()
+            .and_then(|p| session.state.clock_remaining(p.user_id, now))
+            .map(|remaining| remaining.as_secs_f64().ceil() as u32);
+        (
+            session.current_player_id(),
+            session.state.current_round,
+            session_scores(&session),
+            CoopStatus::from_state(&session.state),
+            time_remaining,
+        )
+    };
+
+    if let Some(remaining_words) = exhausted {
+        broadcast_to_lobby(
+            state,
+            lobby_id,
+            ServerMessage::BoardExhausted { remaining_words },
+        )
+        .await;
+    }
+
+    match advance {
+        TurnAdvance::NextTurn => {}
+        TurnAdvance::RoundEnded => {
+            flush_game_session(state, game_id).await;
+            broadcast_to_lobby(
+                state,
+                lobby_id,
+                ServerMessage::RoundEnd {
+                    scores,
+                    next_round: round as i32,
+                },
+            )
+            .await;
+        }
+        TurnAdvance::GameFinished => {
+            finish_game(state, lobby_id, game_id, scores, coop).await;
+            return;
+        }
+    }
+
+    if let Some(current_player) = next_player {
+        broadcast_to_lobby(
+            state,
+            lobby_id,
+            ServerMessage::TurnUpdate {
+                current_player,
+                time_remaining,
+            },
+        )
+        .await;
+    }
+
+    send_commentary(state, lobby_id, game_id).await;
+}
+
+/// Send the lobby's spectators the best words on the board and each player's
+/// chances, for a game that's still going
+///
+/// Spectators are lobby members not playing in the game, users queued for a seat
+/// and admin observers. Nothing is solved while nobody is watching.
+async fn send_commentary(state: &Arc<AppState>, lobby_id: &str, game_id: Uuid) {
+    let Some(seated) = state
+        .active_games
+        .get(&game_id)
+        .map(|session| session.players.clone())
+    else {
+        return;
+    };
+    let Some((spectators, observed)) = state.lobbies.get(lobby_id).map(|lobby| {
+        let spectators: Vec<ClientSender> = lobby
+            .players
+            .iter()
+            .filter(|player| player.is_connected() && !seated.contains(&player.user_id))
+            .map(|player| player.tx.clone())
+            .chain(lobby.waiting_queue.iter().map(|queued| queued.tx.clone()))
+            .collect();
+        (spectators, !lobby.observers.is_empty())
+    }) else {
+        return;
+    };
+    if spectators.is_empty() && !observed {
+        return;
+    }
+
+    let Some((board, current_player, scores, rounds_left)) =
+        state.active_games.get(&game_id).and_then(|session| {
+            if session.state.status != GameStatus::InProgress {
+                return None;
+            }
+            let spectated = BoardView {
+                grid: &session.state.grid,
+                used_words: &session.state.used_words,
+            };
+            // Already solved for the `show_max_remaining` rule, or unsolved
+            let board = match session.best_remaining_words(&spectated) {
+                Some(best) => Ok(best.to_vec()),
+                None => Err((
+                    session.state.grid.clone(),
+                    session.state.used_words.clone(),
+                    session.state.rules,
+                )),
+            };
+            let scores: Vec<(i64, i32)> = match session.state.coop {
+                Some(_) => Vec::new(),
+                None => session
+                    .state
+                    .players
+                    .iter()
+                    .zip(&session.players)
+                    .filter(|(player, _)| !player.forfeited)
+                    .map(|(player, user_id)| (*user_id, player.score))
+                    .collect(),
+            };
+            let rounds_left = (session.state.total_rounds as u32 + 1)
+                .saturating_sub(session.state.current_round as u32);
+            Some((board, session.current_player_id(), scores, rounds_left))
+        })
+    else {
+        return;
+    };
+
+    let best_moves = match board {
+        Ok(best) => best,
+        Err((grid, used_words, rules)) => {
+            let Some(mut solved) =
+                solve_boards(state, game_id, lobby_id, rules, vec![(grid, used_words)]).await
+            else {
+                return;
+            };
+            let Some((grid, used_words, mut best)) = solved.pop() else {
+                return;
+            };
+            if let Some(mut session) = state.active_games.get_mut(&game_id) {
+                session.keep_remaining_words(grid, used_words, best.clone());
+            }
+            best.truncate(COMMENTARY_BEST_MOVES);
+            best
+        }
+    };
+    let message = ServerMessage::Commentary {
+        current_player,
+        best_moves,
+        win_probabilities: win_probabilities(&scores, rounds_left),
+    };
+
+    let payload = OutboundMessage::shared(&message);
+    for tx in spectators {
+        let _ = tx.send(payload.clone()).await;
+    }
+    if let Some(lobby) = state.lobbies.get(lobby_id) {
+        lobby.notify_observers(&message);
+    }
+}
+
+/// Check whether a game's board has run out of words: returns how many unused
+/// words remain if that's at or below the configured threshold.
+/// None while the dictionary is still loading.
+///
+/// The search runs on a blocking thread, so the board is passed by value.
+async fn remaining_words_if_exhausted(
+    state: &Arc<AppState>,
+    lobby_id: &str,
+    grid: Grid,
+    used_words: HashSet<String>,
+) -> Option<usize> {
+    let (strictness, pack) = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| (lobby.dictionary_strictness, lobby.dictionary_pack.clone()))
+        .unwrap_or_default();
+    let threshold = state.config.game.board_exhausted_threshold;
+    let solver_state = state.clone();
+    let remaining = tokio::task::spawn_blocking(move || {
+        let words = LobbyWords {
+            base: solver_state.dictionary.get()?,
+            packs: &solver_state.dictionary_packs,
+            selection: pack.as_ref(),
+            strictness,
+        };
+        Some(find_unused_words(&grid, &words, &used_words, threshold + 1).len())
+    })
+    .await
+    .inspect_err(|e| tracing::error!("Checking for an exhausted board failed: {}", e))
+    .ok()??;
+    (remaining <= threshold).then_some(remaining)
+}
+
+/// The most points a player can still score on a board, in games with the
+/// `show_max_remaining` rule. None otherwise, and until the board's words have
+/// been worked out by `refresh_remaining_scores`.
+fn max_remaining_score(session: &GameSession, board: BoardView, index: usize) -> Option<i32> {
+    if !session.state.rules.show_max_remaining {
+        return None;
+    }
+    let scores = session.remaining_scores(&board)?;
+    Some(solver::max_remaining_score(
+        scores,
+        turns_left(&session.state, index),
+    ))
+}
+
+/// Work out the words left on any board of a game with the `show_max_remaining`
+/// rule that has changed since it was last looked at, and keep them on the
+/// session
+///
+/// Solving a board visits every path on it, so it's done off the session lock
+/// on a blocking thread, once per board rather than for every message.
+pub(super) async fn refresh_remaining_scores(state: &Arc<AppState>, game_id: Uuid) {
+    let (stale, rules, lobby_id) = {
+        let Some(session) = state.active_games.get(&game_id) else {
+            return;
+        };
+        if !session.state.rules.show_max_remaining {
+            return;
+        }
+        let engine = engine_for(&session.state.mode);
+        let spectated = BoardView {
+            grid: &session.state.grid,
+            used_words: &session.state.used_words,
+        };
+        let boards = (0..session.state.players.len())
+            .filter_map(|index| engine.board(&session.state, index))
+            .chain([spectated]);
+        let mut stale: Vec<(Grid, HashSet<String>)> = Vec::new();
+        for board in boards {
+            let known = session.remaining_scores(&board).is_some()
+                || stale
+                    .iter()
+                    .any(|(grid, used)| grid == board.grid && used == board.used_words);
+            if !known {
+                stale.push((board.grid.clone(), board.used_words.clone()));
+            }
+        }
+        (stale, session.state.rules, session.lobby_id.clone())
+    };
+    if stale.is_empty() {
+        return;
+    }
+
+    if let Some(solved) = solve_boards(state, game_id, &lobby_id, rules, stale).await {
+        if let Some(mut session) = state.active_games.get_mut(&game_id) {
+            for (grid, used, words) in solved {
+                session.keep_remaining_words(grid, used, words);
+            }
+        }
+    }
+}
+
+/// Find every unused word on each of a game's boards, best first, on a blocking
+/// thread. None while the dictionary is still loading; boards are solved again
+/// after the next command, once it has loaded.
+async fn solve_boards(
+    state: &Arc<AppState>,
+    game_id: Uuid,
+    lobby_id: &str,
+    rules: GameRules,
+    boards: Vec<(Grid, HashSet<String>)>,
+) -> Option<Vec<(Grid, HashSet<String>, Vec<BestWord>)>> {
+    let (strictness, pack) = state
+        .lobbies
+        .get(lobby_id)
+        .map(|lobby| (lobby.dictionary_strictness, lobby.dictionary_pack.clone()))
+        .unwrap_or_default();
+    let solver_state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let words = LobbyWords {
+            base: solver_state.dictionary.get()?,
+            packs: &solver_state.dictionary_packs,
+            selection: pack.as_ref(),
+            strictness,
+        };
+        let solved: Vec<_> = boards
+            .into_iter()
+            .map(|(grid, used)| {
+                let best = best_words(&grid, &words, &used, &rules, usize::MAX);
+                (grid, used, best)
+            })
+            .collect();
+        Some(solved)
+    })
+    .await
+    .inspect_err(|e| tracing::error!("Solving the boards of game {} failed: {}", game_id, e))
+    .ok()?
+}
+
+/// How many more words a player can play, where the game has turns; a co-op
+/// team scores together, so every player's turns count
+fn turns_left(game_state: &GameState, index: usize) -> Option<usize> {
+    if !engine_for(&game_state.mode).has_turns() {
+        return None;
+    }
+    Some(match game_state.mode {
+        GameMode::Coop => (0..game_state.players.len())
+            .map(|index| game_state.turns_left(index))
+            .sum(),
+        _ => game_state.turns_left(index),
+    })
+}
+
+/// Persist a finished game, evict it from the cache and announce the result
+#[tracing::instrument(skip_all, fields(lobby_id = %lobby_id, game_id = %game_id))]
+async fn finish_game(
+    state: &AppState,
+    lobby_id: &str,
+    game_id: Uuid,
+    final_scores: Vec<ScoreInfo>,
+    coop: Option<CoopStatus>,
+) {
+    // Final write-back before the session leaves the cache
+    flush_game_session(state, game_id).await;
+    let removed = state.active_games.remove(&game_id).map(|(_, session)| {
+        let grid = &session.state.grid;
+        let reason = session
+            .state
+            .end_reason
+            .unwrap_or(GameOverReason::RoundsComplete);
+        (
+            (grid.len(), grid.first().map_or(0, |row| row.len())),
+            reason,
+            session_standings(&session),
+            TimeAttackStatus::from_state(&session.state),
+            PuzzleRushStatus::from_state(&session.state),
+            hot_seat_result(&session),
+        )
+    });
+    let (grid_size, reason, session_standings, time_attack, puzzle_rush, hot_seat) = match removed {
+        Some((size, reason, standings, time_attack, puzzle_rush, hot_seat)) => (
+            Some(size),
+            reason,
+            standings,
+            time_attack,
+            puzzle_rush,
+            hot_seat,
+        ),
+        None => (
+            None,
+            GameOverReason::RoundsComplete,
+            HashMap::new(),
+            None,
+            None,
+            None,
+        ),
+    };
+    let moves = match grid_size {
+        Some(_) => finished_game_moves(state, game_id).await,
+        None => None,
+    };
+
+    // Co-op and time attack teams win or lose together, so there's no individual
+    // winner
+    let is_hot_seat = hot_seat.is_some();
+    let result = match (coop.as_ref(), time_attack.as_ref(), hot_seat) {
+        (Some(coop), _, _) => GameResult::coop(coop.outcome.unwrap_or(CoopOutcome::Lose)),
+        (None, Some(time_attack), _) => GameResult::time_attack(time_attack.coverage_percent),
+        (None, None, Some(hot_seat)) => hot_seat,
+        (None, None, None) => {
+            let best_words = moves.as_deref().map(best_word_scores).unwrap_or_default();
+            let standings: Vec<FinalStanding> = final_scores
+                .iter()
+                .map(|s| {
+                    let from_session = session_standings.get(&s.user_id);
+                    FinalStanding {
+                        user_id: s.user_id,
+                        score: s.score,
+                        passes: from_session.map_or(0, |f| f.passes),
+                        best_word: best_words.get(&s.user_id).copied().unwrap_or(0),
+                        forfeited: from_session.is_some_and(|f| f.forfeited),
+                    }
+                })
+                .collect();
+            decide_result(&standings)
+        }
+    };
+    let winner = result.winner();
+    let coop_won = match result.outcome {
+        GameOutcome::CoopWin => Some(true),
+        GameOutcome::CoopLose => Some(false),
+        GameOutcome::Win | GameOutcome::Draw => None,
+    };
+    // Hot-seat games are one person playing everyone, so they count for no quests
+    let player_ids: Vec<i64> = match is_hot_seat {
+        true => Vec::new(),
+        false => final_scores.iter().map(|s| s.user_id).collect(),
+    };
+    if let Err(e) = db::queries::finish_game(&state.db, game_id, &result).await {
+        tracing::error!("Failed to mark game {} as finished: {}", game_id, e);
+    }
+    // The run goes on the puzzle rush leaderboard, which guests stay off
+    if let Some(rush) = puzzle_rush.as_ref().filter(|_| grid_size.is_some()) {
+        for score in final_scores
+            .iter()
+            .filter(|s| !auth::is_guest_id(s.user_id))
+        {
+            let recorded = db::queries::record_puzzle_rush_run(
+                &state.db,
+                game_id,
+                score.user_id,
+                rush.streak,
+                score.score,
+            )
+            .await;
+            if let Err(e) = recorded {
+                tracing::error!("Failed to record puzzle rush {}: {}", game_id, e);
+            }
+        }
+    }
+    let heatmap = moves
+        .as_deref()
+        .zip(grid_size)
+        .map(|(moves, (rows, cols))| tile_heatmap(moves, rows, cols));
+    let pacing = moves.as_deref().map(move_pacing).unwrap_or_default();
+
+    let guild_id = state.lobbies.get_mut(lobby_id).and_then(|mut lobby| {
+        if lobby.active_game_id == Some(game_id) {
+            lobby.active_game_id = None;
+        }
+        lobby.record_activity(Activity::GameFinished {
+            game_id: game_id.to_string(),
+            winner,
+            winner_username: winner.and_then(|winner| {
+                final_scores
+                    .iter()
+                    .find(|s| s.user_id == winner)
+                    .map(|s| s.username.clone())
+            }),
+            team_won: coop_won,
+        });
+        lobby.guild_id.clone()
+    });
+    presence::notify_lobby_presence(state, lobby_id);
+
+    tracing::info!("Game {} finished in lobby {}", game_id, lobby_id);
+
+    broadcast_to_lobby(
+        state,
+        lobby_id,
+        ServerMessage::GameOver {
+            winner,
+            result,
+            final_scores,
+            coop,
+            heatmap,
+            pacing,
+            reason,
+            time_attack,
+            puzzle_rush,
+        },
+    )
+    .await;
+
+    if let Some(guild_id) = guild_id {
+        broadcast_guild_leaderboard(state, &guild_id).await;
+    }
+
+    // Only the call that actually evicted the session counts the game, so a
+    // finish that races another can't count it twice
+    if grid_size.is_some() {
+        let team_won = coop_won == Some(true);
+        for user_id in player_ids {
+            let won = team_won || winner == Some(user_id);
+            record_quest_event(state, lobby_id, user_id, QuestEvent::GameFinished { won }).await;
+        }
+    }
+
+    // Seats freed up during the game go to the queue for the next one
+    promote_queued_players(state, lobby_id).await;
+}
+
+/// A finished game's moves from the database, for the end-of-game heatmap and pacing
+async fn finished_game_moves(state: &AppState, game_id: Uuid) -> Option<Vec<GameMove>> {
+    match db::queries::get_game_moves(&state.db, game_id).await {
+        Ok(moves) => Some(moves),
+        Err(e) => {
+            tracing::error!("Failed to load moves for finished game {}: {}", game_id, e);
+            None
+        }
+    }
+}
+
+/// Send the guild's updated standings to every lobby in the guild, so channels
+/// playing at the same time see each other's results
+async fn broadcast_guild_leaderboard(state: &AppState, guild_id: &str) {
+    let Ok(guild_id_num) = guild_id.parse::<i64>() else {
+        return;
+    };
+    let standings = match db::queries::get_guild_standings(
+        &state.db,
+        guild_id_num,
+        GUILD_LEADERBOARD_SIZE,
+        None,
+    )
+    .await
+    {
+        Ok(standings) => standings,
+        Err(e) => {
+            tracing::error!("Failed to load leaderboard for guild {}: {}", guild_id, e);
+            return;
+        }
+    };
+
+    let message = ServerMessage::GuildLeaderboardUpdate {
+        guild_id: guild_id.to_string(),
+        standings: GuildStandingInfo::rank_all(standings),
+    };
+
+    // Collect IDs first so no lobby ref is held across the sends
+    let guild_lobbies: Vec<String> = state
+        .lobbies
+        .iter()
+        .filter(|lobby| lobby.guild_id.as_deref() == Some(guild_id))
+        .map(|lobby| lobby.lobby_id.clone())
+        .collect();
+    for lobby_id in guild_lobbies {
+        broadcast_to_lobby(state, &lobby_id, message.clone()).await;
+    }
+}
+
+/// End the turn of a blitz player whose clock has run out, or the game if a
+/// duel or time attack's time is up
+///
+/// Run on every tick of the game's actor, so it never races the players' moves.
+pub(super) async fn check_game_clock(state: &Arc<AppState>, game_id: Uuid) {
+    let expired = state
+        .active_games
+        .get(&game_id)
+        .filter(|session| session.state.current_player_out_of_time(Instant::now()))
+        .map(|session| (session.lobby_id.clone(), session.current_player_id()));
+    if let Some((lobby_id, user_id)) = expired {
+        if let Some(user_id) = user_id {
+            tracing::info!("Player {} ran out of time in game {}", user_id, game_id);
+            broadcast_to_lobby(state, &lobby_id, ServerMessage::PlayerOutOfTime { user_id }).await;
+        }
+        finish_turn(state, &lobby_id, game_id).await;
+    }
+
+    let timed_out = {
+        let Some(mut session) = state.active_games.get_mut(&game_id) else {
+            return;
+        };
+        if session.state.status != GameStatus::InProgress {
+            return;
+        }
+        let Some(reason) =
+            engine_for(&session.state.mode).completion(&session.state, chrono::Utc::now())
+        else {
+            return;
+        };
+        session.commit(vec![GameEvent::GameFinished { reason }]);
+        (session.lobby_id.clone(), session_scores(&session))
+    };
+    let (lobby_id, scores) = timed_out;
+    tracing::info!("Time is up in game {}", game_id);
+    finish_game(state, &lobby_id, game_id, scores, None).await;
+}
+
+/// The lobby the player is currently in
+/// Whether the connection only watches
+async fn is_spectating(player_context: &Arc<tokio::sync::Mutex<PlayerContext>>) -> bool {
+    player_context.lock().await.role.is_spectator()
+}
+
+async fn current_lobby_id(
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+) -> Result<String, ApiError> {
+    player_context
+        .lock()
+        .await
+        .lobby_id
+        .clone()
+        .ok_or(ApiError::NotInLobby)
+}
+
+/// The channel a lobby belongs to; tables only exist in channel lobbies
+fn lobby_channel(state: &AppState, lobby_id: &str) -> Result<String, ApiError> {
+    state
+        .lobbies
+        .get(lobby_id)
+        .ok_or(ApiError::LobbyNotFound)?
+        .channel_id
+        .clone()
+        .ok_or(ApiError::NotInChannelLobby)
+}
+
+/// Move a player to another table of their channel lobby
+async fn enter_table(
+    state: &AppState,
+    tx: &ClientSender,
+    user: &AuthenticatedUser,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+    table_id: &str,
+) -> Result<(), ApiError> {
+    let avatar_url = fetch_user_avatar(state, user.user_id).await;
+    let (lobby_type, lobby_code, _is_host, active_game_id) = add_player_to_lobby(
+        state,
+        table_id,
+        user,
+        avatar_url,
+        tx.clone(),
+        is_spectating(player_context).await,
+    )
+    .await?;
+
+    // Only leave the previous table once the new one has accepted the player
+    switch_player_lobby(state, player_context, table_id, user.user_id).await;
+
+    tx.send(ServerMessage::LobbyJoined {
+        lobby_id: table_id.to_string(),
+        lobby_type,
+        lobby_code,
+    })
+    .await?;
+    send_activity_feed(state, table_id, tx).await?;
+
+    if active_game_id.is_some() {
+        send_active_game_state_if_exists(state, table_id, user.user_id, tx).await?;
+    }
+    Ok(())
+}
+
+/// Send the table browser to everyone in any of a channel's tables
+async fn broadcast_table_list(state: &AppState, channel_id: &str) {
+    let tables = state.channel_tables(channel_id);
+    let message = ServerMessage::TableList {
+        tables: tables.clone(),
+    };
+    for table in &tables {
+        broadcast_to_lobby(state, &table.lobby_id, message.clone()).await;
+    }
+}
+
+/// Check a message against the sender's connection role before it's handled;
+/// host permissions apply to the lobby the message names, else the current one
+async fn authorize_message(
+    msg: &ClientMessage,
+    state: &AppState,
+    user: &AuthenticatedUser,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+) -> Result<(), ApiError> {
+    let permission = Permission::required(msg);
+    let (role, current_lobby_id) = {
+        let context = player_context.lock().await;
+        (context.role, context.lobby_id.clone())
+    };
+    let lobby = match (permission, role) {
+        (Permission::Host(_), ConnectionRole::Player) => {
+            let lobby_id = current_lobby_id.ok_or(ApiError::NotInLobby)?;
+            Some(
+                state
+                    .lobbies
+                    .get(&lobby_id)
+                    .ok_or(ApiError::LobbyNotFound)?,
+            )
+        }
+        _ => None,
+    };
+    permissions::authorize(role, permission, user.user_id, lobby.as_deref())
+}
+
+/// Handle individual client messages
+async fn handle_client_message(
+    msg: ClientMessage,
+    state: &Arc<AppState>,
+    tx: &ClientSender,
+    user: &AuthenticatedUser,
+    player_context: &Arc<tokio::sync::Mutex<PlayerContext>>,
+) -> Result<(), ApiError> {
+    authorize_message(&msg, state, user, player_context).await?;
+
+    match msg {
+        ClientMessage::JoinChannelLobby {
+            channel_id,
+            guild_id,
+        } => {
+            tracing::info!(
+                "User {} ({}) joining channel lobby: {}, guild: {:?}",
+                user.username,
+                user.user_id,
+                channel_id,
+                guild_id
+            );
+
+            // Return to the table they're seated at, or else the channel's main lobby
+            let lobby_id = match state.find_player_table(&channel_id, user.user_id) {
+                Some(table_id) => table_id,
+                None => get_or_create_channel_lobby(state, &channel_id, guild_id).await,
+            };
+
+            // Fetch avatar and add to lobby (handles reconnection)
+            let avatar_url = fetch_user_avatar(state, user.user_id).await;
+            let joined = add_player_to_lobby(
+                state,
+                &lobby_id,
+                user,
+                avatar_url.clone(),
+                tx.clone(),
+                is_spectating(player_context).await,
+            )
+            .await;
+            let (lobby_type, lobby_code, _is_host, active_game_id) = match joined {
+                // A full channel lobby puts newcomers in line for the next game
+                Err(ApiError::LobbyFull) => {
+                    return queue_for_lobby(state, tx, user, player_context, &lobby_id, avatar_url)
+                        .await;
+                }
+                joined => joined?,
+            };
+
+            // Only leave the previous lobby once the new one has accepted the player
+            switch_player_lobby(state, player_context, &lobby_id, user.user_id).await;
+
+            // Send confirmation
+            tx.send(ServerMessage::LobbyJoined {
+                lobby_id: lobby_id.clone(),
+                lobby_type,
+                lobby_code,
+            })
+            .await?;
+            send_activity_feed(state, &lobby_id, tx).await?;
+
+            // If game is active, send game state
+            if active_game_id.is_some() {
+                tracing::info!("Player joined lobby with active game, sending game state");
+                send_active_game_state_if_exists(state, &lobby_id, user.user_id, tx).await?;
+            }
+        }
+
+        ClientMessage::CreateCustomLobby => {
+            tracing::info!(
+                "User {} ({}) creating custom lobby",
+                user.username,
+                user.user_id
+            );
+
+            // Each lobby holds memory and a code until cleanup, so creation is rate limited
+            if !state.lobby_creation_limiter.check(user.user_id) {
+                tracing::warn!(
+                    "User {} ({}) hit the lobby creation rate limit",
+                    user.username,
+                    user.user_id
+                );
+                return Err(ApiError::RateLimited);
+            }
+
+            // Create the custom lobby
+            let (lobby_id, lobby_code) = create_custom_lobby(state)?;
+
+            // Fetch avatar and add to lobby
+            let avatar_url = fetch_user_avatar(state, user.user_id).await;
+
+            // Send lobby created response first
+            tx.send(ServerMessage::LobbyCreated {
+                lobby_code: lobby_code.clone(),
+            })
+            .await?;
+
+            // Then add player, leave the previous lobby, and send joined confirmation
+            // Spectators can't create lobbies, so this is always a player
+            let (lobby_type, lobby_code, _is_host, _active_game_id) =
+                add_player_to_lobby(state, &lobby_id, user, avatar_url, tx.clone(), false).await?;
+            switch_player_lobby(state, player_context, &lobby_id, user.user_id).await;
+
+            tx.send(ServerMessage::LobbyJoined {
+                lobby_id,
+                lobby_type,
+                lobby_code,
+            })
+            .await?;
+        }
+
+        ClientMessage::JoinCustomLobby {
+            lobby_code,
+            invite_token,
+        } => {
+            // An invite token is an alternative to the raw code
+            let lobby_code = match (invite_token, lobby_code) {
+                (Some(token), _) => {
+                    auth::validate_invite_token(&token, &state.config.security.jwt_secret)
+                        .map_err(|_| ApiError::InvalidInvite)?
+                        .lobby_code
+                }
+                (None, Some(code)) => code,
+                (None, None) => {
+                    return Err(ApiError::InvalidMessage(
+                        "lobby_code or invite_token is required".to_string(),
+                    ))
+                }
+            };
+
+            tracing::info!(
+                "User {} ({}) joining custom lobby with code: {}",
+                user.username,
+                user.user_id,
+                lobby_code
+            );
+
+            // Find the lobby by code
+            let lobby_id = state
+                .find_lobby_by_code(&lobby_code)
+                .ok_or_else(|| ApiError::LobbyCodeNotFound(lobby_code.clone()))?;
+
+            // Fetch avatar and add to lobby
+            let avatar_url = fetch_user_avatar(state, user.user_id).await;
+            let (lobby_type, lobby_code, _is_host, active_game_id) = add_player_to_lobby(
+                state,
+                &lobby_id,
+                user,
+                avatar_url,
+                tx.clone(),
+                is_spectating(player_context).await,
+            )
+            .await?;
+
+            // Only leave the previous lobby once the new one has accepted the player
+            switch_player_lobby(state, player_context, &lobby_id, user.user_id).await;
+
+            tx.send(ServerMessage::LobbyJoined {
+                lobby_id: lobby_id.clone(),
+                lobby_type,
+                lobby_code,
+            })
+            .await?;
+            send_activity_feed(state, &lobby_id, tx).await?;
+
+            // If game is active, send game state
+            if active_game_id.is_some() {
+                tracing::info!("Player joined lobby with active game, sending game state");
+                send_active_game_state_if_exists(state, &lobby_id, user.user_id, tx).await?;
+            }
+        }
+
+        ClientMessage::PreviewLobby { lobby_code } => {
+            let lobby_id = state
+                .find_lobby_by_code(&lobby_code)
+                .ok_or_else(|| ApiError::LobbyCodeNotFound(lobby_code.clone()))?;
+            let preview = state
+                .lobbies
+                .get(&lobby_id)
+                .and_then(|lobby| lobby_preview(&lobby, state, user.user_id))
+                .ok_or(ApiError::LobbyCodeNotFound(lobby_code))?;
+            tx.send(preview).await?;
+        }
+
+        ClientMessage::LeaveLobby => {
+            tracing::info!("User {} ({}) leaving lobby", user.username, user.user_id);
+
+            let mut context = player_context.lock().await;
+            if let Some(lobby_id) = context.lobby_id.take() {
+                remove_player_from_lobby(state, &lobby_id, user.user_id).await;
+                state.presence.leave_lobby(user.user_id, &lobby_id);
+                presence::notify_presence(state, user.user_id);
+            }
+            state.last_lobbies.forget(user.user_id);
+        }
+
+        ClientMessage::ListTables => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let channel_id = lobby_channel(state, &lobby_id)?;
+            tx.send(ServerMessage::TableList {
+                tables: state.channel_tables(&channel_id),
+            })
+            .await?;
+        }
+
+        ClientMessage::CreateTable => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let channel_id = lobby_channel(state, &lobby_id)?;
+            let (guild_id, guild_settings) = state
+                .lobbies
+                .get(&lobby_id)
+                .map(|lobby| (lobby.guild_id.clone(), lobby.guild_settings.clone()))
+                .ok_or(ApiError::LobbyNotFound)?;
+
+            let table_id = state.create_table(&channel_id, guild_id, guild_settings)?;
+            tracing::info!(
+                "User {} ({}) opened table {} in channel {}",
+                user.username,
+                user.user_id,
+                table_id,
+                channel_id
+            );
+            enter_table(state, tx, user, player_context, &table_id).await?;
+            broadcast_table_list(state, &channel_id).await;
+        }
+
+        ClientMessage::JoinTable { table_number } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let channel_id = lobby_channel(state, &lobby_id)?;
+            let table_id = Lobby::table_lobby_id(&channel_id, table_number);
+            if !state.lobbies.contains_key(&table_id) {
+                return Err(ApiError::TableNotFound);
+            }
+
+            enter_table(state, tx, user, player_context, &table_id).await?;
+            broadcast_table_list(state, &channel_id).await;
+        }
+
+        ClientMessage::ReserveSeat { user_id } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let reserved_for: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+
+            let ttl = Duration::from_secs(state.config.game.seat_reservation_secs);
+            state.reserve_seat(&lobby_id, reserved_for, ttl)?;
+
+            tracing::info!(
+                "User {} ({}) reserved a seat in lobby {} for {}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                reserved_for
+            );
+
+            tx.send(ServerMessage::SeatReserved {
+                user_id,
+                expires_in_secs: ttl.as_secs(),
+            })
+            .await?;
+        }
+
+        ClientMessage::InvitePlayer { user_id } => {
+            let invitee: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+            if invitee == user.user_id {
+                return Err(ApiError::InvalidUserId);
+            }
+            let lobby_id = current_lobby_id(player_context).await?;
+            let (lobby_code, from) = {
+                let lobby = state
+                    .lobbies
+                    .get(&lobby_id)
+                    .ok_or(ApiError::LobbyNotFound)?;
+                let lobby_code = lobby.lobby_code.clone().ok_or(ApiError::NotInCustomLobby)?;
+                let player = lobby
+                    .players
+                    .get(&user.user_id)
+                    .ok_or(ApiError::NotLobbyMember)?;
+                let from = InviteSender {
+                    user_id: user.user_id.to_string(),
+                    username: player.username.clone(),
+                    avatar_url: player.avatar_url.clone(),
+                };
+                (lobby_code, from)
+            };
+            if !state.invite_limiter.check(user.user_id) {
+                return Err(ApiError::RateLimited);
+            }
+
+            // An invite from someone the invitee blocked is dropped, but reported as
+            // delivered so the block isn't revealed
+            let message = ServerMessage::InviteReceived {
+                from,
+                lobby_code: lobby_code.clone(),
+            };
+            let delivered = state.block_lists.is_blocked(invitee, user.user_id)
+                || state.user_connections.send(invitee, &message);
+            if !delivered {
+                let expires_at = chrono::Utc::now() + QUEUED_INVITE_TTL;
+                let queued = db::queries::queue_lobby_invite(
+                    &state.db,
+                    user.user_id,
+                    invitee,
+                    &lobby_code,
+                    expires_at,
+                )
+                .await?;
+                if !queued {
+                    return Err(ApiError::UserNotFound);
+                }
+            }
+
+            tracing::info!(
+                "User {} ({}) invited {} to lobby {} ({})",
+                user.username,
+                user.user_id,
+                invitee,
+                lobby_code,
+                if delivered { "delivered" } else { "queued" }
+            );
+
+            tx.send(ServerMessage::InviteSent { user_id, delivered })
+                .await?;
+        }
+
+        ClientMessage::GetFriendsPresence { user_ids } => {
+            let user_ids = user_ids
+                .iter()
+                .take(presence::MAX_WATCHED_USERS)
+                .map(|id| id.parse::<i64>().map_err(|_| ApiError::InvalidUserId))
+                .collect::<Result<Vec<_>, _>>()?;
+            state.presence.watch(user.user_id, user_ids.iter().copied());
+
+            let friends = user_ids
+                .iter()
+                .map(|&friend| presence::presence_of(state, friend, user.user_id))
+                .collect();
+            tx.send(ServerMessage::FriendsPresence { friends }).await?;
+        }
+
+        ClientMessage::SetDictionaryStrictness { strictness } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.dictionary_strictness = strictness;
+            }
+
+            tracing::info!(
+                "User {} ({}) set dictionary strictness in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                strictness
+            );
+            broadcast_to_lobby(
+                state,
+                &lobby_id,
+                ServerMessage::DictionaryStrictnessChanged { strictness },
+            )
+            .await;
+        }
+
+        ClientMessage::SetMultiplierLayout { layout } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.multiplier_layout = layout;
+            }
+
+            tracing::info!(
+                "User {} ({}) set multiplier layout in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                layout
+            );
+            broadcast_to_lobby(
+                state,
+                &lobby_id,
+                ServerMessage::MultiplierLayoutChanged { layout },
+            )
+            .await;
+        }
+
+        ClientMessage::SetLetterMode { mode } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.letter_mode = mode;
+            }
+
+            tracing::info!(
+                "User {} ({}) set letter mode in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                mode
+            );
+            broadcast_to_lobby(state, &lobby_id, ServerMessage::LetterModeChanged { mode }).await;
+        }
+
+        ClientMessage::SetBoardTheme { theme } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.board_theme = theme;
             }
+
+            tracing::info!(
+                "User {} ({}) set board theme in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                theme
+            );
+            broadcast_to_lobby(
+                state,
+                &lobby_id,
+                ServerMessage::BoardThemeChanged {
+                    theme,
+                    styles: theme.styles(),
+                },
+            )
+            .await;
         }
 
-        ClientMessage::JoinCustomLobby { lobby_code } => {
+        ClientMessage::SetGameRules { rules } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            rules.validate().map_err(ApiError::InvalidSettings)?;
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.rules = rules;
+            }
+
             tracing::info!(
-                "User {} ({}) joining custom lobby with code: {}",
+                "User {} ({}) set house rules in lobby {} to {:?}",
                 user.username,
                 user.user_id,
-                lobby_code
+                lobby_id,
+                rules
             );
+            broadcast_to_lobby(state, &lobby_id, ServerMessage::GameRulesChanged { rules }).await;
+        }
 
-            // Find the lobby by code
-            let lobby_id = match find_lobby_by_code(state, &lobby_code) {
-                Some(id) => id,
-                None => {
-                    tx.send(ServerMessage::Error {
-                        message: format!("Lobby with code '{}' not found", lobby_code),
-                    })
-                    .await?;
-                    return Ok(());
+        ClientMessage::SetReady { ready } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(lobby) = state.lobbies.get(&lobby_id) {
+                if let Some(mut player) = lobby.players.get_mut(&user.user_id) {
+                    player.ready = ready;
                 }
-            };
+            }
+            // Ready changes are rare and worth showing right away
+            broadcast_lobby_player_list(state, &lobby_id).await;
+        }
 
-            // Remove from previous lobby if different
-            {
-                let mut context = player_context.lock().await;
-                if let Some(old_lobby_id) = &context.lobby_id {
-                    if old_lobby_id != &lobby_id {
-                        remove_player_from_lobby(state, old_lobby_id, user.user_id).await;
-                    }
+        ClientMessage::SetTyping { typing } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            if let Some(lobby) = state.lobbies.get(&lobby_id) {
+                if let Some(mut player) = lobby.players.get_mut(&user.user_id) {
+                    player.typing_since = typing.then(Instant::now);
                 }
-                context.lobby_id = Some(lobby_id.clone());
             }
+        }
 
-            // Fetch avatar and add to lobby
-            let avatar_url = fetch_user_avatar(state, user.user_id).await;
-            if let Some((lobby_type, lobby_code, _is_host, active_game_id)) =
-                add_player_to_lobby(state, &lobby_id, user, avatar_url, tx.clone()).await
-            {
-                tx.send(ServerMessage::LobbyJoined {
-                    lobby_id: lobby_id.clone(),
-                    lobby_type,
-                    lobby_code,
+        ClientMessage::ListDictionaryPacks => {
+            let packs = state
+                .dictionary_packs
+                .list()
+                .into_iter()
+                .map(|pack| DictionaryPackInfo {
+                    name: pack.name.clone(),
+                    description: pack.description.clone(),
+                    language: pack.language.clone(),
+                    word_count: pack.len(),
                 })
-                .await?;
+                .collect();
+            tx.send(ServerMessage::DictionaryPackList { packs }).await?;
+        }
 
-                // If game is active, send game state
-                if active_game_id.is_some() {
-                    tracing::info!("Player joined lobby with active game, sending game state");
-                    send_active_game_state_if_exists(state, &lobby_id, tx).await?;
-                }
+        ClientMessage::SelectPuzzle { puzzle_id } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let puzzle = match puzzle_id {
+                Some(puzzle_id) => Some(
+                    db::queries::get_puzzle(&state.db, puzzle_id)
+                        .await?
+                        .and_then(|puzzle| puzzle.for_lobby())
+                        .ok_or(ApiError::PuzzleNotFound)?,
+                ),
+                None => None,
+            };
+            if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
+                lobby.puzzle = puzzle.clone();
             }
+
+            tracing::info!(
+                "User {} ({}) set puzzle in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                puzzle_id
+            );
+            broadcast_to_lobby(state, &lobby_id, ServerMessage::PuzzleSelected { puzzle }).await;
         }
 
-        ClientMessage::LeaveLobby => {
-            tracing::info!("User {} ({}) leaving lobby", user.username, user.user_id);
+        ClientMessage::SelectDictionaryPack { pack, mode } => {
+            let lobby_id = current_lobby_id(player_context).await?;
 
-            let mut context = player_context.lock().await;
-            if let Some(lobby_id) = context.lobby_id.take() {
-                remove_player_from_lobby(state, &lobby_id, user.user_id).await;
+            let selection = match pack {
+                Some(name) => {
+                    let name = name.trim().to_lowercase();
+                    if state.dictionary_packs.theme(&name).is_none() {
+                        return Err(ApiError::DictionaryPackNotFound);
+                    }
+                    Some(PackSelection { name, mode })
+                }
+                None => None,
+            };
+            let language_change = state.lobbies.get_mut(&lobby_id).and_then(|mut lobby| {
+                let before = lobby.language(&state.dictionary_packs);
+                lobby.dictionary_pack = selection.clone();
+                let after = lobby.language(&state.dictionary_packs);
+                (after != before).then_some(after)
+            });
+
+            tracing::info!(
+                "User {} ({}) set dictionary pack in lobby {} to {:?}",
+                user.username,
+                user.user_id,
+                lobby_id,
+                selection
+            );
+            broadcast_to_lobby(
+                state,
+                &lobby_id,
+                ServerMessage::DictionaryPackSelected { pack: selection },
+            )
+            .await;
+            if let Some(language) = language_change {
+                broadcast_to_lobby(state, &lobby_id, ServerMessage::language_changed(language))
+                    .await;
             }
         }
 
-        ClientMessage::CreateGame { mode } => {
+        ClientMessage::CreateGame {
+            mode,
+            local_players,
+        } => {
             tracing::info!(
                 "User {} ({}) creating game with mode: {:?}",
                 user.username,
                 user.user_id,
                 mode
             );
+
+            // Hot-seat games start in the current lobby with the named local players
+            if mode == GameMode::HotSeat {
+                let lobby_id = current_lobby_id(player_context).await?;
+                let game_started_msg =
+                    handle_start_game(state, &lobby_id, user, mode, None, local_players).await?;
+                broadcast_to_lobby(state, &lobby_id, game_started_msg).await;
+                return Ok(());
+            }
+
+            if let Some(lobby_id) = player_context.lock().await.lobby_id.clone() {
+                let allowed = state
+                    .lobbies
+                    .get(&lobby_id)
+                    .is_none_or(|lobby| lobby.allows_mode(&mode));
+                if !allowed {
+                    return Err(ApiError::ModeNotAllowed);
+                }
+            }
             // TODO: Implement game creation
             let game_id = uuid::Uuid::new_v4().to_string();
             tx.send(ServerMessage::GameCreated { game_id }).await?;
@@ -878,37 +3613,95 @@ async fn handle_client_message(
             // TODO: Implement leave game logic
         }
 
-        ClientMessage::StartGame => {
-            tracing::info!("User {} ({}) starting game", user.username, user.user_id);
-
-            // Get the player's current lobby
-            let context = player_context.lock().await;
-            let lobby_id = match &context.lobby_id {
-                Some(id) => id.clone(),
-                None => {
-                    tx.send(ServerMessage::GameError {
-                        code: "not_in_lobby".to_string(),
-                        message: "You must be in a lobby to start a game".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
-            drop(context);
+        ClientMessage::StartGame { mode, target_score } => {
+            let mode = mode.unwrap_or(GameMode::Multiplayer);
+            tracing::info!(
+                "User {} ({}) starting {:?} game",
+                user.username,
+                user.user_id,
+                mode
+            );
 
             // Validate and start the game
-            match handle_start_game(state, &lobby_id, user).await {
-                Ok(game_started_msg) => {
-                    // Broadcast GameStarted to all players in the lobby
-                    broadcast_to_lobby(state, &lobby_id, game_started_msg).await;
+            let lobby_id = current_lobby_id(player_context).await?;
+            let game_started_msg =
+                handle_start_game(state, &lobby_id, user, mode, target_score, Vec::new()).await?;
+
+            // Broadcast GameStarted to all players in the lobby
+            broadcast_to_lobby(state, &lobby_id, game_started_msg).await;
+        }
+
+        ClientMessage::ScheduleGame {
+            starts_at,
+            mode,
+            target_score,
+        } => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let schedule = ScheduledStart::new(
+                starts_at,
+                mode.unwrap_or(GameMode::Multiplayer),
+                target_score,
+                user.user_id,
+                chrono::Utc::now(),
+            )?;
+            {
+                let mut lobby = state
+                    .lobbies
+                    .get_mut(&lobby_id)
+                    .ok_or(ApiError::LobbyNotFound)?;
+                if !lobby.allows_mode(&schedule.mode) {
+                    return Err(ApiError::ModeNotAllowed);
                 }
-                Err(error_msg) => {
-                    tx.send(error_msg).await?;
+                if lobby.active_game_id.is_some() {
+                    return Err(ApiError::GameInProgress);
                 }
+                lobby.scheduled_start = Some(schedule.clone());
+            }
+
+            tracing::info!(
+                "User {} ({}) scheduled a {:?} game in lobby {} for {}",
+                user.username,
+                user.user_id,
+                schedule.mode,
+                lobby_id,
+                schedule.starts_at
+            );
+            broadcast_to_lobby(state, &lobby_id, ServerMessage::GameScheduled { schedule }).await;
+        }
+
+        ClientMessage::CancelScheduledGame => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            let schedule = state
+                .lobbies
+                .get_mut(&lobby_id)
+                .and_then(|mut lobby| lobby.scheduled_start.take())
+                .ok_or(ApiError::NoScheduledGame)?;
+            if let Some(event_id) = schedule.game_night_event {
+                game_nights::record_cancelled(state, event_id).await;
             }
+
+            tracing::info!(
+                "User {} ({}) cancelled the scheduled game in lobby {}",
+                user.username,
+                user.user_id,
+                lobby_id
+            );
+            broadcast_to_lobby(
+                state,
+                &lobby_id,
+                ServerMessage::ScheduledGameCancelled {
+                    reason: ScheduleCancelReason::Host,
+                },
+            )
+            .await;
         }
 
-        ClientMessage::SubmitWord { word, positions } => {
+        ClientMessage::SubmitWord {
+            word,
+            positions,
+            board_checksum,
+            submission_id,
+        } => {
             tracing::info!(
                 "User {} ({}) submitting word: {} at positions: {:?}",
                 user.username,
@@ -917,263 +3710,92 @@ async fn handle_client_message(
                 positions
             );
 
-            // Get the player's current lobby
-            let context = player_context.lock().await;
-            let lobby_id = match &context.lobby_id {
-                Some(id) => id.clone(),
-                None => {
-                    tx.send(ServerMessage::Error {
-                        message: "You must be in a lobby to submit a word".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
-            drop(context);
-
-            // Get active game from lobby
-            let active_game_id = if let Some(lobby) = state.lobbies.get(&lobby_id) {
-                lobby.active_game_id.clone()
-            } else {
-                tx.send(ServerMessage::Error {
-                    message: "Lobby not found".to_string(),
-                })
-                .await?;
-                return Ok(());
-            };
+            let lobby_id = current_lobby_id(player_context).await?;
 
-            let _game_id_str = match active_game_id {
-                Some(id) => id,
-                None => {
-                    tx.send(ServerMessage::GameError {
-                        code: "no_active_game".to_string(),
-                        message: "No active game in this lobby".to_string(),
-                    })
-                    .await?;
+            // A retry of a word that was already scored gets the original result again
+            if let Some(submission_id) = &submission_id {
+                if let Some(reply) = replay_submission(state, &lobby_id, user, submission_id) {
+                    tx.send(reply).await?;
                     return Ok(());
                 }
-            };
-
-            // Fetch game state from DB
-            let game_state =
-                match db::queries::get_active_game_for_lobby(&state.db, &lobby_id).await {
-                    Ok(Some(gs)) => gs,
-                    Ok(None) => {
-                        tx.send(ServerMessage::GameError {
-                            code: "game_not_found".to_string(),
-                            message: "Game state not found".to_string(),
-                        })
-                        .await?;
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to fetch game state: {}", e);
-                        tx.send(ServerMessage::Error {
-                            message: "Internal server error".to_string(),
-                        })
-                        .await?;
-                        return Ok(());
-                    }
-                };
-
-            // Validate turn
-            // TODO: Proper turn validation using user_id from DB
-            // For now, we'll skip strict turn validation to get it working,
-            // but in production we must check if game_state.players[current_player_index].user_id == user.user_id
-
-            // Check if word is already used
-            if game_state.used_words.contains(&word.to_uppercase()) {
-                tx.send(ServerMessage::InvalidWord {
-                    reason: "Word already used".to_string(),
-                })
-                .await?;
-                return Ok(());
-            }
-
-            // Validate path - use empty HashSet since we only need path validation here
-            let validator = WordValidator::new(std::collections::HashSet::new());
-            if !validator.is_valid_path(&game_state.grid, &positions) {
-                tx.send(ServerMessage::InvalidWord {
-                    reason: "Invalid path".to_string(),
-                })
-                .await?;
-                return Ok(());
-            }
-
-            // Validate word in dictionary using the shared dictionary from AppState
-            if !state.dictionary.contains(&word) {
-                tx.send(ServerMessage::InvalidWord {
-                    reason: "Word not found in dictionary".to_string(),
-                })
-                .await?;
-                return Ok(());
-            }
-
-            // Score word
-            let word_score = Scorer::calculate_score(&game_state.grid, &positions);
-
-            // Update DB
-            let game_uuid = game_state.game_id;
-
-            // 1. Update player score (adds word_score to existing score)
-            if let Err(e) =
-                db::queries::update_player_score(&state.db, game_uuid, user.user_id, word_score)
-                    .await
-            {
-                tracing::error!("Failed to update player score: {}", e);
             }
 
-            // 2. Add to used words
-            let mut new_used_words: Vec<String> = game_state.used_words.into_iter().collect();
-            new_used_words.push(word.to_uppercase());
-            if let Err(e) =
-                db::queries::update_game_board_used_words(&state.db, game_uuid, &new_used_words)
-                    .await
-            {
-                tracing::error!("Failed to update used words: {}", e);
-            }
-
-            // 3. Record move
-            if let Err(e) = db::queries::create_game_move(
-                &state.db,
-                game_uuid,
-                user.user_id,
-                game_state.current_round as i32,
-                &word,
-                word_score,
-                serde_json::to_value(&positions).unwrap_or_default(),
-            )
-            .await
-            {
-                tracing::error!("Failed to record move: {}", e);
+            // Don't validate a path against a board the client no longer has
+            if let Some(checksum) = &board_checksum {
+                if let Some(resync) =
+                    resync_if_stale(state, &lobby_id, user.user_id, checksum).await
+                {
+                    tx.send(resync).await?;
+                    return Ok(());
+                }
             }
-
-            // Get player's current total score for the broadcast
-            let player_total_score = game_state
-                .players
-                .iter()
-                .find(|p| p.username == user.username)
-                .map(|p| p.score + word_score)
-                .unwrap_or(word_score);
-
-            // Broadcast WordScored with the word score (not total)
-            let player_info = crate::websocket::messages::PlayerInfo {
-                user_id: user.user_id,
-                username: user.username.clone(),
-                avatar_url: None, // TODO: Fetch avatar
-                score: player_total_score, // Send new total score for scoreboard update
-                team: None,
-            };
-
-            broadcast_to_lobby(
+            game_actor::perform(
                 state,
                 &lobby_id,
-                ServerMessage::WordScored {
-                    word: word.to_string(),
-                    score: word_score, // Send the word score, not total
-                    player: player_info,
-                    positions: positions.clone(),
+                user,
+                GameAction::SubmitWord {
+                    tx: tx.clone(),
+                    word,
+                    positions,
+                    board_checksum,
+                    submission_id,
                 },
             )
-            .await;
+            .await?;
+        }
 
-            // TODO: Handle turn passing / round end logic
-            // For now, just keep it simple
+        ClientMessage::UndoMove => {
+            let lobby_id = current_lobby_id(player_context).await?;
+            game_actor::perform(state, &lobby_id, user, GameAction::UndoMove).await?;
         }
 
         ClientMessage::PassTurn => {
             tracing::info!("User {} ({}) passing turn", user.username, user.user_id);
 
-            // Get lobby ID
-            let context = player_context.lock().await;
-            let lobby_id = match &context.lobby_id {
-                Some(id) => id.clone(),
-                None => {
-                    tx.send(ServerMessage::Error {
-                        message: "Not in a lobby".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
-            drop(context);
-
-            // Get active game
-            let game_state =
-                match db::queries::get_active_game_for_lobby(&state.db, &lobby_id).await {
-                    Ok(Some(gs)) => gs,
-                    Ok(None) => {
-                        tx.send(ServerMessage::GameError {
-                            code: "no_game".to_string(),
-                            message: "No active game".to_string(),
-                        })
-                        .await?;
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to fetch game state: {}", e);
-                        return Ok(());
-                    }
-                };
+            let lobby_id = current_lobby_id(player_context).await?;
+            game_actor::perform(state, &lobby_id, user, GameAction::PassTurn).await?;
+        }
 
-            // Determine next player
-            let current_idx = game_state.current_player_index;
-            let next_idx = (current_idx + 1) % game_state.players.len();
-            let _next_player = &game_state.players[next_idx];
-
-            // Update DB
-            let game_uuid = game_state.game_id;
-            // Note: We need the user_id (i64) for the DB update, but GamePlayer struct has user_id as Uuid (in-memory) or i64?
-            // Let's check GamePlayer definition. In db/queries.rs it says:
-            // user_id: Uuid::new_v4(), // Generate a UUID for in-memory tracking
-            // Wait, that's a problem. We need the real user_id (i64) to update the DB.
-            // The GameState struct in models/game.rs uses Uuid for user_id?
-            // Let's look at how we get the next player's ID.
-
-            // Actually, we should probably look up the player in the `players` list from `get_active_game_for_lobby`
-            // But `get_active_game_for_lobby` generates fake UUIDs for `user_id` in `GamePlayer` struct?
-            // Line 476 in queries.rs: `user_id: Uuid::new_v4(),`
-            // This seems wrong if we need to reference them back.
-            // However, `current_turn_player` in `games` table is `i64`.
-
-            // For now, let's try to get the real user_id from the `game_players` table again or fix `get_active_game_for_lobby`.
-            // But I can't easily change `get_active_game_for_lobby` right now without seeing `models/game.rs`.
-            // Let's assume we can get the player's real ID by re-querying or using the index.
-
-            // Re-query game players to get real IDs
-            let players = db::queries::get_game_players(&state.db, game_uuid)
-                .await
-                .unwrap_or_default();
-            if players.is_empty() {
-                return Ok(());
+        ClientMessage::BlockUser { user_id } => {
+            let blocked: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+            if blocked == user.user_id {
+                return Err(ApiError::InvalidUserId);
             }
+            db::queries::block_user(&state.db, user.user_id, blocked).await?;
+            state.block_lists.block(user.user_id, blocked);
+            tracing::info!("User {} blocked {}", user.user_id, blocked);
+
+            tx.send(block_list_message(state, user.user_id)).await?;
+        }
 
-            // Sort by team/joined_at to match the order in GameState
-            // The order should be consistent.
-            let next_player_record = &players[next_idx];
-            let next_player_id = next_player_record.user_id;
+        ClientMessage::UnblockUser { user_id } => {
+            let unblocked: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+            db::queries::unblock_user(&state.db, user.user_id, unblocked).await?;
+            state.block_lists.unblock(user.user_id, unblocked);
+            tracing::info!("User {} unblocked {}", user.user_id, unblocked);
 
-            if let Err(e) = db::queries::update_game_round(
-                &state.db,
-                game_uuid,
-                game_state.current_round as i32,
-                next_player_id,
-            )
-            .await
-            {
-                tracing::error!("Failed to update turn: {}", e);
-            }
+            tx.send(block_list_message(state, user.user_id)).await?;
+        }
 
-            // Broadcast update
-            broadcast_to_lobby(
-                state,
-                &lobby_id,
-                ServerMessage::TurnUpdate {
-                    current_player: next_player_id,
-                    time_remaining: None,
-                },
-            )
-            .await;
+        ClientMessage::MutePlayer { user_id } => {
+            let muted: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+            let lobby_id = current_lobby_id(player_context).await?;
+            let message = set_muted(state, &lobby_id, user.user_id, muted, true)?;
+            tx.send(message).await?;
+        }
+
+        ClientMessage::UnmutePlayer { user_id } => {
+            let muted: i64 = user_id.parse().map_err(|_| ApiError::InvalidUserId)?;
+            let lobby_id = current_lobby_id(player_context).await?;
+            let message = set_muted(state, &lobby_id, user.user_id, muted, false)?;
+            tx.send(message).await?;
+        }
+
+        ClientMessage::Resign => {
+            tracing::info!("User {} ({}) resigning", user.username, user.user_id);
+
+            let lobby_id = current_lobby_id(player_context).await?;
+            game_actor::perform(state, &lobby_id, user, GameAction::Resign).await?;
         }
 
         ClientMessage::EnableTimer => {
@@ -1188,79 +3810,41 @@ async fn handle_client_message(
                 user.user_id
             );
 
-            // Get lobby ID
-            let context = player_context.lock().await;
-            let lobby_id = match &context.lobby_id {
-                Some(id) => id.clone(),
-                None => {
-                    tx.send(ServerMessage::Error {
-                        message: "Not in a lobby".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
-            drop(context);
-
-            // Authorization: Check if user is the lobby host
-            if let Some(lobby) = state.lobbies.get(&lobby_id) {
-                if !lobby.is_host(user.user_id) {
-                    tx.send(ServerMessage::Error {
-                        message: "Only the lobby host can access admin functions".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            } else {
-                tx.send(ServerMessage::Error {
-                    message: "Lobby not found".to_string(),
-                })
-                .await?;
-                return Ok(());
-            }
+            let lobby_id = current_lobby_id(player_context).await?;
 
             // Parse channel ID from lobby ID
-            let (channel_id, _) = match db::queries::parse_lobby_id(&lobby_id) {
-                Ok(ids) => ids,
-                Err(_) => {
-                    tx.send(ServerMessage::Error {
-                        message: "Invalid lobby ID".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
+            let (channel_id, _) =
+                db::queries::parse_lobby_id(&lobby_id).map_err(|_| ApiError::InvalidLobbyId)?;
 
             // Fetch games for this channel
-            let games = match sqlx::query_as::<_, crate::models::Game>(
-                "SELECT * FROM games WHERE channel_id = $1 ORDER BY created_at DESC",
-            )
-            .bind(channel_id)
-            .fetch_all(&state.db)
-            .await
-            {
-                Ok(g) => g,
-                Err(e) => {
-                    tracing::error!("Failed to fetch games: {}", e);
-                    tx.send(ServerMessage::Error {
-                        message: "Database error".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
+            let filter = GameListFilter {
+                channel_id: Some(channel_id),
+                ..Default::default()
             };
+            let games = db::queries::list_games(&state.db, &filter, ADMIN_GAMES_LIMIT, 0).await?;
+
+            let game_ids: Vec<Uuid> = games.iter().map(|g| g.game_id).collect();
+            let mut players_by_game: HashMap<Uuid, Vec<_>> = HashMap::new();
+            for player in db::queries::get_game_player_summaries(&state.db, &game_ids).await? {
+                players_by_game
+                    .entry(player.game_id)
+                    .or_default()
+                    .push(player);
+            }
 
+            // Games in this channel were played in this lobby, so it has their connections
+            let lobby = state.lobbies.get(&lobby_id);
             let admin_games = games
-                .into_iter()
+                .iter()
                 .map(|g| {
-                    crate::websocket::messages::AdminGameInfo {
-                        game_id: g.game_id.to_string(),
-                        state: g.state.to_string(),
-                        created_at: g.created_at,
-                        players: vec![], // TODO: Fetch players if needed, keeping it simple for now
-                    }
+                    let players = players_by_game
+                        .get(&g.game_id)
+                        .map_or(&[][..], Vec::as_slice);
+                    let session = state.active_games.get(&g.game_id);
+                    admin::admin_game_info(g, players, lobby.as_deref(), session.as_deref())
                 })
                 .collect();
+            drop(lobby);
 
             tx.send(ServerMessage::AdminGamesList { games: admin_games })
                 .await?;
@@ -1274,82 +3858,89 @@ async fn handle_client_message(
                 game_id
             );
 
-            // Get lobby ID and check authorization
-            let context = player_context.lock().await;
-            let lobby_id = match &context.lobby_id {
-                Some(id) => id.clone(),
-                None => {
-                    tx.send(ServerMessage::Error {
-                        message: "Not in a lobby".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
-            drop(context);
+            let lobby_id = current_lobby_id(player_context).await?;
+            let role = player_context.lock().await.role;
+
+            let game_uuid = uuid::Uuid::parse_str(&game_id).map_err(|_| ApiError::InvalidGameId)?;
+
+            // A game still running in a lobby may only be deleted from that lobby,
+            // unless by a server admin
+            let running_in = state
+                .active_games
+                .get(&game_uuid)
+                .map(|session| session.lobby_id.clone())
+                .or_else(|| {
+                    state
+                        .lobbies
+                        .iter()
+                        .find(|lobby| lobby.active_game_id == Some(game_uuid))
+                        .map(|lobby| lobby.key().clone())
+                });
+            if running_in
+                .as_ref()
+                .is_some_and(|running_in| *running_in != lobby_id)
+                && role != ConnectionRole::Admin
+            {
+                return Err(ApiError::NotHost("delete games"));
+            }
 
-            // Authorization: Check if user is the lobby host
-            if let Some(lobby) = state.lobbies.get(&lobby_id) {
-                if !lobby.is_host(user.user_id) {
-                    tx.send(ServerMessage::Error {
-                        message: "Only the lobby host can delete games".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
+            // Delete game (cascades to players, boards, moves)
+            db::queries::delete_game(&state.db, game_uuid).await?;
+
+            // Drop the cached session so it isn't flushed back, and free its lobby
+            state.active_games.remove(&game_uuid);
+            if let Some(running_in) = running_in {
+                if let Some(mut lobby) = state.lobbies.get_mut(&running_in) {
+                    if lobby.active_game_id == Some(game_uuid) {
+                        lobby.active_game_id = None;
+                        tracing::info!("Cleared active game {} from lobby {}", game_id, running_in);
+                    }
                 }
-            } else {
-                tx.send(ServerMessage::Error {
-                    message: "Lobby not found".to_string(),
-                })
-                .await?;
-                return Ok(());
+                presence::notify_lobby_presence(state, &running_in);
+                promote_queued_players(state, &running_in).await;
             }
 
-            let game_uuid = match uuid::Uuid::parse_str(&game_id) {
-                Ok(id) => id,
-                Err(_) => {
-                    tx.send(ServerMessage::Error {
-                        message: "Invalid game ID".to_string(),
-                    })
-                    .await?;
-                    return Ok(());
-                }
-            };
+            tx.send(ServerMessage::AdminGameDeleted {
+                game_id: game_id.clone(),
+            })
+            .await?;
+        }
+        ClientMessage::AdminDumpLobby { lobby_id } => {
+            tracing::info!(
+                "User {} ({}) dumping lobby {}",
+                user.username,
+                user.user_id,
+                lobby_id
+            );
 
-            // Delete game (cascades to players, boards, moves)
-            match sqlx::query("DELETE FROM games WHERE game_id = $1")
-                .bind(game_uuid)
-                .execute(&state.db)
-                .await
-            {
-                Ok(_) => {
-                    // Also clear active_game_id from lobby if it matches
-                    if let Some(mut lobby) = state.lobbies.get_mut(&lobby_id) {
-                        if let Some(active_id) = &lobby.active_game_id {
-                            if active_id == &game_uuid {
-                                lobby.active_game_id = None;
-                                tracing::info!(
-                                    "Cleared active game {} from lobby {}",
-                                    game_id,
-                                    lobby_id
-                                );
-                            }
-                        }
-                    }
+            let lobby = state
+                .lobbies
+                .get(&lobby_id)
+                .ok_or(ApiError::LobbyNotFound)?;
 
-                    tx.send(ServerMessage::AdminGameDeleted {
-                        game_id: game_id.clone(),
-                    })
-                    .await?;
-                }
-                Err(e) => {
-                    tracing::error!("Failed to delete game: {}", e);
-                    tx.send(ServerMessage::Error {
-                        message: "Failed to delete game".to_string(),
-                    })
-                    .await?;
-                }
+            let lobby_dump = lobby.debug_dump();
+            let game_id = lobby.active_game_id;
+            drop(lobby);
+
+            let mut game_dump = game_id
+                .and_then(|id| state.active_games.get(&id))
+                .map(|session| session.debug_dump());
+
+            // Include the persisted board revisions so a client snapshot can be
+            // compared against what the server had at each move
+            if let (Some(game_id), Some(serde_json::Value::Object(dump))) =
+                (game_id, game_dump.as_mut())
+            {
+                let history = db::queries::get_board_history(&state.db, game_id).await?;
+                dump.insert("board_history".to_string(), serde_json::to_value(history)?);
             }
+
+            tx.send(ServerMessage::AdminLobbyDump {
+                lobby_id,
+                lobby: lobby_dump,
+                game: game_dump,
+            })
+            .await?;
         }
     }
 
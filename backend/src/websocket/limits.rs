@@ -0,0 +1,261 @@
+use std::{
+    collections::VecDeque,
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::http::HeaderMap;
+use dashmap::DashMap;
+
+use crate::error::ApiError;
+
+/// Tracks open WebSocket connections per user and per client IP
+pub struct ConnectionLimiter {
+    max_per_user: usize,
+    max_per_ip: usize,
+    by_user: Arc<DashMap<i64, usize>>,
+    by_ip: Arc<DashMap<IpAddr, usize>>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max_per_user: usize, max_per_ip: usize) -> Self {
+        Self {
+            max_per_user,
+            max_per_ip,
+            by_user: Arc::new(DashMap::new()),
+            by_ip: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Reserve a connection slot for a user and IP.
+    ///
+    /// The slot is held until the returned permit is dropped.
+    pub fn try_acquire(&self, user_id: i64, ip: IpAddr) -> Result<ConnectionPermit, ApiError> {
+        if !try_increment(&self.by_user, user_id, self.max_per_user) {
+            return Err(ApiError::TooManyConnections);
+        }
+        if !try_increment(&self.by_ip, ip, self.max_per_ip) {
+            decrement(&self.by_user, &user_id);
+            return Err(ApiError::TooManyConnections);
+        }
+
+        Ok(ConnectionPermit {
+            user_id,
+            ip,
+            by_user: self.by_user.clone(),
+            by_ip: self.by_ip.clone(),
+        })
+    }
+
+    /// Number of open connections for a user
+    #[cfg(test)]
+    pub fn user_connections(&self, user_id: i64) -> usize {
+        self.by_user.get(&user_id).map(|c| *c).unwrap_or(0)
+    }
+}
+
+/// An open connection slot, released on drop
+pub struct ConnectionPermit {
+    user_id: i64,
+    ip: IpAddr,
+    by_user: Arc<DashMap<i64, usize>>,
+    by_ip: Arc<DashMap<IpAddr, usize>>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        decrement(&self.by_user, &self.user_id);
+        decrement(&self.by_ip, &self.ip);
+    }
+}
+
+fn try_increment<K: Eq + Hash>(counts: &DashMap<K, usize>, key: K, max: usize) -> bool {
+    let mut count = counts.entry(key).or_insert(0);
+    if *count >= max {
+        return false;
+    }
+    *count += 1;
+    true
+}
+
+fn decrement<K: Eq + Hash>(counts: &DashMap<K, usize>, key: &K) {
+    // Drop the entry once it reaches zero so the maps don't grow without bound
+    counts.remove_if_mut(key, |_, count| {
+        *count = count.saturating_sub(1);
+        *count == 0
+    });
+}
+
+/// Resolve the client IP, preferring the last X-Forwarded-For hop when the
+/// server is configured to sit behind a trusted proxy
+///
+/// The proxy appends the address it saw to whatever the client sent, so only the
+/// rightmost entry can be trusted; anything left of it may be forged.
+pub fn client_ip(headers: &HeaderMap, peer: SocketAddr, trust_forwarded_for: bool) -> IpAddr {
+    if trust_forwarded_for {
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .next_back()
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        if let Some(ip) = forwarded {
+            return ip;
+        }
+    }
+    peer.ip()
+}
+
+/// Sliding-window rate limiter keyed by user ID (or client IP)
+pub struct RateLimiter<K = i64> {
+    max_events: usize,
+    window: Duration,
+    events: DashMap<K, VecDeque<Instant>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(max_events: usize, window: Duration) -> Self {
+        Self {
+            max_events,
+            window,
+            events: DashMap::new(),
+        }
+    }
+
+    /// Record an event for a user, or return false if they're over the limit
+    pub fn check(&self, key: K) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: K, now: Instant) -> bool {
+        let mut events = self.events.entry(key).or_default();
+        while events
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.window)
+        {
+            events.pop_front();
+        }
+
+        if events.len() >= self.max_events {
+            return false;
+        }
+        events.push_back(now);
+        true
+    }
+
+    /// Forget users with no events in the current window
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.events.retain(|_, events| {
+            events
+                .back()
+                .is_some_and(|t| now.duration_since(*t) < self.window)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([10, 0, 0, last])
+    }
+
+    #[test]
+    fn test_connection_limit_per_user() {
+        let limiter = ConnectionLimiter::new(2, 10);
+        let _first = limiter.try_acquire(1, ip(1)).unwrap();
+        let _second = limiter.try_acquire(1, ip(2)).unwrap();
+
+        assert!(
+            limiter.try_acquire(1, ip(3)).is_err(),
+            "A third connection for the same user should be rejected"
+        );
+        assert!(
+            limiter.try_acquire(2, ip(3)).is_ok(),
+            "Other users should be unaffected"
+        );
+    }
+
+    #[test]
+    fn test_connection_limit_per_ip() {
+        let limiter = ConnectionLimiter::new(10, 2);
+        let _first = limiter.try_acquire(1, ip(1)).unwrap();
+        let _second = limiter.try_acquire(2, ip(1)).unwrap();
+
+        assert!(limiter.try_acquire(3, ip(1)).is_err());
+        assert_eq!(
+            limiter.user_connections(3),
+            0,
+            "A rejected IP check should release the user slot it reserved"
+        );
+    }
+
+    #[test]
+    fn test_dropping_permit_releases_slot() {
+        let limiter = ConnectionLimiter::new(1, 1);
+        let permit = limiter.try_acquire(1, ip(1)).unwrap();
+        assert_eq!(limiter.user_connections(1), 1);
+
+        drop(permit);
+        assert_eq!(limiter.user_connections(1), 0);
+        assert!(limiter.by_user.is_empty(), "Zero counts should be removed");
+        assert!(limiter.try_acquire(1, ip(1)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limiter_blocks_within_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.check_at(1, start));
+        assert!(limiter.check_at(1, start + Duration::from_secs(1)));
+        assert!(
+            !limiter.check_at(1, start + Duration::from_secs(2)),
+            "Third event inside the window should be rejected"
+        );
+        assert!(limiter.check_at(2, start), "Limits are per user");
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forged_forwarded_hops() {
+        let peer = SocketAddr::from(([10, 0, 0, 1], 443));
+        let mut headers = HeaderMap::new();
+        // The client claims to be 1.2.3.4; the proxy appends the address it saw
+        headers.insert("x-forwarded-for", "1.2.3.4, 203.0.113.7".parse().unwrap());
+
+        assert_eq!(
+            client_ip(&headers, peer, true),
+            IpAddr::from([203, 0, 113, 7])
+        );
+        assert_eq!(
+            client_ip(&headers, peer, false),
+            peer.ip(),
+            "The header is ignored unless a proxy is trusted"
+        );
+
+        // A proxy may add its own header line rather than extend the client's
+        headers.append("x-forwarded-for", "198.51.100.9".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, peer, true),
+            IpAddr::from([198, 51, 100, 9])
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_allows_after_window() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.check_at(1, start));
+        assert!(!limiter.check_at(1, start + Duration::from_secs(59)));
+        assert!(
+            limiter.check_at(1, start + Duration::from_secs(60)),
+            "Events older than the window should no longer count"
+        );
+    }
+}
@@ -1,6 +1,33 @@
 use serde::{Deserialize, Serialize};
 
-use crate::models::{GameMode, GridCell, Position};
+use crate::{
+    avatars,
+    dictionary::{
+        packs::{PackMode, PackSelection},
+        DictionaryStrictness, WordTier,
+    },
+    game::{
+        board_theme::{BoardStyles, BoardTheme},
+        commentary::WinProbability,
+        grid::{LetterMode, MultiplierLayout},
+        letter_bag::LetterBag,
+        pacing::PlayerPacing,
+        result::GameResult,
+        rules::GameRules,
+        scorer::ScoreBreakdown,
+        solver::BestWord,
+    },
+    locale,
+    models::{
+        CoopOutcome, GameMode, GameOverReason, GameState, GridCell, GuildStanding, LobbyPuzzle,
+        PlayerProfile, Position, QuestStatus,
+    },
+    websocket::{
+        activity::ActivityEntry,
+        schedule::{ScheduleCancelReason, ScheduleCountdown, ScheduledStart},
+        trace::current_request_id,
+    },
+};
 
 /// Player information sent with GameStarted message
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,31 +60,186 @@ pub enum ClientMessage {
     },
     /// Create a new custom lobby with a shareable code
     CreateCustomLobby,
-    /// Join an existing custom lobby by its code
+    /// Join an existing custom lobby by its code or a signed invite token
     JoinCustomLobby {
+        #[serde(default)]
+        lobby_code: Option<String>,
+        /// Token from `GET /api/lobbies/{code}/invite`; takes precedence over lobby_code
+        #[serde(default)]
+        invite_token: Option<String>,
+    },
+    /// Look at a custom lobby before joining it; answered with LobbyPreview, and
+    /// the lobby isn't told
+    PreviewLobby {
         lobby_code: String,
     },
     /// Leave the current lobby
     LeaveLobby,
+    /// List the tables in the current channel lobby
+    ListTables,
+    /// Open a new table in the current channel lobby and move to it
+    CreateTable,
+    /// Move to another table in the current channel lobby (table 1 is the main lobby)
+    JoinTable {
+        table_number: u32,
+    },
+    /// Mark yourself ready (or not) for the next game
+    SetReady {
+        ready: bool,
+    },
+    /// Tell the lobby you're typing in chat; expires on its own if not refreshed
+    SetTyping {
+        typing: bool,
+    },
+    /// Hold a seat for a Discord user so the lobby can't fill up before they join (host only)
+    ReserveSeat {
+        user_id: String,
+    },
+    /// Invite a user to your custom lobby; delivered right away if they're connected,
+    /// otherwise when they next connect
+    InvitePlayer {
+        user_id: String,
+    },
+    /// Where these users (the client's Discord friends) are right now; also starts
+    /// `friend_presence_changed` updates for them, replacing any earlier list
+    GetFriendsPresence {
+        user_ids: Vec<String>,
+    },
     CreateGame {
         mode: GameMode,
+        /// Hot-seat only: names of the local players sharing this connection, who
+        /// start a game in the current lobby straight away
+        #[serde(default)]
+        local_players: Vec<String>,
     },
     JoinGame {
         game_id: String,
     },
     LeaveGame,
-    StartGame,
+    StartGame {
+        /// Mode to play; defaults to multiplayer
+        #[serde(default)]
+        mode: Option<GameMode>,
+        /// Co-op only: team score to beat (defaults to a per-turn target)
+        #[serde(default)]
+        target_score: Option<i32>,
+    },
+    /// Host only: start a game on its own at `starts_at`, if enough players are
+    /// ready by then (see `StartGame` for the other fields)
+    ScheduleGame {
+        starts_at: chrono::DateTime<chrono::Utc>,
+        #[serde(default)]
+        mode: Option<GameMode>,
+        #[serde(default)]
+        target_score: Option<i32>,
+    },
+    /// Host only: call off the scheduled game
+    CancelScheduledGame,
     SubmitWord {
         word: String,
         positions: Vec<Position>,
+        /// The client's `board_checksum`; a mismatch gets a ResyncState instead of a score
+        #[serde(default)]
+        board_checksum: Option<String>,
+        /// Client-generated id for this attempt; resending it (e.g. after a dropped
+        /// connection) gets the original WordScored back instead of a second score
+        #[serde(default)]
+        submission_id: Option<String>,
     },
     PassTurn,
+    /// Block a user everywhere: their social messages are no longer delivered to you
+    BlockUser {
+        user_id: String,
+    },
+    UnblockUser {
+        user_id: String,
+    },
+    /// Hide a player's social messages until you leave the current lobby
+    MutePlayer {
+        user_id: String,
+    },
+    UnmutePlayer {
+        user_id: String,
+    },
+    /// Forfeit the current game; the player stays in the lobby
+    Resign,
     EnableTimer,
+    /// Host only: choose which dictionary tiers are playable in the lobby
+    SetDictionaryStrictness {
+        strictness: DictionaryStrictness,
+    },
+    /// Host only: choose how multipliers are placed on the lobby's next boards
+    SetMultiplierLayout {
+        layout: MultiplierLayout,
+    },
+    /// Host only: choose where the lobby's next games draw their letters from
+    SetLetterMode {
+        mode: LetterMode,
+    },
+    /// Host only: choose how the lobby's boards are drawn, including the game
+    /// being played
+    SetBoardTheme {
+        theme: BoardTheme,
+    },
+    /// Take back the word you just played, in games whose rules allow undo
+    UndoMove,
+    /// Host only: set the lobby's house rules for its next games; omitted rules
+    /// go back to the standard ones
+    SetGameRules {
+        rules: GameRules,
+    },
+    /// Host only: deal the lobby's next games a shared puzzle's board, or go
+    /// back to random boards with `puzzle_id: null`
+    SelectPuzzle {
+        puzzle_id: Option<i64>,
+    },
+    /// List the theme packs a host can choose from
+    ListDictionaryPacks,
+    /// Host only: pick a theme pack for the lobby, or clear it with `pack: null`
+    SelectDictionaryPack {
+        pack: Option<String>,
+        #[serde(default)]
+        mode: PackMode,
+    },
     // Admin commands
     AdminGetGames,
     AdminDeleteGame {
         game_id: String,
     },
+    /// Dump the in-memory lobby and game state (for debugging desync reports)
+    AdminDumpLobby {
+        lobby_id: String,
+    },
+}
+
+/// Messages accepted on an admin console connection (`/ws?admin=1`)
+///
+/// The console is read-only apart from the repair commands (`ForceAdvanceTurn`,
+/// `ResetBoard`, `RebuildGame`); it never joins a lobby.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminClientMessage {
+    /// List every lobby in memory along with its running game, if any
+    ListLobbies,
+    /// Start receiving a copy of everything broadcast to a lobby
+    Observe {
+        lobby_id: String,
+    },
+    StopObserving {
+        lobby_id: String,
+    },
+    /// Pass the current player's turn in a stuck game
+    ForceAdvanceTurn {
+        lobby_id: String,
+    },
+    /// Deal a new board for a stuck game, keeping scores, turn and used words
+    ResetBoard {
+        lobby_id: String,
+    },
+    /// Reload a desynced game's state from the database, dropping unflushed moves
+    RebuildGame {
+        lobby_id: String,
+    },
 }
 
 /// Messages sent from server to client
@@ -71,15 +253,153 @@ pub enum ServerMessage {
         /// For custom lobbies, the shareable code
         lobby_code: Option<String>,
     },
+    /// Response to PreviewLobby: who's in the lobby and how it's set up, for a
+    /// confirm screen before joining
+    LobbyPreview {
+        lobby_code: String,
+        players: Vec<LobbyPlayerInfo>,
+        max_players: usize,
+        /// Whether there's a seat for the previewing user right now
+        has_room: bool,
+        /// A game is being played in the lobby
+        game_in_progress: bool,
+        scheduled_start: Option<ScheduledStart>,
+        dictionary_strictness: DictionaryStrictness,
+        dictionary_pack: Option<PackSelection>,
+        multiplier_layout: MultiplierLayout,
+        letter_mode: LetterMode,
+        board_theme: BoardTheme,
+        rules: GameRules,
+        language: String,
+    },
     /// Response to CreateCustomLobby - provides the lobby code to share
     LobbyCreated {
         lobby_code: String,
     },
+    /// Sent on connect when the user was in a lobby that still exists; the client
+    /// can rejoin with JoinChannelLobby/JoinCustomLobby
+    RejoinAvailable {
+        lobby_id: String,
+        lobby_type: LobbyType,
+        lobby_code: Option<String>,
+    },
+    /// The tables in a channel lobby, sent on ListTables and whenever tables change
+    TableList {
+        tables: Vec<TableInfo>,
+    },
+    /// The channel lobby is full; you're waiting for a seat at `position` (1 is next)
+    QueuePositionUpdate {
+        position: usize,
+        queue_length: usize,
+    },
+    /// Confirms a ReserveSeat request to the host
+    SeatReserved {
+        user_id: String,
+        expires_in_secs: u64,
+    },
+    /// Confirms an InvitePlayer request; `delivered` is false if the invite was
+    /// queued for the user's next connection
+    InviteSent {
+        user_id: String,
+        delivered: bool,
+    },
+    /// Someone invited you to their lobby; accept with JoinCustomLobby
+    InviteReceived {
+        from: InviteSender,
+        lobby_code: String,
+    },
+    /// Response to GetFriendsPresence, in the order asked for
+    FriendsPresence {
+        friends: Vec<FriendPresence>,
+    },
+    /// A friend from your last GetFriendsPresence came online, moved or went offline
+    FriendPresenceChanged {
+        friend: FriendPresence,
+    },
     /// Sent to all connected clients when the lobby player list changes
     LobbyPlayerList {
         players: Vec<LobbyPlayerInfo>,
         /// For custom lobbies, include the code so UI can display it
         lobby_code: Option<String>,
+        dictionary_strictness: DictionaryStrictness,
+        dictionary_pack: Option<PackSelection>,
+        multiplier_layout: MultiplierLayout,
+        letter_mode: LetterMode,
+        board_theme: BoardTheme,
+        rules: GameRules,
+        /// Language tag the lobby's rejections and announcements use (e.g. "es")
+        language: String,
+        /// The puzzle the next game is dealt, if the host chose one
+        puzzle: Option<LobbyPuzzle>,
+    },
+    /// The lobby's recent activity, oldest first; sent to a player as they join
+    ActivityFeed {
+        entries: Vec<ActivityEntry>,
+    },
+    /// Periodic live state of an idle lobby (no game running)
+    LobbyHeartbeat {
+        connected_count: usize,
+        /// User IDs of players who are ready
+        ready: Vec<String>,
+        /// User IDs of players who are typing
+        typing: Vec<String>,
+        /// Disconnected players and how long until their seat is released
+        reconnecting: Vec<LobbyCountdown>,
+        /// Reserved seats and how long until the reservation lapses
+        reserved_seats: Vec<LobbyCountdown>,
+        /// Time left before the host's scheduled game, if there is one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scheduled_start: Option<ScheduleCountdown>,
+    },
+    /// The host scheduled a game; it starts on its own at `starts_at` if enough
+    /// players are ready, replacing any earlier schedule
+    GameScheduled {
+        #[serde(flatten)]
+        schedule: ScheduledStart,
+    },
+    /// A scheduled game was called off, or couldn't start when its time came
+    ScheduledGameCancelled {
+        reason: ScheduleCancelReason,
+    },
+    /// The host changed which dictionary tiers are playable
+    DictionaryStrictnessChanged {
+        strictness: DictionaryStrictness,
+    },
+    /// The host changed the multiplier layout; applies from the next game
+    MultiplierLayoutChanged {
+        layout: MultiplierLayout,
+    },
+    /// The host changed where letters come from; applies from the next game
+    LetterModeChanged {
+        mode: LetterMode,
+    },
+    /// The host changed the board theme, with its tile styles to redraw the board
+    BoardThemeChanged {
+        theme: BoardTheme,
+        styles: BoardStyles,
+    },
+    /// The host changed the house rules; they apply from the next game
+    GameRulesChanged {
+        rules: GameRules,
+    },
+    /// Response to ListDictionaryPacks
+    DictionaryPackList {
+        packs: Vec<DictionaryPackInfo>,
+    },
+    /// The host picked (or cleared) the puzzle the lobby's games are dealt
+    PuzzleSelected {
+        puzzle: Option<LobbyPuzzle>,
+    },
+    /// The host picked (or cleared) the lobby's theme pack
+    DictionaryPackSelected {
+        pack: Option<PackSelection>,
+    },
+    /// The lobby's language changed, with a pack in another language or a new
+    /// guild default; word rejections now come back in it
+    LobbyLanguageChanged {
+        language: String,
+        /// Announcement to show, in the new language
+        message: String,
     },
     GameCreated {
         game_id: String,
@@ -95,6 +415,36 @@ pub enum ServerMessage {
         used_words: Vec<String>,
         timer_enabled: bool,
         time_remaining: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        coop: Option<CoopStatus>,
+        /// Blitz clocks for every player
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        clocks: Vec<PlayerClockInfo>,
+        board_checksum: String,
+        /// Tiles left to draw, only sent for letter bag games
+        #[serde(skip_serializing_if = "Option::is_none")]
+        letter_bag: Option<LetterBag>,
+        /// House rules the game is played by
+        #[serde(default)]
+        rules: GameRules,
+        /// Shapes and colours for multiplier and gem tiles, from the lobby's theme
+        board_styles: BoardStyles,
+        /// The most points the viewer can still score on the board, with the
+        /// `show_max_remaining` rule
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_remaining_score: Option<i32>,
+        /// When the duel ends, only sent for duels. `grid` and `used_words` are
+        /// then the receiving player's own.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duel_ends_at: Option<chrono::DateTime<chrono::Utc>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_attack: Option<TimeAttackStatus>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        puzzle_rush: Option<PuzzleRushStatus>,
+    },
+    /// The client's board was stale; replace everything with the enclosed GameState
+    ResyncState {
+        state: Box<ServerMessage>,
     },
     PlayerJoined {
         player: PlayerInfo,
@@ -114,6 +464,31 @@ pub enum ServerMessage {
         current_player_id: String,
         /// Total number of rounds in the game
         total_rounds: u8,
+        mode: GameMode,
+        /// Team progress, only sent for co-op games
+        #[serde(skip_serializing_if = "Option::is_none")]
+        coop: Option<CoopStatus>,
+        /// Starting clock per player, only sent for blitz games
+        #[serde(skip_serializing_if = "Option::is_none")]
+        clock_secs: Option<u64>,
+        board_checksum: String,
+        /// Tiles left to draw, only sent for letter bag games
+        #[serde(skip_serializing_if = "Option::is_none")]
+        letter_bag: Option<LetterBag>,
+        /// House rules the game is played by
+        #[serde(default)]
+        rules: GameRules,
+        /// Shapes and colours for multiplier and gem tiles, from the lobby's theme
+        board_styles: BoardStyles,
+        /// When the duel ends, only sent for duels
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duel_ends_at: Option<chrono::DateTime<chrono::Utc>>,
+        /// The board to clear and when time is up, only sent for time attacks
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_attack: Option<TimeAttackStatus>,
+        /// The target score and the first board's clock, only sent for puzzle rushes
+        #[serde(skip_serializing_if = "Option::is_none")]
+        puzzle_rush: Option<PuzzleRushStatus>,
     },
     /// Game-specific error (e.g., validation failures when starting a game)
     GameError {
@@ -121,18 +496,119 @@ pub enum ServerMessage {
         code: String,
         /// Human-readable error message
         message: String,
+        /// ID of the client message that caused the error, for correlating with server logs
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        /// For `invalid_field`, the message field that was rejected
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        field: Option<String>,
     },
     TurnUpdate {
         current_player: i64,
+        /// Seconds left on the current player's blitz clock
         time_remaining: Option<u32>,
     },
+    /// Sent to a player whose turn it is after they've gone quiet, on every
+    /// connection they have open, wherever they are (another lobby, an idle tab)
+    YourTurn {
+        game_id: String,
+        lobby_id: String,
+        /// When the turn is forfeited; only blitz turns have one
+        deadline: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    /// Sent each turn to lobby members watching a game they aren't playing in,
+    /// never to its players
+    Commentary {
+        current_player: Option<i64>,
+        /// The highest-scoring words on the board right now, best first
+        best_moves: Vec<BestWord>,
+        /// Each player's estimated chance of winning; empty in co-op games
+        win_probabilities: Vec<WinProbability>,
+    },
+    /// A player passed their turn, losing `penalty` points
+    TurnPassed {
+        user_id: i64,
+        penalty: i32,
+        /// The player's total score after the penalty
+        score: i32,
+        /// Passes in a row by this player since they last scored
+        consecutive_passes: u32,
+    },
+    /// Your block list, sent on connect (if not empty) and after every change
+    BlockListUpdated {
+        blocked: Vec<String>,
+    },
+    /// Players you've muted in the current lobby, sent after every change
+    MuteListUpdated {
+        muted: Vec<String>,
+    },
+    /// A player resigned and is out of the turn rotation
+    PlayerResigned {
+        user_id: i64,
+        username: String,
+    },
+    /// Too few unused words are left on the board, so the game ends early.
+    /// Followed by `game_over`.
+    BoardExhausted {
+        remaining_words: usize,
+    },
+    /// A blitz player's clock ran out; they forfeit the rest of their turns
+    PlayerOutOfTime {
+        user_id: i64,
+    },
     WordScored {
         word: String,
         score: i32,
         player: PlayerInfo,
         positions: Vec<Position>,
+        /// Whether the word is common or from the extended (obscure) list
+        tier: WordTier,
+        /// Where the word's points came from
+        breakdown: ScoreBreakdown,
+        /// The most points the player can still score on the board after the
+        /// word, with the `show_max_remaining` rule
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_remaining_score: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        coop: Option<CoopStatus>,
+        /// Checksum of the board after this word
+        board_checksum: String,
+        /// The submitter's `submission_id`, if they sent one
+        #[serde(skip_serializing_if = "Option::is_none")]
+        submission_id: Option<String>,
+        /// In letter bag games, the board after the word's tiles were replaced
+        /// from the bag, and what's left in it; in puzzle rushes, the next board
+        #[serde(skip_serializing_if = "Option::is_none")]
+        grid: Option<Vec<Vec<GridCell>>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        letter_bag: Option<LetterBag>,
+        /// The tiles used after this word, in time attacks
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_attack: Option<TimeAttackStatus>,
+        /// The streak and the next board's clock, in puzzle rushes
+        #[serde(skip_serializing_if = "Option::is_none")]
+        puzzle_rush: Option<PuzzleRushStatus>,
+    },
+    /// Sent to the whole lobby when a duel player scores; unlike `word_scored`,
+    /// which only the player gets, it doesn't give their word away
+    DuelScore {
+        user_id: i64,
+        /// The player's total score
+        score: i32,
+        words_played: usize,
+    },
+    /// A player took back their last word; a `game_state` with the restored board,
+    /// scores and turn follows
+    MoveUndone {
+        user_id: i64,
+        word: String,
+        /// Points the word had scored, now taken off
+        score: i32,
     },
+    /// A submitted word was rejected
     InvalidWord {
+        /// Error code (e.g., "word_already_used", "invalid_path")
+        code: String,
         reason: String,
     },
     RoundEnd {
@@ -140,11 +616,38 @@ pub enum ServerMessage {
         next_round: i32,
     },
     GameOver {
+        /// Always None in co-op, where the team wins or loses together, and in a draw
         winner: Option<i64>,
+        /// Every winner of a draw, and which tie-breaker (if any) decided the game
+        result: GameResult,
         final_scores: Vec<ScoreInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        coop: Option<CoopStatus>,
+        /// How many times each tile was used, indexed like the grid
+        #[serde(skip_serializing_if = "Option::is_none")]
+        heatmap: Option<Vec<Vec<u32>>>,
+        /// Move time stats per player
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pacing: Vec<PlayerPacing>,
+        reason: GameOverReason,
+        /// The tiles the team used, in time attacks
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_attack: Option<TimeAttackStatus>,
+        /// The streak the run ended on, in puzzle rushes
+        #[serde(skip_serializing_if = "Option::is_none")]
+        puzzle_rush: Option<PuzzleRushStatus>,
     },
-    Error {
-        message: String,
+    /// Sent to a player when a game event completes one of their quests
+    QuestCompleted {
+        quest: QuestStatus,
+        /// The player's totals after the reward
+        gems: i64,
+        xp: i64,
+    },
+    /// Sent to every lobby in a guild when a game there finishes
+    GuildLeaderboardUpdate {
+        guild_id: String,
+        standings: Vec<GuildStandingInfo>,
     },
     // Admin responses
     AdminGamesList {
@@ -153,6 +656,81 @@ pub enum ServerMessage {
     AdminGameDeleted {
         game_id: String,
     },
+    /// Response to an admin ListLobbies
+    AdminLobbyList {
+        lobbies: Vec<AdminLobbyInfo>,
+    },
+    /// Confirms an admin Observe (observing = true) or StopObserving (false)
+    AdminObserving {
+        lobby_id: String,
+        observing: bool,
+    },
+    /// A copy of a message broadcast to an observed lobby
+    AdminObserved {
+        lobby_id: String,
+        message: Box<ServerMessage>,
+    },
+    AdminTurnAdvanced {
+        lobby_id: String,
+        game_id: String,
+    },
+    /// Confirms an admin ResetBoard; the lobby was sent the new board
+    AdminBoardReset {
+        lobby_id: String,
+        game_id: String,
+        board_checksum: String,
+    },
+    /// Confirms an admin RebuildGame; the lobby was resynced to the stored state
+    AdminGameRebuilt {
+        lobby_id: String,
+        game_id: String,
+        /// Moves that were only in memory and are now lost
+        discarded_moves: usize,
+    },
+    /// Response to AdminDumpLobby with the raw server-side state
+    AdminLobbyDump {
+        lobby_id: String,
+        lobby: serde_json::Value,
+        /// The cached game session, if a game is in progress
+        game: Option<serde_json::Value>,
+    },
+}
+
+impl ServerMessage {
+    /// Periodic messages that the next one replaces, so a slow client's queue can
+    /// drop them to make room
+    pub fn is_droppable(&self) -> bool {
+        matches!(self, Self::LobbyHeartbeat { .. })
+    }
+
+    /// Build a game error, tagged with the ID of the message being handled
+    pub fn game_error(code: &str, message: impl Into<String>) -> Self {
+        Self::GameError {
+            code: code.to_string(),
+            message: message.into(),
+            request_id: current_request_id(),
+            field: None,
+        }
+    }
+
+    /// A GameError for a client-supplied field that failed validation
+    pub fn field_error(code: &str, message: impl Into<String>, field: &str) -> Self {
+        Self::GameError {
+            code: code.to_string(),
+            message: message.into(),
+            request_id: current_request_id(),
+            field: Some(field.to_string()),
+        }
+    }
+
+    /// Announce a lobby's new language, in that language
+    pub fn language_changed(language: String) -> Self {
+        let message = locale::translate(&language, locale::LANGUAGE_ANNOUNCEMENT)
+            .or_else(|| locale::translate(locale::DEFAULT_LANGUAGE, locale::LANGUAGE_ANNOUNCEMENT))
+            .unwrap_or_default()
+            .to_string();
+        Self::LobbyLanguageChanged { language, message }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -160,7 +738,76 @@ pub struct AdminGameInfo {
     pub game_id: String,
     pub state: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
-    pub players: Vec<String>,
+    pub current_round: i32,
+    pub max_rounds: i32,
+    /// Whose turn it is; null before the game starts or once it's over
+    pub current_player: Option<String>,
+    /// In turn order
+    pub players: Vec<AdminGamePlayerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminGamePlayerInfo {
+    pub user_id: String,
+    pub username: String,
+    pub score: i32,
+    /// In the lobby with an open connection
+    pub is_connected: bool,
+    /// Disconnected but still within the reconnect grace period
+    pub awaiting_reconnect: bool,
+}
+
+/// A row of the guild leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildStandingInfo {
+    /// 1-based; players with equal scores share a rank
+    pub rank: u32,
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub games_played: i64,
+    pub wins: i64,
+    pub total_score: i64,
+}
+
+impl GuildStandingInfo {
+    /// Rank standings already sorted by total score, using competition ranking (1, 1, 3)
+    pub fn rank_all(standings: Vec<GuildStanding>) -> Vec<Self> {
+        let mut ranked: Vec<Self> = Vec::with_capacity(standings.len());
+        for (idx, standing) in standings.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some(prev) if prev.total_score == standing.total_score => prev.rank,
+                _ => idx as u32 + 1,
+            };
+            ranked.push(Self {
+                rank,
+                user_id: standing.user_id.to_string(),
+                username: standing.username,
+                avatar_url: avatars::proxy_url(standing.user_id, standing.avatar_url.as_deref()),
+                games_played: standing.games_played,
+                wins: standing.wins,
+                total_score: standing.total_score,
+            });
+        }
+        ranked
+    }
+}
+
+/// Summary of a lobby for the admin console
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminLobbyInfo {
+    pub lobby_id: String,
+    pub lobby_type: LobbyType,
+    pub lobby_code: Option<String>,
+    pub guild_id: Option<String>,
+    pub host_id: Option<String>,
+    pub player_count: usize,
+    pub connected_count: usize,
+    pub observer_count: usize,
+    pub game_id: Option<String>,
+    pub current_round: Option<u8>,
+    pub total_rounds: Option<u8>,
+    pub current_player: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,6 +817,86 @@ pub struct PlayerInfo {
     pub avatar_url: Option<String>,
     pub score: i32,
     pub team: Option<i32>,
+    /// Equipped title, name color and badge
+    #[serde(default)]
+    pub profile: PlayerProfile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceStatus {
+    Offline,
+    /// Connected but not in a lobby
+    Online,
+    InLobby,
+    InGame,
+}
+
+/// What a friend is up to, with where to join them when they're in a lobby
+/// (JoinCustomLobby with `lobby_code`, or JoinChannelLobby with `channel_id`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriendPresence {
+    pub user_id: String,
+    pub status: PresenceStatus,
+    pub lobby_type: Option<LobbyType>,
+    pub lobby_code: Option<String>,
+    pub channel_id: Option<String>,
+    pub guild_id: Option<String>,
+}
+
+impl FriendPresence {
+    pub fn offline(user_id: i64) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            status: PresenceStatus::Offline,
+            lobby_type: None,
+            lobby_code: None,
+            channel_id: None,
+            guild_id: None,
+        }
+    }
+}
+
+/// Who an invite is from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteSender {
+    pub user_id: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+}
+
+/// A theme pack offered to hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryPackInfo {
+    pub name: String,
+    pub description: Option<String>,
+    /// Set for dictionaries in another language; selecting one switches the lobby's language
+    pub language: Option<String>,
+    pub word_count: usize,
+}
+
+/// Time left on a player's blitz clock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerClockInfo {
+    pub user_id: i64,
+    pub remaining_ms: u64,
+}
+
+/// One table (sub-lobby) of a channel lobby, for the table browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub lobby_id: String,
+    pub table_number: u32,
+    pub player_count: usize,
+    pub host_username: Option<String>,
+    pub in_game: bool,
+}
+
+/// Seconds left before something expires for a lobby user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyCountdown {
+    pub user_id: String,
+    pub secs_left: u64,
 }
 
 /// Simplified player info for lobby display
@@ -178,6 +905,17 @@ pub struct LobbyPlayerInfo {
     pub user_id: String,
     pub username: String,
     pub avatar_url: Option<String>,
+    pub is_host: bool,
+    pub is_connected: bool,
+    /// Disconnected but still within the reconnect grace period
+    pub awaiting_reconnect: bool,
+    pub ready: bool,
+    /// Watching from a spectator connection, without a seat
+    #[serde(default)]
+    pub spectator: bool,
+    /// Equipped title, name color and badge
+    #[serde(default)]
+    pub profile: PlayerProfile,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -186,3 +924,270 @@ pub struct ScoreInfo {
     pub username: String,
     pub score: i32,
 }
+
+/// Shared team progress in a co-op game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoopStatus {
+    pub team_score: i32,
+    pub target_score: i32,
+    pub gems: u32,
+    /// Set once the game is decided
+    pub outcome: Option<CoopOutcome>,
+}
+
+impl CoopStatus {
+    /// Snapshot the team's progress; None for competitive games
+    pub fn from_state(state: &GameState) -> Option<Self> {
+        let coop = state.coop.as_ref()?;
+        Some(Self {
+            team_score: state.team_score(),
+            target_score: coop.target_score,
+            gems: coop.gems,
+            outcome: state.coop_outcome(),
+        })
+    }
+}
+
+/// How much of the board a time attack team has used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeAttackStatus {
+    /// Used tiles, as bit `row * 5 + col`
+    pub covered: u32,
+    pub coverage_percent: u8,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TimeAttackStatus {
+    /// Snapshot the team's coverage; None outside time attacks
+    pub fn from_state(state: &GameState) -> Option<Self> {
+        let time_attack = state.time_attack.as_ref()?;
+        Some(Self {
+            covered: time_attack.covered,
+            coverage_percent: time_attack.coverage_percent(),
+            ends_at: time_attack.ends_at,
+        })
+    }
+}
+
+/// A puzzle rush's streak and current board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzleRushStatus {
+    /// Boards solved so far
+    pub streak: u32,
+    /// Points a word needs to solve the board
+    pub target_score: i32,
+    /// When the current board's time is up
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl PuzzleRushStatus {
+    /// Snapshot the run; None outside puzzle rushes
+    pub fn from_state(state: &GameState) -> Option<Self> {
+        let rush = state.puzzle_rush.as_ref()?;
+        Some(Self {
+            streak: rush.streak,
+            target_score: rush.target_score,
+            ends_at: rush.ends_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(user_id: i64, total_score: i64) -> GuildStanding {
+        GuildStanding {
+            user_id,
+            username: format!("Player{}", user_id),
+            avatar_url: None,
+            games_played: 1,
+            wins: 0,
+            total_score,
+        }
+    }
+
+    #[test]
+    fn test_guild_standings_share_rank_on_ties() {
+        let ranked = GuildStandingInfo::rank_all(vec![
+            standing(1, 300),
+            standing(2, 200),
+            standing(3, 200),
+            standing(4, 100),
+        ]);
+        let ranks: Vec<u32> = ranked.iter().map(|s| s.rank).collect();
+        assert_eq!(
+            ranks,
+            vec![1, 2, 2, 4],
+            "Tied players should share a rank and the next rank should be skipped"
+        );
+        assert_eq!(ranked[0].user_id, "1", "User IDs are sent as strings");
+    }
+
+    #[test]
+    fn test_guild_leaderboard_update_serialization() {
+        let msg = ServerMessage::GuildLeaderboardUpdate {
+            guild_id: "456".to_string(),
+            standings: GuildStandingInfo::rank_all(vec![standing(1, 50)]),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "guild_leaderboard_update");
+        assert_eq!(json["standings"][0]["rank"], 1);
+    }
+
+    #[test]
+    fn test_invite_messages() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type": "invite_player", "user_id": "42"}"#).unwrap();
+        assert!(matches!(msg, ClientMessage::InvitePlayer { user_id } if user_id == "42"));
+
+        let msg = ServerMessage::InviteReceived {
+            from: InviteSender {
+                user_id: "7".to_string(),
+                username: "alice".to_string(),
+                avatar_url: None,
+            },
+            lobby_code: "ABC234".to_string(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "invite_received");
+        assert_eq!(json["from"]["user_id"], "7", "User IDs are sent as strings");
+        assert_eq!(json["lobby_code"], "ABC234");
+    }
+
+    #[test]
+    fn test_lobby_preview_messages() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type": "preview_lobby", "lobby_code": "ABC234"}"#).unwrap();
+        assert!(
+            matches!(msg, ClientMessage::PreviewLobby { lobby_code } if lobby_code == "ABC234")
+        );
+
+        let msg = ServerMessage::LobbyPreview {
+            lobby_code: "ABC234".to_string(),
+            players: Vec::new(),
+            max_players: 6,
+            has_room: true,
+            game_in_progress: false,
+            scheduled_start: None,
+            dictionary_strictness: DictionaryStrictness::default(),
+            dictionary_pack: None,
+            multiplier_layout: MultiplierLayout::default(),
+            letter_mode: LetterMode::default(),
+            board_theme: BoardTheme::default(),
+            rules: GameRules::default(),
+            language: "en".to_string(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "lobby_preview");
+        assert_eq!(json["has_room"], true);
+        assert_eq!(json["game_in_progress"], false);
+    }
+
+    #[test]
+    fn test_puzzle_messages() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type": "select_puzzle", "puzzle_id": 12}"#).unwrap();
+        assert!(matches!(
+            msg,
+            ClientMessage::SelectPuzzle {
+                puzzle_id: Some(12)
+            }
+        ));
+
+        let msg = ServerMessage::PuzzleSelected {
+            puzzle: Some(LobbyPuzzle {
+                puzzle_id: 12,
+                title: "Vowel soup".to_string(),
+                grid: vec![vec![GridCell {
+                    letter: 'A',
+                    value: 1,
+                    multiplier: None,
+                }]],
+            }),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["puzzle"]["title"], "Vowel soup");
+        assert!(
+            json["puzzle"].get("grid").is_none(),
+            "The board stays hidden until the game starts"
+        );
+    }
+
+    #[test]
+    fn test_submit_word_submission_id() {
+        let msg: ClientMessage = serde_json::from_str(
+            r#"{"type": "submit_word", "word": "CAT", "positions": [], "submission_id": "s1"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            msg,
+            ClientMessage::SubmitWord { submission_id: Some(id), .. } if id == "s1"
+        ));
+
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type": "submit_word", "word": "CAT", "positions": []}"#)
+                .unwrap();
+        assert!(
+            matches!(
+                msg,
+                ClientMessage::SubmitWord {
+                    submission_id: None,
+                    ..
+                }
+            ),
+            "Older clients don't send a submission id"
+        );
+    }
+
+    #[test]
+    fn test_start_game_mode_is_optional() {
+        // Older clients send a bare start_game, which must still start a multiplayer game
+        let msg: ClientMessage = serde_json::from_str(r#"{"type": "start_game"}"#).unwrap();
+        assert!(matches!(
+            msg,
+            ClientMessage::StartGame {
+                mode: None,
+                target_score: None
+            }
+        ));
+
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type": "start_game", "mode": "Coop", "target_score": 150}"#)
+                .unwrap();
+        assert!(matches!(
+            msg,
+            ClientMessage::StartGame {
+                mode: Some(GameMode::Coop),
+                target_score: Some(150)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_coop_status_from_state() {
+        use crate::models::GamePlayer;
+        use uuid::Uuid;
+
+        let mut players = vec![
+            GamePlayer::new(Uuid::new_v4(), "Player1".to_string(), None, 0),
+            GamePlayer::new(Uuid::new_v4(), "Player2".to_string(), None, 1),
+        ];
+        players[0].score = 40;
+        players[1].score = 15;
+
+        let competitive = GameState::new(Uuid::new_v4(), vec![], players.clone(), 3);
+        assert!(
+            CoopStatus::from_state(&competitive).is_none(),
+            "Competitive games carry no co-op status"
+        );
+
+        let mut coop = GameState::new_coop(Uuid::new_v4(), vec![], players, 3, 100);
+        coop.coop.as_mut().unwrap().gems = 2;
+        let status = CoopStatus::from_state(&coop).unwrap();
+        assert_eq!(status.team_score, 55);
+        assert_eq!(status.target_score, 100);
+        assert_eq!(status.gems, 2);
+        assert!(status.outcome.is_none());
+    }
+}
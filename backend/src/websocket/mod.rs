@@ -1,4 +1,18 @@
+pub mod activity;
+pub mod admin;
+pub mod game_actor;
 pub mod handler;
+pub mod limits;
 pub mod messages;
+pub mod outbound;
+pub mod permissions;
+pub mod presence;
+pub mod reminders;
+pub mod schedule;
+pub mod trace;
+pub mod validate;
 
-pub use handler::{broadcast_lobby_player_list, handle_websocket};
+pub use handler::{
+    broadcast_lobby_heartbeat, broadcast_lobby_player_list, broadcast_to_lobby, handle_websocket,
+    promote_queued_players,
+};
@@ -0,0 +1,382 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::extract::ws::Utf8Bytes;
+use tokio::sync::{mpsc::error::SendError, Notify};
+
+use super::messages::ServerMessage;
+
+/// A message queued for a player's connection
+#[derive(Debug, Clone)]
+pub enum OutboundMessage {
+    /// Serialized by the connection's send task
+    Message(Box<ServerMessage>),
+    /// Serialized once when broadcast; clones share the same buffer, so every
+    /// recipient sends the same bytes without re-encoding
+    Serialized(Utf8Bytes),
+}
+
+impl OutboundMessage {
+    /// Serialize a broadcast once for all of its recipients
+    pub fn shared(message: &ServerMessage) -> Self {
+        match serde_json::to_string(message) {
+            Ok(json) => Self::Serialized(json.into()),
+            // Leave it to the send tasks, which log the failure
+            Err(_) => Self::Message(Box::new(message.clone())),
+        }
+    }
+
+    /// The JSON text frame to send
+    pub fn into_text(self) -> Result<Utf8Bytes, serde_json::Error> {
+        match self {
+            Self::Message(message) => serde_json::to_string(&message).map(Utf8Bytes::from),
+            Self::Serialized(json) => Ok(json),
+        }
+    }
+
+    /// See `ServerMessage::is_droppable`; broadcasts are never dropped
+    fn is_droppable(&self) -> bool {
+        match self {
+            Self::Message(message) => message.is_droppable(),
+            Self::Serialized(_) => false,
+        }
+    }
+}
+
+impl From<ServerMessage> for OutboundMessage {
+    fn from(message: ServerMessage) -> Self {
+        Self::Message(Box::new(message))
+    }
+}
+
+/// What a connection's send task gets from its queue
+#[derive(Debug)]
+pub enum Delivery {
+    Message(OutboundMessage),
+    /// The client fell behind and messages were thrown away; send it a full
+    /// snapshot of its lobby and game before anything else
+    Resync {
+        dropped: usize,
+    },
+}
+
+/// A player connection's outbound queue
+///
+/// The queue is bounded and never makes the sender wait, so one slow client
+/// can't hold up a broadcast to the rest of its lobby. When it's full:
+/// - the oldest droppable message (a heartbeat) makes room, if there is one
+/// - otherwise everything queued is discarded, along with anything sent until the
+///   send task picks up the `Resync` that replaces it
+/// - if it has stayed full, or waiting on that `Resync`, for the stall timeout,
+///   the connection is closed
+#[derive(Debug)]
+struct Queue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+    stall_timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: VecDeque<OutboundMessage>,
+    /// Messages thrown away since the client was last resynced
+    dropped: usize,
+    needs_resync: bool,
+    /// Since when the queue has been full without draining
+    full_since: Option<Instant>,
+    closed: bool,
+}
+
+impl Queue {
+    fn push(
+        &self,
+        message: OutboundMessage,
+        now: Instant,
+    ) -> Result<(), SendError<OutboundMessage>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.closed {
+            return Err(SendError(message));
+        }
+        // A pending resync counts as full: the send task hasn't caught up either
+        if state.needs_resync || state.messages.len() >= self.capacity {
+            let full_since = *state.full_since.get_or_insert(now);
+            if now.duration_since(full_since) >= self.stall_timeout {
+                state.closed = true;
+                state.messages.clear();
+                drop(state);
+                self.notify.notify_one();
+                return Err(SendError(message));
+            }
+        }
+        if state.needs_resync {
+            // The pending resync snapshot will include this
+            state.dropped += 1;
+            return Ok(());
+        }
+
+        if state.messages.len() >= self.capacity {
+            if let Some(index) = state
+                .messages
+                .iter()
+                .position(OutboundMessage::is_droppable)
+            {
+                state.messages.remove(index);
+            } else if message.is_droppable() {
+                return Ok(());
+            } else {
+                state.dropped += state.messages.len() + 1;
+                state.messages.clear();
+                state.needs_resync = true;
+                drop(state);
+                self.notify.notify_one();
+                return Ok(());
+            }
+        }
+
+        state.messages.push_back(message);
+        drop(state);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// The next delivery, or Err(()) once the queue is closed
+    fn pop(&self) -> Result<Option<Delivery>, ()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.closed {
+            return Err(());
+        }
+        if state.needs_resync {
+            state.needs_resync = false;
+            state.full_since = None;
+            let dropped = std::mem::take(&mut state.dropped);
+            return Ok(Some(Delivery::Resync { dropped }));
+        }
+        let message = state.messages.pop_front();
+        if state.messages.len() < self.capacity {
+            state.full_since = None;
+        }
+        Ok(message.map(Delivery::Message))
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        self.notify.notify_one();
+    }
+}
+
+/// The sending half of a player connection's outbound queue
+#[derive(Debug, Clone)]
+pub struct ClientSender(Arc<Queue>);
+
+/// The receiving half, owned by the connection's send task
+#[derive(Debug)]
+pub struct ClientReceiver(Arc<Queue>);
+
+impl ClientSender {
+    /// A queue holding up to `capacity` messages; a client whose queue stays full
+    /// for `stall_timeout` is disconnected
+    pub fn channel(capacity: usize, stall_timeout: Duration) -> (Self, ClientReceiver) {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            capacity: capacity.max(1),
+            stall_timeout,
+        });
+        (Self(queue.clone()), ClientReceiver(queue))
+    }
+
+    /// Queue a message; fails only once the connection is closed
+    ///
+    /// Never waits for room (see `Queue`). Async for the convenience of callers
+    /// that used to await a channel send.
+    pub async fn send(
+        &self,
+        message: impl Into<OutboundMessage>,
+    ) -> Result<(), SendError<OutboundMessage>> {
+        self.try_send(message)
+    }
+
+    /// Queue a message from synchronous code; same policy as `send`
+    pub fn try_send(
+        &self,
+        message: impl Into<OutboundMessage>,
+    ) -> Result<(), SendError<OutboundMessage>> {
+        self.0.push(message.into(), Instant::now())
+    }
+
+    /// Free slots in the queue
+    pub fn capacity(&self) -> usize {
+        let state = self.0.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.0.capacity.saturating_sub(state.messages.len())
+    }
+}
+
+impl ClientReceiver {
+    /// Wait for the next delivery; None once the queue has been closed because the
+    /// client couldn't keep up
+    pub async fn recv(&mut self) -> Option<Delivery> {
+        loop {
+            match self.0.pop() {
+                Ok(Some(delivery)) => return Some(delivery),
+                Ok(None) => self.0.notify.notified().await,
+                Err(()) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for ClientReceiver {
+    /// With the send task gone, further sends fail like a closed channel's
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn word(score: i32) -> OutboundMessage {
+        OutboundMessage::from(ServerMessage::PlayerOutOfTime {
+            user_id: score as i64,
+        })
+    }
+
+    fn heartbeat() -> OutboundMessage {
+        OutboundMessage::from(ServerMessage::LobbyHeartbeat {
+            connected_count: 1,
+            ready: Vec::new(),
+            typing: Vec::new(),
+            reconnecting: Vec::new(),
+            reserved_seats: Vec::new(),
+            scheduled_start: None,
+        })
+    }
+
+    fn user_id(delivery: Option<Delivery>) -> i64 {
+        match delivery {
+            Some(Delivery::Message(OutboundMessage::Message(message))) => match *message {
+                ServerMessage::PlayerOutOfTime { user_id } => user_id,
+                other => panic!("Unexpected message {:?}", other),
+            },
+            other => panic!("Expected a message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shared_payload_matches_per_socket_serialization() {
+        let message = ServerMessage::PlayerOutOfTime { user_id: 42 };
+        let shared = OutboundMessage::shared(&message).into_text().unwrap();
+        let direct = OutboundMessage::from(message).into_text().unwrap();
+        assert_eq!(shared.as_str(), direct.as_str());
+    }
+
+    #[test]
+    fn test_shared_payload_clones_share_the_buffer() {
+        let shared = OutboundMessage::shared(&ServerMessage::PlayerOutOfTime { user_id: 42 });
+        let first = shared.clone().into_text().unwrap();
+        let second = shared.into_text().unwrap();
+        assert_eq!(
+            first.as_str().as_ptr(),
+            second.as_str().as_ptr(),
+            "Each recipient gets the same bytes, not a copy"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_oldest_heartbeat_first() {
+        let (tx, mut rx) = ClientSender::channel(2, TIMEOUT);
+        tx.try_send(heartbeat()).unwrap();
+        tx.try_send(word(1)).unwrap();
+        tx.try_send(word(2)).unwrap();
+
+        assert_eq!(user_id(rx.recv().await), 1, "The heartbeat made room");
+        assert_eq!(user_id(rx.recv().await), 2);
+
+        tx.try_send(word(3)).unwrap();
+        tx.try_send(word(4)).unwrap();
+        tx.try_send(heartbeat()).unwrap();
+        assert_eq!(
+            user_id(rx.recv().await),
+            3,
+            "A heartbeat arriving at a full queue is the one dropped"
+        );
+        assert_eq!(user_id(rx.recv().await), 4);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_forces_resync() {
+        let (tx, mut rx) = ClientSender::channel(2, TIMEOUT);
+        for score in 1..=4 {
+            tx.try_send(word(score)).unwrap();
+        }
+
+        assert!(
+            matches!(rx.recv().await, Some(Delivery::Resync { dropped: 4 })),
+            "Two queued messages, the overflowing one and one sent while the resync was pending"
+        );
+        tx.try_send(word(6)).unwrap();
+        assert_eq!(
+            user_id(rx.recv().await),
+            6,
+            "Messages after the resync are delivered again"
+        );
+    }
+
+    #[test]
+    fn test_stalled_client_is_disconnected() {
+        let (tx, rx) = ClientSender::channel(1, TIMEOUT);
+        let start = Instant::now();
+        tx.0.push(heartbeat(), start).unwrap();
+        tx.0.push(heartbeat(), start).unwrap();
+        assert!(
+            tx.0.push(heartbeat(), start + TIMEOUT / 2).is_ok(),
+            "Still within the timeout"
+        );
+        assert!(tx.0.push(heartbeat(), start + TIMEOUT).is_err());
+        assert!(tx.try_send(word(1)).is_err(), "The connection stays closed");
+        assert!(matches!(rx.0.pop(), Err(())));
+
+        // Likewise when the queue overflowed into a resync it never picks up
+        let (tx, rx) = ClientSender::channel(1, TIMEOUT);
+        tx.0.push(word(1), start).unwrap();
+        tx.0.push(word(2), start).unwrap();
+        assert!(
+            tx.0.push(word(3), start + TIMEOUT / 2).is_ok(),
+            "Overflowing forced a resync, still within the timeout"
+        );
+        assert!(
+            tx.0.push(word(4), start + TIMEOUT).is_err(),
+            "A client that never picks up its resync is stalled"
+        );
+        assert!(matches!(rx.0.pop(), Err(())));
+    }
+
+    #[test]
+    fn test_draining_resets_the_stall_clock() {
+        let (tx, rx) = ClientSender::channel(1, TIMEOUT);
+        let start = Instant::now();
+        tx.0.push(heartbeat(), start).unwrap();
+        tx.0.push(heartbeat(), start).unwrap();
+        assert!(matches!(rx.0.pop(), Ok(Some(_))));
+
+        tx.0.push(heartbeat(), start + TIMEOUT).unwrap();
+        assert!(
+            tx.0.push(heartbeat(), start + TIMEOUT).is_ok(),
+            "A client that's draining its queue isn't stalled"
+        );
+    }
+
+    #[test]
+    fn test_sends_fail_once_receiver_is_gone() {
+        let (tx, rx) = ClientSender::channel(4, TIMEOUT);
+        drop(rx);
+        assert!(tx.try_send(word(1)).is_err());
+    }
+}
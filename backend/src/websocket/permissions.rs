@@ -0,0 +1,230 @@
+use crate::{error::ApiError, models::GameMode, Lobby};
+
+use super::messages::ClientMessage;
+
+/// What a player connection may do, settled when the socket is upgraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionRole {
+    /// Plays games; host actions need them to be the lobby's host
+    Player,
+    /// Connected with `?spectate=1`: joins lobbies to watch, without taking a
+    /// seat, playing or becoming host
+    Spectator,
+    /// A server admin (`ADMIN_USER_IDS`): plays, can do anything a host can in
+    /// any lobby, and has the admin tools
+    Admin,
+}
+
+impl ConnectionRole {
+    /// The role for a new connection; an admin who asks to spectate is a spectator
+    pub fn resolve(spectate: bool, is_admin: bool) -> Self {
+        match (spectate, is_admin) {
+            (true, _) => Self::Spectator,
+            (false, true) => Self::Admin,
+            (false, false) => Self::Player,
+        }
+    }
+
+    pub fn is_spectator(self) -> bool {
+        self == Self::Spectator
+    }
+}
+
+/// What a client message needs from the connection that sent it
+///
+/// The action names finish the error messages ("Only the lobby host can …",
+/// "Spectators can't …").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Any connection: moving between lobbies, lists, presence and blocking
+    Anyone,
+    /// Taking part in games, which spectators don't
+    Play(&'static str),
+    /// The lobby's host, or a server admin
+    Host(&'static str),
+    /// Only a server admin (`ADMIN_USER_IDS`), whatever lobby they're in
+    Admin,
+}
+
+impl Permission {
+    /// The permission a message needs; every message is listed, so a new one
+    /// can't be added without deciding who may send it
+    pub fn required(msg: &ClientMessage) -> Self {
+        match msg {
+            ClientMessage::JoinChannelLobby { .. }
+            | ClientMessage::JoinCustomLobby { .. }
+            | ClientMessage::PreviewLobby { .. }
+            | ClientMessage::LeaveLobby
+            | ClientMessage::ListTables
+            | ClientMessage::JoinTable { .. }
+            | ClientMessage::SetTyping { .. }
+            | ClientMessage::GetFriendsPresence { .. }
+            | ClientMessage::BlockUser { .. }
+            | ClientMessage::UnblockUser { .. }
+            | ClientMessage::MutePlayer { .. }
+            | ClientMessage::UnmutePlayer { .. }
+            | ClientMessage::ListDictionaryPacks => Self::Anyone,
+
+            ClientMessage::CreateCustomLobby => Self::Play("create lobbies"),
+            ClientMessage::CreateTable => Self::Play("open tables"),
+            ClientMessage::SetReady { .. } => Self::Play("ready up"),
+            ClientMessage::InvitePlayer { .. } => Self::Play("invite players"),
+            ClientMessage::CreateGame {
+                mode: GameMode::HotSeat,
+                ..
+            } => Self::Host("start the game"),
+            ClientMessage::CreateGame { .. } => Self::Play("create games"),
+            ClientMessage::JoinGame { .. } => Self::Play("join games"),
+            ClientMessage::LeaveGame | ClientMessage::Resign => Self::Play("leave games"),
+            ClientMessage::SubmitWord { .. } => Self::Play("submit words"),
+            ClientMessage::PassTurn => Self::Play("pass turns"),
+            ClientMessage::UndoMove => Self::Play("undo moves"),
+
+            ClientMessage::StartGame { .. } => Self::Host("start the game"),
+            ClientMessage::ScheduleGame { .. } => Self::Host("schedule a game"),
+            ClientMessage::CancelScheduledGame => Self::Host("cancel the scheduled game"),
+            ClientMessage::ReserveSeat { .. } => Self::Host("reserve seats"),
+            ClientMessage::EnableTimer => Self::Host("enable the timer"),
+            ClientMessage::SetDictionaryStrictness { .. } => Self::Host("change the dictionary"),
+            ClientMessage::SetMultiplierLayout { .. } => Self::Host("change the multiplier layout"),
+            ClientMessage::SetLetterMode { .. } => Self::Host("change the letter mode"),
+            ClientMessage::SetBoardTheme { .. } => Self::Host("change the board theme"),
+            ClientMessage::SetGameRules { .. } => Self::Host("change the house rules"),
+            ClientMessage::SelectDictionaryPack { .. } => Self::Host("choose a dictionary pack"),
+            ClientMessage::SelectPuzzle { .. } => Self::Host("choose a puzzle"),
+            ClientMessage::AdminGetGames
+            | ClientMessage::AdminDumpLobby { .. }
+            | ClientMessage::AdminDeleteGame { .. } => Self::Admin,
+        }
+    }
+}
+
+/// Whether a connection may send a message that needs `permission`
+///
+/// `lobby` is the lobby a host permission applies to; it's only looked at for
+/// players, as admins may act in any lobby and spectators in none.
+pub fn authorize(
+    role: ConnectionRole,
+    permission: Permission,
+    user_id: i64,
+    lobby: Option<&Lobby>,
+) -> Result<(), ApiError> {
+    match (permission, role) {
+        (Permission::Anyone, _) | (_, ConnectionRole::Admin) => Ok(()),
+        (Permission::Play(action), ConnectionRole::Spectator) => Err(ApiError::Spectating(action)),
+        (Permission::Play(_), ConnectionRole::Player) => Ok(()),
+        (Permission::Admin, _) => Err(ApiError::NotAdmin),
+        (Permission::Host(action), ConnectionRole::Spectator) => Err(ApiError::NotHost(action)),
+        (Permission::Host(action), ConnectionRole::Player) => {
+            let lobby = lobby.ok_or(ApiError::LobbyNotFound)?;
+            if !lobby.is_host(user_id) {
+                return Err(ApiError::NotHost(action));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ClientMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn hosted_lobby(host_id: i64) -> Lobby {
+        let mut lobby = Lobby::new_channel("123".to_string(), None);
+        lobby.host_id = Some(host_id);
+        lobby
+    }
+
+    #[test]
+    fn test_role_resolution() {
+        assert_eq!(
+            ConnectionRole::resolve(false, false),
+            ConnectionRole::Player
+        );
+        assert_eq!(ConnectionRole::resolve(false, true), ConnectionRole::Admin);
+        assert_eq!(
+            ConnectionRole::resolve(true, true),
+            ConnectionRole::Spectator,
+            "Admins can watch without playing too"
+        );
+    }
+
+    #[test]
+    fn test_host_actions() {
+        let lobby = hosted_lobby(1);
+        let start = Permission::required(&parse(r#"{"type":"start_game"}"#));
+        assert_eq!(start, Permission::Host("start the game"));
+
+        assert!(authorize(ConnectionRole::Player, start, 1, Some(&lobby)).is_ok());
+        assert!(matches!(
+            authorize(ConnectionRole::Player, start, 2, Some(&lobby)),
+            Err(ApiError::NotHost("start the game"))
+        ));
+        assert!(matches!(
+            authorize(ConnectionRole::Player, start, 1, None),
+            Err(ApiError::LobbyNotFound)
+        ));
+        assert!(
+            authorize(ConnectionRole::Admin, start, 2, Some(&lobby)).is_ok(),
+            "Admins act as host anywhere"
+        );
+        assert!(authorize(ConnectionRole::Spectator, start, 1, Some(&lobby)).is_err());
+    }
+
+    #[test]
+    fn test_spectators_only_watch() {
+        let submit = Permission::required(&parse(
+            r#"{"type":"submit_word","word":"CAT","positions":[]}"#,
+        ));
+        assert!(matches!(
+            authorize(ConnectionRole::Spectator, submit, 1, None),
+            Err(ApiError::Spectating("submit words"))
+        ));
+        assert!(authorize(ConnectionRole::Player, submit, 1, None).is_ok());
+
+        let join =
+            Permission::required(&parse(r#"{"type":"join_channel_lobby","channel_id":"1"}"#));
+        assert!(authorize(ConnectionRole::Spectator, join, 1, None).is_ok());
+    }
+
+    #[test]
+    fn test_hot_seat_games_start_straight_away() {
+        assert_eq!(
+            Permission::required(&parse(
+                r#"{"type":"create_game","mode":"HotSeat","local_players":["a","b"]}"#
+            )),
+            Permission::Host("start the game")
+        );
+        assert_eq!(
+            Permission::required(&parse(r#"{"type":"create_game","mode":"Multiplayer"}"#)),
+            Permission::Play("create games")
+        );
+    }
+
+    #[test]
+    fn test_admin_tools_need_an_admin() {
+        let lobby = hosted_lobby(1);
+        for json in [
+            r#"{"type":"admin_get_games"}"#,
+            r#"{"type":"admin_dump_lobby","lobby_id":"channel:123"}"#,
+            r#"{"type":"admin_delete_game","game_id":"00000000-0000-0000-0000-000000000000"}"#,
+        ] {
+            let permission = Permission::required(&parse(json));
+            assert_eq!(permission, Permission::Admin);
+            assert!(
+                matches!(
+                    authorize(ConnectionRole::Player, permission, 1, Some(&lobby)),
+                    Err(ApiError::NotAdmin)
+                ),
+                "Hosts don't get the admin tools: {}",
+                json
+            );
+            assert!(authorize(ConnectionRole::Spectator, permission, 1, None).is_err());
+            assert!(authorize(ConnectionRole::Admin, permission, 2, None).is_ok());
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use dashmap::DashMap;
+
+use crate::{
+    websocket::messages::{FriendPresence, PresenceStatus, ServerMessage},
+    AppState, Lobby,
+};
+
+/// Most users one player can watch at once (roughly a large Discord friend list)
+pub const MAX_WATCHED_USERS: usize = 200;
+
+/// Which lobby each connected user is in, and who is watching whom for
+/// presence changes
+///
+/// Status (online, in a lobby, in a game) is worked out from this, the open
+/// connections and the lobbies themselves when it's asked for, so only lobby
+/// moves need recording.
+#[derive(Default)]
+pub struct PresenceIndex {
+    lobbies: DashMap<i64, String>,
+    /// Watched user -> users watching them
+    watchers: DashMap<i64, HashSet<i64>>,
+    /// Watcher -> users they watch
+    watching: DashMap<i64, HashSet<i64>>,
+    /// Presence last sent to a user's watchers, to skip repeats
+    last_sent: DashMap<i64, FriendPresence>,
+}
+
+impl PresenceIndex {
+    pub fn set_lobby(&self, user_id: i64, lobby_id: &str) {
+        self.lobbies.insert(user_id, lobby_id.to_string());
+    }
+
+    /// Forget a user's lobby, unless they've already moved on to another one
+    pub fn leave_lobby(&self, user_id: i64, lobby_id: &str) {
+        self.lobbies
+            .remove_if(&user_id, |_, current| current == lobby_id);
+    }
+
+    pub fn lobby_of(&self, user_id: i64) -> Option<String> {
+        self.lobbies.get(&user_id).map(|lobby_id| lobby_id.clone())
+    }
+
+    /// Replace the set of users a watcher gets presence changes for
+    pub fn watch(&self, watcher: i64, user_ids: impl IntoIterator<Item = i64>) {
+        self.unwatch_all(watcher);
+        let watched: HashSet<i64> = user_ids
+            .into_iter()
+            .filter(|&user_id| user_id != watcher)
+            .take(MAX_WATCHED_USERS)
+            .collect();
+        if watched.is_empty() {
+            return;
+        }
+        for &user_id in &watched {
+            self.watchers.entry(user_id).or_default().insert(watcher);
+        }
+        self.watching.insert(watcher, watched);
+    }
+
+    /// Stop sending a watcher presence changes (they've disconnected)
+    pub fn unwatch_all(&self, watcher: i64) {
+        let Some((_, watched)) = self.watching.remove(&watcher) else {
+            return;
+        };
+        for user_id in watched {
+            let unwatched = self
+                .watchers
+                .remove_if_mut(&user_id, |_, watchers| {
+                    watchers.remove(&watcher);
+                    watchers.is_empty()
+                })
+                .is_some();
+            if unwatched {
+                self.last_sent.remove(&user_id);
+            }
+        }
+    }
+
+    pub fn watchers_of(&self, user_id: i64) -> Vec<i64> {
+        self.watchers
+            .get(&user_id)
+            .map(|watchers| watchers.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Remember the presence sent for a user; false if it's what was sent last
+    fn record(&self, presence: &FriendPresence, user_id: i64) -> bool {
+        if self.last_sent.get(&user_id).as_deref() == Some(presence) {
+            return false;
+        }
+        self.last_sent.insert(user_id, presence.clone());
+        true
+    }
+}
+
+/// A user's presence as `viewer` may see it; users who blocked the viewer always
+/// appear offline
+pub fn presence_of(state: &AppState, user_id: i64, viewer: i64) -> FriendPresence {
+    if state.block_lists.is_blocked(user_id, viewer) {
+        return FriendPresence::offline(user_id);
+    }
+    current_presence(state, user_id)
+}
+
+fn current_presence(state: &AppState, user_id: i64) -> FriendPresence {
+    if !state.user_connections.is_connected(user_id) {
+        return FriendPresence::offline(user_id);
+    }
+    let lobby = state
+        .presence
+        .lobby_of(user_id)
+        .and_then(|lobby_id| state.lobbies.get(&lobby_id));
+    match lobby {
+        Some(lobby) => lobby_presence(user_id, &lobby),
+        None => FriendPresence {
+            status: PresenceStatus::Online,
+            ..FriendPresence::offline(user_id)
+        },
+    }
+}
+
+/// Where to find a user who's in a lobby, so a friend can join them
+fn lobby_presence(user_id: i64, lobby: &Lobby) -> FriendPresence {
+    FriendPresence {
+        user_id: user_id.to_string(),
+        status: if lobby.active_game_id.is_some() {
+            PresenceStatus::InGame
+        } else {
+            PresenceStatus::InLobby
+        },
+        lobby_type: Some(lobby.lobby_type.clone()),
+        lobby_code: lobby.lobby_code.clone(),
+        channel_id: lobby.channel_id.clone(),
+        guild_id: lobby.guild_id.clone(),
+    }
+}
+
+/// Tell a user's watchers if their presence changed
+pub fn notify_presence(state: &AppState, user_id: i64) {
+    let watchers = state.presence.watchers_of(user_id);
+    if watchers.is_empty() {
+        return;
+    }
+    let presence = current_presence(state, user_id);
+    if !state.presence.record(&presence, user_id) {
+        return;
+    }
+    let message = ServerMessage::FriendPresenceChanged { friend: presence };
+    for watcher in watchers {
+        if !state.block_lists.is_blocked(user_id, watcher) {
+            state.user_connections.send(watcher, &message);
+        }
+    }
+}
+
+/// Tell the watchers of everyone in a lobby (e.g., a game started or ended)
+pub fn notify_lobby_presence(state: &AppState, lobby_id: &str) {
+    let user_ids: Vec<i64> = match state.lobbies.get(lobby_id) {
+        Some(lobby) => lobby.players.iter().map(|p| p.user_id).collect(),
+        None => return,
+    };
+    for user_id in user_ids {
+        notify_presence(state, user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lobby_moves() {
+        let index = PresenceIndex::default();
+        index.set_lobby(1, "custom:ABC234");
+        index.set_lobby(1, "channel:42");
+        index.leave_lobby(1, "custom:ABC234");
+        assert_eq!(
+            index.lobby_of(1).as_deref(),
+            Some("channel:42"),
+            "Leaving a lobby the user already moved out of keeps the new one"
+        );
+        index.leave_lobby(1, "channel:42");
+        assert_eq!(index.lobby_of(1), None);
+    }
+
+    #[test]
+    fn test_watching_replaces_and_cleans_up() {
+        let index = PresenceIndex::default();
+        index.watch(1, [2, 3, 1]);
+        index.watch(4, [2]);
+        assert_eq!(
+            index.watchers_of(1),
+            Vec::<i64>::new(),
+            "Users can't watch themselves"
+        );
+
+        let mut watchers = index.watchers_of(2);
+        watchers.sort_unstable();
+        assert_eq!(watchers, vec![1, 4]);
+
+        index.watch(1, [5]);
+        assert!(
+            index.watchers_of(3).is_empty(),
+            "A new list replaces the old one"
+        );
+        assert_eq!(index.watchers_of(5), vec![1]);
+
+        index.unwatch_all(1);
+        index.unwatch_all(4);
+        assert!(index.watchers.is_empty() && index.watching.is_empty());
+    }
+
+    #[test]
+    fn test_watch_list_is_capped() {
+        let index = PresenceIndex::default();
+        index.watch(0, 1..=(MAX_WATCHED_USERS as i64 + 50));
+        assert_eq!(index.watching.get(&0).unwrap().len(), MAX_WATCHED_USERS);
+    }
+
+    #[test]
+    fn test_repeated_presence_is_not_resent() {
+        let index = PresenceIndex::default();
+        let online = FriendPresence {
+            status: PresenceStatus::Online,
+            ..FriendPresence::offline(2)
+        };
+        assert!(index.record(&online, 2));
+        assert!(!index.record(&online, 2), "Nothing changed");
+        assert!(index.record(&FriendPresence::offline(2), 2));
+
+        index.watch(1, [2]);
+        index.unwatch_all(1);
+        assert!(
+            index.last_sent.is_empty(),
+            "Users nobody watches don't keep an entry"
+        );
+    }
+
+    #[test]
+    fn test_lobby_presence() {
+        let mut lobby = Lobby::new_custom("ABC234".to_string());
+        let presence = lobby_presence(7, &lobby);
+        assert_eq!(presence.status, PresenceStatus::InLobby);
+        assert_eq!(presence.lobby_code.as_deref(), Some("ABC234"));
+
+        lobby.active_game_id = Some(uuid::Uuid::new_v4());
+        assert_eq!(lobby_presence(7, &lobby).status, PresenceStatus::InGame);
+
+        let table = Lobby::new_table("42".to_string(), Some("9".to_string()), 2);
+        let presence = lobby_presence(7, &table);
+        assert_eq!(
+            (presence.channel_id.as_deref(), presence.guild_id.as_deref()),
+            (Some("42"), Some("9")),
+            "Channel lobbies are joined by channel"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use serde_json::json;
+
+use super::messages::ServerMessage;
+use crate::{
+    models::{GameMode, GameStatus},
+    AppState,
+};
+
+/// How often waiting turns are checked for idle players
+pub const TURN_REMINDER_INTERVAL: Duration = Duration::from_secs(5);
+const DISCORD_API: &str = "https://discord.com/api/v10";
+
+/// A nudge owed to the player whose turn it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reminder {
+    /// `YourTurn` on every connection they have open
+    YourTurn,
+    /// A Discord DM from the bot
+    DirectMessage,
+}
+
+/// Reminders already sent for a game's current turn
+///
+/// Each is sent at most once per turn, however long the player stays away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TurnReminders {
+    /// `turns_taken` when the turn started
+    turn: u32,
+    your_turn: bool,
+    direct_message: bool,
+}
+
+impl TurnReminders {
+    /// The next reminder due on turn `turn` for a player who hasn't sent anything
+    /// for `idle`, marked as sent
+    ///
+    /// `YourTurn` comes after `your_turn_after` of silence; the DM, if enabled,
+    /// only after `dm_after`, and never before the `YourTurn`.
+    pub fn next_due(
+        &mut self,
+        turn: u32,
+        idle: Duration,
+        your_turn_after: Duration,
+        dm_after: Option<Duration>,
+    ) -> Option<Reminder> {
+        if turn != self.turn {
+            *self = Self {
+                turn,
+                ..Default::default()
+            };
+        }
+        if !self.your_turn {
+            if idle < your_turn_after {
+                return None;
+            }
+            self.your_turn = true;
+            return Some(Reminder::YourTurn);
+        }
+        if !self.direct_message && dm_after.is_some_and(|after| idle >= after) {
+            self.direct_message = true;
+            return Some(Reminder::DirectMessage);
+        }
+        None
+    }
+}
+
+/// Whether a mode has turns to wait for; duels, time attacks and puzzle rushes
+/// are played simultaneously, and hot-seat players share the screen that's being
+/// played on
+fn has_turns(mode: &GameMode) -> bool {
+    !matches!(
+        mode,
+        GameMode::Duel | GameMode::TimeAttack | GameMode::HotSeat | GameMode::PuzzleRush
+    )
+}
+
+/// Background task that reminds idle players it's their turn, so slow games
+/// don't die from inattention
+pub async fn turn_reminder_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(TURN_REMINDER_INTERVAL);
+    let your_turn_after = Duration::from_secs(state.config.game.turn_reminder_secs);
+    let dm_after = state
+        .config
+        .game
+        .turn_reminder_dm_secs
+        .filter(|_| state.config.discord.bot_token.is_some())
+        .map(Duration::from_secs);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("turn_reminders", TURN_REMINDER_INTERVAL);
+        if your_turn_after.is_zero() {
+            continue;
+        }
+
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for mut session in state.active_games.iter_mut() {
+            if session.state.status != GameStatus::InProgress || !has_turns(&session.state.mode) {
+                continue;
+            }
+            let Some(user_id) = session.current_player_id() else {
+                continue;
+            };
+            let idle = state
+                .user_connections
+                .idle_for(user_id, now)
+                .unwrap_or(Duration::MAX);
+            let turn = session.state.turns_taken;
+            let Some(reminder) =
+                session
+                    .turn_reminders
+                    .next_due(turn, idle, your_turn_after, dm_after)
+            else {
+                continue;
+            };
+            // Blitz turns end when the player's clock does
+            let deadline = session
+                .state
+                .current_player()
+                .and_then(|player| session.state.clock_remaining(player.user_id, now))
+                .and_then(|remaining| chrono::Duration::from_std(remaining).ok())
+                .map(|remaining| Utc::now() + remaining);
+            due.push((
+                reminder,
+                user_id,
+                ServerMessage::YourTurn {
+                    game_id: session.game_id.to_string(),
+                    lobby_id: session.lobby_id.clone(),
+                    deadline,
+                },
+            ));
+        }
+
+        for (reminder, user_id, message) in due {
+            match reminder {
+                Reminder::YourTurn => {
+                    state.user_connections.send(user_id, &message);
+                }
+                Reminder::DirectMessage => {
+                    let state = state.clone();
+                    tokio::spawn(async move { send_turn_dm(&state, user_id).await });
+                }
+            }
+        }
+    }
+}
+
+/// DM a player that it's their turn, through the bot; only logged if it fails
+/// (the player may not share a server with the bot, or may have DMs closed)
+async fn send_turn_dm(state: &AppState, user_id: i64) {
+    let Some(token) = &state.config.discord.bot_token else {
+        return;
+    };
+    let auth = format!("Bot {}", token);
+    let channel = state
+        .http_client
+        .post(format!("{}/users/@me/channels", DISCORD_API))
+        .header("Authorization", &auth)
+        .json(&json!({ "recipient_id": user_id.to_string() }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    let channel_id = match channel {
+        Ok(response) => match response.json::<serde_json::Value>().await {
+            Ok(channel) => channel["id"].as_str().map(str::to_string),
+            Err(e) => {
+                tracing::warn!("Unreadable DM channel for {}: {}", user_id, e);
+                None
+            }
+        },
+        Err(e) => {
+            tracing::warn!("Failed to open a DM channel with {}: {}", user_id, e);
+            None
+        }
+    };
+    let Some(channel_id) = channel_id else {
+        return;
+    };
+
+    let body = json!({
+        "content": "⏰ It's your turn in Spell Cast! The other players are waiting for you.",
+        "allowed_mentions": { "parse": [] },
+    });
+    if let Err(e) = state
+        .http_client
+        .post(format!("{}/channels/{}/messages", DISCORD_API, channel_id))
+        .header("Authorization", &auth)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        tracing::warn!("Failed to DM {} about their turn: {}", user_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFTER: Duration = Duration::from_secs(60);
+    const DM_AFTER: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn test_your_turn_waits_for_idle_players() {
+        let mut reminders = TurnReminders::default();
+        assert_eq!(
+            reminders.next_due(1, Duration::from_secs(10), AFTER, None),
+            None,
+            "An active player isn't nagged"
+        );
+        assert_eq!(
+            reminders.next_due(1, AFTER, AFTER, None),
+            Some(Reminder::YourTurn)
+        );
+        assert_eq!(
+            reminders.next_due(1, AFTER * 10, AFTER, None),
+            None,
+            "Once per turn, and no DM unless enabled"
+        );
+    }
+
+    #[test]
+    fn test_dm_follows_your_turn() {
+        let mut reminders = TurnReminders::default();
+        assert_eq!(
+            reminders.next_due(1, DM_AFTER, AFTER, Some(DM_AFTER)),
+            Some(Reminder::YourTurn),
+            "The in-app reminder always goes first"
+        );
+        assert_eq!(
+            reminders.next_due(1, DM_AFTER, AFTER, Some(DM_AFTER)),
+            Some(Reminder::DirectMessage)
+        );
+        assert_eq!(reminders.next_due(1, DM_AFTER, AFTER, Some(DM_AFTER)), None);
+    }
+
+    #[test]
+    fn test_next_turn_starts_over() {
+        let mut reminders = TurnReminders::default();
+        assert_eq!(
+            reminders.next_due(1, AFTER, AFTER, None),
+            Some(Reminder::YourTurn)
+        );
+        assert_eq!(
+            reminders.next_due(2, AFTER, AFTER, None),
+            Some(Reminder::YourTurn),
+            "A new turn gets its own reminder"
+        );
+    }
+
+    #[test]
+    fn test_simultaneous_modes_have_no_turns() {
+        assert!(has_turns(&GameMode::Multiplayer));
+        assert!(has_turns(&GameMode::Blitz));
+        assert!(!has_turns(&GameMode::Duel));
+        assert!(!has_turns(&GameMode::HotSeat));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{handler::handle_start_game, messages::ServerMessage};
+use crate::{auth::AuthenticatedUser, error::ApiError, game_nights, models::GameMode, AppState};
+
+/// How often the scheduler looks for games due to start
+pub const SCHEDULER_INTERVAL: Duration = Duration::from_secs(1);
+/// Furthest ahead a game can be scheduled; lobbies only live in memory
+pub const MAX_SCHEDULE_AHEAD_HOURS: i64 = 24;
+
+/// A game the host has set to start at a future time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledStart {
+    pub starts_at: DateTime<Utc>,
+    pub mode: GameMode,
+    /// Co-op only: team score to beat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_score: Option<i32>,
+    /// User ID of the host who scheduled it
+    pub scheduled_by: String,
+    /// The guild game night occurrence this game is for, if any
+    #[serde(skip)]
+    pub game_night_event: Option<i64>,
+}
+
+impl ScheduledStart {
+    /// Check a host's request: a mode lobbies can start, a start time in the
+    /// future but no more than `MAX_SCHEDULE_AHEAD_HOURS` away
+    pub fn new(
+        starts_at: DateTime<Utc>,
+        mode: GameMode,
+        target_score: Option<i32>,
+        scheduled_by: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ApiError> {
+        if !mode.can_be_scheduled() {
+            return Err(ApiError::UnsupportedMode);
+        }
+        if target_score.is_some_and(|target| target <= 0) {
+            return Err(ApiError::InvalidCoopTarget);
+        }
+        if starts_at <= now || starts_at > now + chrono::Duration::hours(MAX_SCHEDULE_AHEAD_HOURS) {
+            return Err(ApiError::InvalidScheduleTime);
+        }
+        Ok(Self {
+            starts_at,
+            mode,
+            target_score,
+            scheduled_by: scheduled_by.to_string(),
+            game_night_event: None,
+        })
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        now >= self.starts_at
+    }
+
+    /// The countdown shown in the lobby heartbeat
+    pub fn countdown(&self, now: DateTime<Utc>) -> ScheduleCountdown {
+        ScheduleCountdown {
+            starts_at: self.starts_at,
+            mode: self.mode.clone(),
+            secs_left: (self.starts_at - now).num_seconds().max(0) as u64,
+            ready_needed: self.mode.min_players(),
+        }
+    }
+}
+
+/// Time left before a scheduled game, and how many ready players it needs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleCountdown {
+    pub starts_at: DateTime<Utc>,
+    pub mode: GameMode,
+    pub secs_left: u64,
+    pub ready_needed: usize,
+}
+
+/// Why a scheduled game didn't start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleCancelReason {
+    /// The host called it off
+    Host,
+    /// Too few players were ready when the time came
+    NotEnoughReady { ready: usize, needed: usize },
+    /// Starting failed, e.g. a game was still running; `code` is the error's
+    StartFailed { code: String },
+}
+
+/// Background task that starts scheduled games when their time comes, or
+/// cancels them if too few players are ready
+pub async fn game_scheduler_task(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        state.record_heartbeat("game_scheduler", SCHEDULER_INTERVAL);
+
+        let now = Utc::now();
+        let due: Vec<(String, ScheduledStart)> = state
+            .lobbies
+            .iter_mut()
+            .filter_map(|mut lobby| {
+                if !lobby.scheduled_start.as_ref()?.is_due(now) {
+                    return None;
+                }
+                Some((lobby.lobby_id.clone(), lobby.scheduled_start.take()?))
+            })
+            .collect();
+
+        for (lobby_id, schedule) in due {
+            start_scheduled_game(&state, &lobby_id, schedule).await;
+        }
+    }
+}
+
+/// Start a scheduled game on the host's behalf, or tell the lobby why it can't
+async fn start_scheduled_game(state: &Arc<AppState>, lobby_id: &str, schedule: ScheduledStart) {
+    let Some((host, ready)) = state.lobbies.get(lobby_id).map(|lobby| {
+        let host = lobby.host_id.and_then(|host_id| {
+            lobby.players.get(&host_id).map(|host| AuthenticatedUser {
+                user_id: host_id,
+                username: host.username.clone(),
+            })
+        });
+        (host, lobby.ready_player_count())
+    }) else {
+        return;
+    };
+
+    let needed = schedule.mode.min_players();
+    let started = match host {
+        Some(host) if ready >= needed => handle_start_game(
+            state,
+            lobby_id,
+            &host,
+            schedule.mode,
+            schedule.target_score,
+            Vec::new(),
+        )
+        .await
+        .map_err(|e| ScheduleCancelReason::StartFailed {
+            code: e.code().to_string(),
+        }),
+        _ => Err(ScheduleCancelReason::NotEnoughReady { ready, needed }),
+    };
+
+    match started {
+        Ok(game_started) => {
+            tracing::info!("Scheduled game started in lobby {}", lobby_id);
+            if let (
+                Some(event_id),
+                ServerMessage::GameStarted {
+                    game_id, players, ..
+                },
+            ) = (schedule.game_night_event, &game_started)
+            {
+                let user_ids: Vec<i64> = players
+                    .iter()
+                    .filter_map(|player| player.user_id.parse().ok())
+                    .collect();
+                if let Ok(game_id) = game_id.parse() {
+                    game_nights::record_start(state, event_id, game_id, &user_ids).await;
+                }
+            }
+            super::broadcast_to_lobby(state, lobby_id, game_started).await;
+        }
+        Err(reason) => {
+            tracing::info!(
+                "Scheduled game in lobby {} cancelled: {:?}",
+                lobby_id,
+                reason
+            );
+            if let Some(event_id) = schedule.game_night_event {
+                game_nights::record_cancelled(state, event_id).await;
+            }
+            super::broadcast_to_lobby(
+                state,
+                lobby_id,
+                ServerMessage::ScheduledGameCancelled { reason },
+            )
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_start_time_must_be_ahead_but_not_too_far() {
+        let now = at(0);
+        let schedule =
+            |starts_at| ScheduledStart::new(starts_at, GameMode::Multiplayer, None, 1, now);
+
+        assert!(matches!(schedule(now), Err(ApiError::InvalidScheduleTime)));
+        assert!(matches!(
+            schedule(at(-60)),
+            Err(ApiError::InvalidScheduleTime)
+        ));
+        assert!(schedule(at(60)).is_ok());
+        assert!(schedule(at(MAX_SCHEDULE_AHEAD_HOURS * 3600)).is_ok());
+        assert!(matches!(
+            schedule(at(MAX_SCHEDULE_AHEAD_HOURS * 3600 + 1)),
+            Err(ApiError::InvalidScheduleTime)
+        ));
+    }
+
+    #[test]
+    fn test_only_lobby_modes_can_be_scheduled() {
+        let now = at(0);
+        assert!(matches!(
+            ScheduledStart::new(at(60), GameMode::HotSeat, None, 1, now),
+            Err(ApiError::UnsupportedMode)
+        ));
+        assert!(matches!(
+            ScheduledStart::new(at(60), GameMode::Coop, Some(0), 1, now),
+            Err(ApiError::InvalidCoopTarget)
+        ));
+        assert!(ScheduledStart::new(at(60), GameMode::Coop, Some(150), 1, now).is_ok());
+    }
+
+    #[test]
+    fn test_countdown() {
+        let schedule = ScheduledStart::new(at(90), GameMode::Coop, None, 7, at(0)).unwrap();
+        assert!(!schedule.is_due(at(89)));
+        assert!(schedule.is_due(at(90)));
+
+        let countdown = schedule.countdown(at(30));
+        assert_eq!(countdown.secs_left, 60);
+        assert_eq!(countdown.ready_needed, 1, "Co-op can be played solo");
+        assert_eq!(schedule.countdown(at(120)).secs_left, 0);
+    }
+
+    #[test]
+    fn test_cancel_reasons_serialize_flat() {
+        let json = serde_json::to_value(ServerMessage::ScheduledGameCancelled {
+            reason: ScheduleCancelReason::NotEnoughReady {
+                ready: 1,
+                needed: 2,
+            },
+        })
+        .unwrap();
+        assert_eq!(json["type"], "scheduled_game_cancelled");
+        assert_eq!(json["reason"]["kind"], "not_enough_ready");
+        assert_eq!(json["reason"]["needed"], 2);
+    }
+}
@@ -0,0 +1,68 @@
+use std::future::Future;
+
+use uuid::Uuid;
+
+tokio::task_local! {
+    /// ID of the client message currently being handled on this task
+    static REQUEST_ID: String;
+}
+
+/// Generate an ID for an incoming WebSocket message
+pub fn new_request_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Run a message handler with `request_id` as the current request ID
+pub async fn with_request_id<F: Future>(request_id: String, f: F) -> F::Output {
+    REQUEST_ID.scope(request_id, f).await
+}
+
+/// The ID of the message being handled, if called from within `with_request_id`
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_request_ids_are_unique() {
+        let first = new_request_id();
+        let second = new_request_id();
+        assert_ne!(first, second, "Each message should get its own request ID");
+        assert_eq!(first.len(), 32, "Request IDs should be 32 hex characters");
+    }
+
+    #[test]
+    fn test_current_request_id_outside_scope() {
+        assert!(
+            current_request_id().is_none(),
+            "No request ID should be set outside a handler scope"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_current_request_id_inside_scope() {
+        let id = with_request_id("abc123".to_string(), async { current_request_id() }).await;
+        assert_eq!(id.as_deref(), Some("abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_error_messages_echo_request_id() {
+        use crate::websocket::messages::ServerMessage;
+
+        let json = with_request_id("abc123".to_string(), async {
+            serde_json::to_value(ServerMessage::game_error("not_host", "nope")).unwrap()
+        })
+        .await;
+        assert_eq!(json["request_id"], "abc123");
+
+        // Errors raised outside a message handler omit the field entirely
+        let json = serde_json::to_value(ServerMessage::game_error("not_host", "nope")).unwrap();
+        assert!(
+            json.get("request_id").is_none(),
+            "request_id should be skipped when not set"
+        );
+    }
+}
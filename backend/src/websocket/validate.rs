@@ -0,0 +1,229 @@
+use crate::{error::ApiError, game::grid::GRID_SIZE, LOBBY_CODE_CHARSET, LOBBY_CODE_LONG_LENGTH};
+
+use super::{messages::ClientMessage, presence::MAX_WATCHED_USERS};
+
+/// Most positions in a word: every tile on the board
+pub const MAX_POSITIONS: usize = GRID_SIZE * GRID_SIZE;
+/// Longest word accepted (`game_moves.word` is VARCHAR(50))
+pub const MAX_WORD_LEN: usize = 50;
+/// Longest Discord snowflake: a u64 has 20 digits
+const MAX_SNOWFLAKE_LEN: usize = 20;
+/// Longest `submission_id` accepted on SubmitWord (a UUID is 36)
+const MAX_SUBMISSION_ID_LEN: usize = 64;
+/// Longest game ID; a UUID is 36, or 32 without dashes
+const MAX_GAME_ID_LEN: usize = 36;
+/// Longest lobby ID, e.g. `channel:<snowflake>:table:<n>`
+const MAX_LOBBY_ID_LEN: usize = 64;
+/// Longest signed invite token, board checksum or dictionary pack name
+const MAX_TOKEN_LEN: usize = 512;
+const MAX_CHECKSUM_LEN: usize = 128;
+const MAX_PACK_NAME_LEN: usize = 64;
+/// Most local players listed on a hot-seat CreateGame, and the longest name
+/// looked at; the handler applies the game's own, tighter limits
+const MAX_LOCAL_PLAYERS: usize = 16;
+const MAX_LOCAL_PLAYER_LEN: usize = 128;
+
+/// Check the size and format of every client-supplied field in a message
+/// before it reaches the handlers
+///
+/// These are shape checks only (lengths, counts, character sets), so a message
+/// that passes can still be refused by the game; one that fails never gets that
+/// far and is answered with `invalid_field` naming the offending field.
+pub fn validate(msg: &ClientMessage) -> Result<(), ApiError> {
+    match msg {
+        ClientMessage::JoinChannelLobby {
+            channel_id,
+            guild_id,
+        } => {
+            snowflake("channel_id", channel_id)?;
+            if let Some(guild_id) = guild_id {
+                snowflake("guild_id", guild_id)?;
+            }
+        }
+        ClientMessage::JoinCustomLobby {
+            lobby_code,
+            invite_token,
+        } => {
+            if let Some(code) = lobby_code {
+                lobby_code_format(code)?;
+            }
+            if let Some(token) = invite_token {
+                max_len("invite_token", token, MAX_TOKEN_LEN)?;
+            }
+        }
+        ClientMessage::PreviewLobby { lobby_code } => lobby_code_format(lobby_code)?,
+        ClientMessage::ReserveSeat { user_id }
+        | ClientMessage::InvitePlayer { user_id }
+        | ClientMessage::BlockUser { user_id }
+        | ClientMessage::UnblockUser { user_id }
+        | ClientMessage::MutePlayer { user_id }
+        | ClientMessage::UnmutePlayer { user_id } => snowflake("user_id", user_id)?,
+        ClientMessage::GetFriendsPresence { user_ids } => {
+            if user_ids.len() > MAX_WATCHED_USERS {
+                return Err(invalid("user_ids", "too many entries"));
+            }
+            for user_id in user_ids {
+                snowflake("user_ids", user_id)?;
+            }
+        }
+        ClientMessage::CreateGame { local_players, .. } => {
+            if local_players.len() > MAX_LOCAL_PLAYERS {
+                return Err(invalid("local_players", "too many entries"));
+            }
+            for name in local_players {
+                max_len("local_players", name, MAX_LOCAL_PLAYER_LEN)?;
+            }
+        }
+        ClientMessage::JoinGame { game_id } | ClientMessage::AdminDeleteGame { game_id } => {
+            max_len("game_id", game_id, MAX_GAME_ID_LEN)?
+        }
+        ClientMessage::SubmitWord {
+            word,
+            positions,
+            board_checksum,
+            submission_id,
+        } => {
+            if word.chars().count() > MAX_WORD_LEN {
+                return Err(invalid("word", "too long"));
+            }
+            if positions.len() > MAX_POSITIONS {
+                return Err(invalid("positions", "too many entries"));
+            }
+            if let Some(checksum) = board_checksum {
+                max_len("board_checksum", checksum, MAX_CHECKSUM_LEN)?;
+            }
+            if let Some(submission_id) = submission_id {
+                max_len("submission_id", submission_id, MAX_SUBMISSION_ID_LEN)?;
+            }
+        }
+        ClientMessage::SelectDictionaryPack {
+            pack: Some(pack), ..
+        } => max_len("pack", pack, MAX_PACK_NAME_LEN)?,
+        ClientMessage::AdminDumpLobby { lobby_id } => {
+            max_len("lobby_id", lobby_id, MAX_LOBBY_ID_LEN)?
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn invalid(field: &'static str, reason: &'static str) -> ApiError {
+    ApiError::InvalidField { field, reason }
+}
+
+fn max_len(field: &'static str, value: &str, max: usize) -> Result<(), ApiError> {
+    if value.len() > max {
+        return Err(invalid(field, "too long"));
+    }
+    Ok(())
+}
+
+/// A Discord ID: 1-20 ASCII digits
+fn snowflake(field: &'static str, value: &str) -> Result<(), ApiError> {
+    if value.is_empty()
+        || value.len() > MAX_SNOWFLAKE_LEN
+        || !value.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid(field, "must be a Discord ID"));
+    }
+    Ok(())
+}
+
+/// A lobby code as it might be typed: letters from the code charset in either
+/// case, with surrounding whitespace (both are normalized on lookup)
+fn lobby_code_format(code: &str) -> Result<(), ApiError> {
+    let code = code.trim();
+    if code.is_empty() || code.len() > LOBBY_CODE_LONG_LENGTH {
+        return Err(invalid("lobby_code", "wrong length"));
+    }
+    if !code
+        .bytes()
+        .all(|b| LOBBY_CODE_CHARSET.contains(&b.to_ascii_uppercase()))
+    {
+        return Err(invalid("lobby_code", "contains characters codes never use"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Position;
+
+    fn parse(json: &str) -> ClientMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn field_of(result: Result<(), ApiError>) -> &'static str {
+        match result {
+            Err(ApiError::InvalidField { field, .. }) => field,
+            other => panic!("Expected InvalidField, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_accepts_well_formed_messages() {
+        for json in [
+            r#"{"type":"join_channel_lobby","channel_id":"123456789012345678","guild_id":"42"}"#,
+            r#"{"type":"join_channel_lobby","channel_id":"1","guild_id":null}"#,
+            r#"{"type":"join_custom_lobby","lobby_code":" abcd23 "}"#,
+            r#"{"type":"preview_lobby","lobby_code":"ABCD23"}"#,
+            r#"{"type":"invite_player","user_id":"987654321"}"#,
+            r#"{"type":"get_friends_presence","user_ids":["1","2"]}"#,
+            r#"{"type":"leave_lobby"}"#,
+        ] {
+            assert!(validate(&parse(json)).is_ok(), "{} should pass", json);
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed_ids() {
+        let msg = parse(r#"{"type":"join_channel_lobby","channel_id":"general"}"#);
+        assert_eq!(field_of(validate(&msg)), "channel_id");
+
+        let msg = parse(r#"{"type":"join_channel_lobby","channel_id":"1","guild_id":""}"#);
+        assert_eq!(field_of(validate(&msg)), "guild_id");
+
+        let msg = parse(r#"{"type":"block_user","user_id":"123456789012345678901"}"#);
+        assert_eq!(field_of(validate(&msg)), "user_id", "21 digits is too many");
+
+        let msg = parse(r#"{"type":"get_friends_presence","user_ids":["1","x"]}"#);
+        assert_eq!(field_of(validate(&msg)), "user_ids");
+    }
+
+    #[test]
+    fn test_rejects_lobby_codes_outside_the_charset() {
+        let msg = parse(r#"{"type":"join_custom_lobby","lobby_code":"ABC1O0"}"#);
+        assert_eq!(field_of(validate(&msg)), "lobby_code");
+
+        let msg = parse(r#"{"type":"join_custom_lobby","lobby_code":"ABCDEFGHJK"}"#);
+        assert_eq!(field_of(validate(&msg)), "lobby_code", "Too long");
+    }
+
+    #[test]
+    fn test_caps_word_submissions() {
+        let submit = |word: String, positions: usize, submission_id: Option<String>| {
+            ClientMessage::SubmitWord {
+                word,
+                positions: vec![Position { row: 0, col: 0 }; positions],
+                board_checksum: None,
+                submission_id,
+            }
+        };
+
+        assert!(validate(&submit("CAT".into(), 3, None)).is_ok());
+        assert!(validate(&submit("A".repeat(MAX_WORD_LEN), MAX_POSITIONS, None)).is_ok());
+        assert_eq!(
+            field_of(validate(&submit("CAT".into(), MAX_POSITIONS + 1, None))),
+            "positions"
+        );
+        assert_eq!(
+            field_of(validate(&submit("A".repeat(MAX_WORD_LEN + 1), 3, None))),
+            "word"
+        );
+        assert_eq!(
+            field_of(validate(&submit("CAT".into(), 3, Some("x".repeat(65))))),
+            "submission_id"
+        );
+    }
+}
@@ -12,6 +12,21 @@ export function getApiUrl(path) {
   return path;
 }
 
+// Base64url without padding, as PKCE expects
+function base64Url(bytes) {
+  return btoa(String.fromCharCode(...bytes))
+    .replace(/\+/g, '-')
+    .replace(/\//g, '_')
+    .replace(/=+$/, '');
+}
+
+// A random PKCE code verifier and its S256 challenge
+async function createPkcePair() {
+  const verifier = base64Url(crypto.getRandomValues(new Uint8Array(32)));
+  const digest = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(verifier));
+  return { verifier, challenge: base64Url(new Uint8Array(digest)) };
+}
+
 export async function initDiscord() {
   // Get client ID from environment or config
   const clientId = import.meta.env.VITE_DISCORD_CLIENT_ID;
@@ -34,16 +49,42 @@ export async function initDiscord() {
     isDiscordActivity = window.location.host.includes('discordsays.com');
     console.log('Running in Discord Activity:', isDiscordActivity);
 
+    // Ask for the scopes this deployment uses (identify and guilds, plus any
+    // optional ones such as guilds.members.read)
+    let scope = ['identify', 'guilds'];
+    try {
+      const scopesResponse = await fetch(getApiUrl('/api/auth/scopes'));
+      if (scopesResponse.ok) {
+        ({ scopes: scope } = await scopesResponse.json());
+      }
+    } catch (error) {
+      console.warn('Failed to load OAuth scopes, using the defaults:', error);
+    }
+
+    // Get a single-use state from the backend, bound to our PKCE challenge, so
+    // the code can only be exchanged by this client
+    const pkce = await createPkcePair();
+    const stateResponse = await fetch(getApiUrl('/api/auth/state'), {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+      },
+      body: JSON.stringify({ code_challenge: pkce.challenge }),
+    });
+    if (!stateResponse.ok) {
+      throw new Error(`Failed to start sign-in: ${stateResponse.status}`);
+    }
+    const { state } = await stateResponse.json();
+
     // Authorize the app
     const { code } = await discordSdk.commands.authorize({
       client_id: clientId,
       response_type: 'code',
-      state: '',
+      state,
       prompt: 'none',
-      scope: [
-        'identify',
-        'guilds',
-      ],
+      scope,
+      code_challenge: pkce.challenge,
+      code_challenge_method: 'S256',
     });
 
     console.log('Authorization code received');
@@ -56,7 +97,7 @@ export async function initDiscord() {
       headers: {
         'Content-Type': 'application/json',
       },
-      body: JSON.stringify({ code }),
+      body: JSON.stringify({ code, state, code_verifier: pkce.verifier }),
     });
 
     if (!response.ok) {
@@ -120,3 +161,15 @@ export async function initDiscord() {
 export function getDiscordSdk() {
   return discordSdk;
 }
+
+// The server-rendered result card for a finished game, for the share flow to
+// post into the channel; built from stored scores so it can't be edited
+export async function fetchResultCard(gameId, accessToken) {
+  const response = await fetch(getApiUrl(`/api/games/${gameId}/result-card`), {
+    headers: { Authorization: `Bearer ${accessToken}` },
+  });
+  if (!response.ok) {
+    throw new Error(`Failed to load result card: ${response.status}`);
+  }
+  return response.json();
+}
@@ -6,6 +6,8 @@ export class GameUI {
     this.currentGrid = null;
     this.gameId = null;
     this.currentPlayerId = null;
+    // Server checksum of the board we're showing, sent back with submissions
+    this.boardChecksum = null;
     this.setupListeners();
   }
 
@@ -15,7 +17,15 @@ export class GameUI {
       this.handleGameState(data);
     });
 
+    // Our board was stale; the server sent a full game state to replace it
+    this.gameClient.on('resync_state', (data) => {
+      this.handleGameState(data.state);
+    });
+
     this.gameClient.on('game_started', (data) => {
+      this.boardChecksum = data.board_checksum;
+      this.letterBag = data.letter_bag || null;
+      this.rules = data.rules;
       this.initializeGame({
         gameId: data.game_id,
         grid: data.grid,
@@ -26,10 +36,25 @@ export class GameUI {
     });
 
     this.gameClient.on('word_scored', (data) => {
+      this.boardChecksum = data.board_checksum;
+      // A replayed result for a retried submission has already been applied
+      if (data.submission_id && data.submission_id === this.scoredSubmissionId) return;
+      if (data.submission_id && data.submission_id === this.pendingSubmissionId) {
+        this.scoredSubmissionId = data.submission_id;
+        this.pendingSubmissionId = null;
+      }
       this.handleWordScored(data);
     });
 
+    // The restored board and scores follow in a game_state
+    this.gameClient.on('move_undone', (data) => {
+      this.scoredSubmissionId = null;
+      this.currentPlayerId = data.user_id;
+      this.updateTurnIndicator(data.user_id);
+    });
+
     this.gameClient.on('invalid_word', (data) => {
+      this.pendingSubmissionId = null;
       this.handleInvalidWord(data);
     });
 
@@ -37,6 +62,11 @@ export class GameUI {
       this.handleTurnUpdate(data);
     });
 
+    // Only sent while we're watching a game we aren't playing in
+    this.gameClient.on('commentary', (data) => {
+      this.commentary = data;
+    });
+
     this.gameClient.on('round_end', (data) => {
       this.handleRoundEnd(data);
     });
@@ -50,6 +80,9 @@ export class GameUI {
     console.log('Game state received:', data);
 
     this.currentGrid = data.grid;
+    this.boardChecksum = data.board_checksum;
+    this.letterBag = data.letter_bag || null;
+    this.rules = data.rules;
     this.renderGrid(data.grid);
     this.renderPlayers(data.players);
     this.renderUsedWords(data.used_words);
@@ -229,6 +262,8 @@ export class GameUI {
   calculateScore() {
     let score = 0;
 
+    let wordMultiplier = 1;
+
     this.selectedTiles.forEach(pos => {
       const cell = this.currentGrid[pos.row][pos.col];
       let value = cell.value;
@@ -237,10 +272,13 @@ export class GameUI {
         value *= 2;
       } else if (cell.multiplier === 'TL') {
         value *= 3;
+      } else if (cell.multiplier === 'DW') {
+        wordMultiplier *= 2;
       }
 
       score += value;
     });
+    score *= wordMultiplier;
 
     // Add length bonus
     const length = this.selectedTiles.length;
@@ -270,7 +308,7 @@ export class GameUI {
       .map(pos => this.currentGrid[pos.row][pos.col].letter)
       .join('');
 
-    this.gameClient.submitWord(word, this.selectedTiles);
+    this.pendingSubmissionId = this.gameClient.submitWord(word, this.selectedTiles, this.boardChecksum);
   }
 
   renderPlayers(players) {
@@ -295,6 +333,12 @@ export class GameUI {
   handleWordScored(data) {
     console.log('Word scored:', data);
     this.clearSelection();
+    // Letter bag games replace the played tiles from the bag
+    if (data.grid) {
+      this.currentGrid = data.grid;
+      this.letterBag = data.letter_bag || null;
+      this.renderGrid(data.grid);
+    }
     // TODO: Add animation for scored word
   }
 
@@ -323,7 +367,11 @@ export class GameUI {
     const container = document.getElementById('final-scores');
     const announcement = document.getElementById('winner-announcement');
 
-    if (announcement && data.winner) {
+    if (announcement && data.result?.outcome === 'draw') {
+      const names = data.result.winners
+        .map(id => data.final_scores.find(s => s.user_id === id)?.username || 'Player');
+      announcement.textContent = `🤝 Draw between ${names.join(' & ')}`;
+    } else if (announcement && data.winner) {
       const winner = data.final_scores.find(s => s.user_id === data.winner);
       announcement.textContent = `🎉 ${winner?.username || 'Player'} wins! 🎉`;
     }
@@ -123,9 +123,14 @@ class App {
     this.gameClient.on('connected', () => {
       console.log('WebSocket connected');
       // If we have a channel context (Discord activity), auto-join the channel lobby
+      const inviteToken = new URLSearchParams(window.location.search).get('invite');
       if (this.channelId) {
         console.log('Auto-joining channel lobby:', this.channelId);
         this.gameClient.joinChannelLobby(this.channelId, this.guildId);
+      } else if (inviteToken) {
+        // Opened from an invite link
+        console.log('Joining custom lobby from invite link');
+        this.gameClient.joinWithInvite(inviteToken);
       } else {
         // No channel context (e.g., web client or DM with bot)
         // User can manually create or join a custom lobby
@@ -134,6 +139,15 @@ class App {
       }
     });
 
+    // The server remembered the custom lobby we dropped out of; channel lobbies
+    // are rejoined by the auto-join above
+    this.gameClient.on('rejoin_available', (data) => {
+      if (!this.channelId && data.lobby_code && !this.currentLobbyCode) {
+        console.log('Rejoining custom lobby:', data.lobby_code);
+        this.gameClient.joinCustomLobby(data.lobby_code);
+      }
+    });
+
     // Handle lobby created response
     this.gameClient.on('lobby_created', (data) => {
       console.log('Custom lobby created with code:', data.lobby_code);
@@ -162,6 +176,20 @@ class App {
         this.currentLobbyCode = data.lobby_code;
         this.displayLobbyCode(data.lobby_code);
       }
+      if (data.language) {
+        document.documentElement.lang = data.language;
+      }
+    });
+
+    // What happened in the lobby before we joined, oldest first
+    this.gameClient.on('activity_feed', (data) => {
+      this.activityFeed = data.entries;
+    });
+
+    // The lobby switched language (e.g. a Spanish dictionary pack)
+    this.gameClient.on('lobby_language_changed', (data) => {
+      console.log('Lobby language:', data.language, '-', data.message);
+      document.documentElement.lang = data.language;
     });
 
     // Listen for errors
@@ -257,6 +285,9 @@ class App {
 
       const playerCard = document.createElement('div');
       playerCard.className = 'player-card';
+      if (!player.is_connected) {
+        playerCard.classList.add('disconnected');
+      }
 
       const img = document.createElement('img');
       img.src = avatarUrl;
@@ -272,10 +303,25 @@ class App {
 
       const span = document.createElement('span');
       span.className = 'player-name';
-      span.textContent = player.username;
+      span.textContent = player.is_host ? `👑 ${player.username}` : player.username;
+      if (player.profile?.name_color) {
+        span.style.color = player.profile.name_color;
+      }
 
       playerCard.appendChild(img);
       playerCard.appendChild(span);
+      if (player.profile?.title) {
+        const title = document.createElement('span');
+        title.className = 'player-title';
+        title.textContent = player.profile.title;
+        playerCard.appendChild(title);
+      }
+      if (player.ready) {
+        const badge = document.createElement('span');
+        badge.className = 'player-ready';
+        badge.textContent = 'Ready';
+        playerCard.appendChild(badge);
+      }
       container.appendChild(playerCard);
     });
 
@@ -309,7 +355,16 @@ class App {
       item.className = 'admin-game-item';
 
       const info = document.createElement('span');
-      info.textContent = `${new Date(game.created_at).toLocaleTimeString()} - ${game.state}`;
+      const players = game.players
+        .map(p => {
+          const status = p.is_connected ? '' : (p.awaiting_reconnect ? ' (reconnecting)' : ' (offline)');
+          const turn = p.user_id === game.current_player ? '*' : '';
+          return `${turn}${p.username} ${p.score}${status}`;
+        })
+        .join(', ');
+      info.textContent = `${new Date(game.created_at).toLocaleTimeString()} - ${game.state}` +
+        ` - round ${game.current_round}/${game.max_rounds}` +
+        (players ? ` - ${players}` : '');
 
       const buttonsContainer = document.createElement('div');
       buttonsContainer.className = 'admin-game-buttons';
@@ -115,6 +115,14 @@ export class GameClient {
     });
   }
 
+  // Join a custom lobby using a signed invite token
+  joinWithInvite(inviteToken) {
+    this.send({
+      type: 'join_custom_lobby',
+      invite_token: inviteToken,
+    });
+  }
+
   // Leave the current lobby
   leaveLobby() {
     this.send({
@@ -122,11 +130,100 @@ export class GameClient {
     });
   }
 
+  // Hold a seat in the current lobby for a Discord user (host only)
+  setDictionaryStrictness(strictness) {
+    this.send({
+      type: 'set_dictionary_strictness',
+      strictness,
+    });
+  }
+
+  // Host only: 'classic', 'chaos' or 'none'; applies from the next game
+  setMultiplierLayout(layout) {
+    this.send({
+      type: 'set_multiplier_layout',
+      layout,
+    });
+  }
+
+  // Host only: 'weighted' or 'bag'; applies from the next game
+  setLetterMode(mode) {
+    this.send({
+      type: 'set_letter_mode',
+      mode,
+    });
+  }
+
+  // Host only: e.g. { min_word_length: 4, allow_proper_nouns: true,
+  // long_word_bonus: { min_length: 6, points: 10 }, gems: false }; omitted rules
+  // are the standard ones. Applies from the next game
+  setGameRules(rules) {
+    this.send({
+      type: 'set_game_rules',
+      rules,
+    });
+  }
+
+  listDictionaryPacks() {
+    this.send({
+      type: 'list_dictionary_packs',
+    });
+  }
+
+  selectDictionaryPack(pack, mode = 'layer') {
+    this.send({
+      type: 'select_dictionary_pack',
+      pack,
+      mode,
+    });
+  }
+
+  reserveSeat(userId) {
+    this.send({
+      type: 'reserve_seat',
+      user_id: userId,
+    });
+  }
+
+  listTables() {
+    this.send({
+      type: 'list_tables',
+    });
+  }
+
+  createTable() {
+    this.send({
+      type: 'create_table',
+    });
+  }
+
+  joinTable(tableNumber) {
+    this.send({
+      type: 'join_table',
+      table_number: tableNumber,
+    });
+  }
+
+  setReady(ready) {
+    this.send({
+      type: 'set_ready',
+      ready,
+    });
+  }
+
+  setTyping(typing) {
+    this.send({
+      type: 'set_typing',
+      typing,
+    });
+  }
+
   // Game actions
-  createGame(mode) {
+  createGame(mode, localPlayers = []) {
     this.send({
       type: 'create_game',
       mode,
+      local_players: localPlayers,
     });
   }
 
@@ -137,18 +234,24 @@ export class GameClient {
     });
   }
 
-  startGame() {
+  startGame(mode = 'Multiplayer', targetScore = null) {
     this.send({
       type: 'start_game',
+      mode,
+      ...(targetScore !== null && { target_score: targetScore }),
     });
   }
 
-  submitWord(word, positions) {
+  // Returns the submission id; a resend with the same id is never scored twice
+  submitWord(word, positions, boardChecksum = null, submissionId = crypto.randomUUID()) {
     this.send({
       type: 'submit_word',
       word,
       positions,
+      board_checksum: boardChecksum,
+      submission_id: submissionId,
     });
+    return submissionId;
   }
 
   passTurn() {
@@ -157,6 +260,47 @@ export class GameClient {
     });
   }
 
+  // Take back the word you just played (games with the undo rule, within 10 seconds)
+  undoMove() {
+    this.send({
+      type: 'undo_move',
+    });
+  }
+
+  blockUser(userId) {
+    this.send({
+      type: 'block_user',
+      user_id: userId,
+    });
+  }
+
+  unblockUser(userId) {
+    this.send({
+      type: 'unblock_user',
+      user_id: userId,
+    });
+  }
+
+  mutePlayer(userId) {
+    this.send({
+      type: 'mute_player',
+      user_id: userId,
+    });
+  }
+
+  unmutePlayer(userId) {
+    this.send({
+      type: 'unmute_player',
+      user_id: userId,
+    });
+  }
+
+  resign() {
+    this.send({
+      type: 'resign',
+    });
+  }
+
   enableTimer() {
     this.send({
       type: 'enable_timer',
@@ -176,4 +320,11 @@ export class GameClient {
       game_id: gameId,
     });
   }
+
+  dumpLobby(lobbyId) {
+    this.send({
+      type: 'admin_dump_lobby',
+      lobby_id: lobbyId,
+    });
+  }
 }